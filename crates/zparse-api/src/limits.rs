@@ -0,0 +1,238 @@
+//! Request limits for the API server
+//!
+//! All limits are read from environment variables at startup. A value of `0`
+//! disables the corresponding limit.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::Json;
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+const DEFAULT_MAX_BODY_BYTES: usize = 1024 * 1024;
+const DEFAULT_RATE_LIMIT: u32 = 120;
+const DEFAULT_RATE_WINDOW_SECS: u64 = 60;
+
+/// Limits applied to incoming requests and to the parsers behind them
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+    /// Maximum request body size in bytes (`ZPARSE_MAX_BODY_BYTES`)
+    pub max_body_bytes: usize,
+    /// Requests allowed per client IP per window (`ZPARSE_RATE_LIMIT`)
+    pub rate_limit: u32,
+    /// Rate limit window length (`ZPARSE_RATE_LIMIT_WINDOW_SECS`)
+    pub rate_window: Duration,
+    /// Maximum nesting depth for parsers (`ZPARSE_MAX_DEPTH`)
+    pub max_depth: u16,
+    /// Maximum input size for parsers in bytes (`ZPARSE_MAX_SIZE`)
+    pub max_size: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            rate_limit: DEFAULT_RATE_LIMIT,
+            rate_window: Duration::from_secs(DEFAULT_RATE_WINDOW_SECS),
+            max_depth: zparse::json::parser::DEFAULT_MAX_DEPTH,
+            max_size: zparse::json::parser::DEFAULT_MAX_SIZE,
+        }
+    }
+}
+
+impl Limits {
+    /// Read limits from the environment, falling back to defaults
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            max_body_bytes: env_or("ZPARSE_MAX_BODY_BYTES", defaults.max_body_bytes),
+            rate_limit: env_or("ZPARSE_RATE_LIMIT", defaults.rate_limit),
+            rate_window: Duration::from_secs(env_or(
+                "ZPARSE_RATE_LIMIT_WINDOW_SECS",
+                DEFAULT_RATE_WINDOW_SECS,
+            )),
+            max_depth: env_or("ZPARSE_MAX_DEPTH", defaults.max_depth),
+            max_size: env_or("ZPARSE_MAX_SIZE", defaults.max_size),
+        }
+    }
+
+    /// Conversion options with parser limits applied to every format
+    pub fn convert_options(&self) -> zparse::ConvertOptions {
+        zparse::ConvertOptions {
+            json: zparse::JsonConfig::new(self.max_depth, self.max_size),
+            csv: zparse::CsvConfig::default().with_max_size(self.max_size),
+            toml: zparse::TomlConfig::new(self.max_depth, self.max_size),
+            yaml: zparse::YamlConfig::new(self.max_depth),
+            xml: zparse::XmlConfig::new(self.max_size).with_max_depth(self.max_depth),
+            ..Default::default()
+        }
+    }
+}
+
+fn env_or<T: FromStr>(name: &str, default: T) -> T {
+    parse_or(name, std::env::var(name).ok(), default)
+}
+
+/// `raw`, the value of the variable `name`, parsed, or `default` when it is
+/// unset or invalid
+fn parse_or<T: FromStr>(name: &str, raw: Option<String>, default: T) -> T {
+    let Some(raw) = raw else {
+        return default;
+    };
+    match raw.trim().parse() {
+        Ok(value) => value,
+        Err(_) => {
+            tracing::warn!(name, value = raw, "ignoring invalid limit");
+            default
+        }
+    }
+}
+
+/// Fixed-window, per-IP request counter
+#[derive(Debug)]
+pub struct RateLimiter {
+    limit: u32,
+    window: Duration,
+    clients: Mutex<Clients>,
+}
+
+/// Window start and request count of each client, and when expired windows
+/// were last dropped
+#[derive(Debug)]
+struct Clients {
+    windows: HashMap<IpAddr, (Instant, u32)>,
+    pruned: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            clients: Mutex::new(Clients {
+                windows: HashMap::new(),
+                pruned: Instant::now(),
+            }),
+        }
+    }
+
+    /// Record a request from `ip`; returns false when the client is over its limit
+    pub fn check(&self, ip: IpAddr) -> bool {
+        self.check_at(ip, Instant::now())
+    }
+
+    fn check_at(&self, ip: IpAddr, now: Instant) -> bool {
+        if self.limit == 0 || self.window.is_zero() {
+            return true;
+        }
+
+        let mut clients = match self.clients.lock() {
+            Ok(clients) => clients,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        // Once per window, drop the windows that have expired so idle clients
+        // do not accumulate
+        if now.saturating_duration_since(clients.pruned) >= self.window {
+            clients
+                .windows
+                .retain(|_, (started, _)| now.saturating_duration_since(*started) < self.window);
+            clients.pruned = now;
+        }
+
+        let entry = clients.windows.entry(ip).or_insert((now, 0));
+        if now.saturating_duration_since(entry.0) >= self.window {
+            *entry = (now, 0);
+        }
+        if entry.1 >= self.limit {
+            return false;
+        }
+        entry.1 = entry.1.saturating_add(1);
+        true
+    }
+}
+
+/// Middleware rejecting clients that exceed the rate limit with 429
+pub async fn rate_limit(
+    State(limiter): State<Arc<RateLimiter>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if limiter.check(addr.ip()) {
+        return next.run(request).await;
+    }
+
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        Json(serde_json::json!({
+            "status": "err",
+            "error": "rate limit exceeded",
+        })),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIENT: IpAddr = IpAddr::V4(std::net::Ipv4Addr::LOCALHOST);
+    const OTHER: IpAddr = IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED);
+
+    #[test]
+    fn rejects_requests_over_the_limit() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(60));
+        let now = Instant::now();
+        assert!(limiter.check_at(CLIENT, now));
+        assert!(limiter.check_at(CLIENT, now));
+        assert!(!limiter.check_at(CLIENT, now));
+        assert!(limiter.check_at(OTHER, now));
+    }
+
+    #[test]
+    fn resets_the_count_when_the_window_ends() {
+        let window = Duration::from_secs(60);
+        let limiter = RateLimiter::new(1, window);
+        let now = Instant::now();
+        assert!(limiter.check_at(CLIENT, now));
+        assert!(!limiter.check_at(CLIENT, now + window / 2));
+        assert!(limiter.check_at(CLIENT, now + window));
+    }
+
+    #[test]
+    fn zero_disables_the_limiter() {
+        let now = Instant::now();
+        let unlimited = RateLimiter::new(0, Duration::from_secs(60));
+        let no_window = RateLimiter::new(1, Duration::ZERO);
+        for _ in 0..10 {
+            assert!(unlimited.check_at(CLIENT, now));
+            assert!(no_window.check_at(CLIENT, now));
+        }
+    }
+
+    #[test]
+    fn drops_expired_windows_once_a_window_has_passed() {
+        let window = Duration::from_secs(60);
+        let limiter = RateLimiter::new(5, window);
+        let now = Instant::now();
+        assert!(limiter.check_at(CLIENT, now));
+        assert!(limiter.check_at(OTHER, now + window * 2));
+        let clients = limiter.clients.lock().map(|clients| clients.windows.len());
+        assert_eq!(clients.ok(), Some(1));
+    }
+
+    #[test]
+    fn parses_limits_and_ignores_invalid_values() {
+        assert_eq!(parse_or("LIMIT", Some(" 42 ".to_string()), 7_u32), 42);
+        assert_eq!(parse_or("LIMIT", Some("lots".to_string()), 7_u32), 7);
+        assert_eq!(parse_or("LIMIT", Some("-1".to_string()), 7_u32), 7);
+        assert_eq!(parse_or("LIMIT", None, 7_u32), 7);
+    }
+}
@@ -1,25 +1,63 @@
 #![forbid(unsafe_code)]
 
-use axum::{Json, Router, routing::get, routing::post};
+mod limits;
+mod negotiate;
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::{Json, Router, middleware, routing::get, routing::post};
 use serde::{Deserialize, Serialize};
 use tower_http::cors::{Any, CorsLayer};
+use utoipa::{OpenApi, ToSchema};
+
+use crate::limits::{Limits, RateLimiter};
 
-#[derive(Debug, Deserialize)]
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "zparse-api",
+        description = "Parse and convert JSON/JSONC/CSV/TOML/YAML/XML",
+        license(name = "GPL-3.0")
+    ),
+    paths(health, formats, parse, convert),
+    components(schemas(
+        ParseRequest,
+        ConvertRequest,
+        InputFormat,
+        OutputFormat,
+        ApiResponse,
+        ConvertResponse
+    ))
+)]
+struct ApiDoc;
+
+#[derive(Debug, Deserialize, ToSchema)]
 struct ParseRequest {
+    /// Document to parse
     content: String,
     format: InputFormat,
+    /// Single ASCII character used as CSV field separator
+    #[schema(value_type = Option<String>, example = ";")]
     csv_delimiter: Option<char>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct ConvertRequest {
+    /// Document to convert
     content: String,
     from: InputFormat,
     to: OutputFormat,
+    /// Single ASCII character used as CSV field separator
+    #[schema(value_type = Option<String>, example = ";")]
     csv_delimiter: Option<char>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, ToSchema)]
 #[serde(rename_all = "lowercase")]
 enum InputFormat {
     Json,
@@ -30,7 +68,7 @@ enum InputFormat {
     Xml,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, ToSchema)]
 #[serde(rename_all = "lowercase")]
 enum OutputFormat {
     Json,
@@ -65,26 +103,45 @@ impl From<OutputFormat> for zparse::Format {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 #[serde(tag = "status", rename_all = "lowercase")]
 enum ApiResponse {
-    Ok { data: serde_json::Value },
-    Err { error: String },
+    /// Parsed document rendered as JSON
+    Ok {
+        #[schema(value_type = Object)]
+        data: serde_json::Value,
+    },
+    Err {
+        error: String,
+    },
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct ConvertResponse {
+    /// `ok` or `error`
+    #[schema(value_type = String, example = "ok")]
     status: &'static str,
+    /// Converted document, or the error message when `status` is `error`
     content: String,
 }
 
 #[tokio::main]
 async fn main() {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
+    let limits = Limits::from_env();
+    let limiter = Arc::new(RateLimiter::new(limits.rate_limit, limits.rate_window));
+
     let app = Router::new()
         .route("/api/health", get(health))
         .route("/api/formats", get(formats))
         .route("/api/parse", post(parse))
         .route("/api/convert", post(convert))
+        .route("/api/openapi.json", get(openapi))
+        .with_state(limits)
+        .layer(body_limit(limits.max_body_bytes))
+        .layer(middleware::from_fn_with_state(limiter, limits::rate_limit))
         .layer(
             CorsLayer::new()
                 .allow_origin(Any)
@@ -99,61 +156,151 @@ async fn main() {
     let listener = match tokio::net::TcpListener::bind(&addr).await {
         Ok(listener) => listener,
         Err(err) => {
-            eprintln!("failed to bind {addr}: {err}");
+            tracing::error!("failed to bind {addr}: {err}");
             return;
         }
     };
 
-    if let Err(err) = axum::serve(listener, app).await {
-        eprintln!("server error: {err}");
+    let service = app.into_make_service_with_connect_info::<SocketAddr>();
+    if let Err(err) = axum::serve(listener, service).await {
+        tracing::error!("server error: {err}");
+    }
+}
+
+fn body_limit(max_body_bytes: usize) -> DefaultBodyLimit {
+    if max_body_bytes == 0 {
+        DefaultBodyLimit::disable()
+    } else {
+        DefaultBodyLimit::max(max_body_bytes)
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/health",
+    responses((status = 200, description = "Server is up", body = Object, example = json!({"status": "ok"})))
+)]
 async fn health() -> Json<serde_json::Value> {
     Json(serde_json::json!({"status": "ok"}))
 }
 
-async fn formats() -> Json<Vec<&'static str>> {
-    Json(vec!["json", "jsonc", "csv", "toml", "yaml", "xml"])
+#[utoipa::path(
+    get,
+    path = "/api/formats",
+    responses((status = 200, description = "Supported input format names", body = Vec<String>))
+)]
+async fn formats() -> Json<Vec<String>> {
+    let mut names = Vec::new();
+    for name in zparse::registry::global().names() {
+        names.push(name.to_string());
+        if name == "json" {
+            names.push("jsonc".to_string());
+        }
+    }
+    Json(names)
 }
 
-async fn parse(Json(payload): Json<ParseRequest>) -> Json<ApiResponse> {
-    match parse_to_json(&payload.content, payload.format, payload.csv_delimiter) {
+#[utoipa::path(
+    post,
+    path = "/api/parse",
+    request_body = ParseRequest,
+    responses(
+        (status = 200, description = "Parse result", body = ApiResponse),
+        (status = 413, description = "Request body too large"),
+        (status = 429, description = "Rate limit exceeded")
+    )
+)]
+async fn parse(
+    State(limits): State<Limits>,
+    Json(payload): Json<ParseRequest>,
+) -> Json<ApiResponse> {
+    let options = convert_options(&limits, payload.format, payload.csv_delimiter);
+    match parse_to_json(&payload.content, payload.format, &options) {
         Ok(data) => Json(ApiResponse::Ok { data }),
         Err(err) => Json(ApiResponse::Err { error: err }),
     }
 }
 
-async fn convert(Json(payload): Json<ConvertRequest>) -> Json<ConvertResponse> {
-    let csv_config = csv_config_from_delimiter(payload.csv_delimiter);
-    let result = if matches!(payload.from, InputFormat::Jsonc) {
-        let config = zparse::JsonConfig {
-            allow_comments: true,
-            allow_trailing_commas: true,
-            ..zparse::JsonConfig::default()
-        };
-        zparse::convert_with_options(
-            &payload.content,
-            payload.from.into(),
-            payload.to.into(),
-            &zparse::ConvertOptions {
-                json: config,
-                csv: csv_config,
-            },
+/// Convert a JSON-wrapped request, or a raw document when `Content-Type` names
+/// its format (the output format then comes from `Accept`, defaulting to JSON).
+///
+/// `Content-Type` alone decides which: `application/json` is always a
+/// `ConvertRequest`, and any other supported media type is a raw document.
+/// Send raw JSON documents as `text/json`.
+#[utoipa::path(
+    post,
+    path = "/api/convert",
+    request_body(content(
+        (ConvertRequest = "application/json"),
+        (String = "text/json"),
+        (String = "application/toml"),
+        (String = "application/yaml"),
+        (String = "application/xml"),
+        (String = "text/csv")
+    )),
+    responses(
+        (status = 200, description = "Conversion result, or the converted document for raw requests", body = ConvertResponse),
+        (status = 406, description = "Accept names no supported format"),
+        (status = 413, description = "Request body too large"),
+        (status = 415, description = "Unsupported Content-Type"),
+        (status = 422, description = "Invalid request, or a raw document that failed to convert"),
+        (status = 429, description = "Rate limit exceeded")
+    )
+)]
+async fn convert(State(limits): State<Limits>, headers: HeaderMap, body: Bytes) -> Response {
+    if let Some(from) = negotiate::raw_input_format(&headers) {
+        return convert_raw(&limits, &headers, from, &body);
+    }
+    if negotiate::content_format(&headers) != Some(zparse::Format::Json) {
+        return (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "expected a JSON request or a document Content-Type",
         )
-    } else if matches!(payload.from, InputFormat::Csv) && payload.csv_delimiter.is_some() {
-        zparse::convert_with_options(
-            &payload.content,
-            payload.from.into(),
-            payload.to.into(),
-            &zparse::ConvertOptions {
-                csv: csv_config,
-                ..Default::default()
-            },
+            .into_response();
+    }
+    match serde_json::from_slice::<ConvertRequest>(&body) {
+        Ok(payload) => convert_wrapped(&limits, payload).into_response(),
+        Err(err) => (StatusCode::UNPROCESSABLE_ENTITY, err.to_string()).into_response(),
+    }
+}
+
+fn convert_raw(
+    limits: &Limits,
+    headers: &HeaderMap,
+    from: zparse::Format,
+    body: &[u8],
+) -> Response {
+    let Some(to) = negotiate::accepted_format(headers, zparse::Format::Json) else {
+        return (
+            StatusCode::NOT_ACCEPTABLE,
+            "Accept names no supported format",
         )
-    } else {
-        zparse::convert(&payload.content, payload.from.into(), payload.to.into())
+            .into_response();
     };
+    let Ok(input) = std::str::from_utf8(body) else {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "request body is not valid UTF-8",
+        )
+            .into_response();
+    };
+
+    match zparse::convert_with_options(input, from, to, &limits.convert_options()) {
+        Ok(content) => {
+            ([(header::CONTENT_TYPE, negotiate::media_type(to))], content).into_response()
+        }
+        Err(err) => (StatusCode::UNPROCESSABLE_ENTITY, err.to_string()).into_response(),
+    }
+}
+
+fn convert_wrapped(limits: &Limits, payload: ConvertRequest) -> Json<ConvertResponse> {
+    let options = convert_options(limits, payload.from, payload.csv_delimiter);
+    let result = zparse::convert_with_options(
+        &payload.content,
+        payload.from.into(),
+        payload.to.into(),
+        &options,
+    );
 
     match result {
         Ok(content) => Json(ConvertResponse {
@@ -167,48 +314,33 @@ async fn convert(Json(payload): Json<ConvertRequest>) -> Json<ConvertResponse> {
     }
 }
 
-fn csv_config_from_delimiter(delimiter: Option<char>) -> zparse::CsvConfig {
-    match delimiter {
-        Some(ch) if ch.is_ascii() => zparse::CsvConfig::default().with_delimiter(ch as u8),
-        _ => zparse::CsvConfig::default(),
+async fn openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+fn convert_options(
+    limits: &Limits,
+    format: InputFormat,
+    csv_delimiter: Option<char>,
+) -> zparse::ConvertOptions {
+    let mut options = limits.convert_options();
+    if matches!(format, InputFormat::Jsonc) {
+        options.json = options.json.with_comments(true).with_trailing_commas(true);
+    }
+    if let Some(ch) = csv_delimiter
+        && ch.is_ascii()
+    {
+        options.csv = options.csv.with_delimiter(ch as u8);
     }
+    options
 }
 
 fn parse_to_json(
     input: &str,
     format: InputFormat,
-    csv_delimiter: Option<char>,
+    options: &zparse::ConvertOptions,
 ) -> Result<serde_json::Value, String> {
-    let csv_config = csv_config_from_delimiter(csv_delimiter);
-    let json = if matches!(format, InputFormat::Jsonc) {
-        let config = zparse::JsonConfig {
-            allow_comments: true,
-            allow_trailing_commas: true,
-            ..zparse::JsonConfig::default()
-        };
-        zparse::convert_with_options(
-            input,
-            format.into(),
-            zparse::Format::Json,
-            &zparse::ConvertOptions {
-                json: config,
-                csv: csv_config,
-            },
-        )
-    } else if matches!(format, InputFormat::Csv) && csv_delimiter.is_some() {
-        zparse::convert_with_options(
-            input,
-            format.into(),
-            zparse::Format::Json,
-            &zparse::ConvertOptions {
-                csv: csv_config,
-                ..Default::default()
-            },
-        )
-    } else {
-        zparse::convert(input, format.into(), zparse::Format::Json)
-    };
-
-    let json: String = json.map_err(|err| err.to_string())?;
+    let json = zparse::convert_with_options(input, format.into(), zparse::Format::Json, options)
+        .map_err(|err| err.to_string())?;
     serde_json::from_str(&json).map_err(|err| err.to_string())
 }
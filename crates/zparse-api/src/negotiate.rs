@@ -0,0 +1,218 @@
+//! Content negotiation for raw conversion requests
+//!
+//! `POST /api/convert` also accepts a bare document whose `Content-Type` names
+//! its format. The output format comes from the `Accept` header and the
+//! converted document is returned as the response body.
+//!
+//! `Content-Type` alone tells the two apart: `application/json` is always a
+//! JSON-wrapped `ConvertRequest`, and any other supported media type is a raw
+//! document. Raw JSON documents are sent as `text/json`.
+
+use axum::http::HeaderMap;
+use axum::http::header::{ACCEPT, CONTENT_TYPE};
+
+/// Media types per format; the first entry is used for responses
+const MEDIA_TYPES: &[(zparse::Format, &[&str])] = &[
+    (zparse::Format::Json, &["application/json", "text/json"]),
+    (zparse::Format::Toml, &["application/toml"]),
+    (
+        zparse::Format::Yaml,
+        &[
+            "application/yaml",
+            "application/x-yaml",
+            "text/yaml",
+            "text/x-yaml",
+        ],
+    ),
+    (zparse::Format::Xml, &["application/xml", "text/xml"]),
+    (zparse::Format::Csv, &["text/csv"]),
+];
+
+/// Media type of a JSON-wrapped `ConvertRequest`
+pub const WRAPPED_MEDIA_TYPE: &str = "application/json";
+
+/// Format named by a media type, ignoring parameters such as `charset`
+pub fn format_for_media_type(media_type: &str) -> Option<zparse::Format> {
+    let essence = media_type.split(';').next().unwrap_or_default().trim();
+    MEDIA_TYPES
+        .iter()
+        .find(|(_, names)| names.iter().any(|name| name.eq_ignore_ascii_case(essence)))
+        .map(|(format, _)| *format)
+}
+
+/// Media type sent back for `format`
+pub fn media_type(format: zparse::Format) -> &'static str {
+    MEDIA_TYPES
+        .iter()
+        .find(|(candidate, _)| *candidate == format)
+        .and_then(|(_, names)| names.first().copied())
+        .unwrap_or("text/plain")
+}
+
+/// Format of the request body according to `Content-Type`
+pub fn content_format(headers: &HeaderMap) -> Option<zparse::Format> {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(format_for_media_type)
+}
+
+/// Preferred output format according to `Accept`.
+///
+/// Ranges are tried by descending `q`, in the client's order when equal.
+/// A missing header or `*/*` yields `fallback`, and `type/*` yields
+/// `fallback` when it has that type and otherwise the first format that
+/// does. None means the client accepts none of the supported formats.
+pub fn accepted_format(headers: &HeaderMap, fallback: zparse::Format) -> Option<zparse::Format> {
+    let Some(accept) = headers.get(ACCEPT).and_then(|value| value.to_str().ok()) else {
+        return Some(fallback);
+    };
+
+    let mut ranges: Vec<(f32, &str)> = accept
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';');
+            let media_type = parts.next()?.trim();
+            let quality = parts
+                .filter_map(|param| param.split_once('='))
+                .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+                .map_or(Some(1.0), |(_, q)| q.trim().parse::<f32>().ok())?;
+            (quality > 0.0 && !media_type.is_empty()).then_some((quality, media_type))
+        })
+        .collect();
+    // Stable, so equally weighted ranges keep the client's order.
+    ranges.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    ranges
+        .into_iter()
+        .find_map(|(_, media_type)| match media_type.strip_suffix("/*") {
+            Some("*") => Some(fallback),
+            Some(kind) => {
+                let has_kind = |names: &[&str]| {
+                    names.iter().any(|name| {
+                        name.split_once('/')
+                            .is_some_and(|(candidate, _)| candidate.eq_ignore_ascii_case(kind))
+                    })
+                };
+                MEDIA_TYPES
+                    .iter()
+                    .filter(|(_, names)| has_kind(names))
+                    .map(|(format, _)| *format)
+                    .min_by_key(|format| *format != fallback)
+            }
+            None => format_for_media_type(media_type),
+        })
+}
+
+/// Input format of a raw document request, or None for a JSON-wrapped
+/// `ConvertRequest`, which is exactly a body sent as `application/json`
+pub fn raw_input_format(headers: &HeaderMap) -> Option<zparse::Format> {
+    let content_type = headers.get(CONTENT_TYPE)?.to_str().ok()?;
+    let essence = content_type.split(';').next().unwrap_or_default().trim();
+    if essence.eq_ignore_ascii_case(WRAPPED_MEDIA_TYPE) {
+        return None;
+    }
+    format_for_media_type(essence)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+    use zparse::Format;
+
+    fn headers(content_type: Option<&'static str>, accept: Option<&'static str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(content_type) = content_type {
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+        }
+        if let Some(accept) = accept {
+            headers.insert(ACCEPT, HeaderValue::from_static(accept));
+        }
+        headers
+    }
+
+    #[test]
+    fn accepted_format_table() {
+        let cases = [
+            (None, Some(Format::Json)),
+            (Some("*/*"), Some(Format::Json)),
+            (Some("application/yaml"), Some(Format::Yaml)),
+            (Some("Application/TOML; charset=utf-8"), Some(Format::Toml)),
+            (Some("text/html, text/csv"), Some(Format::Csv)),
+            (Some("application/xml;q=0.5, text/yaml"), Some(Format::Yaml)),
+            (
+                Some("text/csv;q=0.2, application/toml;Q=0.9"),
+                Some(Format::Toml),
+            ),
+            (Some("text/yaml;q=0.5, text/csv;q=0.5"), Some(Format::Yaml)),
+            (Some("application/toml;q=0, */*;q=0.1"), Some(Format::Json)),
+            (Some("text/*"), Some(Format::Json)),
+            (
+                Some("application/*;q=1, text/xml;q=0.5"),
+                Some(Format::Json),
+            ),
+            (Some("image/*"), None),
+            (Some("text/html"), None),
+            (Some("application/toml;q=abc, text/csv"), Some(Format::Csv)),
+        ];
+        for (accept, expected) in cases {
+            assert_eq!(
+                accepted_format(&headers(None, accept), Format::Json),
+                expected,
+                "Accept: {accept:?}"
+            );
+        }
+        assert_eq!(
+            accepted_format(&headers(None, Some("text/*")), Format::Xml),
+            Some(Format::Xml)
+        );
+    }
+
+    #[test]
+    fn raw_input_is_decided_by_content_type_alone() {
+        let cases = [
+            (Some("application/json"), None, None),
+            (
+                Some("application/json; charset=utf-8"),
+                Some("application/toml"),
+                None,
+            ),
+            (Some("text/json"), None, Some(Format::Json)),
+            (
+                Some("text/json"),
+                Some("application/yaml"),
+                Some(Format::Json),
+            ),
+            (Some("application/toml"), None, Some(Format::Toml)),
+            (
+                Some("text/csv"),
+                Some("application/json"),
+                Some(Format::Csv),
+            ),
+            (Some("text/plain"), None, None),
+            (None, None, None),
+        ];
+        for (content_type, accept, expected) in cases {
+            assert_eq!(
+                raw_input_format(&headers(content_type, accept)),
+                expected,
+                "Content-Type: {content_type:?}, Accept: {accept:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn media_types_round_trip() {
+        for format in [
+            Format::Json,
+            Format::Toml,
+            Format::Yaml,
+            Format::Xml,
+            Format::Csv,
+        ] {
+            assert_eq!(format_for_media_type(media_type(format)), Some(format));
+        }
+    }
+}
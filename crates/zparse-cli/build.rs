@@ -0,0 +1,20 @@
+use std::path::PathBuf;
+
+use clap::CommandFactory;
+
+#[path = "src/cli.rs"]
+#[allow(dead_code)]
+mod cli;
+
+fn main() -> std::io::Result<()> {
+    println!("cargo:rerun-if-changed=src/cli.rs");
+
+    let Some(out_dir) = std::env::var_os("OUT_DIR") else {
+        return Ok(());
+    };
+    let man_dir = PathBuf::from(out_dir).join("man");
+    std::fs::create_dir_all(&man_dir)?;
+
+    let command = cli::Args::command();
+    clap_mangen::generate_to(command, &man_dir)
+}
@@ -0,0 +1,865 @@
+//! Command-line definitions
+//!
+//! Kept free of `zparse` types so `build.rs` can include this file to render
+//! man pages.
+
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "zparse",
+    version,
+    about = "Parse and convert JSON/JSONC/CSV/TOML/YAML/XML",
+    args_conflicts_with_subcommands = true,
+    after_help = "Examples:\n  zparse --parse input.json --print-output\n  zparse --convert input.json --from json --to toml\n  zparse convert --from csv --to json input.csv\n  zparse parse --from json input.json\n  cat input.xml | zparse parse --from xml"
+)]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Parse input and validate (top-level mode)
+    #[arg(long, value_name = "INPUT", num_args = 0..=1, default_missing_value = "-", conflicts_with = "convert")]
+    pub parse: Option<PathBuf>,
+    /// Convert between formats (top-level mode)
+    #[arg(long, value_name = "INPUT", num_args = 0..=1, default_missing_value = "-", conflicts_with = "parse")]
+    pub convert: Option<PathBuf>,
+    /// Input format (json, jsonc, csv, toml, yaml, xml)
+    #[arg(short, long, value_enum)]
+    pub from: Option<FormatArg>,
+    /// Output format (json, jsonc, json5, csv, toml, yaml, xml)
+    #[arg(short, long, value_enum)]
+    pub to: Option<OutputFormatArg>,
+    /// Output file (defaults to stdout)
+    #[arg(short, long, value_name = "OUTPUT")]
+    pub output: Option<PathBuf>,
+    /// Write input/converted output instead of "ok"
+    #[arg(long = "print-output")]
+    pub print_output: bool,
+    /// Rewrite the input file with the converted output (atomic temp file + rename)
+    #[arg(short = 'i', long, conflicts_with = "output")]
+    pub in_place: bool,
+    /// Keep a copy of the original input next to it with this suffix (e.g. .bak; requires --in-place)
+    #[arg(long, value_name = "SUFFIX", requires = "in_place")]
+    pub backup: Option<String>,
+    /// Expand ${VAR} and ${VAR:-default} in string values before converting
+    #[arg(long)]
+    pub interpolate_env: bool,
+    /// Resolve $include keys and YAML !include tags relative to each input file
+    #[arg(long)]
+    pub resolve_includes: bool,
+    /// Replace values of secret-looking keys (password, token, secret, ...) with [REDACTED]
+    #[arg(long)]
+    pub redact: bool,
+    /// Additional key pattern to redact (case-insensitive substring; repeatable, implies --redact)
+    #[arg(long = "redact-key", value_name = "PATTERN")]
+    pub redact_keys: Vec<String>,
+    /// Rename object keys recursively (snake, camel, kebab)
+    #[arg(long, value_enum, value_name = "CASE")]
+    pub rename_keys: Option<KeyCaseArg>,
+    /// Sort object keys recursively for stable output
+    #[arg(long)]
+    pub sort_keys: bool,
+    /// Sort arrays of objects by this field for stable output
+    #[arg(long, value_name = "FIELD")]
+    pub sort_arrays_by: Option<String>,
+    /// How YAML nulls are written to TOML (error, omit, empty-string)
+    #[arg(long, value_enum, value_name = "POLICY", default_value = "error")]
+    pub toml_null: TomlNullArg,
+    /// Wrap a non-table YAML root under this key when writing TOML
+    #[arg(long, value_name = "KEY")]
+    pub toml_root_key: Option<String>,
+    /// Reject arrays mixing value types when writing TOML (for TOML 0.5 readers)
+    #[arg(long)]
+    pub toml_homogeneous_arrays: bool,
+    /// Turn XML text that looks like a number or boolean into one
+    #[arg(long)]
+    pub xml_infer_types: bool,
+    /// Types of XML element text, e.g. port=integer,debug=bool (string, number, integer, bool)
+    #[arg(long, value_name = "SPEC")]
+    pub xml_types: Option<String>,
+    /// Whitespace in XML text (preserve, drop-blank, trim, collapse); xml:space="preserve" wins
+    #[arg(long, value_enum, value_name = "MODE", default_value = "drop-blank")]
+    pub xml_whitespace: XmlWhitespaceArg,
+    /// What a repeated YAML mapping key does (error, first-wins, last-wins); the lenient policies warn
+    #[arg(long, value_enum, value_name = "POLICY", default_value = "error")]
+    pub yaml_duplicate_keys: YamlDuplicateKeysArg,
+    /// Which unquoted YAML scalars are booleans, nulls and numbers (core, yaml11, json)
+    #[arg(long, value_enum, value_name = "SCHEMA", default_value = "core")]
+    pub yaml_schema: YamlSchemaArg,
+    /// Read unquoted YAML timestamps (2001-12-14, 2001-12-14 21:59:43Z) as dates and times
+    #[arg(long)]
+    pub yaml_timestamps: bool,
+    /// How strings containing newlines are written to TOML (escaped, basic, literal)
+    #[arg(long, value_enum, value_name = "STYLE", default_value = "escaped")]
+    pub toml_strings: TomlStringsArg,
+    /// Fold multi-line TOML basic strings at this width with line-ending backslashes
+    #[arg(long, value_name = "WIDTH")]
+    pub toml_fold_width: Option<usize>,
+    /// How byte strings are written to JSON and TOML (base64, array)
+    #[arg(long, value_enum, value_name = "ENCODING", default_value = "base64")]
+    pub bytes: BytesArg,
+    /// Digits written after the decimal point for fractional numbers
+    #[arg(long, value_name = "DIGITS")]
+    pub float_precision: Option<usize>,
+    /// Use scientific notation once a number's decimal exponent reaches this magnitude
+    #[arg(long, value_name = "EXP")]
+    pub exponent_threshold: Option<u32>,
+    /// How numbers without a fractional part are written (integer, decimal)
+    #[arg(long, value_enum, value_name = "STYLE", default_value = "integer")]
+    pub integral_floats: IntegralFloatsArg,
+    /// Indent JSON output (the default when writing to a terminal; pipes and files get compact output)
+    #[arg(long)]
+    pub pretty: bool,
+    /// End indented JSONC/JSON5 arrays and objects with a trailing comma
+    #[arg(long)]
+    pub output_trailing_commas: bool,
+    /// Leave identifier keys unquoted in JSON5 output
+    #[arg(long)]
+    pub unquoted_keys: bool,
+    /// Quote character for JSON5 strings (double, single)
+    #[arg(long, value_enum, value_name = "QUOTE", default_value = "double")]
+    pub json_quote: JsonQuoteArg,
+    /// Continue JSON5 strings across lines at embedded newlines
+    #[arg(long)]
+    pub multiline_strings: bool,
+    /// Quote character around XML attribute values (double, single)
+    #[arg(long, value_enum, value_name = "QUOTE", default_value = "double")]
+    pub xml_quote: XmlQuoteArg,
+    /// Write non-ASCII characters in XML as character references (&#xE9;)
+    #[arg(long)]
+    pub xml_ascii: bool,
+    /// Spaces per nesting level in YAML output (default: 2)
+    #[arg(long, value_name = "WIDTH")]
+    pub yaml_indent: Option<usize>,
+    /// Where YAML sequence dashes go under a key (indented, aligned)
+    #[arg(long, value_enum, value_name = "STYLE")]
+    pub yaml_sequences: Option<YamlSequencesArg>,
+    /// When YAML strings are quoted (always, as-needed, preserve)
+    #[arg(long, value_enum, value_name = "WHEN", default_value = "always")]
+    pub yaml_strings: YamlStringsArg,
+    /// Give local date-times this offset (e.g. +02:00 or Z) in the output
+    #[arg(long, value_name = "OFFSET")]
+    pub local_offset: Option<String>,
+    /// Write date-times that have an offset in UTC
+    #[arg(long)]
+    pub utc_datetimes: bool,
+    /// Allow JSON comments (// and /* */)
+    #[arg(long)]
+    pub json_comments: bool,
+    /// Allow trailing commas in JSON
+    #[arg(long)]
+    pub json_trailing_commas: bool,
+    /// CSV field delimiter as a single character (default: ,)
+    #[arg(long, value_name = "CHAR")]
+    pub csv_delimiter: Option<char>,
+    /// Color documents and errors written to a terminal (auto honors NO_COLOR)
+    #[arg(long, value_enum, default_value_t = ColorArg::Auto, global = true)]
+    pub color: ColorArg,
+    /// Give up on http(s) URL inputs after this many seconds
+    #[arg(long, value_name = "SECS", default_value_t = 30, global = true)]
+    pub http_timeout: u64,
+    /// Refuse http(s) URL inputs larger than this many bytes
+    #[arg(long, value_name = "BYTES", default_value_t = 64 * 1024 * 1024, global = true)]
+    pub http_max_size: u64,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Validate an input file or stdin
+    Parse(ParseArgs),
+    /// Convert between formats
+    Convert(ConvertArgs),
+    /// Print a shell completion script to stdout
+    Completions(CompletionsArgs),
+    /// Work with JSON Schemas
+    Schema(SchemaArgs),
+    /// Generate Rust structs (serde) from sample documents
+    Codegen(CodegenArgs),
+    /// Compare two documents and report the first difference (exit code 1 if they differ)
+    #[command(visible_alias = "eq")]
+    Compare(CompareArgs),
+    /// Report keys repeated or conflicting across the config files in a directory, and includes with no effect (exit code 1 if any)
+    Audit(AuditArgs),
+    /// Reformat a file in its own format, keeping JSONC comments and TOML key quoting
+    Fmt(FmtArgs),
+    /// Print a file with sorted keys and normalized values, for use as a git diff textconv
+    Textconv(TextconvArgs),
+    /// Render a text template with values from a document
+    Render(RenderArgs),
+    /// Write each item of an array in a document to its own file, or as NDJSON
+    Split(SplitArgs),
+    /// Print the first, last or a random sample of the items of an array or NDJSON stream
+    Peek(PeekArgs),
+    /// Summarize a document's structure: types, keys, depth, array lengths and sizes
+    Stats(StatsArgs),
+    /// Flatten chosen fields of an array of records into rows, e.g. for CSV
+    Table(TableArgs),
+    /// Run a jq-like expression against a document and print each result
+    #[command(name = "x", visible_alias = "query")]
+    Query(QueryArgs),
+    /// Write a reproducible pseudo-random document, e.g. as benchmark input
+    Testgen(TestgenArgs),
+    /// List the formats zparse can read and write, with their file extensions
+    Formats,
+    /// Browse a document as a collapsible tree, with search and path copying
+    Explore(ExploreArgs),
+}
+
+#[derive(Debug, Parser)]
+pub struct ParseArgs {
+    /// Input files or http(s) URLs (defaults to stdin); several are each reported as `PATH: ok` or an error
+    #[arg(value_name = "INPUT")]
+    pub inputs: Vec<PathBuf>,
+    /// Input format (json, jsonc, csv, toml, yaml, xml)
+    #[arg(short, long, value_enum)]
+    pub from: Option<FormatArg>,
+    /// Output file (defaults to stdout)
+    #[arg(short, long, value_name = "OUTPUT")]
+    pub output: Option<PathBuf>,
+    /// Write input content instead of "ok"
+    #[arg(long = "print-output")]
+    pub print_output: bool,
+    /// Allow JSON comments (// and /* */)
+    #[arg(long)]
+    pub json_comments: bool,
+    /// Allow trailing commas in JSON
+    #[arg(long)]
+    pub json_trailing_commas: bool,
+    /// CSV field delimiter as a single character (default: ,)
+    #[arg(long, value_name = "CHAR")]
+    pub csv_delimiter: Option<char>,
+    /// Check up to N inputs at once (0 for one per CPU)
+    #[arg(short, long, value_name = "N", default_value_t = 1)]
+    pub jobs: usize,
+}
+
+#[derive(Debug, Parser)]
+pub struct ConvertArgs {
+    /// Input files (defaults to stdin); later files take precedence when merging
+    #[arg(value_name = "INPUT")]
+    pub inputs: Vec<PathBuf>,
+    /// Input format (json, jsonc, csv, toml, yaml, xml)
+    #[arg(short, long, value_enum)]
+    pub from: Option<FormatArg>,
+    /// Output format (json, jsonc, json5, csv, toml, yaml, xml)
+    #[arg(short, long, value_enum)]
+    pub to: OutputFormatArg,
+    /// Merge multiple inputs into one document instead of emitting an array of them
+    #[arg(long, value_enum, value_name = "STRATEGY")]
+    pub merge: Option<MergeArg>,
+    /// Output file (defaults to stdout)
+    #[arg(short, long, value_name = "OUTPUT")]
+    pub output: Option<PathBuf>,
+    /// Write converted output instead of "ok" (implied for stdin input without --output)
+    #[arg(long = "print-output")]
+    pub print_output: bool,
+    /// Rewrite the input file with the converted output (atomic temp file + rename)
+    #[arg(short = 'i', long, conflicts_with = "output")]
+    pub in_place: bool,
+    /// Keep a copy of the original input next to it with this suffix (e.g. .bak; requires --in-place)
+    #[arg(long, value_name = "SUFFIX", requires = "in_place")]
+    pub backup: Option<String>,
+    /// Expand ${VAR} and ${VAR:-default} in string values before converting
+    #[arg(long)]
+    pub interpolate_env: bool,
+    /// Resolve $include keys and YAML !include tags relative to each input file
+    #[arg(long)]
+    pub resolve_includes: bool,
+    /// Replace values of secret-looking keys (password, token, secret, ...) with [REDACTED]
+    #[arg(long)]
+    pub redact: bool,
+    /// Additional key pattern to redact (case-insensitive substring; repeatable, implies --redact)
+    #[arg(long = "redact-key", value_name = "PATTERN")]
+    pub redact_keys: Vec<String>,
+    /// Rename object keys recursively (snake, camel, kebab)
+    #[arg(long, value_enum, value_name = "CASE")]
+    pub rename_keys: Option<KeyCaseArg>,
+    /// Sort object keys recursively for stable output
+    #[arg(long)]
+    pub sort_keys: bool,
+    /// Sort arrays of objects by this field for stable output
+    #[arg(long, value_name = "FIELD")]
+    pub sort_arrays_by: Option<String>,
+    /// How YAML nulls are written to TOML (error, omit, empty-string)
+    #[arg(long, value_enum, value_name = "POLICY", default_value = "error")]
+    pub toml_null: TomlNullArg,
+    /// Wrap a non-table YAML root under this key when writing TOML
+    #[arg(long, value_name = "KEY")]
+    pub toml_root_key: Option<String>,
+    /// Reject arrays mixing value types when writing TOML (for TOML 0.5 readers)
+    #[arg(long)]
+    pub toml_homogeneous_arrays: bool,
+    /// Turn XML text that looks like a number or boolean into one
+    #[arg(long)]
+    pub xml_infer_types: bool,
+    /// Types of XML element text, e.g. port=integer,debug=bool (string, number, integer, bool)
+    #[arg(long, value_name = "SPEC")]
+    pub xml_types: Option<String>,
+    /// Whitespace in XML text (preserve, drop-blank, trim, collapse); xml:space="preserve" wins
+    #[arg(long, value_enum, value_name = "MODE", default_value = "drop-blank")]
+    pub xml_whitespace: XmlWhitespaceArg,
+    /// What a repeated YAML mapping key does (error, first-wins, last-wins); the lenient policies warn
+    #[arg(long, value_enum, value_name = "POLICY", default_value = "error")]
+    pub yaml_duplicate_keys: YamlDuplicateKeysArg,
+    /// Which unquoted YAML scalars are booleans, nulls and numbers (core, yaml11, json)
+    #[arg(long, value_enum, value_name = "SCHEMA", default_value = "core")]
+    pub yaml_schema: YamlSchemaArg,
+    /// Read unquoted YAML timestamps (2001-12-14, 2001-12-14 21:59:43Z) as dates and times
+    #[arg(long)]
+    pub yaml_timestamps: bool,
+    /// How strings containing newlines are written to TOML (escaped, basic, literal)
+    #[arg(long, value_enum, value_name = "STYLE", default_value = "escaped")]
+    pub toml_strings: TomlStringsArg,
+    /// Fold multi-line TOML basic strings at this width with line-ending backslashes
+    #[arg(long, value_name = "WIDTH")]
+    pub toml_fold_width: Option<usize>,
+    /// How byte strings are written to JSON and TOML (base64, array)
+    #[arg(long, value_enum, value_name = "ENCODING", default_value = "base64")]
+    pub bytes: BytesArg,
+    /// Digits written after the decimal point for fractional numbers
+    #[arg(long, value_name = "DIGITS")]
+    pub float_precision: Option<usize>,
+    /// Use scientific notation once a number's decimal exponent reaches this magnitude
+    #[arg(long, value_name = "EXP")]
+    pub exponent_threshold: Option<u32>,
+    /// How numbers without a fractional part are written (integer, decimal)
+    #[arg(long, value_enum, value_name = "STYLE", default_value = "integer")]
+    pub integral_floats: IntegralFloatsArg,
+    /// Indent JSON output (the default when writing to a terminal; pipes and files get compact output)
+    #[arg(long)]
+    pub pretty: bool,
+    /// End indented JSONC/JSON5 arrays and objects with a trailing comma
+    #[arg(long)]
+    pub output_trailing_commas: bool,
+    /// Leave identifier keys unquoted in JSON5 output
+    #[arg(long)]
+    pub unquoted_keys: bool,
+    /// Quote character for JSON5 strings (double, single)
+    #[arg(long, value_enum, value_name = "QUOTE", default_value = "double")]
+    pub json_quote: JsonQuoteArg,
+    /// Continue JSON5 strings across lines at embedded newlines
+    #[arg(long)]
+    pub multiline_strings: bool,
+    /// Quote character around XML attribute values (double, single)
+    #[arg(long, value_enum, value_name = "QUOTE", default_value = "double")]
+    pub xml_quote: XmlQuoteArg,
+    /// Write non-ASCII characters in XML as character references (&#xE9;)
+    #[arg(long)]
+    pub xml_ascii: bool,
+    /// Spaces per nesting level in YAML output (default: 2)
+    #[arg(long, value_name = "WIDTH")]
+    pub yaml_indent: Option<usize>,
+    /// Where YAML sequence dashes go under a key (indented, aligned)
+    #[arg(long, value_enum, value_name = "STYLE")]
+    pub yaml_sequences: Option<YamlSequencesArg>,
+    /// When YAML strings are quoted (always, as-needed, preserve)
+    #[arg(long, value_enum, value_name = "WHEN", default_value = "always")]
+    pub yaml_strings: YamlStringsArg,
+    /// Give local date-times this offset (e.g. +02:00 or Z) in the output
+    #[arg(long, value_name = "OFFSET")]
+    pub local_offset: Option<String>,
+    /// Write date-times that have an offset in UTC
+    #[arg(long)]
+    pub utc_datetimes: bool,
+    /// Allow JSON comments (// and /* */)
+    #[arg(long)]
+    pub json_comments: bool,
+    /// Allow trailing commas in JSON
+    #[arg(long)]
+    pub json_trailing_commas: bool,
+    /// CSV field delimiter as a single character (default: ,)
+    #[arg(long, value_name = "CHAR")]
+    pub csv_delimiter: Option<char>,
+    /// Read and parse up to N inputs at once (0 for one per CPU)
+    #[arg(short, long, value_name = "N", default_value_t = 1)]
+    pub jobs: usize,
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+pub enum FormatArg {
+    Json,
+    Jsonc,
+    Csv,
+    Toml,
+    #[value(alias = "yml")]
+    Yaml,
+    Xml,
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+pub enum OutputFormatArg {
+    Json,
+    Jsonc,
+    Json5,
+    Csv,
+    Toml,
+    #[value(alias = "yml")]
+    Yaml,
+    Xml,
+}
+
+#[derive(Debug, Parser)]
+pub struct CompletionsArgs {
+    /// Shell to generate completions for
+    #[arg(value_enum)]
+    pub shell: Shell,
+}
+
+#[derive(Debug, Parser)]
+pub struct TextconvArgs {
+    /// File to print (the format comes from its extension or content)
+    #[arg(value_name = "INPUT")]
+    pub input: PathBuf,
+}
+
+#[derive(Debug, Parser)]
+pub struct RenderArgs {
+    /// Document with the values (defaults to stdin)
+    #[arg(value_name = "INPUT")]
+    pub input: Option<PathBuf>,
+    /// Template file using {{ path.to.value }}, {{ for x in list }} and {{ if path }} tags
+    #[arg(short, long, value_name = "TEMPLATE")]
+    pub template: PathBuf,
+    /// Input format (json, jsonc, csv, toml, yaml, xml)
+    #[arg(short, long, value_enum)]
+    pub from: Option<FormatArg>,
+    /// Output file (defaults to stdout)
+    #[arg(short, long, value_name = "OUTPUT")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+pub struct SplitArgs {
+    /// Input file (defaults to stdin); JSON is streamed rather than loaded whole
+    #[arg(value_name = "INPUT")]
+    pub input: Option<PathBuf>,
+    /// Input format (json, jsonc, csv, toml, yaml, xml)
+    #[arg(short, long, value_enum)]
+    pub from: Option<FormatArg>,
+    /// Dot-separated path to the array, e.g. data.items (defaults to the root)
+    #[arg(short, long, value_name = "PATH", default_value = "")]
+    pub path: String,
+    /// Directory for one file per item, named 000000.json, 000001.json, ...
+    #[arg(long, value_name = "DIR", required_unless_present = "ndjson")]
+    pub out_dir: Option<PathBuf>,
+    /// Format of the item files
+    #[arg(long, value_enum, default_value = "json")]
+    pub format: FormatArg,
+    /// Write the items as newline-delimited JSON instead of files
+    #[arg(long, conflicts_with_all = ["out_dir", "format"])]
+    pub ndjson: bool,
+    /// Output file for --ndjson (defaults to stdout)
+    #[arg(short, long, value_name = "OUTPUT", requires = "ndjson")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+#[command(group = clap::ArgGroup::new("pick").required(true).args(["head", "tail", "sample"]))]
+pub struct PeekArgs {
+    /// Input file (defaults to stdin); .ndjson and .jsonl files are read as NDJSON
+    #[arg(value_name = "INPUT")]
+    pub input: Option<PathBuf>,
+    /// Input format (json, jsonc, csv, toml, yaml, xml)
+    #[arg(short, long, value_enum, conflicts_with = "ndjson")]
+    pub from: Option<FormatArg>,
+    /// Read the input as newline-delimited JSON and print the picked lines the same way
+    #[arg(long)]
+    pub ndjson: bool,
+    /// Dot-separated path to the array, e.g. data.items (defaults to the root)
+    #[arg(
+        short,
+        long,
+        value_name = "PATH",
+        default_value = "",
+        conflicts_with = "ndjson"
+    )]
+    pub path: String,
+    /// Keep the first N items (JSON stops reading after them)
+    #[arg(long, value_name = "N")]
+    pub head: Option<usize>,
+    /// Keep the last N items
+    #[arg(long, value_name = "N")]
+    pub tail: Option<usize>,
+    /// Keep N items chosen at random, in document order
+    #[arg(long, value_name = "N")]
+    pub sample: Option<usize>,
+    /// Seed for --sample; the same seed picks the same items
+    #[arg(long, value_name = "SEED", default_value_t = 0, requires = "sample")]
+    pub seed: u64,
+    /// Output format for the array of picked items (json, jsonc, json5, csv, toml, yaml, xml)
+    #[arg(
+        short,
+        long,
+        value_enum,
+        default_value = "json",
+        conflicts_with = "ndjson"
+    )]
+    pub to: OutputFormatArg,
+    /// Output file (defaults to stdout)
+    #[arg(short, long, value_name = "OUTPUT")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+pub struct StatsArgs {
+    /// Input file (defaults to stdin)
+    #[arg(value_name = "INPUT")]
+    pub input: Option<PathBuf>,
+    /// Input format (json, jsonc, csv, toml, yaml, xml)
+    #[arg(short, long, value_enum)]
+    pub from: Option<FormatArg>,
+    /// Write the summary as a document in this format instead of text
+    #[arg(short, long, value_enum)]
+    pub to: Option<OutputFormatArg>,
+    /// Show only the N most common keys
+    #[arg(long, value_name = "N", default_value_t = 20)]
+    pub top_keys: usize,
+    /// Output file (defaults to stdout)
+    #[arg(short, long, value_name = "OUTPUT")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+pub struct ExploreArgs {
+    /// Input file (defaults to stdin)
+    #[arg(value_name = "INPUT")]
+    pub input: Option<PathBuf>,
+    /// Input format (json, jsonc, csv, toml, yaml, xml)
+    #[arg(short, long, value_enum)]
+    pub from: Option<FormatArg>,
+}
+
+#[derive(Debug, Parser)]
+pub struct TableArgs {
+    /// Input file (defaults to stdin)
+    #[arg(value_name = "INPUT")]
+    pub input: Option<PathBuf>,
+    /// Input format (json, jsonc, csv, toml, yaml, xml)
+    #[arg(short, long, value_enum)]
+    pub from: Option<FormatArg>,
+    /// Comma-separated column paths, optionally renamed: id,name,city=address.city
+    #[arg(short, long, value_name = "COLUMNS", required = true)]
+    pub columns: String,
+    /// Dot-separated path to the array of records, e.g. data.items (defaults to the root)
+    #[arg(short, long, value_name = "PATH", default_value = "")]
+    pub path: String,
+    /// Output format (json, jsonc, json5, csv, toml, yaml, xml)
+    #[arg(short, long, value_enum, default_value = "csv")]
+    pub to: OutputFormatArg,
+    /// Output file (defaults to stdout)
+    #[arg(short, long, value_name = "OUTPUT")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+pub struct QueryArgs {
+    /// Expression to run, e.g. '.items[] | select(.price > 10) | .name'
+    #[arg(value_name = "EXPR")]
+    pub expr: String,
+    /// Input file (defaults to stdin)
+    #[arg(value_name = "INPUT")]
+    pub input: Option<PathBuf>,
+    /// Input format (json, jsonc, csv, toml, yaml, xml)
+    #[arg(short, long, value_enum)]
+    pub from: Option<FormatArg>,
+    /// Print string results without quotes
+    #[arg(short, long)]
+    pub raw_output: bool,
+    /// Print JSON results on one line each, even on a terminal
+    #[arg(short, long)]
+    pub compact: bool,
+    /// Output format for each result (json, jsonc, json5, csv, toml, yaml, xml)
+    #[arg(short, long, value_enum, default_value = "json")]
+    pub to: OutputFormatArg,
+    /// Output file (defaults to stdout)
+    #[arg(short, long, value_name = "OUTPUT")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum MergeArg {
+    /// Later inputs replace top-level keys
+    Shallow,
+    /// Nested tables/objects are merged key by key
+    Deep,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum KeyCaseArg {
+    /// snake_case
+    Snake,
+    /// camelCase
+    Camel,
+    /// kebab-case
+    Kebab,
+}
+
+#[derive(Debug, Parser)]
+pub struct SchemaArgs {
+    #[command(subcommand)]
+    pub command: SchemaCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SchemaCommand {
+    /// Infer a JSON Schema from one or more sample documents
+    Infer(SchemaInferArgs),
+}
+
+#[derive(Debug, Parser)]
+pub struct SchemaInferArgs {
+    #[command(flatten)]
+    pub samples: SampleArgs,
+    /// Output file (defaults to stdout)
+    #[arg(short, long, value_name = "OUTPUT")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+pub struct CodegenArgs {
+    #[command(flatten)]
+    pub samples: SampleArgs,
+    /// Name of the top-level type
+    #[arg(long, value_name = "NAME", default_value = "Root")]
+    pub name: String,
+    /// Treat the input as a JSON Schema (e.g. from `zparse schema infer`) instead of samples
+    #[arg(long)]
+    pub schema: bool,
+    /// Output file (defaults to stdout)
+    #[arg(short, long, value_name = "OUTPUT")]
+    pub output: Option<PathBuf>,
+}
+
+/// Sample documents shared by `schema infer` and `codegen`
+#[derive(Debug, Parser)]
+pub struct SampleArgs {
+    /// Sample files (defaults to stdin)
+    #[arg(value_name = "INPUT")]
+    pub inputs: Vec<PathBuf>,
+    /// Input format (json, jsonc, csv, toml, yaml, xml)
+    #[arg(short, long, value_enum)]
+    pub from: Option<FormatArg>,
+    /// Treat a top-level array in each input as a list of samples
+    #[arg(long)]
+    pub records: bool,
+    /// Allow JSON comments (// and /* */)
+    #[arg(long)]
+    pub json_comments: bool,
+    /// Allow trailing commas in JSON
+    #[arg(long)]
+    pub json_trailing_commas: bool,
+    /// CSV field delimiter as a single character (default: ,)
+    #[arg(long, value_name = "CHAR")]
+    pub csv_delimiter: Option<char>,
+}
+
+#[derive(Debug, Parser)]
+pub struct CompareArgs {
+    /// Left document
+    #[arg(value_name = "LEFT")]
+    pub left: PathBuf,
+    /// Right document (may be a different format)
+    #[arg(value_name = "RIGHT")]
+    pub right: PathBuf,
+    /// Input format for both documents (defaults to each file's extension)
+    #[arg(short, long, value_enum)]
+    pub from: Option<FormatArg>,
+    /// Relative tolerance when comparing numbers
+    #[arg(long, value_name = "EPSILON", default_value_t = 0.0)]
+    pub epsilon: f64,
+    /// Treat objects with keys in a different order as different
+    #[arg(long)]
+    pub strict_key_order: bool,
+    /// Treat a null entry and a missing key as equal
+    #[arg(long)]
+    pub null_equals_missing: bool,
+    /// Treat arrays with the same items in a different order as equal
+    #[arg(long)]
+    pub ignore_array_order: bool,
+    /// Match strings against numbers, booleans and datetimes they spell (e.g. "8080" and 8080)
+    #[arg(long)]
+    pub coerce_scalars: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct AuditArgs {
+    /// Directory to scan, including subdirectories (hidden entries are skipped)
+    #[arg(value_name = "DIR", default_value = ".")]
+    pub dir: PathBuf,
+    /// Write the report as a document in this format instead of text
+    #[arg(short, long, value_enum)]
+    pub to: Option<OutputFormatArg>,
+    /// Output file (defaults to stdout)
+    #[arg(short, long, value_name = "OUTPUT")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+pub struct FmtArgs {
+    /// File to format (the format comes from its extension or content)
+    #[arg(value_name = "INPUT")]
+    pub input: PathBuf,
+    /// Output file (defaults to stdout)
+    #[arg(short, long, value_name = "OUTPUT")]
+    pub output: Option<PathBuf>,
+    /// Rewrite the input file (atomic temp file + rename)
+    #[arg(short = 'i', long, conflicts_with = "output")]
+    pub in_place: bool,
+    /// Keep a copy of the original input next to it with this suffix (e.g. .bak; requires --in-place)
+    #[arg(long, value_name = "SUFFIX", requires = "in_place")]
+    pub backup: Option<String>,
+    /// Spaces per nesting level for YAML (defaults to the file's own)
+    #[arg(long, value_name = "WIDTH")]
+    pub yaml_indent: Option<usize>,
+    /// Where YAML sequence dashes go under a key (defaults to the file's own)
+    #[arg(long, value_enum, value_name = "STYLE")]
+    pub yaml_sequences: Option<YamlSequencesArg>,
+    /// When YAML strings are quoted (always, as-needed, preserve)
+    #[arg(long, value_enum, value_name = "WHEN", default_value = "always")]
+    pub yaml_strings: YamlStringsArg,
+    /// Allow JSON comments (// and /* */), e.g. for VS Code settings.json
+    #[arg(long)]
+    pub json_comments: bool,
+    /// Allow trailing commas in JSON
+    #[arg(long)]
+    pub json_trailing_commas: bool,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum TomlNullArg {
+    /// Fail and report the path of the null
+    Error,
+    /// Drop null entries
+    Omit,
+    /// Write nulls as empty strings
+    EmptyString,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ColorArg {
+    /// When writing to a terminal and NO_COLOR is unset
+    Auto,
+    /// Always, even when piped
+    Always,
+    /// Never
+    Never,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum BytesArg {
+    /// Padded base64 strings
+    Base64,
+    /// Arrays of byte values
+    Array,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum IntegralFloatsArg {
+    /// Write 8080.0 as 8080
+    Integer,
+    /// Write 8080.0 as 8080.0
+    Decimal,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum TomlStringsArg {
+    /// Single-line strings with escaped newlines
+    Escaped,
+    /// Multi-line basic strings ("""...""")
+    Basic,
+    /// Multi-line literal strings ('''...'''), falling back to basic
+    Literal,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum JsonQuoteArg {
+    /// "..."
+    Double,
+    /// '...'
+    Single,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum XmlWhitespaceArg {
+    /// Keep all text, indentation included
+    Preserve,
+    /// Drop whitespace-only text such as indentation
+    DropBlank,
+    /// Also trim whitespace around text
+    Trim,
+    /// Also collapse runs of whitespace to one space
+    Collapse,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum YamlDuplicateKeysArg {
+    /// Fail, as YAML requires
+    Error,
+    /// Keep the first value
+    FirstWins,
+    /// Keep the last value
+    LastWins,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum YamlSchemaArg {
+    /// YAML 1.2: true/false, null/~, decimal, 0o octal and 0x hex numbers, .inf/.nan
+    Core,
+    /// YAML 1.1: also yes/no/on/off, 0-prefixed octal, 0b binary and 1:30 base 60
+    Yaml11,
+    /// Only true, false, null and JSON numbers
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum XmlQuoteArg {
+    /// name="..."
+    Double,
+    /// name='...'
+    Single,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum YamlStringsArg {
+    /// Double-quote every string
+    Always,
+    /// Quote strings a reader could mistake for another type, like "no" or "1.0"
+    AsNeeded,
+    /// Keep the quotes of the source when reformatting; as needed otherwise
+    Preserve,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum YamlSequencesArg {
+    /// key:\n  - item
+    Indented,
+    /// key:\n- item
+    Aligned,
+}
+
+#[derive(Debug, Parser)]
+pub struct TestgenArgs {
+    /// Seed; the same seed and options always give the same document
+    #[arg(short, long, default_value_t = 0)]
+    pub seed: u64,
+    /// Approximate size as compact JSON, e.g. 4096, 512k or 10MB
+    #[arg(long, value_name = "SIZE", default_value = "64k")]
+    pub size: String,
+    /// Deepest nesting, counting the root as 1
+    #[arg(long, value_name = "N", default_value_t = 6)]
+    pub depth: usize,
+    /// Most entries in one nested array or object
+    #[arg(long, value_name = "N", default_value_t = 8)]
+    pub width: usize,
+    /// Weights of value kinds, e.g. 'string=3,integer=1,object=1' (null, bool, integer, float, string, array, object)
+    #[arg(long, value_name = "WEIGHTS")]
+    pub mix: Option<String>,
+    /// Output format (json, jsonc, json5, csv, toml, yaml, xml)
+    #[arg(short, long, value_enum, default_value = "json")]
+    pub to: OutputFormatArg,
+    /// Output file (defaults to stdout)
+    #[arg(short, long, value_name = "OUTPUT")]
+    pub output: Option<PathBuf>,
+}
@@ -0,0 +1,179 @@
+//! Terminal colors for documents and errors
+//!
+//! Documents are colored with the library's own highlighter, so the colors
+//! follow how zparse reads the text. Errors with a position show the source
+//! line with a caret under the problem. `--color auto` colors only what goes
+//! to a terminal, and not at all when `NO_COLOR` is set to a non-empty value.
+
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use zparse::highlight::{TokenClass, highlight};
+
+use crate::cli::ColorArg;
+
+const RESET: &str = "\x1b[0m";
+const ERROR: &str = "\x1b[1;31m";
+const GUTTER: &str = "\x1b[1;34m";
+
+static CHOICE: OnceLock<ColorArg> = OnceLock::new();
+
+/// Remember the `--color` choice for [`stdout`] and [`stderr`]
+pub fn init(choice: ColorArg) {
+    let _ = CHOICE.set(choice);
+}
+
+/// Whether to color what is written to stdout
+pub fn stdout() -> bool {
+    enabled(io::stdout().is_terminal())
+}
+
+/// Whether to color what is written to stderr
+pub fn stderr() -> bool {
+    enabled(io::stderr().is_terminal())
+}
+
+fn enabled(terminal: bool) -> bool {
+    if !cfg!(feature = "color") {
+        return false;
+    }
+    match CHOICE.get().copied().unwrap_or(ColorArg::Auto) {
+        ColorArg::Always => true,
+        ColorArg::Never => false,
+        ColorArg::Auto => terminal && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()),
+    }
+}
+
+/// ANSI color of a highlighted range; punctuation and XML text stay plain
+fn code(class: TokenClass) -> Option<&'static str> {
+    match class {
+        TokenClass::Key => Some("\x1b[34m"),
+        TokenClass::String => Some("\x1b[32m"),
+        TokenClass::Number => Some("\x1b[36m"),
+        TokenClass::Boolean | TokenClass::Null => Some("\x1b[35m"),
+        TokenClass::Datetime => Some("\x1b[33m"),
+        TokenClass::Comment => Some("\x1b[90m"),
+        TokenClass::Tag | TokenClass::Directive => Some("\x1b[1;35m"),
+        _ => None,
+    }
+}
+
+/// `text`, a document in `format`, with its keys, strings, numbers and so on
+/// colored. Text the highlighter cannot read (JSON5 output, for one) is
+/// returned as is rather than half colored.
+pub fn document(text: &str, format: zparse::Format) -> String {
+    let highlights = highlight(text, format);
+    if highlights
+        .iter()
+        .any(|(_, class)| *class == TokenClass::Invalid)
+    {
+        return text.to_string();
+    }
+    let mut colored = String::with_capacity(text.len().saturating_add(text.len() / 2));
+    let mut end = 0;
+    for (range, class) in highlights {
+        let (Some(gap), Some(token)) = (text.get(end..range.start), text.get(range.clone())) else {
+            continue;
+        };
+        colored.push_str(gap);
+        match code(class) {
+            Some(code) => {
+                colored.push_str(code);
+                colored.push_str(token);
+                colored.push_str(RESET);
+            }
+            None => colored.push_str(token),
+        }
+        end = range.end;
+    }
+    colored.push_str(text.get(end..).unwrap_or_default());
+    colored
+}
+
+/// `err`, raised while reading `source`, as an error for the user: its
+/// message, then the line it points at with a caret under the problem
+pub fn source_error(err: zparse::Error, source: &str, path: Option<&Path>) -> anyhow::Error {
+    anyhow::anyhow!(diagnostic(&err, source, path, stderr()))
+}
+
+/// The message of `err`, followed by `path:line:col` and the source line with
+/// carets under the span when the error has a position, then its context
+/// frames
+pub fn diagnostic(err: &zparse::Error, source: &str, path: Option<&Path>, color: bool) -> String {
+    let mut text = located(err, source, path, color);
+    // `=` lines up with the gutter's `|`
+    let pad = " ".repeat(err.span().start.line.max(1).to_string().len());
+    for frame in err.frames() {
+        text.push_str(&format!("\n{pad} = {frame}"));
+    }
+    text
+}
+
+fn located(err: &zparse::Error, source: &str, path: Option<&Path>, color: bool) -> String {
+    let span = err.span();
+    let start = span.start;
+    let Some(text) = (start.line > 0)
+        .then(|| {
+            source
+                .lines()
+                .nth(usize::try_from(start.line - 1).unwrap_or(usize::MAX))
+        })
+        .flatten()
+    else {
+        return err.message().to_string();
+    };
+
+    // Columns come from the offset when it lies on the line, so tabs and
+    // multi-byte characters line up; otherwise from the reported column.
+    let line_start = source
+        .get(..start.offset)
+        .map(|before| before.rfind('\n').map_or(0, |newline| newline + 1));
+    let prefix = line_start
+        .and_then(|line_start| source.get(line_start..start.offset))
+        .filter(|prefix| !prefix.contains('\n'));
+    let indent: String = match prefix {
+        Some(prefix) => prefix
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect(),
+        None => " ".repeat(usize::try_from(start.col.saturating_sub(1)).unwrap_or(0)),
+    };
+    let width = match source.get(start.offset..span.end.offset) {
+        Some(covered) if span.end.line == start.line && !covered.is_empty() => {
+            covered.chars().count()
+        }
+        _ => 1,
+    };
+    let col = prefix.map_or(start.col, |prefix| {
+        u32::try_from(prefix.chars().count())
+            .unwrap_or(u32::MAX)
+            .saturating_add(1)
+    });
+
+    let number = start.line.to_string();
+    let pad = " ".repeat(number.len());
+    let (gutter, error, reset) = if color {
+        (GUTTER, ERROR, RESET)
+    } else {
+        ("", "", "")
+    };
+    let location = path.map_or_else(|| "<stdin>".to_string(), |p| p.display().to_string());
+    format!(
+        "{message}\n\
+         {pad}{gutter}-->{reset} {location}:{line}:{col}\n\
+         {pad} {gutter}|{reset}\n\
+         {gutter}{number} |{reset} {text}\n\
+         {pad} {gutter}|{reset} {indent}{error}{carets}{reset}",
+        message = err.message(),
+        line = start.line,
+        carets = "^".repeat(width),
+    )
+}
+
+/// `err` as printed before exiting: `error:` (red on a terminal) and the
+/// message with its causes
+pub fn report(err: &anyhow::Error) -> String {
+    let (error, reset) = if stderr() { (ERROR, RESET) } else { ("", "") };
+    format!("{error}error{reset}: {err:#}")
+}
@@ -0,0 +1,435 @@
+//! `zparse explore`: an interactive tree view of a document
+//!
+//! Objects and arrays start collapsed below the first level and open on
+//! demand, so only the visible part of a large document is laid out. Search
+//! looks through the whole document, opening the containers around each
+//! match. The path of the selected value can be copied to the terminal's
+//! clipboard (OSC 52) as a JSON pointer or a `zparse query` path.
+
+use std::collections::HashSet;
+use std::io::{self, Write};
+
+use anyhow::{Context, Result};
+use ratatui::DefaultTerminal;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use zparse::Value;
+
+const HELP: &str = "↑↓ move  ←→ fold  space toggle  e/c expand/collapse all  / search  n/N next/prev  y/Y copy pointer/path  q quit";
+
+/// Longest scalar shown on a row before it is cut short
+const PREVIEW_CHARS: usize = 120;
+
+/// One step from a container into a child
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// A visible line of the tree
+struct Row {
+    path: Vec<Segment>,
+    pointer: String,
+    container: bool,
+}
+
+struct Explorer<'v> {
+    root: &'v Value,
+    title: String,
+    expanded: HashSet<String>,
+    rows: Vec<Row>,
+    list: ListState,
+    /// Text typed after `/`, while the prompt is open
+    prompt: Option<String>,
+    query: String,
+    status: String,
+}
+
+/// Browse `value` until the user quits
+pub fn run(value: &Value, title: String) -> Result<()> {
+    let mut explorer = Explorer::new(value, title);
+    let mut terminal = ratatui::init();
+    let result = explorer.event_loop(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl<'v> Explorer<'v> {
+    fn new(root: &'v Value, title: String) -> Self {
+        let mut explorer = Self {
+            root,
+            title,
+            expanded: HashSet::from([String::new()]),
+            rows: Vec::new(),
+            list: ListState::default().with_selected(Some(0)),
+            prompt: None,
+            query: String::new(),
+            status: HELP.to_string(),
+        };
+        explorer.layout();
+        explorer
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal
+                .draw(|frame| self.draw(frame))
+                .context("failed to draw")?;
+            let Event::Key(key) = event::read().context("failed to read input")? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if let Some(prompt) = &mut self.prompt {
+                match key.code {
+                    KeyCode::Esc => self.prompt = None,
+                    KeyCode::Enter => {
+                        self.query = std::mem::take(prompt);
+                        self.prompt = None;
+                        self.find(true, true);
+                    }
+                    KeyCode::Backspace => {
+                        prompt.pop();
+                    }
+                    KeyCode::Char(c) => prompt.push(c),
+                    _ => {}
+                }
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(());
+                }
+                KeyCode::Up | KeyCode::Char('k') => self.list.select_previous(),
+                KeyCode::Down | KeyCode::Char('j') => self.list.select_next(),
+                KeyCode::PageUp => self.list.scroll_up_by(20),
+                KeyCode::PageDown => self.list.scroll_down_by(20),
+                KeyCode::Home | KeyCode::Char('g') => self.list.select_first(),
+                KeyCode::End | KeyCode::Char('G') => self.list.select_last(),
+                KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter => self.set_open(true),
+                KeyCode::Left | KeyCode::Char('h') => self.close_or_parent(),
+                KeyCode::Char(' ') => self.toggle(),
+                KeyCode::Char('e') => self.expand_all(),
+                KeyCode::Char('c') => {
+                    self.expanded = HashSet::from([String::new()]);
+                    self.layout();
+                    self.list.select_first();
+                }
+                KeyCode::Char('/') => self.prompt = Some(String::new()),
+                KeyCode::Char('n') => self.find(true, false),
+                KeyCode::Char('N') => self.find(false, false),
+                KeyCode::Char('y') => self.copy(false),
+                KeyCode::Char('Y') => self.copy(true),
+                _ => {}
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut ratatui::Frame) {
+        let [tree, status] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let items: Vec<ListItem> = self
+            .rows
+            .iter()
+            .map(|row| ListItem::new(self.line(row)))
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(self.title.as_str()))
+            .highlight_style(Style::new().reversed());
+        frame.render_stateful_widget(list, tree, &mut self.list);
+
+        let status_line = match &self.prompt {
+            Some(prompt) => format!("/{prompt}"),
+            None => self.status.clone(),
+        };
+        frame.render_widget(Paragraph::new(status_line), status);
+    }
+
+    /// The text of `row`: indentation, fold marker, key and value preview
+    fn line(&self, row: &Row) -> Line<'static> {
+        let mut spans = vec![Span::raw("  ".repeat(row.path.len().saturating_sub(1)))];
+        spans.push(Span::raw(
+            match (row.container, self.expanded.contains(&row.pointer)) {
+                (true, true) => "▾ ",
+                (true, false) => "▸ ",
+                (false, _) => "  ",
+            },
+        ));
+        match row.path.last() {
+            Some(Segment::Key(key)) => spans.push(Span::styled(key.clone(), Color::Blue)),
+            Some(Segment::Index(index)) => {
+                spans.push(Span::styled(format!("[{index}]"), Color::DarkGray))
+            }
+            None => spans.push(Span::raw("(root)")),
+        }
+        spans.push(Span::raw(": "));
+        let value = lookup(self.root, &row.path).unwrap_or(&Value::Null);
+        spans.push(match value {
+            Value::Object(entries) => {
+                Span::styled(format!("{{{} keys}}", entries.len()), Color::DarkGray)
+            }
+            Value::Array(items) => {
+                Span::styled(format!("[{} items]", items.len()), Color::DarkGray)
+            }
+            Value::String(_) => Span::styled(preview(value), Color::Green),
+            Value::Number(_) => Span::styled(preview(value), Color::Cyan),
+            Value::Bool(_) | Value::Null => Span::styled(preview(value), Color::Magenta),
+            Value::Datetime(_) | Value::Bytes(_) => Span::styled(preview(value), Color::Yellow),
+        });
+        Line::from(spans)
+    }
+
+    /// Rebuild the visible rows from the expanded containers
+    fn layout(&mut self) {
+        let mut rows = Vec::new();
+        let mut stack = vec![(Vec::new(), self.root)];
+        while let Some((path, value)) = stack.pop() {
+            let pointer = pointer(&path);
+            let container = value.is_object() || value.is_array();
+            let open = container && self.expanded.contains(&pointer);
+            rows.push(Row {
+                path: path.clone(),
+                pointer,
+                container,
+            });
+            if open {
+                let mut children = children(&path, value);
+                children.reverse();
+                stack.extend(children);
+            }
+        }
+        self.rows = rows;
+    }
+
+    fn selected(&self) -> Option<&Row> {
+        self.list.selected().and_then(|index| self.rows.get(index))
+    }
+
+    fn set_open(&mut self, open: bool) {
+        let Some(row) = self.selected() else {
+            return;
+        };
+        if !row.container {
+            return;
+        }
+        let pointer = row.pointer.clone();
+        if open {
+            self.expanded.insert(pointer);
+        } else {
+            self.expanded.remove(&pointer);
+        }
+        self.layout();
+    }
+
+    fn toggle(&mut self) {
+        let open = self
+            .selected()
+            .is_some_and(|row| !self.expanded.contains(&row.pointer));
+        self.set_open(open);
+    }
+
+    /// Fold the selected container, or select its parent if it is folded
+    fn close_or_parent(&mut self) {
+        let Some(row) = self.selected() else {
+            return;
+        };
+        if row.container && self.expanded.contains(&row.pointer) {
+            self.set_open(false);
+            return;
+        }
+        let parent = row.path.split_last().map(|(_, parent)| parent.to_vec());
+        if let Some(parent) = parent {
+            self.select(&parent);
+        }
+    }
+
+    fn expand_all(&mut self) {
+        let mut stack = vec![(Vec::new(), self.root)];
+        while let Some((path, value)) = stack.pop() {
+            if value.is_object() || value.is_array() {
+                self.expanded.insert(pointer(&path));
+                stack.extend(children(&path, value));
+            }
+        }
+        self.layout();
+    }
+
+    /// Open the containers around `path` and select it
+    fn select(&mut self, path: &[Segment]) {
+        for depth in 0..path.len() {
+            self.expanded
+                .insert(pointer(path.get(..depth).unwrap_or_default()));
+        }
+        self.layout();
+        let index = self.rows.iter().position(|row| row.path == path);
+        self.list.select(index);
+    }
+
+    /// Select the next (or previous) value in document order whose key or
+    /// text contains the query, ignoring case; `here` also accepts the
+    /// selected value
+    fn find(&mut self, forward: bool, here: bool) {
+        if self.query.is_empty() {
+            return;
+        }
+        let needle = self.query.to_lowercase();
+        let current = self
+            .selected()
+            .map(|row| row.path.clone())
+            .unwrap_or_default();
+        // Each value's position in document order, to find the selected one
+        let mut order = 0;
+        let mut selected = 0;
+        let mut matches = Vec::new();
+        let mut stack = vec![(Vec::new(), self.root)];
+        while let Some((path, value)) = stack.pop() {
+            let key_matches = match path.last() {
+                Some(Segment::Key(key)) => key.to_lowercase().contains(&needle),
+                _ => false,
+            };
+            let text_matches = !value.is_object()
+                && !value.is_array()
+                && preview(value).to_lowercase().contains(&needle);
+            let mut children = children(&path, value);
+            children.reverse();
+            stack.extend(children);
+            if path == current {
+                selected = order;
+            }
+            if key_matches || text_matches {
+                matches.push((order, path));
+            }
+            order += 1;
+        }
+
+        let index = if forward {
+            matches
+                .iter()
+                .position(|(order, _)| *order > selected || (here && *order == selected))
+                .unwrap_or(0)
+        } else {
+            matches
+                .iter()
+                .rposition(|(order, _)| *order < selected || (here && *order == selected))
+                .unwrap_or(matches.len().saturating_sub(1))
+        };
+        let total = matches.len();
+        match matches.into_iter().nth(index) {
+            Some((_, path)) => {
+                self.select(&path);
+                self.status = format!("match {} of {total} for {:?}", index + 1, self.query);
+            }
+            None => self.status = format!("no match for {:?}", self.query),
+        }
+    }
+
+    /// Copy the selected path to the clipboard, as a `zparse query` path or a
+    /// JSON pointer
+    fn copy(&mut self, query: bool) {
+        let Some(row) = self.selected() else {
+            return;
+        };
+        let text = if query {
+            query_path(&row.path)
+        } else {
+            row.pointer.clone()
+        };
+        // Value::Bytes serializes as base64, which OSC 52 wants
+        let encoded = zparse::serialize(
+            &Value::Bytes(text.as_bytes().to_vec()),
+            zparse::Format::Json,
+        )
+        .unwrap_or_default();
+        let mut stdout = io::stdout();
+        let copied = write!(stdout, "\x1b]52;c;{}\x07", encoded.trim_matches('"'))
+            .and_then(|()| stdout.flush());
+        self.status = match copied {
+            Ok(()) => format!("copied {text}"),
+            Err(err) => format!("could not copy {text}: {err}"),
+        };
+    }
+}
+
+/// The child values of `value` with their paths
+fn children<'v>(path: &[Segment], value: &'v Value) -> Vec<(Vec<Segment>, &'v Value)> {
+    let child = |segment| {
+        let mut child = path.to_vec();
+        child.push(segment);
+        child
+    };
+    match value {
+        Value::Object(entries) => entries
+            .iter()
+            .map(|(key, value)| (child(Segment::Key(key.clone())), value))
+            .collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(index, value)| (child(Segment::Index(index)), value))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn lookup<'v>(root: &'v Value, path: &[Segment]) -> Option<&'v Value> {
+    path.iter()
+        .try_fold(root, |value, segment| match (value, segment) {
+            (Value::Object(entries), Segment::Key(key)) => entries.get(key),
+            (Value::Array(items), Segment::Index(index)) => items.get(*index),
+            _ => None,
+        })
+}
+
+/// JSON pointer of `path`, e.g. `/servers/0/host`
+fn pointer(path: &[Segment]) -> String {
+    path.iter()
+        .map(|segment| match segment {
+            Segment::Key(key) => format!("/{}", key.replace('~', "~0").replace('/', "~1")),
+            Segment::Index(index) => format!("/{index}"),
+        })
+        .collect()
+}
+
+/// `zparse query` path of `path`, e.g. `.servers[0].host`
+fn query_path(path: &[Segment]) -> String {
+    if path.is_empty() {
+        return ".".to_string();
+    }
+    path.iter()
+        .map(|segment| match segment {
+            Segment::Key(key)
+                if key
+                    .chars()
+                    .next()
+                    .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                    && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+            {
+                format!(".{key}")
+            }
+            Segment::Key(key) => format!(".{}", json_string(key)),
+            Segment::Index(index) => format!("[{index}]"),
+        })
+        .collect()
+}
+
+/// `key` as a JSON string
+fn json_string(key: &str) -> String {
+    zparse::serialize(&Value::from(key), zparse::Format::Json).unwrap_or_default()
+}
+
+/// A scalar as JSON, cut short after [`PREVIEW_CHARS`]
+fn preview(value: &Value) -> String {
+    let text = zparse::serialize(value, zparse::Format::Json).unwrap_or_default();
+    let mut chars = text.chars();
+    let mut preview: String = chars.by_ref().take(PREVIEW_CHARS).collect();
+    if chars.next().is_some() {
+        preview.push('…');
+    }
+    preview
+}
@@ -0,0 +1,67 @@
+//! Inputs given as http(s) URLs
+//!
+//! With the `http` feature, an input such as
+//! `https://example.com/config.yaml` is downloaded instead of opened, so
+//! hosted configs can be checked without a separate download step. A request
+//! gives up after `--http-timeout` seconds and a body over `--http-max-size`
+//! bytes is refused, so a slow or huge response can't hang a CI job or fill
+//! its memory. The format is inferred from the URL's extension, then from the
+//! content, as for files.
+//!
+//! The client is a [`zparse::InputSource`] for the `http` and `https`
+//! schemes, so it is found the same way as sources other crates register.
+
+use std::io::Read;
+
+use zparse::InputSource;
+
+/// Downloads http(s) URLs
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(not(feature = "http"), allow(dead_code))]
+struct Http {
+    timeout_secs: u64,
+    max_size: u64,
+}
+
+/// Register the http(s) source with `--http-timeout` and `--http-max-size`
+pub fn init(timeout_secs: u64, max_size: u64) {
+    zparse::source::register(Http {
+        timeout_secs,
+        max_size,
+    });
+}
+
+impl InputSource for Http {
+    fn schemes(&self) -> &[&str] {
+        &["http", "https"]
+    }
+
+    #[cfg(feature = "http")]
+    fn open(&self, location: &str) -> zparse::Result<Box<dyn Read + Send>> {
+        use std::time::Duration;
+
+        let agent: ureq::Agent = ureq::Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(self.timeout_secs)))
+            .build()
+            .into();
+        let response = agent
+            .get(location)
+            .call()
+            .map_err(|err| zparse::source::error(location, err))?;
+        Ok(Box::new(
+            response
+                .into_body()
+                .into_with_config()
+                .limit(self.max_size)
+                .reader(),
+        ))
+    }
+
+    #[cfg(not(feature = "http"))]
+    fn open(&self, location: &str) -> zparse::Result<Box<dyn Read + Send>> {
+        Err(zparse::source::error(
+            location,
+            "zparse was built without the `http` feature",
+        ))
+    }
+}
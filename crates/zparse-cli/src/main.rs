@@ -1,130 +1,29 @@
-use std::io::{self, Read, Write};
-use std::path::PathBuf;
+mod cli;
+mod color;
+#[cfg(feature = "explore")]
+mod explore;
+mod fetch;
+
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use anyhow::{Context, Result, bail};
-use clap::{Parser, Subcommand, ValueEnum};
-
-#[derive(Debug, Parser)]
-#[command(
-    name = "zparse",
-    version,
-    about = "Parse and convert JSON/JSONC/CSV/TOML/YAML/XML",
-    args_conflicts_with_subcommands = true,
-    after_help = "Examples:\n  zparse --parse input.json --print-output\n  zparse --convert input.json --from json --to toml\n  zparse convert --from csv --to json input.csv\n  zparse parse --from json input.json\n  cat input.xml | zparse parse --from xml"
-)]
-struct Args {
-    #[command(subcommand)]
-    command: Option<Command>,
-    /// Parse input and validate (top-level mode)
-    #[arg(long, value_name = "INPUT", num_args = 0..=1, default_missing_value = "-", conflicts_with = "convert")]
-    parse: Option<PathBuf>,
-    /// Convert between formats (top-level mode)
-    #[arg(long, value_name = "INPUT", num_args = 0..=1, default_missing_value = "-", conflicts_with = "parse")]
-    convert: Option<PathBuf>,
-    /// Input format (json, jsonc, csv, toml, yaml, xml)
-    #[arg(short, long, value_enum)]
-    from: Option<FormatArg>,
-    /// Output format (json, csv, toml, yaml, xml)
-    #[arg(short, long, value_enum)]
-    to: Option<OutputFormatArg>,
-    /// Output file (defaults to stdout)
-    #[arg(short, long, value_name = "OUTPUT")]
-    output: Option<PathBuf>,
-    /// Write input/converted output instead of "ok"
-    #[arg(long = "print-output")]
-    print_output: bool,
-    /// Allow JSON comments (// and /* */)
-    #[arg(long)]
-    json_comments: bool,
-    /// Allow trailing commas in JSON
-    #[arg(long)]
-    json_trailing_commas: bool,
-    /// CSV field delimiter as a single character (default: ,)
-    #[arg(long, value_name = "CHAR")]
-    csv_delimiter: Option<char>,
-}
-
-#[derive(Debug, Subcommand)]
-enum Command {
-    /// Validate an input file or stdin
-    Parse(ParseArgs),
-    /// Convert between formats
-    Convert(ConvertArgs),
-}
-
-#[derive(Debug, Parser)]
-struct ParseArgs {
-    /// Input file (defaults to stdin)
-    #[arg(value_name = "INPUT")]
-    input: Option<PathBuf>,
-    /// Input format (json, jsonc, csv, toml, yaml, xml)
-    #[arg(short, long, value_enum)]
-    from: Option<FormatArg>,
-    /// Output file (defaults to stdout)
-    #[arg(short, long, value_name = "OUTPUT")]
-    output: Option<PathBuf>,
-    /// Write input content instead of "ok"
-    #[arg(long = "print-output")]
-    print_output: bool,
-    /// Allow JSON comments (// and /* */)
-    #[arg(long)]
-    json_comments: bool,
-    /// Allow trailing commas in JSON
-    #[arg(long)]
-    json_trailing_commas: bool,
-    /// CSV field delimiter as a single character (default: ,)
-    #[arg(long, value_name = "CHAR")]
-    csv_delimiter: Option<char>,
-}
-
-#[derive(Debug, Parser)]
-struct ConvertArgs {
-    /// Input file (defaults to stdin)
-    #[arg(value_name = "INPUT")]
-    input: Option<PathBuf>,
-    /// Input format (json, jsonc, csv, toml, yaml, xml)
-    #[arg(short, long, value_enum)]
-    from: Option<FormatArg>,
-    /// Output format (json, csv, toml, yaml, xml)
-    #[arg(short, long, value_enum)]
-    to: OutputFormatArg,
-    /// Output file (defaults to stdout)
-    #[arg(short, long, value_name = "OUTPUT")]
-    output: Option<PathBuf>,
-    /// Write converted output instead of "ok"
-    #[arg(long = "print-output")]
-    print_output: bool,
-    /// Allow JSON comments (// and /* */)
-    #[arg(long)]
-    json_comments: bool,
-    /// Allow trailing commas in JSON
-    #[arg(long)]
-    json_trailing_commas: bool,
-    /// CSV field delimiter as a single character (default: ,)
-    #[arg(long, value_name = "CHAR")]
-    csv_delimiter: Option<char>,
-}
-
-#[derive(Clone, Debug, ValueEnum)]
-enum FormatArg {
-    Json,
-    Jsonc,
-    Csv,
-    Toml,
-    #[value(alias = "yml")]
-    Yaml,
-    Xml,
-}
-
-#[derive(Clone, Debug, ValueEnum)]
-enum OutputFormatArg {
-    Json,
-    Csv,
-    Toml,
-    #[value(alias = "yml")]
-    Yaml,
-    Xml,
-}
+use clap::{CommandFactory, Parser};
+
+#[cfg(feature = "xml")]
+use crate::cli::XmlWhitespaceArg;
+use crate::cli::{
+    Args, AuditArgs, BytesArg, CodegenArgs, Command, CompareArgs, CompletionsArgs, ConvertArgs,
+    ExploreArgs, FmtArgs, FormatArg, IntegralFloatsArg, JsonQuoteArg, KeyCaseArg, MergeArg,
+    OutputFormatArg, ParseArgs, PeekArgs, QueryArgs, RenderArgs, SampleArgs, SchemaArgs,
+    SchemaCommand, SchemaInferArgs, SplitArgs, StatsArgs, TableArgs, TestgenArgs, TextconvArgs,
+    TomlNullArg, TomlStringsArg, XmlQuoteArg, YamlSequencesArg, YamlStringsArg,
+};
+#[cfg(feature = "yaml")]
+use crate::cli::{YamlDuplicateKeysArg, YamlSchemaArg};
 
 impl From<FormatArg> for zparse::Format {
     fn from(value: FormatArg) -> Self {
@@ -141,7 +40,9 @@ impl From<FormatArg> for zparse::Format {
 impl From<OutputFormatArg> for zparse::Format {
     fn from(value: OutputFormatArg) -> Self {
         match value {
-            OutputFormatArg::Json => zparse::Format::Json,
+            OutputFormatArg::Json | OutputFormatArg::Jsonc | OutputFormatArg::Json5 => {
+                zparse::Format::Json
+            }
             OutputFormatArg::Csv => zparse::Format::Csv,
             OutputFormatArg::Toml => zparse::Format::Toml,
             OutputFormatArg::Yaml => zparse::Format::Yaml,
@@ -150,24 +51,181 @@ impl From<OutputFormatArg> for zparse::Format {
     }
 }
 
-fn main() -> Result<()> {
+impl From<MergeArg> for zparse::MergeStrategy {
+    fn from(value: MergeArg) -> Self {
+        match value {
+            MergeArg::Shallow => zparse::MergeStrategy::Shallow,
+            MergeArg::Deep => zparse::MergeStrategy::Deep,
+        }
+    }
+}
+
+impl From<KeyCaseArg> for zparse::KeyCase {
+    fn from(value: KeyCaseArg) -> Self {
+        match value {
+            KeyCaseArg::Snake => zparse::KeyCase::Snake,
+            KeyCaseArg::Camel => zparse::KeyCase::Camel,
+            KeyCaseArg::Kebab => zparse::KeyCase::Kebab,
+        }
+    }
+}
+
+impl From<TomlNullArg> for zparse::NullPolicy {
+    fn from(value: TomlNullArg) -> Self {
+        match value {
+            TomlNullArg::Error => zparse::NullPolicy::Error,
+            TomlNullArg::Omit => zparse::NullPolicy::Omit,
+            TomlNullArg::EmptyString => zparse::NullPolicy::EmptyString,
+        }
+    }
+}
+
+impl From<BytesArg> for zparse::BytesEncoding {
+    fn from(value: BytesArg) -> Self {
+        match value {
+            BytesArg::Base64 => zparse::BytesEncoding::Base64,
+            BytesArg::Array => zparse::BytesEncoding::Array,
+        }
+    }
+}
+
+impl From<IntegralFloatsArg> for zparse::IntegralFloats {
+    fn from(value: IntegralFloatsArg) -> Self {
+        match value {
+            IntegralFloatsArg::Integer => zparse::IntegralFloats::Integer,
+            IntegralFloatsArg::Decimal => zparse::IntegralFloats::Decimal,
+        }
+    }
+}
+
+impl From<JsonQuoteArg> for zparse::JsonQuote {
+    fn from(value: JsonQuoteArg) -> Self {
+        match value {
+            JsonQuoteArg::Double => zparse::JsonQuote::Double,
+            JsonQuoteArg::Single => zparse::JsonQuote::Single,
+        }
+    }
+}
+
+#[cfg(feature = "xml")]
+impl From<XmlWhitespaceArg> for zparse::XmlWhitespace {
+    fn from(value: XmlWhitespaceArg) -> Self {
+        match value {
+            XmlWhitespaceArg::Preserve => zparse::XmlWhitespace::Preserve,
+            XmlWhitespaceArg::DropBlank => zparse::XmlWhitespace::DropBlank,
+            XmlWhitespaceArg::Trim => zparse::XmlWhitespace::Trim,
+            XmlWhitespaceArg::Collapse => zparse::XmlWhitespace::Collapse,
+        }
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl From<YamlDuplicateKeysArg> for zparse::YamlDuplicateKeys {
+    fn from(value: YamlDuplicateKeysArg) -> Self {
+        match value {
+            YamlDuplicateKeysArg::Error => zparse::YamlDuplicateKeys::Error,
+            YamlDuplicateKeysArg::FirstWins => zparse::YamlDuplicateKeys::FirstWins,
+            YamlDuplicateKeysArg::LastWins => zparse::YamlDuplicateKeys::LastWins,
+        }
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl From<YamlSchemaArg> for zparse::YamlSchema {
+    fn from(value: YamlSchemaArg) -> Self {
+        match value {
+            YamlSchemaArg::Core => zparse::YamlSchema::Core,
+            YamlSchemaArg::Yaml11 => zparse::YamlSchema::Yaml11,
+            YamlSchemaArg::Json => zparse::YamlSchema::Json,
+        }
+    }
+}
+
+impl From<XmlQuoteArg> for zparse::XmlQuote {
+    fn from(value: XmlQuoteArg) -> Self {
+        match value {
+            XmlQuoteArg::Double => zparse::XmlQuote::Double,
+            XmlQuoteArg::Single => zparse::XmlQuote::Single,
+        }
+    }
+}
+
+impl From<YamlSequencesArg> for zparse::YamlSequenceStyle {
+    fn from(value: YamlSequencesArg) -> Self {
+        match value {
+            YamlSequencesArg::Indented => zparse::YamlSequenceStyle::Indented,
+            YamlSequencesArg::Aligned => zparse::YamlSequenceStyle::Aligned,
+        }
+    }
+}
+
+impl From<YamlStringsArg> for zparse::YamlStringStyle {
+    fn from(value: YamlStringsArg) -> Self {
+        match value {
+            YamlStringsArg::Always => zparse::YamlStringStyle::Always,
+            YamlStringsArg::AsNeeded => zparse::YamlStringStyle::AsNeeded,
+            YamlStringsArg::Preserve => zparse::YamlStringStyle::Preserve,
+        }
+    }
+}
+
+impl From<TomlStringsArg> for zparse::TomlStringStyle {
+    fn from(value: TomlStringsArg) -> Self {
+        match value {
+            TomlStringsArg::Escaped => zparse::TomlStringStyle::Escaped,
+            TomlStringsArg::Basic => zparse::TomlStringStyle::MultilineBasic,
+            TomlStringsArg::Literal => zparse::TomlStringStyle::MultilineLiteral,
+        }
+    }
+}
+
+fn main() -> ExitCode {
     let args = Args::parse();
+    color::init(args.color);
+    fetch::init(args.http_timeout, args.http_max_size);
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", color::report(&err));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Args) -> Result<()> {
     if let Some(command) = args.command {
         return match command {
             Command::Parse(parse_args) => run_parse(parse_args),
             Command::Convert(convert_args) => run_convert(convert_args),
+            Command::Completions(completions_args) => run_completions(completions_args),
+            Command::Schema(schema_args) => run_schema(schema_args),
+            Command::Codegen(codegen_args) => run_codegen(codegen_args),
+            Command::Compare(compare_args) => run_compare(compare_args),
+            Command::Fmt(fmt_args) => run_fmt(fmt_args),
+            Command::Textconv(textconv_args) => run_textconv(textconv_args),
+            Command::Render(render_args) => run_render(render_args),
+            Command::Split(split_args) => run_split(split_args),
+            Command::Peek(peek_args) => run_peek(peek_args),
+            Command::Audit(audit_args) => run_audit(audit_args),
+            Command::Stats(stats_args) => run_stats(stats_args),
+            Command::Table(table_args) => run_table(table_args),
+            Command::Query(query_args) => run_query(query_args),
+            Command::Testgen(testgen_args) => run_testgen(testgen_args),
+            Command::Formats => run_formats(),
+            Command::Explore(explore_args) => run_explore(explore_args),
         };
     }
 
     if args.parse.is_some() {
         let parse_args = ParseArgs {
-            input: normalize_flag_input(args.parse),
+            inputs: normalize_flag_input(args.parse).into_iter().collect(),
             from: args.from,
             output: args.output,
             print_output: args.print_output,
             json_comments: args.json_comments,
             json_trailing_commas: args.json_trailing_commas,
             csv_delimiter: args.csv_delimiter,
+            jobs: 1,
         };
         return run_parse(parse_args);
     }
@@ -177,14 +235,52 @@ fn main() -> Result<()> {
             .to
             .ok_or_else(|| anyhow::anyhow!("--to is required when using --convert"))?;
         let convert_args = ConvertArgs {
-            input: normalize_flag_input(args.convert),
+            inputs: normalize_flag_input(args.convert).into_iter().collect(),
             from: args.from,
             to,
+            merge: None,
             output: args.output,
             print_output: args.print_output,
+            in_place: args.in_place,
+            backup: args.backup,
+            interpolate_env: args.interpolate_env,
+            resolve_includes: args.resolve_includes,
+            redact: args.redact,
+            redact_keys: args.redact_keys,
+            rename_keys: args.rename_keys,
+            sort_keys: args.sort_keys,
+            sort_arrays_by: args.sort_arrays_by,
+            toml_null: args.toml_null,
+            toml_root_key: args.toml_root_key,
+            toml_homogeneous_arrays: args.toml_homogeneous_arrays,
+            xml_infer_types: args.xml_infer_types,
+            xml_types: args.xml_types,
+            xml_whitespace: args.xml_whitespace,
+            yaml_duplicate_keys: args.yaml_duplicate_keys,
+            yaml_schema: args.yaml_schema,
+            yaml_timestamps: args.yaml_timestamps,
+            toml_strings: args.toml_strings,
+            toml_fold_width: args.toml_fold_width,
+            bytes: args.bytes,
+            float_precision: args.float_precision,
+            exponent_threshold: args.exponent_threshold,
+            integral_floats: args.integral_floats,
+            pretty: args.pretty,
+            output_trailing_commas: args.output_trailing_commas,
+            unquoted_keys: args.unquoted_keys,
+            json_quote: args.json_quote,
+            multiline_strings: args.multiline_strings,
+            xml_quote: args.xml_quote,
+            xml_ascii: args.xml_ascii,
+            yaml_indent: args.yaml_indent,
+            yaml_sequences: args.yaml_sequences,
+            yaml_strings: args.yaml_strings,
+            local_offset: args.local_offset,
+            utc_datetimes: args.utc_datetimes,
             json_comments: args.json_comments,
             json_trailing_commas: args.json_trailing_commas,
             csv_delimiter: args.csv_delimiter,
+            jobs: 1,
         };
         return run_convert(convert_args);
     }
@@ -193,58 +289,196 @@ fn main() -> Result<()> {
 }
 
 fn run_parse(args: ParseArgs) -> Result<()> {
-    let input_data = read_input(&args.input)?;
-    let (from, is_jsonc) = resolve_format(args.from, &args.input)?;
+    if args.inputs.len() > 1 {
+        return run_parse_batch(&args);
+    }
+    let input = args.inputs.first().cloned();
+    let input_data = read_input(&input)?;
+    let (from, warnings) = check_input(&args, &input, &input_data)?;
+    for warning in &warnings {
+        eprintln!("{warning}");
+    }
+
+    if args.print_output {
+        write_document(&args.output, &input_data, from)
+    } else {
+        write_output(&args.output, b"ok\n")
+    }
+}
+
+/// Check several inputs, up to `--jobs` at once, reporting each in the order
+/// given and failing at the end if any of them did.
+fn run_parse_batch(args: &ParseArgs) -> Result<()> {
+    if args.print_output {
+        bail!("--print-output requires a single input");
+    }
+    let results = parallel_map(&args.inputs, args.jobs, |path| {
+        let input = Some(path.clone());
+        let input_data = read_input(&input)?;
+        check_input(args, &input, &input_data).map(|(_, warnings)| warnings)
+    });
+
+    let mut report = String::new();
+    let mut failed = 0_usize;
+    for (path, result) in args.inputs.iter().zip(results) {
+        match result {
+            Ok(warnings) => {
+                for warning in &warnings {
+                    eprintln!("{}: {warning}", path.display());
+                }
+                report.push_str(&format!("{}: ok\n", path.display()));
+            }
+            Err(err) => {
+                failed += 1;
+                eprintln!(
+                    "{}",
+                    color::report(&err.context(path.display().to_string()))
+                );
+            }
+        }
+    }
+    write_output(&args.output, report.as_bytes())?;
+    if failed > 0 {
+        bail!("{failed} of {} inputs failed", args.inputs.len());
+    }
+    Ok(())
+}
+
+/// Validate one input, returning its format and any warnings.
+fn check_input(
+    args: &ParseArgs,
+    input: &Option<PathBuf>,
+    input_data: &str,
+) -> Result<(zparse::Format, Vec<zparse::Warning>)> {
+    let (from, is_jsonc) = resolve_format(args.from.clone(), input, Some(input_data))?;
     let json_config =
         json_config_from_flags(is_jsonc, args.json_comments, args.json_trailing_commas);
 
-    match from {
+    let at_source = |err| color::source_error(err, input_data, input.as_deref());
+    let warnings = match from {
         zparse::Format::Json => {
             let mut parser = zparse::json::Parser::with_config(input_data.as_bytes(), json_config);
-            parser.parse_value()?;
+            parser.validate().map_err(at_source)?;
+            parser.take_warnings()
         }
         zparse::Format::Csv => {
             let config = csv_config_from_flags(args.csv_delimiter)?;
             let mut parser = zparse::csv::Parser::with_config(input_data.as_bytes(), config);
-            parser.parse()?;
+            parser.validate().map_err(at_source)?;
+            Vec::new()
         }
         zparse::Format::Toml => {
             let mut parser = zparse::toml::Parser::new(input_data.as_bytes());
-            parser.parse()?;
+            parser.validate().map_err(at_source)?;
+            parser.take_warnings()
         }
+        #[cfg(feature = "yaml")]
         zparse::Format::Yaml => {
             let mut parser = zparse::yaml::Parser::new(input_data.as_bytes());
-            parser.parse()?;
+            parser.validate().map_err(at_source)?;
+            parser.take_warnings()
         }
+        #[cfg(feature = "xml")]
         zparse::Format::Xml => {
             let mut parser = zparse::xml::Parser::new(input_data.as_bytes());
-            parser.parse()?;
+            parser.validate().map_err(at_source)?;
+            Vec::new()
         }
-    }
+        #[allow(unreachable_patterns)]
+        format => {
+            zparse::validate(input_data, format, &zparse::ConvertOptions::default())?;
+            Vec::new()
+        }
+    };
+    Ok((from, warnings))
+}
 
-    if args.print_output {
-        write_output(&args.output, input_data.as_bytes())?;
-    } else {
-        write_output(&args.output, b"ok\n")?;
+/// Run `f` on each item using up to `jobs` threads (0 for one per CPU),
+/// returning the results in the order of `items`.
+fn parallel_map<T: Sync, R: Send>(items: &[T], jobs: usize, f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let jobs = match jobs {
+        0 => std::thread::available_parallelism().map_or(1, usize::from),
+        jobs => jobs,
     }
-    Ok(())
+    .min(items.len());
+    if jobs <= 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let (f, next) = (&f, &next);
+    let mut results: Vec<(usize, R)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs)
+            .map(|_| {
+                scope.spawn(move || {
+                    let mut done = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(index) else {
+                            break done;
+                        };
+                        done.push((index, f(item)));
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    });
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
 }
 
 fn run_convert(args: ConvertArgs) -> Result<()> {
-    let input_data = read_input(&args.input)?;
-    let (from, is_jsonc) = resolve_format(args.from, &args.input)?;
-    let json_config =
-        json_config_from_flags(is_jsonc, args.json_comments, args.json_trailing_commas);
-    let csv_config = csv_config_from_flags(args.csv_delimiter)?;
-    let options = zparse::ConvertOptions {
-        json: json_config,
-        csv: csv_config,
-    };
-    let to = args.to.into();
-    let output = zparse::convert_with_options(&input_data, from, to, &options)?;
+    if args.in_place
+        && (args.inputs.len() != 1
+            || args
+                .inputs
+                .iter()
+                .any(|path| zparse::source::find(path).is_some()))
+    {
+        bail!("--in-place requires exactly one input file");
+    }
+    if args.resolve_includes && args.inputs.is_empty() {
+        bail!("--resolve-includes requires an input file");
+    }
 
-    if args.print_output {
-        write_output(&args.output, output.as_bytes())?;
+    let to = args.to.clone().into();
+    let registry_input = args
+        .inputs
+        .first()
+        .is_some_and(|path| registry_reader(args.from.as_ref(), path).is_some());
+    let output =
+        if args.inputs.len() > 1 || args.merge.is_some() || has_transforms(&args) || registry_input
+        {
+            convert_documents(&args, to)?
+        } else {
+            let input = args.inputs.first().cloned();
+            let input_data = read_input(&input)?;
+            let (from, is_jsonc) = resolve_format(args.from.clone(), &input, Some(&input_data))?;
+            let options = convert_options_from_flags(&args, is_jsonc)?;
+            warn_duplicate_keys(&input, &input_data, from, &options);
+            zparse::convert_with_options(&input_data, from, to, &options)
+                .map_err(|err| color::source_error(err, &input_data, input.as_deref()))?
+        };
+
+    if args.in_place
+        && let Some(path) = args.inputs.first()
+    {
+        let output = zparse::compress::encode(path, output.as_bytes())?;
+        zparse::ops::write_atomic(path, &output, args.backup.as_deref())?;
+    }
+
+    // Piped stdin with no output file goes straight to stdout, so
+    // `curl ... | zparse convert --to yaml` needs no extra flags.
+    if args.print_output || (args.inputs.is_empty() && args.output.is_none()) {
+        write_document(&args.output, &output, to)?;
     } else {
         if let Some(path) = &args.output {
             write_output(&Some(path.clone()), output.as_bytes())?;
@@ -258,27 +492,855 @@ fn run_convert(args: ConvertArgs) -> Result<()> {
     Ok(())
 }
 
+/// Parse every input, up to `--jobs` at once, then merge them or collect
+/// them into an array in the order given.
+///
+/// A single input without `--merge` is emitted as-is. When several inputs
+/// fail, each error is printed before giving up.
+fn convert_documents(args: &ConvertArgs, to: zparse::Format) -> Result<String> {
+    let inputs: Vec<Option<PathBuf>> = if args.inputs.is_empty() {
+        vec![None]
+    } else {
+        args.inputs.iter().cloned().map(Some).collect()
+    };
+
+    let results = parallel_map(&inputs, args.jobs, |input| load_document(args, input));
+    let mut documents = Vec::with_capacity(inputs.len());
+    let mut errors = Vec::new();
+    for (input, result) in inputs.iter().zip(results) {
+        match result {
+            Ok(value) => documents.push(value),
+            Err(err) => errors.push((input, err)),
+        }
+    }
+    let failed = errors.len();
+    if failed > 1 {
+        for (input, err) in errors {
+            let path = input.as_deref().unwrap_or(Path::new("stdin"));
+            eprintln!(
+                "{}",
+                color::report(&err.context(path.display().to_string()))
+            );
+        }
+        bail!("{failed} of {} inputs failed", inputs.len());
+    }
+    if let Some((_, err)) = errors.pop() {
+        return Err(err);
+    }
+
+    let mut value = match args.merge {
+        Some(strategy) => zparse::merge_all(documents, strategy.into()),
+        None if documents.len() == 1 => documents.pop().unwrap_or(zparse::Value::Null),
+        None => zparse::Value::from(documents),
+    };
+    if args.redact || !args.redact_keys.is_empty() {
+        let redactor = args
+            .redact_keys
+            .iter()
+            .fold(zparse::Redactor::default(), |redactor, key| {
+                redactor.with_pattern(key)
+            });
+        redactor.redact(&mut value);
+    }
+    if let Some(case) = args.rename_keys {
+        value.rename_keys(case.into());
+    }
+    if let Some(field) = &args.sort_arrays_by {
+        value.sort_arrays_by(field);
+    }
+    if args.sort_keys {
+        value.sort_keys();
+    }
+    Ok(zparse::serialize_with_options(
+        &value,
+        to,
+        &format_options_from_flags(args)?,
+    )?)
+}
+
+/// Read and parse one input of `convert`, applying `--interpolate-env`.
+fn load_document(args: &ConvertArgs, input: &Option<PathBuf>) -> Result<zparse::Value> {
+    let describe = || match input {
+        Some(path) => path.display().to_string(),
+        None => "stdin".to_string(),
+    };
+    let mut value = match input {
+        Some(path) if let Some(reader) = registry_reader(args.from.as_ref(), path) => {
+            let data = zparse::source::read(path)
+                .with_context(|| format!("failed to read input file {}", path.display()))?;
+            reader
+                .parse_bytes(&data, &convert_options_from_flags(args, false)?)
+                .with_context(|| format!("failed to parse {}", describe()))?
+        }
+        Some(path) if args.resolve_includes => {
+            let (_, is_jsonc) = resolve_format(args.from.clone(), input, None)?;
+            load_with_includes(path, convert_options_from_flags(args, is_jsonc)?)?
+        }
+        _ => {
+            let input_data = read_input(input)?;
+            let (from, is_jsonc) = resolve_format(args.from.clone(), input, Some(&input_data))?;
+            let options = convert_options_from_flags(args, is_jsonc)?;
+            warn_duplicate_keys(input, &input_data, from, &options);
+            zparse::parse_with_options(&input_data, from, &options)
+                .map_err(|err| color::source_error(err, &input_data, input.as_deref()))?
+        }
+    };
+    if args.interpolate_env {
+        value
+            .interpolate_env()
+            .with_context(|| format!("failed to interpolate {}", describe()))?;
+    }
+    Ok(value)
+}
+
+/// Print the repeated keys a lenient `--yaml-duplicate-keys` lets through.
+/// Conversion does not return warnings, so the input is checked separately,
+/// and only when the policy asks for warnings.
+#[cfg_attr(not(feature = "yaml"), allow(unused_variables))]
+fn warn_duplicate_keys(
+    input: &Option<PathBuf>,
+    input_data: &str,
+    from: zparse::Format,
+    options: &zparse::ConvertOptions,
+) {
+    #[cfg(feature = "yaml")]
+    if from == zparse::Format::Yaml
+        && options.yaml.duplicate_keys != zparse::YamlDuplicateKeys::Error
+    {
+        let mut parser = zparse::YamlParser::with_config(input_data.as_bytes(), options.yaml);
+        if parser.validate().is_ok() {
+            for warning in parser.take_warnings() {
+                match input {
+                    Some(path) => eprintln!("{}: {warning}", path.display()),
+                    None => eprintln!("{warning}"),
+                }
+            }
+        }
+    }
+}
+
+/// The registry format reading `path` when `--from` is not given and the
+/// extension belongs to a format outside [`zparse::Format`], like `.plist`.
+fn registry_reader(
+    from: Option<&FormatArg>,
+    path: &Path,
+) -> Option<Arc<dyn zparse::FormatProvider>> {
+    if from.is_some() {
+        return None;
+    }
+    zparse::registry::global()
+        .for_path(path)
+        .filter(|provider| provider.builtin().is_none())
+        .cloned()
+}
+
+/// Whether any flag requires parsing inputs into values before serializing.
+fn has_transforms(args: &ConvertArgs) -> bool {
+    args.interpolate_env
+        || args.resolve_includes
+        || args.redact
+        || !args.redact_keys.is_empty()
+        || args.rename_keys.is_some()
+        || args.sort_keys
+        || args.sort_arrays_by.is_some()
+}
+
+/// Load `path` with includes resolved, sandboxed to its parent directory.
+fn load_with_includes(path: &Path, options: zparse::ConvertOptions) -> Result<zparse::Value> {
+    let root = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut resolver = zparse::IncludeResolver::new(root)?.with_options(options);
+    resolver
+        .load(path)
+        .with_context(|| format!("failed to resolve includes in {}", path.display()))
+}
+
+fn convert_options_from_flags(
+    args: &ConvertArgs,
+    is_jsonc: bool,
+) -> Result<zparse::ConvertOptions> {
+    Ok(zparse::ConvertOptions {
+        json: json_config_from_flags(is_jsonc, args.json_comments, args.json_trailing_commas),
+        csv: csv_config_from_flags(args.csv_delimiter)?,
+        #[cfg(feature = "xml")]
+        xml: zparse::XmlConfig::default().with_whitespace(args.xml_whitespace.into()),
+        #[cfg(feature = "yaml")]
+        yaml: zparse::YamlConfig::default()
+            .with_duplicate_keys(args.yaml_duplicate_keys.into())
+            .with_schema(args.yaml_schema.into())
+            .with_timestamps(args.yaml_timestamps),
+        toml_compat: zparse::TomlCompat {
+            null: args.toml_null.into(),
+            homogeneous_arrays: args.toml_homogeneous_arrays,
+            root_key: args.toml_root_key.clone(),
+        },
+        #[cfg(feature = "xml")]
+        xml_types: match &args.xml_types {
+            Some(spec) => spec.parse().context("invalid --xml-types")?,
+            None => zparse::XmlTypes::new(),
+        }
+        .with_infer(args.xml_infer_types),
+        format: format_options_from_flags(args)?,
+        ..Default::default()
+    })
+}
+
+fn format_options_from_flags(args: &ConvertArgs) -> Result<zparse::FormatOptions> {
+    Ok(zparse::FormatOptions::new()
+        .with_toml_strings(args.toml_strings.into())
+        .with_toml_fold_width(args.toml_fold_width.unwrap_or(0))
+        .with_bytes(args.bytes.into())
+        .with_float_precision(args.float_precision)
+        .with_exponent_threshold(args.exponent_threshold)
+        .with_integral_floats(args.integral_floats.into())
+        .with_pretty(
+            args.pretty || (args.output.is_none() && !args.in_place && io::stdout().is_terminal()),
+        )
+        .with_json_dialect(match args.to {
+            OutputFormatArg::Jsonc => zparse::JsonDialect::Jsonc,
+            OutputFormatArg::Json5 => zparse::JsonDialect::Json5,
+            _ => zparse::JsonDialect::Json,
+        })
+        .with_json_trailing_commas(args.output_trailing_commas)
+        .with_json_unquoted_keys(args.unquoted_keys)
+        .with_json_quote(args.json_quote.into())
+        .with_json_multiline_strings(args.multiline_strings)
+        .with_xml_quote(args.xml_quote.into())
+        .with_xml_ascii(args.xml_ascii)
+        .with_yaml_indent(args.yaml_indent)
+        .with_yaml_sequences(args.yaml_sequences.map(Into::into))
+        .with_yaml_strings(args.yaml_strings.into())
+        .with_datetime_local_offset(utc_offset_from_flag(args.local_offset.as_deref())?)
+        .with_datetime_utc(args.utc_datetimes))
+}
+
+fn run_completions(args: CompletionsArgs) -> Result<()> {
+    let mut command = Args::command();
+    let name = command.get_name().to_string();
+    let mut script = Vec::new();
+    clap_complete::generate(args.shell, &mut command, name, &mut script);
+    write_output(&None, &script)
+}
+
+fn run_schema(args: SchemaArgs) -> Result<()> {
+    match args.command {
+        SchemaCommand::Infer(infer_args) => run_schema_infer(infer_args),
+    }
+}
+
+fn run_schema_infer(args: SchemaInferArgs) -> Result<()> {
+    let samples = load_samples(&args.samples)?;
+    let schema = zparse::schema::infer(&samples);
+    let output = zparse::serialize(&schema, zparse::Format::Json)?;
+    write_document(&args.output, &output, zparse::Format::Json)
+}
+
+fn run_codegen(args: CodegenArgs) -> Result<()> {
+    let samples = load_samples(&args.samples)?;
+    let code = if args.schema {
+        let [schema] = samples.as_slice() else {
+            bail!("--schema expects exactly one schema document");
+        };
+        zparse::codegen::rust_from_schema(schema, &args.name)
+    } else {
+        zparse::codegen::rust_from_samples(&samples, &args.name)
+    };
+    write_output(&args.output, code.as_bytes())
+}
+
+fn run_compare(args: CompareArgs) -> Result<()> {
+    let load = |path: &PathBuf| -> Result<zparse::Value> {
+        let input = Some(path.clone());
+        let input_data = read_input(&input)?;
+        let (from, is_jsonc) = resolve_format(args.from.clone(), &input, Some(&input_data))?;
+        let options = zparse::ConvertOptions {
+            json: json_config_from_flags(is_jsonc, false, false),
+            ..Default::default()
+        };
+        zparse::parse_with_options(&input_data, from, &options)
+            .with_context(|| format!("failed to parse {}", path.display()))
+    };
+    let left = load(&args.left)?;
+    let right = load(&args.right)?;
+
+    let options = zparse::CompareOptions::new()
+        .with_epsilon(args.epsilon)
+        .with_ignore_key_order(!args.strict_key_order)
+        .with_null_equals_missing(args.null_equals_missing)
+        .with_ignore_array_order(args.ignore_array_order)
+        .with_coerce_scalars(args.coerce_scalars);
+    match zparse::values_diff_first_mismatch_with(&left, &right, &options) {
+        None => write_output(&None, b"ok\n"),
+        Some(mismatch) => {
+            write_output(&None, format!("{mismatch}\n").as_bytes())?;
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run_audit(args: AuditArgs) -> Result<()> {
+    let report = zparse::audit::audit_dir(&args.dir, &zparse::ConvertOptions::default())
+        .with_context(|| format!("failed to audit {}", args.dir.display()))?;
+    let output = match args.to {
+        None => format!("{report}\n"),
+        Some(to) => {
+            let format = zparse::FormatOptions::new()
+                .with_pretty(args.output.is_none() && io::stdout().is_terminal())
+                .with_json_dialect(match to {
+                    OutputFormatArg::Jsonc => zparse::JsonDialect::Jsonc,
+                    OutputFormatArg::Json5 => zparse::JsonDialect::Json5,
+                    _ => zparse::JsonDialect::Json,
+                });
+            let mut output =
+                zparse::serialize_with_options(&report.to_value(), to.into(), &format)?;
+            output.push('\n');
+            output
+        }
+    };
+    write_output(&args.output, output.as_bytes())?;
+    if !report.is_clean() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn run_fmt(args: FmtArgs) -> Result<()> {
+    let options = zparse::ConvertOptions {
+        json: json_config_from_flags(false, args.json_comments, args.json_trailing_commas),
+        format: zparse::FormatOptions::new()
+            .with_pretty(true)
+            .with_yaml_indent(args.yaml_indent)
+            .with_yaml_sequences(args.yaml_sequences.map(Into::into))
+            .with_yaml_strings(args.yaml_strings.into()),
+        ..Default::default()
+    };
+    let formatted = zparse::ops::format_file(&args.input, &options)
+        .with_context(|| format!("failed to format {}", args.input.display()))?;
+    let formatted = format!("{formatted}\n");
+    if args.in_place {
+        let formatted = zparse::compress::encode(&args.input, formatted.as_bytes())?;
+        zparse::ops::write_atomic(&args.input, &formatted, args.backup.as_deref())?;
+        return write_output(&None, b"ok\n");
+    }
+    match zparse::detect_format_from_path(&args.input) {
+        Some(format) => write_document(&args.output, &formatted, format),
+        None => write_output(&args.output, formatted.as_bytes()),
+    }
+}
+
+/// Print the canonical text of a file for `git diff`. A file that doesn't
+/// parse (say, mid-merge) is printed as is, so the diff still shows it.
+fn run_textconv(args: TextconvArgs) -> Result<()> {
+    match zparse::ops::textconv_file(&args.input, &zparse::ConvertOptions::default()) {
+        Ok(text) => write_output(&None, text.as_bytes()),
+        Err(err) => {
+            eprintln!("warning: {err}; showing {} unchanged", args.input.display());
+            let raw = std::fs::read(&args.input)
+                .with_context(|| format!("failed to read input file {}", args.input.display()))?;
+            write_output(&None, &raw)
+        }
+    }
+}
+
+fn run_render(args: RenderArgs) -> Result<()> {
+    let template = std::fs::read_to_string(&args.template)
+        .with_context(|| format!("failed to read template {}", args.template.display()))?;
+    let template = zparse::template::Template::parse(&template)
+        .with_context(|| format!("invalid template {}", args.template.display()))?;
+    let input = normalize_flag_input(args.input);
+    let input_data = read_input(&input)?;
+    let (from, is_jsonc) = resolve_format(args.from, &input, Some(&input_data))?;
+    let options = zparse::ConvertOptions {
+        json: json_config_from_flags(is_jsonc, false, false),
+        ..Default::default()
+    };
+    let value = zparse::parse_with_options(&input_data, from, &options)?;
+    let rendered = template
+        .render(&value)
+        .with_context(|| format!("failed to render {}", args.template.display()))?;
+    write_output(&args.output, rendered.as_bytes())
+}
+
+fn run_split(args: SplitArgs) -> Result<()> {
+    let input = normalize_flag_input(args.input);
+    let mut sink = if args.ndjson {
+        SplitSink::lines(args.output)?
+    } else {
+        let Some(out_dir) = args.out_dir else {
+            bail!("--out-dir or --ndjson is required");
+        };
+        std::fs::create_dir_all(&out_dir)
+            .with_context(|| format!("failed to create directory {}", out_dir.display()))?;
+        SplitSink::Files {
+            dir: out_dir,
+            to: zparse::Format::from(args.format),
+            index: 0,
+        }
+    };
+    let each = |item: zparse::Value| sink.write(&item);
+
+    match json_reader(args.from.clone(), &input)? {
+        Some((reader, is_jsonc)) => {
+            let config = json_config_from_flags(is_jsonc, false, false);
+            zparse::split::for_each_json_element_from_reader(reader, config, &args.path, each)?;
+        }
+        None => {
+            let input_data = read_input(&input)?;
+            let (from, is_jsonc) = resolve_format(args.from, &input, Some(&input_data))?;
+            let options = zparse::ConvertOptions {
+                json: json_config_from_flags(is_jsonc, false, false),
+                ..Default::default()
+            };
+            zparse::split::for_each_element(&input_data, from, &options, &args.path, each)?;
+        }
+    }
+    sink.finish()
+}
+
+/// A reader over `input` when `--from` or its extension says it is JSON, so
+/// `split` can stream it rather than load it whole. Compressed input is
+/// decompressed in memory first.
+fn json_reader(
+    from: Option<FormatArg>,
+    input: &Option<PathBuf>,
+) -> Result<Option<(Box<dyn Read>, bool)>> {
+    let Ok((zparse::Format::Json, is_jsonc)) = resolve_format(from, input, None) else {
+        return Ok(None);
+    };
+    let describe = || match input {
+        Some(path) => format!("failed to read input file {}", path.display()),
+        None => "failed to read stdin".to_string(),
+    };
+    let reader: Box<dyn Read> = match input {
+        Some(path) => zparse::source::open(path).with_context(describe)?,
+        None => Box::new(io::stdin()),
+    };
+    let mut reader = io::BufReader::new(reader);
+    let head = reader.fill_buf().with_context(describe)?;
+    let compression = input
+        .as_deref()
+        .and_then(zparse::Compression::from_path)
+        .or_else(|| zparse::Compression::detect(head));
+    if let Some(compression) = compression {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).with_context(describe)?;
+        let bytes = compression.decompress(&bytes).with_context(describe)?;
+        return Ok(Some((Box::new(io::Cursor::new(bytes)), is_jsonc)));
+    }
+    Ok(Some((Box::new(reader), is_jsonc)))
+}
+
+/// Where `split` writes items as they are read
+enum SplitSink {
+    /// One pretty-printed file per item in `dir`
+    Files {
+        dir: PathBuf,
+        to: zparse::Format,
+        index: usize,
+    },
+    /// NDJSON written line by line to the named output
+    Lines(io::BufWriter<Box<dyn Write>>, PathBuf),
+    /// NDJSON for a compressed output file, written whole at the end
+    Compressed { path: PathBuf, lines: String },
+}
+
+impl SplitSink {
+    fn lines(output: Option<PathBuf>) -> Result<Self> {
+        let (writer, name): (Box<dyn Write>, _) = match output {
+            Some(path) if zparse::Compression::from_path(&path).is_some() => {
+                return Ok(Self::Compressed {
+                    path,
+                    lines: String::new(),
+                });
+            }
+            Some(path) => {
+                let file = std::fs::File::create(&path)
+                    .with_context(|| format!("failed to write output file {}", path.display()))?;
+                (Box::new(file), path)
+            }
+            None => (Box::new(io::stdout()), PathBuf::from("<stdout>")),
+        };
+        Ok(Self::Lines(io::BufWriter::new(writer), name))
+    }
+
+    fn write(&mut self, item: &zparse::Value) -> zparse::Result<()> {
+        match self {
+            Self::Files { dir, to, index } => {
+                let format = zparse::FormatOptions::new().with_pretty(true);
+                let mut text = zparse::serialize_with_options(item, *to, &format)?;
+                text.push('\n');
+                let path = dir.join(format!("{index:06}.{}", extension(*to)));
+                *index = index.saturating_add(1);
+                std::fs::write(&path, text).map_err(|err| write_error(&path, &err))
+            }
+            Self::Lines(writer, name) => {
+                let line = zparse::serialize(item, zparse::Format::Json)?;
+                writer
+                    .write_all(line.as_bytes())
+                    .and_then(|()| writer.write_all(b"\n"))
+                    .map_err(|err| write_error(name, &err))
+            }
+            Self::Compressed { lines, .. } => {
+                lines.push_str(&zparse::serialize(item, zparse::Format::Json)?);
+                lines.push('\n');
+                Ok(())
+            }
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            Self::Files { .. } => write_output(&None, b"ok\n"),
+            Self::Lines(mut writer, name) => writer
+                .flush()
+                .with_context(|| format!("failed to write output file {}", name.display())),
+            Self::Compressed { path, lines } => write_output(&Some(path), lines.as_bytes()),
+        }
+    }
+}
+
+fn write_error(path: &Path, err: &io::Error) -> zparse::Error {
+    zparse::Error::with_message(
+        zparse::ErrorKind::Io {
+            path: path.display().to_string(),
+        },
+        zparse::Span::empty(),
+        format!("failed to write output file {}: {err}", path.display()),
+    )
+}
+
+fn run_peek(args: PeekArgs) -> Result<()> {
+    let pick = match (args.head, args.tail, args.sample) {
+        (Some(count), _, _) => zparse::split::Pick::Head(count),
+        (_, Some(count), _) => zparse::split::Pick::Tail(count),
+        (_, _, Some(count)) => zparse::split::Pick::Sample {
+            count,
+            seed: args.seed,
+        },
+        _ => bail!("one of --head, --tail or --sample is required"),
+    };
+    let input = normalize_flag_input(args.input);
+    let input_data = read_input(&input)?;
+    let ndjson = args.ndjson
+        || input.as_deref().is_some_and(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| matches!(ext.to_ascii_lowercase().as_str(), "ndjson" | "jsonl"))
+        });
+
+    if ndjson {
+        let items = zparse::split::pick_ndjson(&input_data, zparse::JsonConfig::default(), pick)?;
+        let mut lines = String::new();
+        for item in &items {
+            lines.push_str(&zparse::serialize(item, zparse::Format::Json)?);
+            lines.push('\n');
+        }
+        return write_output(&args.output, lines.as_bytes());
+    }
+
+    let (from, is_jsonc) = resolve_format(args.from, &input, Some(&input_data))?;
+    let options = zparse::ConvertOptions {
+        json: json_config_from_flags(is_jsonc, false, false),
+        ..Default::default()
+    };
+    let items = zparse::split::pick(&input_data, from, &options, &args.path, pick)?;
+    let format = zparse::FormatOptions::new()
+        .with_pretty(args.output.is_none() && io::stdout().is_terminal())
+        .with_json_dialect(match args.to {
+            OutputFormatArg::Jsonc => zparse::JsonDialect::Jsonc,
+            OutputFormatArg::Json5 => zparse::JsonDialect::Json5,
+            _ => zparse::JsonDialect::Json,
+        });
+    let to = args.to.into();
+    let mut output = zparse::serialize_with_options(&zparse::Value::from(items), to, &format)?;
+    output.push('\n');
+    write_document(&args.output, &output, to)
+}
+
+fn run_stats(args: StatsArgs) -> Result<()> {
+    let input = normalize_flag_input(args.input);
+    let input_data = read_input(&input)?;
+    let (from, is_jsonc) = resolve_format(args.from, &input, Some(&input_data))?;
+    let options = zparse::ConvertOptions {
+        json: json_config_from_flags(is_jsonc, false, false),
+        ..Default::default()
+    };
+    let value = zparse::parse_with_options(&input_data, from, &options)
+        .map_err(|err| color::source_error(err, &input_data, input.as_deref()))?;
+    let mut stats = zparse::Stats {
+        input_bytes: Some(input_data.len()),
+        ..value.stats()
+    };
+    let top: Vec<String> = stats
+        .top_keys(args.top_keys)
+        .into_iter()
+        .map(|(key, _)| key.to_string())
+        .collect();
+    stats.keys.retain(|key, _| top.contains(key));
+
+    let Some(to) = args.to else {
+        return write_output(&args.output, stats.to_string().as_bytes());
+    };
+    let format = zparse::FormatOptions::new()
+        .with_pretty(args.output.is_none() && io::stdout().is_terminal())
+        .with_json_dialect(match to {
+            OutputFormatArg::Jsonc => zparse::JsonDialect::Jsonc,
+            OutputFormatArg::Json5 => zparse::JsonDialect::Json5,
+            _ => zparse::JsonDialect::Json,
+        });
+    let to = to.into();
+    let mut output = zparse::serialize_with_options(&stats.to_value(), to, &format)?;
+    output.push('\n');
+    write_document(&args.output, &output, to)
+}
+
+#[cfg(feature = "explore")]
+fn run_explore(args: ExploreArgs) -> Result<()> {
+    if !io::stdout().is_terminal() {
+        bail!("explore needs a terminal on stdout");
+    }
+    let input = normalize_flag_input(args.input);
+    let input_data = read_input(&input)?;
+    let (from, is_jsonc) = resolve_format(args.from, &input, Some(&input_data))?;
+    let options = zparse::ConvertOptions {
+        json: json_config_from_flags(is_jsonc, false, false),
+        ..Default::default()
+    };
+    let value = zparse::parse_with_options(&input_data, from, &options)
+        .map_err(|err| color::source_error(err, &input_data, input.as_deref()))?;
+    let title = input
+        .as_deref()
+        .map_or_else(|| "<stdin>".to_string(), |path| path.display().to_string());
+    explore::run(&value, title)
+}
+
+#[cfg(not(feature = "explore"))]
+fn run_explore(_args: ExploreArgs) -> Result<()> {
+    bail!("zparse was built without the `explore` feature")
+}
+
+fn run_query(args: QueryArgs) -> Result<()> {
+    let query = zparse::query::Query::parse(&args.expr).context("invalid expression")?;
+    let input = normalize_flag_input(args.input);
+    let input_data = read_input(&input)?;
+    let (from, is_jsonc) = resolve_format(args.from, &input, Some(&input_data))?;
+    let options = zparse::ConvertOptions {
+        json: json_config_from_flags(is_jsonc, false, false),
+        ..Default::default()
+    };
+    let value = zparse::parse_with_options(&input_data, from, &options)
+        .map_err(|err| color::source_error(err, &input_data, input.as_deref()))?;
+    let results = query.eval(&value)?;
+    let format = zparse::FormatOptions::new()
+        .with_pretty(!args.compact && args.output.is_none() && io::stdout().is_terminal())
+        .with_json_dialect(match args.to {
+            OutputFormatArg::Jsonc => zparse::JsonDialect::Jsonc,
+            OutputFormatArg::Json5 => zparse::JsonDialect::Json5,
+            _ => zparse::JsonDialect::Json,
+        });
+    let to = args.to.into();
+    let mut output = String::new();
+    for result in &results {
+        match result {
+            zparse::Value::String(text) if args.raw_output => output.push_str(text),
+            _ => output.push_str(&zparse::serialize_with_options(result, to, &format)?),
+        }
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+    }
+    write_document(&args.output, &output, to)
+}
+
+fn run_testgen(args: TestgenArgs) -> Result<()> {
+    let mix = match &args.mix {
+        Some(mix) => mix.parse().context("invalid --mix")?,
+        None => zparse::testgen::Mix::new(),
+    };
+    let options = zparse::testgen::Options::new()
+        .with_seed(args.seed)
+        .with_size(parse_size(&args.size)?)
+        .with_max_depth(args.depth)
+        .with_max_width(args.width)
+        .with_mix(mix);
+    let format = zparse::FormatOptions::new()
+        .with_pretty(args.output.is_none() && io::stdout().is_terminal())
+        .with_json_dialect(match args.to {
+            OutputFormatArg::Jsonc => zparse::JsonDialect::Jsonc,
+            OutputFormatArg::Json5 => zparse::JsonDialect::Json5,
+            _ => zparse::JsonDialect::Json,
+        });
+    let to = args.to.into();
+    let value = zparse::testgen::generate_for(to, &options);
+    let mut output = zparse::serialize_with_options(&value, to, &format)?;
+    if !output.ends_with('\n') {
+        output.push('\n');
+    }
+    write_output(&args.output, output.as_bytes())
+}
+
+/// Read a byte count such as `4096`, `512k`, `10MB` or `1GiB` (powers of 1024)
+fn parse_size(text: &str) -> Result<usize> {
+    let text = text.trim();
+    let split = text
+        .find(|ch: char| !ch.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (digits, unit) = text.split_at(split);
+    let shift = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 0,
+        "k" | "kb" | "kib" => 10,
+        "m" | "mb" | "mib" => 20,
+        "g" | "gb" | "gib" => 30,
+        _ => bail!("invalid size {text:?}: unknown unit {unit:?}"),
+    };
+    let count: usize = digits
+        .parse()
+        .with_context(|| format!("invalid size {text:?}"))?;
+    count
+        .checked_mul(1_usize.checked_shl(shift).unwrap_or(usize::MAX))
+        .with_context(|| format!("size {text:?} is too large"))
+}
+
+fn run_table(args: TableArgs) -> Result<()> {
+    let columns = zparse::table::columns(&args.columns);
+    if columns.is_empty() {
+        bail!("--columns needs at least one column");
+    }
+    let input = normalize_flag_input(args.input);
+    let input_data = read_input(&input)?;
+    let (from, is_jsonc) = resolve_format(args.from, &input, Some(&input_data))?;
+    let options = zparse::ConvertOptions {
+        json: json_config_from_flags(is_jsonc, false, false),
+        ..Default::default()
+    };
+    let value = zparse::parse_with_options(&input_data, from, &options)?;
+    let records = zparse::table::Column::parse(&args.path)
+        .get(&value)
+        .with_context(|| format!("no value at {:?}", args.path))?;
+    let rows = zparse::table::extract(records, &columns)?;
+    let format = zparse::FormatOptions::new()
+        .with_pretty(args.output.is_none() && io::stdout().is_terminal())
+        .with_json_dialect(match args.to {
+            OutputFormatArg::Jsonc => zparse::JsonDialect::Jsonc,
+            OutputFormatArg::Json5 => zparse::JsonDialect::Json5,
+            _ => zparse::JsonDialect::Json,
+        });
+    let mut output = zparse::serialize_with_options(&rows, args.to.into(), &format)?;
+    if !output.is_empty() && !output.ends_with('\n') {
+        output.push('\n');
+    }
+    write_output(&args.output, output.as_bytes())
+}
+
+fn run_formats() -> Result<()> {
+    let registry = zparse::registry::global();
+    let mut listing = String::new();
+    for name in registry.names() {
+        let Some(provider) = registry.get(name) else {
+            continue;
+        };
+        let access = if provider.can_serialize() {
+            "read/write"
+        } else {
+            "read"
+        };
+        let extensions = provider
+            .extensions()
+            .iter()
+            .map(|ext| format!(".{ext}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        listing.push_str(&format!("{name:<12}{access:<12}{extensions}\n"));
+    }
+    write_output(&None, listing.as_bytes())
+}
+
+fn extension(format: zparse::Format) -> &'static str {
+    match format {
+        zparse::Format::Json => "json",
+        zparse::Format::Csv => "csv",
+        zparse::Format::Toml => "toml",
+        zparse::Format::Yaml => "yaml",
+        zparse::Format::Xml => "xml",
+    }
+}
+
+/// Parse every sample input, splitting top-level arrays with `--records`.
+fn load_samples(args: &SampleArgs) -> Result<Vec<zparse::Value>> {
+    let inputs: Vec<Option<PathBuf>> = if args.inputs.is_empty() {
+        vec![None]
+    } else {
+        args.inputs.iter().cloned().map(Some).collect()
+    };
+
+    let mut samples = Vec::new();
+    for input in &inputs {
+        let input_data = read_input(input)?;
+        let (from, is_jsonc) = resolve_format(args.from.clone(), input, Some(&input_data))?;
+        let options = zparse::ConvertOptions {
+            json: json_config_from_flags(is_jsonc, args.json_comments, args.json_trailing_commas),
+            csv: csv_config_from_flags(args.csv_delimiter)?,
+            ..Default::default()
+        };
+        let value = zparse::parse_with_options(&input_data, from, &options)?;
+        match value {
+            zparse::Value::Array(records) if args.records => samples.extend(records),
+            other => samples.push(other),
+        }
+    }
+    Ok(samples)
+}
+
+/// Read a file, or stdin when there is none, decompressing gzip and zstd
+/// input by its extension or header.
 fn read_input(path: &Option<PathBuf>) -> Result<String> {
-    match path {
-        Some(path) => std::fs::read_to_string(path)
-            .with_context(|| format!("failed to read input file {}", path.display())),
+    let bytes = match path {
+        Some(path) => zparse::source::read(path)
+            .with_context(|| format!("failed to read input file {}", path.display()))?,
         None => {
-            let mut buffer = String::new();
+            let mut buffer = Vec::new();
             io::stdin()
-                .read_to_string(&mut buffer)
+                .read_to_end(&mut buffer)
                 .context("failed to read stdin")?;
-            if buffer.trim().is_empty() {
+            if buffer.trim_ascii().is_empty() {
                 bail!("no input provided on stdin");
             }
-            Ok(buffer)
+            buffer
         }
+    };
+    let describe = || match path {
+        Some(path) => format!("failed to read input file {}", path.display()),
+        None => "failed to read stdin".to_string(),
+    };
+    let compression = path
+        .as_deref()
+        .and_then(zparse::Compression::from_path)
+        .or_else(|| zparse::Compression::detect(&bytes));
+    let bytes = match compression {
+        Some(compression) => compression.decompress(&bytes).with_context(describe)?,
+        None => bytes,
+    };
+    String::from_utf8(bytes).with_context(describe)
+}
+
+/// Write a document in `format`, colored when it goes to a terminal that
+/// allows color
+fn write_document(path: &Option<PathBuf>, text: &str, format: zparse::Format) -> Result<()> {
+    if path.is_none() && color::stdout() {
+        return write_output(path, color::document(text, format).as_bytes());
     }
+    write_output(path, text.as_bytes())
 }
 
 fn write_output(path: &Option<PathBuf>, data: &[u8]) -> Result<()> {
     match path {
-        Some(path) => std::fs::write(path, data)
-            .with_context(|| format!("failed to write output file {}", path.display())),
+        Some(path) => {
+            let context = || format!("failed to write output file {}", path.display());
+            let data = zparse::compress::encode(path, data).with_context(context)?;
+            std::fs::write(path, data).with_context(context)
+        }
         None => {
             let mut stdout = io::stdout();
             stdout.write_all(data).context("failed to write stdout")?;
@@ -294,30 +1356,25 @@ fn normalize_flag_input(input: Option<PathBuf>) -> Option<PathBuf> {
     }
 }
 
+/// Pick the input format from `--from`, the file extension, or failing both
+/// the content itself (so piped stdin needs no `--from`).
 fn resolve_format(
     from: Option<FormatArg>,
     input: &Option<PathBuf>,
+    content: Option<&str>,
 ) -> Result<(zparse::Format, bool)> {
     let format_arg = from
         .or_else(|| {
-            input.as_ref().and_then(|path| {
-                if path
-                    .extension()
-                    .and_then(|ext| ext.to_str())
-                    .map(|ext| ext.eq_ignore_ascii_case("jsonc"))
-                    .unwrap_or(false)
-                {
-                    Some(FormatArg::Jsonc)
-                } else {
-                    zparse::detect_format_from_path(path).map(|fmt| match fmt {
-                        zparse::Format::Json => FormatArg::Json,
-                        zparse::Format::Csv => FormatArg::Csv,
-                        zparse::Format::Toml => FormatArg::Toml,
-                        zparse::Format::Yaml => FormatArg::Yaml,
-                        zparse::Format::Xml => FormatArg::Xml,
-                    })
-                }
-            })
+            let path = input.as_deref();
+            if path.is_some_and(zparse::ops::is_jsonc_path) {
+                return Some(FormatArg::Jsonc);
+            }
+            content
+                .map_or_else(
+                    || path.and_then(zparse::detect_format_from_path),
+                    |content| zparse::ops::infer_format(path, content),
+                )
+                .map(format_arg)
         })
         .ok_or_else(|| {
             anyhow::anyhow!(
@@ -330,6 +1387,16 @@ fn resolve_format(
     Ok((zparse_format, is_jsonc))
 }
 
+fn format_arg(format: zparse::Format) -> FormatArg {
+    match format {
+        zparse::Format::Json => FormatArg::Json,
+        zparse::Format::Csv => FormatArg::Csv,
+        zparse::Format::Toml => FormatArg::Toml,
+        zparse::Format::Yaml => FormatArg::Yaml,
+        zparse::Format::Xml => FormatArg::Xml,
+    }
+}
+
 fn json_config_from_flags(
     is_jsonc: bool,
     allow_comments: bool,
@@ -346,6 +1413,19 @@ fn json_config_from_flags(
     config
 }
 
+fn utc_offset_from_flag(offset: Option<&str>) -> Result<Option<time::UtcOffset>> {
+    let Some(offset) = offset else {
+        return Ok(None);
+    };
+    if offset.eq_ignore_ascii_case("z") {
+        return Ok(Some(time::UtcOffset::UTC));
+    }
+    let format = time::macros::format_description!("[offset_hour sign:mandatory]:[offset_minute]");
+    time::UtcOffset::parse(offset, &format)
+        .map(Some)
+        .with_context(|| format!("invalid offset {offset:?}, expected Z or +HH:MM"))
+}
+
 fn csv_config_from_flags(delimiter: Option<char>) -> Result<zparse::CsvConfig> {
     match delimiter {
         None => Ok(zparse::CsvConfig::default()),
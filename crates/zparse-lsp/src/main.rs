@@ -0,0 +1,270 @@
+#![forbid(unsafe_code)]
+
+//! Language server for JSON, TOML and YAML
+//!
+//! Speaks LSP over stdio and provides diagnostics from the zparse parsers,
+//! document formatting and an outline of keys and tables. Open documents
+//! are kept as [`zparse::IncrementalDocument`]s, so an edit only reparses the
+//! sections it touches.
+
+mod position;
+mod symbols;
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Result;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+    Notification as LspNotification, PublishDiagnostics,
+};
+use lsp_types::request::{DocumentSymbolRequest, Formatting, Request as LspRequest};
+use lsp_types::{
+    Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, DocumentFormattingParams, DocumentSymbolParams,
+    DocumentSymbolResponse, OneOf, PublishDiagnosticsParams, ServerCapabilities, ServerInfo,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Uri,
+};
+use zparse::{ConvertOptions, Format, IncrementalDocument};
+
+use crate::position::LineIndex;
+
+/// Name reported as the source of diagnostics
+const SOURCE: &str = "zparse";
+
+/// An open text document
+struct Open {
+    doc: IncrementalDocument,
+    version: i32,
+}
+
+fn main() -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    let capabilities = serde_json::to_value(capabilities())?;
+    let initialize = serde_json::json!({
+        "capabilities": capabilities,
+        "serverInfo": ServerInfo {
+            name: "zparse-lsp".to_string(),
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        },
+    });
+    connection.initialize_finish(connection.initialize_start()?.0, initialize)?;
+    serve(&connection)?;
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
+
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(
+            TextDocumentSyncKind::INCREMENTAL,
+        )),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        ..ServerCapabilities::default()
+    }
+}
+
+/// Handle messages until the client shuts the server down
+fn serve(connection: &Connection) -> Result<()> {
+    let mut open: HashMap<String, Open> = HashMap::new();
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                let response = respond(&open, request);
+                connection.sender.send(Message::Response(response))?;
+            }
+            Message::Notification(notification) => {
+                if let Some(params) = notify(&mut open, notification) {
+                    let notification =
+                        Notification::new(PublishDiagnostics::METHOD.to_string(), params);
+                    connection
+                        .sender
+                        .send(Message::Notification(notification))?;
+                }
+            }
+            Message::Response(_) => {}
+        }
+    }
+    Ok(())
+}
+
+fn respond(open: &HashMap<String, Open>, request: Request) -> Response {
+    match request.method.as_str() {
+        Formatting::METHOD => handle::<Formatting, _>(request, |params| format(open, &params)),
+        DocumentSymbolRequest::METHOD => {
+            handle::<DocumentSymbolRequest, _>(request, |params| document_symbols(open, &params))
+        }
+        _ => Response::new_err(
+            request.id,
+            ErrorCode::MethodNotFound as i32,
+            format!("unsupported request {}", request.method),
+        ),
+    }
+}
+
+/// Answer a request of type `R` with the result of `handler`
+fn handle<R, T>(request: Request, handler: impl FnOnce(R::Params) -> T) -> Response
+where
+    R: LspRequest,
+    T: serde::Serialize,
+{
+    let params = match serde_json::from_value::<R::Params>(request.params) {
+        Ok(params) => params,
+        Err(err) => {
+            return Response::new_err(request.id, ErrorCode::InvalidParams as i32, err.to_string());
+        }
+    };
+    match serde_json::to_value(handler(params)) {
+        Ok(result) => Response::new_ok(request.id, result),
+        Err(err) => Response::new_err(request.id, ErrorCode::InternalError as i32, err.to_string()),
+    }
+}
+
+/// Apply a document notification, returning the diagnostics to publish
+fn notify(
+    open: &mut HashMap<String, Open>,
+    notification: Notification,
+) -> Option<PublishDiagnosticsParams> {
+    match notification.method.as_str() {
+        DidOpenTextDocument::METHOD => {
+            let params: DidOpenTextDocumentParams =
+                serde_json::from_value(notification.params).ok()?;
+            let item = params.text_document;
+            let (format, options) = document_format(&item.uri, &item.language_id)?;
+            let doc = IncrementalDocument::with_options(format, item.text, options);
+            let key = item.uri.as_str().to_string();
+            open.insert(
+                key.clone(),
+                Open {
+                    doc,
+                    version: item.version,
+                },
+            );
+            open.get(&key).map(|entry| diagnostics(item.uri, entry))
+        }
+        DidChangeTextDocument::METHOD => {
+            let params: DidChangeTextDocumentParams =
+                serde_json::from_value(notification.params).ok()?;
+            let uri = params.text_document.uri;
+            let entry = open.get_mut(uri.as_str())?;
+            for change in params.content_changes {
+                match change.range {
+                    Some(range) => {
+                        let index = LineIndex::new(entry.doc.text());
+                        let start = index.offset(range.start);
+                        let end = index.offset(range.end);
+                        // Offsets come from the index, so the edit cannot fail.
+                        let _ = entry.doc.edit(start..end, &change.text);
+                    }
+                    None => {
+                        entry.doc = IncrementalDocument::with_options(
+                            entry.doc.format(),
+                            change.text,
+                            entry.doc.options().clone(),
+                        );
+                    }
+                }
+            }
+            entry.version = params.text_document.version;
+            Some(diagnostics(uri, entry))
+        }
+        DidCloseTextDocument::METHOD => {
+            let params: DidCloseTextDocumentParams =
+                serde_json::from_value(notification.params).ok()?;
+            let uri = params.text_document.uri;
+            open.remove(uri.as_str());
+            Some(PublishDiagnosticsParams::new(uri, Vec::new(), None))
+        }
+        _ => None,
+    }
+}
+
+/// Format and parser options for a document, from its language or, failing
+/// that, its file extension
+fn document_format(uri: &Uri, language_id: &str) -> Option<(Format, ConvertOptions)> {
+    let path = Path::new(uri.path().as_str());
+    let format = match language_id {
+        "json" | "jsonc" => Format::Json,
+        "toml" => Format::Toml,
+        "yaml" => Format::Yaml,
+        _ => zparse::detect_format_from_path(path)?,
+    };
+    if !matches!(format, Format::Json | Format::Toml | Format::Yaml) {
+        return None;
+    }
+    let mut options = zparse::ops::options_for_path(path, &ConvertOptions::default());
+    if language_id == "jsonc" {
+        options.json = options.json.with_comments(true).with_trailing_commas(true);
+    }
+    Some((format, options))
+}
+
+fn diagnostics(uri: Uri, entry: &Open) -> PublishDiagnosticsParams {
+    let index = LineIndex::new(entry.doc.text());
+    let errors = entry.doc.errors().into_iter().map(|err| Diagnostic {
+        range: index.range(err.span().start.offset, err.span().end.offset),
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some(SOURCE.to_string()),
+        message: std::iter::once(err.message())
+            .chain(err.frames().iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        ..Diagnostic::default()
+    });
+    let warnings = entry.doc.warnings().into_iter().map(|warning| Diagnostic {
+        range: index.range(warning.span().start.offset, warning.span().end.offset),
+        severity: Some(DiagnosticSeverity::WARNING),
+        source: Some(SOURCE.to_string()),
+        message: warning.kind().to_string(),
+        ..Diagnostic::default()
+    });
+    PublishDiagnosticsParams::new(uri, errors.chain(warnings).collect(), Some(entry.version))
+}
+
+/// The whole document reformatted, or no edits when it does not parse or
+/// is already formatted
+fn format(open: &HashMap<String, Open>, params: &DocumentFormattingParams) -> Vec<TextEdit> {
+    let Some(entry) = open.get(params.text_document.uri.as_str()) else {
+        return Vec::new();
+    };
+    let text = entry.doc.text();
+    let mut options = entry.doc.options().clone();
+    options.format = options.format.with_pretty(true);
+    if options.json.allow_comments {
+        options.format = options
+            .format
+            .with_json_dialect(zparse::JsonDialect::Jsonc)
+            .with_json_trailing_commas(options.json.allow_trailing_commas);
+    }
+    let Ok(mut formatted) = zparse::ops::format_str(text, entry.doc.format(), &options) else {
+        return Vec::new();
+    };
+    if !formatted.ends_with('\n') {
+        formatted.push('\n');
+    }
+    if formatted == text {
+        return Vec::new();
+    }
+    let index = LineIndex::new(text);
+    vec![TextEdit::new(index.range(0, text.len()), formatted)]
+}
+
+fn document_symbols(
+    open: &HashMap<String, Open>,
+    params: &DocumentSymbolParams,
+) -> Option<DocumentSymbolResponse> {
+    let entry = open.get(params.text_document.uri.as_str())?;
+    let value = entry.doc.value().ok()?;
+    Some(DocumentSymbolResponse::Nested(symbols::document_symbols(
+        entry.doc.text(),
+        entry.doc.format(),
+        entry.doc.options(),
+        &value,
+    )))
+}
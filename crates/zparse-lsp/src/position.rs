@@ -0,0 +1,89 @@
+//! Conversion between byte offsets and LSP positions
+//!
+//! LSP positions count lines from 0 and columns in UTF-16 code units.
+
+use lsp_types::{Position, Range};
+
+/// Line starts of a text, for converting offsets in both directions
+pub struct LineIndex<'a> {
+    text: &'a str,
+    /// Byte offset of every line start; the first is always 0
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        Self { text, starts }
+    }
+
+    /// Position of byte `offset`, clamped to the end of the text
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.text.len());
+        let line = self.starts.partition_point(|start| *start <= offset) - 1;
+        let start = self.starts[line];
+        let character = self.text[start..offset]
+            .chars()
+            .map(char::len_utf16)
+            .sum::<usize>();
+        Position::new(to_u32(line), to_u32(character))
+    }
+
+    pub fn range(&self, start: usize, end: usize) -> Range {
+        Range::new(self.position(start), self.position(end))
+    }
+
+    /// Byte offset of `position`; columns past the end of a line and lines
+    /// past the end of the text are clamped
+    pub fn offset(&self, position: Position) -> usize {
+        let Some(start) = self.starts.get(position.line as usize).copied() else {
+            return self.text.len();
+        };
+        let line = self.text[start..]
+            .split_inclusive('\n')
+            .next()
+            .unwrap_or_default();
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let mut units = 0;
+        for (index, ch) in line.char_indices() {
+            if units >= position.character as usize {
+                return start + index;
+            }
+            units += ch.len_utf16();
+        }
+        start + line.len()
+    }
+
+    /// Range of line `line` (0-based) without its line break
+    pub fn line_range(&self, line: usize) -> Range {
+        let start = self.starts.get(line).copied().unwrap_or(self.text.len());
+        let end = self
+            .starts
+            .get(line + 1)
+            .map_or(self.text.len(), |next| next - 1);
+        let end = if self.text[..end].ends_with('\r') {
+            end - 1
+        } else {
+            end
+        };
+        self.range(start, end.max(start))
+    }
+
+    /// Text of line `line` (0-based) without its line break
+    pub fn line(&self, line: usize) -> &'a str {
+        let start = self.starts.get(line).copied().unwrap_or(self.text.len());
+        self.text[start..].lines().next().unwrap_or_default()
+    }
+
+    /// Byte offset of the start of line `line` (0-based)
+    pub fn line_start(&self, line: usize) -> usize {
+        self.starts.get(line).copied().unwrap_or(self.text.len())
+    }
+}
+
+fn to_u32(value: usize) -> u32 {
+    u32::try_from(value).unwrap_or(u32::MAX)
+}
@@ -0,0 +1,118 @@
+//! Document symbols from object keys and TOML tables
+
+use std::collections::BTreeMap;
+
+use lsp_types::{DocumentSymbol, Position, Range, SymbolKind};
+use zparse::{ConvertOptions, Format, Value};
+
+use crate::position::LineIndex;
+
+/// Outline of `value` parsed from `text`: one symbol per object key, nested
+/// like the document. Array items holding objects or arrays are listed by
+/// index; other items have no position of their own and are left out.
+pub fn document_symbols(
+    text: &str,
+    format: Format,
+    options: &ConvertOptions,
+    value: &Value,
+) -> Vec<DocumentSymbol> {
+    let lines = zparse::key_lines(text, format, options).unwrap_or_default();
+    let index = LineIndex::new(text);
+    children(value, "", &lines, &index)
+}
+
+/// Symbols for the entries of `value` found at JSON pointer `pointer`
+fn children(
+    value: &Value,
+    pointer: &str,
+    lines: &BTreeMap<String, u32>,
+    index: &LineIndex<'_>,
+) -> Vec<DocumentSymbol> {
+    match value {
+        Value::Object(obj) => obj
+            .iter()
+            .filter_map(|(key, value)| {
+                let pointer = format!("{pointer}/{}", escape_token(key));
+                let line = lines.get(&pointer).map(|line| line.saturating_sub(1));
+                symbol(key, line, value, &pointer, lines, index)
+            })
+            .collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| matches!(item, Value::Object(_) | Value::Array(_)))
+            .filter_map(|(position, item)| {
+                let pointer = format!("{pointer}/{position}");
+                symbol(&position.to_string(), None, item, &pointer, lines, index)
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Symbol named `name` for `value`, on 0-based `line` or, for array items,
+/// on the line of its first located child
+#[allow(deprecated)]
+fn symbol(
+    name: &str,
+    line: Option<u32>,
+    value: &Value,
+    pointer: &str,
+    lines: &BTreeMap<String, u32>,
+    index: &LineIndex<'_>,
+) -> Option<DocumentSymbol> {
+    let children = children(value, pointer, lines, index);
+    let line = line.or_else(|| children.first().map(|child| child.range.start.line))?;
+    let line_range = index.line_range(line as usize);
+    let end = children
+        .iter()
+        .map(|child| child.range.end)
+        .fold(line_range.end, Position::max);
+    let selection_range = index
+        .line(line as usize)
+        .find(name)
+        .map_or(line_range, |column| {
+            let start = index.line_start(line as usize) + column;
+            index.range(start, start + name.len())
+        });
+    Some(DocumentSymbol {
+        name: name.to_string(),
+        detail: detail(value),
+        kind: kind(value),
+        tags: None,
+        deprecated: None,
+        range: Range::new(Position::new(line, 0), end),
+        selection_range,
+        children: (!children.is_empty()).then_some(children),
+    })
+}
+
+fn kind(value: &Value) -> SymbolKind {
+    match value {
+        Value::Object(_) => SymbolKind::OBJECT,
+        Value::Array(_) => SymbolKind::ARRAY,
+        Value::Number(_) => SymbolKind::NUMBER,
+        Value::Bool(_) => SymbolKind::BOOLEAN,
+        Value::Null => SymbolKind::NULL,
+        _ => SymbolKind::STRING,
+    }
+}
+
+/// Short description shown next to the name: the value of scalars, the
+/// size of collections
+fn detail(value: &Value) -> Option<String> {
+    match value {
+        Value::Object(obj) => Some(format!("{} keys", obj.len())),
+        Value::Array(items) => Some(format!("{} items", items.len())),
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Bool(flag) => Some(flag.to_string()),
+        Value::Null => Some("null".to_string()),
+        _ => None,
+    }
+}
+
+/// Escape an object key for use as a JSON pointer token
+fn escape_token(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
@@ -13,6 +13,7 @@ struct JsError {
     kind: String,
     message: String,
     span: Option<JsSpan>,
+    context: Vec<String>,
 }
 
 #[derive(Serialize)]
@@ -54,6 +55,7 @@ impl From<zparse::Error> for JsError {
             kind: stable_error_kind(e.kind()).to_string(),
             message: e.message().to_string(),
             span,
+            context: e.frames().to_vec(),
         }
     }
 }
@@ -75,6 +77,15 @@ fn stable_error_kind(kind: &zparse::ErrorKind) -> &'static str {
         zparse::ErrorKind::InvalidArray => "InvalidArray",
         zparse::ErrorKind::MaxDepthExceeded { .. } => "MaxDepthExceeded",
         zparse::ErrorKind::MaxSizeExceeded { .. } => "MaxSizeExceeded",
+        zparse::ErrorKind::UndefinedVariable { .. } => "UndefinedVariable",
+        zparse::ErrorKind::InvalidInclude { .. } => "InvalidInclude",
+        zparse::ErrorKind::Io { .. } => "Io",
+        zparse::ErrorKind::UnsupportedValue { .. } => "UnsupportedValue",
+        zparse::ErrorKind::UnknownFormat { .. } => "UnknownFormat",
+        zparse::ErrorKind::FormatDisabled { .. } => "FormatDisabled",
+        zparse::ErrorKind::PathNotFound { .. } => "PathNotFound",
+        // Kinds added to zparse before this list catches up
+        _ => "Unknown",
     }
 }
 
@@ -84,6 +95,7 @@ impl JsError {
             kind: "UnknownFormat".to_string(),
             message: format!("Unknown format: {}", format),
             span: None,
+            context: Vec::new(),
         }
     }
 }
@@ -163,6 +175,47 @@ pub fn detect_format(path: &str) -> Option<String> {
     zparse::detect_format_from_path(path).map(|f| format!("{:?}", f).to_lowercase())
 }
 
+/// Highlighted range for the WASM boundary
+#[derive(Debug, PartialEq, Serialize)]
+struct JsHighlight {
+    start: usize,
+    end: usize,
+    class: &'static str,
+}
+
+/// Classify source text for syntax highlighting
+/// - input: the document
+/// - format: source format ("json", "csv", "toml", "yaml", "xml")
+/// Returns an array of `{ start, end, class }` with UTF-16 offsets, ready for
+/// JavaScript string slicing
+#[wasm_bindgen]
+pub fn highlight(input: &str, format: &str) -> Result<JsValue, JsValue> {
+    let fmt = parse_format(format).map_err(|e| serialize_to_js(&e))?;
+    Ok(serialize_to_js(&highlight_utf16(input, fmt)))
+}
+
+/// Highlights of `input` with byte offsets translated to UTF-16 offsets
+fn highlight_utf16(input: &str, format: Format) -> Vec<JsHighlight> {
+    let highlights = zparse::highlight::highlight(input, format);
+    let mut chars = input.char_indices().peekable();
+    let mut utf16 = 0;
+    // Ranges are sorted and disjoint, so the offsets only ever grow.
+    let mut to_utf16 = |offset: usize| {
+        while let Some((_, ch)) = chars.next_if(|(index, _)| *index < offset) {
+            utf16 += ch.len_utf16();
+        }
+        utf16
+    };
+    highlights
+        .into_iter()
+        .map(|(range, class)| JsHighlight {
+            start: to_utf16(range.start),
+            end: to_utf16(range.end),
+            class: class.name(),
+        })
+        .collect()
+}
+
 type Format = zparse::convert::Format;
 
 fn parse_format(s: &str) -> Result<Format, JsError> {
@@ -182,12 +235,14 @@ fn parse_csv_delimiter(s: &str) -> Result<u8, JsError> {
         kind: "InvalidToken".to_string(),
         message: "CSV delimiter must be a single ASCII character".to_string(),
         span: None,
+        context: Vec::new(),
     })?;
     if chars.next().is_some() {
         return Err(JsError {
             kind: "InvalidToken".to_string(),
             message: "CSV delimiter must be a single character".to_string(),
             span: None,
+            context: Vec::new(),
         });
     }
     if !ch.is_ascii() {
@@ -195,6 +250,7 @@ fn parse_csv_delimiter(s: &str) -> Result<u8, JsError> {
             kind: "InvalidToken".to_string(),
             message: "CSV delimiter must be an ASCII character".to_string(),
             span: None,
+            context: Vec::new(),
         });
     }
     let byte = ch as u8;
@@ -206,6 +262,7 @@ fn parse_csv_delimiter(s: &str) -> Result<u8, JsError> {
                 ch
             ),
             span: None,
+            context: Vec::new(),
         });
     }
     Ok(byte)
@@ -356,6 +413,31 @@ active: false
         }
     }
 
+    mod highlight_tests {
+        use super::*;
+
+        #[wasm_bindgen_test]
+        fn utf16_offsets() {
+            let highlights = highlight_utf16(r#"{"é😀": 1}"#, Format::Json);
+            assert_eq!(
+                highlights.get(1),
+                Some(&JsHighlight {
+                    start: 1,
+                    end: 6,
+                    class: "key",
+                })
+            );
+            assert_eq!(
+                highlights.get(3),
+                Some(&JsHighlight {
+                    start: 8,
+                    end: 9,
+                    class: "number",
+                })
+            );
+        }
+    }
+
     mod detect_format_tests {
         use super::*;
 
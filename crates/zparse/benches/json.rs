@@ -1,6 +1,6 @@
 use criterion::{Criterion, criterion_group, criterion_main};
 use std::hint::black_box;
-use zparse::from_str;
+use zparse::{JsonParser, from_str};
 
 // Test data - include inline for simplicity
 const SIMPLE_JSON: &str = r#"{"name": "test", "value": 42}"#;
@@ -37,5 +37,17 @@ fn bench_array(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_simple, bench_nested, bench_array);
+fn bench_validate(c: &mut Criterion) {
+    c.bench_function("zparse_validate_array", |b| {
+        b.iter(|| JsonParser::new(black_box(ARRAY_JSON.as_bytes())).validate())
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_simple,
+    bench_nested,
+    bench_array,
+    bench_validate
+);
 criterion_main!(benches);
@@ -0,0 +1,74 @@
+//! Decoder adapter for the toml-test suite
+//!
+//! Reads TOML on stdin and writes the toml-test JSON encoding on stdout, where
+//! every scalar becomes `{"type": ..., "value": ...}`. Invalid input exits
+//! with a non-zero status.
+//!
+//! ```text
+//! cargo build --example toml_test_decoder
+//! toml-test target/debug/examples/toml_test_decoder
+//! ```
+//!
+//! Numbers are stored as `f64`, so floats with an integral value such as
+//! `1.0` are reported as integers.
+
+use std::io::{Read, Write};
+
+use zparse::{Array, Format, Object, Value};
+
+fn tagged(kind: &str, value: String) -> Value {
+    let mut object = Object::new();
+    object.insert("type", kind);
+    object.insert("value", value);
+    Value::Object(object)
+}
+
+fn number(n: f64) -> Value {
+    const I64_LIMIT: f64 = 9_223_372_036_854_775_808.0;
+    if n.is_finite() && n.fract() == 0.0 && n.abs() < I64_LIMIT {
+        tagged("integer", format!("{n}"))
+    } else if n.is_nan() {
+        tagged("float", "nan".to_string())
+    } else if n.is_infinite() {
+        let sign = if n.is_sign_negative() { "-" } else { "" };
+        tagged("float", format!("{sign}inf"))
+    } else {
+        tagged("float", format!("{n}"))
+    }
+}
+
+fn encode(value: &Value) -> Value {
+    match value {
+        Value::Object(entries) => Value::Object(
+            entries
+                .iter()
+                .map(|(key, value)| (key.clone(), encode(value)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(encode).collect::<Array>()),
+        Value::String(text) => tagged("string", text.clone()),
+        Value::Bool(flag) => tagged("bool", flag.to_string()),
+        Value::Number(n) => number(*n),
+        Value::Datetime(datetime) => {
+            let kind = match datetime {
+                zparse::TomlDatetime::OffsetDateTime(_) => "datetime",
+                zparse::TomlDatetime::LocalDateTime(_) => "datetime-local",
+                zparse::TomlDatetime::LocalDate(_) => "date-local",
+                zparse::TomlDatetime::LocalTime(_) => "time-local",
+            };
+            tagged(kind, datetime.to_string())
+        }
+        // TOML has neither; keep the output well-formed anyway.
+        Value::Null => tagged("string", String::new()),
+        Value::Bytes(bytes) => tagged("string", String::from_utf8_lossy(bytes).into_owned()),
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+    let value = zparse::from_toml_str(&input)?;
+    let output = zparse::serialize(&encode(&value), Format::Json)?;
+    std::io::stdout().write_all(output.as_bytes())?;
+    Ok(())
+}
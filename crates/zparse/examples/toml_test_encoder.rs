@@ -0,0 +1,62 @@
+//! Encoder adapter for the toml-test suite
+//!
+//! Reads the toml-test JSON encoding on stdin (scalars written as
+//! `{"type": ..., "value": ...}`) and writes the equivalent TOML document on
+//! stdout.
+//!
+//! ```text
+//! cargo build --example toml_test_encoder
+//! toml-test -encoder target/debug/examples/toml_test_encoder
+//! ```
+
+use std::io::{Read, Write};
+
+use zparse::{Array, Format, Object, TomlDatetime, Value};
+
+fn scalar(kind: &str, text: &str) -> Result<Value, Box<dyn std::error::Error>> {
+    Ok(match kind {
+        "string" => Value::String(text.to_string()),
+        "integer" => Value::from(text.parse::<i64>()?),
+        "float" => Value::Number(match text {
+            "inf" | "+inf" => f64::INFINITY,
+            "-inf" => f64::NEG_INFINITY,
+            "nan" | "+nan" | "-nan" => f64::NAN,
+            _ => text.parse()?,
+        }),
+        "bool" => Value::Bool(text.parse()?),
+        "datetime" | "datetime-local" | "date-local" | "time-local" => {
+            Value::Datetime(text.parse::<TomlDatetime>()?)
+        }
+        other => return Err(format!("unknown toml-test type {other:?}").into()),
+    })
+}
+
+fn decode(value: &Value) -> Result<Value, Box<dyn std::error::Error>> {
+    match value {
+        Value::Object(entries) => {
+            let kind = entries.get("type").and_then(Value::as_string);
+            let text = entries.get("value").and_then(Value::as_string);
+            if let (2, Some(kind), Some(text)) = (entries.len(), kind, text) {
+                return scalar(kind, text);
+            }
+            let mut table = Object::with_capacity(entries.len());
+            for (key, value) in entries {
+                table.insert(key.clone(), decode(value)?);
+            }
+            Ok(Value::Object(table))
+        }
+        Value::Array(items) => Ok(Value::Array(
+            items.iter().map(decode).collect::<Result<Array, _>>()?,
+        )),
+        other => Err(format!("unexpected value in toml-test JSON: {other:?}").into()),
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+    let value = decode(&zparse::from_str(&input)?)?;
+    let output = zparse::serialize(&value, Format::Toml)?;
+    std::io::stdout().write_all(output.as_bytes())?;
+    Ok(())
+}
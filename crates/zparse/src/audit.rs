@@ -0,0 +1,484 @@
+//! Checks across a directory of configuration files
+//!
+//! [`audit_dir`] parses every file it recognizes under a directory and
+//! reports:
+//!
+//! - **duplicates**: a key path (JSON pointer) set to the same value in more
+//!   than one file, which usually means one of them can go;
+//! - **conflicts**: a key path set to different values in different files;
+//! - **unused includes**: `$include` targets (see [`include`](crate::include))
+//!   that contribute nothing, because they are empty or every key they set is
+//!   set again by a later include or by the including object itself.
+//!
+//! Arrays count as single values. Files are read as written, without
+//! resolving their includes, and files that fail to parse are listed in
+//! [`Report::errors`] rather than stopping the audit.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::convert::{ConvertOptions, parse_with_options};
+use crate::detect_format_from_path;
+use crate::error::{Error, ErrorKind, Result, Span};
+use crate::include::INCLUDE_KEY;
+use crate::input::{self, Utf8Mode};
+use crate::locate::{escape_token, key_lines};
+use crate::ops::{options_for_path, read_with_format};
+use crate::value::{Object, Value};
+
+/// Where a key was set
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Location {
+    pub file: PathBuf,
+    /// 1-based line of the key, when the format records it
+    pub line: Option<u32>,
+}
+
+/// The same value set for a key path in several files
+#[derive(Clone, Debug, PartialEq)]
+pub struct Duplicate {
+    pub pointer: String,
+    pub value: Value,
+    pub locations: Vec<Location>,
+}
+
+/// Different values set for a key path in different files
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conflict {
+    pub pointer: String,
+    pub definitions: Vec<(Location, Value)>,
+}
+
+/// Why an include has no effect
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnusedReason {
+    /// The included document sets no keys
+    Empty,
+    /// Every key it sets is set again later
+    Overridden,
+}
+
+/// An `$include` target that contributes nothing
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnusedInclude {
+    /// File holding the include
+    pub file: PathBuf,
+    /// JSON pointer of the object holding the include
+    pub pointer: String,
+    /// The included file
+    pub target: PathBuf,
+    pub reason: UnusedReason,
+}
+
+/// Findings of [`audit_dir`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Report {
+    /// Every file audited, in path order
+    pub files: Vec<PathBuf>,
+    pub duplicates: Vec<Duplicate>,
+    pub conflicts: Vec<Conflict>,
+    pub unused_includes: Vec<UnusedInclude>,
+    /// Files that could not be read or parsed, and includes that could not be
+    /// followed
+    pub errors: Vec<(PathBuf, Error)>,
+}
+
+impl Report {
+    /// Whether the audit found nothing to report
+    pub fn is_clean(&self) -> bool {
+        self.duplicates.is_empty()
+            && self.conflicts.is_empty()
+            && self.unused_includes.is_empty()
+            && self.errors.is_empty()
+    }
+
+    /// The report as a document
+    pub fn to_value(&self) -> Value {
+        let location = |location: &Location| {
+            let mut object = Object::new();
+            object.insert("file", location.file.display().to_string());
+            if let Some(line) = location.line {
+                object.insert("line", Value::from(line));
+            }
+            Value::Object(object)
+        };
+
+        let mut report = Object::new();
+        report.insert(
+            "files",
+            Value::from(
+                self.files
+                    .iter()
+                    .map(|file| Value::from(file.display().to_string()))
+                    .collect::<Vec<_>>(),
+            ),
+        );
+        report.insert(
+            "duplicates",
+            Value::from(
+                self.duplicates
+                    .iter()
+                    .map(|duplicate| {
+                        let mut object = Object::new();
+                        object.insert("pointer", duplicate.pointer.clone());
+                        object.insert("value", duplicate.value.clone());
+                        object.insert(
+                            "locations",
+                            Value::from(
+                                duplicate.locations.iter().map(location).collect::<Vec<_>>(),
+                            ),
+                        );
+                        Value::Object(object)
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+        );
+        report.insert(
+            "conflicts",
+            Value::from(
+                self.conflicts
+                    .iter()
+                    .map(|conflict| {
+                        let mut object = Object::new();
+                        object.insert("pointer", conflict.pointer.clone());
+                        let definitions = conflict.definitions.iter().map(|(at, value)| {
+                            let mut definition = location(at);
+                            if let Value::Object(entries) = &mut definition {
+                                entries.insert("value", value.clone());
+                            }
+                            definition
+                        });
+                        object.insert("definitions", Value::from(definitions.collect::<Vec<_>>()));
+                        Value::Object(object)
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+        );
+        report.insert(
+            "unused_includes",
+            Value::from(
+                self.unused_includes
+                    .iter()
+                    .map(|unused| {
+                        let mut object = Object::new();
+                        object.insert("file", unused.file.display().to_string());
+                        object.insert("pointer", unused.pointer.clone());
+                        object.insert("target", unused.target.display().to_string());
+                        object.insert(
+                            "reason",
+                            match unused.reason {
+                                UnusedReason::Empty => "empty",
+                                UnusedReason::Overridden => "overridden",
+                            },
+                        );
+                        Value::Object(object)
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+        );
+        report.insert(
+            "errors",
+            Value::from(
+                self.errors
+                    .iter()
+                    .map(|(file, err)| {
+                        let mut object = Object::new();
+                        object.insert("file", file.display().to_string());
+                        object.insert("error", err.to_string());
+                        Value::Object(object)
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+        );
+        Value::Object(report)
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{line}", self.file.display()),
+            None => write!(f, "{}", self.file.display()),
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for duplicate in &self.duplicates {
+            writeln!(f, "duplicate {}:", duplicate.pointer)?;
+            for location in &duplicate.locations {
+                writeln!(f, "  {location}")?;
+            }
+        }
+        for conflict in &self.conflicts {
+            writeln!(f, "conflict {}:", conflict.pointer)?;
+            for (location, value) in &conflict.definitions {
+                writeln!(f, "  {location}: {}", inline(value))?;
+            }
+        }
+        for unused in &self.unused_includes {
+            let reason = match unused.reason {
+                UnusedReason::Empty => "is empty",
+                UnusedReason::Overridden => "is overridden",
+            };
+            writeln!(
+                f,
+                "unused include {} in {} at {}: {reason}",
+                unused.target.display(),
+                unused.file.display(),
+                if unused.pointer.is_empty() {
+                    "/"
+                } else {
+                    &unused.pointer
+                },
+            )?;
+        }
+        for (file, err) in &self.errors {
+            writeln!(f, "error {}: {err}", file.display())?;
+        }
+        write!(
+            f,
+            "{} files, {} duplicates, {} conflicts, {} unused includes, {} errors",
+            self.files.len(),
+            self.duplicates.len(),
+            self.conflicts.len(),
+            self.unused_includes.len(),
+            self.errors.len()
+        )
+    }
+}
+
+/// Audit every JSON, TOML, YAML, XML and CSV file under `dir`, skipping
+/// hidden files and directories
+pub fn audit_dir(dir: impl AsRef<Path>, options: &ConvertOptions) -> Result<Report> {
+    let mut files = Vec::new();
+    collect_files(dir.as_ref(), &mut files)?;
+    files.sort();
+
+    let mut report = Report::default();
+    let mut parsed: BTreeMap<PathBuf, Value> = BTreeMap::new();
+    // Every definition of each key path: file, line and value
+    let mut definitions: BTreeMap<String, Vec<(Location, Value)>> = BTreeMap::new();
+
+    for file in &files {
+        let (value, lines) = match parse_file(file, options) {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                report.errors.push((file.clone(), err));
+                continue;
+            }
+        };
+        let mut leaves = Vec::new();
+        collect_leaves(&value, String::new(), &mut leaves);
+        for (pointer, leaf) in leaves {
+            if pointer.is_empty() {
+                continue;
+            }
+            let location = Location {
+                file: file.clone(),
+                line: lines.get(&pointer).copied(),
+            };
+            definitions
+                .entry(pointer)
+                .or_default()
+                .push((location, leaf.clone()));
+        }
+        parsed.insert(file.clone(), value);
+    }
+
+    for (pointer, defined) in definitions {
+        let in_files: BTreeSet<&PathBuf> = defined.iter().map(|(at, _)| &at.file).collect();
+        if in_files.len() < 2 {
+            continue;
+        }
+        let first = defined.first().map(|(_, value)| value);
+        if defined.iter().all(|(_, value)| Some(value) == first) {
+            report.duplicates.push(Duplicate {
+                pointer,
+                value: first.cloned().unwrap_or_default(),
+                locations: defined.into_iter().map(|(at, _)| at).collect(),
+            });
+        } else {
+            report.conflicts.push(Conflict {
+                pointer,
+                definitions: defined,
+            });
+        }
+    }
+
+    for (file, value) in &parsed {
+        let mut includes = Vec::new();
+        collect_includes(value, String::new(), &mut includes);
+        for (pointer, object) in includes {
+            check_includes(file, &pointer, object, &parsed, options, &mut report);
+        }
+    }
+
+    report.files = files;
+    Ok(report)
+}
+
+fn parse_file(path: &Path, options: &ConvertOptions) -> Result<(Value, BTreeMap<String, u32>)> {
+    let (format, input) = read_with_format(path, options.utf8)?;
+    let options = options_for_path(path, options);
+    let mut value = parse_with_options(&input, format, &options)?;
+    if options.utf8 == Utf8Mode::Bytes {
+        input::restore_bytes(&mut value);
+    }
+    let lines = key_lines(&input, format, &options).unwrap_or_default();
+    Ok((value, lines))
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let io_error = |err: std::io::Error| {
+        Error::with_message(
+            ErrorKind::Io {
+                path: dir.display().to_string(),
+            },
+            Span::empty(),
+            format!("{}: {err}", dir.display()),
+        )
+    };
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir).map_err(io_error)? {
+            let path = entry.map_err(io_error)?.path();
+            let hidden = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with('.'));
+            if hidden {
+                continue;
+            }
+            if path.is_dir() {
+                pending.push(path);
+            } else if detect_format_from_path(&path).is_some() {
+                files.push(path);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Key paths of the values that are not objects, skipping include keys
+fn collect_leaves<'v>(value: &'v Value, pointer: String, leaves: &mut Vec<(String, &'v Value)>) {
+    let mut pending = vec![(pointer, value)];
+    while let Some((pointer, value)) = pending.pop() {
+        match value {
+            Value::Object(entries) if !entries.is_empty() => {
+                for (key, item) in entries.iter().rev() {
+                    if key != INCLUDE_KEY {
+                        pending.push((format!("{pointer}/{}", escape_token(key)), item));
+                    }
+                }
+            }
+            _ => leaves.push((pointer, value)),
+        }
+    }
+}
+
+/// Objects holding an include key, with their JSON pointers
+fn collect_includes<'v>(value: &'v Value, pointer: String, found: &mut Vec<(String, &'v Object)>) {
+    let mut pending = vec![(pointer, value)];
+    while let Some((pointer, value)) = pending.pop() {
+        match value {
+            Value::Object(entries) => {
+                if entries.contains_key(INCLUDE_KEY) {
+                    found.push((pointer.clone(), entries));
+                }
+                for (key, item) in entries.iter().rev() {
+                    pending.push((format!("{pointer}/{}", escape_token(key)), item));
+                }
+            }
+            Value::Array(items) => {
+                for (index, item) in items.iter().enumerate().rev() {
+                    pending.push((format!("{pointer}/{index}"), item));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Report the targets of the include in `object` that later targets or the
+/// object's own keys override completely
+fn check_includes(
+    file: &Path,
+    pointer: &str,
+    object: &Object,
+    parsed: &BTreeMap<PathBuf, Value>,
+    options: &ConvertOptions,
+    report: &mut Report,
+) {
+    let targets: Vec<&str> = match object.get(INCLUDE_KEY) {
+        Some(Value::String(target)) => vec![target.as_str()],
+        Some(Value::Array(targets)) => targets.iter().filter_map(Value::as_string).collect(),
+        _ => Vec::new(),
+    };
+    let dir = file.parent().unwrap_or_else(|| Path::new("."));
+
+    // Key paths each include sets, followed by those of the object itself
+    let mut layers: Vec<(PathBuf, BTreeSet<String>)> = Vec::new();
+    for target in targets {
+        let path = dir.join(target);
+        let loaded;
+        let value = match parsed.get(&path) {
+            Some(value) => value,
+            None => match parse_file(&path, options) {
+                Ok((value, _)) => {
+                    loaded = value;
+                    &loaded
+                }
+                Err(err) => {
+                    report.errors.push((file.to_path_buf(), err));
+                    continue;
+                }
+            },
+        };
+        let mut leaves = Vec::new();
+        collect_leaves(value, String::new(), &mut leaves);
+        let keys = leaves
+            .into_iter()
+            .map(|(pointer, _)| pointer)
+            .filter(|pointer| !pointer.is_empty())
+            .collect();
+        layers.push((path, keys));
+    }
+    let mut own = Vec::new();
+    for (key, item) in object.iter().filter(|(key, _)| *key != INCLUDE_KEY) {
+        collect_leaves(item, format!("/{}", escape_token(key)), &mut own);
+    }
+    let mut later: BTreeSet<String> = own
+        .into_iter()
+        .map(|(pointer, _)| pointer)
+        .filter(|pointer| !pointer.is_empty())
+        .collect();
+
+    let mut unused = Vec::new();
+    for (target, keys) in layers.into_iter().rev() {
+        let reason = if keys.is_empty() {
+            Some(UnusedReason::Empty)
+        } else if keys.is_subset(&later) {
+            Some(UnusedReason::Overridden)
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            unused.push(UnusedInclude {
+                file: file.to_path_buf(),
+                pointer: pointer.to_string(),
+                target,
+                reason,
+            });
+        }
+        later.extend(keys);
+    }
+    report.unused_includes.extend(unused.into_iter().rev());
+}
+
+/// A value on one line, for the text report
+fn inline(value: &Value) -> String {
+    crate::convert::serialize_json(value, &crate::FormatOptions::default())
+}
@@ -0,0 +1,71 @@
+//! Standard base64 (RFC 4648) used for byte strings in text formats
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encode `bytes` with padding
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    let mut output = String::with_capacity(bytes.len().div_ceil(3).saturating_mul(4));
+    for chunk in bytes.chunks(3) {
+        let [a, b, c] = match *chunk {
+            [a] => [a, 0, 0],
+            [a, b] => [a, b, 0],
+            [a, b, c] => [a, b, c],
+            _ => continue,
+        };
+        let group = u32::from(a) << 16 | u32::from(b) << 8 | u32::from(c);
+        let symbols = u32::try_from(chunk.len()).unwrap_or(3).saturating_add(1);
+        for index in 0..4_u32 {
+            if index < symbols {
+                let shift = 18_u32.saturating_sub(index * 6);
+                output.push(symbol(group >> shift));
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
+
+fn symbol(sextet: u32) -> char {
+    let index = usize::try_from(sextet & 0x3f).unwrap_or(0);
+    ALPHABET.get(index).copied().map_or('A', char::from)
+}
+
+/// Decode padded or unpadded base64, ignoring ASCII whitespace.
+///
+/// Returns None on characters outside the alphabet or a truncated group.
+#[cfg(any(feature = "yaml", feature = "xml"))]
+pub(crate) fn decode(input: &str) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len() / 4 * 3);
+    let mut group = 0_u32;
+    let mut count = 0_u32;
+    let mut padding = false;
+    for byte in input.bytes() {
+        if byte.is_ascii_whitespace() {
+            continue;
+        }
+        if byte == b'=' {
+            padding = true;
+            continue;
+        }
+        if padding {
+            return None;
+        }
+        let value = ALPHABET.iter().position(|symbol| *symbol == byte)?;
+        group = group << 6 | u32::try_from(value).ok()?;
+        count += 1;
+        if count == 4 {
+            let [_, a, b, c] = group.to_be_bytes();
+            output.extend_from_slice(&[a, b, c]);
+            group = 0;
+            count = 0;
+        }
+    }
+    match count {
+        0 => {}
+        2 => output.push(u8::try_from(group >> 4).ok()?),
+        3 => output.extend_from_slice(&u16::try_from(group >> 2).ok()?.to_be_bytes()),
+        _ => return None,
+    }
+    Some(output)
+}
@@ -0,0 +1,110 @@
+//! Key case conversion
+//!
+//! Keys are split into words on `_`, `-`, spaces and case boundaries
+//! (`httpServer`, `HTTPServer` and `http-server` all split into
+//! `http` + `server`) and rejoined in the target style. Leading separators are
+//! preserved, so `_id` stays `_id`.
+
+use crate::value::Value;
+
+/// Naming convention for object keys
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyCase {
+    /// `snake_case`
+    Snake,
+    /// `camelCase`
+    Camel,
+    /// `kebab-case`
+    Kebab,
+}
+
+/// Convert a single key to `case`
+pub fn convert_key(key: &str, case: KeyCase) -> String {
+    let body = key.trim_start_matches(is_separator);
+    let prefix = key
+        .get(..key.len().saturating_sub(body.len()))
+        .unwrap_or_default();
+
+    let words = split_words(body);
+    if words.is_empty() {
+        return key.to_string();
+    }
+
+    let mut out = String::with_capacity(key.len());
+    out.push_str(prefix);
+    match case {
+        KeyCase::Snake => out.push_str(&words.join("_")),
+        KeyCase::Kebab => out.push_str(&words.join("-")),
+        KeyCase::Camel => {
+            for (idx, word) in words.iter().enumerate() {
+                if idx == 0 {
+                    out.push_str(word);
+                } else {
+                    let mut chars = word.chars();
+                    if let Some(first) = chars.next() {
+                        out.extend(first.to_uppercase());
+                        out.push_str(chars.as_str());
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+impl Value {
+    /// Recursively rename object keys to `case`.
+    ///
+    /// When two keys convert to the same name the later one wins.
+    pub fn rename_keys(&mut self, case: KeyCase) {
+        match self {
+            Self::Object(entries) => {
+                *entries = std::mem::take(entries)
+                    .into_iter()
+                    .map(|(key, mut value)| {
+                        value.rename_keys(case);
+                        (convert_key(&key, case), value)
+                    })
+                    .collect();
+            }
+            Self::Array(items) => items.iter_mut().for_each(|item| item.rename_keys(case)),
+            _ => {}
+        }
+    }
+}
+
+fn is_separator(ch: char) -> bool {
+    matches!(ch, '_' | '-' | ' ')
+}
+
+fn split_words(input: &str) -> Vec<String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+
+    for (idx, &ch) in chars.iter().enumerate() {
+        if is_separator(ch) {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+
+        if ch.is_uppercase() && !current.is_empty() {
+            let prev = idx.checked_sub(1).and_then(|prev| chars.get(prev));
+            let next = chars.get(idx.saturating_add(1));
+            let after_lower = prev.is_some_and(|p| p.is_lowercase() || p.is_ascii_digit());
+            let acronym_end =
+                prev.is_some_and(|p| p.is_uppercase()) && next.is_some_and(|n| n.is_lowercase());
+            if after_lower || acronym_end {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.extend(ch.to_lowercase());
+    }
+
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
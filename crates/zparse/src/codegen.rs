@@ -0,0 +1,184 @@
+//! Rust struct generation from sample documents
+//!
+//! Samples are first summarized with [`crate::schema::infer`], so several
+//! samples can contribute to one set of definitions: keys missing from some
+//! samples become `Option` fields and nested objects become their own
+//! structs. The output targets `serde`; positions with mixed or unknown types
+//! fall back to `serde_json::Value`.
+
+use std::collections::HashSet;
+use std::fmt::Write as _;
+
+use crate::case::{KeyCase, convert_key};
+use crate::schema;
+use crate::value::{Object, Value};
+
+const FALLBACK_TYPE: &str = "serde_json::Value";
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "try", "typeof", "unsized", "virtual", "yield",
+];
+
+/// Generate Rust structs describing `samples`, with `root` as the top-level type name
+pub fn rust_from_samples(samples: &[Value], root: &str) -> String {
+    rust_from_schema(&schema::infer(samples), root)
+}
+
+/// Generate Rust structs from a JSON Schema produced by [`schema::infer`]
+pub fn rust_from_schema(schema: &Value, root: &str) -> String {
+    let mut generator = Generator::default();
+    let root_name = type_name(root);
+    let root_type = generator.type_for(schema, &root_name);
+
+    let mut out = String::from("use serde::{Deserialize, Serialize};\n");
+    if root_type != root_name {
+        let _ = write!(out, "\npub type {root_name} = {root_type};\n");
+    }
+    for definition in &generator.structs {
+        out.push('\n');
+        out.push_str(definition);
+    }
+    out
+}
+
+#[derive(Debug, Default)]
+struct Generator {
+    names: HashSet<String>,
+    structs: Vec<String>,
+}
+
+impl Generator {
+    /// Reserve a unique type name based on `base`
+    fn claim(&mut self, base: &str) -> String {
+        let mut name = base.to_string();
+        let mut suffix = 2_u32;
+        while self.names.contains(&name) {
+            name = format!("{base}{suffix}");
+            suffix = suffix.saturating_add(1);
+        }
+        self.names.insert(name.clone());
+        name
+    }
+
+    /// Rust type for a schema node; `name` is used if a struct is needed
+    fn type_for(&mut self, node: &Value, name: &str) -> String {
+        let Some(node) = node.as_object() else {
+            return FALLBACK_TYPE.to_string();
+        };
+        let types: Vec<&str> = match node.get("type") {
+            Some(Value::String(single)) => vec![single.as_str()],
+            Some(Value::Array(many)) => many.iter().filter_map(Value::as_string).collect(),
+            _ => Vec::new(),
+        };
+        let nullable = types.contains(&"null");
+        let concrete: Vec<&str> = types.into_iter().filter(|t| *t != "null").collect();
+
+        let inner = match concrete.as_slice() {
+            ["boolean"] => "bool".to_string(),
+            ["integer"] => "i64".to_string(),
+            ["number"] => "f64".to_string(),
+            ["string"] => "String".to_string(),
+            ["array"] => {
+                let item_name = format!("{name}Item");
+                match node.get("items") {
+                    Some(items) => format!("Vec<{}>", self.type_for(items, &item_name)),
+                    None => format!("Vec<{FALLBACK_TYPE}>"),
+                }
+            }
+            ["object"] => self.struct_for(node, name),
+            _ => return FALLBACK_TYPE.to_string(),
+        };
+        if nullable {
+            format!("Option<{inner}>")
+        } else {
+            inner
+        }
+    }
+
+    fn struct_for(&mut self, node: &Object, name: &str) -> String {
+        let name = self.claim(name);
+        // Reserve the slot so nested structs are emitted after their parent.
+        let index = self.structs.len();
+        self.structs.push(String::new());
+
+        let required: Vec<&str> = match node.get("required") {
+            Some(Value::Array(keys)) => keys.iter().filter_map(Value::as_string).collect(),
+            _ => Vec::new(),
+        };
+
+        let mut body = String::new();
+        let _ = writeln!(
+            body,
+            "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\npub struct {name} {{"
+        );
+        if let Some(Value::Object(properties)) = node.get("properties") {
+            for (key, property) in properties {
+                let field = field_name(key);
+                let mut ty = self.type_for(property, &format!("{name}{}", type_name(key)));
+                if !required.contains(&key.as_str()) && !ty.starts_with("Option<") {
+                    ty = format!("Option<{ty}>");
+                }
+                if field.trim_start_matches("r#") != key {
+                    let _ = writeln!(body, "    #[serde(rename = {key:?})]");
+                }
+                if ty.starts_with("Option<") {
+                    let _ = writeln!(
+                        body,
+                        "    #[serde(default, skip_serializing_if = \"Option::is_none\")]"
+                    );
+                }
+                let _ = writeln!(body, "    pub {field}: {ty},");
+            }
+        }
+        body.push_str("}\n");
+
+        if let Some(slot) = self.structs.get_mut(index) {
+            *slot = body;
+        }
+        name
+    }
+}
+
+/// `PascalCase` type name derived from a key
+fn type_name(key: &str) -> String {
+    let camel = convert_key(&sanitize(key), KeyCase::Camel);
+    let mut chars = camel.trim_start_matches('_').chars();
+    let name: String = match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => "Field".to_string(),
+    };
+    if name.starts_with(|ch: char| ch.is_ascii_digit()) {
+        format!("T{name}")
+    } else {
+        name
+    }
+}
+
+/// `snake_case` field identifier derived from a key
+fn field_name(key: &str) -> String {
+    let snake = convert_key(&sanitize(key), KeyCase::Snake);
+    if snake.is_empty() || snake.chars().all(|ch| ch == '_') {
+        return "field".to_string();
+    }
+    if snake.starts_with(|ch: char| ch.is_ascii_digit()) {
+        return format!("_{snake}");
+    }
+    if KEYWORDS.contains(&snake.as_str()) {
+        return match snake.as_str() {
+            "self" | "Self" | "super" | "crate" => format!("{snake}_"),
+            _ => format!("r#{snake}"),
+        };
+    }
+    snake
+}
+
+/// Replace characters that cannot appear in identifiers with `_`
+fn sanitize(key: &str) -> String {
+    key.chars()
+        .map(|ch| if ch.is_alphanumeric() { ch } else { '_' })
+        .collect()
+}
@@ -0,0 +1,322 @@
+//! Value comparison with mismatch reporting
+//!
+//! [`values_equal`] answers whether two documents match;
+//! [`values_diff_first_mismatch`] also says where they diverge, which keeps
+//! assertion failures on large documents readable. The `_with` variants take
+//! [`CompareOptions`] for looser semantics, e.g. in cross-format round trips.
+//!
+//! Numbers are stored as `f64`, so integer `1` and float `1.0` always compare
+//! equal. Generators that quote every scalar write `"8080"` for `8080`;
+//! [`CompareOptions::coerce_scalars`] matches those against typed values.
+
+use std::fmt;
+
+use crate::convert::{Format, serialize};
+#[cfg(feature = "toml")]
+use crate::value::TomlDatetime;
+use crate::value::{Object, Value, canonical_number};
+
+/// One step in a path through a document
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathSegment {
+    /// Object key
+    Key(String),
+    /// Array index
+    Index(usize),
+}
+
+/// Location and values of the first difference between two documents
+#[derive(Clone, Debug, PartialEq)]
+pub struct PathedMismatch {
+    /// Path from the root to the differing value
+    pub path: Vec<PathSegment>,
+    /// Value on the left side, `None` if the key or index is missing
+    pub left: Option<Value>,
+    /// Value on the right side, `None` if the key or index is missing
+    pub right: Option<Value>,
+}
+
+impl PathedMismatch {
+    /// Path rendered as `$.key[0]["odd key"]`
+    pub fn path_string(&self) -> String {
+        let mut out = String::from("$");
+        for segment in &self.path {
+            match segment {
+                PathSegment::Key(key) if is_plain_key(key) => {
+                    out.push('.');
+                    out.push_str(key);
+                }
+                PathSegment::Key(key) => out.push_str(&format!("[{key:?}]")),
+                PathSegment::Index(index) => out.push_str(&format!("[{index}]")),
+            }
+        }
+        out
+    }
+}
+
+impl fmt::Display for PathedMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "mismatch at {}: left={} right={}",
+            self.path_string(),
+            describe(self.left.as_ref()),
+            describe(self.right.as_ref())
+        )
+    }
+}
+
+/// Options relaxing how values are compared
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CompareOptions {
+    /// Relative tolerance for numbers: `|a - b| <= epsilon * max(|a|, |b|, 1)`
+    pub epsilon: f64,
+    /// Treat objects with the same entries in a different order as equal
+    pub ignore_key_order: bool,
+    /// Treat a `null` entry and a missing key as equal
+    pub null_equals_missing: bool,
+    /// Treat arrays holding the same items in a different order as equal
+    pub ignore_array_order: bool,
+    /// Compare strings with numbers, booleans and datetimes by what they
+    /// spell, so `"8080"` matches `8080`
+    pub coerce_scalars: bool,
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CompareOptions {
+    /// Exact numbers, key order ignored, `null` distinct from missing, array
+    /// order and scalar types significant
+    pub const fn new() -> Self {
+        Self {
+            epsilon: 0.0,
+            ignore_key_order: true,
+            null_equals_missing: false,
+            ignore_array_order: false,
+            coerce_scalars: false,
+        }
+    }
+
+    /// Set the relative tolerance for numbers
+    pub const fn with_epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    /// Ignore (true) or enforce (false) key order
+    pub const fn with_ignore_key_order(mut self, ignore: bool) -> Self {
+        self.ignore_key_order = ignore;
+        self
+    }
+
+    /// Enable or disable treating `null` as a missing key
+    pub const fn with_null_equals_missing(mut self, enabled: bool) -> Self {
+        self.null_equals_missing = enabled;
+        self
+    }
+
+    /// Ignore (true) or enforce (false) the order of array items
+    pub const fn with_ignore_array_order(mut self, ignore: bool) -> Self {
+        self.ignore_array_order = ignore;
+        self
+    }
+
+    /// Enable or disable matching strings against other scalars
+    pub const fn with_coerce_scalars(mut self, enabled: bool) -> Self {
+        self.coerce_scalars = enabled;
+        self
+    }
+
+    /// Equal as `==` on [`Value`] has it, where NaN equals NaN and `-0.0`
+    /// equals `0.0`, or both finite and within `epsilon`
+    fn numbers_equal(&self, a: f64, b: f64) -> bool {
+        let (a, b) = (canonical_number(a), canonical_number(b));
+        if a == b || (a.is_nan() && b.is_nan()) {
+            return true;
+        }
+        a.is_finite()
+            && b.is_finite()
+            && (a - b).abs() <= self.epsilon * a.abs().max(b.abs()).max(1.0)
+    }
+}
+
+/// Returns true if `a` and `b` are structurally equal
+pub fn values_equal(a: &Value, b: &Value) -> bool {
+    values_diff_first_mismatch(a, b).is_none()
+}
+
+/// Returns true if `a` and `b` are equal under `options`
+pub fn values_equal_with(a: &Value, b: &Value, options: &CompareOptions) -> bool {
+    values_diff_first_mismatch_with(a, b, options).is_none()
+}
+
+/// Find the first position where `a` and `b` differ, in document order
+pub fn values_diff_first_mismatch(a: &Value, b: &Value) -> Option<PathedMismatch> {
+    values_diff_first_mismatch_with(a, b, &CompareOptions::default())
+}
+
+/// Find the first position where `a` and `b` differ under `options`
+pub fn values_diff_first_mismatch_with(
+    a: &Value,
+    b: &Value,
+    options: &CompareOptions,
+) -> Option<PathedMismatch> {
+    let mut path = Vec::new();
+    first_mismatch(a, b, options, &mut path)
+}
+
+fn first_mismatch(
+    a: &Value,
+    b: &Value,
+    options: &CompareOptions,
+    path: &mut Vec<PathSegment>,
+) -> Option<PathedMismatch> {
+    match (a, b) {
+        (Value::Number(left), Value::Number(right)) => {
+            if options.numbers_equal(*left, *right) {
+                None
+            } else {
+                Some(mismatch(path, Some(a), Some(b)))
+            }
+        }
+        (Value::Array(left), Value::Array(right)) if options.ignore_array_order => {
+            unordered_mismatch(&left.0, &right.0, options, path)
+        }
+        (Value::Array(left), Value::Array(right)) => {
+            for index in 0..left.len().max(right.len()) {
+                path.push(PathSegment::Index(index));
+                let found = match (left.get(index), right.get(index)) {
+                    (Some(l), Some(r)) => first_mismatch(l, r, options, path),
+                    (l, r) => Some(mismatch(path, l, r)),
+                };
+                path.pop();
+                if found.is_some() {
+                    return found;
+                }
+            }
+            None
+        }
+        (Value::Object(left), Value::Object(right)) => {
+            let extra = right.keys().filter(|key| !left.contains_key(key));
+            for key in left.keys().chain(extra) {
+                path.push(PathSegment::Key(key.clone()));
+                let found = match (left.get(key), right.get(key)) {
+                    (Some(l), Some(r)) => first_mismatch(l, r, options, path),
+                    (Some(Value::Null), None) | (None, Some(Value::Null))
+                        if options.null_equals_missing =>
+                    {
+                        None
+                    }
+                    (l, r) => Some(mismatch(path, l, r)),
+                };
+                path.pop();
+                if found.is_some() {
+                    return found;
+                }
+            }
+            if !options.ignore_key_order && !same_key_order(left, right, options) {
+                return Some(mismatch(path, Some(a), Some(b)));
+            }
+            None
+        }
+        _ if a == b => None,
+        (Value::String(text), other) | (other, Value::String(text))
+            if options.coerce_scalars && spells(text, other, options) =>
+        {
+            None
+        }
+        _ => Some(mismatch(path, Some(a), Some(b))),
+    }
+}
+
+/// First item of `left` with no equal item left in `right`, or the first
+/// unmatched item of `right`; each item is matched at most once
+fn unordered_mismatch(
+    left: &[Value],
+    right: &[Value],
+    options: &CompareOptions,
+    path: &mut Vec<PathSegment>,
+) -> Option<PathedMismatch> {
+    let mut unmatched: Vec<usize> = (0..right.len()).collect();
+    for (index, item) in left.iter().enumerate() {
+        let found = unmatched.iter().position(|candidate| {
+            right.get(*candidate).is_some_and(|other| {
+                first_mismatch(item, other, options, &mut Vec::new()).is_none()
+            })
+        });
+        match found {
+            Some(position) => {
+                unmatched.remove(position);
+            }
+            None => {
+                path.push(PathSegment::Index(index));
+                let found = mismatch(path, Some(item), None);
+                path.pop();
+                return Some(found);
+            }
+        }
+    }
+    let index = *unmatched.first()?;
+    path.push(PathSegment::Index(index));
+    let found = mismatch(path, None, right.get(index));
+    path.pop();
+    Some(found)
+}
+
+/// Whether `text` is how `other`, a scalar, would be written
+fn spells(text: &str, other: &Value, options: &CompareOptions) -> bool {
+    match other {
+        Value::Number(number) => text
+            .trim()
+            .parse::<f64>()
+            .is_ok_and(|parsed| options.numbers_equal(parsed, *number)),
+        Value::Bool(flag) => text.trim() == if *flag { "true" } else { "false" },
+        Value::Null => text.trim() == "null",
+        #[cfg(feature = "toml")]
+        Value::Datetime(datetime) => text
+            .trim()
+            .parse::<TomlDatetime>()
+            .is_ok_and(|parsed| parsed == *datetime),
+        #[cfg(not(feature = "toml"))]
+        Value::Datetime(datetime) => text.trim() == datetime.to_string(),
+        _ => false,
+    }
+}
+
+/// Whether keys present in both objects appear in the same relative order
+fn same_key_order(left: &Object, right: &Object, options: &CompareOptions) -> bool {
+    let relevant = |obj: &Object, other: &Object| -> Vec<String> {
+        obj.iter()
+            .filter(|(key, value)| {
+                other.contains_key(key) || !(options.null_equals_missing && value.is_null())
+            })
+            .map(|(key, _)| key.clone())
+            .collect()
+    };
+    relevant(left, right) == relevant(right, left)
+}
+
+fn mismatch(path: &[PathSegment], left: Option<&Value>, right: Option<&Value>) -> PathedMismatch {
+    PathedMismatch {
+        path: path.to_vec(),
+        left: left.cloned(),
+        right: right.cloned(),
+    }
+}
+
+fn is_plain_key(key: &str) -> bool {
+    !key.is_empty()
+        && !key.starts_with(|ch: char| ch.is_ascii_digit())
+        && key.chars().all(|ch| ch.is_alphanumeric() || ch == '_')
+}
+
+fn describe(value: Option<&Value>) -> String {
+    match value {
+        None => "<missing>".to_string(),
+        Some(value) => serialize(value, Format::Json).unwrap_or_else(|_| format!("{value:?}")),
+    }
+}
@@ -0,0 +1,140 @@
+//! Transparent gzip and zstd compression of files
+//!
+//! Files named like `data.json.gz` or `config.yaml.zst`, or whose bytes start
+//! with a gzip or zstd header, are decompressed as they are read by
+//! [`crate::parse_path`], [`crate::ops`] and the CLI, and written compressed
+//! when the output path asks for it. Reading and writing each compression
+//! needs its cargo feature (`gzip`, `zstd`); without it such a file fails
+//! with [`ErrorKind::FormatDisabled`] rather than being parsed as garbage.
+
+use std::borrow::Cow;
+use std::path::Path;
+
+use crate::error::{Error, ErrorKind, Result, Span};
+
+/// A compression applied to a whole file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    /// gzip (`.gz`)
+    Gzip,
+    /// Zstandard (`.zst`)
+    Zstd,
+}
+
+impl Compression {
+    /// The compression named by the last extension of `path`
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let ext = path.as_ref().extension()?.to_str()?;
+        match ext.to_ascii_lowercase().as_str() {
+            "gz" => Some(Self::Gzip),
+            "zst" => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+
+    /// The compression whose header `bytes` start with. Neither header is
+    /// valid UTF-8, so text is never mistaken for compressed data.
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(&[0x1f, 0x8b]) {
+            Some(Self::Gzip)
+        } else if bytes.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Self::Zstd)
+        } else {
+            None
+        }
+    }
+
+    /// The cargo feature that reads and writes this compression
+    pub const fn feature(self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+        }
+    }
+
+    /// Decompress a whole file
+    #[cfg_attr(not(any(feature = "gzip", feature = "zstd")), allow(unused_variables))]
+    pub fn decompress(self, bytes: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            #[cfg(feature = "gzip")]
+            Self::Gzip => {
+                use std::io::Read;
+                let mut out = Vec::new();
+                flate2::read::MultiGzDecoder::new(bytes)
+                    .read_to_end(&mut out)
+                    .map_err(|err| self.error(&err))?;
+                Ok(out)
+            }
+            #[cfg(feature = "zstd")]
+            Self::Zstd => zstd::stream::decode_all(bytes).map_err(|err| self.error(&err)),
+            #[allow(unreachable_patterns)]
+            _ => Err(self.disabled()),
+        }
+    }
+
+    /// Compress a whole file at the default level
+    #[cfg_attr(not(any(feature = "gzip", feature = "zstd")), allow(unused_variables))]
+    pub fn compress(self, bytes: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            #[cfg(feature = "gzip")]
+            Self::Gzip => {
+                use std::io::Write;
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(bytes).map_err(|err| self.error(&err))?;
+                encoder.finish().map_err(|err| self.error(&err))
+            }
+            #[cfg(feature = "zstd")]
+            Self::Zstd => zstd::stream::encode_all(bytes, 0).map_err(|err| self.error(&err)),
+            #[allow(unreachable_patterns)]
+            _ => Err(self.disabled()),
+        }
+    }
+
+    #[cfg_attr(not(any(feature = "gzip", feature = "zstd")), allow(dead_code))]
+    fn error(self, err: &std::io::Error) -> Error {
+        Error::with_message(
+            ErrorKind::InvalidToken,
+            Span::empty(),
+            format!("invalid {} data: {err}", self.feature()),
+        )
+    }
+
+    fn disabled(self) -> Error {
+        Error::new(
+            ErrorKind::FormatDisabled {
+                feature: self.feature().to_string(),
+            },
+            Span::empty(),
+        )
+    }
+}
+
+/// The contents of a file read from `path` (`None` for stdin), decompressed
+/// when its extension or header says it is compressed
+pub fn decode<'a>(path: Option<&Path>, bytes: &'a [u8]) -> Result<Cow<'a, [u8]>> {
+    match path
+        .and_then(Compression::from_path)
+        .or_else(|| Compression::detect(bytes))
+    {
+        Some(compression) => compression.decompress(bytes).map(Cow::Owned),
+        None => Ok(Cow::Borrowed(bytes)),
+    }
+}
+
+/// `bytes` compressed as the extension of `path` asks, if it does
+pub fn encode<'a>(path: &Path, bytes: &'a [u8]) -> Result<Cow<'a, [u8]>> {
+    match Compression::from_path(path) {
+        Some(compression) => compression.compress(bytes).map(Cow::Owned),
+        None => Ok(Cow::Borrowed(bytes)),
+    }
+}
+
+/// `path` without a compression extension, so `data.json.gz` reads as
+/// `data.json` when its format is inferred
+pub fn strip_extension(path: &Path) -> Cow<'_, Path> {
+    match Compression::from_path(path) {
+        Some(_) => Cow::Owned(path.with_extension("")),
+        None => Cow::Borrowed(path),
+    }
+}
@@ -0,0 +1,390 @@
+//! Layered configuration loading
+//!
+//! A [`ConfigLoader`] reads an ordered list of sources such as
+//! `defaults.toml`, `config.d/*.yaml` and `overrides.json`, merges them with
+//! later files taking precedence, expands `${NAME}` placeholders (see
+//! [`interpolate`](crate::interpolate)) and records the file and line each
+//! key came from.
+//!
+//! Sources may use `*` and `?` wildcards in their file name component; the
+//! matching files are loaded in lexical order, so `10-base.yaml` is applied
+//! before `20-site.yaml`. A plain path must exist unless it was added with
+//! [`ConfigLoader::optional`], while a pattern that matches nothing is skipped.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::convert::{ConvertOptions, parse_with_options};
+use crate::error::{Error, ErrorKind, Result, Span};
+use crate::input::{self, Utf8Mode};
+use crate::locate::{escape_token, key_lines};
+use crate::merge::MergeStrategy;
+use crate::ops::{options_for_path, read_with_format};
+use crate::value::{Object, Value};
+
+/// Builds a configuration from layered files
+#[derive(Clone, Debug)]
+pub struct ConfigLoader {
+    sources: Vec<Source>,
+    options: ConvertOptions,
+    strategy: MergeStrategy,
+    interpolate: bool,
+}
+
+#[derive(Clone, Debug)]
+struct Source {
+    pattern: PathBuf,
+    required: bool,
+}
+
+impl Default for ConfigLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConfigLoader {
+    /// Create a loader with no sources, deep merging and env interpolation
+    pub fn new() -> Self {
+        Self {
+            sources: Vec::new(),
+            options: ConvertOptions::default(),
+            strategy: MergeStrategy::Deep,
+            interpolate: true,
+        }
+    }
+
+    /// Append a path or glob pattern; plain paths must exist
+    pub fn source(mut self, pattern: impl AsRef<Path>) -> Self {
+        self.sources.push(Source {
+            pattern: pattern.as_ref().to_path_buf(),
+            required: true,
+        });
+        self
+    }
+
+    /// Append a path or glob pattern that is skipped when missing
+    pub fn optional(mut self, pattern: impl AsRef<Path>) -> Self {
+        self.sources.push(Source {
+            pattern: pattern.as_ref().to_path_buf(),
+            required: false,
+        });
+        self
+    }
+
+    /// Parser options used for every file
+    pub fn with_options(mut self, options: ConvertOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// How each file is merged onto the files before it
+    pub fn with_strategy(mut self, strategy: MergeStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Enable or disable `${NAME}` expansion from the process environment
+    pub fn with_interpolation(mut self, interpolate: bool) -> Self {
+        self.interpolate = interpolate;
+        self
+    }
+
+    /// Files the sources currently resolve to, in load order
+    pub fn files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for source in &self.sources {
+            if is_pattern(&source.pattern) {
+                files.extend(expand_pattern(&source.pattern)?);
+            } else if source.pattern.is_file() {
+                files.push(source.pattern.clone());
+            } else if source.required {
+                return Err(Error::with_message(
+                    ErrorKind::Io {
+                        path: source.pattern.display().to_string(),
+                    },
+                    Span::empty(),
+                    format!("{}: file not found", source.pattern.display()),
+                ));
+            }
+        }
+        Ok(files)
+    }
+
+    /// Load, interpolate and merge every file.
+    ///
+    /// Placeholders are expanded per file, so errors name the file they occur
+    /// in. With no files the result is an empty object.
+    pub fn load(&self) -> Result<LoadedConfig> {
+        let files = self.files()?;
+        let mut merged: Option<Value> = None;
+        let mut sources = BTreeMap::new();
+        let mut lines = Vec::with_capacity(files.len());
+
+        for (index, path) in files.iter().enumerate() {
+            let (format, input) = read_with_format(path, self.options.utf8)?;
+            let options = options_for_path(path, &self.options);
+            let mut value =
+                parse_with_options(&input, format, &options).map_err(|err| with_path(path, err))?;
+            if options.utf8 == Utf8Mode::Bytes {
+                input::restore_bytes(&mut value);
+            }
+            // Lines are best effort; the document itself already parsed.
+            lines.push(key_lines(&input, format, &options).unwrap_or_default());
+            if self.interpolate {
+                value
+                    .interpolate_env()
+                    .map_err(|err| with_path(path, err))?;
+            }
+            record(
+                &mut sources,
+                String::new(),
+                merged.as_ref(),
+                &value,
+                index,
+                self.strategy,
+            );
+            merged = Some(match merged {
+                Some(mut base) => {
+                    base.merge(value, self.strategy);
+                    base
+                }
+                None => value,
+            });
+        }
+
+        Ok(LoadedConfig {
+            value: merged.unwrap_or_else(|| Value::Object(Object::new())),
+            files,
+            sources,
+            lines,
+        })
+    }
+}
+
+/// Result of [`ConfigLoader::load`]: the merged value and where its keys came
+/// from
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoadedConfig {
+    value: Value,
+    files: Vec<PathBuf>,
+    sources: BTreeMap<String, usize>,
+    /// Key lines of each file, by JSON pointer
+    lines: Vec<BTreeMap<String, u32>>,
+}
+
+/// Where a merged value was defined
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Origin<'a> {
+    /// File that last set the value
+    pub path: &'a Path,
+    /// 1-based line of the key in that file, when known
+    pub line: Option<u32>,
+}
+
+impl fmt::Display for Origin<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{line}", self.path.display()),
+            None => write!(f, "{}", self.path.display()),
+        }
+    }
+}
+
+impl LoadedConfig {
+    /// The merged configuration
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// Take the merged configuration
+    pub fn into_value(self) -> Value {
+        self.value
+    }
+
+    /// Files that were loaded, in order
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// File that last set the value at a JSON pointer such as `/server/port`.
+    ///
+    /// Array elements report the file that set the array. Returns None when
+    /// nothing exists at `pointer`.
+    pub fn source(&self, pointer: &str) -> Option<&Path> {
+        self.provenance(pointer).map(|origin| origin.path)
+    }
+
+    /// File and line that last set the value at a JSON pointer such as
+    /// `/server/port`.
+    ///
+    /// The line is the one of the innermost key leading to the value in that
+    /// file, so array elements and TOML inline tables report the line of
+    /// their key. Returns None when nothing exists at `pointer`.
+    pub fn provenance(&self, pointer: &str) -> Option<Origin<'_>> {
+        resolve_pointer(&self.value, pointer)?;
+        let index = ancestors(pointer).find_map(|current| self.sources.get(current))?;
+        let line = self
+            .lines
+            .get(*index)
+            .and_then(|lines| ancestors(pointer).find_map(|current| lines.get(current).copied()));
+        Some(Origin {
+            path: self.files.get(*index)?,
+            line,
+        })
+    }
+
+    /// Every recorded JSON pointer with the file that set it, in pointer order
+    pub fn sources(&self) -> impl Iterator<Item = (&str, &Path)> {
+        self.sources.iter().filter_map(|(pointer, &index)| {
+            self.files
+                .get(index)
+                .map(|path| (pointer.as_str(), path.as_path()))
+        })
+    }
+}
+
+/// Attribute the keys of `overlay` to file `index`, mirroring how
+/// [`Value::merge`] combines it with `base`
+fn record(
+    sources: &mut BTreeMap<String, usize>,
+    pointer: String,
+    base: Option<&Value>,
+    overlay: &Value,
+    index: usize,
+    strategy: MergeStrategy,
+) {
+    let merges = pointer.is_empty() || strategy == MergeStrategy::Deep;
+    let base = match base {
+        Some(Value::Object(base)) if merges && overlay.is_object() => Some(base),
+        _ => {
+            let prefix = format!("{pointer}/");
+            sources.retain(|key, _| !key.starts_with(&prefix));
+            None
+        }
+    };
+    if let Value::Object(entries) = overlay {
+        for (key, value) in entries {
+            let child = format!("{pointer}/{}", escape_token(key));
+            record(
+                sources,
+                child,
+                base.and_then(|base| base.get(key)),
+                value,
+                index,
+                strategy,
+            );
+        }
+    }
+    sources.insert(pointer, index);
+}
+
+/// `pointer` followed by each of its parents, ending with the root
+fn ancestors(pointer: &str) -> impl Iterator<Item = &str> {
+    std::iter::successors(Some(pointer), |current| {
+        current.rsplit_once('/').map(|(parent, _)| parent)
+    })
+}
+
+fn resolve_pointer<'a>(value: &'a Value, pointer: &str) -> Option<&'a Value> {
+    if pointer.is_empty() {
+        return Some(value);
+    }
+    let mut current = value;
+    for token in pointer.strip_prefix('/')?.split('/') {
+        let token = token.replace("~1", "/").replace("~0", "~");
+        current = match current {
+            Value::Object(entries) => entries.get(&token)?,
+            Value::Array(items) => items.get(token.parse().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+fn is_pattern(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.contains(['*', '?']))
+}
+
+fn expand_pattern(pattern: &Path) -> Result<Vec<PathBuf>> {
+    let dir = match pattern.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let name = pattern
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let io_error = |err: std::io::Error| {
+        Error::with_message(
+            ErrorKind::Io {
+                path: dir.display().to_string(),
+            },
+            Span::empty(),
+            format!("{}: {err}", dir.display()),
+        )
+    };
+
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(io_error(err)),
+    };
+    let mut matches = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(io_error)?.path();
+        let matched = path
+            .file_name()
+            .and_then(|file| file.to_str())
+            .is_some_and(|file| wildcard_match(name, file));
+        if matched && path.is_file() {
+            matches.push(path);
+        }
+    }
+    matches.sort();
+    Ok(matches)
+}
+
+/// Match `text` against a pattern where `*` is any run of characters and `?`
+/// is exactly one character
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match (pattern.get(p), text.get(t)) {
+            (Some('*'), _) => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            (Some('?'), _) => {
+                p += 1;
+                t += 1;
+            }
+            (Some(expected), Some(actual)) if expected == actual => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern
+        .get(p..)
+        .is_some_and(|rest| rest.iter().all(|&c| c == '*'))
+}
+
+fn with_path(path: &Path, err: Error) -> Error {
+    err.map_message(|message| format!("{}: {message}", path.display()))
+}
@@ -1,16 +1,41 @@
 //! Format conversion utilities
 
+use crate::base64;
+#[cfg(feature = "xml")]
+use crate::converter::XmlMapping;
+use crate::converter::converter;
 use crate::csv::Parser as CsvParser;
+#[cfg(feature = "xml")]
 use crate::csv::infer_primitive_value;
 use crate::csv::parser::Config as CsvConfig;
 use crate::error::{Error, ErrorKind, Result, Span};
+use crate::input::Utf8Mode;
 use crate::json::{Config as JsonConfig, Parser as JsonParser};
-use crate::toml::Parser as TomlParser;
-use crate::value::{Array, Object, TomlDatetime, Value};
+#[cfg(any(feature = "toml", feature = "yaml"))]
+use crate::locate::escape_token;
+#[cfg(feature = "toml")]
+use crate::toml::{Config as TomlConfig, Parser as TomlParser};
+#[cfg(feature = "toml")]
+use crate::toml_compat::TomlCompat;
+#[cfg(any(feature = "toml", feature = "xml"))]
+use crate::value::Object;
+use crate::value::{Array, TomlDatetime, Value};
+#[cfg(feature = "xml")]
+use crate::xml;
+#[cfg(feature = "xml")]
 use crate::xml::model::{Content as XmlContent, Document as XmlDocument, Element as XmlElement};
-use crate::xml::parser::Parser as XmlParser;
-use crate::yaml::Parser as YamlParser;
+#[cfg(feature = "xml")]
+use crate::xml::parser::{Config as XmlConfig, Parser as XmlParser};
+#[cfg(feature = "xml")]
+use crate::xml::types::Types as XmlTypes;
+#[cfg(feature = "yaml")]
+use crate::yaml::{
+    Config as YamlConfig, Parser as YamlParser, Quote as YamlQuote, Quotes as YamlQuotes,
+    detect_indentation,
+};
+#[cfg(feature = "xml")]
 use indexmap::IndexMap;
+use time::UtcOffset;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Format {
@@ -26,6 +51,322 @@ pub enum Format {
 pub struct ConvertOptions {
     pub json: JsonConfig,
     pub csv: CsvConfig,
+    #[cfg(feature = "toml")]
+    pub toml: TomlConfig,
+    #[cfg(feature = "yaml")]
+    pub yaml: YamlConfig,
+    #[cfg(feature = "xml")]
+    pub xml: XmlConfig,
+    /// Types for XML text converted to other formats
+    #[cfg(feature = "xml")]
+    pub xml_types: XmlTypes,
+    /// Coercions for YAML → TOML conversion
+    #[cfg(feature = "toml")]
+    pub toml_compat: TomlCompat,
+    /// Output formatting
+    pub format: FormatOptions,
+    /// Handling of invalid UTF-8 in documents read from bytes or files
+    pub utf8: Utf8Mode,
+}
+
+/// How TOML strings are written
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TomlStringStyle {
+    /// Single-line basic strings with escaped newlines
+    #[default]
+    Escaped,
+    /// Multi-line basic strings (`"""…"""`) for strings containing newlines
+    MultilineBasic,
+    /// Multi-line literal strings (`'''…'''`) for strings containing newlines,
+    /// falling back to basic strings when the content cannot be written literally
+    MultilineLiteral,
+}
+
+/// How byte strings are written to JSON and TOML
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BytesEncoding {
+    /// Base64 string (RFC 4648, padded)
+    #[default]
+    Base64,
+    /// Array of byte values
+    Array,
+}
+
+/// How numbers without a fractional part (e.g. `8080.0`) are written
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IntegralFloats {
+    /// As integers (`8080`). TOML falls back to a float when the value does
+    /// not fit a 64-bit integer.
+    #[default]
+    Integer,
+    /// With a decimal point (`8080.0`)
+    Decimal,
+}
+
+/// JSON dialect written for [`Format::Json`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum JsonDialect {
+    /// Strict JSON (RFC 8259)
+    #[default]
+    Json,
+    /// JSON with comments, as read by VS Code settings files: allows trailing
+    /// commas
+    Jsonc,
+    /// JSON5: also allows unquoted keys, single-quoted and multi-line strings,
+    /// and writes non-finite numbers as `NaN`/`Infinity` instead of `null`
+    Json5,
+}
+
+/// Quote character for JSON5 strings and keys
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum JsonQuote {
+    /// `"…"`
+    #[default]
+    Double,
+    /// `'…'`, leaving double quotes in the content unescaped
+    Single,
+}
+
+/// Quote character around XML attribute values
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum XmlQuote {
+    /// `"…"`
+    #[default]
+    Double,
+    /// `'…'`, leaving double quotes in values unescaped
+    Single,
+}
+
+/// Where block sequences nested in a mapping put their dashes in YAML output
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum YamlSequenceStyle {
+    /// Dashes indented under the parent key (`key:\n  - item`)
+    #[default]
+    Indented,
+    /// Dashes aligned with the parent key (`key:\n- item`)
+    Aligned,
+}
+
+/// When YAML strings are quoted
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum YamlStringStyle {
+    /// Every string is double-quoted
+    #[default]
+    Always,
+    /// Strings are plain unless a YAML reader could take them for another
+    /// type (`no`, `on`, `1.0`, `2024-01-01`) or misread their characters
+    AsNeeded,
+    /// Strings keep the quotes they had in the source when a lossless
+    /// document is written back; others are quoted as needed
+    Preserve,
+}
+
+/// Output formatting options
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// How byte strings are written to JSON and TOML. YAML always uses
+    /// `!!binary`; CSV and XML always use base64.
+    pub bytes: BytesEncoding,
+    /// How TOML strings are written
+    pub toml_strings: TomlStringStyle,
+    /// Fold multi-line basic TOML strings at this many characters per line using
+    /// line-ending backslashes (0 disables folding). Also makes single-line
+    /// strings longer than the width multi-line under [`TomlStringStyle::MultilineBasic`].
+    pub toml_fold_width: usize,
+    /// Digits after the decimal point for numbers with a fractional part
+    /// (`None` writes the shortest representation that reads back exactly)
+    pub float_precision: Option<usize>,
+    /// Use scientific notation once the decimal exponent reaches this magnitude,
+    /// e.g. 6 writes `1234567` as `1.234567e6` and `0.0000012` as `1.2e-6`
+    /// (`None` never does)
+    pub exponent_threshold: Option<u32>,
+    /// How numbers without a fractional part are written
+    pub integral_floats: IntegralFloats,
+    /// Indent JSON output by two spaces per level instead of writing one line
+    pub pretty: bool,
+    /// JSON dialect to write; the `json_*` options below only apply to the
+    /// dialects that allow them
+    pub json_dialect: JsonDialect,
+    /// Write a comma after the last entry of indented arrays and objects
+    /// (JSONC and JSON5, pretty output only)
+    pub json_trailing_commas: bool,
+    /// Leave object keys that are identifiers unquoted (JSON5)
+    pub json_unquoted_keys: bool,
+    /// Quote character for strings and quoted keys (JSON5)
+    pub json_quote: JsonQuote,
+    /// Continue strings on the next line after each newline with a
+    /// line-ending backslash (JSON5)
+    pub json_multiline_strings: bool,
+    /// Spaces per YAML nesting level (`None` keeps the source file's width
+    /// when reformatting YAML, otherwise 2)
+    pub yaml_indent: Option<usize>,
+    /// Placement of YAML sequence dashes (`None` keeps the source file's style
+    /// when reformatting YAML, otherwise indented)
+    pub yaml_sequences: Option<YamlSequenceStyle>,
+    /// When YAML strings are quoted; keys are quoted as needed
+    pub yaml_strings: YamlStringStyle,
+    /// Quote character around XML attribute values
+    pub xml_quote: XmlQuote,
+    /// Write characters outside ASCII in XML text and attribute values as
+    /// character references (`&#xE9;`), for readers that mishandle UTF-8
+    pub xml_ascii: bool,
+    /// Offset given to local date-times, e.g. TOML `1979-05-27T07:32:00`
+    /// (`None` writes them without one)
+    pub datetime_local_offset: Option<UtcOffset>,
+    /// Write date-times that have an offset in UTC, after
+    /// [`Self::datetime_local_offset`] is applied
+    pub datetime_utc: bool,
+}
+
+impl FormatOptions {
+    /// Create default formatting options
+    pub const fn new() -> Self {
+        Self {
+            bytes: BytesEncoding::Base64,
+            toml_strings: TomlStringStyle::Escaped,
+            toml_fold_width: 0,
+            float_precision: None,
+            exponent_threshold: None,
+            integral_floats: IntegralFloats::Integer,
+            pretty: false,
+            json_dialect: JsonDialect::Json,
+            json_trailing_commas: false,
+            json_unquoted_keys: false,
+            json_quote: JsonQuote::Double,
+            json_multiline_strings: false,
+            yaml_indent: None,
+            yaml_sequences: None,
+            yaml_strings: YamlStringStyle::Always,
+            xml_quote: XmlQuote::Double,
+            xml_ascii: false,
+            datetime_local_offset: None,
+            datetime_utc: false,
+        }
+    }
+
+    /// Set how byte strings are written to JSON and TOML
+    pub const fn with_bytes(mut self, encoding: BytesEncoding) -> Self {
+        self.bytes = encoding;
+        self
+    }
+
+    /// Set how TOML strings are written
+    pub const fn with_toml_strings(mut self, style: TomlStringStyle) -> Self {
+        self.toml_strings = style;
+        self
+    }
+
+    /// Set the TOML multi-line string fold width
+    pub const fn with_toml_fold_width(mut self, width: usize) -> Self {
+        self.toml_fold_width = width;
+        self
+    }
+
+    /// Set the digits written after the decimal point
+    pub const fn with_float_precision(mut self, precision: Option<usize>) -> Self {
+        self.float_precision = precision;
+        self
+    }
+
+    /// Set the exponent magnitude at which scientific notation is used
+    pub const fn with_exponent_threshold(mut self, threshold: Option<u32>) -> Self {
+        self.exponent_threshold = threshold;
+        self
+    }
+
+    /// Set how numbers without a fractional part are written
+    pub const fn with_integral_floats(mut self, style: IntegralFloats) -> Self {
+        self.integral_floats = style;
+        self
+    }
+
+    /// Set whether JSON output is indented
+    pub const fn with_pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+
+    /// Set the JSON dialect to write
+    pub const fn with_json_dialect(mut self, dialect: JsonDialect) -> Self {
+        self.json_dialect = dialect;
+        self
+    }
+
+    /// Set whether indented JSONC/JSON5 output ends containers with a comma
+    pub const fn with_json_trailing_commas(mut self, trailing: bool) -> Self {
+        self.json_trailing_commas = trailing;
+        self
+    }
+
+    /// Set whether JSON5 identifier keys are written without quotes
+    pub const fn with_json_unquoted_keys(mut self, unquoted: bool) -> Self {
+        self.json_unquoted_keys = unquoted;
+        self
+    }
+
+    /// Set the JSON5 quote character
+    pub const fn with_json_quote(mut self, quote: JsonQuote) -> Self {
+        self.json_quote = quote;
+        self
+    }
+
+    /// Set whether JSON5 strings continue across lines at newlines
+    pub const fn with_json_multiline_strings(mut self, multiline: bool) -> Self {
+        self.json_multiline_strings = multiline;
+        self
+    }
+
+    /// Set the spaces per YAML nesting level
+    pub const fn with_yaml_indent(mut self, indent: Option<usize>) -> Self {
+        self.yaml_indent = indent;
+        self
+    }
+
+    /// Set where YAML sequence dashes go
+    pub const fn with_yaml_sequences(mut self, style: Option<YamlSequenceStyle>) -> Self {
+        self.yaml_sequences = style;
+        self
+    }
+
+    /// Set when YAML strings are quoted
+    pub const fn with_yaml_strings(mut self, style: YamlStringStyle) -> Self {
+        self.yaml_strings = style;
+        self
+    }
+
+    /// Set the offset given to local date-times
+    pub const fn with_datetime_local_offset(mut self, offset: Option<UtcOffset>) -> Self {
+        self.datetime_local_offset = offset;
+        self
+    }
+
+    /// Set whether date-times with an offset are written in UTC
+    pub const fn with_datetime_utc(mut self, utc: bool) -> Self {
+        self.datetime_utc = utc;
+        self
+    }
+
+    /// Set the quote character around XML attribute values
+    pub const fn with_xml_quote(mut self, quote: XmlQuote) -> Self {
+        self.xml_quote = quote;
+        self
+    }
+
+    /// Set whether non-ASCII characters in XML are written as references
+    pub const fn with_xml_ascii(mut self, ascii: bool) -> Self {
+        self.xml_ascii = ascii;
+        self
+    }
+
+    /// Fill the YAML indentation options left unset with the style used by
+    /// the YAML document `input`
+    #[cfg(feature = "yaml")]
+    pub fn with_yaml_style_of(mut self, input: &str) -> Self {
+        let detected = detect_indentation(input);
+        self.yaml_indent = self.yaml_indent.or(detected.width);
+        self.yaml_sequences = self.yaml_sequences.or(detected.sequences);
+        self
+    }
 }
 
 /// Convert between supported formats
@@ -34,65 +375,203 @@ pub fn convert(input: &str, from: Format, to: Format) -> Result<String> {
 }
 
 /// Convert between supported formats with options
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(input, options), fields(len = input.len()))
+)]
 pub fn convert_with_options(
     input: &str,
     from: Format,
     to: Format,
     options: &ConvertOptions,
 ) -> Result<String> {
-    if from == to {
-        if from == Format::Json
-            && (options.json.allow_comments || options.json.allow_trailing_commas)
-        {
-            let value = parse_value(input, from, options)?;
-            return serialize_value(&value, to);
-        }
-        return Ok(input.to_string());
-    }
+    converter(from, to).convert(input, options)
+}
 
-    match (from, to) {
-        (Format::Csv, Format::Xml) => {
-            let value = parse_value(input, from, options)?;
-            let doc = csv_value_to_xml(&value)?;
-            Ok(serialize_xml(&doc))
-        }
-        (Format::Xml, Format::Csv) => {
-            let mut parser = XmlParser::new(input.as_bytes());
-            let doc = parser.parse()?;
-            let value = xml_to_csv_value(&doc)?;
-            serialize_value(&value, to)
-        }
-        (Format::Xml, _) => {
-            let mut parser = XmlParser::new(input.as_bytes());
+/// Parse input in any supported format into a [`Value`].
+///
+/// XML documents use the same mapping as XML conversion (`@attributes`/`#text`).
+pub fn parse_with_options(input: &str, format: Format, options: &ConvertOptions) -> Result<Value> {
+    match format {
+        #[cfg(feature = "xml")]
+        Format::Xml => {
+            let mut parser = XmlParser::with_config(input.as_bytes(), options.xml);
             let doc = parser.parse()?;
-            let value = xml_to_value(&doc);
-            serialize_value(&value, to)
-        }
-        (_, Format::Xml) => {
-            let value = parse_value(input, from, options)?;
-            let doc = value_to_xml(&value);
-            Ok(serialize_xml(&doc))
+            xml_to_value(&doc, &XmlMapping::default(), &options.xml_types)
         }
-        _ => {
-            let value = parse_value(input, from, options)?;
-            let value = normalize_for_target(value, from, to);
-            serialize_value(&value, to)
+        _ => parse_value(input, format, options),
+    }
+}
+
+/// Check that `input` is a valid document in `format` without keeping it.
+///
+/// JSON and CSV are checked without building any values, so this is the
+/// cheaper choice when only the verdict matters. The other formats are parsed
+/// and dropped; see each parser's `validate` for why.
+pub fn validate(input: &str, format: Format, options: &ConvertOptions) -> Result<()> {
+    match format {
+        Format::Json => JsonParser::with_config(input.as_bytes(), options.json).validate(),
+        Format::Csv => CsvParser::with_config(input.as_bytes(), options.csv).validate(),
+        #[cfg(feature = "toml")]
+        Format::Toml => TomlParser::with_config(input.as_bytes(), options.toml).validate(),
+        #[cfg(feature = "yaml")]
+        Format::Yaml => YamlParser::with_config(input.as_bytes(), options.yaml).validate(),
+        #[cfg(feature = "xml")]
+        Format::Xml => XmlParser::with_config(input.as_bytes(), options.xml).validate(),
+        #[cfg(not(feature = "toml"))]
+        Format::Toml => Err(format.disabled()),
+        #[cfg(not(feature = "yaml"))]
+        Format::Yaml => Err(format.disabled()),
+        #[cfg(not(feature = "xml"))]
+        Format::Xml => Err(format.disabled()),
+    }
+}
+
+/// Serialize a [`Value`] to any supported format.
+pub fn serialize(value: &Value, format: Format) -> Result<String> {
+    serialize_with_options(value, format, &FormatOptions::default())
+}
+
+/// Serialize a [`Value`] to any supported format with formatting options.
+pub fn serialize_with_options(
+    value: &Value,
+    format: Format,
+    options: &FormatOptions,
+) -> Result<String> {
+    format.serializer().serialize(value, options)
+}
+
+/// Writes a [`Value`] as a document in one output format.
+///
+/// Every built-in format has one, see [`Format::serializer`], so code that
+/// emits documents can take any of them without matching on [`Format`].
+pub trait Serializer {
+    /// Write `value` using `options`; options for other formats are ignored
+    fn serialize(&self, value: &Value, options: &FormatOptions) -> Result<String>;
+}
+
+/// JSON output (and JSONC/JSON5 per [`FormatOptions::json_dialect`])
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct JsonSerializer;
+
+/// CSV output for an array of flat records
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CsvSerializer;
+
+/// TOML output for a table at the root
+#[cfg(feature = "toml")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TomlSerializer;
+
+/// YAML output
+#[cfg(feature = "yaml")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct YamlSerializer;
+
+/// XML output
+#[cfg(feature = "xml")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct XmlSerializer;
+
+impl Serializer for JsonSerializer {
+    fn serialize(&self, value: &Value, options: &FormatOptions) -> Result<String> {
+        if options.pretty {
+            Ok(serialize_json_pretty(value, options, 0))
+        } else {
+            Ok(serialize_json(value, options))
         }
     }
 }
 
-fn normalize_for_target(value: Value, from: Format, to: Format) -> Value {
-    match (from, to, value) {
-        (Format::Csv, Format::Toml, Value::Array(rows)) => {
-            let mut root = Object::new();
-            root.insert("rows", Value::Array(rows));
-            Value::Object(root)
+impl Serializer for CsvSerializer {
+    fn serialize(&self, value: &Value, options: &FormatOptions) -> Result<String> {
+        serialize_csv(value, options)
+    }
+}
+
+#[cfg(feature = "toml")]
+impl Serializer for TomlSerializer {
+    fn serialize(&self, value: &Value, options: &FormatOptions) -> Result<String> {
+        serialize_toml(value, options)
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl Serializer for YamlSerializer {
+    fn serialize(&self, value: &Value, options: &FormatOptions) -> Result<String> {
+        Ok(serialize_yaml(value, options, None))
+    }
+}
+
+#[cfg(feature = "xml")]
+impl Serializer for XmlSerializer {
+    fn serialize(&self, value: &Value, options: &FormatOptions) -> Result<String> {
+        xml::writer::to_string_with_options(&value_to_xml(value, &XmlMapping::default()), options)
+    }
+}
+
+/// Stands in for the serializer of a format whose cargo feature is off
+#[cfg(not(all(feature = "toml", feature = "yaml", feature = "xml")))]
+struct DisabledSerializer(Format);
+
+#[cfg(not(all(feature = "toml", feature = "yaml", feature = "xml")))]
+impl Serializer for DisabledSerializer {
+    fn serialize(&self, _value: &Value, _options: &FormatOptions) -> Result<String> {
+        Err(self.0.disabled())
+    }
+}
+
+impl Format {
+    /// The serializer writing this format
+    pub fn serializer(self) -> &'static dyn Serializer {
+        match self {
+            Self::Json => &JsonSerializer,
+            Self::Csv => &CsvSerializer,
+            #[cfg(feature = "toml")]
+            Self::Toml => &TomlSerializer,
+            #[cfg(feature = "yaml")]
+            Self::Yaml => &YamlSerializer,
+            #[cfg(feature = "xml")]
+            Self::Xml => &XmlSerializer,
+            #[cfg(not(feature = "toml"))]
+            Self::Toml => &DisabledSerializer(Self::Toml),
+            #[cfg(not(feature = "yaml"))]
+            Self::Yaml => &DisabledSerializer(Self::Yaml),
+            #[cfg(not(feature = "xml"))]
+            Self::Xml => &DisabledSerializer(Self::Xml),
         }
-        (_, _, value) => value,
+    }
+
+    /// Whether this format was compiled in. JSON and CSV always are; TOML,
+    /// YAML and XML need the cargo feature of the same name.
+    pub const fn is_enabled(self) -> bool {
+        match self {
+            Self::Json | Self::Csv => true,
+            Self::Toml => cfg!(feature = "toml"),
+            Self::Yaml => cfg!(feature = "yaml"),
+            Self::Xml => cfg!(feature = "xml"),
+        }
+    }
+
+    /// The error for using this format without its cargo feature
+    pub(crate) fn disabled(self) -> Error {
+        let feature = match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Toml => "toml",
+            Self::Yaml => "yaml",
+            Self::Xml => "xml",
+        };
+        Error::new(
+            ErrorKind::FormatDisabled {
+                feature: feature.to_string(),
+            },
+            Span::empty(),
+        )
     }
 }
 
-fn parse_value(input: &str, format: Format, options: &ConvertOptions) -> Result<Value> {
+pub(crate) fn parse_value(input: &str, format: Format, options: &ConvertOptions) -> Result<Value> {
     match format {
         Format::Json => {
             let mut parser = JsonParser::with_config(input.as_bytes(), options.json);
@@ -102,14 +581,23 @@ fn parse_value(input: &str, format: Format, options: &ConvertOptions) -> Result<
             let mut parser = CsvParser::with_config(input.as_bytes(), options.csv);
             parser.parse()
         }
+        #[cfg(feature = "toml")]
         Format::Toml => {
-            let mut parser = TomlParser::new(input.as_bytes());
+            let mut parser = TomlParser::with_config(input.as_bytes(), options.toml);
             parser.parse()
         }
+        #[cfg(feature = "yaml")]
         Format::Yaml => {
-            let mut parser = YamlParser::new(input.as_bytes());
+            let mut parser = YamlParser::with_config(input.as_bytes(), options.yaml);
             parser.parse()
         }
+        #[cfg(not(feature = "toml"))]
+        Format::Toml => Err(format.disabled()),
+        #[cfg(not(feature = "yaml"))]
+        Format::Yaml => Err(format.disabled()),
+        #[cfg(not(feature = "xml"))]
+        Format::Xml => Err(format.disabled()),
+        #[cfg(feature = "xml")]
         Format::Xml => Err(Error::with_message(
             ErrorKind::InvalidToken,
             Span::empty(),
@@ -118,21 +606,7 @@ fn parse_value(input: &str, format: Format, options: &ConvertOptions) -> Result<
     }
 }
 
-fn serialize_value(value: &Value, format: Format) -> Result<String> {
-    match format {
-        Format::Json => Ok(serialize_json(value)),
-        Format::Csv => serialize_csv(value),
-        Format::Toml => serialize_toml(value),
-        Format::Yaml => Ok(serialize_yaml(value, 0)),
-        Format::Xml => Err(Error::with_message(
-            ErrorKind::InvalidToken,
-            Span::empty(),
-            "xml requires xml serializer".to_string(),
-        )),
-    }
-}
-
-fn serialize_csv(value: &Value) -> Result<String> {
+fn serialize_csv(value: &Value, options: &FormatOptions) -> Result<String> {
     let mut owned_rows = Array::new();
     let rows = match value {
         Value::Array(rows) => rows,
@@ -158,13 +632,17 @@ fn serialize_csv(value: &Value) -> Result<String> {
     }
 
     let mut headers = Vec::new();
-    for row in rows {
+    for (index, row) in rows.iter().enumerate() {
         let obj = row.as_object().ok_or_else(|| {
             Error::with_message(
                 ErrorKind::InvalidToken,
                 Span::empty(),
-                "csv output requires array of objects".to_string(),
+                format!(
+                    "csv output requires array of objects, found {}",
+                    row.type_name()
+                ),
             )
+            .context(format!("while converting item [{index}] to a row"))
         })?;
 
         for key in obj.keys() {
@@ -206,18 +684,15 @@ fn serialize_csv(value: &Value) -> Result<String> {
                     Value::Bool(boolean) => boolean.to_string(),
                     Value::Number(number) => {
                         if number.is_finite() {
-                            if number.fract() == 0.0 {
-                                format!("{number:.0}")
-                            } else {
-                                number.to_string()
-                            }
+                            format_number(*number, Format::Csv, options)
                         } else {
                             String::new()
                         }
                     }
                     Value::String(text) => text.clone(),
-                    Value::Datetime(dt) => format_datetime(dt),
-                    Value::Array(_) | Value::Object(_) => serialize_json(value),
+                    Value::Datetime(dt) => format_datetime_with(dt, options),
+                    Value::Bytes(bytes) => base64::encode(bytes),
+                    Value::Array(_) | Value::Object(_) => serialize_json(value, options),
                 };
                 if matches!(value, Value::String(_)) {
                     escape_csv_force_quoted(&cell)
@@ -246,31 +721,189 @@ fn escape_csv_force_quoted(input: &str) -> String {
     format!("\"{}\"", input.replace('"', "\"\""))
 }
 
-fn serialize_json(value: &Value) -> String {
-    match value {
-        Value::Null => "null".to_string(),
-        Value::Bool(b) => b.to_string(),
-        Value::Number(n) => {
-            if n.is_finite() {
-                n.to_string()
-            } else {
-                "null".to_string()
+/// Byte strings as the value JSON and TOML write in their place
+fn bytes_to_value(bytes: &[u8], options: &FormatOptions) -> Value {
+    match options.bytes {
+        BytesEncoding::Base64 => Value::String(base64::encode(bytes)),
+        BytesEncoding::Array => Value::Array(
+            bytes
+                .iter()
+                .copied()
+                .map(|byte| Value::from(u32::from(byte)))
+                .collect(),
+        ),
+    }
+}
+
+pub(crate) fn serialize_json(value: &Value, options: &FormatOptions) -> String {
+    write_json(value, options, None)
+}
+
+fn serialize_json_pretty(value: &Value, options: &FormatOptions, indent: usize) -> String {
+    write_json(value, options, Some(indent))
+}
+
+/// An array or object being written, with its remaining entries
+enum JsonFrame<'v> {
+    Array(std::slice::Iter<'v, Value>, bool),
+    Object(indexmap::map::Iter<'v, String, Value>, bool),
+}
+
+/// Write `value` as JSON, pretty-printed from `indent` when given.
+///
+/// Open containers live on an explicit stack rather than the call stack, so
+/// documents nested arbitrarily deep can be written.
+fn write_json(value: &Value, options: &FormatOptions, indent: Option<usize>) -> String {
+    let trailing = indent.is_some()
+        && options.json_trailing_commas
+        && options.json_dialect != JsonDialect::Json;
+    let newline = |output: &mut String, depth: usize| {
+        if let Some(indent) = indent {
+            output.push('\n');
+            output.push_str(&"  ".repeat(indent + depth));
+        }
+    };
+    let mut output = String::new();
+    let mut stack: Vec<JsonFrame<'_>> = Vec::new();
+    let mut next = Some(value);
+
+    loop {
+        match next.take() {
+            Some(Value::Array(arr)) if !arr.is_empty() => {
+                output.push('[');
+                stack.push(JsonFrame::Array(arr.iter(), true));
+            }
+            Some(Value::Object(obj)) if !obj.is_empty() => {
+                output.push('{');
+                stack.push(JsonFrame::Object(obj.iter(), true));
+            }
+            Some(Value::Array(_)) => output.push_str("[]"),
+            Some(Value::Object(_)) => output.push_str("{}"),
+            Some(Value::Bytes(bytes)) => output.push_str(&write_json(
+                &bytes_to_value(bytes, options),
+                options,
+                indent.map(|indent| indent + stack.len()),
+            )),
+            Some(value) => output.push_str(&json_scalar(value, options)),
+            None => {}
+        }
+
+        let depth = stack.len();
+        let Some(frame) = stack.last_mut() else {
+            break;
+        };
+        let (first, close) = match frame {
+            JsonFrame::Array(items, first) => {
+                next = items.next();
+                (first, ']')
+            }
+            JsonFrame::Object(entries, first) => {
+                next = entries.next().map(|(key, value)| {
+                    if !*first {
+                        output.push(',');
+                    }
+                    newline(&mut output, depth);
+                    output.push_str(&json_key(key, options));
+                    output.push_str(if indent.is_some() { ": " } else { ":" });
+                    value
+                });
+                if next.is_some() {
+                    *first = false;
+                    continue;
+                }
+                (first, '}')
             }
+        };
+        if next.is_some() {
+            if !*first {
+                output.push(',');
+            }
+            *first = false;
+            newline(&mut output, depth);
+        } else {
+            if trailing {
+                output.push(',');
+            }
+            newline(&mut output, depth - 1);
+            output.push(close);
+            stack.pop();
         }
-        Value::String(s) => format!("\"{}\"", escape_json(s)),
-        Value::Array(arr) => {
-            let items: Vec<String> = arr.iter().map(serialize_json).collect();
-            format!("[{}]", items.join(","))
+    }
+
+    output
+}
+
+fn json_scalar(value: &Value, options: &FormatOptions) -> String {
+    match value {
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) if n.is_finite() => format_number(*n, Format::Json, options),
+        Value::Number(n) if options.json_dialect == JsonDialect::Json5 => if n.is_nan() {
+            "NaN"
+        } else if n.is_sign_negative() {
+            "-Infinity"
+        } else {
+            "Infinity"
         }
-        Value::Object(obj) => {
-            let pairs: Vec<String> = obj
-                .iter()
-                .map(|(k, v)| format!("\"{}\":{}", escape_json(k), serialize_json(v)))
-                .collect();
-            format!("{{{}}}", pairs.join(","))
+        .to_string(),
+        Value::String(s) => json_string(s, options),
+        Value::Datetime(dt) => json_string(&format_datetime_with(dt, options), options),
+        _ => "null".to_string(),
+    }
+}
+
+/// Write an object key, unquoted when JSON5 output allows it
+pub(crate) fn json_key(key: &str, options: &FormatOptions) -> String {
+    if options.json_dialect == JsonDialect::Json5
+        && options.json_unquoted_keys
+        && is_json5_identifier(key)
+    {
+        key.to_string()
+    } else {
+        json_quoted(key, options, false)
+    }
+}
+
+/// Write a string value in the quoting style selected by `options`
+fn json_string(input: &str, options: &FormatOptions) -> String {
+    json_quoted(input, options, options.json_multiline_strings)
+}
+
+fn json_quoted(input: &str, options: &FormatOptions, multiline: bool) -> String {
+    if options.json_dialect != JsonDialect::Json5 {
+        return format!("\"{}\"", escape_json(input));
+    }
+    let quote = match options.json_quote {
+        JsonQuote::Double => '"',
+        JsonQuote::Single => '\'',
+    };
+    let mut result = String::with_capacity(input.len() + 2);
+    result.push(quote);
+    for ch in input.chars() {
+        match ch {
+            '\\' => result.push_str("\\\\"),
+            '\n' if multiline => result.push_str("\\n\\\n"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            ch if ch == quote => {
+                result.push('\\');
+                result.push(ch);
+            }
+            ch => result.push(ch),
         }
-        Value::Datetime(dt) => format!("\"{}\"", format_datetime(dt)),
     }
+    result.push(quote);
+    result
+}
+
+/// Whether `key` is an ECMAScript identifier name (ASCII subset) that JSON5
+/// accepts without quotes
+fn is_json5_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_' || ch == '$')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '$')
 }
 
 fn escape_string(input: &str) -> String {
@@ -292,9 +925,21 @@ fn escape_json(input: &str) -> String {
     escape_string(input)
 }
 
-fn serialize_toml(value: &Value) -> Result<String> {
+#[cfg(feature = "toml")]
+fn serialize_toml(value: &Value, options: &FormatOptions) -> Result<String> {
+    serialize_toml_with_keys(value, options, &|_, key| toml_key(key))
+}
+
+/// Serialize TOML, spelling each key with `keys`, which receives the JSON
+/// pointer of the keyed value and the key itself
+#[cfg(feature = "toml")]
+pub(crate) fn serialize_toml_with_keys(
+    value: &Value,
+    options: &FormatOptions,
+    keys: &dyn Fn(&str, &str) -> String,
+) -> Result<String> {
     match value {
-        Value::Object(obj) => Ok(serialize_toml_object(obj)),
+        Value::Object(obj) => Ok(serialize_toml_object(obj, options, keys)),
         _ => Err(Error::with_message(
             ErrorKind::InvalidToken,
             Span::empty(),
@@ -303,42 +948,232 @@ fn serialize_toml(value: &Value) -> Result<String> {
     }
 }
 
-fn serialize_toml_object(obj: &Object) -> String {
+#[cfg(feature = "toml")]
+fn serialize_toml_object(
+    obj: &Object,
+    options: &FormatOptions,
+    keys: &dyn Fn(&str, &str) -> String,
+) -> String {
     let mut lines = Vec::new();
     for (key, value) in obj.iter() {
-        lines.push(format!("{key} = {}", serialize_toml_value(value)));
+        let pointer = format!("/{}", escape_token(key));
+        lines.push(format!(
+            "{} = {}",
+            keys(&pointer, key),
+            serialize_toml_value(value, options, keys, &pointer)
+        ));
     }
     lines.join("\n")
 }
 
-fn serialize_toml_value(value: &Value) -> String {
+/// Write `value` as an inline TOML value
+#[cfg(feature = "toml")]
+pub(crate) fn toml_inline_value(value: &Value, options: &FormatOptions) -> String {
+    serialize_toml_value(value, options, &|_, key| toml_key(key), "")
+}
+
+/// Write an inline TOML value, keeping open arrays and inline tables on an
+/// explicit stack so deep nesting cannot overflow the call stack
+#[cfg(feature = "toml")]
+fn serialize_toml_value(
+    value: &Value,
+    options: &FormatOptions,
+    keys: &dyn Fn(&str, &str) -> String,
+    pointer: &str,
+) -> String {
+    enum Open<'v> {
+        Array(std::iter::Enumerate<std::slice::Iter<'v, Value>>),
+        Table(indexmap::map::Iter<'v, String, Value>),
+    }
+
+    let mut output = String::new();
+    let mut pointer = pointer.to_string();
+    // Each open container with the length of its own pointer and whether
+    // none of its entries is written yet
+    let mut stack: Vec<(Open<'_>, usize, bool)> = Vec::new();
+    let mut next = Some(value);
+
+    loop {
+        match next.take() {
+            Some(Value::Array(arr)) => {
+                output.push('[');
+                stack.push((Open::Array(arr.iter().enumerate()), pointer.len(), true));
+            }
+            Some(Value::Object(obj)) => {
+                output.push('{');
+                stack.push((Open::Table(obj.iter()), pointer.len(), true));
+            }
+            Some(Value::Bytes(bytes)) => output.push_str(&serialize_toml_value(
+                &bytes_to_value(bytes, options),
+                options,
+                keys,
+                &pointer,
+            )),
+            Some(value) => output.push_str(&toml_scalar(value, options)),
+            None => {}
+        }
+
+        let Some((open, base, first)) = stack.last_mut() else {
+            break;
+        };
+        pointer.truncate(*base);
+        let separator = if *first { "" } else { ", " };
+        match open {
+            Open::Array(items) => match items.next() {
+                Some((index, item)) => {
+                    output.push_str(separator);
+                    pointer.push_str(&format!("/{index}"));
+                    next = Some(item);
+                }
+                None => output.push(']'),
+            },
+            Open::Table(entries) => match entries.next() {
+                Some((key, value)) => {
+                    output.push_str(separator);
+                    pointer.push('/');
+                    pointer.push_str(&escape_token(key));
+                    output.push_str(&keys(&pointer, key));
+                    output.push_str(" = ");
+                    next = Some(value);
+                }
+                None => output.push('}'),
+            },
+        }
+        if next.is_some() {
+            *first = false;
+        } else {
+            stack.pop();
+        }
+    }
+
+    output
+}
+
+#[cfg(feature = "toml")]
+fn toml_scalar(value: &Value, options: &FormatOptions) -> String {
     match value {
-        Value::Null => "\"\"".to_string(),
         Value::Bool(b) => b.to_string(),
-        Value::Number(n) => {
-            if n.is_finite() {
-                n.to_string()
-            } else {
-                "nan".to_string()
-            }
+        Value::Number(n) if n.is_nan() => "nan".to_string(),
+        Value::Number(n) if n.is_infinite() => {
+            if n.is_sign_negative() { "-inf" } else { "inf" }.to_string()
         }
-        Value::String(s) => format!("\"{}\"", escape_toml(s)),
-        Value::Array(arr) => {
-            let items: Vec<String> = arr.iter().map(serialize_toml_value).collect();
-            format!("[{}]", items.join(", "))
+        Value::Number(n) => format_number(*n, Format::Toml, options),
+        Value::String(s) => toml_string(s, options),
+        Value::Datetime(dt) => format_datetime_with(dt, options),
+        _ => "\"\"".to_string(),
+    }
+}
+
+/// Write a TOML string in the style selected by `options`
+#[cfg(feature = "toml")]
+fn toml_string(input: &str, options: &FormatOptions) -> String {
+    let multiline = input.contains('\n');
+    match options.toml_strings {
+        TomlStringStyle::MultilineLiteral if multiline && is_toml_literal_safe(input) => {
+            format!("'''\n{input}'''")
         }
-        Value::Object(obj) => {
-            let entries: Vec<String> = obj
-                .iter()
-                .map(|(k, v)| format!("{k} = {}", serialize_toml_value(v)))
+        TomlStringStyle::MultilineBasic | TomlStringStyle::MultilineLiteral
+            if multiline
+                || (options.toml_strings == TomlStringStyle::MultilineBasic
+                    && options.toml_fold_width > 0
+                    && input.chars().count() > options.toml_fold_width) =>
+        {
+            let lines: Vec<String> = escape_toml_multiline(input)
+                .split('\n')
+                .map(|line| fold_toml_line(line, options.toml_fold_width))
                 .collect();
-            format!("{{{}}}", entries.join(", "))
+            format!("\"\"\"\n{}\"\"\"", lines.join("\n"))
+        }
+        _ => format!("\"{}\"", escape_toml(input)),
+    }
+}
+
+/// Whether `input` can be written verbatim between `'''` delimiters
+#[cfg(feature = "toml")]
+fn is_toml_literal_safe(input: &str) -> bool {
+    !input.contains("'''")
+        && !input.ends_with('\'')
+        && !input
+            .chars()
+            .any(|ch| ch.is_control() && !matches!(ch, '\n' | '\t'))
+}
+
+/// Escape the content of a multi-line basic string, keeping newlines.
+///
+/// Quotes are only escaped where they would otherwise form a closing `"""`
+/// or touch the closing delimiter.
+#[cfg(feature = "toml")]
+fn escape_toml_multiline(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => result.push_str("\\\\"),
+            '"' if matches!(chars.peek(), Some('"') | None) => result.push_str("\\\""),
+            '\n' | '\t' => result.push(ch),
+            '\r' => result.push_str("\\r"),
+            ch if ch.is_control() => result.push_str(&format!("\\u{:04X}", u32::from(ch))),
+            ch => result.push(ch),
         }
-        Value::Datetime(dt) => format_datetime(dt),
     }
+    result
 }
 
-fn csv_value_to_xml(value: &Value) -> Result<XmlDocument> {
+/// Fold an escaped line at spaces so pieces stay within `width` characters
+/// where possible.
+///
+/// Breaks are placed after a run of spaces, so the whitespace trimmed after a
+/// line-ending backslash is never part of the content.
+#[cfg(feature = "toml")]
+fn fold_toml_line(line: &str, width: usize) -> String {
+    if width == 0 || line.chars().count() <= width {
+        return line.to_string();
+    }
+
+    // Words with their trailing spaces; every word after the first starts
+    // with a non-space character.
+    let mut words: Vec<String> = Vec::new();
+    let mut previous = None;
+    for ch in line.chars() {
+        match words.last_mut() {
+            Some(word) if !(previous == Some(' ') && ch != ' ') => word.push(ch),
+            _ => words.push(ch.to_string()),
+        }
+        previous = Some(ch);
+    }
+
+    let mut pieces: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+    for word in words {
+        let word_len = word.chars().count();
+        if current_len > 0 && current_len + word_len > width {
+            pieces.push(std::mem::take(&mut current));
+            current_len = 0;
+        }
+        current.push_str(&word);
+        current_len += word_len;
+    }
+    pieces.push(current);
+    pieces.join("\\\n")
+}
+
+/// Quote keys that are not valid TOML bare keys
+#[cfg(feature = "toml")]
+pub(crate) fn toml_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-');
+    if bare {
+        key.to_string()
+    } else {
+        format!("\"{}\"", escape_toml(key))
+    }
+}
+
+#[cfg(feature = "xml")]
+pub(crate) fn csv_value_to_xml(value: &Value, mapping: &XmlMapping) -> Result<XmlDocument> {
     let rows = value.as_array().ok_or_else(|| {
         Error::with_message(
             ErrorKind::InvalidToken,
@@ -360,30 +1195,33 @@ fn csv_value_to_xml(value: &Value) -> Result<XmlDocument> {
         let mut row_children = Vec::new();
         for (key, value) in obj.iter() {
             let element = XmlElement {
-                name: key.clone(),
+                name: xml_name(key),
                 attributes: IndexMap::new(),
-                children: value_to_children(value),
+                children: xml_text(value).map(XmlContent::Text).into_iter().collect(),
             };
             row_children.push(XmlContent::Element(element));
         }
 
         children.push(XmlContent::Element(XmlElement {
-            name: "row".to_string(),
+            name: mapping.row.clone(),
             attributes: IndexMap::new(),
             children: row_children,
         }));
     }
 
-    Ok(XmlDocument {
-        root: XmlElement {
-            name: "root".to_string(),
-            attributes: IndexMap::new(),
-            children,
-        },
-    })
+    Ok(XmlDocument::new(XmlElement {
+        name: mapping.root.clone(),
+        attributes: IndexMap::new(),
+        children,
+    }))
 }
 
-fn xml_to_csv_value(doc: &XmlDocument) -> Result<Value> {
+#[cfg(feature = "xml")]
+pub(crate) fn xml_to_csv_value(
+    doc: &XmlDocument,
+    mapping: &XmlMapping,
+    types: &XmlTypes,
+) -> Result<Value> {
     let mut rows = Array::new();
 
     for child in &doc.root.children {
@@ -391,14 +1229,14 @@ fn xml_to_csv_value(doc: &XmlDocument) -> Result<Value> {
             continue;
         };
 
-        if row_element.name != "row" {
+        if row_element.name != mapping.row {
             continue;
         }
 
         let mut row = Object::new();
         for row_child in &row_element.children {
             if let XmlContent::Element(field) = row_child {
-                row.insert(field.name.clone(), xml_leaf_to_value(field)?);
+                row.insert(field.name.clone(), xml_leaf_to_value(field, types)?);
             }
         }
 
@@ -408,252 +1246,592 @@ fn xml_to_csv_value(doc: &XmlDocument) -> Result<Value> {
     Ok(Value::Array(rows))
 }
 
-fn xml_leaf_to_value(element: &XmlElement) -> Result<Value> {
-    if element.children.is_empty() {
-        return Ok(Value::Null);
-    }
-
-    if element.children.len() == 1 {
-        if let Some(XmlContent::Text(text)) = element.children.first() {
-            let trimmed = text.trim();
-            return Ok(
-                infer_primitive_value(trimmed).unwrap_or_else(|| Value::String(text.clone()))
-            );
+#[cfg(feature = "xml")]
+fn xml_leaf_to_value(element: &XmlElement, types: &XmlTypes) -> Result<Value> {
+    let mut text: Option<String> = None;
+    for child in &element.children {
+        match child {
+            XmlContent::Text(part) | XmlContent::CData(part) => {
+                text.get_or_insert_default().push_str(part);
+            }
+            XmlContent::Comment(_) | XmlContent::ProcessingInstruction { .. } => {}
+            XmlContent::Element(_) => {
+                return Err(Error::with_message(
+                    ErrorKind::InvalidToken,
+                    Span::empty(),
+                    "xml row fields must be simple leaf elements".to_string(),
+                ));
+            }
         }
     }
 
-    Err(Error::with_message(
-        ErrorKind::InvalidToken,
-        Span::empty(),
-        "xml row fields must be simple leaf elements".to_string(),
-    ))
+    let Some(text) = text else {
+        return Ok(Value::Null);
+    };
+    if types.hint(&element.name).is_some() {
+        return types.text_value(&element.name, &text);
+    }
+    let trimmed = text.trim();
+    Ok(infer_primitive_value(trimmed).unwrap_or(Value::String(text)))
 }
 
-fn escape_toml(input: &str) -> String {
+#[cfg(feature = "toml")]
+pub(crate) fn escape_toml(input: &str) -> String {
     escape_string(input)
 }
 
-fn serialize_yaml(value: &Value, indent: usize) -> String {
-    let pad = " ".repeat(indent);
-    match value {
-        Value::Null => format!("{pad}null"),
-        Value::Bool(b) => format!("{pad}{b}"),
-        Value::Number(n) => format!("{pad}{n}"),
-        Value::String(s) => format!("{pad}\"{}\"", escape_yaml(s)),
-        Value::Datetime(dt) => format!("{pad}{}", format_datetime(dt)),
-        Value::Array(arr) => arr
-            .iter()
-            .map(|v| {
-                let item = serialize_yaml(v, indent + 2);
-                format!("{pad}- {}", item.trim_start())
-            })
-            .collect::<Vec<_>>()
-            .join("\n"),
-        Value::Object(obj) => obj
-            .iter()
-            .map(|(k, v)| {
-                let value = serialize_yaml(v, indent + 2);
-                if matches!(v, Value::Array(_) | Value::Object(_)) {
-                    format!("{pad}{k}:\n{value}")
-                } else {
-                    format!("{pad}{k}: {}", value.trim_start())
+/// Write `value` as block YAML, keeping open sequences and mappings on an
+/// explicit stack so deep nesting cannot overflow the call stack. `quotes`,
+/// from a lossless parse, are the source quotes [`YamlStringStyle::Preserve`]
+/// keeps.
+#[cfg(feature = "yaml")]
+pub(crate) fn serialize_yaml(
+    value: &Value,
+    options: &FormatOptions,
+    quotes: Option<&YamlQuotes>,
+) -> String {
+    enum Open<'v> {
+        Sequence(std::iter::Enumerate<std::slice::Iter<'v, Value>>),
+        Mapping(indexmap::map::Iter<'v, String, Value>),
+    }
+
+    let width = options.yaml_indent.filter(|width| *width > 0).unwrap_or(2);
+    let quotes = quotes.filter(|_| options.yaml_strings == YamlStringStyle::Preserve);
+    let mut output = String::new();
+    // Each open collection with its indentation, its JSON pointer (kept only
+    // to look up `quotes`) and whether its first line is still to be
+    // written; that line continues a `- ` or `key: ` line when the
+    // collection was opened inline
+    let mut stack: Vec<(Open<'_>, usize, String, bool, bool)> = Vec::new();
+    let mut next = Some((value, 0, String::new(), false));
+
+    loop {
+        if let Some((value, indent, pointer, inline)) = next.take() {
+            let pad = if inline { 0 } else { indent };
+            match value {
+                Value::Array(arr) if !arr.is_empty() => {
+                    let open = Open::Sequence(arr.iter().enumerate());
+                    stack.push((open, indent, pointer, true, inline));
                 }
-            })
-            .collect::<Vec<_>>()
-            .join("\n"),
+                Value::Object(obj) if !obj.is_empty() => {
+                    stack.push((Open::Mapping(obj.iter()), indent, pointer, true, inline));
+                }
+                _ => {
+                    let quote = quotes.and_then(|quotes| quotes.strings.get(&pointer));
+                    output.push_str(&" ".repeat(pad));
+                    output.push_str(&yaml_scalar(value, options, quote.copied()));
+                }
+            }
+        }
+
+        let Some((open, indent, pointer, first, inline)) = stack.last_mut() else {
+            break;
+        };
+        let entry = match open {
+            Open::Sequence(items) => items.next().map(|(index, item)| (Err(index), item)),
+            Open::Mapping(entries) => entries.next().map(|(key, value)| (Ok(key), value)),
+        };
+        let Some((key, value)) = entry else {
+            stack.pop();
+            continue;
+        };
+        if !*first {
+            output.push('\n');
+        }
+        if !(*first && *inline) {
+            output.push_str(&" ".repeat(*indent));
+        }
+        *first = false;
+        let child_pointer = match (quotes, key) {
+            (None, _) => String::new(),
+            (Some(_), Ok(key)) => format!("{pointer}/{}", escape_token(key)),
+            (Some(_), Err(index)) => format!("{pointer}/{index}"),
+        };
+
+        let block = match key {
+            // Item content lines up after the "- " marker; nested
+            // sequences and some mappings start on their own line instead
+            Err(_) => {
+                output.push('-');
+                next = Some((value, *indent + 2, child_pointer, false));
+                yaml_block_item(value)
+            }
+            Ok(key) => {
+                let quote = quotes.and_then(|quotes| quotes.keys.get(&child_pointer));
+                output.push_str(&yaml_key(key, quote.copied()));
+                output.push(':');
+                let child = match value {
+                    Value::Array(_)
+                        if options.yaml_sequences == Some(YamlSequenceStyle::Aligned) =>
+                    {
+                        *indent
+                    }
+                    _ => *indent + width,
+                };
+                next = Some((value, child, child_pointer, false));
+                matches!(value, Value::Array(_) | Value::Object(_)) && !value.is_empty()
+            }
+        };
+        if block {
+            output.push('\n');
+        } else {
+            output.push(' ');
+            next = next.map(|(value, indent, pointer, _)| (value, indent, pointer, true));
+        }
     }
-}
 
-fn escape_yaml(input: &str) -> String {
-    escape_string(input)
+    output
 }
 
-fn format_datetime(dt: &TomlDatetime) -> String {
-    use time::format_description::well_known::Rfc3339;
-    use time::macros::format_description;
-    match dt {
-        TomlDatetime::OffsetDateTime(value) => value
-            .format(&Rfc3339)
-            .unwrap_or_else(|_| "1979-05-27T07:32:00Z".to_string()),
-        TomlDatetime::LocalDateTime(value) => value
-            .format(&format_description!(
-                "[year]-[month]-[day]T[hour]:[minute]:[second]"
-            ))
-            .unwrap_or_else(|_| "1979-05-27T07:32:00".to_string()),
-        TomlDatetime::LocalDate(value) => value
-            .format(&format_description!("[year]-[month]-[day]"))
-            .unwrap_or_else(|_| "1979-05-27".to_string()),
-        TomlDatetime::LocalTime(value) => value
-            .format(&format_description!("[hour]:[minute]:[second]"))
-            .unwrap_or_else(|_| "07:32:00".to_string()),
-    }
-}
-
-fn xml_to_value(doc: &XmlDocument) -> Value {
-    let mut root = Object::new();
-    root.insert(&doc.root.name, element_to_value(&doc.root));
-    Value::Object(root)
-}
-
-fn element_to_value(element: &XmlElement) -> Value {
-    let mut obj = Object::new();
+/// A mapping key, kept in the quotes it had in the source, otherwise quoted
+/// unless it is plain text every YAML reader takes for the same string key
+#[cfg(feature = "yaml")]
+fn yaml_key(key: &str, quote: Option<YamlQuote>) -> String {
+    let plain = key
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | ' '))
+        && !key.starts_with(['-', ' '])
+        && !key.ends_with(' ')
+        && !key.is_empty()
+        && !yaml_typed(key);
+    match quote {
+        Some(quote) => yaml_quoted(key, quote),
+        None if plain => key.to_string(),
+        None => format!("\"{}\"", escape_yaml(key)),
+    }
+}
 
-    if !element.attributes.is_empty() {
-        let mut attrs = Object::new();
-        for (key, value) in element.attributes.iter() {
-            attrs.insert(key, value.clone());
+#[cfg(feature = "yaml")]
+fn yaml_scalar(value: &Value, options: &FormatOptions, quote: Option<YamlQuote>) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) if n.is_nan() => ".nan".to_string(),
+        Value::Number(n) if n.is_infinite() => {
+            format!("{}.inf", if n.is_sign_negative() { "-" } else { "" })
         }
-        obj.insert("@attributes", Value::Object(attrs));
+        Value::Number(n) => format_number(*n, Format::Yaml, options),
+        Value::String(s) => match (options.yaml_strings, quote) {
+            (YamlStringStyle::Always, _) => format!("\"{}\"", escape_yaml(s)),
+            (YamlStringStyle::Preserve, Some(quote)) => yaml_quoted(s, quote),
+            _ if yaml_plain(s) => s.clone(),
+            _ => format!("\"{}\"", escape_yaml(s)),
+        },
+        Value::Datetime(dt) => format_datetime_with(dt, options),
+        Value::Bytes(bytes) => format!("!!binary {}", base64::encode(bytes)),
+        Value::Array(_) => "[]".to_string(),
+        Value::Object(_) => "{}".to_string(),
     }
+}
 
-    let mut text = String::new();
-    for child in &element.children {
-        if let XmlContent::Text(value) = child {
-            text.push_str(value);
+/// `text` in `quote`, or double quotes when single quotes cannot hold it:
+/// they have no escapes, and fold line breaks
+#[cfg(feature = "yaml")]
+fn yaml_quoted(text: &str, quote: YamlQuote) -> String {
+    match quote {
+        YamlQuote::Single if !text.chars().any(char::is_control) => {
+            format!("'{}'", text.replace('\'', "''"))
         }
+        _ => format!("\"{}\"", escape_yaml(text)),
     }
-    if !text.trim().is_empty() {
-        obj.insert("#text", Value::String(text));
+}
+
+/// Whether `text` can be written as a plain scalar and read back as the same
+/// string, by zparse and by YAML 1.1 readers alike
+#[cfg(feature = "yaml")]
+fn yaml_plain(text: &str) -> bool {
+    let Some(first) = text.chars().next() else {
+        return false;
+    };
+    let indicator = matches!(
+        first,
+        '-' | '?'
+            | ':'
+            | ','
+            | '['
+            | ']'
+            | '{'
+            | '}'
+            | '#'
+            | '&'
+            | '*'
+            | '!'
+            | '|'
+            | '>'
+            | '\''
+            | '"'
+            | '%'
+            | '@'
+            | '`'
+            | ' '
+    );
+    !indicator
+        && !text.ends_with([' ', ':'])
+        && !text.contains(": ")
+        && !text.contains(" #")
+        && !text.chars().any(char::is_control)
+        && !yaml_typed(text)
+}
+
+/// Whether some YAML reader resolves the plain scalar `text` to something
+/// other than a string: null, booleans including YAML 1.1's `yes`/`no`/`on`/
+/// `off`, and words starting like a number, which covers YAML 1.1 octal, hex,
+/// sexagesimal and timestamps
+#[cfg(feature = "yaml")]
+fn yaml_typed(text: &str) -> bool {
+    let keyword = matches!(
+        text.to_ascii_lowercase().as_str(),
+        "~" | "null" | "true" | "false" | "yes" | "no" | "on" | "off"
+    );
+    let mut chars = text.chars();
+    let numeric = match (chars.next(), chars.next()) {
+        (Some('+' | '-' | '.'), Some(second)) => second.is_ascii_digit(),
+        (Some(first), _) => first.is_ascii_digit(),
+        (None, _) => false,
+    } && !text.contains(char::is_whitespace);
+    keyword
+        || numeric
+        || !matches!(
+            crate::yaml::parser::parse_scalar_value(text),
+            Value::String(_)
+        )
+}
+
+#[cfg(feature = "yaml")]
+fn yaml_block_item(value: &Value) -> bool {
+    let nested = |v: &Value| matches!(v, Value::Array(_) | Value::Object(_)) && !v.is_empty();
+    match value {
+        Value::Array(arr) => !arr.is_empty(),
+        // A block under the last key is fine; one followed by further keys
+        // would leave those keys at a column the parser never saw
+        Value::Object(obj) => obj.values().rev().skip(1).any(nested),
+        _ => false,
     }
+}
 
-    for child in &element.children {
-        if let XmlContent::Element(child) = child {
-            let value = element_to_value(child);
-            if let Some(existing) = obj.get_mut(&child.name) {
-                match existing {
-                    Value::Array(items) => {
-                        items.push(value);
-                    }
-                    _ => {
-                        let previous = std::mem::replace(existing, Value::Null);
-                        *existing = Value::Array(vec![previous, value].into());
-                    }
-                }
-            } else {
-                obj.insert(&child.name, value);
-            }
-        }
+/// Write a finite number following `options` and the conventions of `format`.
+///
+/// Without a fixed precision, positional and scientific output both use the
+/// shortest digits that parse back to the same `f64`, so round trips are exact.
+fn format_number(n: f64, format: Format, options: &FormatOptions) -> String {
+    let scientific = format!("{n:e}");
+    let exponent = scientific
+        .rsplit_once('e')
+        .and_then(|(_, exponent)| exponent.parse::<i32>().ok())
+        .unwrap_or(0);
+    let integral = n.fract() == 0.0;
+
+    if let Some(threshold) = options.exponent_threshold
+        && n != 0.0
+        && exponent.unsigned_abs() >= threshold
+    {
+        return match options.float_precision {
+            Some(precision) if !integral => format!("{n:.precision$e}"),
+            _ => scientific,
+        };
+    }
+
+    if !integral {
+        return match options.float_precision {
+            Some(precision) => format!("{n:.precision$}"),
+            None => n.to_string(),
+        };
     }
 
-    if obj.is_empty() {
-        Value::Object(Object::new())
+    let digits = format!("{n:.0}");
+    // TOML integers are 64-bit; anything larger must be written as a float.
+    let fits_integer = format != Format::Toml
+        || (-9.223_372_036_854_776e18..9.223_372_036_854_776e18).contains(&n);
+    if options.integral_floats == IntegralFloats::Decimal || !fits_integer {
+        format!("{digits}.0")
     } else {
-        Value::Object(obj)
+        digits
     }
 }
 
-fn value_to_xml(value: &Value) -> XmlDocument {
-    let root = XmlElement {
-        name: "root".to_string(),
-        attributes: IndexMap::new(),
-        children: value_to_children(value),
-    };
-    XmlDocument { root }
+#[cfg(feature = "yaml")]
+fn escape_yaml(input: &str) -> String {
+    escape_string(input)
 }
 
-fn value_to_children(value: &Value) -> Vec<XmlContent> {
-    match value {
-        Value::Object(obj) => obj
-            .iter()
-            .flat_map(|(key, value)| value_to_elements(key, value))
-            .map(XmlContent::Element)
-            .collect(),
-        Value::Array(arr) => arr.iter().flat_map(value_to_children).collect(),
-        Value::String(text) => vec![XmlContent::Text(text.clone())],
-        Value::Number(n) => vec![XmlContent::Text(n.to_string())],
-        Value::Bool(b) => vec![XmlContent::Text(b.to_string())],
-        Value::Null => Vec::new(),
-        Value::Datetime(dt) => vec![XmlContent::Text(format_datetime(dt))],
+/// [`format_datetime`] after the datetime options in `options`
+fn format_datetime_with(dt: &TomlDatetime, options: &FormatOptions) -> String {
+    let mut dt = dt.clone();
+    if let Some(offset) = options.datetime_local_offset {
+        dt = dt.with_local_offset(offset);
     }
+    if options.datetime_utc {
+        dt = dt.to_utc();
+    }
+    format_datetime(&dt)
 }
 
-fn value_to_elements(name: &str, value: &Value) -> Vec<XmlElement> {
-    match value {
-        Value::Array(arr) => arr
-            .iter()
-            .flat_map(|value| value_to_elements(name, value))
-            .collect(),
-        Value::Object(obj) => {
-            let mut attributes = IndexMap::new();
-            let mut children = Vec::new();
-
-            if let Some(Value::Object(attrs)) = obj.get("@attributes") {
-                for (key, value) in attrs.iter() {
-                    if let Value::String(text) = value {
-                        attributes.insert(key.clone(), text.clone());
-                    } else {
-                        attributes.insert(key.clone(), serialize_json(value));
-                    }
-                }
+/// Write a datetime as TOML does: RFC 3339 with an offset, and the local
+/// kinds without fractional seconds
+pub(crate) fn format_datetime(dt: &TomlDatetime) -> String {
+    match dt {
+        TomlDatetime::OffsetDateTime(value) => {
+            let offset = value.offset();
+            if !(0..=9999).contains(&value.year()) || offset.seconds_past_minute() != 0 {
+                return "1979-05-27T07:32:00Z".to_string();
             }
-
-            if let Some(Value::String(text)) = obj.get("#text") {
-                children.push(XmlContent::Text(text.clone()));
+            let mut output = format!(
+                "{}T{}",
+                format_date(value.date()),
+                format_time(value.time())
+            );
+            if value.nanosecond() != 0 {
+                let fraction = format!("{:09}", value.nanosecond());
+                output.push('.');
+                output.push_str(fraction.trim_end_matches('0'));
             }
+            if offset.is_utc() {
+                output.push('Z');
+            } else {
+                output.push(if offset.is_negative() { '-' } else { '+' });
+                output.push_str(&format!(
+                    "{:02}:{:02}",
+                    offset.whole_hours().unsigned_abs(),
+                    offset.minutes_past_hour().unsigned_abs()
+                ));
+            }
+            output
+        }
+        TomlDatetime::LocalDateTime(value) => {
+            format!(
+                "{}T{}",
+                format_date(value.date()),
+                format_time(value.time())
+            )
+        }
+        TomlDatetime::LocalDate(value) => format_date(*value),
+        TomlDatetime::LocalTime(value) => format_time(*value),
+    }
+}
 
-            for (key, value) in obj.iter() {
-                if key == "@attributes" || key == "#text" {
-                    continue;
+fn format_date(date: time::Date) -> String {
+    let year = date.year();
+    let sign = if year < 0 { "-" } else { "" };
+    format!(
+        "{sign}{:04}-{:02}-{:02}",
+        year.unsigned_abs(),
+        u8::from(date.month()),
+        date.day()
+    )
+}
+
+fn format_time(time: time::Time) -> String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        time.hour(),
+        time.minute(),
+        time.second()
+    )
+}
+
+/// Convert an XML tree to a value, keeping the elements still being read on
+/// an explicit stack so deep trees cannot overflow the call stack
+#[cfg(feature = "xml")]
+pub(crate) fn xml_to_value(
+    doc: &XmlDocument,
+    mapping: &XmlMapping,
+    types: &XmlTypes,
+) -> Result<Value> {
+    let mut stack = vec![open_element_value(&doc.root, mapping, types)?];
+
+    loop {
+        let child = stack
+            .last_mut()
+            .and_then(|(_, _, children)| children.next());
+        if let Some(child) = child {
+            stack.push(open_element_value(child, mapping, types)?);
+            continue;
+        }
+
+        let Some((element, value, _)) = stack.pop() else {
+            return Ok(Value::Object(Object::new()));
+        };
+        let Some((_, parent, _)) = stack.last_mut() else {
+            let mut root = Object::new();
+            root.insert(&element.name, Value::Object(value));
+            return Ok(Value::Object(root));
+        };
+        let value = Value::Object(value);
+        if let Some(existing) = parent.get_mut(&element.name) {
+            match existing {
+                Value::Array(items) => {
+                    items.push(value);
                 }
-                for element in value_to_elements(key, value) {
-                    children.push(XmlContent::Element(element));
+                _ => {
+                    let previous = std::mem::replace(existing, Value::Null);
+                    *existing = Value::Array(vec![previous, value].into());
                 }
             }
+        } else {
+            parent.insert(&element.name, value);
+        }
+    }
+}
 
-            vec![XmlElement {
-                name: name.to_string(),
-                attributes,
-                children,
-            }]
+#[cfg(feature = "xml")]
+type ElementChildren<'d> = Box<dyn Iterator<Item = &'d XmlElement> + 'd>;
+
+/// Start the object for `element` with its attributes and text, typed by
+/// `types`; its child elements are added as they are finished
+#[cfg(feature = "xml")]
+fn open_element_value<'d>(
+    element: &'d XmlElement,
+    mapping: &XmlMapping,
+    types: &XmlTypes,
+) -> Result<(&'d XmlElement, Object, ElementChildren<'d>)> {
+    let mut obj = Object::new();
+
+    if !element.attributes.is_empty() {
+        let mut attrs = Object::new();
+        for (key, value) in element.attributes.iter() {
+            attrs.insert(key, value.clone());
         }
-        _ => vec![XmlElement {
-            name: name.to_string(),
-            attributes: IndexMap::new(),
-            children: value_to_children(value),
-        }],
+        obj.insert(&mapping.attributes_key, Value::Object(attrs));
     }
+
+    let text: String = element
+        .children
+        .iter()
+        .filter_map(XmlContent::text)
+        .collect();
+    if !text.is_empty() {
+        obj.insert(&mapping.text_key, types.text_value(&element.name, &text)?);
+    }
+
+    let children = element.children.iter().filter_map(|child| match child {
+        XmlContent::Element(child) => Some(child),
+        _ => None,
+    });
+    Ok((element, obj, Box::new(children)))
 }
 
-fn serialize_xml(doc: &XmlDocument) -> String {
-    let mut output = String::new();
-    serialize_element(&doc.root, &mut output);
-    output
+/// Pending work while turning a value into XML elements
+#[cfg(feature = "xml")]
+enum XmlTask<'v> {
+    /// Add the content of a value to the open element
+    Children(&'v Value),
+    /// Add elements called `name` holding a value to the open element
+    Elements(&'v str, &'v Value),
+    /// Finish the open element and add it to its parent
+    Close,
 }
 
-fn serialize_element(element: &XmlElement, output: &mut String) {
-    output.push('<');
-    output.push_str(&element.name);
+#[cfg(feature = "xml")]
+pub(crate) fn value_to_xml(value: &Value, mapping: &XmlMapping) -> XmlDocument {
+    let mut open = vec![xml_element(&mapping.root, IndexMap::new())];
+    let mut tasks = vec![XmlTask::Close, XmlTask::Children(value)];
+
+    while let Some(task) = tasks.pop() {
+        match task {
+            XmlTask::Children(Value::Object(obj)) => tasks.extend(
+                obj.iter()
+                    .rev()
+                    .map(|(key, value)| XmlTask::Elements(key, value)),
+            ),
+            XmlTask::Children(Value::Array(arr)) => {
+                tasks.extend(arr.iter().rev().map(XmlTask::Children));
+            }
+            XmlTask::Children(value) => {
+                if let (Some(text), Some(element)) = (xml_text(value), open.last_mut()) {
+                    element.children.push(XmlContent::Text(text));
+                }
+            }
+            XmlTask::Elements(name, Value::Array(arr)) => {
+                tasks.extend(arr.iter().rev().map(|value| XmlTask::Elements(name, value)))
+            }
+            XmlTask::Elements(name, Value::Object(obj)) => {
+                let mut attributes = IndexMap::new();
+                if let Some(Value::Object(attrs)) = obj.get(&mapping.attributes_key) {
+                    for (key, value) in attrs.iter() {
+                        if let Value::String(text) = value {
+                            attributes.insert(key.clone(), text.clone());
+                        } else {
+                            attributes.insert(
+                                key.clone(),
+                                serialize_json(value, &FormatOptions::default()),
+                            );
+                        }
+                    }
+                }
 
-    for (key, value) in element.attributes.iter() {
-        output.push(' ');
-        output.push_str(key);
-        output.push_str("=\"");
-        output.push_str(&escape_xml(value));
-        output.push('"');
+                let mut element = xml_element(name, attributes);
+                if let Some(Value::String(text)) = obj.get(&mapping.text_key) {
+                    element.children.push(XmlContent::Text(text.clone()));
+                }
+                open.push(element);
+                tasks.push(XmlTask::Close);
+                tasks.extend(
+                    obj.iter()
+                        .rev()
+                        .filter(|(key, _)| {
+                            **key != mapping.attributes_key && **key != mapping.text_key
+                        })
+                        .map(|(key, value)| XmlTask::Elements(key, value)),
+                );
+            }
+            XmlTask::Elements(name, value) => {
+                open.push(xml_element(name, IndexMap::new()));
+                tasks.push(XmlTask::Close);
+                tasks.push(XmlTask::Children(value));
+            }
+            XmlTask::Close => {
+                let Some(element) = open.pop() else {
+                    break;
+                };
+                match open.last_mut() {
+                    Some(parent) => parent.children.push(XmlContent::Element(element)),
+                    None => return XmlDocument::new(element),
+                }
+            }
+        }
     }
 
-    if element.children.is_empty() {
-        output.push_str("/>");
-        return;
+    XmlDocument::new(xml_element(&mapping.root, IndexMap::new()))
+}
+
+#[cfg(feature = "xml")]
+fn xml_element(name: &str, attributes: IndexMap<String, String>) -> XmlElement {
+    XmlElement {
+        name: xml_name(name),
+        attributes,
+        children: Vec::new(),
     }
+}
 
-    output.push('>');
-    for child in &element.children {
-        match child {
-            XmlContent::Element(child) => serialize_element(child, output),
-            XmlContent::Text(text) => output.push_str(&escape_xml(text)),
-        }
+/// `key` as an element name: characters XML does not allow in names become
+/// `_`, and a name that would start with a digit, `-`, `.` or `:` gets a
+/// leading `_`, so any key produces well-formed XML
+#[cfg(feature = "xml")]
+fn xml_name(key: &str) -> String {
+    let mut name: String = key
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if !name.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        name.insert(0, '_');
     }
-    output.push_str("</");
-    output.push_str(&element.name);
-    output.push('>');
+    name
 }
 
-fn escape_xml(input: &str) -> String {
-    input
-        .replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('"', "&quot;")
-        .replace('\'', "&apos;")
+#[cfg(feature = "xml")]
+fn xml_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Datetime(dt) => Some(format_datetime(dt)),
+        Value::Bytes(bytes) => Some(base64::encode(bytes)),
+        Value::Null | Value::Array(_) | Value::Object(_) => None,
+    }
 }
@@ -0,0 +1,293 @@
+//! Conversion strategies for pairs of formats
+//!
+//! [`convert_with_options`](crate::convert_with_options) looks up the
+//! [`Converter`] for a pair with [`converter`] and runs it. Strategies are
+//! plain structs, so settings that only matter to one pair (the element names
+//! CSV rows get in XML, the table CSV rows go under in TOML) live on that
+//! strategy instead of in [`ConvertOptions`], which keeps the settings every
+//! pair shares.
+//!
+//! ```
+//! use zparse::converter::{Converter, CsvToXml, XmlMapping};
+//! use zparse::ConvertOptions;
+//!
+//! # fn main() -> zparse::Result<()> {
+//! let strategy = CsvToXml {
+//!     mapping: XmlMapping {
+//!         root: "people".to_string(),
+//!         row: "person".to_string(),
+//!         ..XmlMapping::default()
+//!     },
+//! };
+//! let xml = strategy.convert("name\nAda\n", &ConvertOptions::default())?;
+//! assert_eq!(xml, "<people><person><name>Ada</name></person></people>");
+//! # Ok(())
+//! # }
+//! ```
+
+#[cfg(feature = "xml")]
+use crate::convert::XmlQuote;
+use crate::convert::{ConvertOptions, Format, JsonDialect, parse_value};
+#[cfg(feature = "xml")]
+use crate::convert::{csv_value_to_xml, value_to_xml, xml_to_csv_value, xml_to_value};
+use crate::error::Result;
+#[cfg(feature = "toml")]
+use crate::toml_compat::{toml_to_yaml, yaml_to_toml};
+use crate::value::{Object, Value};
+#[cfg(feature = "xml")]
+use crate::xml;
+#[cfg(feature = "xml")]
+use crate::xml::model::Document as XmlDocument;
+#[cfg(feature = "xml")]
+use crate::xml::parser::Parser as XmlParser;
+
+/// Turns a document in one format into a document in another
+pub trait Converter {
+    /// Convert `input` using the settings in `options` that apply to the pair
+    fn convert(&self, input: &str, options: &ConvertOptions) -> Result<String>;
+}
+
+/// Names used when mapping between XML elements and values
+#[cfg(feature = "xml")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XmlMapping {
+    /// Element wrapping converted values (`root`)
+    pub root: String,
+    /// Element holding each CSV record (`row`)
+    pub row: String,
+    /// Key holding an element's attributes (`@attributes`)
+    pub attributes_key: String,
+    /// Key holding an element's text (`#text`)
+    pub text_key: String,
+}
+
+#[cfg(feature = "xml")]
+impl Default for XmlMapping {
+    fn default() -> Self {
+        Self {
+            root: "root".to_string(),
+            row: "row".to_string(),
+            attributes_key: "@attributes".to_string(),
+            text_key: "#text".to_string(),
+        }
+    }
+}
+
+/// Rewrite a document in its own format. JSON is rewritten when comments,
+/// trailing commas, pretty output or another dialect are asked for, YAML
+/// when an indentation style is, and XML when pretty output, ASCII output or
+/// single-quoted attributes are; otherwise the input is returned as is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Reformat(pub Format);
+
+impl Converter for Reformat {
+    fn convert(&self, input: &str, options: &ConvertOptions) -> Result<String> {
+        let format = self.0;
+        if !format.is_enabled() {
+            return Err(format.disabled());
+        }
+        if format == Format::Json
+            && (options.json.allow_comments
+                || options.json.allow_trailing_commas
+                || options.format.pretty
+                || options.format.json_dialect != JsonDialect::Json)
+        {
+            let value = parse_value(input, format, options)?;
+            return format.serializer().serialize(&value, &options.format);
+        }
+        #[cfg(feature = "yaml")]
+        if format == Format::Yaml
+            && (options.format.yaml_indent.is_some() || options.format.yaml_sequences.is_some())
+        {
+            let value = parse_value(input, format, options)?;
+            let style = options.format.with_yaml_style_of(input);
+            return format.serializer().serialize(&value, &style);
+        }
+        #[cfg(feature = "xml")]
+        if format == Format::Xml
+            && (options.format.pretty
+                || options.format.xml_ascii
+                || options.format.xml_quote != XmlQuote::Double)
+        {
+            return xml::writer::to_string_with_options(
+                &parse_xml(input, options)?,
+                &options.format,
+            );
+        }
+        Ok(input.to_string())
+    }
+}
+
+/// Parse into a [`Value`] and write that in the target format
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ViaValue {
+    pub from: Format,
+    pub to: Format,
+}
+
+impl Converter for ViaValue {
+    fn convert(&self, input: &str, options: &ConvertOptions) -> Result<String> {
+        let value = parse_value(input, self.from, options)?;
+        self.to.serializer().serialize(&value, &options.format)
+    }
+}
+
+/// CSV records to a TOML document, which needs a table at the root
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CsvToToml {
+    /// Key of the array of tables holding the records (`rows`)
+    pub table: String,
+}
+
+impl Default for CsvToToml {
+    fn default() -> Self {
+        Self {
+            table: "rows".to_string(),
+        }
+    }
+}
+
+impl Converter for CsvToToml {
+    fn convert(&self, input: &str, options: &ConvertOptions) -> Result<String> {
+        let value = match parse_value(input, Format::Csv, options)? {
+            Value::Array(rows) => {
+                let mut root = Object::new();
+                root.insert(&self.table, Value::Array(rows));
+                Value::Object(root)
+            }
+            value => value,
+        };
+        Format::Toml.serializer().serialize(&value, &options.format)
+    }
+}
+
+/// CSV records to one element per record, holding one element per field
+#[cfg(feature = "xml")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CsvToXml {
+    pub mapping: XmlMapping,
+}
+
+#[cfg(feature = "xml")]
+impl Converter for CsvToXml {
+    fn convert(&self, input: &str, options: &ConvertOptions) -> Result<String> {
+        let value = parse_value(input, Format::Csv, options)?;
+        xml::writer::to_string_with_options(
+            &csv_value_to_xml(&value, &self.mapping)?,
+            &options.format,
+        )
+    }
+}
+
+/// The record elements under the XML root to CSV, the reverse of [`CsvToXml`]
+#[cfg(feature = "xml")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct XmlToCsv {
+    pub mapping: XmlMapping,
+}
+
+#[cfg(feature = "xml")]
+impl Converter for XmlToCsv {
+    fn convert(&self, input: &str, options: &ConvertOptions) -> Result<String> {
+        let value = xml_to_csv_value(
+            &parse_xml(input, options)?,
+            &self.mapping,
+            &options.xml_types,
+        )?;
+        Format::Csv.serializer().serialize(&value, &options.format)
+    }
+}
+
+/// XML to another format, mapping attributes and text to keys
+#[cfg(feature = "xml")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FromXml {
+    pub to: Format,
+    pub mapping: XmlMapping,
+}
+
+#[cfg(feature = "xml")]
+impl Converter for FromXml {
+    fn convert(&self, input: &str, options: &ConvertOptions) -> Result<String> {
+        let value = xml_to_value(
+            &parse_xml(input, options)?,
+            &self.mapping,
+            &options.xml_types,
+        )?;
+        self.to.serializer().serialize(&value, &options.format)
+    }
+}
+
+/// Another format to XML, the reverse of [`FromXml`]
+#[cfg(feature = "xml")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ToXml {
+    pub from: Format,
+    pub mapping: XmlMapping,
+}
+
+#[cfg(feature = "xml")]
+impl Converter for ToXml {
+    fn convert(&self, input: &str, options: &ConvertOptions) -> Result<String> {
+        let value = parse_value(input, self.from, options)?;
+        xml::writer::to_string_with_options(&value_to_xml(&value, &self.mapping), &options.format)
+    }
+}
+
+/// YAML to TOML, checking TOML's restrictions with
+/// [`ConvertOptions::toml_compat`]
+#[cfg(feature = "toml")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct YamlToToml;
+
+#[cfg(feature = "toml")]
+impl Converter for YamlToToml {
+    fn convert(&self, input: &str, options: &ConvertOptions) -> Result<String> {
+        yaml_to_toml(input, options)
+    }
+}
+
+/// TOML to YAML
+#[cfg(feature = "toml")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TomlToYaml;
+
+#[cfg(feature = "toml")]
+impl Converter for TomlToYaml {
+    fn convert(&self, input: &str, options: &ConvertOptions) -> Result<String> {
+        toml_to_yaml(input, options)
+    }
+}
+
+/// The strategy [`convert_with_options`](crate::convert_with_options) uses
+/// for a pair of formats, with default settings
+pub fn converter(from: Format, to: Format) -> Box<dyn Converter> {
+    match (from, to) {
+        _ if from == to => Box::new(Reformat(from)),
+        (Format::Csv, Format::Toml) => Box::new(CsvToToml::default()),
+        #[cfg(feature = "xml")]
+        (Format::Csv, Format::Xml) => Box::new(CsvToXml::default()),
+        #[cfg(feature = "xml")]
+        (Format::Xml, Format::Csv) => Box::new(XmlToCsv::default()),
+        #[cfg(feature = "toml")]
+        (Format::Yaml, Format::Toml) => Box::new(YamlToToml),
+        #[cfg(feature = "toml")]
+        (Format::Toml, Format::Yaml) => Box::new(TomlToYaml),
+        #[cfg(feature = "xml")]
+        (Format::Xml, _) => Box::new(FromXml {
+            to,
+            mapping: XmlMapping::default(),
+        }),
+        #[cfg(feature = "xml")]
+        (_, Format::Xml) => Box::new(ToXml {
+            from,
+            mapping: XmlMapping::default(),
+        }),
+        _ => Box::new(ViaValue { from, to }),
+    }
+}
+
+#[cfg(feature = "xml")]
+fn parse_xml(input: &str, options: &ConvertOptions) -> Result<XmlDocument> {
+    XmlParser::with_config(input.as_bytes(), options.xml).parse()
+}
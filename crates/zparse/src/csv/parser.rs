@@ -83,27 +83,12 @@ impl<'a> Parser<'a> {
         self.bytes_parsed
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(format = "csv"))
+    )]
     pub fn parse(&mut self) -> Result<Value> {
-        if matches!(self.config.delimiter, b'\n' | b'\r' | b'"') {
-            return Err(Error::with_message(
-                ErrorKind::InvalidToken,
-                Span::empty(),
-                "invalid CSV delimiter: delimiter cannot be newline, carriage return, or quote"
-                    .to_string(),
-            ));
-        }
-
-        if self.config.max_size > 0 && self.input.len() > self.config.max_size {
-            return Err(Error::at(
-                ErrorKind::MaxSizeExceeded {
-                    max: self.config.max_size,
-                },
-                self.bytes_parsed,
-                1,
-                1,
-            ));
-        }
-
+        self.check_limits()?;
         let records = self.parse_records()?;
         self.bytes_parsed = self.input.len();
         if records.is_empty() {
@@ -148,8 +133,48 @@ impl<'a> Parser<'a> {
         Ok(Value::Array(rows))
     }
 
+    /// Check that the complete input is valid CSV without building a value.
+    ///
+    /// Records are read one at a time and dropped, so no rows are kept and
+    /// no field types are inferred.
+    pub fn validate(&mut self) -> Result<()> {
+        self.check_limits()?;
+        self.for_each_record(|_| {})?;
+        self.bytes_parsed = self.input.len();
+        Ok(())
+    }
+
+    fn check_limits(&self) -> Result<()> {
+        if matches!(self.config.delimiter, b'\n' | b'\r' | b'"') {
+            return Err(Error::with_message(
+                ErrorKind::InvalidToken,
+                Span::empty(),
+                "invalid CSV delimiter: delimiter cannot be newline, carriage return, or quote"
+                    .to_string(),
+            ));
+        }
+
+        if self.config.max_size > 0 && self.input.len() > self.config.max_size {
+            return Err(Error::at(
+                ErrorKind::MaxSizeExceeded {
+                    max: self.config.max_size,
+                },
+                self.bytes_parsed,
+                1,
+                1,
+            ));
+        }
+        Ok(())
+    }
+
     fn parse_records(&self) -> Result<Vec<Vec<Field>>> {
         let mut records = Vec::new();
+        self.for_each_record(|record| records.push(record))?;
+        Ok(records)
+    }
+
+    /// Read each non-blank record in turn and hand it to `f`
+    fn for_each_record(&self, mut f: impl FnMut(Vec<Field>)) -> Result<()> {
         let mut index = 0usize;
 
         while index < self.input.len() {
@@ -168,12 +193,12 @@ impl<'a> Parser<'a> {
 
             let (record, next) = self.parse_record(index)?;
             if !is_blank_record(&record) {
-                records.push(record);
+                f(record);
             }
             index = next;
         }
 
-        Ok(records)
+        Ok(())
     }
 
     fn parse_record(&self, mut index: usize) -> Result<(Vec<Field>, usize)> {
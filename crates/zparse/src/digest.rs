@@ -0,0 +1,92 @@
+//! Content-addressable digests of values
+//!
+//! [`Value::hash_canonical`] hashes a canonical encoding of a value with
+//! SHA-256, so two documents with the same data get the same [`Digest`] no
+//! matter their source format, key order or whitespace. Useful for
+//! deduplicating configuration snapshots or keying caches.
+//!
+//! The encoding tags every value with its type and length-prefixes strings,
+//! byte strings and containers; object entries are sorted by key. `-0.0` is
+//! encoded as `0.0` and every NaN as a single NaN. A datetime is encoded by
+//! its TOML text, distinct from a string with the same text. The encoding is
+//! stable across releases.
+
+use std::fmt;
+
+use crate::sha256::Sha256;
+use crate::value::{Value, canonical_number};
+
+/// SHA-256 digest of a value's canonical encoding
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Digest([u8; 32]);
+
+impl Digest {
+    /// The raw 32 digest bytes
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl fmt::Display for Digest {
+    /// Formats the digest as 64 lowercase hex digits
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
+}
+
+impl Value {
+    /// Digest of this value that ignores key order and source formatting
+    pub fn hash_canonical(&self) -> Digest {
+        let mut hasher = Sha256::new();
+        encode(self, &mut hasher);
+        Digest(hasher.finish())
+    }
+}
+
+fn encode(value: &Value, hasher: &mut Sha256) {
+    match value {
+        Value::Null => hasher.update(b"n"),
+        Value::Bool(true) => hasher.update(b"t"),
+        Value::Bool(false) => hasher.update(b"f"),
+        Value::Number(n) => {
+            hasher.update(b"d");
+            hasher.update(&canonical_number(*n).to_bits().to_be_bytes());
+        }
+        Value::String(s) => {
+            hasher.update(b"s");
+            encode_bytes(s.as_bytes(), hasher);
+        }
+        Value::Bytes(bytes) => {
+            hasher.update(b"b");
+            encode_bytes(bytes, hasher);
+        }
+        Value::Datetime(datetime) => {
+            hasher.update(b"T");
+            encode_bytes(datetime.to_string().as_bytes(), hasher);
+        }
+        Value::Array(items) => {
+            hasher.update(b"a");
+            encode_len(items.len(), hasher);
+            items.iter().for_each(|item| encode(item, hasher));
+        }
+        Value::Object(entries) => {
+            hasher.update(b"o");
+            encode_len(entries.len(), hasher);
+            let mut sorted: Vec<_> = entries.iter().collect();
+            sorted.sort_by(|a, b| a.0.cmp(b.0));
+            for (key, value) in sorted {
+                encode_bytes(key.as_bytes(), hasher);
+                encode(value, hasher);
+            }
+        }
+    }
+}
+
+fn encode_bytes(bytes: &[u8], hasher: &mut Sha256) {
+    encode_len(bytes.len(), hasher);
+    hasher.update(bytes);
+}
+
+fn encode_len(len: usize, hasher: &mut Sha256) {
+    hasher.update(&u64::try_from(len).unwrap_or(u64::MAX).to_be_bytes());
+}
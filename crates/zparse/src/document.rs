@@ -0,0 +1,157 @@
+//! Parse results with source metadata
+//!
+//! [`parse_document`] wraps the parsed [`Value`] together with what was
+//! learned while reading it: the source format and path, a declared version,
+//! and any lenient extensions that had to be accepted. The raw `from_*` and
+//! [`crate::parse_with_options`] entry points remain available when only the
+//! value is needed.
+
+use std::path::{Path, PathBuf};
+
+use crate::convert::{ConvertOptions, Format, parse_with_options};
+use crate::detect_format_from_path;
+use crate::error::{Error, ErrorKind, Result, Span};
+use crate::json::Parser as JsonParser;
+use crate::ops::unknown_format;
+#[cfg(feature = "toml")]
+use crate::toml::Parser as TomlParser;
+use crate::value::Value;
+#[cfg(feature = "yaml")]
+use crate::yaml::Parser as YamlParser;
+
+const BOM: &str = "\u{feff}";
+
+/// Non-standard input accepted while parsing
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Extension {
+    /// A UTF-8 byte order mark was stripped
+    ByteOrderMark,
+    /// JSON comments were skipped
+    JsonComments,
+    /// JSON trailing commas were accepted
+    JsonTrailingCommas,
+}
+
+/// A parsed value with metadata about its source
+#[derive(Clone, Debug, PartialEq)]
+pub struct Document {
+    /// Parsed value
+    pub value: Value,
+    /// Format the value was parsed from
+    pub format: Format,
+    /// Path the input was read from, if any
+    pub source: Option<PathBuf>,
+    /// Version declared by the input (e.g. `<?xml version="1.0"?>`)
+    pub version: Option<String>,
+    /// Lenient extensions that were needed to parse the input
+    pub extensions: Vec<Extension>,
+    /// Human-readable notes about the parse, including parser warnings
+    pub warnings: Vec<String>,
+}
+
+impl Document {
+    /// Returns true if any non-standard input was accepted
+    pub fn is_lenient(&self) -> bool {
+        !self.extensions.is_empty()
+    }
+
+    /// Discard the metadata and keep the value
+    pub fn into_value(self) -> Value {
+        self.value
+    }
+}
+
+/// Parse `input` and collect source metadata
+pub fn parse_document(input: &str, format: Format, options: &ConvertOptions) -> Result<Document> {
+    let mut extensions = Vec::new();
+    let mut warnings = Vec::new();
+
+    let input = match input.strip_prefix(BOM) {
+        Some(rest) => {
+            extensions.push(Extension::ByteOrderMark);
+            warnings.push("stripped UTF-8 byte order mark".to_string());
+            rest
+        }
+        None => input,
+    };
+
+    let value = match format {
+        Format::Json => {
+            let mut parser = JsonParser::with_config(input.as_bytes(), options.json);
+            let value = parser.parse_value()?;
+            let comments = parser.comments_skipped();
+            if comments > 0 {
+                extensions.push(Extension::JsonComments);
+                warnings.push(format!("stripped {comments} comment(s)"));
+            }
+            let commas = parser.trailing_commas_skipped();
+            if commas > 0 {
+                extensions.push(Extension::JsonTrailingCommas);
+                warnings.push(format!("accepted {commas} trailing comma(s)"));
+            }
+            warnings.extend(parser.take_warnings().iter().map(ToString::to_string));
+            value
+        }
+        #[cfg(feature = "toml")]
+        Format::Toml => {
+            let mut parser = TomlParser::with_config(input.as_bytes(), options.toml);
+            let value = parser.parse()?;
+            warnings.extend(parser.take_warnings().iter().map(ToString::to_string));
+            value
+        }
+        #[cfg(feature = "yaml")]
+        Format::Yaml => {
+            let mut parser = YamlParser::with_config(input.as_bytes(), options.yaml);
+            let value = parser.parse()?;
+            warnings.extend(parser.take_warnings().iter().map(ToString::to_string));
+            value
+        }
+        _ => parse_with_options(input, format, options)?,
+    };
+
+    let version = match format {
+        Format::Xml => xml_version(input),
+        _ => None,
+    };
+
+    Ok(Document {
+        value,
+        format,
+        source: None,
+        version,
+        extensions,
+        warnings,
+    })
+}
+
+/// Read and parse `path`, inferring the format from its extension
+pub fn parse_document_file(path: impl AsRef<Path>, options: &ConvertOptions) -> Result<Document> {
+    let path = path.as_ref();
+    let io_error = |message: String| {
+        Error::with_message(
+            ErrorKind::Io {
+                path: path.display().to_string(),
+            },
+            Span::empty(),
+            message,
+        )
+    };
+    let format = detect_format_from_path(path).ok_or_else(|| unknown_format(path))?;
+    let input = std::fs::read_to_string(path)
+        .map_err(|err| io_error(format!("{}: {err}", path.display())))?;
+
+    let mut document = parse_document(&input, format, options)?;
+    document.source = Some(path.to_path_buf());
+    Ok(document)
+}
+
+/// Version from a leading `<?xml version="..."?>` declaration
+fn xml_version(input: &str) -> Option<String> {
+    let declaration = input.trim_start().strip_prefix("<?xml")?;
+    let declaration = declaration.get(..declaration.find("?>")?)?;
+    let rest = declaration.split_once("version")?.1.trim_start();
+    let rest = rest.strip_prefix('=')?.trim_start();
+    let quote = rest.chars().next().filter(|ch| matches!(ch, '"' | '\''))?;
+    let rest = rest.get(1..)?;
+    Some(rest.get(..rest.find(quote)?)?.to_string())
+}
@@ -0,0 +1,222 @@
+//! Hooks for encrypted values
+//!
+//! Tools like SOPS keep a config readable by encrypting only its values and
+//! wrapping each one in a marker such as `ENC[...]`. zparse does no
+//! cryptography itself: an [`Encryption`] finds the marked strings and hands
+//! their payload to a user-supplied [`Cipher`] after parsing, and before
+//! formatting wraps what the cipher returns for the values of secret-looking
+//! keys. Only string values are encrypted; decrypted values are strings.
+//!
+//! ```
+//! use zparse::encryption::{Encryption, FnCipher};
+//! use zparse::{Format, FormatOptions, Value};
+//!
+//! # fn main() -> zparse::Result<()> {
+//! // A stand-in for a real cipher: reverses the text
+//! let cipher = FnCipher {
+//!     decrypt: |_: &str, payload: &str| Ok(payload.chars().rev().collect()),
+//!     encrypt: |_: &str, plain: &str| Ok(plain.chars().rev().collect()),
+//! };
+//! let hooks = Encryption::new(&cipher);
+//!
+//! let value = hooks.parse(r#"{"password": "ENC[2retnuh]"}"#, Format::Json, &Default::default())?;
+//! assert_eq!(value.get("password"), Some(&Value::from("hunter2")));
+//!
+//! let json = hooks.serialize(&value, Format::Json, &FormatOptions::default())?;
+//! assert_eq!(json, r#"{"password":"ENC[2retnuh]"}"#);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::convert::{
+    ConvertOptions, Format, FormatOptions, parse_with_options, serialize_with_options,
+};
+use crate::error::Result;
+use crate::locate::escape_token;
+use crate::redact::Redactor;
+use crate::value::Value;
+
+/// Prefix of values wrapped by [`Marker::default`]
+pub const DEFAULT_PREFIX: &str = "ENC[";
+
+/// Suffix of values wrapped by [`Marker::default`]
+pub const DEFAULT_SUFFIX: &str = "]";
+
+/// Turns marker payloads into plaintext and back. Both methods get the JSON
+/// pointer of the value, which ciphers that bind it as associated data need.
+pub trait Cipher {
+    /// Plaintext for the payload found between a marker's prefix and suffix
+    fn decrypt(&self, pointer: &str, payload: &str) -> Result<String>;
+
+    /// Payload to store between a marker's prefix and suffix
+    fn encrypt(&self, pointer: &str, plaintext: &str) -> Result<String>;
+}
+
+/// A [`Cipher`] made of two closures
+#[derive(Clone, Copy, Debug)]
+pub struct FnCipher<D, E> {
+    pub decrypt: D,
+    pub encrypt: E,
+}
+
+impl<D, E> Cipher for FnCipher<D, E>
+where
+    D: Fn(&str, &str) -> Result<String>,
+    E: Fn(&str, &str) -> Result<String>,
+{
+    fn decrypt(&self, pointer: &str, payload: &str) -> Result<String> {
+        (self.decrypt)(pointer, payload)
+    }
+
+    fn encrypt(&self, pointer: &str, plaintext: &str) -> Result<String> {
+        (self.encrypt)(pointer, plaintext)
+    }
+}
+
+/// The text around an encrypted payload
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Marker {
+    pub prefix: String,
+    pub suffix: String,
+}
+
+impl Default for Marker {
+    fn default() -> Self {
+        Self {
+            prefix: DEFAULT_PREFIX.to_string(),
+            suffix: DEFAULT_SUFFIX.to_string(),
+        }
+    }
+}
+
+impl Marker {
+    /// The payload of `text` if the whole string is wrapped in this marker
+    pub fn payload<'t>(&self, text: &'t str) -> Option<&'t str> {
+        text.strip_prefix(&self.prefix)?.strip_suffix(&self.suffix)
+    }
+
+    /// Wrap `payload` in this marker
+    pub fn wrap(&self, payload: &str) -> String {
+        format!("{}{payload}{}", self.prefix, self.suffix)
+    }
+}
+
+/// Runs a [`Cipher`] over the marked values of a document
+pub struct Encryption<'c> {
+    cipher: &'c dyn Cipher,
+    marker: Marker,
+    keys: Redactor,
+}
+
+impl<'c> Encryption<'c> {
+    /// Use `cipher` with the `ENC[...]` marker, encrypting the values of keys
+    /// matched by [`Redactor::default`]
+    pub fn new(cipher: &'c dyn Cipher) -> Self {
+        Self {
+            cipher,
+            marker: Marker::default(),
+            keys: Redactor::default(),
+        }
+    }
+
+    /// Set the marker around encrypted values
+    pub fn with_marker(mut self, marker: Marker) -> Self {
+        self.marker = marker;
+        self
+    }
+
+    /// Set the keys whose values [`Self::encrypt`] encrypts
+    pub fn with_keys(mut self, keys: Redactor) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    /// Replace every marked string in `value` with its plaintext
+    pub fn decrypt(&self, value: &mut Value) -> Result<()> {
+        self.walk(
+            value,
+            &mut String::new(),
+            true,
+            &|pointer, text| match self.marker.payload(text) {
+                Some(payload) => self.cipher.decrypt(pointer, payload).map(Some),
+                None => Ok(None),
+            },
+        )
+    }
+
+    /// Encrypt and mark the strings under matching keys. Values that are
+    /// already marked are left alone, so encrypting twice changes nothing.
+    pub fn encrypt(&self, value: &mut Value) -> Result<()> {
+        self.walk(value, &mut String::new(), false, &|pointer, text| {
+            if self.marker.payload(text).is_some() {
+                return Ok(None);
+            }
+            let payload = self.cipher.encrypt(pointer, text)?;
+            Ok(Some(self.marker.wrap(&payload)))
+        })
+    }
+
+    /// Parse `input` and decrypt its marked values
+    pub fn parse(&self, input: &str, format: Format, options: &ConvertOptions) -> Result<Value> {
+        let mut value = parse_with_options(input, format, options)?;
+        self.decrypt(&mut value)?;
+        Ok(value)
+    }
+
+    /// Write `value` with the values under matching keys encrypted
+    pub fn serialize(
+        &self,
+        value: &Value,
+        format: Format,
+        options: &FormatOptions,
+    ) -> Result<String> {
+        let mut value = value.clone();
+        self.encrypt(&mut value)?;
+        serialize_with_options(&value, format, options)
+    }
+
+    /// Call `hook` on the strings in `value` and store what it returns.
+    /// Unless `selected` is set, only strings under a matching key are seen.
+    fn walk(
+        &self,
+        value: &mut Value,
+        pointer: &mut String,
+        selected: bool,
+        hook: &dyn Fn(&str, &str) -> Result<Option<String>>,
+    ) -> Result<()> {
+        match value {
+            Value::String(text) if selected => {
+                if let Some(replacement) = hook(pointer, text)? {
+                    *text = replacement;
+                }
+                Ok(())
+            }
+            Value::Array(items) => {
+                for (index, item) in items.iter_mut().enumerate() {
+                    let len = pointer.len();
+                    pointer.push_str(&format!("/{index}"));
+                    self.walk(item, pointer, selected, hook)?;
+                    pointer.truncate(len);
+                }
+                Ok(())
+            }
+            Value::Object(entries) => {
+                for (key, item) in entries.iter_mut() {
+                    let len = pointer.len();
+                    pointer.push('/');
+                    pointer.push_str(&escape_token(key));
+                    let selected = selected || self.keys.matches(key);
+                    self.walk(item, pointer, selected, hook)?;
+                    pointer.truncate(len);
+                }
+                Ok(())
+            }
+            Value::String(_)
+            | Value::Null
+            | Value::Bool(_)
+            | Value::Number(_)
+            | Value::Datetime(_)
+            | Value::Bytes(_) => Ok(()),
+        }
+    }
+}
@@ -45,6 +45,7 @@ impl Span {
 
 /// Error kind for detailed categorization
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ErrorKind {
     InvalidEscapeSequence,
     InvalidUnicodeEscape,
@@ -61,6 +62,13 @@ pub enum ErrorKind {
     InvalidArray,
     MaxDepthExceeded { max: u16 },
     MaxSizeExceeded { max: usize },
+    UndefinedVariable { name: String },
+    InvalidInclude { path: String },
+    Io { path: String },
+    UnsupportedValue { path: String },
+    UnknownFormat { path: String },
+    FormatDisabled { feature: String },
+    PathNotFound { path: String },
 }
 
 impl fmt::Display for ErrorKind {
@@ -85,6 +93,15 @@ impl fmt::Display for ErrorKind {
                 write!(f, "max depth exceeded: {max}")
             }
             Self::MaxSizeExceeded { max } => write!(f, "max size exceeded: {max}"),
+            Self::UndefinedVariable { name } => write!(f, "undefined variable: {name}"),
+            Self::InvalidInclude { path } => write!(f, "invalid include: {path}"),
+            Self::Io { path } => write!(f, "i/o error: {path}"),
+            Self::UnsupportedValue { path } => write!(f, "unsupported value at {path}"),
+            Self::UnknownFormat { path } => write!(f, "unknown format: {path}"),
+            Self::FormatDisabled { feature } => {
+                write!(f, "format not enabled: build with the `{feature}` feature")
+            }
+            Self::PathNotFound { path } => write!(f, "path not found: {path}"),
         }
     }
 }
@@ -95,23 +112,32 @@ pub struct Error {
     kind: ErrorKind,
     span: Span,
     message: String,
+    /// What was being done when the error happened, innermost first
+    context: Box<[String]>,
 }
 
 impl Error {
     pub fn new(kind: ErrorKind, span: Span) -> Self {
         let message = kind.to_string();
+        #[cfg(feature = "tracing")]
+        tracing::debug!(kind = ?kind, at = %span.start, "{message}");
         Self {
             kind,
             span,
             message,
+            context: Box::default(),
         }
     }
 
     pub fn with_message(kind: ErrorKind, span: Span, message: impl Into<String>) -> Self {
+        let message = message.into();
+        #[cfg(feature = "tracing")]
+        tracing::debug!(kind = ?kind, at = %span.start, "{message}");
         Self {
             kind,
             span,
-            message: message.into(),
+            message,
+            context: Box::default(),
         }
     }
 
@@ -132,11 +158,44 @@ impl Error {
         let pos = Pos::new(offset, line, col);
         Self::new(kind, Span::new(pos, pos))
     }
+
+    /// Add a frame saying what was being done when the error happened, e.g.
+    /// `"while parsing table [server.ssh]"`. Frames are added as the error
+    /// propagates, so the first is the innermost.
+    #[must_use]
+    pub fn context(mut self, frame: impl Into<String>) -> Self {
+        let mut context = std::mem::take(&mut self.context).into_vec();
+        context.push(frame.into());
+        self.context = context.into_boxed_slice();
+        self
+    }
+
+    /// The frames added by [`Self::context`], innermost first
+    pub fn frames(&self) -> &[String] {
+        &self.context
+    }
+
+    /// The same error with its message rewritten by `f`, keeping its kind,
+    /// span and frames
+    pub(crate) fn map_message(mut self, f: impl FnOnce(&str) -> String) -> Self {
+        self.message = f(&self.message);
+        self
+    }
+
+    /// The same error with its span moved by `f`, keeping everything else
+    pub(crate) fn map_span(mut self, f: impl FnOnce(Span) -> Span) -> Self {
+        self.span = f(self.span);
+        self
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "error at {}: {}", self.span.start, self.message)
+        write!(f, "error at {}: {}", self.span.start, self.message)?;
+        for frame in &self.context {
+            write!(f, "\n  {frame}")?;
+        }
+        Ok(())
     }
 }
 
@@ -0,0 +1,289 @@
+//! Streaming events shared by all formats
+//!
+//! Needs the `unstable` feature and is not covered by semver.
+//!
+//! Each format's streaming parser has its own event type ([`json::Event`],
+//! [`yaml::Event`], [`toml::Event`]). [`Event`] is the common shape they all
+//! map to, so a consumer written against [`EventReader`] handles every
+//! format. JSON and YAML events convert one to one; TOML describes tables by
+//! header rather than by nesting, so its reader walks the parsed document.
+//!
+//! ```
+//! # #[cfg(feature = "unstable")]
+//! # fn main() -> zparse::Result<()> {
+//! use zparse::event::{Container, Event, EventReader, reader};
+//! use zparse::{ConvertOptions, Format};
+//!
+//! let options = ConvertOptions::default();
+//! for (input, format) in [("{\"a\": 1}", Format::Json), ("a = 1", Format::Toml)] {
+//!     let mut events = reader(input, format, &options)?;
+//!     assert_eq!(events.read_event()?, Some(Event::ContainerStart(Container::Object)));
+//!     assert_eq!(events.read_event()?, Some(Event::Key("a".to_string())));
+//! }
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "unstable"))]
+//! # fn main() {}
+//! ```
+
+use std::collections::VecDeque;
+
+use crate::convert::{ConvertOptions, Format, parse_with_options};
+use crate::error::{Error, ErrorKind, Result, Span};
+use crate::json;
+#[cfg(feature = "toml")]
+use crate::toml;
+use crate::value::{Array, Object, Value};
+#[cfg(feature = "yaml")]
+use crate::yaml;
+
+/// Kind of container an event opens or closes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Container {
+    /// JSON object, YAML mapping, TOML table or XML element
+    Object,
+    /// JSON array, YAML sequence or TOML array
+    Array,
+}
+
+/// A streaming event in any format
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// Start of a container
+    ContainerStart(Container),
+    /// Object key (always followed by a value or container start)
+    Key(String),
+    /// Scalar value
+    Value(Value),
+    /// End of the innermost open container
+    ContainerEnd(Container),
+}
+
+impl From<json::Event> for Event {
+    fn from(event: json::Event) -> Self {
+        match event {
+            json::Event::ObjectStart => Self::ContainerStart(Container::Object),
+            json::Event::ObjectEnd => Self::ContainerEnd(Container::Object),
+            json::Event::ArrayStart => Self::ContainerStart(Container::Array),
+            json::Event::ArrayEnd => Self::ContainerEnd(Container::Array),
+            json::Event::Key(key) => Self::Key(key),
+            json::Event::Value(value) => Self::Value(value),
+        }
+    }
+}
+
+impl From<Event> for json::Event {
+    fn from(event: Event) -> Self {
+        match event {
+            Event::ContainerStart(Container::Object) => Self::ObjectStart,
+            Event::ContainerEnd(Container::Object) => Self::ObjectEnd,
+            Event::ContainerStart(Container::Array) => Self::ArrayStart,
+            Event::ContainerEnd(Container::Array) => Self::ArrayEnd,
+            Event::Key(key) => Self::Key(key),
+            Event::Value(value) => Self::Value(value),
+        }
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl From<yaml::Event> for Event {
+    fn from(event: yaml::Event) -> Self {
+        match event {
+            yaml::Event::MappingStart => Self::ContainerStart(Container::Object),
+            yaml::Event::MappingEnd => Self::ContainerEnd(Container::Object),
+            yaml::Event::SequenceStart => Self::ContainerStart(Container::Array),
+            yaml::Event::SequenceEnd => Self::ContainerEnd(Container::Array),
+            yaml::Event::Key(key) => Self::Key(key),
+            yaml::Event::Value(value) => Self::Value(value),
+        }
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl From<Event> for yaml::Event {
+    fn from(event: Event) -> Self {
+        match event {
+            Event::ContainerStart(Container::Object) => Self::MappingStart,
+            Event::ContainerEnd(Container::Object) => Self::MappingEnd,
+            Event::ContainerStart(Container::Array) => Self::SequenceStart,
+            Event::ContainerEnd(Container::Array) => Self::SequenceEnd,
+            Event::Key(key) => Self::Key(key),
+            Event::Value(value) => Self::Value(value),
+        }
+    }
+}
+
+/// A source of [`Event`]s
+pub trait EventReader {
+    /// The next event, or `None` once the document is finished
+    fn read_event(&mut self) -> Result<Option<Event>>;
+}
+
+impl EventReader for json::Parser<'_> {
+    fn read_event(&mut self) -> Result<Option<Event>> {
+        Ok(self.next_event()?.map(Event::from))
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl EventReader for yaml::Parser<'_> {
+    fn read_event(&mut self) -> Result<Option<Event>> {
+        Ok(self.next_event()?.map(Event::from))
+    }
+}
+
+#[cfg(feature = "toml")]
+impl EventReader for toml::Parser<'_> {
+    /// Parses the rest of the document on the first call and walks the
+    /// resulting table, since TOML headers can reopen tables out of order
+    fn read_event(&mut self) -> Result<Option<Event>> {
+        if self.unified_events.is_none() {
+            let root = self.parse()?;
+            self.unified_events = Some(ValueEvents::new(&root));
+        }
+        Ok(self.unified_events.as_mut().and_then(ValueEvents::next))
+    }
+}
+
+/// The events describing an already parsed [`Value`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ValueEvents {
+    events: VecDeque<Event>,
+}
+
+impl ValueEvents {
+    /// Queue the events for `value`
+    pub fn new(value: &Value) -> Self {
+        let mut events = VecDeque::new();
+        push_events(value, &mut events);
+        Self { events }
+    }
+}
+
+impl Iterator for ValueEvents {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        self.events.pop_front()
+    }
+}
+
+impl EventReader for ValueEvents {
+    fn read_event(&mut self) -> Result<Option<Event>> {
+        Ok(self.next())
+    }
+}
+
+/// Read `input` as events. JSON and YAML stream from their parsers; TOML,
+/// CSV and XML are parsed first, XML using the same mapping as conversion.
+pub fn reader<'a>(
+    input: &'a str,
+    format: Format,
+    options: &ConvertOptions,
+) -> Result<Box<dyn EventReader + 'a>> {
+    Ok(match format {
+        Format::Json => Box::new(json::Parser::with_config(input.as_bytes(), options.json)),
+        #[cfg(feature = "yaml")]
+        Format::Yaml => Box::new(yaml::Parser::with_config(input.as_bytes(), options.yaml)),
+        #[cfg(feature = "toml")]
+        Format::Toml => Box::new(toml::Parser::with_config(input.as_bytes(), options.toml)),
+        _ => Box::new(ValueEvents::new(&parse_with_options(
+            input, format, options,
+        )?)),
+    })
+}
+
+/// Queue the events for `value` without recursing, so deep values are fine
+pub(crate) fn push_events(value: &Value, events: &mut VecDeque<Event>) {
+    enum Open<'v> {
+        Array(std::slice::Iter<'v, Value>),
+        Object(indexmap::map::Iter<'v, String, Value>),
+    }
+
+    let mut stack = Vec::new();
+    let mut next = Some(value);
+    loop {
+        match next.take() {
+            Some(Value::Object(obj)) => {
+                events.push_back(Event::ContainerStart(Container::Object));
+                stack.push(Open::Object(obj.iter()));
+            }
+            Some(Value::Array(arr)) => {
+                events.push_back(Event::ContainerStart(Container::Array));
+                stack.push(Open::Array(arr.iter()));
+            }
+            Some(value) => events.push_back(Event::Value(value.clone())),
+            None => {}
+        }
+
+        match stack.last_mut() {
+            Some(Open::Array(items)) => next = items.next(),
+            Some(Open::Object(entries)) => {
+                next = entries.next().map(|(key, value)| {
+                    events.push_back(Event::Key(key.clone()));
+                    value
+                });
+            }
+            None => return,
+        }
+        if next.is_none() {
+            match stack.pop() {
+                Some(Open::Array(_)) => events.push_back(Event::ContainerEnd(Container::Array)),
+                Some(Open::Object(_)) => events.push_back(Event::ContainerEnd(Container::Object)),
+                None => {}
+            }
+        }
+    }
+}
+
+/// Build the value the events from `reader` describe
+pub fn collect(reader: &mut dyn EventReader) -> Result<Value> {
+    enum Open {
+        Array(Array),
+        Object(Object, Option<String>),
+    }
+
+    let mut stack: Vec<Open> = Vec::new();
+    while let Some(event) = reader.read_event()? {
+        let value = match event {
+            Event::ContainerStart(Container::Object) => {
+                stack.push(Open::Object(Object::new(), None));
+                continue;
+            }
+            Event::ContainerStart(Container::Array) => {
+                stack.push(Open::Array(Array::new()));
+                continue;
+            }
+            Event::Key(key) => match stack.last_mut() {
+                Some(Open::Object(_, pending @ None)) => {
+                    *pending = Some(key);
+                    continue;
+                }
+                _ => return Err(unexpected("key outside of an object")),
+            },
+            Event::Value(value) => value,
+            Event::ContainerEnd(kind) => match (kind, stack.pop()) {
+                (Container::Array, Some(Open::Array(arr))) => Value::Array(arr),
+                (Container::Object, Some(Open::Object(obj, None))) => Value::Object(obj),
+                _ => return Err(unexpected("container end without a matching start")),
+            },
+        };
+
+        match stack.last_mut() {
+            None => return Ok(value),
+            Some(Open::Array(arr)) => arr.push(value),
+            Some(Open::Object(obj, pending)) => match pending.take() {
+                Some(key) => {
+                    obj.insert(key, value);
+                }
+                None => return Err(unexpected("value without a key in an object")),
+            },
+        }
+    }
+
+    Err(unexpected("events ended before a complete value"))
+}
+
+fn unexpected(message: &str) -> Error {
+    Error::with_message(ErrorKind::InvalidToken, Span::empty(), message.to_string())
+}
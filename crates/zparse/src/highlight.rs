@@ -0,0 +1,503 @@
+//! Syntax highlighting backed by the lexers
+//!
+//! [`highlight`] splits a document into byte ranges tagged with a
+//! [`TokenClass`]. JSON, TOML and YAML go through the same lexers as the
+//! parsers, so an editor colours text the way zparse reads it. Whitespace and
+//! YAML indentation are left out, ranges are sorted and never overlap, and
+//! everything from the first lexing error on is one [`TokenClass::Invalid`]
+//! range.
+//!
+//! ```
+//! use zparse::Format;
+//! use zparse::highlight::{TokenClass, highlight};
+//!
+//! let highlights = highlight("port = 8080 # http", Format::Toml);
+//! assert_eq!(
+//!     highlights,
+//!     [
+//!         (0..4, TokenClass::Key),
+//!         (5..6, TokenClass::Punctuation),
+//!         (7..11, TokenClass::Number),
+//!         (12..18, TokenClass::Comment),
+//!     ]
+//! );
+//! ```
+
+use std::iter::Peekable;
+use std::ops::Range;
+
+use crate::convert::Format;
+use crate::csv::infer_primitive_value;
+use crate::error::{Result, Span};
+use crate::lexer::{JsonLexer, SpannedToken, TokenKind};
+#[cfg(feature = "toml")]
+use crate::lexer::{TomlLexer, TomlTokenKind};
+#[cfg(feature = "yaml")]
+use crate::lexer::{YamlLexer, YamlTokenKind};
+use crate::value::Value;
+#[cfg(feature = "yaml")]
+use crate::yaml::parser::parse_scalar_value;
+
+/// Byte range of the input and its class
+pub type Highlight = (Range<usize>, TokenClass);
+
+/// Syntactic role of a highlighted range
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TokenClass {
+    /// Object or mapping key, TOML table name, XML attribute name or CSV
+    /// header field
+    Key,
+    /// String value including its quotes, XML attribute value or CDATA section
+    String,
+    /// Number, including TOML `inf` and `nan`
+    Number,
+    /// `true` or `false`
+    Boolean,
+    /// `null` or YAML `~`
+    Null,
+    /// TOML date-time
+    Datetime,
+    /// Brackets, braces, separators and XML tag delimiters
+    Punctuation,
+    /// Comment, including its `#`, `//` or `<!--` markers
+    Comment,
+    /// YAML tag such as `!!str` or XML element name
+    Tag,
+    /// XML declaration, processing instruction or DOCTYPE
+    Directive,
+    /// XML character data
+    Text,
+    /// Input from the first lexing error to the end
+    Invalid,
+}
+
+impl TokenClass {
+    /// Lowercase name, usable as a CSS class
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Key => "key",
+            Self::String => "string",
+            Self::Number => "number",
+            Self::Boolean => "boolean",
+            Self::Null => "null",
+            Self::Datetime => "datetime",
+            Self::Punctuation => "punctuation",
+            Self::Comment => "comment",
+            Self::Tag => "tag",
+            Self::Directive => "directive",
+            Self::Text => "text",
+            Self::Invalid => "invalid",
+        }
+    }
+}
+
+/// Classify the source text of `input` read as `format`.
+///
+/// JSON comments are recognised so JSONC highlights too. CSV is read with a
+/// comma delimiter; see [`highlight_csv`] for others. Formats whose cargo
+/// feature is off get no highlights.
+pub fn highlight(input: &str, format: Format) -> Vec<Highlight> {
+    match format {
+        Format::Json => json(input),
+        #[cfg(feature = "toml")]
+        Format::Toml => toml(input),
+        #[cfg(feature = "yaml")]
+        Format::Yaml => yaml(input),
+        #[cfg(feature = "xml")]
+        Format::Xml => xml(input),
+        Format::Csv => highlight_csv(input, b','),
+        #[allow(unreachable_patterns)]
+        _ => Vec::new(),
+    }
+}
+
+/// Classify CSV fields separated by `delimiter`: the header row as keys and
+/// other fields by the type CSV parsing infers for them
+pub fn highlight_csv(input: &str, delimiter: u8) -> Vec<Highlight> {
+    let bytes = input.as_bytes();
+    let mut highlights = Vec::new();
+    let mut header = true;
+    let mut index = 0;
+    while index < bytes.len() {
+        let start = index;
+        if bytes.get(index) == Some(&b'"') {
+            index = index.saturating_add(1);
+            loop {
+                match bytes.get(index) {
+                    None => {
+                        highlights.push((start..bytes.len(), TokenClass::Invalid));
+                        return highlights;
+                    }
+                    Some(b'"') if bytes.get(index.saturating_add(1)) == Some(&b'"') => {
+                        index = index.saturating_add(2);
+                    }
+                    Some(b'"') => {
+                        index = index.saturating_add(1);
+                        break;
+                    }
+                    Some(_) => index = index.saturating_add(1),
+                }
+            }
+            let class = if header {
+                TokenClass::Key
+            } else {
+                TokenClass::String
+            };
+            highlights.push((start..index, class));
+        }
+        while let Some(byte) = bytes.get(index) {
+            if *byte == delimiter || matches!(byte, b'\n' | b'\r') {
+                break;
+            }
+            index = index.saturating_add(1);
+        }
+        if let Some(field) = input.get(start..index)
+            && !field.is_empty()
+            && !field.starts_with('"')
+        {
+            let class = if header {
+                TokenClass::Key
+            } else {
+                match infer_primitive_value(field.trim()) {
+                    Some(Value::Null) => TokenClass::Null,
+                    Some(Value::Bool(_)) => TokenClass::Boolean,
+                    Some(_) => TokenClass::Number,
+                    None => TokenClass::String,
+                }
+            };
+            highlights.push((start..index, class));
+        }
+        match bytes.get(index) {
+            Some(byte) if *byte == delimiter => {
+                highlights.push((index..index.saturating_add(1), TokenClass::Punctuation));
+            }
+            Some(b'\n') => header = false,
+            _ => {}
+        }
+        index = index.saturating_add(1);
+    }
+    highlights
+}
+
+fn json(input: &str) -> Vec<Highlight> {
+    let mut lexer = JsonLexer::with_options(input.as_bytes(), true);
+    lexer.record_comments();
+    let (tokens, invalid) = collect(&mut lexer);
+    let mut highlights = Vec::with_capacity(tokens.len());
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        let class = match token.kind {
+            TokenKind::String(_) if next_is(&mut tokens, |kind| *kind == TokenKind::Colon) => {
+                TokenClass::Key
+            }
+            TokenKind::String(_) => TokenClass::String,
+            TokenKind::Number(_) => TokenClass::Number,
+            TokenKind::True | TokenKind::False => TokenClass::Boolean,
+            TokenKind::Null => TokenClass::Null,
+            _ => TokenClass::Punctuation,
+        };
+        highlights.push((range(token.span), class));
+    }
+    highlights.extend(
+        lexer
+            .take_comments()
+            .into_iter()
+            .map(|(span, _)| (range(span), TokenClass::Comment)),
+    );
+    highlights.sort_by_key(|(range, _)| range.start);
+    finish(input, highlights, invalid)
+}
+
+#[cfg(feature = "toml")]
+fn toml(input: &str) -> Vec<Highlight> {
+    let (tokens, invalid) = collect(&mut TomlLexer::new(input.as_bytes()));
+    let mut highlights = Vec::with_capacity(tokens.len());
+    let mut line_start = true;
+    let mut header = false;
+    // Open arrays and inline tables, inside which `[` never starts a header
+    let mut nesting = 0usize;
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        let key = header
+            || next_is(&mut tokens, |kind| {
+                matches!(kind, TomlTokenKind::Equals | TomlTokenKind::Dot)
+            });
+        let class = match &token.kind {
+            TomlTokenKind::Newline => None,
+            TomlTokenKind::LeftBracket | TomlTokenKind::DoubleLeftBracket
+                if line_start && nesting == 0 =>
+            {
+                header = true;
+                Some(TokenClass::Punctuation)
+            }
+            TomlTokenKind::RightBracket | TomlTokenKind::DoubleRightBracket if header => {
+                header = false;
+                Some(TokenClass::Punctuation)
+            }
+            TomlTokenKind::BareKey(_)
+            | TomlTokenKind::String(_)
+            | TomlTokenKind::Integer(_)
+            | TomlTokenKind::Float(_)
+            | TomlTokenKind::Bool(_)
+            | TomlTokenKind::Datetime(_)
+                if key =>
+            {
+                Some(TokenClass::Key)
+            }
+            TomlTokenKind::LeftBracket | TomlTokenKind::LeftBrace => {
+                nesting = nesting.saturating_add(1);
+                Some(TokenClass::Punctuation)
+            }
+            TomlTokenKind::RightBracket | TomlTokenKind::RightBrace => {
+                nesting = nesting.saturating_sub(1);
+                Some(TokenClass::Punctuation)
+            }
+            TomlTokenKind::BareKey(word) if matches!(word.as_str(), "inf" | "nan") => {
+                Some(TokenClass::Number)
+            }
+            TomlTokenKind::BareKey(_) => Some(TokenClass::Key),
+            TomlTokenKind::String(_) => Some(TokenClass::String),
+            TomlTokenKind::Integer(_) | TomlTokenKind::Float(_) => Some(TokenClass::Number),
+            TomlTokenKind::Bool(_) => Some(TokenClass::Boolean),
+            TomlTokenKind::Datetime(_) => Some(TokenClass::Datetime),
+            _ => Some(TokenClass::Punctuation),
+        };
+        line_start = token.kind == TomlTokenKind::Newline;
+        if let Some(class) = class {
+            highlights.push((range(token.span), class));
+        }
+    }
+    let end = invalid.unwrap_or(input.len());
+    gaps(input, &mut highlights, end, false);
+    finish(input, highlights, invalid)
+}
+
+#[cfg(feature = "yaml")]
+fn yaml(input: &str) -> Vec<Highlight> {
+    let (tokens, invalid) = collect(&mut YamlLexer::new(input.as_bytes()));
+    let mut highlights = Vec::with_capacity(tokens.len());
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        let class = match &token.kind {
+            YamlTokenKind::Indent | YamlTokenKind::Dedent | YamlTokenKind::Newline => continue,
+            YamlTokenKind::Scalar(_) | YamlTokenKind::QuotedScalar(_)
+                if next_is(&mut tokens, |kind| *kind == YamlTokenKind::Colon) =>
+            {
+                TokenClass::Key
+            }
+            YamlTokenKind::Scalar(text) => match parse_scalar_value(text) {
+                _ if text.starts_with('!') => TokenClass::Tag,
+                Value::Null => TokenClass::Null,
+                Value::Bool(_) => TokenClass::Boolean,
+                Value::Number(_) => TokenClass::Number,
+                _ => TokenClass::String,
+            },
+            YamlTokenKind::QuotedScalar(_) | YamlTokenKind::Binary(_) => TokenClass::String,
+            YamlTokenKind::Tag(_) => TokenClass::Tag,
+            _ => TokenClass::Punctuation,
+        };
+        highlights.push((range(token.span), class));
+    }
+    let end = invalid.unwrap_or(input.len());
+    gaps(input, &mut highlights, end, true);
+    finish(input, highlights, invalid)
+}
+
+#[cfg(feature = "xml")]
+fn xml(input: &str) -> Vec<Highlight> {
+    let mut highlights = Vec::new();
+    let mut index = 0;
+    while let Some(rest) = input.get(index..).filter(|rest| !rest.is_empty()) {
+        let end = if rest.starts_with("<!--") {
+            closing(input, index, "-->", TokenClass::Comment, &mut highlights)
+        } else if rest.starts_with("<![CDATA[") {
+            closing(input, index, "]]>", TokenClass::String, &mut highlights)
+        } else if rest.starts_with("<?") {
+            closing(input, index, "?>", TokenClass::Directive, &mut highlights)
+        } else if rest.starts_with("<!") {
+            closing(input, index, ">", TokenClass::Directive, &mut highlights)
+        } else if rest.starts_with('<') {
+            xml_tag(input, index, &mut highlights)
+        } else {
+            let text = rest
+                .find('<')
+                .map_or(rest, |end| rest.get(..end).unwrap_or(rest));
+            let trimmed = text.trim_start();
+            let start = index.saturating_add(text.len().saturating_sub(trimmed.len()));
+            let trimmed = trimmed.trim_end();
+            if !trimmed.is_empty() {
+                highlights.push((start..start.saturating_add(trimmed.len()), TokenClass::Text));
+            }
+            Some(index.saturating_add(text.len()))
+        };
+        let Some(end) = end else {
+            highlights.push((index..input.len(), TokenClass::Invalid));
+            break;
+        };
+        index = end;
+    }
+    highlights
+}
+
+/// Highlight a construct from `start` through the `close` delimiter as
+/// `class`, returning the index after it
+#[cfg(feature = "xml")]
+fn closing(
+    input: &str,
+    start: usize,
+    close: &str,
+    class: TokenClass,
+    highlights: &mut Vec<Highlight>,
+) -> Option<usize> {
+    let end = find(input, start, close)?.saturating_add(close.len());
+    highlights.push((start..end, class));
+    Some(end)
+}
+
+/// Highlight an element tag starting at the `<` at `start`, returning the
+/// index after its `>`; None when the tag is malformed or unterminated
+#[cfg(feature = "xml")]
+fn xml_tag(input: &str, start: usize, highlights: &mut Vec<Highlight>) -> Option<usize> {
+    let bytes = input.as_bytes();
+    let mut index = start.saturating_add(1);
+    if bytes.get(index) == Some(&b'/') {
+        index = index.saturating_add(1);
+    }
+    highlights.push((start..index, TokenClass::Punctuation));
+    let name_end = scan(bytes, index, |byte| {
+        !byte.is_ascii_whitespace() && !matches!(byte, b'/' | b'>')
+    });
+    if name_end == index {
+        return None;
+    }
+    highlights.push((index..name_end, TokenClass::Tag));
+    index = name_end;
+    loop {
+        index = scan(bytes, index, |byte| byte.is_ascii_whitespace());
+        match bytes.get(index)? {
+            b'>' => {
+                let end = index.saturating_add(1);
+                highlights.push((index..end, TokenClass::Punctuation));
+                return Some(end);
+            }
+            b'/' if bytes.get(index.saturating_add(1)) == Some(&b'>') => {
+                let end = index.saturating_add(2);
+                highlights.push((index..end, TokenClass::Punctuation));
+                return Some(end);
+            }
+            b'=' | b'"' | b'\'' | b'/' => return None,
+            _ => {}
+        }
+        let name_end = scan(bytes, index, |byte| {
+            !byte.is_ascii_whitespace() && !matches!(byte, b'=' | b'/' | b'>')
+        });
+        highlights.push((index..name_end, TokenClass::Key));
+        index = scan(bytes, name_end, |byte| byte.is_ascii_whitespace());
+        if bytes.get(index) != Some(&b'=') {
+            continue;
+        }
+        highlights.push((index..index.saturating_add(1), TokenClass::Punctuation));
+        index = scan(bytes, index.saturating_add(1), |byte| {
+            byte.is_ascii_whitespace()
+        });
+        let quote = match bytes.get(index)? {
+            b'"' => "\"",
+            b'\'' => "'",
+            _ => return None,
+        };
+        let end = find(input, index.saturating_add(1), quote)?.saturating_add(1);
+        highlights.push((index..end, TokenClass::String));
+        index = end;
+    }
+}
+
+/// Index of the first byte from `start` that fails `accept`
+#[cfg(any(feature = "toml", feature = "yaml", feature = "xml"))]
+fn scan(bytes: &[u8], start: usize, accept: impl Fn(u8) -> bool) -> usize {
+    bytes
+        .get(start..)
+        .and_then(|rest| rest.iter().position(|byte| !accept(*byte)))
+        .map_or(bytes.len(), |offset| start.saturating_add(offset))
+}
+
+/// Index of the next `pattern` at or after `start`
+#[cfg(feature = "xml")]
+fn find(input: &str, start: usize, pattern: &str) -> Option<usize> {
+    input
+        .get(start..)?
+        .find(pattern)
+        .map(|offset| start.saturating_add(offset))
+}
+
+/// Drain a lexer, returning its tokens and the offset of the first error
+fn collect<K>(
+    lexer: &mut impl Iterator<Item = Result<SpannedToken<K>>>,
+) -> (Vec<SpannedToken<K>>, Option<usize>) {
+    let mut tokens: Vec<SpannedToken<K>> = Vec::new();
+    for token in lexer {
+        match token {
+            Ok(token) => tokens.push(token),
+            Err(err) => {
+                let last = tokens.last().map_or(0, |token| token.span.end.offset);
+                return (tokens, Some(err.span().start.offset.max(last)));
+            }
+        }
+    }
+    (tokens, None)
+}
+
+/// Whether the next token matches `accept`
+fn next_is<K, I: Iterator<Item = SpannedToken<K>>>(
+    tokens: &mut Peekable<I>,
+    accept: impl Fn(&K) -> bool,
+) -> bool {
+    tokens.peek().is_some_and(|next| accept(&next.kind))
+}
+
+/// Add the `#` comments, and YAML `!tags` when `tags` is set, found between
+/// the highlighted tokens before `end`
+#[cfg(any(feature = "toml", feature = "yaml"))]
+fn gaps(input: &str, highlights: &mut Vec<Highlight>, end: usize, tags: bool) {
+    let bytes = input.as_bytes();
+    let mut found = Vec::new();
+    let tokens = highlights
+        .iter()
+        .map(|(range, _)| (range.start, range.end))
+        .chain(std::iter::once((end, end)));
+    let mut index = 0;
+    for (token_start, token_end) in tokens {
+        while index < token_start.min(end) {
+            match bytes.get(index) {
+                Some(b'#') => {
+                    let comment_end =
+                        scan(bytes, index, |byte| !matches!(byte, b'\n' | b'\r')).min(token_start);
+                    found.push((index..comment_end, TokenClass::Comment));
+                    index = comment_end;
+                }
+                Some(b'!') if tags => {
+                    let tag_end =
+                        scan(bytes, index, |byte| !byte.is_ascii_whitespace()).min(token_start);
+                    found.push((index..tag_end, TokenClass::Tag));
+                    index = tag_end;
+                }
+                _ => index = index.saturating_add(1),
+            }
+        }
+        index = index.max(token_end);
+    }
+    highlights.extend(found);
+    highlights.sort_by_key(|(range, _)| range.start);
+}
+
+/// Append the invalid tail starting at `invalid`, if any
+fn finish(input: &str, mut highlights: Vec<Highlight>, invalid: Option<usize>) -> Vec<Highlight> {
+    if let Some(start) = invalid.filter(|start| *start < input.len()) {
+        highlights.push((start..input.len(), TokenClass::Invalid));
+    }
+    highlights
+}
+
+const fn range(span: Span) -> Range<usize> {
+    span.start.offset..span.end.offset
+}
@@ -0,0 +1,186 @@
+//! Include directive resolution
+//!
+//! Large configurations can be split across files and stitched back together
+//! into a single [`Value`]:
+//!
+//! - In any format, an object with an [`INCLUDE_KEY`] entry (a path or an
+//!   array of paths) is replaced by the included documents, deep-merged in
+//!   order. Sibling keys are merged on top, so they override included values.
+//! - In YAML, a scalar of the form `!include path` is replaced by the
+//!   included document.
+//!
+//! Paths are relative to the including file. Every resolved file must live
+//! under the resolver's root directory, and include cycles are rejected.
+
+use std::path::{Path, PathBuf};
+
+use crate::convert::{ConvertOptions, Format, parse_with_options};
+use crate::detect_format_from_path;
+use crate::error::{Error, ErrorKind, Result, Span};
+use crate::merge::{MergeStrategy, merge_all};
+use crate::value::{Object, Value};
+
+/// Object key holding include paths
+pub const INCLUDE_KEY: &str = "$include";
+
+/// YAML tag marking a scalar as an include path
+pub(crate) const INCLUDE_TAG: &str = "!include";
+
+/// Loads documents and resolves their include directives
+#[derive(Clone, Debug)]
+pub struct Resolver {
+    root: PathBuf,
+    options: ConvertOptions,
+    stack: Vec<PathBuf>,
+}
+
+impl Resolver {
+    /// Create a resolver that only reads files under `root`
+    pub fn new(root: impl AsRef<Path>) -> Result<Self> {
+        let root = root.as_ref();
+        let root = root
+            .canonicalize()
+            .map_err(|err| include_error(root, format!("invalid include root: {err}")))?;
+        Ok(Self {
+            root,
+            options: ConvertOptions::default(),
+            stack: Vec::new(),
+        })
+    }
+
+    /// Parser options used for every loaded file
+    pub fn with_options(mut self, options: ConvertOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Load `path` and resolve all includes reachable from it
+    pub fn load(&mut self, path: impl AsRef<Path>) -> Result<Value> {
+        let path = path.as_ref();
+        let canonical = path.canonicalize().map_err(|err| {
+            include_error(path, format!("failed to open {}: {err}", path.display()))
+        })?;
+
+        if !canonical.starts_with(&self.root) {
+            return Err(include_error(
+                path,
+                format!(
+                    "{} is outside the include root {}",
+                    path.display(),
+                    self.root.display()
+                ),
+            ));
+        }
+        if self.stack.contains(&canonical) {
+            return Err(include_error(
+                path,
+                format!("include cycle detected at {}", path.display()),
+            ));
+        }
+
+        let format = detect_format_from_path(&canonical).ok_or_else(|| {
+            include_error(
+                path,
+                format!("could not infer format of {}", path.display()),
+            )
+        })?;
+        let input = std::fs::read_to_string(&canonical).map_err(|err| {
+            include_error(path, format!("failed to read {}: {err}", path.display()))
+        })?;
+        let mut value = parse_with_options(&input, format, &self.options)
+            .map_err(|err| err.map_message(|message| format!("{}: {message}", path.display())))?;
+
+        let dir = canonical
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| self.root.clone());
+        self.stack.push(canonical);
+        let resolved = self.resolve(&mut value, &dir, format);
+        self.stack.pop();
+        resolved?;
+        Ok(value)
+    }
+
+    fn resolve(&mut self, value: &mut Value, dir: &Path, format: Format) -> Result<()> {
+        match value {
+            Value::String(text) if format == Format::Yaml => {
+                if let Some(target) = text.strip_prefix(INCLUDE_TAG)
+                    && target.starts_with(char::is_whitespace)
+                {
+                    *value = self.load(dir.join(target.trim()))?;
+                }
+                Ok(())
+            }
+            Value::Array(items) => items
+                .iter_mut()
+                .try_for_each(|item| self.resolve(item, dir, format)),
+            Value::Object(entries) => {
+                let Some(spec) = entries.get(INCLUDE_KEY).cloned() else {
+                    return entries
+                        .iter_mut()
+                        .try_for_each(|(_, item)| self.resolve(item, dir, format));
+                };
+
+                let mut siblings: Object = std::mem::take(entries)
+                    .into_iter()
+                    .filter(|(key, _)| key != INCLUDE_KEY)
+                    .collect();
+                siblings
+                    .iter_mut()
+                    .try_for_each(|(_, item)| self.resolve(item, dir, format))?;
+
+                let mut included = Vec::new();
+                for target in include_targets(&spec)? {
+                    included.push(self.load(dir.join(target))?);
+                }
+                let mut merged = merge_all(included, MergeStrategy::Deep);
+                if !siblings.is_empty() {
+                    merged.merge(Value::Object(siblings), MergeStrategy::Deep);
+                }
+                *value = merged;
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Load `path`, resolving includes with its parent directory as the root
+pub fn load_with_includes(path: impl AsRef<Path>) -> Result<Value> {
+    let path = path.as_ref();
+    let root = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    Resolver::new(root)?.load(path)
+}
+
+fn include_targets(spec: &Value) -> Result<Vec<&str>> {
+    let invalid = || {
+        Error::with_message(
+            ErrorKind::InvalidInclude {
+                path: String::new(),
+            },
+            Span::empty(),
+            format!("{INCLUDE_KEY} must be a path or an array of paths"),
+        )
+    };
+    match spec {
+        Value::String(path) => Ok(vec![path.as_str()]),
+        Value::Array(paths) => paths
+            .iter()
+            .map(|path| path.as_string().ok_or_else(invalid))
+            .collect(),
+        _ => Err(invalid()),
+    }
+}
+
+fn include_error(path: &Path, message: String) -> Error {
+    Error::with_message(
+        ErrorKind::InvalidInclude {
+            path: path.display().to_string(),
+        },
+        Span::empty(),
+        message,
+    )
+}
@@ -0,0 +1,602 @@
+//! Incremental reparsing for editors
+//!
+//! [`IncrementalDocument`] keeps a TOML or YAML document split into top-level
+//! sections: a TOML section starts at each table header and a YAML section at
+//! each unindented key or sequence item. Every section is lexed and parsed on
+//! its own and keeps its diagnostics and highlights, so an edit only reparses
+//! the sections it touches and shifts the positions of the others. Keys
+//! defined by more than one section are reported when the sections are
+//! checked against each other. JSON, CSV and XML documents are a single
+//! section and reparse in full.
+//!
+//! ```
+//! use zparse::Format;
+//! use zparse::incremental::IncrementalDocument;
+//! # fn main() -> Result<(), zparse::Error> {
+//! let mut doc = IncrementalDocument::new(Format::Toml, "[a]\nx = 1\n[b]\ny = 2\n");
+//! let reparse = doc.edit(8..9, "true")?;
+//! assert_eq!(reparse.sections, 1);
+//! assert!(doc.errors().is_empty());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::ops::Range;
+
+use crate::convert::{ConvertOptions, Format};
+use crate::error::{Error, ErrorKind, Pos, Result, Span};
+use crate::highlight::{Highlight, highlight};
+#[cfg(feature = "toml")]
+use crate::lexer::{TomlLexer, TomlTokenKind};
+#[cfg(feature = "toml")]
+use crate::toml;
+use crate::value::{Object, Value};
+use crate::warning::{Warning, WarningKind};
+#[cfg(feature = "yaml")]
+use crate::yaml;
+use crate::{json, parse_bytes_with_options};
+
+/// Part of the document reparsed by [`IncrementalDocument::edit`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Reparse {
+    /// Byte range of the edited text that was lexed and parsed again
+    pub range: Range<usize>,
+    /// Number of sections parsed
+    pub sections: usize,
+}
+
+/// A document kept parsed across edits
+#[derive(Clone, Debug)]
+pub struct IncrementalDocument {
+    format: Format,
+    options: ConvertOptions,
+    text: String,
+    /// Contiguous sections covering the whole text
+    sections: Vec<Section>,
+}
+
+/// TOML table header that opens a section
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Header {
+    path: Vec<String>,
+    /// Whether the header is an `[[array]]` of tables
+    array: bool,
+}
+
+/// Top-level section, with positions relative to its first byte
+#[derive(Clone, Debug)]
+struct Section {
+    /// Byte offset of the section in the document
+    start: usize,
+    /// 1-based line number of the first line
+    line: u32,
+    len: usize,
+    /// Number of line breaks in the section
+    breaks: u32,
+    header: Option<Header>,
+    value: Result<Value>,
+    warnings: Vec<Warning>,
+    highlights: Vec<Highlight>,
+}
+
+impl IncrementalDocument {
+    /// Parse `text` as `format`
+    pub fn new(format: Format, text: impl Into<String>) -> Self {
+        Self::with_options(format, text, ConvertOptions::default())
+    }
+
+    /// Parse `text` as `format` with the parser settings of `options`, e.g.
+    /// to accept comments in JSONC
+    pub fn with_options(format: Format, text: impl Into<String>, options: ConvertOptions) -> Self {
+        let text = text.into();
+        let sections = parse_region(format, &options, &text, 0, 1).0;
+        Self {
+            format,
+            options,
+            text,
+            sections,
+        }
+    }
+
+    pub const fn options(&self) -> &ConvertOptions {
+        &self.options
+    }
+
+    pub const fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Byte ranges of the sections, in document order
+    pub fn sections(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        self.sections
+            .iter()
+            .map(|section| section.start..section.start.saturating_add(section.len))
+    }
+
+    /// Replace the bytes in `range` with `text` and reparse the sections the
+    /// edit touches.
+    ///
+    /// A neighbouring section is also reparsed when the edit removes the
+    /// header or key that separated them, or leaves an unclosed TOML string
+    /// or array that may now swallow the next header. Fails without
+    /// changing the document when `range` is out of bounds or splits a
+    /// character.
+    pub fn edit(&mut self, range: Range<usize>, text: &str) -> Result<Reparse> {
+        if range.start > range.end
+            || !self.text.is_char_boundary(range.start)
+            || !self.text.is_char_boundary(range.end)
+        {
+            return Err(Error::with_message(
+                ErrorKind::InvalidToken,
+                Span::empty(),
+                format!(
+                    "edit range {}..{} is not within the {}-byte document",
+                    range.start,
+                    range.end,
+                    self.text.len()
+                ),
+            ));
+        }
+
+        let containing = |offset: usize| {
+            self.sections
+                .iter()
+                .rposition(|section| section.start <= offset)
+                .unwrap_or(0)
+        };
+        let mut first = containing(range.start);
+        let mut last = containing(range.end);
+
+        let old_len = self.text.len();
+        self.text.replace_range(range.clone(), text);
+        let grown = self.text.len().wrapping_sub(old_len);
+
+        loop {
+            let (start, line) = self
+                .sections
+                .get(first)
+                .map_or((0, 1), |section| (section.start, section.line));
+            let old_end = self
+                .sections
+                .get(last)
+                .map_or(old_len, |section| section.start.saturating_add(section.len));
+            let end = old_end.wrapping_add(grown);
+            let region = self.text.get(start..end).unwrap_or_default();
+            let (parsed, open) = parse_region(self.format, &self.options, region, start, line);
+            // Text that no longer opens a section belongs to the one before.
+            let opens = match self.format {
+                Format::Toml => parsed
+                    .first()
+                    .is_some_and(|section| section.header.is_some()),
+                Format::Yaml => starts_yaml_section(region),
+                Format::Json | Format::Csv | Format::Xml => true,
+            };
+            if first > 0 && !opens {
+                first -= 1;
+                continue;
+            }
+            if open && last.saturating_add(1) < self.sections.len() {
+                last += 1;
+                continue;
+            }
+
+            let old_breaks: u32 = self
+                .sections
+                .get(first..=last)
+                .map_or(0, |sections| sections.iter().map(|s| s.breaks).sum());
+            let new_breaks: u32 = parsed.iter().map(|section| section.breaks).sum();
+            let count = parsed.len();
+            let tail = last.saturating_add(1);
+            for section in self.sections.get_mut(tail..).unwrap_or_default() {
+                section.start = section.start.wrapping_add(grown);
+                section.line = section
+                    .line
+                    .saturating_add(new_breaks)
+                    .saturating_sub(old_breaks);
+            }
+            self.sections.splice(first..tail, parsed);
+            return Ok(Reparse {
+                range: start..end,
+                sections: count,
+            });
+        }
+    }
+
+    /// Parse errors of every section and conflicts between sections
+    pub fn errors(&self) -> Vec<Error> {
+        let mut errors: Vec<Error> = self
+            .sections
+            .iter()
+            .filter_map(|section| {
+                let err = section.value.as_ref().err()?;
+                Some(Error::with_message(
+                    err.kind().clone(),
+                    section.relocate(err.span()),
+                    err.message(),
+                ))
+            })
+            .collect();
+        if self.format == Format::Toml {
+            errors.extend(self.toml_conflicts());
+        }
+        errors
+    }
+
+    /// Warnings of every section, and YAML keys repeated across sections
+    pub fn warnings(&self) -> Vec<Warning> {
+        let mut warnings: Vec<Warning> = self
+            .sections
+            .iter()
+            .flat_map(|section| {
+                section.warnings.iter().map(|warning| {
+                    Warning::new(warning.kind().clone(), section.relocate(warning.span()))
+                })
+            })
+            .collect();
+        if self.format == Format::Yaml {
+            let mut seen = Object::new();
+            for section in &self.sections {
+                let Ok(Value::Object(obj)) = &section.value else {
+                    continue;
+                };
+                for key in obj.keys() {
+                    if seen.insert(key.clone(), Value::Null).is_some() {
+                        warnings.push(Warning::new(
+                            WarningKind::DuplicateKey { key: key.clone() },
+                            section.first_line(),
+                        ));
+                    }
+                }
+            }
+        }
+        warnings
+    }
+
+    /// Highlights of the whole document, as [`crate::highlight::highlight`]
+    /// would return them
+    pub fn highlights(&self) -> Vec<Highlight> {
+        self.sections
+            .iter()
+            .flat_map(|section| {
+                section.highlights.iter().map(|(range, class)| {
+                    (
+                        range.start.saturating_add(section.start)
+                            ..range.end.saturating_add(section.start),
+                        *class,
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// The document's value, merged from the sections; fails with the first
+    /// error of [`IncrementalDocument::errors`]
+    pub fn value(&self) -> Result<Value> {
+        if let Some(err) = self.errors().into_iter().next() {
+            return Err(err);
+        }
+        let mut values = self.sections.iter().filter_map(|section| {
+            let value = section.value.as_ref().ok()?;
+            let blank = self.format == Format::Yaml
+                && value.is_null()
+                && self.sections.len() > 1
+                && !self.section_has_content(section);
+            (!blank).then_some((section, value))
+        });
+        let Some((_, first)) = values.next() else {
+            return Ok(Value::Null);
+        };
+        let mut merged = first.clone();
+        for (section, value) in values {
+            let merged_section = if self.format == Format::Yaml {
+                merge_yaml(&mut merged, value.clone())
+            } else {
+                merge(&mut merged, value.clone())
+            };
+            merged_section.map_err(|key| {
+                Error::with_message(
+                    ErrorKind::DuplicateKey { key: key.clone() },
+                    section.first_line(),
+                    format!("key {key:?} is defined in more than one section"),
+                )
+            })?;
+        }
+        Ok(merged)
+    }
+
+    /// Whether the section holds more than comments and blank lines
+    fn section_has_content(&self, section: &Section) -> bool {
+        self.text
+            .get(section.start..section.start.saturating_add(section.len))
+            .is_some_and(|text| {
+                text.lines().any(|line| {
+                    let line = line.trim();
+                    !line.is_empty() && !line.starts_with('#')
+                })
+            })
+    }
+
+    /// TOML tables defined twice, and headers reopening a root key that is
+    /// not a table
+    fn toml_conflicts(&self) -> Vec<Error> {
+        let root = self
+            .sections
+            .first()
+            .filter(|section| section.header.is_none())
+            .and_then(|section| section.value.as_ref().ok())
+            .and_then(Value::as_object);
+        let mut errors = Vec::new();
+        let mut headers: Vec<&Header> = Vec::new();
+        for section in &self.sections {
+            let Some(header) = &section.header else {
+                continue;
+            };
+            let key = header.path.join(".");
+            let reopened = headers
+                .iter()
+                .any(|seen| seen.path == header.path && !(seen.array && header.array));
+            let scalar_root = header.path.first().is_some_and(|name| {
+                root.and_then(|root| root.get(name))
+                    .is_some_and(|value| !value.is_object() && !value.is_array())
+            });
+            if reopened || scalar_root {
+                errors.push(Error::with_message(
+                    ErrorKind::DuplicateKey { key: key.clone() },
+                    section.first_line(),
+                    format!("table {key:?} is defined more than once"),
+                ));
+            }
+            headers.push(header);
+        }
+        errors
+    }
+}
+
+impl Section {
+    /// Move a section-relative span into the document
+    fn relocate(&self, span: Span) -> Span {
+        if span == Span::empty() {
+            return self.first_line();
+        }
+        Span::new(self.pos(span.start), self.pos(span.end))
+    }
+
+    fn pos(&self, pos: Pos) -> Pos {
+        Pos::new(
+            pos.offset.saturating_add(self.start),
+            pos.line.saturating_add(self.line).saturating_sub(1),
+            pos.col,
+        )
+    }
+
+    /// Empty span at the start of the section
+    fn first_line(&self) -> Span {
+        let pos = Pos::new(self.start, self.line, 1);
+        Span::new(pos, pos)
+    }
+}
+
+/// Split `region`, which starts at byte `start` on `line` of the document,
+/// into sections and parse each one. Also returns whether the region ends
+/// inside an unterminated TOML string or array.
+fn parse_region(
+    format: Format,
+    options: &ConvertOptions,
+    region: &str,
+    start: usize,
+    line: u32,
+) -> (Vec<Section>, bool) {
+    let (splits, open) = match format {
+        #[cfg(feature = "toml")]
+        Format::Toml => split_toml(region),
+        Format::Yaml => (split_yaml(region), false),
+        _ => (vec![(0, None)], false),
+    };
+    let mut sections = Vec::with_capacity(splits.len());
+    let mut line = line;
+    let mut splits = splits.into_iter().peekable();
+    while let Some((offset, header)) = splits.next() {
+        let end = splits.peek().map_or(region.len(), |(next, _)| *next);
+        let text = region.get(offset..end).unwrap_or_default();
+        let (value, warnings) = parse_section(format, options, text);
+        let breaks = u32::try_from(text.matches('\n').count()).unwrap_or(u32::MAX);
+        sections.push(Section {
+            start: start.saturating_add(offset),
+            line,
+            len: text.len(),
+            breaks,
+            header,
+            value,
+            warnings,
+            highlights: highlight(text, format),
+        });
+        line = line.saturating_add(breaks);
+    }
+    (sections, open)
+}
+
+fn parse_section(
+    format: Format,
+    options: &ConvertOptions,
+    text: &str,
+) -> (Result<Value>, Vec<Warning>) {
+    match format {
+        #[cfg(feature = "toml")]
+        Format::Toml => {
+            let mut parser = toml::Parser::with_config(text.as_bytes(), options.toml);
+            (parser.parse(), parser.take_warnings())
+        }
+        #[cfg(feature = "yaml")]
+        Format::Yaml => {
+            let mut parser = yaml::Parser::with_config(text.as_bytes(), options.yaml);
+            (parser.parse(), parser.take_warnings())
+        }
+        Format::Json => {
+            let mut parser = json::Parser::with_config(text.as_bytes(), options.json);
+            (parser.parse_value(), parser.take_warnings())
+        }
+        _ => (
+            parse_bytes_with_options(text.as_bytes(), format, options),
+            Vec::new(),
+        ),
+    }
+}
+
+/// Section starts in a TOML region: the line of every table header outside
+/// of arrays and inline tables. After a lexing error the region is scanned
+/// again from the next line.
+#[cfg(feature = "toml")]
+fn split_toml(region: &str) -> (Vec<(usize, Option<Header>)>, bool) {
+    let mut splits = vec![(0, None)];
+    let mut resume = 0;
+    while let Some(rest) = region.get(resume..) {
+        let mut lexer = TomlLexer::new(rest.as_bytes());
+        let mut line_start = true;
+        let mut nesting = 0usize;
+        // Whether the tokens belong to the header that opened the last split
+        let mut in_header = false;
+        let mut failed = None;
+        for token in lexer.by_ref() {
+            let token = match token {
+                Ok(token) => token,
+                Err(err) => {
+                    failed = Some(err);
+                    break;
+                }
+            };
+            let offset = resume.saturating_add(token.span.start.offset);
+            match &token.kind {
+                TomlTokenKind::LeftBracket | TomlTokenKind::DoubleLeftBracket
+                    if line_start && nesting == 0 =>
+                {
+                    let header = Header {
+                        path: Vec::new(),
+                        array: token.kind == TomlTokenKind::DoubleLeftBracket,
+                    };
+                    let line = region
+                        .get(..offset)
+                        .and_then(|before| before.rfind('\n'))
+                        .map_or(0, |newline| newline.saturating_add(1));
+                    if line == 0 {
+                        splits = vec![(0, Some(header))];
+                    } else {
+                        splits.push((line, Some(header)));
+                    }
+                    in_header = true;
+                }
+                TomlTokenKind::RightBracket
+                | TomlTokenKind::DoubleRightBracket
+                | TomlTokenKind::Newline
+                    if in_header =>
+                {
+                    in_header = false;
+                }
+                TomlTokenKind::Dot if in_header => {}
+                kind if in_header => {
+                    let segment = match kind {
+                        TomlTokenKind::BareKey(text) | TomlTokenKind::String(text) => text.clone(),
+                        _ => rest
+                            .get(token.span.start.offset..token.span.end.offset)
+                            .unwrap_or_default()
+                            .to_string(),
+                    };
+                    if let Some((_, Some(header))) = splits.last_mut() {
+                        header.path.push(segment);
+                    }
+                }
+                TomlTokenKind::LeftBracket | TomlTokenKind::LeftBrace => {
+                    nesting = nesting.saturating_add(1);
+                }
+                TomlTokenKind::RightBracket | TomlTokenKind::RightBrace => {
+                    nesting = nesting.saturating_sub(1);
+                }
+                _ => {}
+            }
+            line_start = token.kind == TomlTokenKind::Newline;
+        }
+        match failed {
+            Some(err) if *err.kind() == ErrorKind::UnterminatedString => return (splits, true),
+            Some(err) => {
+                let at = resume.saturating_add(err.span().start.offset);
+                let Some(newline) = region.get(at..).and_then(|after| after.find('\n')) else {
+                    return (splits, false);
+                };
+                resume = at.saturating_add(newline).saturating_add(1);
+            }
+            None => return (splits, nesting > 0),
+        }
+    }
+    (splits, false)
+}
+
+/// Section starts in a YAML region: every unindented line other than
+/// comments, document markers and closing flow brackets
+fn split_yaml(region: &str) -> Vec<(usize, Option<Header>)> {
+    let mut splits = vec![(0, None)];
+    let mut offset = 0;
+    for line in region.split_inclusive('\n') {
+        if offset > 0 && starts_yaml_section(line) {
+            splits.push((offset, None));
+        }
+        offset += line.len();
+    }
+    splits
+}
+
+fn starts_yaml_section(line: &str) -> bool {
+    line.chars()
+        .next()
+        .is_some_and(|ch| !ch.is_whitespace() && !matches!(ch, '#' | ']' | '}'))
+        && !line.starts_with("---")
+        && !line.starts_with("...")
+}
+
+/// Merge a section's value into the document, appending to arrays and
+/// extending the last table of an array of tables; returns the conflicting
+/// key on failure
+fn merge(target: &mut Value, source: Value) -> std::result::Result<(), String> {
+    match (target, source) {
+        (Value::Object(target), Value::Object(source)) => {
+            for (key, value) in source {
+                match target.get_mut(&key) {
+                    Some(existing) => merge(existing, value).map_err(|_| key)?,
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+            Ok(())
+        }
+        (Value::Array(target), Value::Array(source)) => {
+            target.0.extend(source);
+            Ok(())
+        }
+        (Value::Array(target), source @ Value::Object(_)) => match target.0.last_mut() {
+            Some(last @ Value::Object(_)) => merge(last, source),
+            _ => Err(String::new()),
+        },
+        _ => Err(String::new()),
+    }
+}
+
+/// Merge a YAML section into the document: later top-level keys replace
+/// earlier ones, as the parser does within a section
+fn merge_yaml(target: &mut Value, source: Value) -> std::result::Result<(), String> {
+    match (target, source) {
+        (Value::Object(target), Value::Object(source)) => {
+            for (key, value) in source {
+                target.insert(key, value);
+            }
+            Ok(())
+        }
+        (Value::Array(target), Value::Array(source)) => {
+            target.0.extend(source);
+            Ok(())
+        }
+        _ => Err(String::new()),
+    }
+}
@@ -1,5 +1,126 @@
 //! Input abstraction for different sources
 
+use std::borrow::Cow;
+use std::path::Path;
+
+use crate::error::{Error, ErrorKind, Pos, Result, Span};
+use crate::value::Value;
+
+/// What to do with bytes that are not valid UTF-8 when a document is read
+/// from bytes ([`crate::parse_bytes_with_options`], [`crate::parse_path`],
+/// [`crate::FormatProvider::parse_bytes`])
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Utf8Mode {
+    /// Fail, pointing at the line and column of the first invalid byte
+    #[default]
+    Strict,
+    /// Replace each invalid sequence with U+FFFD, as
+    /// [`String::from_utf8_lossy`] does
+    Lossy,
+    /// Read strings that contain invalid bytes as [`Value::Bytes`], keeping
+    /// every byte. Keys are text, so invalid bytes in keys become U+FFFD, as
+    /// they do in operations that write text back such as
+    /// [`crate::ops::convert_file`].
+    Bytes,
+}
+
+/// First of the private-use characters that stand in for invalid bytes
+/// 0x80..=0xFF while a document is parsed in [`Utf8Mode::Bytes`]
+const STAND_IN: u32 = 0x10_FF00;
+
+/// `bytes` as text, handling invalid UTF-8 as `mode` says. In
+/// [`Utf8Mode::Bytes`] each invalid byte becomes a stand-in character that
+/// [`restore_bytes`] turns back into the byte after parsing.
+pub(crate) fn decode(bytes: &[u8], mode: Utf8Mode) -> Result<Cow<'_, str>> {
+    let err = match std::str::from_utf8(bytes) {
+        Ok(text) => return Ok(Cow::Borrowed(text)),
+        Err(err) => err,
+    };
+    match mode {
+        Utf8Mode::Strict => Err(invalid_utf8(bytes, err.valid_up_to())),
+        Utf8Mode::Lossy => Ok(String::from_utf8_lossy(bytes)),
+        Utf8Mode::Bytes => {
+            let mut text = String::with_capacity(bytes.len().saturating_mul(2));
+            for chunk in bytes.utf8_chunks() {
+                if chunk.valid().chars().any(is_stand_in) {
+                    return Err(Error::with_message(
+                        ErrorKind::InvalidToken,
+                        Span::empty(),
+                        "input uses U+10FF80..U+10FFFF, which stand for invalid bytes in bytes mode",
+                    ));
+                }
+                text.push_str(chunk.valid());
+                text.extend(
+                    chunk.invalid().iter().filter_map(|byte| {
+                        char::from_u32(STAND_IN.saturating_add(u32::from(*byte)))
+                    }),
+                );
+            }
+            Ok(Cow::Owned(text))
+        }
+    }
+}
+
+/// Error for invalid UTF-8 starting at byte `offset` of `bytes`
+fn invalid_utf8(bytes: &[u8], offset: usize) -> Error {
+    let before = bytes
+        .get(..offset)
+        .and_then(|before| std::str::from_utf8(before).ok())
+        .unwrap_or_default();
+    let line_start = before
+        .rfind('\n')
+        .map_or(0, |newline| newline.saturating_add(1));
+    let count = |n: usize| u32::try_from(n).unwrap_or(u32::MAX).saturating_add(1);
+    let line = count(before.matches('\n').count());
+    let col = count(before.get(line_start..).unwrap_or_default().chars().count());
+    let pos = Pos::new(offset, line, col);
+    let byte = bytes.get(offset).copied().unwrap_or_default();
+    Error::with_message(
+        ErrorKind::InvalidToken,
+        Span::new(
+            pos,
+            Pos::new(offset.saturating_add(1), line, col.saturating_add(1)),
+        ),
+        format!("input is not valid UTF-8: unexpected byte 0x{byte:02X}"),
+    )
+}
+
+fn is_stand_in(c: char) -> bool {
+    (STAND_IN + 0x80..=STAND_IN + 0xFF).contains(&u32::from(c))
+}
+
+/// Turn strings holding stand-ins from [`decode`] into [`Value::Bytes`], and
+/// stand-ins in keys into U+FFFD
+pub(crate) fn restore_bytes(value: &mut Value) {
+    let mut stack = vec![value];
+    while let Some(value) = stack.pop() {
+        match value {
+            Value::String(text) if text.chars().any(is_stand_in) => {
+                let mut bytes = Vec::with_capacity(text.len());
+                for c in text.chars() {
+                    match u32::from(c).checked_sub(STAND_IN).map(u8::try_from) {
+                        Some(Ok(byte)) if is_stand_in(c) => bytes.push(byte),
+                        _ => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+                    }
+                }
+                *value = Value::Bytes(bytes);
+            }
+            Value::Array(items) => stack.extend(items.iter_mut()),
+            Value::Object(entries) => {
+                let entries = entries.as_index_map_mut();
+                if entries.keys().any(|key| key.chars().any(is_stand_in)) {
+                    *entries = std::mem::take(entries)
+                        .into_iter()
+                        .map(|(key, value)| (key.replace(is_stand_in, "\u{fffd}"), value))
+                        .collect();
+                }
+                stack.extend(entries.values_mut());
+            }
+            _ => {}
+        }
+    }
+}
+
 /// Input source abstraction
 #[derive(Clone, Debug)]
 pub struct Input<'a> {
@@ -62,3 +183,53 @@ impl<'a> From<&'a [u8]> for Input<'a> {
         Self::from_bytes(b)
     }
 }
+
+/// A file read into memory once, to parse without copying it again into a
+/// `String`; peak memory stays close to the size of the file plus the parsed
+/// value.
+///
+/// ```no_run
+/// # fn main() -> zparse::Result<()> {
+/// let file = zparse::FileInput::open("export.json")?;
+/// let value = zparse::from_str(file.as_str()?)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FileInput {
+    bytes: Vec<u8>,
+    path: String,
+}
+
+impl FileInput {
+    /// Read the file at `path`
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|err| crate::ops::io_error(path, &err))?;
+        Ok(Self {
+            bytes,
+            path: path.display().to_string(),
+        })
+    }
+
+    /// The file's bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// The file's bytes as text, failing at the first byte that is not UTF-8
+    pub fn as_str(&self) -> Result<&str> {
+        std::str::from_utf8(&self.bytes).map_err(|err| invalid_utf8(&self.bytes, err.valid_up_to()))
+    }
+
+    /// The file's bytes as an [`Input`] named after the file
+    pub fn input(&self) -> Input<'_> {
+        Input::from_bytes(&self.bytes).with_filename(&self.path)
+    }
+}
+
+impl AsRef<[u8]> for FileInput {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
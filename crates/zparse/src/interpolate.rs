@@ -0,0 +1,117 @@
+//! Environment variable interpolation for parsed documents
+//!
+//! Placeholders are expanded in string values only; keys and non-string
+//! values are left untouched. Supported forms:
+//!
+//! - `${NAME}` expands to the value of `NAME`, failing if it is unset
+//! - `${NAME:-default}` expands to `default` when `NAME` is unset or empty
+//! - `$${` is an escape for a literal `${`
+//!
+//! A `$` not followed by `{` is kept as-is.
+
+use crate::error::{Error, ErrorKind, Result, Span};
+use crate::value::Value;
+
+impl Value {
+    /// Expand `${NAME}` / `${NAME:-default}` placeholders from the process environment
+    pub fn interpolate_env(&mut self) -> Result<()> {
+        self.interpolate_with(|name| std::env::var(name).ok())
+    }
+
+    /// Expand placeholders using `lookup` to resolve variable names
+    pub fn interpolate_with<F>(&mut self, mut lookup: F) -> Result<()>
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        interpolate_value(self, &mut lookup)
+    }
+}
+
+fn interpolate_value<F>(value: &mut Value, lookup: &mut F) -> Result<()>
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    match value {
+        Value::String(text) => {
+            if text.contains('$') {
+                *text = expand(text, lookup)?;
+            }
+            Ok(())
+        }
+        Value::Array(items) => items
+            .iter_mut()
+            .try_for_each(|item| interpolate_value(item, lookup)),
+        Value::Object(entries) => entries
+            .iter_mut()
+            .try_for_each(|(_, item)| interpolate_value(item, lookup)),
+        Value::Null | Value::Bool(_) | Value::Number(_) | Value::Datetime(_) | Value::Bytes(_) => {
+            Ok(())
+        }
+    }
+}
+
+fn expand<F>(text: &str, lookup: &mut F) -> Result<String>
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(idx) = rest.find('$') {
+        let (literal, tail) = rest.split_at(idx);
+        out.push_str(literal);
+        let after_dollar = tail.get(1..).unwrap_or_default();
+
+        if let Some(placeholder) = after_dollar.strip_prefix('{') {
+            let end = placeholder.find('}').ok_or_else(|| {
+                Error::with_message(
+                    ErrorKind::InvalidToken,
+                    Span::empty(),
+                    format!("unterminated placeholder in {text:?}"),
+                )
+            })?;
+            let (body, remainder) = placeholder.split_at(end);
+            out.push_str(&resolve(body, lookup)?);
+            rest = remainder.get(1..).unwrap_or_default();
+        } else if let Some(remainder) = after_dollar.strip_prefix("${") {
+            out.push_str("${");
+            rest = remainder;
+        } else {
+            out.push('$');
+            rest = after_dollar;
+        }
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn resolve<F>(body: &str, lookup: &mut F) -> Result<String>
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let (name, default) = match body.split_once(":-") {
+        Some((name, default)) => (name, Some(default)),
+        None => (body, None),
+    };
+
+    if name.is_empty() || !name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_') {
+        return Err(Error::with_message(
+            ErrorKind::InvalidToken,
+            Span::empty(),
+            format!("invalid variable name in placeholder ${{{body}}}"),
+        ));
+    }
+
+    match (lookup(name), default) {
+        (Some(value), Some(default)) if value.is_empty() => Ok(default.to_string()),
+        (Some(value), _) => Ok(value),
+        (None, Some(default)) => Ok(default.to_string()),
+        (None, None) => Err(Error::new(
+            ErrorKind::UndefinedVariable {
+                name: name.to_string(),
+            },
+            Span::empty(),
+        )),
+    }
+}
@@ -1,7 +1,9 @@
 //! JSON streaming parser module
 
 pub mod event;
+pub mod multi;
 pub mod parser;
 
 pub use event::Event;
+pub use multi::{MultiObject, MultiValue};
 pub use parser::{Config, Parser};
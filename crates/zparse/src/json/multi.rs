@@ -0,0 +1,179 @@
+//! Duplicate-preserving JSON documents
+//!
+//! [`Parser::parse_value`](crate::json::Parser::parse_value) keeps the last
+//! value of a repeated key, as most JSON readers do. Tools that need to see
+//! the raw structure (for example to flag smuggled duplicate keys) can use
+//! [`Parser::parse_multi`](crate::json::Parser::parse_multi) instead, which
+//! keeps every key/value pair in source order.
+
+use crate::value::{Object, Value};
+
+/// A JSON value whose objects keep repeated keys
+#[derive(Clone, Debug, PartialEq)]
+pub enum MultiValue {
+    /// Null, boolean, number or string
+    Scalar(Value),
+    /// Array of values
+    Array(Vec<Self>),
+    /// Object with every key/value pair in source order
+    Object(MultiObject),
+}
+
+impl MultiValue {
+    /// Returns the scalar if this is not a container
+    pub fn as_scalar(&self) -> Option<&Value> {
+        match self {
+            Self::Scalar(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements if this is an array
+    pub fn as_array(&self) -> Option<&[Self]> {
+        match self {
+            Self::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Returns the object if this is an object
+    pub fn as_object(&self) -> Option<&MultiObject> {
+        match self {
+            Self::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+
+    /// Returns true if any object in this value repeats a key
+    pub fn has_duplicates(&self) -> bool {
+        match self {
+            Self::Scalar(_) => false,
+            Self::Array(items) => items.iter().any(Self::has_duplicates),
+            Self::Object(object) => {
+                !object.duplicate_keys().is_empty()
+                    || object.iter().any(|(_, value)| value.has_duplicates())
+            }
+        }
+    }
+
+    /// Collapse into a [`Value`], keeping the last value of repeated keys
+    /// exactly as [`Parser::parse_value`](crate::json::Parser::parse_value) does
+    pub fn into_value(self) -> Value {
+        match self {
+            Self::Scalar(value) => value,
+            Self::Array(items) => Value::Array(items.into_iter().map(Self::into_value).collect()),
+            Self::Object(object) => {
+                let mut collapsed = Object::with_capacity(object.len());
+                for (key, value) in object.0 {
+                    collapsed.insert(key, value.into_value());
+                }
+                Value::Object(collapsed)
+            }
+        }
+    }
+}
+
+impl From<Value> for MultiValue {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Array(items) => Self::Array(items.into_iter().map(Self::from).collect()),
+            Value::Object(object) => Self::Object(MultiObject(
+                object
+                    .into_iter()
+                    .map(|(key, value)| (key, Self::from(value)))
+                    .collect(),
+            )),
+            scalar => Self::Scalar(scalar),
+        }
+    }
+}
+
+impl From<MultiValue> for Value {
+    fn from(value: MultiValue) -> Self {
+        value.into_value()
+    }
+}
+
+/// An object that keeps every key/value pair, including repeated keys
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MultiObject(Vec<(String, MultiValue)>);
+
+impl MultiObject {
+    /// Create an empty object
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of pairs, counting every occurrence of a repeated key
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if the object has no pairs
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Append a pair, even if `key` is already present
+    pub fn push(&mut self, key: impl Into<String>, value: impl Into<MultiValue>) {
+        self.0.push((key.into(), value.into()));
+    }
+
+    /// Returns the last value for `key`, the one a regular parse keeps
+    pub fn get(&self, key: &str) -> Option<&MultiValue> {
+        self.0
+            .iter()
+            .rev()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Returns every value for `key` in source order
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a MultiValue> {
+        self.0
+            .iter()
+            .filter(move |(name, _)| name == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Keys that occur more than once, in order of their first repetition
+    pub fn duplicate_keys(&self) -> Vec<&str> {
+        let mut duplicates: Vec<&str> = Vec::new();
+        for (index, (key, _)) in self.0.iter().enumerate() {
+            let seen = self
+                .0
+                .get(..index)
+                .is_some_and(|before| before.iter().any(|(name, _)| name == key));
+            if seen && !duplicates.contains(&key.as_str()) {
+                duplicates.push(key);
+            }
+        }
+        duplicates
+    }
+
+    /// Iterate over every pair in source order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &MultiValue)> {
+        self.0.iter().map(|(key, value)| (key.as_str(), value))
+    }
+}
+
+impl IntoIterator for MultiObject {
+    type Item = (String, MultiValue);
+    type IntoIter = std::vec::IntoIter<(String, MultiValue)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl FromIterator<(String, MultiValue)> for MultiObject {
+    fn from_iter<I: IntoIterator<Item = (String, MultiValue)>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl From<MultiObject> for MultiValue {
+    fn from(object: MultiObject) -> Self {
+        Self::Object(object)
+    }
+}
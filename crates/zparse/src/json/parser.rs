@@ -1,10 +1,14 @@
 //! JSON streaming parser implementation
 
+use std::collections::HashSet;
+
 use crate::error::{Error, ErrorKind, Result, Span};
 use crate::json::event::Event;
+use crate::json::multi::{MultiObject, MultiValue};
 use crate::lexer::json::JsonLexer;
 use crate::lexer::{Token, TokenKind};
 use crate::value::{Array, Object, Value};
+use crate::warning::{Warning, WarningKind};
 
 pub const DEFAULT_MAX_DEPTH: u16 = 128;
 pub const DEFAULT_MAX_SIZE: usize = 10 * 1024 * 1024;
@@ -79,6 +83,7 @@ enum ContainerContext {
 /// Streaming JSON parser with depth and size limits
 #[derive(Debug)]
 pub struct Parser<'a> {
+    input: &'a [u8],
     lexer: JsonLexer<'a>,
     config: Config,
     depth: u16,
@@ -93,6 +98,13 @@ pub struct Parser<'a> {
     is_first_element: bool,
     /// Whether we just consumed a comma and expect a key
     expecting_key: bool,
+    /// Whether the root value is complete, leaving only the end of input
+    root_done: bool,
+    /// Trailing commas accepted under `allow_trailing_commas`
+    trailing_commas_skipped: usize,
+    /// Span of the most recently lexed token
+    last_span: Span,
+    warnings: Vec<Warning>,
 }
 
 impl<'a> Parser<'a> {
@@ -104,6 +116,7 @@ impl<'a> Parser<'a> {
     /// Create a new parser with custom configuration
     pub fn with_config(input: &'a [u8], config: Config) -> Self {
         Self {
+            input,
             lexer: JsonLexer::with_options(input, config.allow_comments),
             config,
             depth: 0,
@@ -113,6 +126,10 @@ impl<'a> Parser<'a> {
             expecting_value: false,
             is_first_element: true,
             expecting_key: false,
+            root_done: false,
+            trailing_commas_skipped: 0,
+            last_span: Span::empty(),
+            warnings: Vec::new(),
         }
     }
 
@@ -121,6 +138,7 @@ impl<'a> Parser<'a> {
         let token = self.lexer.next_token()?;
 
         let span = token.span;
+        self.last_span = span;
         self.bytes_parsed = span.end.offset;
 
         // Check size limit after updating
@@ -139,6 +157,9 @@ impl<'a> Parser<'a> {
         if token.kind == TokenKind::Eof && self.context_stack.is_empty() {
             return Ok(None);
         }
+        if self.root_done && self.context_stack.is_empty() {
+            return Err(self.expected_error("end of input", &token));
+        }
 
         // Determine current context
         let current_context = self.context_stack.last().copied();
@@ -151,12 +172,16 @@ impl<'a> Parser<'a> {
     }
 
     /// Parse the complete input into a Value
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(format = "json"))
+    )]
     pub fn parse_value(&mut self) -> Result<Value> {
         let mut object_stack: Vec<Object> = Vec::new();
         let mut array_stack: Vec<Array> = Vec::new();
         // Stack to track keys for nested objects
-        let mut key_stack: Vec<Option<String>> = Vec::new();
-        let mut current_key: Option<String> = None;
+        let mut key_stack: Vec<Option<(String, Span)>> = Vec::new();
+        let mut current_key: Option<(String, Span)> = None;
 
         while let Some(event) = self.next_event()? {
             match event {
@@ -174,16 +199,16 @@ impl<'a> Parser<'a> {
 
                     if let Some(key) = obj_key {
                         if let Some(parent_obj) = object_stack.last_mut() {
-                            parent_obj.insert(key, Value::Object(obj));
+                            self.insert_entry(parent_obj, key, Value::Object(obj));
                         } else if let Some(parent_arr) = array_stack.last_mut() {
                             parent_arr.push(Value::Object(obj));
                         } else {
-                            return Ok(Value::Object(obj));
+                            return self.finish(Value::Object(obj));
                         }
                     } else if let Some(parent_arr) = array_stack.last_mut() {
                         parent_arr.push(Value::Object(obj));
                     } else {
-                        return Ok(Value::Object(obj));
+                        return self.finish(Value::Object(obj));
                     }
                 }
                 Event::ArrayStart => {
@@ -200,32 +225,32 @@ impl<'a> Parser<'a> {
 
                     if let Some(key) = arr_key {
                         if let Some(parent_obj) = object_stack.last_mut() {
-                            parent_obj.insert(key, Value::Array(arr));
+                            self.insert_entry(parent_obj, key, Value::Array(arr));
                         } else if let Some(parent_arr) = array_stack.last_mut() {
                             parent_arr.push(Value::Array(arr));
                         } else {
-                            return Ok(Value::Array(arr));
+                            return self.finish(Value::Array(arr));
                         }
                     } else if let Some(parent_arr) = array_stack.last_mut() {
                         parent_arr.push(Value::Array(arr));
                     } else {
-                        return Ok(Value::Array(arr));
+                        return self.finish(Value::Array(arr));
                     }
                 }
                 Event::Key(key) => {
-                    current_key = Some(key);
+                    current_key = Some((key, self.last_span));
                 }
                 Event::Value(value) => {
                     if let Some(key) = current_key.take() {
                         if let Some(parent_obj) = object_stack.last_mut() {
-                            parent_obj.insert(key, value);
+                            self.insert_entry(parent_obj, key, value);
                         } else {
-                            return Ok(value);
+                            return self.finish(value);
                         }
                     } else if let Some(parent_arr) = array_stack.last_mut() {
                         parent_arr.push(value);
                     } else {
-                        return Ok(value);
+                        return self.finish(value);
                     }
                 }
             }
@@ -246,6 +271,101 @@ impl<'a> Parser<'a> {
         Err(self.error(ErrorKind::InvalidToken))
     }
 
+    /// Check that the complete input is valid JSON without building a value.
+    ///
+    /// Strings are checked in place rather than copied, so apart from the
+    /// stack of open containers nothing is allocated. Errors are the ones
+    /// [`Self::parse_value`] would return. Duplicate keys are still recorded
+    /// as warnings, compared as written: `"a"` and `"\u0061"` count as two.
+    pub fn validate(&mut self) -> Result<()> {
+        self.lexer.skip_strings();
+        // Raw key text of each open object
+        let mut keys: Vec<HashSet<&'a [u8]>> = Vec::new();
+        let mut events = 0_usize;
+        while let Some(event) = self.next_event()? {
+            events = events.saturating_add(1);
+            match event {
+                Event::ObjectStart => keys.push(HashSet::new()),
+                Event::ObjectEnd => {
+                    keys.pop();
+                }
+                Event::Key(_) => {
+                    let span = self.last_span;
+                    let raw = self
+                        .input
+                        .get(span.start.offset..span.end.offset)
+                        .unwrap_or_default();
+                    let seen = keys.last_mut().is_some_and(|open| !open.insert(raw));
+                    if seen {
+                        let text = raw.get(1..raw.len().saturating_sub(1)).unwrap_or_default();
+                        self.warnings.push(Warning::new(
+                            WarningKind::DuplicateKey {
+                                key: String::from_utf8_lossy(text).into_owned(),
+                            },
+                            span,
+                        ));
+                    }
+                }
+                _ => {}
+            }
+        }
+        if events == 0 {
+            return Err(self.error(ErrorKind::InvalidToken));
+        }
+        Ok(())
+    }
+
+    /// Parse the complete input, keeping every pair of objects with repeated keys.
+    ///
+    /// See [`MultiValue`]; no duplicate-key warnings are recorded since nothing
+    /// is overwritten.
+    pub fn parse_multi(&mut self) -> Result<MultiValue> {
+        // Open containers with the key they will be stored under in their parent
+        let mut stack: Vec<(Option<String>, MultiValue)> = Vec::new();
+        let mut current_key: Option<String> = None;
+
+        while let Some(event) = self.next_event()? {
+            let value = match event {
+                Event::ObjectStart => {
+                    stack.push((current_key.take(), MultiValue::Object(MultiObject::new())));
+                    continue;
+                }
+                Event::ArrayStart => {
+                    stack.push((current_key.take(), MultiValue::Array(Vec::new())));
+                    continue;
+                }
+                Event::Key(key) => {
+                    current_key = Some(key);
+                    continue;
+                }
+                Event::ObjectEnd | Event::ArrayEnd => {
+                    let (key, container) = stack
+                        .pop()
+                        .ok_or_else(|| self.error(ErrorKind::InvalidToken))?;
+                    current_key = key;
+                    container
+                }
+                Event::Value(value) => MultiValue::from(value),
+            };
+
+            match stack.last_mut() {
+                None => return self.finish(value),
+                Some((_, MultiValue::Object(object))) => {
+                    let key = current_key
+                        .take()
+                        .ok_or_else(|| self.error(ErrorKind::InvalidToken))?;
+                    object.push(key, value);
+                }
+                Some((_, MultiValue::Array(items))) => items.push(value),
+                Some((_, MultiValue::Scalar(_))) => {
+                    return Err(self.error(ErrorKind::InvalidToken));
+                }
+            }
+        }
+
+        Err(self.error(ErrorKind::InvalidToken))
+    }
+
     /// Returns the parser configuration.
     pub fn config(&self) -> &Config {
         &self.config
@@ -261,8 +381,45 @@ impl<'a> Parser<'a> {
         self.bytes_parsed
     }
 
+    /// Returns the span of the token behind the most recent event, e.g. the
+    /// key of an [`Event::Key`].
+    pub fn event_span(&self) -> Span {
+        self.last_span
+    }
+
+    /// Returns the number of comments skipped so far.
+    pub fn comments_skipped(&self) -> usize {
+        self.lexer.comments_skipped()
+    }
+
+    /// Returns the number of trailing commas accepted so far.
+    pub fn trailing_commas_skipped(&self) -> usize {
+        self.trailing_commas_skipped
+    }
+
+    /// Drain the non-fatal warnings recorded so far.
+    ///
+    /// Duplicate keys are only detected by [`Self::parse_value`], which builds
+    /// the objects; the event API leaves deduplication to the caller.
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        let mut warnings = self.lexer.take_warnings();
+        warnings.append(&mut self.warnings);
+        warnings.sort_by_key(|warning| warning.span().start.offset);
+        warnings
+    }
+
     // Helper methods
 
+    fn insert_entry(&mut self, object: &mut Object, (key, span): (String, Span), value: Value) {
+        if object.contains_key(&key) {
+            self.warnings.push(Warning::new(
+                WarningKind::DuplicateKey { key: key.clone() },
+                span,
+            ));
+        }
+        object.insert(key, value);
+    }
+
     fn handle_root(&mut self, token: Token) -> Result<Option<Event>> {
         match token.kind {
             TokenKind::LeftBrace => {
@@ -277,20 +434,32 @@ impl<'a> Parser<'a> {
                 self.is_first_element = true;
                 Ok(Some(Event::ArrayStart))
             }
-            TokenKind::Null => Ok(Some(Event::Value(Value::Null))),
-            TokenKind::True => Ok(Some(Event::Value(Value::Bool(true)))),
-            TokenKind::False => Ok(Some(Event::Value(Value::Bool(false)))),
-            TokenKind::String(s) => Ok(Some(Event::Value(Value::String(s)))),
-            TokenKind::Number(n) => Ok(Some(Event::Value(Value::Number(n)))),
+            TokenKind::Null
+            | TokenKind::True
+            | TokenKind::False
+            | TokenKind::String(_)
+            | TokenKind::Number(_) => {
+                self.root_done = true;
+                self.parse_value_token(token)
+            }
             TokenKind::Eof => Ok(None),
             _ => Err(self.expected_error("value", &token)),
         }
     }
 
+    /// Return the root `value` once the rest of the input is confirmed empty
+    fn finish<T>(&mut self, value: T) -> Result<T> {
+        match self.next_event()? {
+            None => Ok(value),
+            Some(_) => Err(self.error(ErrorKind::InvalidToken)),
+        }
+    }
+
     fn handle_in_object(&mut self, token: Token) -> Result<Option<Event>> {
         if self.expecting_key {
             match token.kind {
                 TokenKind::RightBrace if self.config.allow_trailing_commas => {
+                    self.trailing_commas_skipped = self.trailing_commas_skipped.saturating_add(1);
                     self.expecting_key = false;
                     self.pop_context();
                     return Ok(Some(Event::ObjectEnd));
@@ -332,7 +501,7 @@ impl<'a> Parser<'a> {
                 self.pop_context();
                 Ok(Some(Event::ObjectEnd))
             }
-            TokenKind::String(s) if self.is_first_element || self.expect_comma() => {
+            TokenKind::String(s) if self.is_first_element => {
                 // This is a key
                 self.is_first_element = false;
                 self.expecting_colon_after_key = true;
@@ -362,6 +531,7 @@ impl<'a> Parser<'a> {
             TokenKind::RightBracket
                 if self.expecting_value && self.config.allow_trailing_commas =>
             {
+                self.trailing_commas_skipped = self.trailing_commas_skipped.saturating_add(1);
                 self.expecting_value = false;
                 self.pop_context();
                 Ok(Some(Event::ArrayEnd))
@@ -371,7 +541,7 @@ impl<'a> Parser<'a> {
                 self.expecting_value = true;
                 self.next_event()
             }
-            _ if self.is_first_element || self.expecting_value || self.expect_comma() => {
+            _ if self.is_first_element || self.expecting_value => {
                 self.is_first_element = false;
                 self.expecting_value = false;
                 self.parse_value_token(token)
@@ -424,11 +594,6 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn expect_comma(&self) -> bool {
-        // We expect a comma if we're not at the first element and not expecting a colon or value
-        !self.is_first_element && !self.expecting_colon_after_key && !self.expecting_value
-    }
-
     fn increment_depth(&mut self, opening_span: Span) -> Result<()> {
         if self.config.max_depth > 0 && self.depth >= self.config.max_depth {
             return Err(Error::at(
@@ -447,6 +612,7 @@ impl<'a> Parser<'a> {
     fn pop_context(&mut self) {
         self.context_stack.pop();
         self.depth = self.depth.saturating_sub(1);
+        self.root_done = self.context_stack.is_empty();
         // Reset state for next element in parent container
         if !self.context_stack.is_empty() {
             self.is_first_element = false;
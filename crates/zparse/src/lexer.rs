@@ -1,13 +1,50 @@
-//! Lexer module for tokenization
+//! Lexers for JSON, TOML and YAML
+//!
+//! Needs the `unstable` feature and is not covered by semver.
+//!
+//! Each lexer turns input bytes into [`SpannedToken`]s whose span covers the
+//! source text the token was read from, for tools such as syntax highlighters
+//! that need tokens rather than values. A lexer is an iterator of
+//! `Result<SpannedToken<_>>` that ends at the end of input and after the first
+//! error; `next_token` also returns the final `Eof` token.
+//!
+//! The token kinds are `#[non_exhaustive]`, so new tokens can be added
+//! without a breaking release; existing variants and their meaning are
+//! stable. Whitespace is never tokenized and comments are skipped (see
+//! [`JsonLexer::record_comments`] to keep JSON comments).
+//!
+//! ```
+//! # #[cfg(feature = "unstable")]
+//! # fn main() -> Result<(), zparse::Error> {
+//! use zparse::lexer::{TomlLexer, TomlTokenKind};
+//! let tokens = TomlLexer::new(b"port = 8080").collect::<Result<Vec<_>, _>>()?;
+//! let kinds: Vec<_> = tokens.into_iter().map(|token| token.kind).collect();
+//! assert_eq!(
+//!     kinds,
+//!     [
+//!         TomlTokenKind::BareKey("port".to_string()),
+//!         TomlTokenKind::Equals,
+//!         TomlTokenKind::Integer(8080),
+//!     ]
+//! );
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "unstable"))]
+//! # fn main() {}
+//! ```
 
 pub mod cursor;
 pub mod json;
 pub mod token;
+#[cfg(feature = "toml")]
 pub mod toml;
+#[cfg(feature = "yaml")]
 pub mod yaml;
 
 pub use cursor::Cursor;
 pub use json::JsonLexer;
-pub use token::{Token, TokenKind};
+pub use token::{SpannedToken, Token, TokenKind};
+#[cfg(feature = "toml")]
 pub use toml::{TomlLexer, TomlToken, TomlTokenKind};
+#[cfg(feature = "yaml")]
 pub use yaml::{YamlLexer, YamlToken, YamlTokenKind};
@@ -23,38 +23,21 @@ impl<'a> Cursor<'a> {
     }
 
     /// Get current byte without consuming
-    #[allow(clippy::indexing_slicing)]
-    // SAFETY: Bounds checked in condition
     pub const fn current(&self) -> Option<u8> {
-        if self.pos < self.input.len() {
-            Some(self.input[self.pos])
-        } else {
-            None
-        }
+        self.peek(0)
     }
 
     /// Peek at byte ahead without consuming
-    #[allow(clippy::indexing_slicing)]
-    // SAFETY: Bounds checked in condition
     pub const fn peek(&self, ahead: usize) -> Option<u8> {
-        let idx = self.pos.saturating_add(ahead);
-        if idx < self.input.len() {
-            Some(self.input[idx])
-        } else {
-            None
+        match self.input.split_at_checked(self.pos.saturating_add(ahead)) {
+            Some((_, [byte, ..])) => Some(*byte),
+            _ => None,
         }
     }
 
     /// Peek at n bytes ahead without consuming
-    #[allow(clippy::indexing_slicing)]
-    // SAFETY: Bounds checked in condition
     pub fn peek_bytes(&self, n: usize) -> Option<&[u8]> {
-        let end = self.pos.saturating_add(n);
-        if end <= self.input.len() {
-            Some(&self.input[self.pos..end])
-        } else {
-            None
-        }
+        self.input.get(self.pos..self.pos.saturating_add(n))
     }
 
     /// Advance cursor by n bytes
@@ -112,10 +95,8 @@ impl<'a> Cursor<'a> {
     }
 
     /// Get remaining bytes
-    #[allow(clippy::indexing_slicing)]
-    // SAFETY: Bounds checked - pos is always <= input.len()
     pub fn remaining(&self) -> &[u8] {
-        &self.input[self.pos..]
+        self.input.get(self.pos..).unwrap_or_default()
     }
 
     /// Get current position index
@@ -123,10 +104,8 @@ impl<'a> Cursor<'a> {
         self.pos
     }
 
-    /// Get slice from start to current position
-    #[allow(clippy::indexing_slicing)]
-    // SAFETY: Bounds checked - start is expected to be a valid position <= pos <= input.len()
+    /// Get slice from start to current position (empty if start is past it)
     pub fn slice_from(&self, start: usize) -> &'a [u8] {
-        &self.input[start..self.pos]
+        self.input.get(start..self.pos).unwrap_or_default()
     }
 }
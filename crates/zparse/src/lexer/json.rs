@@ -1,14 +1,25 @@
 //! JSON-specific lexer
 
-use crate::error::{Error, ErrorKind, Result, Span};
+use std::iter::FusedIterator;
+
+use crate::error::{Error, ErrorKind, Pos, Result, Span};
 use crate::lexer::cursor::Cursor;
 use crate::lexer::token::{Token, TokenKind};
+use crate::warning::{Warning, WarningKind};
 
 /// JSON lexer that tokenizes JSON input
 #[derive(Clone, Debug)]
 pub struct JsonLexer<'a> {
     cursor: Cursor<'a>,
     allow_comments: bool,
+    comments_skipped: usize,
+    /// Source text of each skipped comment, when recording
+    comments: Option<Vec<(Span, String)>>,
+    warnings: Vec<Warning>,
+    /// Check strings without copying them; string tokens are then empty
+    skip_strings: bool,
+    /// Whether iteration reached the end of input or an error
+    done: bool,
 }
 
 impl<'a> JsonLexer<'a> {
@@ -17,6 +28,11 @@ impl<'a> JsonLexer<'a> {
         Self {
             cursor: Cursor::new(input),
             allow_comments: false,
+            comments_skipped: 0,
+            comments: None,
+            warnings: Vec::new(),
+            skip_strings: false,
+            done: false,
         }
     }
 
@@ -25,9 +41,44 @@ impl<'a> JsonLexer<'a> {
         Self {
             cursor: Cursor::new(input),
             allow_comments,
+            comments_skipped: 0,
+            comments: None,
+            warnings: Vec::new(),
+            skip_strings: false,
+            done: false,
         }
     }
 
+    /// Number of comments skipped so far
+    pub const fn comments_skipped(&self) -> usize {
+        self.comments_skipped
+    }
+
+    /// Start recording the comments skipped, see [`JsonLexer::take_comments`]
+    pub fn record_comments(&mut self) {
+        self.comments.get_or_insert_with(Vec::new);
+    }
+
+    /// Drain the recorded comments in source order, each with its span and
+    /// its text including the `//` or `/* */` delimiters
+    pub fn take_comments(&mut self) -> Vec<(Span, String)> {
+        self.comments
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Check strings without copying them, for validation: string tokens
+    /// are empty but errors and spans are unchanged
+    pub(crate) fn skip_strings(&mut self) {
+        self.skip_strings = true;
+    }
+
+    /// Drain the warnings recorded so far
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
     /// Get the next token from the input
     pub fn next_token(&mut self) -> Result<Token> {
         self.skip_ignorable()?;
@@ -84,10 +135,13 @@ impl<'a> JsonLexer<'a> {
 
     /// Lex a string literal
     fn lex_string(&mut self) -> Result<TokenKind> {
+        let start_pos = self.cursor.position();
         // Consume opening quote
         self.cursor.advance();
 
-        let mut result = String::new();
+        // Raw bytes so multi-byte UTF-8 sequences are copied through intact
+        let mut result = Vec::new();
+        let copy = !self.skip_strings;
 
         loop {
             match self.cursor.current() {
@@ -115,19 +169,24 @@ impl<'a> JsonLexer<'a> {
                             ));
                         }
                         Some(escape_char) => {
-                            match escape_char {
-                                b'"' => result.push('"'),
-                                b'\\' => result.push('\\'),
-                                b'/' => result.push('/'),
-                                b'b' => result.push('\x08'),
-                                b'f' => result.push('\x0C'),
-                                b'n' => result.push('\n'),
-                                b'r' => result.push('\r'),
-                                b't' => result.push('\t'),
+                            let byte = match escape_char {
+                                b'"' => b'"',
+                                b'\\' => b'\\',
+                                b'/' => b'/',
+                                b'b' => 0x08,
+                                b'f' => 0x0C,
+                                b'n' => b'\n',
+                                b'r' => b'\r',
+                                b't' => b'\t',
                                 b'u' => {
                                     self.cursor.advance();
                                     let code_point = self.lex_unicode_escape()?;
-                                    result.push(code_point);
+                                    if copy {
+                                        let mut buf = [0; 4];
+                                        result.extend_from_slice(
+                                            code_point.encode_utf8(&mut buf).as_bytes(),
+                                        );
+                                    }
                                     continue;
                                 }
                                 _ => {
@@ -138,6 +197,9 @@ impl<'a> JsonLexer<'a> {
                                         self.cursor.position().col,
                                     ));
                                 }
+                            };
+                            if copy {
+                                result.push(byte);
                             }
                             self.cursor.advance();
                         }
@@ -153,13 +215,37 @@ impl<'a> JsonLexer<'a> {
                             self.cursor.position().col,
                         ));
                     }
-                    result.push(char::from(b));
+                    if copy {
+                        result.push(b);
+                    }
                     self.cursor.advance();
                 }
             }
         }
 
-        Ok(TokenKind::String(result))
+        if !copy {
+            // Escapes are ASCII, so the raw text is UTF-8 exactly when the
+            // decoded string would be
+            return std::str::from_utf8(self.cursor.slice_from(start_pos.offset))
+                .map(|_| TokenKind::String(String::new()))
+                .map_err(|_| {
+                    Error::with_message(
+                        ErrorKind::InvalidToken,
+                        Span::new(start_pos, self.cursor.position()),
+                        "string is not valid UTF-8",
+                    )
+                });
+        }
+
+        String::from_utf8(result)
+            .map(TokenKind::String)
+            .map_err(|_| {
+                Error::with_message(
+                    ErrorKind::InvalidToken,
+                    Span::new(start_pos, self.cursor.position()),
+                    "string is not valid UTF-8",
+                )
+            })
     }
 
     fn skip_ignorable(&mut self) -> Result<()> {
@@ -180,15 +266,18 @@ impl<'a> JsonLexer<'a> {
     }
 
     fn skip_comment(&mut self) -> Result<()> {
+        self.comments_skipped = self.comments_skipped.saturating_add(1);
+        let start = self.cursor.position();
         match self.cursor.peek(1) {
             Some(b'/') => {
                 self.cursor.advance_by(2);
                 while let Some(b) = self.cursor.current() {
-                    self.cursor.advance();
-                    if b == b'\n' {
+                    if b == b'\n' || b == b'\r' {
                         break;
                     }
+                    self.cursor.advance();
                 }
+                self.record_comment(start);
                 Ok(())
             }
             Some(b'*') => {
@@ -196,6 +285,7 @@ impl<'a> JsonLexer<'a> {
                 while self.cursor.current().is_some() {
                     if self.cursor.peek_bytes(2) == Some(b"*/") {
                         self.cursor.advance_by(2);
+                        self.record_comment(start);
                         return Ok(());
                     }
                     self.cursor.advance();
@@ -216,8 +306,40 @@ impl<'a> JsonLexer<'a> {
         }
     }
 
-    /// Lex a unicode escape sequence (\uXXXX)
+    fn record_comment(&mut self, start: Pos) {
+        if let Some(comments) = self.comments.as_mut() {
+            let text = String::from_utf8_lossy(self.cursor.slice_from(start.offset));
+            let span = Span::new(start, self.cursor.position());
+            comments.push((span, text.into_owned()));
+        }
+    }
+
+    /// Lex a unicode escape sequence (\uXXXX), joining a UTF-16 surrogate
+    /// pair written as two escapes
     fn lex_unicode_escape(&mut self) -> Result<char> {
+        let start_pos = self.cursor.position();
+        let mut code = self.lex_hex4()?;
+
+        if (0xD800..0xDC00).contains(&code) && self.cursor.peek_bytes(2) == Some(b"\\u") {
+            self.cursor.advance_by(2);
+            let low = self.lex_hex4()?;
+            if (0xDC00..0xE000).contains(&low) {
+                code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+            }
+        }
+
+        char::from_u32(code).ok_or_else(|| {
+            Error::at(
+                ErrorKind::InvalidUnicodeEscape,
+                start_pos.offset,
+                start_pos.line,
+                start_pos.col,
+            )
+        })
+    }
+
+    /// Lex the four hex digits of a unicode escape
+    fn lex_hex4(&mut self) -> Result<u32> {
         let start_pos = self.cursor.position();
         let mut code: u32 = 0;
 
@@ -251,14 +373,7 @@ impl<'a> JsonLexer<'a> {
             }
         }
 
-        char::from_u32(code).ok_or_else(|| {
-            Error::at(
-                ErrorKind::InvalidUnicodeEscape,
-                start_pos.offset,
-                start_pos.line,
-                start_pos.col,
-            )
-        })
+        Ok(code)
     }
 
     /// Lex null literal
@@ -312,6 +427,7 @@ impl<'a> JsonLexer<'a> {
     /// Lex a number literal
     fn lex_number(&mut self) -> Result<TokenKind> {
         let start = self.cursor.pos();
+        let start_pos = self.cursor.position();
 
         // Optional minus sign
         if self.cursor.current() == Some(b'-') {
@@ -390,23 +506,43 @@ impl<'a> JsonLexer<'a> {
             Error::at(ErrorKind::InvalidNumber, pos.offset, pos.line, pos.col)
         })?;
 
+        // Fractions are inexact by nature; only integers are worth flagging,
+        // and those of up to 15 digits are always exact.
+        let is_integer = !num_str.contains(['.', 'e', 'E']);
+        if is_integer && num_str.len() > 15 && num.to_string() != num_str {
+            self.warnings.push(Warning::new(
+                WarningKind::PrecisionLoss {
+                    literal: num_str.to_string(),
+                },
+                Span::new(start_pos, self.cursor.position()),
+            ));
+        }
+
         Ok(TokenKind::Number(num))
     }
 }
 
-impl<'a> Iterator for JsonLexer<'a> {
+/// Yields tokens up to (not including) [`TokenKind::Eof`], stopping after
+/// the first error
+impl Iterator for JsonLexer<'_> {
     type Item = Result<Token>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
         match self.next_token() {
-            Ok(token) => {
-                if token.kind == TokenKind::Eof {
-                    None
-                } else {
-                    Some(Ok(token))
-                }
+            Ok(token) if token.kind == TokenKind::Eof => {
+                self.done = true;
+                None
+            }
+            Ok(token) => Some(Ok(token)),
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
             }
-            Err(e) => Some(Err(e)),
         }
     }
 }
+
+impl FusedIterator for JsonLexer<'_> {}
@@ -1,28 +1,34 @@
-//! Token types for JSON lexer
+//! Token types shared by the lexers, and the JSON token kinds
 
 use crate::error::Span;
 
 /// JSON token types
 #[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum TokenKind {
-    // Structural
-    LeftBrace,    // {
-    RightBrace,   // }
-    LeftBracket,  // [
-    RightBracket, // ]
-    Colon,        // :
-    Comma,        // ,
-
-    // Literals
+    /// `{`
+    LeftBrace,
+    /// `}`
+    RightBrace,
+    /// `[`
+    LeftBracket,
+    /// `]`
+    RightBracket,
+    /// `:`
+    Colon,
+    /// `,`
+    Comma,
+    /// `null`
     Null,
+    /// `true`
     True,
+    /// `false`
     False,
-
-    // Values
+    /// String with escapes resolved; the span includes the quotes
     String(String),
+    /// Number literal
     Number(f64),
-
-    // Special
+    /// End of input, returned by `next_token` but not by the iterator
     Eof,
 }
 
@@ -60,18 +66,23 @@ impl TokenKind {
     }
 }
 
-/// Token with source location
+/// Token of kind `K` with the source range it was read from
 #[derive(Clone, Debug, PartialEq)]
-pub struct Token {
-    pub kind: TokenKind,
+pub struct SpannedToken<K> {
+    pub kind: K,
     pub span: Span,
 }
 
-impl Token {
-    pub const fn new(kind: TokenKind, span: Span) -> Self {
+impl<K> SpannedToken<K> {
+    pub const fn new(kind: K, span: Span) -> Self {
         Self { kind, span }
     }
+}
+
+/// JSON token with source location
+pub type Token = SpannedToken<TokenKind>;
 
+impl Token {
     pub const fn eof(span: Span) -> Self {
         Self {
             kind: TokenKind::Eof,
@@ -1,47 +1,60 @@
 //! TOML-specific lexer
 
-use crate::error::{Error, ErrorKind, Result, Span};
+use crate::error::{Error, ErrorKind, Pos, Result, Span};
 use crate::lexer::cursor::Cursor;
+use crate::lexer::token::SpannedToken;
+use std::iter::FusedIterator;
 
 /// TOML token types
 #[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum TomlTokenKind {
+    /// `[` opening a table header or an array
     LeftBracket,
+    /// `]`
     RightBracket,
+    /// `[[` opening an array of tables header
     DoubleLeftBracket,
+    /// `]]`
     DoubleRightBracket,
+    /// `{`
     LeftBrace,
+    /// `}`
     RightBrace,
+    /// `=`
     Equals,
+    /// `,`
     Comma,
+    /// `.` between key segments
     Dot,
+    /// End of a line (`\n` or `\r\n`); comments before it are skipped
     Newline,
+    /// Unquoted key segment; bare words in value position such as `inf` and
+    /// `nan` lex as this too
     BareKey(String),
+    /// Basic or literal string, single- or multi-line, with escapes resolved
     String(String),
+    /// Integer in decimal, hex, octal or binary notation
     Integer(i64),
+    /// Float, including signed `+inf`, `-inf` and `±nan`
     Float(f64),
+    /// `true` or `false`
     Bool(bool),
+    /// Offset or local date-time, date or time, as written
     Datetime(String),
+    /// End of input, returned by `next_token` but not by the iterator
     Eof,
 }
 
 /// TOML token with span information
-#[derive(Clone, Debug, PartialEq)]
-pub struct TomlToken {
-    pub kind: TomlTokenKind,
-    pub span: Span,
-}
-
-impl TomlToken {
-    pub const fn new(kind: TomlTokenKind, span: Span) -> Self {
-        Self { kind, span }
-    }
-}
+pub type TomlToken = SpannedToken<TomlTokenKind>;
 
 /// TOML lexer
 #[derive(Clone, Debug)]
 pub struct TomlLexer<'a> {
     cursor: Cursor<'a>,
+    /// Whether iteration reached the end of input or an error
+    done: bool,
 }
 
 impl<'a> TomlLexer<'a> {
@@ -49,6 +62,7 @@ impl<'a> TomlLexer<'a> {
     pub const fn new(input: &'a [u8]) -> Self {
         Self {
             cursor: Cursor::new(input),
+            done: false,
         }
     }
 
@@ -71,6 +85,10 @@ impl<'a> TomlLexer<'a> {
                 self.cursor.advance();
                 TomlTokenKind::Newline
             }
+            Some(b'\r') if self.cursor.peek(1) == Some(b'\n') => {
+                self.cursor.advance_by(2);
+                TomlTokenKind::Newline
+            }
             Some(b'[') => {
                 if self.cursor.peek(1) == Some(b'[') {
                     self.cursor.advance_by(2);
@@ -139,7 +157,7 @@ impl<'a> TomlLexer<'a> {
 
     fn skip_space(&mut self) {
         while let Some(b) = self.cursor.current() {
-            if matches!(b, b' ' | b'\t' | b'\r') {
+            if matches!(b, b' ' | b'\t') {
                 self.cursor.advance();
             } else {
                 break;
@@ -147,12 +165,42 @@ impl<'a> TomlLexer<'a> {
         }
     }
 
+    /// Consume one LF or CRLF line ending, returning whether one was present
+    fn skip_newline(&mut self) -> bool {
+        match self.cursor.current() {
+            Some(b'\n') => {
+                self.cursor.advance();
+                true
+            }
+            Some(b'\r') if self.cursor.peek(1) == Some(b'\n') => {
+                self.cursor.advance_by(2);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether only spaces or tabs remain before the end of the line
+    fn at_line_end(&self) -> bool {
+        let mut ahead = 0;
+        loop {
+            match self.cursor.peek(ahead) {
+                Some(b' ' | b'\t') => ahead = ahead.saturating_add(1),
+                Some(b'\n') => return true,
+                Some(b'\r') => return self.cursor.peek(ahead.saturating_add(1)) == Some(b'\n'),
+                _ => return false,
+            }
+        }
+    }
+
+    /// Skip to the end of the line, leaving the line break to be lexed as
+    /// [`TomlTokenKind::Newline`]
     fn skip_comment(&mut self) {
         while let Some(b) = self.cursor.current() {
-            self.cursor.advance();
-            if b == b'\n' {
+            if b == b'\n' || (b == b'\r' && self.cursor.peek(1) == Some(b'\n')) {
                 break;
             }
+            self.cursor.advance();
         }
     }
 
@@ -162,7 +210,8 @@ impl<'a> TomlLexer<'a> {
         }
 
         self.cursor.advance();
-        let mut result = String::new();
+        let start = self.cursor.position();
+        let mut result = Vec::new();
 
         loop {
             match self.cursor.current() {
@@ -178,9 +227,10 @@ impl<'a> TomlLexer<'a> {
                     self.cursor.advance();
                     break;
                 }
-                Some(b'\'') => {
+                Some(b'\\') => {
                     self.cursor.advance();
-                    result.push(self.lex_basic_escape()?);
+                    let ch = self.lex_basic_escape()?;
+                    result.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes());
                 }
                 Some(b'\n') => {
                     return Err(Error::at(
@@ -191,18 +241,21 @@ impl<'a> TomlLexer<'a> {
                     ));
                 }
                 Some(b) => {
-                    result.push(char::from(b));
+                    result.push(b);
                     self.cursor.advance();
                 }
             }
         }
 
-        Ok(TomlTokenKind::String(result))
+        self.string_token(result, start)
     }
 
     fn lex_multiline_basic_string(&mut self) -> Result<TomlTokenKind> {
         self.cursor.advance_by(3);
-        let mut result = String::new();
+        // A line ending right after the opening delimiter is trimmed.
+        self.skip_newline();
+        let start = self.cursor.position();
+        let mut result = Vec::new();
 
         loop {
             match self.cursor.current() {
@@ -219,21 +272,38 @@ impl<'a> TomlLexer<'a> {
                         self.cursor.advance_by(3);
                         break;
                     }
-                    result.push('"');
+                    result.push(b'"');
                     self.cursor.advance();
                 }
                 Some(b'\\') => {
                     self.cursor.advance();
-                    result.push(self.lex_basic_escape()?);
+                    if self.at_line_end() {
+                        // Line-ending backslash: drop all whitespace up to the
+                        // next non-whitespace character, across lines.
+                        loop {
+                            if matches!(self.cursor.current(), Some(b' ' | b'\t')) {
+                                self.cursor.advance();
+                            } else if !self.skip_newline() {
+                                break;
+                            }
+                        }
+                    } else {
+                        let ch = self.lex_basic_escape()?;
+                        result.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes());
+                    }
+                }
+                Some(b'\r') if self.cursor.peek(1) == Some(b'\n') => {
+                    result.push(b'\n');
+                    self.cursor.advance_by(2);
                 }
                 Some(b) => {
-                    result.push(char::from(b));
+                    result.push(b);
                     self.cursor.advance();
                 }
             }
         }
 
-        Ok(TomlTokenKind::String(result))
+        self.string_token(result, start)
     }
 
     fn lex_basic_escape(&mut self) -> Result<char> {
@@ -330,7 +400,8 @@ impl<'a> TomlLexer<'a> {
         }
 
         self.cursor.advance();
-        let mut result = String::new();
+        let start = self.cursor.position();
+        let mut result = Vec::new();
 
         loop {
             match self.cursor.current() {
@@ -355,18 +426,21 @@ impl<'a> TomlLexer<'a> {
                     ));
                 }
                 Some(b) => {
-                    result.push(char::from(b));
+                    result.push(b);
                     self.cursor.advance();
                 }
             }
         }
 
-        Ok(TomlTokenKind::String(result))
+        self.string_token(result, start)
     }
 
     fn lex_multiline_literal_string(&mut self) -> Result<TomlTokenKind> {
         self.cursor.advance_by(3);
-        let mut result = String::new();
+        // A line ending right after the opening delimiter is trimmed.
+        self.skip_newline();
+        let start = self.cursor.position();
+        let mut result = Vec::new();
 
         loop {
             match self.cursor.current() {
@@ -383,17 +457,34 @@ impl<'a> TomlLexer<'a> {
                         self.cursor.advance_by(3);
                         break;
                     }
-                    result.push('\'');
+                    result.push(b'\'');
                     self.cursor.advance();
                 }
+                Some(b'\r') if self.cursor.peek(1) == Some(b'\n') => {
+                    result.push(b'\n');
+                    self.cursor.advance_by(2);
+                }
                 Some(b) => {
-                    result.push(char::from(b));
+                    result.push(b);
                     self.cursor.advance();
                 }
             }
         }
 
-        Ok(TomlTokenKind::String(result))
+        self.string_token(result, start)
+    }
+
+    /// A string token from the bytes of a string that started at `start`
+    fn string_token(&self, bytes: Vec<u8>, start: Pos) -> Result<TomlTokenKind> {
+        String::from_utf8(bytes)
+            .map(TomlTokenKind::String)
+            .map_err(|_| {
+                Error::with_message(
+                    ErrorKind::InvalidToken,
+                    Span::new(start, self.cursor.position()),
+                    "string is not valid UTF-8",
+                )
+            })
     }
 
     fn lex_bare_key_or_bool(&mut self) -> Result<TomlTokenKind> {
@@ -501,6 +592,31 @@ impl<'a> TomlLexer<'a> {
     }
 }
 
+/// Yields tokens up to (not including) [`TomlTokenKind::Eof`], stopping after
+/// the first error
+impl Iterator for TomlLexer<'_> {
+    type Item = Result<TomlToken>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.next_token() {
+            Ok(token) if token.kind == TomlTokenKind::Eof => {
+                self.done = true;
+                None
+            }
+            Ok(token) => Some(Ok(token)),
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+impl FusedIterator for TomlLexer<'_> {}
+
 fn parse_special_float(text: &str) -> Option<f64> {
     match text {
         "inf" | "+inf" => Some(f64::INFINITY),
@@ -1,39 +1,57 @@
 //! YAML lexer with indentation-aware tokens
 
 use std::collections::VecDeque;
+use std::iter::FusedIterator;
+use std::iter::Peekable;
+use std::str::CharIndices;
 
-use crate::error::{Error, ErrorKind, Result, Span};
+use crate::base64;
+use crate::error::{Error, ErrorKind, Pos, Result, Span};
+use crate::include::INCLUDE_TAG;
+use crate::lexer::token::SpannedToken;
+use crate::warning::{Warning, WarningKind};
+
+/// Tag marking a base64-encoded byte string
+const BINARY_TAG: &str = "!!binary";
 
 /// YAML token kinds
 #[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum YamlTokenKind {
+    /// A line indented further than the enclosing block
     Indent,
+    /// A line indented less than the enclosing block, one per closed level
     Dedent,
+    /// `-` starting a sequence item
     Dash,
+    /// `:` after a mapping key
     Colon,
+    /// `[`
     LeftBracket,
+    /// `]`
     RightBracket,
+    /// `{`
     LeftBrace,
+    /// `}`
     RightBrace,
+    /// `,` in a flow collection
     Comma,
+    /// Plain (unquoted) scalar
     Scalar(String),
+    /// Single- or double-quoted scalar with escapes resolved
     QuotedScalar(String),
+    /// Node tag such as `!!str`; dropped by the lexer with a warning
+    Tag(String),
+    /// Base64 scalar tagged `!!binary`, already decoded
+    Binary(Vec<u8>),
+    /// End of a non-empty line; blank and comment-only lines produce no tokens
     Newline,
+    /// End of input, returned by `next_token` but not by the iterator
     Eof,
 }
 
 /// YAML token with span
-#[derive(Clone, Debug, PartialEq)]
-pub struct YamlToken {
-    pub kind: YamlTokenKind,
-    pub span: Span,
-}
-
-impl YamlToken {
-    pub const fn new(kind: YamlTokenKind, span: Span) -> Self {
-        Self { kind, span }
-    }
-}
+pub type YamlToken = SpannedToken<YamlTokenKind>;
 
 /// YAML lexer
 #[derive(Clone, Debug)]
@@ -43,6 +61,9 @@ pub struct YamlLexer<'a> {
     line: u32,
     indent_stack: Vec<usize>,
     pending: VecDeque<YamlToken>,
+    warnings: Vec<Warning>,
+    /// Whether iteration reached the end of input or an error
+    done: bool,
 }
 
 impl<'a> YamlLexer<'a> {
@@ -54,9 +75,21 @@ impl<'a> YamlLexer<'a> {
             line: 1,
             indent_stack: vec![0],
             pending: VecDeque::new(),
+            warnings: Vec::new(),
+            done: false,
         }
     }
 
+    /// Drain the warnings recorded so far
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// The input byte at `offset`
+    pub(crate) fn byte_at(&self, offset: usize) -> Option<u8> {
+        self.input.get(offset).copied()
+    }
+
     /// Get the next token
     pub fn next_token(&mut self) -> Result<YamlToken> {
         if let Some(token) = self.pending.pop_front() {
@@ -64,11 +97,13 @@ impl<'a> YamlLexer<'a> {
         }
 
         if self.index >= self.input.len() {
+            let end = self.end_pos();
+            let span = Span::new(end, end);
             if self.indent_stack.len() > 1 {
                 self.indent_stack.pop();
-                return Ok(YamlToken::new(YamlTokenKind::Dedent, Span::empty()));
+                return Ok(YamlToken::new(YamlTokenKind::Dedent, span));
             }
-            return Ok(YamlToken::new(YamlTokenKind::Eof, Span::empty()));
+            return Ok(YamlToken::new(YamlTokenKind::Eof, span));
         }
 
         let line_start = self.index;
@@ -93,26 +128,39 @@ impl<'a> YamlLexer<'a> {
             line_end
         };
 
-        let line_str = std::str::from_utf8(line_bytes).map_err(|_| {
+        let line_str = std::str::from_utf8(line_bytes).map_err(|err| {
+            let pos = Pos::new(
+                line_start.saturating_add(err.valid_up_to()),
+                self.line,
+                column(err.valid_up_to()),
+            );
             Error::with_message(
                 ErrorKind::InvalidToken,
-                Span::empty(),
+                Span::new(pos, pos),
                 "invalid utf-8 in yaml".to_string(),
             )
         })?;
+        // Treat CRLF like LF so no stray `\r` reaches scalars or spans.
+        let line_str = line_str.strip_suffix('\r').unwrap_or(line_str);
+        let locator = LineLocator {
+            line: line_str,
+            offset: line_start,
+            number: self.line,
+        };
 
-        let (indent, content) = split_indent(line_str)?;
+        let (indent, content) = split_indent(line_str, &locator)?;
         let content = strip_comment(content);
         if content.trim().is_empty() {
             self.line = self.line.saturating_add(1);
             return self.next_token();
         }
 
+        let indent_span = Span::new(locator.pos(indent), locator.pos(indent));
         let current_indent = *self.indent_stack.last().unwrap_or(&0);
         if indent > current_indent {
             self.indent_stack.push(indent);
             self.pending
-                .push_back(YamlToken::new(YamlTokenKind::Indent, Span::empty()));
+                .push_back(YamlToken::new(YamlTokenKind::Indent, indent_span));
         } else if indent < current_indent {
             while let Some(last) = self.indent_stack.last() {
                 if *last == indent {
@@ -120,23 +168,60 @@ impl<'a> YamlLexer<'a> {
                 }
                 self.indent_stack.pop();
                 self.pending
-                    .push_back(YamlToken::new(YamlTokenKind::Dedent, Span::empty()));
+                    .push_back(YamlToken::new(YamlTokenKind::Dedent, indent_span));
             }
             if *self.indent_stack.last().unwrap_or(&0) != indent {
                 return Err(Error::with_message(
                     ErrorKind::InvalidToken,
-                    Span::empty(),
+                    indent_span,
                     "invalid indentation".to_string(),
                 ));
             }
         }
 
-        let tokens = lex_line(content)?;
-        for token in tokens {
-            self.pending.push_back(token);
+        let mut tokens = lex_line(content, &locator)?.into_iter().peekable();
+        while let Some(token) = tokens.next() {
+            match token.kind {
+                YamlTokenKind::Tag(tag) if tag == BINARY_TAG => {
+                    let Some(target) = tokens.next_if(|next| {
+                        matches!(
+                            next.kind,
+                            YamlTokenKind::Scalar(_) | YamlTokenKind::QuotedScalar(_)
+                        )
+                    }) else {
+                        return Err(Error::with_message(
+                            ErrorKind::InvalidToken,
+                            token.span,
+                            "expected base64 scalar after !!binary".to_string(),
+                        ));
+                    };
+                    let (YamlTokenKind::Scalar(text) | YamlTokenKind::QuotedScalar(text)) =
+                        &target.kind
+                    else {
+                        continue;
+                    };
+                    let span = Span::new(token.span.start, target.span.end);
+                    let bytes = base64::decode(text).ok_or_else(|| {
+                        Error::with_message(
+                            ErrorKind::InvalidToken,
+                            span,
+                            "invalid base64 in !!binary scalar".to_string(),
+                        )
+                    })?;
+                    self.pending
+                        .push_back(YamlToken::new(YamlTokenKind::Binary(bytes), span));
+                }
+                YamlTokenKind::Tag(tag) => self
+                    .warnings
+                    .push(Warning::new(WarningKind::IgnoredTag { tag }, token.span)),
+                _ => self.pending.push_back(token),
+            }
         }
-        self.pending
-            .push_back(YamlToken::new(YamlTokenKind::Newline, Span::empty()));
+        let line_end = locator.pos(line_str.len());
+        self.pending.push_back(YamlToken::new(
+            YamlTokenKind::Newline,
+            Span::new(line_end, line_end),
+        ));
 
         self.line = self.line.saturating_add(1);
         self.pending.pop_front().ok_or_else(|| {
@@ -147,9 +232,101 @@ impl<'a> YamlLexer<'a> {
             )
         })
     }
+
+    /// Position just past the last byte of input
+    fn end_pos(&self) -> Pos {
+        let len = self.input.len();
+        let last_line_start = self
+            .input
+            .iter()
+            .rposition(|byte| *byte == b'\n')
+            .map_or(0, |newline| newline.saturating_add(1));
+        if last_line_start == len {
+            // Every line has been consumed, including its newline.
+            Pos::new(len, self.line, 1)
+        } else {
+            Pos::new(
+                len,
+                self.line.saturating_sub(1),
+                column(len.saturating_sub(last_line_start)),
+            )
+        }
+    }
+}
+
+/// Yields tokens up to (not including) [`YamlTokenKind::Eof`], stopping after
+/// the first error
+impl Iterator for YamlLexer<'_> {
+    type Item = Result<YamlToken>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.next_token() {
+            Ok(token) if token.kind == YamlTokenKind::Eof => {
+                self.done = true;
+                None
+            }
+            Ok(token) => Some(Ok(token)),
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+impl FusedIterator for YamlLexer<'_> {}
+
+/// Maps byte indexes within one input line to source positions
+#[derive(Clone, Copy, Debug)]
+struct LineLocator<'a> {
+    line: &'a str,
+    /// Byte offset of the line start within the input
+    offset: usize,
+    /// 1-based line number
+    number: u32,
+}
+
+impl LineLocator<'_> {
+    /// Position of the byte at `index` within the line
+    fn pos(&self, index: usize) -> Pos {
+        Pos::new(
+            self.offset.saturating_add(index),
+            self.number,
+            column(index),
+        )
+    }
+
+    /// Index within the line where `part`, a subslice of the line, starts
+    fn index_of(&self, part: &str) -> usize {
+        part.as_ptr()
+            .addr()
+            .saturating_sub(self.line.as_ptr().addr())
+    }
+
+    /// Span of bytes `start..end` of `part`, a subslice of the line
+    fn span_in(&self, part: &str, start: usize, end: usize) -> Span {
+        let base = self.index_of(part);
+        Span::new(
+            self.pos(base.saturating_add(start)),
+            self.pos(base.saturating_add(end)),
+        )
+    }
+
+    /// Span covering `part`, a subslice of the line
+    fn span_of(&self, part: &str) -> Span {
+        self.span_in(part, 0, part.len())
+    }
+}
+
+/// 1-based column for a 0-based byte index
+fn column(index: usize) -> u32 {
+    u32::try_from(index.saturating_add(1)).unwrap_or(u32::MAX)
 }
 
-fn split_indent(line: &str) -> Result<(usize, &str)> {
+fn split_indent<'a>(line: &'a str, locator: &LineLocator<'_>) -> Result<(usize, &'a str)> {
     let mut indent = 0_usize;
     for ch in line.chars() {
         match ch {
@@ -157,7 +334,7 @@ fn split_indent(line: &str) -> Result<(usize, &str)> {
             '\t' => {
                 return Err(Error::with_message(
                     ErrorKind::InvalidToken,
-                    Span::empty(),
+                    locator.span_in(line, indent, indent.saturating_add(1)),
                     "tabs not allowed for indentation".to_string(),
                 ));
             }
@@ -191,7 +368,7 @@ fn strip_comment(line: &str) -> &str {
     line
 }
 
-fn lex_line(line: &str) -> Result<Vec<YamlToken>> {
+fn lex_line(line: &str, locator: &LineLocator<'_>) -> Result<Vec<YamlToken>> {
     let trimmed = line.trim();
     if trimmed.is_empty() {
         return Ok(Vec::new());
@@ -200,111 +377,182 @@ fn lex_line(line: &str) -> Result<Vec<YamlToken>> {
     if let Some(rest) = trimmed.strip_prefix('-') {
         let rest = rest.strip_prefix(' ').unwrap_or(rest).trim();
         let mut tokens = Vec::new();
-        tokens.push(YamlToken::new(YamlTokenKind::Dash, Span::empty()));
+        tokens.push(YamlToken::new(
+            YamlTokenKind::Dash,
+            locator.span_in(trimmed, 0, 1),
+        ));
         if !rest.is_empty() {
-            tokens.extend(lex_value_tokens(rest)?);
+            tokens.extend(lex_value_tokens(rest, locator)?);
         }
         return Ok(tokens);
     }
 
-    lex_mapping_or_scalar(trimmed)
+    lex_mapping_or_scalar(trimmed, locator)
 }
 
-fn lex_mapping_or_scalar(line: &str) -> Result<Vec<YamlToken>> {
-    if let Some((key, value)) = split_key_value(line)? {
+fn lex_mapping_or_scalar(line: &str, locator: &LineLocator<'_>) -> Result<Vec<YamlToken>> {
+    if let Some((key, colon, value)) = split_key_value(line)? {
         let mut tokens = Vec::new();
-        let key = parse_scalar(key)?;
-        tokens.push(YamlToken::new(YamlTokenKind::Scalar(key), Span::empty()));
-        tokens.push(YamlToken::new(YamlTokenKind::Colon, Span::empty()));
+        let key_span = locator.span_of(key);
+        let key = parse_scalar(key).map_err(|err| relocate(&err, key_span))?;
+        tokens.push(YamlToken::new(YamlTokenKind::Scalar(key), key_span));
+        tokens.push(YamlToken::new(
+            YamlTokenKind::Colon,
+            locator.span_in(line, colon, colon.saturating_add(1)),
+        ));
         if let Some(value) = value {
-            tokens.extend(lex_value_tokens(value)?);
+            tokens.extend(lex_value_tokens(value, locator)?);
         }
         return Ok(tokens);
     }
 
-    lex_value_tokens(line)
+    lex_value_tokens(line, locator)
 }
 
-fn lex_value_tokens(line: &str) -> Result<Vec<YamlToken>> {
+fn lex_value_tokens(line: &str, locator: &LineLocator<'_>) -> Result<Vec<YamlToken>> {
     let mut tokens = Vec::new();
-    let mut chars = line.chars().peekable();
-    while let Some(ch) = chars.peek().copied() {
+    let mut chars = line.char_indices().peekable();
+    // Open flow collections; inside one, `,[]{}` end a plain scalar
+    let mut depth = 0usize;
+    while let Some((start, ch)) = chars.peek().copied() {
         if ch.is_whitespace() {
             chars.next();
             continue;
         }
 
-        match ch {
-            '[' => {
-                chars.next();
-                tokens.push(YamlToken::new(YamlTokenKind::LeftBracket, Span::empty()));
-            }
-            ']' => {
-                chars.next();
-                tokens.push(YamlToken::new(YamlTokenKind::RightBracket, Span::empty()));
-            }
-            '{' => {
-                chars.next();
-                tokens.push(YamlToken::new(YamlTokenKind::LeftBrace, Span::empty()));
-            }
-            '}' => {
-                chars.next();
-                tokens.push(YamlToken::new(YamlTokenKind::RightBrace, Span::empty()));
-            }
-            ',' => {
-                chars.next();
-                tokens.push(YamlToken::new(YamlTokenKind::Comma, Span::empty()));
-            }
-            ':' => {
-                chars.next();
-                tokens.push(YamlToken::new(YamlTokenKind::Colon, Span::empty()));
-            }
-            '"' => {
-                let scalar = parse_quoted(&mut chars, '"')?;
-                tokens.push(YamlToken::new(
-                    YamlTokenKind::QuotedScalar(scalar),
-                    Span::empty(),
-                ));
+        let punctuation = match ch {
+            '[' => Some(YamlTokenKind::LeftBracket),
+            ']' => Some(YamlTokenKind::RightBracket),
+            '{' => Some(YamlTokenKind::LeftBrace),
+            '}' => Some(YamlTokenKind::RightBrace),
+            ',' => Some(YamlTokenKind::Comma),
+            ':' => Some(YamlTokenKind::Colon),
+            _ => None,
+        };
+        if let Some(kind) = punctuation {
+            match kind {
+                YamlTokenKind::LeftBracket | YamlTokenKind::LeftBrace => depth += 1,
+                YamlTokenKind::RightBracket | YamlTokenKind::RightBrace => {
+                    depth = depth.saturating_sub(1);
+                }
+                _ => {}
             }
-            '\'' => {
-                let scalar = parse_quoted(&mut chars, '\'')?;
-                tokens.push(YamlToken::new(
-                    YamlTokenKind::QuotedScalar(scalar),
-                    Span::empty(),
-                ));
+            chars.next();
+            let span = locator.span_in(line, start, next_index(&mut chars, line));
+            tokens.push(YamlToken::new(kind, span));
+            continue;
+        }
+
+        match ch {
+            '"' | '\'' => {
+                let scalar = parse_quoted(&mut chars, ch, line, locator)?;
+                let span = locator.span_in(line, start, next_index(&mut chars, line));
+                tokens.push(YamlToken::new(YamlTokenKind::QuotedScalar(scalar), span));
             }
-            _ => {
+            '!' => {
                 let mut value = String::new();
-                while let Some(ch) = chars.peek().copied() {
-                    if ch.is_whitespace() || matches!(ch, '[' | ']' | '{' | '}' | ',' | ':') {
-                        break;
-                    }
+                while let Some((_, ch)) = chars.next_if(|(_, ch)| !ch.is_whitespace()) {
                     value.push(ch);
-                    chars.next();
                 }
-                let value = parse_scalar(&value)?;
-                tokens.push(YamlToken::new(YamlTokenKind::Scalar(value), Span::empty()));
+                if value == INCLUDE_TAG {
+                    // Keep `!include path` together so the include resolver
+                    // sees the tag and its target as a single scalar.
+                    while chars.next_if(|(_, ch)| ch.is_whitespace()).is_some() {}
+                    let target = match chars.peek().copied() {
+                        Some((_, quote @ ('"' | '\''))) => {
+                            parse_quoted(&mut chars, quote, line, locator)?
+                        }
+                        _ => {
+                            let mut target = String::new();
+                            while let Some((_, ch)) = chars.next_if(|(_, ch)| !ch.is_whitespace()) {
+                                target.push(ch);
+                            }
+                            target
+                        }
+                    };
+                    value.push(' ');
+                    value.push_str(&target);
+                    let span = locator.span_in(line, start, next_index(&mut chars, line));
+                    tokens.push(YamlToken::new(YamlTokenKind::Scalar(value), span));
+                    continue;
+                }
+                let span = locator.span_in(line, start, next_index(&mut chars, line));
+                tokens.push(YamlToken::new(YamlTokenKind::Tag(value), span));
+            }
+            _ => {
+                let end = plain_scalar_end(line, start, depth > 0);
+                while chars.next_if(|(index, _)| *index < end).is_some() {}
+                let span = locator.span_in(line, start, end);
+                let value = line.get(start..end).unwrap_or_default();
+                let value = parse_scalar(value).map_err(|err| relocate(&err, span))?;
+                tokens.push(YamlToken::new(YamlTokenKind::Scalar(value), span));
             }
         }
     }
     Ok(tokens)
 }
 
-fn parse_quoted<I>(chars: &mut std::iter::Peekable<I>, quote: char) -> Result<String>
-where
-    I: Iterator<Item = char>,
-{
+/// Byte index where the plain scalar starting at `start` ends, before any
+/// trailing whitespace. Plain scalars may hold spaces and colons (`a b`,
+/// `07:32:00`); they end at `: `, and in flow collections at `,[]{}`.
+fn plain_scalar_end(line: &str, start: usize, in_flow: bool) -> usize {
+    let rest = line.get(start..).unwrap_or_default();
+    let mut end = start;
+    let mut chars = rest.char_indices().peekable();
+    while let Some((offset, ch)) = chars.next() {
+        let next = chars.peek().map(|(_, next)| *next);
+        let ends = match ch {
+            ':' => {
+                next.is_none_or(|next| next.is_whitespace() || (in_flow && ",[]{}".contains(next)))
+            }
+            '[' | ']' | '{' | '}' | ',' => in_flow,
+            _ => false,
+        };
+        if ends {
+            break;
+        }
+        if !ch.is_whitespace() {
+            end = start + offset + ch.len_utf8();
+        }
+    }
+    end
+}
+
+/// Byte index of the next unconsumed character, or the end of `line`
+fn next_index(chars: &mut Peekable<CharIndices<'_>>, line: &str) -> usize {
+    chars.peek().map_or(line.len(), |(index, _)| *index)
+}
+
+/// Re-anchor an error raised without location information
+fn relocate(err: &Error, span: Span) -> Error {
+    Error::with_message(err.kind().clone(), span, err.message())
+}
+
+fn parse_quoted(
+    chars: &mut Peekable<CharIndices<'_>>,
+    quote: char,
+    line: &str,
+    locator: &LineLocator<'_>,
+) -> Result<String> {
     let mut result = String::new();
+    let start = next_index(chars, line);
     let _ = chars.next();
-    while let Some(ch) = chars.next() {
+    while let Some((index, ch)) = chars.next() {
+        // `''` is a quote inside a single-quoted scalar
+        if quote == '\'' && ch == '\'' && chars.next_if(|(_, next)| *next == '\'').is_some() {
+            result.push('\'');
+            continue;
+        }
         if ch == quote {
             return Ok(result);
         }
         if quote == '"' && ch == '\\' {
-            let next = chars.next().ok_or_else(|| {
+            let escape_span =
+                locator.span_in(line, index, next_index(chars, line).saturating_add(1));
+            let next = chars.next().map(|(_, next)| next).ok_or_else(|| {
                 Error::with_message(
                     ErrorKind::InvalidEscapeSequence,
-                    Span::empty(),
+                    escape_span,
                     "invalid escape".to_string(),
                 )
             })?;
@@ -317,7 +565,7 @@ where
                 _ => {
                     return Err(Error::with_message(
                         ErrorKind::InvalidEscapeSequence,
-                        Span::empty(),
+                        escape_span,
                         "invalid escape".to_string(),
                     ));
                 }
@@ -328,12 +576,13 @@ where
     }
     Err(Error::with_message(
         ErrorKind::UnterminatedString,
-        Span::empty(),
+        locator.span_in(line, start, line.len()),
         "unterminated string".to_string(),
     ))
 }
 
-fn split_key_value(line: &str) -> Result<Option<(&str, Option<&str>)>> {
+/// Split `key: value` at the first unquoted colon, returning its byte index
+fn split_key_value(line: &str) -> Result<Option<(&str, usize, Option<&str>)>> {
     let mut in_single = false;
     let mut in_double = false;
     let mut prev_escape = false;
@@ -353,7 +602,7 @@ fn split_key_value(line: &str) -> Result<Option<(&str, Option<&str>)>> {
                 let key = line[..idx].trim();
                 let rest = line[idx + 1..].trim();
                 let value = if rest.is_empty() { None } else { Some(rest) };
-                return Ok(Some((key, value)));
+                return Ok(Some((key, idx, value)));
             }
             _ => {}
         }
@@ -15,33 +15,230 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! Any supported format can be read through [`parse_path`], which infers the
+//! format from the file, or [`parse_bytes`] when the format is known:
+//!
+//! ```
+//! use zparse::{Format, parse_bytes};
+//! # fn main() -> Result<(), zparse::Error> {
+//! let value = parse_bytes(b"name = \"John\"", Format::Toml)?;
+//! assert!(value.as_object().is_some_and(|obj| obj.contains_key("name")));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Panics
+//!
+//! Parsing never panics: every function that reads a document, whatever its
+//! bytes, returns `Ok` or `Err`. That covers the `parse*` and `from_*`
+//! functions, the format parsers, [`parse_document`], the event reader,
+//! [`highlight`], [`IncrementalDocument::edit`] and serialization of the
+//! values they return. Slicing and unwrapping are denied by lint in the crate
+//! and the contract is checked by `tests/panic_free_tests.rs` and the fuzz
+//! targets in `fuzz/`; a panic on any input is a bug. Indexing an [`Object`]
+//! or [`Array`] with `[]` panics on a missing entry, like `HashMap` and `Vec`
+//! do; use [`Value::get`] for keys and indexes that come from input.
+//!
+//! # Stability
+//!
+//! Everything public follows semver, except the items behind the `unstable`
+//! feature: the lexers and tokens (`lexer`, `JsonLexer`, `Token`, ...) and
+//! the cross-format event API (`event`, `EventReader`). Those expose how the
+//! parsers work inside and may change in a minor release when the parsers
+//! are reworked for speed. Without the feature they are not exported.
 
 #![forbid(unsafe_code)]
 
 pub mod error;
 pub use error::{Error, ErrorKind, Pos, Result, Span};
 
+pub mod warning;
+pub use warning::{Warning, WarningKind};
+
+mod base64;
+mod locate;
+pub use locate::key_lines;
+mod rng;
+mod sha256;
+
+pub mod compress;
+pub use compress::Compression;
+
 pub mod input;
-pub use input::Input;
+pub use input::{FileInput, Input, Utf8Mode};
 
+#[cfg(feature = "unstable")]
 pub mod lexer;
-pub use lexer::{Token, TokenKind};
+#[cfg(not(feature = "unstable"))]
+#[allow(dead_code, unused_imports)]
+pub(crate) mod lexer;
+#[cfg(feature = "unstable")]
+pub use lexer::{SpannedToken, Token, TokenKind};
+
+pub mod highlight;
+
+pub mod incremental;
+pub use incremental::IncrementalDocument;
 
 pub mod value;
-pub use value::{Array, Object, TomlDatetime, Value};
+pub use value::{Array, Object, TomlDatetime, Value, ValueIndex};
 
 pub mod convert;
-pub use convert::{ConvertOptions, Format, convert, convert_with_options};
+#[cfg(feature = "toml")]
+pub use convert::TomlSerializer;
+#[cfg(feature = "xml")]
+pub use convert::XmlSerializer;
+#[cfg(feature = "yaml")]
+pub use convert::YamlSerializer;
+pub use convert::{
+    BytesEncoding, ConvertOptions, CsvSerializer, Format, FormatOptions, IntegralFloats,
+    JsonDialect, JsonQuote, JsonSerializer, Serializer, TomlStringStyle, XmlQuote,
+    YamlSequenceStyle, YamlStringStyle, convert, convert_with_options, parse_with_options,
+    serialize, serialize_with_options, validate,
+};
+
+pub mod converter;
+pub use converter::Converter;
+#[cfg(feature = "xml")]
+pub use converter::XmlMapping;
+
+#[cfg(feature = "unstable")]
+pub mod event;
+#[cfg(not(feature = "unstable"))]
+#[allow(dead_code)]
+pub(crate) mod event;
+#[cfg(feature = "unstable")]
+pub use event::EventReader;
+
+pub mod partial;
+pub use partial::parse_until_path;
+
+#[cfg(feature = "xml")]
+pub mod plist;
+
+pub mod registry;
+pub use registry::{FormatProvider, Registry};
+
+pub mod source;
+pub use source::InputSource;
+
+pub mod document;
+pub use document::{Document, Extension, parse_document, parse_document_file};
+
+pub mod ops;
+
+#[cfg(feature = "toml")]
+pub mod toml_compat;
+#[cfg(feature = "toml")]
+pub use toml_compat::{NullPolicy, TomlCompat};
+
+pub mod merge;
+pub use merge::{MergeStrategy, merge_all};
+
+pub mod interpolate;
+
+pub mod template;
+
+pub mod include;
+pub use include::{Resolver as IncludeResolver, load_with_includes};
+
+pub mod config_loader;
+pub use config_loader::{ConfigLoader, LoadedConfig, Origin};
+
+pub mod audit;
+
+pub mod redact;
+pub use redact::Redactor;
+
+pub mod encryption;
+pub use encryption::{Cipher, Encryption};
+
+pub mod case;
+pub use case::{KeyCase, convert_key};
+
+pub mod sort;
+
+pub mod split;
+
+pub mod table;
+
+pub mod query;
+
+pub mod testgen;
+
+pub mod metrics;
+pub use metrics::Stats;
+
+pub mod digest;
+pub use digest::Digest;
+
+#[cfg(feature = "lossless")]
+pub mod lossless;
+
+pub mod schema;
+
+pub mod codegen;
+
+pub mod shared;
+pub use shared::{ArcValue, Interner};
+
+pub mod store;
+pub use store::{ConfigStore, Snapshot};
+
+pub mod compare;
+pub use compare::{
+    CompareOptions, PathedMismatch, values_diff_first_mismatch, values_diff_first_mismatch_with,
+    values_equal, values_equal_with,
+};
 
 pub mod csv;
 pub use csv::{Config as CsvConfig, Parser as CsvParser};
 
+pub mod prelude;
+
+/// Guess the format of a document from its content.
+///
+/// Checks run from the most to the least distinctive syntax: XML, JSON, TOML,
+/// YAML (mappings and sequences only) and finally CSV with a delimited header.
+/// Returns None when no parser accepts the input.
+pub fn detect_format_from_content(input: &str) -> Option<Format> {
+    let text = input.trim_start_matches('\u{feff}').trim_start();
+    if text.is_empty() {
+        return None;
+    }
+    if text.starts_with('<') {
+        return Some(Format::Xml);
+    }
+    if (text.starts_with('{') || text.starts_with('[')) && from_str(text).is_ok() {
+        return Some(Format::Json);
+    }
+    #[cfg(feature = "toml")]
+    if from_toml_str(text).is_ok() {
+        return Some(Format::Toml);
+    }
+    #[cfg(feature = "yaml")]
+    if from_yaml_str(text).is_ok_and(|value| value.is_object() || value.is_array()) {
+        return Some(Format::Yaml);
+    }
+    let header = text.lines().next().unwrap_or_default();
+    if header.contains(',') && from_csv_str(text).is_ok() {
+        return Some(Format::Csv);
+    }
+    None
+}
+
+/// File extensions recognized by [`detect_format_from_path`]
+pub const SUPPORTED_EXTENSIONS: &[&str] = &["json", "jsonc", "toml", "yaml", "yml", "xml", "csv"];
+
 /// Detect input format from a file path extension (case-insensitive).
 ///
 /// Returns None if the path has no extension or the extension is unsupported.
 /// Note: `.jsonc` files are detected as `Format::Json` (JSONC is JSON with config flags).
+/// A trailing `.gz` or `.zst` is looked through, so `data.json.gz` is JSON.
 pub fn detect_format_from_path(path: impl AsRef<std::path::Path>) -> Option<Format> {
-    let ext = path.as_ref().extension()?.to_str()?;
+    let path = compress::strip_extension(path.as_ref());
+    let ext = path.extension()?.to_str()?;
     match ext.to_ascii_lowercase().as_str() {
         "json" => Some(Format::Json),
         "jsonc" => Some(Format::Json),
@@ -54,16 +251,74 @@ pub fn detect_format_from_path(path: impl AsRef<std::path::Path>) -> Option<Form
 }
 
 pub mod json;
+#[cfg(feature = "toml")]
 pub mod toml;
+#[cfg(feature = "xml")]
 pub mod xml;
+#[cfg(feature = "yaml")]
 pub mod yaml;
-pub use json::{Config, Event, Parser};
-pub use toml::{Config as TomlConfig, Parser as TomlParser};
+pub use json::{Config, Event, MultiObject, MultiValue, Parser};
+#[cfg(feature = "toml")]
+pub use toml::{Builder as TomlBuilder, Config as TomlConfig, Parser as TomlParser};
+#[cfg(feature = "xml")]
 pub use xml::{
     Config as XmlConfig, Content as XmlContent, Document as XmlDocument, Element as XmlElement,
-    Parser as XmlParser,
+    Parser as XmlParser, TextType as XmlTextType, Types as XmlTypes, Whitespace as XmlWhitespace,
+};
+#[cfg(feature = "yaml")]
+pub use yaml::{
+    Config as YamlConfig, DuplicateKeys as YamlDuplicateKeys, Parser as YamlParser,
+    Schema as YamlSchema,
 };
-pub use yaml::{Config as YamlConfig, Parser as YamlParser};
+
+/// Parse a document of any supported format from bytes.
+///
+/// The bytes must be UTF-8; a leading byte order mark is skipped. XML uses the
+/// same `@attributes`/`#text` mapping as [`parse_with_options`]. Invalid UTF-8
+/// fails at its line and column unless [`ConvertOptions::utf8`] allows it.
+pub fn parse_bytes(bytes: &[u8], format: Format) -> Result<Value> {
+    parse_bytes_with_options(bytes, format, &ConvertOptions::default())
+}
+
+/// Parse a document of any supported format from bytes with options
+pub fn parse_bytes_with_options(
+    bytes: &[u8],
+    format: Format,
+    options: &ConvertOptions,
+) -> Result<Value> {
+    let input = input::decode(bytes, options.utf8)?;
+    let input = input.strip_prefix('\u{feff}').unwrap_or(&input);
+    let mut value = parse_with_options(input, format, options)?;
+    if options.utf8 == Utf8Mode::Bytes {
+        input::restore_bytes(&mut value);
+    }
+    Ok(value)
+}
+
+/// Read and parse a file of any supported format.
+///
+/// The format comes from the extension (`.jsonc` enables comments and trailing
+/// commas) or, for unknown extensions, from the content.
+pub fn parse_path(path: impl AsRef<std::path::Path>) -> Result<Value> {
+    parse_path_with_options(path, &ConvertOptions::default())
+}
+
+/// Read and parse a file of any supported format.
+///
+/// The entry point from zparse 0.x, kept for code migrating from it; same as
+/// [`parse_path`]. A file whose format can't be inferred fails with
+/// [`ErrorKind::UnknownFormat`].
+pub fn parse_file(path: impl AsRef<std::path::Path>) -> Result<Value> {
+    parse_path(path)
+}
+
+/// Read and parse a file of any supported format with options
+pub fn parse_path_with_options(
+    path: impl AsRef<std::path::Path>,
+    options: &ConvertOptions,
+) -> Result<Value> {
+    ops::parse_file(path, options).map(|(_, value)| value)
+}
 
 /// Parse JSON from string
 pub fn from_str(s: &str) -> Result<Value> {
@@ -86,6 +341,13 @@ pub fn from_str_with_config(s: &str, config: Config) -> Result<Value> {
     parser.parse_value()
 }
 
+/// Parse JSON from string, keeping repeated object keys
+pub fn from_str_multi(s: &str) -> Result<MultiValue> {
+    let input = Input::from_str(s);
+    let mut parser = Parser::new(input.as_bytes());
+    parser.parse_multi()
+}
+
 /// Parse CSV from string
 pub fn from_csv_str(s: &str) -> Result<Value> {
     let mut parser = CsvParser::new(s.as_bytes());
@@ -128,66 +390,87 @@ pub fn from_csv_bytes_with_delimiter(bytes: &[u8], delimiter: u8) -> Result<Valu
     parser.parse()
 }
 /// Parse TOML from string
+#[cfg(feature = "toml")]
 pub fn from_toml_str(s: &str) -> Result<Value> {
     let mut parser = TomlParser::new(s.as_bytes());
     parser.parse()
 }
 
 /// Parse TOML from bytes
+#[cfg(feature = "toml")]
 pub fn from_toml_bytes(bytes: &[u8]) -> Result<Value> {
     let mut parser = TomlParser::new(bytes);
     parser.parse()
 }
 
 /// Parse TOML with custom configuration
+#[cfg(feature = "toml")]
 pub fn from_toml_str_with_config(s: &str, config: TomlConfig) -> Result<Value> {
     let mut parser = TomlParser::with_config(s.as_bytes(), config);
     parser.parse()
 }
 
 /// Parse YAML from string
+#[cfg(feature = "yaml")]
 pub fn from_yaml_str(s: &str) -> Result<Value> {
     let mut parser = YamlParser::new(s.as_bytes());
     parser.parse()
 }
 
 /// Parse YAML from bytes
+#[cfg(feature = "yaml")]
 pub fn from_yaml_bytes(bytes: &[u8]) -> Result<Value> {
     let mut parser = YamlParser::new(bytes);
     parser.parse()
 }
 
 /// Parse YAML with custom configuration
+#[cfg(feature = "yaml")]
 pub fn from_yaml_str_with_config(s: &str, config: YamlConfig) -> Result<Value> {
     let mut parser = YamlParser::with_config(s.as_bytes(), config);
     parser.parse()
 }
 
 /// Parse XML from string
+#[cfg(feature = "xml")]
 pub fn from_xml_str(s: &str) -> Result<XmlDocument> {
     let mut parser = XmlParser::new(s.as_bytes());
     parser.parse()
 }
 
 /// Parse XML from string with custom configuration
+#[cfg(feature = "xml")]
 pub fn from_xml_str_with_config(s: &str, config: XmlConfig) -> Result<XmlDocument> {
     let mut parser = XmlParser::with_config(s.as_bytes(), config);
     parser.parse()
 }
 
 /// Parse XML from bytes
+#[cfg(feature = "xml")]
 pub fn from_xml_bytes(bytes: &[u8]) -> Result<XmlDocument> {
     let mut parser = XmlParser::new(bytes);
     parser.parse()
 }
 
 /// Parse XML from bytes with custom configuration
+#[cfg(feature = "xml")]
 pub fn from_xml_bytes_with_config(bytes: &[u8], config: XmlConfig) -> Result<XmlDocument> {
     let mut parser = XmlParser::with_config(bytes, config);
     parser.parse()
 }
 
+/// Write an XML document, keeping its CDATA sections, comments and
+/// processing instructions
+#[cfg(feature = "xml")]
+pub fn to_xml_string(doc: &XmlDocument) -> Result<String> {
+    xml::writer::to_string(doc)
+}
+
 /// Convenience re-exports
 pub use json::{Config as JsonConfig, Parser as JsonParser};
+#[cfg(feature = "unstable")]
 pub use lexer::json::JsonLexer;
+#[cfg(all(feature = "unstable", feature = "toml"))]
+pub use lexer::toml::TomlLexer;
+#[cfg(all(feature = "unstable", feature = "yaml"))]
 pub use lexer::yaml::YamlLexer;
@@ -0,0 +1,167 @@
+//! Source lines of object keys
+//!
+//! Used to report where a value was defined. Keys are addressed by JSON
+//! pointer (`/server/port`, `/servers/0/host`); values inside TOML inline
+//! tables and arrays share the line of the key they are assigned to. CSV and
+//! XML documents are not located.
+
+use std::collections::BTreeMap;
+#[cfg(feature = "toml")]
+use std::collections::HashMap;
+
+use crate::convert::{ConvertOptions, Format};
+use crate::error::Result;
+use crate::json::{Event as JsonEvent, Parser as JsonParser};
+#[cfg(feature = "toml")]
+use crate::toml::{Event as TomlEvent, Parser as TomlParser};
+#[cfg(feature = "toml")]
+use crate::value::Value;
+#[cfg(feature = "yaml")]
+use crate::yaml::Parser as YamlParser;
+
+/// Escape an object key for use as a JSON pointer token
+pub(crate) fn escape_token(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// 1-based line of every object key in `input`, by JSON pointer.
+///
+/// A key repeated in a JSON object or YAML mapping keeps its first line.
+pub fn key_lines(
+    input: &str,
+    format: Format,
+    options: &ConvertOptions,
+) -> Result<BTreeMap<String, u32>> {
+    let lines = match format {
+        Format::Json => json_key_lines(input, options)?,
+        #[cfg(feature = "toml")]
+        Format::Toml => toml_key_lines(input, options)?,
+        #[cfg(feature = "yaml")]
+        Format::Yaml => {
+            let mut parser = YamlParser::with_config(input.as_bytes(), options.yaml);
+            parser.record_key_lines();
+            parser.parse()?;
+            parser.take_key_lines()
+        }
+        Format::Csv | Format::Xml => Vec::new(),
+        #[allow(unreachable_patterns)]
+        _ => return Err(format.disabled()),
+    };
+    let mut located = BTreeMap::new();
+    for (pointer, line) in lines {
+        located.entry(pointer).or_insert(line);
+    }
+    Ok(located)
+}
+
+enum Frame {
+    Object(String),
+    Array(usize),
+}
+
+fn json_key_lines(input: &str, options: &ConvertOptions) -> Result<Vec<(String, u32)>> {
+    let mut parser = JsonParser::with_config(input.as_bytes(), options.json);
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut lines = Vec::new();
+
+    while let Some(event) = parser.next_event()? {
+        let completes_item = match event {
+            JsonEvent::ObjectStart => {
+                frames.push(Frame::Object(String::new()));
+                false
+            }
+            JsonEvent::ArrayStart => {
+                frames.push(Frame::Array(0));
+                false
+            }
+            JsonEvent::ObjectEnd | JsonEvent::ArrayEnd => {
+                frames.pop();
+                true
+            }
+            JsonEvent::Key(key) => {
+                if let Some(Frame::Object(current)) = frames.last_mut() {
+                    *current = escape_token(&key);
+                }
+                let pointer = frames
+                    .iter()
+                    .map(|frame| match frame {
+                        Frame::Object(key) => format!("/{key}"),
+                        Frame::Array(index) => format!("/{index}"),
+                    })
+                    .collect();
+                lines.push((pointer, parser.event_span().start.line));
+                false
+            }
+            JsonEvent::Value(_) => true,
+        };
+        if completes_item && let Some(Frame::Array(index)) = frames.last_mut() {
+            *index += 1;
+        }
+    }
+    Ok(lines)
+}
+
+#[cfg(feature = "toml")]
+fn toml_key_lines(input: &str, options: &ConvertOptions) -> Result<Vec<(String, u32)>> {
+    let mut parser = TomlParser::with_config(input.as_bytes(), options.toml);
+    // Elements seen so far of each array of tables
+    let mut array_tables: HashMap<String, usize> = HashMap::new();
+    let mut table = String::new();
+    let mut lines = Vec::new();
+
+    while let Some(event) = parser.next_event()? {
+        let line = parser.event_span().start.line;
+        match event {
+            TomlEvent::TableStart { path, is_array } => {
+                table.clear();
+                let last = path.len().saturating_sub(1);
+                for (depth, segment) in path.iter().enumerate() {
+                    table.push('/');
+                    table.push_str(&escape_token(segment));
+                    lines.push((table.clone(), line));
+                    let count = array_tables.get(&table).copied();
+                    let count = if depth == last && is_array {
+                        let count = count.unwrap_or_default() + 1;
+                        array_tables.insert(table.clone(), count);
+                        Some(count)
+                    } else {
+                        count
+                    };
+                    if let Some(count) = count {
+                        table.push_str(&format!("/{}", count.saturating_sub(1)));
+                        lines.push((table.clone(), line));
+                    }
+                }
+            }
+            TomlEvent::KeyValue { key, value } => {
+                let mut pointer = table.clone();
+                for segment in &key {
+                    pointer.push('/');
+                    pointer.push_str(&escape_token(segment));
+                    lines.push((pointer.clone(), line));
+                }
+                nested_key_lines(&value, &pointer, line, &mut lines);
+            }
+        }
+    }
+    Ok(lines)
+}
+
+#[cfg(feature = "toml")]
+fn nested_key_lines(value: &Value, pointer: &str, line: u32, lines: &mut Vec<(String, u32)>) {
+    match value {
+        Value::Object(entries) => {
+            for (key, value) in entries {
+                let child = format!("{pointer}/{}", escape_token(key));
+                lines.push((child.clone(), line));
+                nested_key_lines(value, &child, line, lines);
+            }
+        }
+        Value::Array(items) => {
+            for (index, value) in items.iter().enumerate() {
+                nested_key_lines(value, &format!("{pointer}/{index}"), line, lines);
+            }
+        }
+        _ => {}
+    }
+}
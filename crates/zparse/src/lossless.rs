@@ -0,0 +1,423 @@
+//! Parsing that remembers source formatting for faithful rewrites
+//!
+//! Enabled by the `lossless` feature. [`parse_lossless`] keeps, next to the
+//! parsed value, the parts of the source spelling a plain parse throws away,
+//! so a tool that loads, edits and saves a file changes as little of it as
+//! possible. For TOML that is how each key was written: a key quoted in the
+//! source is written quoted again and a bare key stays bare. For JSON it is
+//! the comments of a JSONC file, attached to the value they document and
+//! written back around it. For YAML it is the quotes around keys and
+//! strings, kept when writing with [`crate::YamlStringStyle::Preserve`].
+//!
+//! ```
+//! use zparse::lossless::parse_lossless;
+//! use zparse::{ConvertOptions, Format, FormatOptions};
+//! # fn main() -> Result<(), zparse::Error> {
+//! let doc = parse_lossless("\"name\" = 'zparse'", Format::Toml, &ConvertOptions::default())?;
+//! let out = doc.serialize(Format::Toml, &FormatOptions::default())?;
+//! assert_eq!(out, "\"name\" = \"zparse\"");
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ```
+//! use zparse::lossless::parse_lossless;
+//! use zparse::{ConvertOptions, Format, FormatOptions, JsonConfig};
+//! # fn main() -> Result<(), zparse::Error> {
+//! let options = ConvertOptions {
+//!     json: JsonConfig::default().with_comments(true),
+//!     ..Default::default()
+//! };
+//! let doc = parse_lossless("{\n  // spaces per tab\n  \"tabSize\": 2\n}", Format::Json, &options)?;
+//! let out = doc.serialize(Format::Json, &FormatOptions::default())?;
+//! assert_eq!(out, "{\n  // spaces per tab\n  \"tabSize\": 2\n}");
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+#[cfg(feature = "yaml")]
+use crate::convert::serialize_yaml;
+use crate::convert::{
+    ConvertOptions, Format, FormatOptions, JsonDialect, escape_toml, json_key, parse_with_options,
+    serialize_json, serialize_toml_with_keys, serialize_with_options, toml_key,
+};
+use crate::error::{Result, Span};
+use crate::lexer::{JsonLexer, TokenKind};
+use crate::locate::escape_token;
+pub use crate::toml::KeyStyle;
+use crate::toml::Parser as TomlParser;
+use crate::value::Value;
+#[cfg(feature = "yaml")]
+use crate::yaml::{Parser as YamlParser, Quotes as YamlQuotes};
+
+/// A parsed document with the source formatting needed to write it back
+#[derive(Clone, Debug, PartialEq)]
+pub struct LosslessDocument {
+    value: Value,
+    format: Format,
+    /// Spelling of each TOML key by JSON pointer of its value
+    key_styles: HashMap<String, KeyStyle>,
+    /// Comments of a JSON document by JSON pointer of the value they belong to
+    comments: HashMap<String, Comments>,
+    /// Quoted keys and strings of a YAML document
+    #[cfg(feature = "yaml")]
+    yaml_quotes: YamlQuotes,
+}
+
+/// Comments a lossless JSON parse attached to one value.
+///
+/// Each comment is kept as written, including its `//` or `/* */` delimiters.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Comments {
+    /// Comments on the lines before the value, or before its key
+    pub leading: Vec<String>,
+    /// Comments after the value on the same line; for the root value also
+    /// the comments at the end of the document
+    pub trailing: Vec<String>,
+    /// Comments after the last entry of an array or object, before the
+    /// closing bracket
+    pub dangling: Vec<String>,
+}
+
+impl LosslessDocument {
+    /// The parsed value
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// The parsed value, for edits before serializing again
+    pub fn value_mut(&mut self) -> &mut Value {
+        &mut self.value
+    }
+
+    /// Discard the formatting and keep the value
+    pub fn into_value(self) -> Value {
+        self.value
+    }
+
+    /// Format the document was parsed from
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// How the TOML key naming the value at `pointer` was written, if the
+    /// source had one
+    pub fn key_style(&self, pointer: &str) -> Option<KeyStyle> {
+        self.key_styles.get(pointer).copied()
+    }
+
+    /// Comments attached to the JSON value at `pointer`
+    pub fn comments(&self, pointer: &str) -> Option<&Comments> {
+        self.comments.get(pointer)
+    }
+
+    /// The quoted keys and strings of a YAML source
+    #[cfg(feature = "yaml")]
+    pub fn yaml_quotes(&self) -> &YamlQuotes {
+        &self.yaml_quotes
+    }
+
+    /// Serialize the value, reusing the recorded formatting where `format`
+    /// can express it.
+    ///
+    /// Keys added after parsing, and recorded styles that can't hold the key
+    /// (a literal key containing `'`), fall back to the default quoting. JSON
+    /// with comments is always indented so line comments stay on their own
+    /// lines; comments of removed values are dropped.
+    pub fn serialize(&self, format: Format, options: &FormatOptions) -> Result<String> {
+        match format {
+            Format::Json if !self.comments.is_empty() => Ok(self.json_with_comments(options)),
+            Format::Toml => {
+                let spell = |pointer: &str, key: &str| match self.key_style(pointer) {
+                    Some(KeyStyle::Basic) => format!("\"{}\"", escape_toml(key)),
+                    Some(KeyStyle::Literal) if is_literal_key(key) => format!("'{key}'"),
+                    _ => toml_key(key),
+                };
+                serialize_toml_with_keys(&self.value, options, &spell)
+            }
+            #[cfg(feature = "yaml")]
+            Format::Yaml => Ok(serialize_yaml(
+                &self.value,
+                options,
+                Some(&self.yaml_quotes),
+            )),
+            _ => serialize_with_options(&self.value, format, options),
+        }
+    }
+}
+
+/// Parse `input` keeping the formatting [`LosslessDocument::serialize`] reuses
+pub fn parse_lossless(
+    input: &str,
+    format: Format,
+    options: &ConvertOptions,
+) -> Result<LosslessDocument> {
+    let mut key_styles = HashMap::new();
+    let mut comments = HashMap::new();
+    #[cfg(feature = "yaml")]
+    let mut yaml_quotes = YamlQuotes::default();
+    let value = match format {
+        Format::Toml => {
+            let mut parser = TomlParser::with_config(input.as_bytes(), options.toml);
+            parser.record_key_styles();
+            let value = parser.parse()?;
+            key_styles.extend(parser.take_key_styles());
+            value
+        }
+        Format::Json => {
+            let value = parse_with_options(input, format, options)?;
+            if options.json.allow_comments {
+                comments = json_comments(input)?;
+            }
+            value
+        }
+        #[cfg(feature = "yaml")]
+        Format::Yaml => {
+            let mut parser = YamlParser::with_config(input.as_bytes(), options.yaml);
+            parser.record_quotes();
+            let value = parser.parse()?;
+            yaml_quotes = parser.take_quotes();
+            value
+        }
+        _ => parse_with_options(input, format, options)?,
+    };
+    Ok(LosslessDocument {
+        value,
+        format,
+        key_styles,
+        comments,
+        #[cfg(feature = "yaml")]
+        yaml_quotes,
+    })
+}
+
+impl LosslessDocument {
+    /// Indented JSON with the recorded comments around their values
+    fn json_with_comments(&self, options: &FormatOptions) -> String {
+        let root = self.comments("");
+        let mut lines: Vec<String> = root.map(|c| c.leading.clone()).unwrap_or_default();
+        lines.push(self.json_value(&self.value, "", 0, options));
+        lines.extend(root.map(|c| c.trailing.clone()).unwrap_or_default());
+        lines.join("\n")
+    }
+
+    fn json_value(
+        &self,
+        value: &Value,
+        pointer: &str,
+        indent: usize,
+        options: &FormatOptions,
+    ) -> String {
+        let (open, close, entries): (char, char, Vec<(String, String)>) = match value {
+            Value::Array(items) => (
+                '[',
+                ']',
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(index, item)| {
+                        let pointer = format!("{pointer}/{index}");
+                        let text = self.json_value(item, &pointer, indent + 1, options);
+                        (pointer, text)
+                    })
+                    .collect(),
+            ),
+            Value::Object(entries) => (
+                '{',
+                '}',
+                entries
+                    .iter()
+                    .map(|(key, item)| {
+                        let pointer = format!("{pointer}/{}", escape_token(key));
+                        let text = self.json_value(item, &pointer, indent + 1, options);
+                        (pointer, format!("{}: {text}", json_key(key, options)))
+                    })
+                    .collect(),
+            ),
+            _ => return serialize_json(value, options),
+        };
+        let dangling = self
+            .comments(pointer)
+            .map(|c| c.dangling.as_slice())
+            .unwrap_or_default();
+        if entries.is_empty() && dangling.is_empty() {
+            return format!("{open}{close}");
+        }
+
+        let pad = "  ".repeat(indent + 1);
+        let trailing_comma =
+            options.json_trailing_commas && options.json_dialect != JsonDialect::Json;
+        let count = entries.len();
+        let mut out = String::from(open);
+        for (index, (pointer, text)) in entries.into_iter().enumerate() {
+            let comments = self.comments(&pointer);
+            for comment in comments.map(|c| c.leading.as_slice()).unwrap_or_default() {
+                out.push_str(&format!("\n{pad}{comment}"));
+            }
+            out.push_str(&format!("\n{pad}{text}"));
+            if index + 1 < count || trailing_comma {
+                out.push(',');
+            }
+            for comment in comments.map(|c| c.trailing.as_slice()).unwrap_or_default() {
+                out.push_str(&format!(" {comment}"));
+            }
+        }
+        for comment in dangling {
+            out.push_str(&format!("\n{pad}{comment}"));
+        }
+        out.push_str(&format!("\n{}{close}", "  ".repeat(indent)));
+        out
+    }
+}
+
+/// Attach each comment of a JSONC document to a value: comments on the line
+/// of a value trail it, comments on lines of their own lead the next entry,
+/// and comments before a closing bracket dangle in that container
+fn json_comments(input: &str) -> Result<HashMap<String, Comments>> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let mut lexer = JsonLexer::with_options(input.as_bytes(), true);
+    lexer.record_comments();
+    let mut tokens = Vec::new();
+    loop {
+        let token = lexer.next_token()?;
+        let eof = token.kind == TokenKind::Eof;
+        tokens.push(token);
+        if eof {
+            break;
+        }
+    }
+
+    let mut attacher = CommentAttacher::default();
+    let mut comments = lexer.take_comments().into_iter().peekable();
+    for token in tokens {
+        while let Some((span, text)) =
+            comments.next_if(|(span, _)| span.start.offset < token.span.start.offset)
+        {
+            attacher.comment(span, text);
+        }
+        attacher.token(&token.kind, token.span);
+    }
+    Ok(attacher.comments)
+}
+
+struct Container {
+    pointer: String,
+    is_array: bool,
+    /// Index of the current array item
+    index: usize,
+    /// Pointer of the current object entry, once its key is read
+    entry: Option<String>,
+}
+
+#[derive(Default)]
+struct CommentAttacher {
+    comments: HashMap<String, Comments>,
+    stack: Vec<Container>,
+    /// Comments waiting for the entry they lead
+    pending: Vec<String>,
+    /// Pointer and last line of the most recently completed value
+    last: Option<(String, u32)>,
+}
+
+impl CommentAttacher {
+    fn comment(&mut self, span: Span, text: String) {
+        match &self.last {
+            Some((pointer, line)) if span.start.line == *line && self.pending.is_empty() => {
+                let pointer = pointer.clone();
+                self.entry(pointer).trailing.push(text);
+            }
+            _ => self.pending.push(text),
+        }
+    }
+
+    fn token(&mut self, kind: &TokenKind, span: Span) {
+        match kind {
+            TokenKind::LeftBrace | TokenKind::LeftBracket => {
+                let pointer = self.value_start();
+                self.stack.push(Container {
+                    pointer,
+                    is_array: *kind == TokenKind::LeftBracket,
+                    index: 0,
+                    entry: None,
+                });
+                self.last = None;
+            }
+            TokenKind::RightBrace | TokenKind::RightBracket => {
+                if let Some(container) = self.stack.pop() {
+                    if !self.pending.is_empty() {
+                        let pending = std::mem::take(&mut self.pending);
+                        self.entry(container.pointer.clone())
+                            .dangling
+                            .extend(pending);
+                    }
+                    self.last = Some((container.pointer, span.end.line));
+                }
+            }
+            TokenKind::Comma => {
+                if let Some(container) = self.stack.last_mut() {
+                    container.index += 1;
+                    container.entry = None;
+                }
+            }
+            TokenKind::Colon => {}
+            TokenKind::String(key)
+                if self
+                    .stack
+                    .last()
+                    .is_some_and(|c| !c.is_array && c.entry.is_none()) =>
+            {
+                let parent = self
+                    .stack
+                    .last()
+                    .map(|c| c.pointer.as_str())
+                    .unwrap_or_default();
+                let pointer = format!("{parent}/{}", escape_token(key));
+                self.lead(&pointer);
+                if let Some(container) = self.stack.last_mut() {
+                    container.entry = Some(pointer);
+                }
+                self.last = None;
+            }
+            TokenKind::Eof if !self.pending.is_empty() => {
+                let pending = std::mem::take(&mut self.pending);
+                self.entry(String::new()).trailing.extend(pending);
+            }
+            TokenKind::Eof => {}
+            _ => {
+                let pointer = self.value_start();
+                self.last = Some((pointer, span.end.line));
+            }
+        }
+    }
+
+    /// Pointer of the value starting at the current token, handing it any
+    /// pending comments
+    fn value_start(&mut self) -> String {
+        let pointer = match self.stack.last() {
+            None => String::new(),
+            Some(c) if c.is_array => format!("{}/{}", c.pointer, c.index),
+            Some(c) => c.entry.clone().unwrap_or_default(),
+        };
+        self.lead(&pointer);
+        pointer
+    }
+
+    fn lead(&mut self, pointer: &str) {
+        if !self.pending.is_empty() {
+            let pending = std::mem::take(&mut self.pending);
+            self.entry(pointer.to_string()).leading.extend(pending);
+        }
+    }
+
+    fn entry(&mut self, pointer: String) -> &mut Comments {
+        self.comments.entry(pointer).or_default()
+    }
+}
+
+/// Whether `key` can be written between single quotes
+fn is_literal_key(key: &str) -> bool {
+    !key.chars()
+        .any(|ch| ch == '\'' || (ch.is_control() && ch != '\t'))
+}
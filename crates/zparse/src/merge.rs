@@ -0,0 +1,52 @@
+//! Merging of parsed documents
+
+use crate::value::Value;
+
+/// How two documents are combined by [`Value::merge`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Top-level keys of the overlay replace keys of the base
+    Shallow,
+    /// Nested objects are merged key by key; other values are replaced
+    #[default]
+    Deep,
+}
+
+impl Value {
+    /// Merge `other` on top of this value.
+    ///
+    /// Only objects are combined; when either side is not an object the value
+    /// is replaced by `other`. Arrays are replaced rather than concatenated.
+    pub fn merge(&mut self, other: Self, strategy: MergeStrategy) {
+        match (self, other) {
+            (Self::Object(base), Self::Object(overlay)) => {
+                for (key, value) in overlay {
+                    match (strategy, base.get_mut(&key)) {
+                        (MergeStrategy::Deep, Some(existing)) => existing.merge(value, strategy),
+                        _ => {
+                            base.insert(key, value);
+                        }
+                    }
+                }
+            }
+            (base, other) => *base = other,
+        }
+    }
+}
+
+/// Merge documents in order, later documents taking precedence.
+///
+/// Returns `Value::Null` when `values` is empty.
+pub fn merge_all<I>(values: I, strategy: MergeStrategy) -> Value
+where
+    I: IntoIterator<Item = Value>,
+{
+    let mut values = values.into_iter();
+    let Some(mut merged) = values.next() else {
+        return Value::Null;
+    };
+    for value in values {
+        merged.merge(value, strategy);
+    }
+    merged
+}
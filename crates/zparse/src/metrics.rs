@@ -0,0 +1,243 @@
+//! Size and shape metrics of parsed documents
+//!
+//! Parsers already enforce depth and size limits on their input; these let
+//! applications apply their own limits after parsing, e.g. to reject a merged
+//! configuration that grew too large, or log how complex a document is. The
+//! walks use an explicit stack, so arbitrarily deep values are safe to inspect.
+//!
+//! [`Value::stats`] gathers all of them in one pass, with counts by type and
+//! key and a distribution of array lengths, to get a quick picture of an
+//! unfamiliar payload.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::mem::size_of;
+
+use crate::value::{Object, Value};
+
+impl Value {
+    /// Nesting depth counted in arrays and objects, the same way parsers
+    /// apply `max_depth`: scalars are 0, `[]` is 1 and `{"a": [1]}` is 2
+    pub fn depth(&self) -> usize {
+        let mut deepest = 0;
+        let mut stack = vec![(self, 0)];
+        while let Some((value, depth)) = stack.pop() {
+            match value {
+                Self::Array(items) => stack.extend(items.iter().map(|item| (item, depth + 1))),
+                Self::Object(entries) => {
+                    stack.extend(entries.values().map(|entry| (entry, depth + 1)));
+                }
+                _ => continue,
+            }
+            deepest = deepest.max(depth + 1);
+        }
+        deepest
+    }
+
+    /// Number of values in the tree, including this one and every container
+    pub fn count_nodes(&self) -> usize {
+        let mut count = 0;
+        let mut stack = vec![self];
+        while let Some(value) = stack.pop() {
+            count += 1;
+            match value {
+                Self::Array(items) => stack.extend(items.iter()),
+                Self::Object(entries) => stack.extend(entries.values()),
+                _ => {}
+            }
+        }
+        count
+    }
+
+    /// Estimated bytes held by the tree: this value plus the heap allocations
+    /// of its strings, byte strings, arrays and objects.
+    ///
+    /// Allocated capacity is counted rather than length, and object entries
+    /// include their key, hash and index slot. Allocator overhead is ignored,
+    /// so treat the result as a lower bound for comparisons and logging.
+    pub fn approx_memory(&self) -> usize {
+        let entry = size_of::<String>() + size_of::<Self>() + 2 * size_of::<usize>();
+        let mut total = size_of::<Self>();
+        let mut stack = vec![self];
+        while let Some(value) = stack.pop() {
+            match value {
+                Self::String(s) => total += s.capacity(),
+                Self::Bytes(bytes) => total += bytes.capacity(),
+                Self::Array(items) => {
+                    total += items.0.capacity() * size_of::<Self>();
+                    stack.extend(items.iter());
+                }
+                Self::Object(entries) => {
+                    total += entries.0.capacity() * entry;
+                    total += entries.keys().map(String::capacity).sum::<usize>();
+                    stack.extend(entries.values());
+                }
+                Self::Null | Self::Bool(_) | Self::Number(_) | Self::Datetime(_) => {}
+            }
+        }
+        total
+    }
+}
+
+/// Summary of a document's structure, from [`Value::stats`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Size of the source text, when known; [`Value::stats`] leaves it unset
+    pub input_bytes: Option<usize>,
+    /// Number of values of each [`Value::type_name`], containers included
+    pub types: BTreeMap<&'static str, usize>,
+    /// How many objects each key appears in
+    pub keys: BTreeMap<String, usize>,
+    /// Same as [`Value::depth`]
+    pub max_depth: usize,
+    /// Number of arrays by length, bucketed by powers of ten: the key is the
+    /// bucket's lower bound (0, 1, 10, 100, ...) and 0 holds only empty arrays
+    pub array_lengths: BTreeMap<usize, usize>,
+    /// Length of the longest array
+    pub max_array_length: usize,
+    /// Total UTF-8 bytes of string values (keys not included)
+    pub string_bytes: usize,
+    /// Total bytes of byte strings
+    pub byte_string_bytes: usize,
+    /// Same as [`Value::approx_memory`]
+    pub memory_bytes: usize,
+}
+
+impl Stats {
+    /// The `limit` most common keys, most common first and ties by name
+    pub fn top_keys(&self, limit: usize) -> Vec<(&str, usize)> {
+        let mut keys: Vec<(&str, usize)> = self
+            .keys
+            .iter()
+            .map(|(key, count)| (key.as_str(), *count))
+            .collect();
+        keys.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        keys.truncate(limit);
+        keys
+    }
+
+    /// The stats as an object, for writing out in any format. Array length
+    /// buckets are keyed by their range, e.g. `"10-99"`.
+    pub fn to_value(&self) -> Value {
+        let count = |n: usize| Value::from(u64::try_from(n).unwrap_or(u64::MAX));
+        let mut object = Object::new();
+        if let Some(bytes) = self.input_bytes {
+            object.insert("input_bytes", count(bytes));
+        }
+        object.insert(
+            "types",
+            Value::Object(
+                self.types
+                    .iter()
+                    .map(|(name, n)| ((*name).to_string(), count(*n)))
+                    .collect(),
+            ),
+        );
+        object.insert(
+            "keys",
+            Value::Object(
+                self.top_keys(usize::MAX)
+                    .into_iter()
+                    .map(|(key, n)| (key.to_string(), count(n)))
+                    .collect(),
+            ),
+        );
+        object.insert("max_depth", count(self.max_depth));
+        object.insert(
+            "array_lengths",
+            Value::Object(
+                self.array_lengths
+                    .iter()
+                    .map(|(low, n)| (bucket_label(*low), count(*n)))
+                    .collect(),
+            ),
+        );
+        object.insert("max_array_length", count(self.max_array_length));
+        object.insert("string_bytes", count(self.string_bytes));
+        object.insert("byte_string_bytes", count(self.byte_string_bytes));
+        object.insert("memory_bytes", count(self.memory_bytes));
+        Value::Object(object)
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(bytes) = self.input_bytes {
+            writeln!(f, "input:        {bytes} bytes")?;
+        }
+        writeln!(f, "max depth:    {}", self.max_depth)?;
+        writeln!(f, "strings:      {} bytes", self.string_bytes)?;
+        if self.byte_string_bytes > 0 {
+            writeln!(f, "byte strings: {} bytes", self.byte_string_bytes)?;
+        }
+        writeln!(f, "memory:       ~{} bytes", self.memory_bytes)?;
+        writeln!(f, "types:")?;
+        for (name, n) in &self.types {
+            writeln!(f, "  {name:<10} {n}")?;
+        }
+        if !self.array_lengths.is_empty() {
+            writeln!(f, "array lengths (max {}):", self.max_array_length)?;
+            for (low, n) in &self.array_lengths {
+                writeln!(f, "  {:<10} {n}", bucket_label(*low))?;
+            }
+        }
+        if !self.keys.is_empty() {
+            writeln!(f, "keys:")?;
+            for (key, n) in self.top_keys(usize::MAX) {
+                writeln!(f, "  {key:<10} {n}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Value {
+    /// Structure summary of the tree, see [`Stats`]
+    pub fn stats(&self) -> Stats {
+        let mut stats = Stats {
+            max_depth: self.depth(),
+            memory_bytes: self.approx_memory(),
+            ..Stats::default()
+        };
+        let mut stack = vec![self];
+        while let Some(value) = stack.pop() {
+            *stats.types.entry(value.type_name()).or_default() += 1;
+            match value {
+                Self::String(s) => stats.string_bytes += s.len(),
+                Self::Bytes(bytes) => stats.byte_string_bytes += bytes.len(),
+                Self::Array(items) => {
+                    *stats.array_lengths.entry(bucket(items.len())).or_default() += 1;
+                    stats.max_array_length = stats.max_array_length.max(items.len());
+                    stack.extend(items.iter());
+                }
+                Self::Object(entries) => {
+                    for (key, entry) in entries.iter() {
+                        *stats.keys.entry(key.clone()).or_default() += 1;
+                        stack.push(entry);
+                    }
+                }
+                Self::Null | Self::Bool(_) | Self::Number(_) | Self::Datetime(_) => {}
+            }
+        }
+        stats
+    }
+}
+
+/// Lower bound of the power-of-ten bucket holding `len`
+fn bucket(len: usize) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    let mut low = 1;
+    while low <= len / 10 {
+        low *= 10;
+    }
+    low
+}
+
+fn bucket_label(low: usize) -> String {
+    match low {
+        0 => "0".to_string(),
+        _ => format!("{low}-{}", low * 10 - 1),
+    }
+}
@@ -0,0 +1,296 @@
+//! High-level file operations behind the `zparse` CLI
+//!
+//! These are the same steps `zparse parse` and `zparse convert` take, exposed
+//! so other tools can embed them instead of shelling out: formats are inferred
+//! from the file extension (falling back to the content), `.jsonc` files are
+//! read as lenient JSON, and files are replaced atomically.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::compress;
+use crate::convert::{ConvertOptions, Format, convert_with_options, parse_with_options};
+use crate::error::{Error, ErrorKind, Result, Span};
+use crate::input::{self, Utf8Mode};
+use crate::source;
+use crate::value::Value;
+use crate::{SUPPORTED_EXTENSIONS, detect_format_from_content, detect_format_from_path};
+
+/// Returns true if `path` has a `.jsonc` extension (case-insensitive), before
+/// any `.gz` or `.zst`
+pub fn is_jsonc_path(path: &Path) -> bool {
+    compress::strip_extension(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("jsonc"))
+}
+
+/// Format of a document from its path, or from `content` when the extension
+/// is missing or unknown
+pub fn infer_format(path: Option<&Path>, content: &str) -> Option<Format> {
+    path.and_then(detect_format_from_path)
+        .or_else(|| detect_format_from_content(content))
+}
+
+/// `options` adjusted for reading `path`: `.jsonc` files allow comments and
+/// trailing commas
+pub fn options_for_path(path: &Path, options: &ConvertOptions) -> ConvertOptions {
+    let mut options = options.clone();
+    if is_jsonc_path(path) {
+        options.json = options.json.with_comments(true).with_trailing_commas(true);
+    }
+    options
+}
+
+/// Read and parse `path`, returning the inferred format with the value.
+///
+/// Compressed files are decompressed first, see [`crate::compress`]. A
+/// valid UTF-8 file is parsed from the bytes read, without being copied
+/// again into a `String`.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(path = %path.as_ref().display()))
+)]
+pub fn parse_file(path: impl AsRef<Path>, options: &ConvertOptions) -> Result<(Format, Value)> {
+    let path = path.as_ref();
+    let bytes = source::read(path)?;
+    let bytes = compress::decode(Some(path), &bytes)?;
+    let input = input::decode(&bytes, options.utf8)?;
+    let input = input.strip_prefix('\u{feff}').unwrap_or(&input);
+    let format = infer_format(Some(path), input).ok_or_else(|| unknown_format(path))?;
+    #[cfg(feature = "tracing")]
+    tracing::debug!(?format, len = input.len(), "inferred format");
+    let mut value = parse_with_options(input, format, &options_for_path(path, options))?;
+    if options.utf8 == Utf8Mode::Bytes {
+        input::restore_bytes(&mut value);
+    }
+    Ok((format, value))
+}
+
+/// Read `path` and convert it to `to`
+pub fn convert_file(
+    path: impl AsRef<Path>,
+    to: Format,
+    options: &ConvertOptions,
+) -> Result<String> {
+    let path = path.as_ref();
+    let (from, input) = read_with_format(path, text_mode(options.utf8))?;
+    convert_with_options(&input, from, to, &options_for_path(path, options))
+}
+
+/// Convert `input` into `output`, inferring the target format from the output
+/// extension unless `to` is given. An output ending in `.gz` or `.zst` is
+/// compressed, see [`crate::compress`].
+pub fn convert_path(
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    to: Option<Format>,
+    options: &ConvertOptions,
+) -> Result<()> {
+    let output = output.as_ref();
+    let to = match to {
+        Some(format) => format,
+        None => detect_format_from_path(output).ok_or_else(|| unknown_format(output))?,
+    };
+    let converted = convert_file(input, to, options)?;
+    write_atomic(
+        output,
+        &compress::encode(output, converted.as_bytes())?,
+        None,
+    )
+}
+
+/// Convert `path` to `to` and replace it, optionally keeping the original as
+/// `path` + `backup_suffix`
+pub fn convert_in_place(
+    path: impl AsRef<Path>,
+    to: Format,
+    options: &ConvertOptions,
+    backup_suffix: Option<&str>,
+) -> Result<()> {
+    let path = path.as_ref();
+    let converted = convert_file(path, to, options)?;
+    write_atomic(
+        path,
+        &compress::encode(path, converted.as_bytes())?,
+        backup_suffix,
+    )
+}
+
+/// Read `path` and write it out again in its own format using
+/// `options.format` (e.g. to pretty print JSON or normalize TOML strings).
+///
+/// YAML keeps the file's indentation unless `options.format` sets it. XML
+/// is written back from its element tree, keeping CDATA sections, comments
+/// and processing instructions. With the `lossless` feature, comments in
+/// JSONC files and the quoting of TOML keys are kept.
+pub fn format_file(path: impl AsRef<Path>, options: &ConvertOptions) -> Result<String> {
+    let path = path.as_ref();
+    let (format, input) = read_with_format(path, text_mode(options.utf8))?;
+    format_str(&input, format, &options_for_path(path, options))
+}
+
+/// Write `input` out again in its own `format`, see [`format_file`]
+pub fn format_str(input: &str, format: Format, options: &ConvertOptions) -> Result<String> {
+    #[cfg(feature = "xml")]
+    if format == Format::Xml {
+        let doc = crate::XmlParser::with_config(input.as_bytes(), options.xml).parse()?;
+        return crate::xml::writer::to_string_with_options(&doc, &options.format);
+    }
+    #[cfg(feature = "yaml")]
+    if format == Format::Yaml {
+        let mut options = options.clone();
+        options.format = options.format.with_yaml_style_of(input);
+        return format_document(input, format, &options);
+    }
+    format_document(input, format, options)
+}
+
+/// Rewrite `path` in its own format, see [`format_file`]
+pub fn format_in_place(
+    path: impl AsRef<Path>,
+    options: &ConvertOptions,
+    backup_suffix: Option<&str>,
+) -> Result<()> {
+    let path = path.as_ref();
+    let formatted = format_file(path, options)?;
+    write_atomic(
+        path,
+        &compress::encode(path, formatted.as_bytes())?,
+        backup_suffix,
+    )
+}
+
+/// Canonical text of `path` for diffing, as used by `zparse textconv`: the
+/// file's own format, pretty printed with every object's keys sorted.
+///
+/// Comments and quoting are dropped and numbers are written the way zparse
+/// writes them, so two files that parse to the same value always produce
+/// the same text, ending with a newline.
+pub fn textconv_file(path: impl AsRef<Path>, options: &ConvertOptions) -> Result<String> {
+    let (format, mut value) = parse_file(path, options)?;
+    value.sort_keys();
+    let mut text =
+        crate::serialize_with_options(&value, format, &options.format.with_pretty(true))?;
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+    Ok(text)
+}
+
+#[cfg(feature = "lossless")]
+fn format_document(input: &str, format: Format, options: &ConvertOptions) -> Result<String> {
+    crate::lossless::parse_lossless(input, format, options)?.serialize(format, &options.format)
+}
+
+#[cfg(not(feature = "lossless"))]
+fn format_document(input: &str, format: Format, options: &ConvertOptions) -> Result<String> {
+    let value = parse_with_options(input, format, options)?;
+    crate::serialize_with_options(&value, format, &options.format)
+}
+
+/// Replace `path` with `data` through a synced temporary file in the same
+/// directory and a rename, so readers never see a partial file.
+///
+/// An existing file keeps its permissions; with `backup_suffix` a copy of it
+/// is left at `path` + suffix first.
+pub fn write_atomic(
+    path: impl AsRef<Path>,
+    data: &[u8],
+    backup_suffix: Option<&str>,
+) -> Result<()> {
+    let path = path.as_ref();
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let name = path
+        .file_name()
+        .ok_or_else(|| {
+            let err =
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, "path has no file name");
+            io_error(path, &err)
+        })?
+        .to_string_lossy();
+    let temp = dir.join(format!(".{name}.{}.tmp", std::process::id()));
+
+    let result = (|| {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp)
+            .map_err(|err| io_error(&temp, &err))?;
+        file.write_all(data)
+            .and_then(|()| file.sync_all())
+            .map_err(|err| io_error(&temp, &err))?;
+
+        if let Ok(metadata) = fs::metadata(path) {
+            file.set_permissions(metadata.permissions())
+                .map_err(|err| io_error(&temp, &err))?;
+            if let Some(suffix) = backup_suffix {
+                let mut backup = path.as_os_str().to_owned();
+                backup.push(suffix);
+                let backup = PathBuf::from(backup);
+                fs::copy(path, &backup).map_err(|err| io_error(&backup, &err))?;
+            }
+        }
+
+        fs::rename(&temp, path).map_err(|err| io_error(path, &err))
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Read `path` as text and infer its format. In [`Utf8Mode::Bytes`], callers
+/// pass values parsed from the text through [`input::restore_bytes`].
+pub(crate) fn read_with_format(path: &Path, utf8: Utf8Mode) -> Result<(Format, String)> {
+    let bytes = source::read(path)?;
+    let bytes = compress::decode(Some(path), &bytes)?;
+    let mut input = input::decode(&bytes, utf8)?.into_owned();
+    if input.starts_with('\u{feff}') {
+        input.drain(..'\u{feff}'.len_utf8());
+    }
+    let format = infer_format(Some(path), &input).ok_or_else(|| unknown_format(path))?;
+    Ok((format, input))
+}
+
+/// Error for a file whose format can't be inferred, naming the extensions
+/// that are recognized
+pub(crate) fn unknown_format(path: &Path) -> Error {
+    Error::with_message(
+        ErrorKind::UnknownFormat {
+            path: path.display().to_string(),
+        },
+        Span::empty(),
+        format!(
+            "could not infer format of {} (supported extensions: {})",
+            path.display(),
+            SUPPORTED_EXTENSIONS
+                .iter()
+                .map(|ext| format!(".{ext}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    )
+}
+
+/// `utf8` for reads whose text is written back out, where bytes can't be kept
+const fn text_mode(utf8: Utf8Mode) -> Utf8Mode {
+    match utf8 {
+        Utf8Mode::Bytes => Utf8Mode::Lossy,
+        mode => mode,
+    }
+}
+
+pub(crate) fn io_error(path: &Path, err: &std::io::Error) -> Error {
+    Error::with_message(
+        ErrorKind::Io {
+            path: path.display().to_string(),
+        },
+        Span::empty(),
+        format!("{}: {err}", path.display()),
+    )
+}
@@ -0,0 +1,174 @@
+//! Reading only part of a document
+//!
+//! [`parse_until_path`] returns the value at a dot-separated path such as
+//! `metadata.version` or `items.0.id` and [`parse_first`] the first items of
+//! the root array. Both stop reading as soon as they have their answer: the
+//! rest of the document is neither built nor checked, so a syntax error after
+//! that point goes unnoticed. JSON is read straight from its streaming parser
+//! and gains the most; the other formats still parse the whole input before
+//! the first event, and only skip building the parts that aren't needed.
+//!
+//! ```
+//! use zparse::Format;
+//! use zparse::partial::parse_until_path;
+//!
+//! # fn main() -> zparse::Result<()> {
+//! // Everything after the version is never looked at
+//! let input = r#"{"metadata": {"version": 3}, "data": [1, 2, oops"#;
+//! let version = parse_until_path(input, Format::Json, "metadata.version")?;
+//! assert_eq!(version.and_then(|v| v.as_number()), Some(3.0));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::convert::{ConvertOptions, Format};
+use crate::error::Result;
+use crate::event::{Container, Event, EventReader, collect, reader};
+use crate::value::{Array, Value};
+
+/// The value at `path` in `input`, or `None` if the document doesn't have it
+pub fn parse_until_path(input: &str, format: Format, path: &str) -> Result<Option<Value>> {
+    parse_until_path_with_options(input, format, path, &ConvertOptions::default())
+}
+
+/// [`parse_until_path`] with parser options
+pub fn parse_until_path_with_options(
+    input: &str,
+    format: Format,
+    path: &str,
+    options: &ConvertOptions,
+) -> Result<Option<Value>> {
+    read_path(&mut *reader(input, format, options)?, path)
+}
+
+/// Up to `count` items of the array at the root of `input`; a document that
+/// isn't an array counts as a single item
+pub fn parse_first(
+    input: &str,
+    format: Format,
+    count: usize,
+    options: &ConvertOptions,
+) -> Result<Array> {
+    read_items(&mut *reader(input, format, options)?, count)
+}
+
+/// Read events until the value at `path` is complete and return it. Path
+/// segments are object keys, or indices into arrays; an empty path is the
+/// whole document.
+///
+/// Part of the event API, so only available with the `unstable` feature.
+#[cfg(feature = "unstable")]
+pub fn find_path(events: &mut dyn EventReader, path: &str) -> Result<Option<Value>> {
+    read_path(events, path)
+}
+
+/// Read events for up to `count` items of the root array.
+///
+/// Part of the event API, so only available with the `unstable` feature.
+#[cfg(feature = "unstable")]
+pub fn take_items(events: &mut dyn EventReader, count: usize) -> Result<Array> {
+    read_items(events, count)
+}
+
+fn read_path(events: &mut dyn EventReader, path: &str) -> Result<Option<Value>> {
+    enum Frame {
+        /// Index of the current item and of the next one
+        Array { current: usize, next: usize },
+        /// Key of the current entry
+        Object { key: Option<String> },
+    }
+
+    let target: Vec<&str> = path.split('.').filter(|seg| !seg.is_empty()).collect();
+    let mut frames: Vec<Frame> = Vec::new();
+
+    while let Some(event) = events.read_event()? {
+        match &event {
+            Event::Key(key) => {
+                if let Some(Frame::Object { key: current }) = frames.last_mut() {
+                    *current = Some(key.clone());
+                }
+                continue;
+            }
+            Event::ContainerEnd(_) => {
+                frames.pop();
+                if frames.is_empty() {
+                    return Ok(None);
+                }
+                continue;
+            }
+            Event::ContainerStart(_) | Event::Value(_) => {}
+        }
+
+        if let Some(Frame::Array { current, next }) = frames.last_mut() {
+            *current = *next;
+            *next = next.saturating_add(1);
+        }
+        let at_target = frames.len() == target.len()
+            && frames.iter().zip(&target).all(|(frame, seg)| match frame {
+                Frame::Array { current, .. } => seg.parse::<usize>().ok() == Some(*current),
+                Frame::Object { key } => key.as_deref() == Some(*seg),
+            });
+
+        match event {
+            Event::ContainerStart(kind) if at_target => {
+                return collect(&mut Prepend::new(Event::ContainerStart(kind), events)).map(Some);
+            }
+            Event::Value(value) if at_target => return Ok(Some(value)),
+            Event::Value(_) if frames.is_empty() => return Ok(None),
+            Event::ContainerStart(Container::Array) => {
+                frames.push(Frame::Array {
+                    current: 0,
+                    next: 0,
+                });
+            }
+            Event::ContainerStart(Container::Object) => frames.push(Frame::Object { key: None }),
+            _ => {}
+        }
+    }
+
+    Ok(None)
+}
+
+fn read_items(events: &mut dyn EventReader, count: usize) -> Result<Array> {
+    let mut items = Array::new();
+    match events.read_event()? {
+        Some(Event::ContainerStart(Container::Array)) => {}
+        Some(event) if count > 0 => {
+            items.push(collect(&mut Prepend::new(event, events))?);
+            return Ok(items);
+        }
+        _ => return Ok(items),
+    }
+
+    while items.len() < count {
+        match events.read_event()? {
+            Some(Event::ContainerEnd(_)) | None => break,
+            Some(event) => items.push(collect(&mut Prepend::new(event, events))?),
+        }
+    }
+    Ok(items)
+}
+
+/// An event already read, followed by the rest of a reader
+struct Prepend<'r> {
+    first: Option<Event>,
+    rest: &'r mut dyn EventReader,
+}
+
+impl<'r> Prepend<'r> {
+    fn new(first: Event, rest: &'r mut dyn EventReader) -> Self {
+        Self {
+            first: Some(first),
+            rest,
+        }
+    }
+}
+
+impl EventReader for Prepend<'_> {
+    fn read_event(&mut self) -> Result<Option<Event>> {
+        match self.first.take() {
+            Some(event) => Ok(Some(event)),
+            None => self.rest.read_event(),
+        }
+    }
+}
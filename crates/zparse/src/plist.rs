@@ -0,0 +1,564 @@
+//! Apple property lists
+//!
+//! Property lists map onto [`Value`] directly: `dict` is an object, `array`
+//! an array, `string` a string, `integer` and `real` numbers, `true`/`false`
+//! booleans, `date` an offset datetime in UTC and `data` a byte string.
+//!
+//! [`from_str`] reads the XML form, [`from_bytes`] also reads binary
+//! (`bplist00`) files, and [`to_string`] writes XML. Property lists have no
+//! null, so writing one fails with the path of the null. Binary plist UIDs,
+//! as found in keyed archives, read as `{"CF$UID": n}`.
+//!
+//! The format is also available as `"plist"` in the [format
+//! registry](crate::registry).
+
+use time::format_description::well_known::Rfc3339;
+use time::macros::{datetime, format_description};
+use time::{Duration, OffsetDateTime, UtcOffset};
+
+use crate::base64;
+use crate::convert::{ConvertOptions, FormatOptions, Serializer};
+use crate::error::{Error, ErrorKind, Result, Span};
+use crate::registry::FormatProvider;
+use crate::value::{Array, Object, TomlDatetime, Value};
+use crate::xml::writer::escape;
+use crate::xml::{Content, Element, Parser as XmlParser};
+
+/// Deepest nesting of arrays and dicts read from a property list
+const MAX_DEPTH: usize = 128;
+
+/// Start of binary property list dates, 2001-01-01T00:00:00Z
+const EPOCH: OffsetDateTime = datetime!(2001-01-01 0:00 UTC);
+
+/// Parse an XML property list
+pub fn from_str(input: &str) -> Result<Value> {
+    let document = XmlParser::new(input.as_bytes()).parse()?;
+    let root = &document.root;
+    if root.name != "plist" {
+        return read_element(root, 0);
+    }
+    let mut items = elements(root);
+    match (items.next(), items.next()) {
+        (Some(value), None) => read_element(value, 0),
+        _ => Err(invalid("<plist> must hold exactly one value")),
+    }
+}
+
+/// Parse a binary or XML property list
+pub fn from_bytes(input: &[u8]) -> Result<Value> {
+    if input.starts_with(b"bplist00") {
+        return Binary::new(input)?.read_root();
+    }
+    let text = std::str::from_utf8(input).map_err(|_| invalid("plist is not valid UTF-8"))?;
+    from_str(text.strip_prefix('\u{feff}').unwrap_or(text))
+}
+
+/// Write a value as an XML property list
+pub fn to_string(value: &Value) -> Result<String> {
+    let mut output = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" ",
+        "\"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n",
+        "<plist version=\"1.0\">\n",
+    ));
+    write_value(value, 0, "", &mut output)?;
+    output.push_str("</plist>\n");
+    Ok(output)
+}
+
+/// Property lists as a [`FormatProvider`], registered by default as `"plist"`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Plist;
+
+impl FormatProvider for Plist {
+    fn name(&self) -> &str {
+        "plist"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["plist"]
+    }
+
+    fn parse(&self, input: &str, _options: &ConvertOptions) -> Result<Value> {
+        from_str(input)
+    }
+
+    fn parse_bytes(&self, input: &[u8], _options: &ConvertOptions) -> Result<Value> {
+        from_bytes(input)
+    }
+
+    fn serialize(&self, value: &Value, options: &FormatOptions) -> Result<String> {
+        Serializer::serialize(self, value, options)
+    }
+
+    fn can_serialize(&self) -> bool {
+        true
+    }
+
+    fn detect(&self, input: &str) -> bool {
+        input.starts_with("bplist00")
+            || (input.trim_start().starts_with('<') && input.contains("<plist"))
+    }
+}
+
+impl Serializer for Plist {
+    fn serialize(&self, value: &Value, _options: &FormatOptions) -> Result<String> {
+        to_string(value)
+    }
+}
+
+fn elements(element: &Element) -> impl Iterator<Item = &Element> {
+    element.children.iter().filter_map(|child| match child {
+        Content::Element(element) => Some(element),
+        _ => None,
+    })
+}
+
+fn text(element: &Element) -> String {
+    element.children.iter().filter_map(Content::text).collect()
+}
+
+fn read_element(element: &Element, depth: usize) -> Result<Value> {
+    if depth >= MAX_DEPTH {
+        return Err(Error::new(
+            ErrorKind::MaxDepthExceeded {
+                max: u16::try_from(MAX_DEPTH).unwrap_or(u16::MAX),
+            },
+            Span::empty(),
+        ));
+    }
+    match element.name.as_str() {
+        "dict" => {
+            let mut object = Object::new();
+            let mut children = elements(element);
+            while let Some(key) = children.next() {
+                if key.name != "key" {
+                    return Err(invalid(&format!(
+                        "expected <key> in <dict>, found <{}>",
+                        key.name
+                    )));
+                }
+                let value = children
+                    .next()
+                    .ok_or_else(|| invalid("<key> in <dict> has no value"))?;
+                object.insert(text(key), read_element(value, depth + 1)?);
+            }
+            Ok(Value::Object(object))
+        }
+        "array" => elements(element)
+            .map(|item| read_element(item, depth + 1))
+            .collect::<Result<Array>>()
+            .map(Value::Array),
+        "string" => Ok(Value::String(text(element))),
+        "integer" => parse_integer(text(element).trim()),
+        "real" => {
+            let raw = text(element);
+            match raw.trim() {
+                "nan" => Ok(Value::Number(f64::NAN)),
+                "inf" | "+inf" => Ok(Value::Number(f64::INFINITY)),
+                "-inf" => Ok(Value::Number(f64::NEG_INFINITY)),
+                number => number
+                    .parse::<f64>()
+                    .map(Value::Number)
+                    .map_err(|_| invalid(&format!("invalid <real> {number:?}"))),
+            }
+        }
+        "true" => Ok(Value::Bool(true)),
+        "false" => Ok(Value::Bool(false)),
+        "date" => {
+            let raw = text(element);
+            OffsetDateTime::parse(raw.trim(), &Rfc3339)
+                .map(|date| Value::Datetime(TomlDatetime::OffsetDateTime(date)))
+                .map_err(|_| invalid(&format!("invalid <date> {:?}", raw.trim())))
+        }
+        "data" => base64::decode(&text(element))
+            .map(Value::Bytes)
+            .ok_or_else(|| invalid("invalid base64 in <data>")),
+        other => Err(invalid(&format!("unknown plist element <{other}>"))),
+    }
+}
+
+fn parse_integer(text: &str) -> Result<Value> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let magnitude = match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => digits.parse::<u64>(),
+    }
+    .map_err(|_| invalid(&format!("invalid <integer> {text:?}")))?;
+    if negative {
+        let value = i64::try_from(magnitude)
+            .map(i64::wrapping_neg)
+            .or_else(|_| {
+                if magnitude == i64::MIN.unsigned_abs() {
+                    Ok(i64::MIN)
+                } else {
+                    Err(invalid(&format!("<integer> {text:?} is out of range")))
+                }
+            })?;
+        Ok(Value::from(value))
+    } else {
+        Ok(Value::from(magnitude))
+    }
+}
+
+fn write_value(value: &Value, depth: usize, path: &str, output: &mut String) -> Result<()> {
+    let indent = "\t".repeat(depth);
+    match value {
+        Value::Null => {
+            return Err(Error::with_message(
+                ErrorKind::UnsupportedValue {
+                    path: path.to_string(),
+                },
+                Span::empty(),
+                format!("plist has no null (at {path:?})"),
+            ));
+        }
+        Value::Bool(true) => output.push_str(&format!("{indent}<true/>\n")),
+        Value::Bool(false) => output.push_str(&format!("{indent}<false/>\n")),
+        Value::Number(number) if number.fract() == 0.0 && number.abs() < 9.0e15 => {
+            output.push_str(&format!("{indent}<integer>{number:.0}</integer>\n"));
+        }
+        Value::Number(number) => {
+            let text = if number.is_nan() {
+                "nan".to_string()
+            } else if number.is_infinite() {
+                if *number > 0.0 { "inf" } else { "-inf" }.to_string()
+            } else {
+                number.to_string()
+            };
+            output.push_str(&format!("{indent}<real>{text}</real>\n"));
+        }
+        Value::String(text) => {
+            output.push_str(&format!("{indent}<string>{}</string>\n", escape(text)));
+        }
+        Value::Datetime(datetime) => {
+            let date = utc_date(datetime).ok_or_else(|| {
+                Error::with_message(
+                    ErrorKind::UnsupportedValue {
+                        path: path.to_string(),
+                    },
+                    Span::empty(),
+                    format!("plist dates need a day (at {path:?})"),
+                )
+            })?;
+            output.push_str(&format!("{indent}<date>{date}</date>\n"));
+        }
+        Value::Bytes(bytes) => {
+            output.push_str(&format!("{indent}<data>{}</data>\n", base64::encode(bytes)));
+        }
+        Value::Array(items) if items.is_empty() => output.push_str(&format!("{indent}<array/>\n")),
+        Value::Array(items) => {
+            output.push_str(&format!("{indent}<array>\n"));
+            for (index, item) in items.iter().enumerate() {
+                write_value(
+                    item,
+                    depth + 1,
+                    &child_path(path, &index.to_string()),
+                    output,
+                )?;
+            }
+            output.push_str(&format!("{indent}</array>\n"));
+        }
+        Value::Object(entries) if entries.is_empty() => {
+            output.push_str(&format!("{indent}<dict/>\n"));
+        }
+        Value::Object(entries) => {
+            output.push_str(&format!("{indent}<dict>\n"));
+            for (key, entry) in entries.iter() {
+                output.push_str(&format!("{indent}\t<key>{}</key>\n", escape(key)));
+                write_value(entry, depth + 1, &child_path(path, key), output)?;
+            }
+            output.push_str(&format!("{indent}</dict>\n"));
+        }
+    }
+    Ok(())
+}
+
+fn child_path(parent: &str, child: &str) -> String {
+    if parent.is_empty() {
+        child.to_string()
+    } else {
+        format!("{parent}.{child}")
+    }
+}
+
+/// A datetime as a plist `date` (`2024-01-31T12:00:00Z`); local datetimes and
+/// dates are taken as UTC, times alone have no date
+fn utc_date(datetime: &TomlDatetime) -> Option<String> {
+    let utc = match datetime {
+        TomlDatetime::OffsetDateTime(value) => value.to_offset(UtcOffset::UTC),
+        TomlDatetime::LocalDateTime(value) => value.assume_utc(),
+        TomlDatetime::LocalDate(value) => value.midnight().assume_utc(),
+        TomlDatetime::LocalTime(_) => return None,
+    };
+    utc.format(format_description!(
+        "[year]-[month]-[day]T[hour]:[minute]:[second]Z"
+    ))
+    .ok()
+}
+
+/// A binary property list being read
+struct Binary<'a> {
+    input: &'a [u8],
+    offsets: Vec<usize>,
+    ref_size: usize,
+    root: usize,
+    /// Values left to build before giving up, so shared references can't
+    /// blow up the output
+    budget: usize,
+}
+
+impl<'a> Binary<'a> {
+    fn new(input: &'a [u8]) -> Result<Self> {
+        let trailer = input
+            .len()
+            .checked_sub(32)
+            .and_then(|start| input.get(start..))
+            .filter(|_| input.len() >= 40)
+            .ok_or_else(|| invalid("binary plist is truncated"))?;
+        let field = |range: std::ops::Range<usize>| -> Result<usize> {
+            trailer
+                .get(range)
+                .and_then(read_uint)
+                .and_then(|value| usize::try_from(value).ok())
+                .ok_or_else(|| invalid("binary plist trailer is invalid"))
+        };
+        let offset_size = field(6..7)?;
+        let ref_size = field(7..8)?;
+        let count = field(8..16)?;
+        let root = field(16..24)?;
+        let table = field(24..32)?;
+        if !(1..=8).contains(&offset_size) || !(1..=8).contains(&ref_size) || root >= count {
+            return Err(invalid("binary plist trailer is invalid"));
+        }
+        let table_len = count
+            .checked_mul(offset_size)
+            .ok_or_else(|| invalid("binary plist offset table is invalid"))?;
+        let table = table
+            .checked_add(table_len)
+            .and_then(|end| input.get(table..end))
+            .ok_or_else(|| invalid("binary plist offset table is out of range"))?;
+        let offsets = table
+            .chunks(offset_size)
+            .map(|chunk| {
+                read_uint(chunk)
+                    .and_then(|offset| usize::try_from(offset).ok())
+                    .ok_or_else(|| invalid("binary plist offset is invalid"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            input,
+            offsets,
+            ref_size,
+            root,
+            budget: input.len().saturating_mul(4),
+        })
+    }
+
+    fn read_root(mut self) -> Result<Value> {
+        self.read_object(self.root, 0)
+    }
+
+    fn read_object(&mut self, index: usize, depth: usize) -> Result<Value> {
+        if depth >= MAX_DEPTH {
+            return Err(Error::new(
+                ErrorKind::MaxDepthExceeded {
+                    max: u16::try_from(MAX_DEPTH).unwrap_or(u16::MAX),
+                },
+                Span::empty(),
+            ));
+        }
+        self.budget = self
+            .budget
+            .checked_sub(1)
+            .ok_or_else(|| invalid("binary plist expands to too many values"))?;
+        let offset = *self
+            .offsets
+            .get(index)
+            .ok_or_else(|| invalid("binary plist object reference is out of range"))?;
+        let marker = *self
+            .input
+            .get(offset)
+            .ok_or_else(|| invalid("binary plist object offset is out of range"))?;
+        let start = offset.saturating_add(1);
+        let (kind, info) = (marker >> 4, marker & 0x0f);
+        match (kind, info) {
+            (0xa | 0xc, _) => {
+                let (len, start) = self.length(start, info)?;
+                let refs = self.refs(start, len)?;
+                let mut array = Array::with_capacity(refs.len());
+                for item in refs {
+                    array.push(self.read_object(item, depth + 1)?);
+                }
+                Ok(Value::Array(array))
+            }
+            (0xd, _) => {
+                let (len, start) = self.length(start, info)?;
+                let keys = self.refs(start, len)?;
+                let values =
+                    self.refs(start.saturating_add(len.saturating_mul(self.ref_size)), len)?;
+                let mut object = Object::new();
+                for (key, value) in keys.into_iter().zip(values) {
+                    let Value::String(key) = self.read_object(key, depth + 1)? else {
+                        return Err(invalid("binary plist dict key is not a string"));
+                    };
+                    object.insert(key, self.read_object(value, depth + 1)?);
+                }
+                Ok(Value::Object(object))
+            }
+            _ => self.read_scalar(marker, start),
+        }
+    }
+
+    /// An object that holds no references, kept out of [`Self::read_object`]
+    /// so each level of nesting uses little stack
+    fn read_scalar(&self, marker: u8, start: usize) -> Result<Value> {
+        let (kind, info) = (marker >> 4, marker & 0x0f);
+        match (kind, info) {
+            (0x0, 0x0) => Ok(Value::Null),
+            (0x0, 0x8) => Ok(Value::Bool(false)),
+            (0x0, 0x9) => Ok(Value::Bool(true)),
+            (0x1, _) => {
+                let (value, _) = self.read_int(start, info)?;
+                Ok(i64::try_from(value).map_or_else(
+                    |_| Value::from(u64::try_from(value).unwrap_or(u64::MAX)),
+                    Value::from,
+                ))
+            }
+            (0x2, 0x2) => {
+                let bytes = self.bytes(start, 4)?;
+                let bits: [u8; 4] = bytes.try_into().map_err(|_| truncated())?;
+                Ok(Value::Number(f64::from(f32::from_be_bytes(bits))))
+            }
+            (0x2, 0x3) => Ok(Value::Number(self.read_f64(start)?)),
+            (0x3, 0x3) => {
+                let seconds = self.read_f64(start)?;
+                Duration::checked_seconds_f64(seconds)
+                    .and_then(|since| EPOCH.checked_add(since))
+                    .map(|date| Value::Datetime(TomlDatetime::OffsetDateTime(date)))
+                    .ok_or_else(|| invalid("binary plist date is out of range"))
+            }
+            (0x4, _) => {
+                let (len, start) = self.length(start, info)?;
+                Ok(Value::Bytes(self.bytes(start, len)?.to_vec()))
+            }
+            (0x5, _) => {
+                let (len, start) = self.length(start, info)?;
+                Ok(Value::String(
+                    self.bytes(start, len)?
+                        .iter()
+                        .copied()
+                        .map(char::from)
+                        .collect(),
+                ))
+            }
+            (0x6, _) => {
+                let (len, start) = self.length(start, info)?;
+                let units: Vec<u16> = self
+                    .bytes(start, len.saturating_mul(2))?
+                    .chunks_exact(2)
+                    .map(|pair| match *pair {
+                        [high, low] => u16::from_be_bytes([high, low]),
+                        _ => 0,
+                    })
+                    .collect();
+                String::from_utf16(&units)
+                    .map(Value::String)
+                    .map_err(|_| invalid("binary plist string is not valid UTF-16"))
+            }
+            (0x8, _) => {
+                let bytes = self.bytes(start, usize::from(info).saturating_add(1))?;
+                let uid = read_uint(bytes).ok_or_else(|| invalid("binary plist UID is invalid"))?;
+                let mut object = Object::new();
+                object.insert("CF$UID", Value::from(uid));
+                Ok(Value::Object(object))
+            }
+            _ => Err(invalid(&format!(
+                "unknown binary plist marker 0x{marker:02x}"
+            ))),
+        }
+    }
+
+    /// The integer after an `0x1n` marker and the offset following it
+    fn read_int(&self, start: usize, info: u8) -> Result<(i128, usize)> {
+        let len = 1usize
+            .checked_shl(u32::from(info))
+            .filter(|len| *len <= 16)
+            .ok_or_else(|| invalid("binary plist integer is too wide"))?;
+        let bytes = self.bytes(start, len)?;
+        let value = match bytes.len() {
+            8 => {
+                let bits: [u8; 8] = bytes.try_into().map_err(|_| truncated())?;
+                i128::from(i64::from_be_bytes(bits))
+            }
+            // 128-bit integers hold unsigned 64-bit values in the low half
+            16 => i128::from(read_uint(bytes.get(8..).unwrap_or_default()).unwrap_or_default()),
+            _ => i128::from(read_uint(bytes).unwrap_or_default()),
+        };
+        Ok((value, start.saturating_add(len)))
+    }
+
+    fn read_f64(&self, start: usize) -> Result<f64> {
+        let bits: [u8; 8] = self.bytes(start, 8)?.try_into().map_err(|_| truncated())?;
+        Ok(f64::from_be_bytes(bits))
+    }
+
+    /// The length of a data, string or container object and where its
+    /// content starts: the marker's low nibble, or an integer after it
+    fn length(&self, start: usize, info: u8) -> Result<(usize, usize)> {
+        if info != 0x0f {
+            return Ok((usize::from(info), start));
+        }
+        let marker = *self.input.get(start).ok_or_else(truncated)?;
+        if marker >> 4 != 0x1 {
+            return Err(invalid("binary plist length is not an integer"));
+        }
+        let (len, next) = self.read_int(start.saturating_add(1), marker & 0x0f)?;
+        let len = usize::try_from(len).map_err(|_| invalid("binary plist length is invalid"))?;
+        Ok((len, next))
+    }
+
+    fn refs(&self, start: usize, count: usize) -> Result<Vec<usize>> {
+        self.bytes(start, count.saturating_mul(self.ref_size))?
+            .chunks(self.ref_size)
+            .map(|chunk| {
+                read_uint(chunk)
+                    .and_then(|index| usize::try_from(index).ok())
+                    .ok_or_else(|| invalid("binary plist object reference is invalid"))
+            })
+            .collect()
+    }
+
+    fn bytes(&self, start: usize, len: usize) -> Result<&'a [u8]> {
+        start
+            .checked_add(len)
+            .and_then(|end| self.input.get(start..end))
+            .ok_or_else(truncated)
+    }
+}
+
+/// A big-endian unsigned integer of up to 8 bytes
+fn read_uint(bytes: &[u8]) -> Option<u64> {
+    if bytes.len() > 8 {
+        return None;
+    }
+    Some(
+        bytes
+            .iter()
+            .fold(0u64, |value, byte| value << 8 | u64::from(*byte)),
+    )
+}
+
+fn truncated() -> Error {
+    invalid("binary plist is truncated")
+}
+
+fn invalid(message: &str) -> Error {
+    Error::with_message(ErrorKind::InvalidToken, Span::empty(), message.to_string())
+}
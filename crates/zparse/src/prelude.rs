@@ -0,0 +1,39 @@
+//! The most-used items in one import
+//!
+//! ```
+//! use zparse::prelude::*;
+//! # fn main() -> Result<(), Error> {
+//! let value = parse_bytes(b"name = \"zparse\"", Format::Toml)?;
+//! let json = serialize(&value, Format::Json)?;
+//! assert_eq!(json, r#"{"name":"zparse"}"#);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The prelude holds the value types, formats, errors, the parse, convert and
+//! serialize functions and the traits whose methods are called on them.
+//! Format-specific parsers and configs stay at the crate root, and so does
+//! [`crate::Result`], which would shadow the standard `Result` in a glob
+//! import.
+
+pub use crate::convert::{
+    ConvertOptions, Format, FormatOptions, Serializer, convert, convert_with_options,
+    parse_with_options, serialize, serialize_with_options, validate,
+};
+pub use crate::converter::Converter;
+pub use crate::document::{Document, parse_document, parse_document_file};
+pub use crate::error::{Error, ErrorKind, Span};
+pub use crate::ops::format_str;
+pub use crate::registry::{FormatProvider, Registry};
+pub use crate::value::{Array, Object, TomlDatetime, Value, ValueIndex};
+pub use crate::warning::Warning;
+pub use crate::{
+    detect_format_from_content, detect_format_from_path, from_bytes, from_csv_str, from_str,
+    parse_bytes, parse_bytes_with_options, parse_file, parse_path, parse_path_with_options,
+};
+#[cfg(feature = "toml")]
+pub use crate::{from_toml_bytes, from_toml_str};
+#[cfg(feature = "xml")]
+pub use crate::{from_xml_bytes, from_xml_str};
+#[cfg(feature = "yaml")]
+pub use crate::{from_yaml_bytes, from_yaml_str};
@@ -0,0 +1,1901 @@
+//! A small jq-like language for picking apart and reshaping documents
+//!
+//! [`eval`] runs an expression against a [`Value`] and returns every value it
+//! produces, in order. The language is a subset of jq:
+//!
+//! - Paths: `.`, `.name`, `."any key"`, `.["key"]`, `.[0]`, `.[-1]`,
+//!   `.[2:4]`, `.[]` for every item of an array or value of an object and
+//!   `..` for every value, recursively. A `?` after any expression drops its
+//!   errors, as in `.[]?`.
+//! - `a | b` runs `b` on each output of `a`, `a, b` outputs both.
+//! - Literals: numbers, `"strings with \(.interpolation)"`, `true`,
+//!   `false`, `null`, arrays `[.a, .b]` and objects `{id, name: .n, (.k): .v}`.
+//! - Operators: `+ - * / %`, `== != < <= > >=`, `and`, `or` and `a // b`,
+//!   which gives `b` when `a` has no outputs besides `false` and `null`.
+//! - `if A then B elif C then D else E end`, `EXPR as $name | BODY` and
+//!   `reduce EXPR as $name (INIT; UPDATE)`.
+//! - Functions: `empty`, `error(msg)`, `not`, `length`, `keys`,
+//!   `keys_unsorted`, `has(k)`, `contains(x)`, `type`, `select(f)`, `map(f)`,
+//!   `map_values(f)`, `to_entries`, `from_entries`, `with_entries(f)`, `add`,
+//!   `any`, `all`, `any(f)`, `all(f)`, `flatten`, `flatten(depth)`,
+//!   `range(n)`, `range(from; to)`, `floor`, `ceil`, `round`, `abs`, `sort`,
+//!   `sort_by(f)`, `group_by(f)`, `unique`, `unique_by(f)`, `min`, `max`,
+//!   `min_by(f)`, `max_by(f)`, `reverse`, `first`, `last`, `first(f)`,
+//!   `last(f)`, `limit(n; f)`, `recurse`, `tostring`, `tonumber`, `tojson`,
+//!   `fromjson`, `ascii_downcase`, `ascii_upcase`, `startswith(s)`,
+//!   `endswith(s)`, `ltrimstr(s)`, `rtrimstr(s)`, `split(s)`, `join(s)` and
+//!   the type filters `arrays`, `objects`, `strings`, `numbers`, `booleans`
+//!   and `nulls`.
+//!
+//! As in jq, only `false` and `null` are false; `0` and `""` are true. There
+//! are no user-defined functions, assignments, regular expressions or
+//! `@format` strings.
+//!
+//! ```
+//! use zparse::query::eval;
+//! # fn main() -> Result<(), zparse::Error> {
+//! let value = zparse::from_str(r#"{"users": [{"name": "ana", "age": 31}, {"name": "bo", "age": 17}]}"#)?;
+//! let adults = eval(r#".users[] | select(.age >= 18) | "\(.name) (\(.age))""#, &value)?;
+//! assert_eq!(adults, vec![zparse::Value::from("ana (31)")]);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::cmp::Ordering;
+
+use crate::convert::{Format, serialize};
+use crate::error::{Error, ErrorKind, Pos, Result, Span};
+use crate::value::{Array, Object, Value};
+
+/// Deepest nesting of brackets, parentheses and blocks in an expression
+const MAX_DEPTH: usize = 64;
+
+/// Most numbers one `range` call produces
+const MAX_RANGE: u32 = 1 << 20;
+
+/// Largest bound `range` accepts, 2^53: past it adding 1 no longer changes a
+/// number
+const MAX_RANGE_BOUND: f64 = 9_007_199_254_740_992.0;
+
+/// Run `expr` against `value` and collect its outputs, see the
+/// [module docs](self)
+pub fn eval(expr: &str, value: &Value) -> Result<Vec<Value>> {
+    Query::parse(expr)?.eval(value)
+}
+
+/// A parsed expression, reusable across documents
+#[derive(Clone, Debug)]
+pub struct Query {
+    root: Node,
+}
+
+impl Query {
+    /// Parse `source`, failing with the location of the first syntax error
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut parser = Parser {
+            source,
+            offset: 0,
+            depth: 0,
+        };
+        let root = parser.pipe()?;
+        parser.skip_space();
+        if !parser.rest().is_empty() {
+            return Err(parser.error("unexpected input after the expression"));
+        }
+        Ok(Self { root })
+    }
+
+    /// Outputs of the expression for `value`
+    pub fn eval(&self, value: &Value) -> Result<Vec<Value>> {
+        let mut out = Vec::new();
+        run(&self.root, value, &mut Vec::new(), &mut out)?;
+        Ok(out)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    expr: Expr,
+    span: Span,
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Identity,
+    Recurse,
+    Literal(Value),
+    Interpolate(Vec<Part>),
+    Field(Box<Node>, String),
+    Index(Box<Node>, Box<Node>),
+    Slice(Box<Node>, Option<Box<Node>>, Option<Box<Node>>),
+    Iterate(Box<Node>),
+    Try(Box<Node>),
+    Pipe(Box<Node>, Box<Node>),
+    Comma(Box<Node>, Box<Node>),
+    Negate(Box<Node>),
+    Binary(Op, Box<Node>, Box<Node>),
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    Alternative(Box<Node>, Box<Node>),
+    Array(Option<Box<Node>>),
+    Object(Vec<(Node, Node)>),
+    If(Vec<(Node, Node)>, Option<Box<Node>>),
+    Variable(String),
+    Bind {
+        source: Box<Node>,
+        name: String,
+        body: Box<Node>,
+    },
+    Reduce {
+        source: Box<Node>,
+        name: String,
+        init: Box<Node>,
+        update: Box<Node>,
+    },
+    Call(String, Vec<Node>),
+}
+
+/// A piece of an interpolated string
+#[derive(Clone, Debug)]
+enum Part {
+    Text(String),
+    Expr(Node),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+const KEYWORDS: &[&str] = &[
+    "as", "and", "or", "if", "then", "elif", "else", "end", "reduce",
+];
+
+/// Recursive descent over the source text, from the loosest binding
+/// operator (`|`) to the tightest (postfix paths)
+struct Parser<'s> {
+    source: &'s str,
+    offset: usize,
+    depth: usize,
+}
+
+impl<'s> Parser<'s> {
+    fn rest(&self) -> &'s str {
+        self.source.get(self.offset..).unwrap_or_default()
+    }
+
+    fn advance(&mut self, bytes: usize) {
+        self.offset = self.offset.saturating_add(bytes).min(self.source.len());
+    }
+
+    /// Skip whitespace and `#` comments
+    fn skip_space(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.advance(rest.len().saturating_sub(trimmed.len()));
+            if !trimmed.starts_with('#') {
+                return;
+            }
+            let line = trimmed.find('\n').unwrap_or(trimmed.len());
+            self.advance(line);
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_space();
+        self.rest().chars().next()
+    }
+
+    /// Consume `token` if it comes next and isn't the start of `longer`
+    fn eat_unless(&mut self, token: &str, longer: &[&str]) -> bool {
+        self.skip_space();
+        let rest = self.rest();
+        if rest.starts_with(token) && !longer.iter().any(|long| rest.starts_with(long)) {
+            self.advance(token.len());
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.eat_unless(token, &[])
+    }
+
+    fn expect(&mut self, token: &str) -> Result<()> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{token}`")))
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        self.skip_space();
+        let rest = self.rest();
+        let after = rest.get(keyword.len()..).unwrap_or_default();
+        if rest.starts_with(keyword) && !after.starts_with(is_identifier_char) {
+            self.advance(keyword.len());
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<()> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{keyword}`")))
+        }
+    }
+
+    /// An identifier starting right at the current offset
+    fn identifier(&mut self) -> Option<String> {
+        let rest = self.rest();
+        if !rest.starts_with(|ch: char| ch.is_ascii_alphabetic() || ch == '_') {
+            return None;
+        }
+        let len = rest
+            .find(|ch: char| !is_identifier_char(ch))
+            .unwrap_or(rest.len());
+        let name = rest.get(..len)?.to_string();
+        self.advance(len);
+        Some(name)
+    }
+
+    fn variable(&mut self) -> Result<String> {
+        self.expect("$")?;
+        self.identifier()
+            .ok_or_else(|| self.error("expected a variable name after `$`"))
+    }
+
+    fn node(&self, expr: Expr, start: usize) -> Node {
+        Node {
+            expr,
+            span: span(self.source, start, self.offset),
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> Error {
+        syntax(span(self.source, self.offset, self.offset), message)
+    }
+
+    fn start(&mut self) -> usize {
+        self.skip_space();
+        self.offset
+    }
+
+    fn pipe(&mut self) -> Result<Node> {
+        let start = self.start();
+        let left = self.comma()?;
+        if self.eat_keyword("as") {
+            let name = self.variable()?;
+            self.expect("|")?;
+            let body = self.pipe()?;
+            return Ok(self.node(
+                Expr::Bind {
+                    source: Box::new(left),
+                    name,
+                    body: Box::new(body),
+                },
+                start,
+            ));
+        }
+        if self.eat("|") {
+            let right = self.pipe()?;
+            return Ok(self.node(Expr::Pipe(Box::new(left), Box::new(right)), start));
+        }
+        Ok(left)
+    }
+
+    fn comma(&mut self) -> Result<Node> {
+        let start = self.start();
+        let mut left = self.alternative()?;
+        while self.eat(",") {
+            let right = self.alternative()?;
+            left = self.node(Expr::Comma(Box::new(left), Box::new(right)), start);
+        }
+        Ok(left)
+    }
+
+    fn alternative(&mut self) -> Result<Node> {
+        let start = self.start();
+        let left = self.or()?;
+        if self.eat("//") {
+            let right = self.alternative()?;
+            return Ok(self.node(Expr::Alternative(Box::new(left), Box::new(right)), start));
+        }
+        Ok(left)
+    }
+
+    fn or(&mut self) -> Result<Node> {
+        let start = self.start();
+        let mut left = self.and()?;
+        while self.eat_keyword("or") {
+            let right = self.and()?;
+            left = self.node(Expr::Or(Box::new(left), Box::new(right)), start);
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Node> {
+        let start = self.start();
+        let mut left = self.comparison()?;
+        while self.eat_keyword("and") {
+            let right = self.comparison()?;
+            left = self.node(Expr::And(Box::new(left), Box::new(right)), start);
+        }
+        Ok(left)
+    }
+
+    fn comparison(&mut self) -> Result<Node> {
+        let start = self.start();
+        let left = self.additive()?;
+        let op = [
+            ("==", Op::Eq),
+            ("!=", Op::Ne),
+            ("<=", Op::Le),
+            (">=", Op::Ge),
+            ("<", Op::Lt),
+            (">", Op::Gt),
+        ]
+        .into_iter()
+        .find(|(token, _)| self.eat(token));
+        match op {
+            Some((_, op)) => {
+                let right = self.additive()?;
+                Ok(self.node(Expr::Binary(op, Box::new(left), Box::new(right)), start))
+            }
+            None => Ok(left),
+        }
+    }
+
+    fn additive(&mut self) -> Result<Node> {
+        let start = self.start();
+        let mut left = self.multiplicative()?;
+        loop {
+            let op = if self.eat("+") {
+                Op::Add
+            } else if self.eat("-") {
+                Op::Sub
+            } else {
+                return Ok(left);
+            };
+            let right = self.multiplicative()?;
+            left = self.node(Expr::Binary(op, Box::new(left), Box::new(right)), start);
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Node> {
+        let start = self.start();
+        let mut left = self.unary()?;
+        loop {
+            let op = if self.eat("*") {
+                Op::Mul
+            } else if self.eat_unless("/", &["//"]) {
+                Op::Div
+            } else if self.eat("%") {
+                Op::Rem
+            } else {
+                return Ok(left);
+            };
+            let right = self.unary()?;
+            left = self.node(Expr::Binary(op, Box::new(left), Box::new(right)), start);
+        }
+    }
+
+    fn unary(&mut self) -> Result<Node> {
+        let start = self.start();
+        let mut negations = 0_usize;
+        while self.eat("-") {
+            negations = negations.saturating_add(1);
+        }
+        let mut node = self.postfix()?;
+        for _ in 0..negations {
+            node = self.node(Expr::Negate(Box::new(node)), start);
+        }
+        Ok(node)
+    }
+
+    fn postfix(&mut self) -> Result<Node> {
+        let start = self.start();
+        let mut node = self.primary()?;
+        loop {
+            self.skip_space();
+            let rest = self.rest();
+            let mut chars = rest.chars();
+            let (first, second) = (chars.next(), chars.next());
+            node = match (first, second) {
+                (Some('['), _) => {
+                    self.advance(1);
+                    self.bracket(node, start)?
+                }
+                (Some('.'), Some(next))
+                    if next == '"' || next == '[' || next == '_' || next.is_ascii_alphabetic() =>
+                {
+                    self.advance(1);
+                    self.field(node, start)?
+                }
+                (Some('?'), _) if !rest.starts_with("?//") => {
+                    self.advance(1);
+                    self.node(Expr::Try(Box::new(node)), start)
+                }
+                _ => return Ok(node),
+            };
+        }
+    }
+
+    /// The rest of `.name`, `."key"` or `.[...]` after the dot
+    fn field(&mut self, target: Node, start: usize) -> Result<Node> {
+        if let Some(name) = self.identifier() {
+            return Ok(self.node(Expr::Field(Box::new(target), name), start));
+        }
+        if self.rest().starts_with('[') {
+            self.advance(1);
+            return self.bracket(target, start);
+        }
+        let key = self.string()?;
+        Ok(match key.expr {
+            Expr::Literal(Value::String(name)) => {
+                self.node(Expr::Field(Box::new(target), name), start)
+            }
+            _ => self.node(Expr::Index(Box::new(target), Box::new(key)), start),
+        })
+    }
+
+    /// The rest of `[]`, `[index]` or `[from:to]` after the bracket
+    fn bracket(&mut self, target: Node, start: usize) -> Result<Node> {
+        let target = Box::new(target);
+        if self.eat("]") {
+            return Ok(self.node(Expr::Iterate(target), start));
+        }
+        if self.eat(":") {
+            let to = self.pipe()?;
+            self.expect("]")?;
+            return Ok(self.node(Expr::Slice(target, None, Some(Box::new(to))), start));
+        }
+        let index = self.pipe()?;
+        if self.eat(":") {
+            let to = if self.peek() == Some(']') {
+                None
+            } else {
+                Some(Box::new(self.pipe()?))
+            };
+            self.expect("]")?;
+            return Ok(self.node(Expr::Slice(target, Some(Box::new(index)), to), start));
+        }
+        self.expect("]")?;
+        Ok(self.node(Expr::Index(target, Box::new(index)), start))
+    }
+
+    fn primary(&mut self) -> Result<Node> {
+        self.depth = self.depth.saturating_add(1);
+        if self.depth > MAX_DEPTH {
+            return Err(Error::new(
+                ErrorKind::MaxDepthExceeded {
+                    max: u16::try_from(MAX_DEPTH).unwrap_or(u16::MAX),
+                },
+                span(self.source, self.offset, self.offset),
+            ));
+        }
+        let node = self.term();
+        self.depth = self.depth.saturating_sub(1);
+        node
+    }
+
+    fn term(&mut self) -> Result<Node> {
+        let start = self.start();
+        let rest = self.rest();
+        if rest.starts_with("..") {
+            self.advance(2);
+            return Ok(self.node(Expr::Recurse, start));
+        }
+        if rest.starts_with('.') {
+            self.advance(1);
+            let identity = self.node(Expr::Identity, start);
+            return match self.rest().chars().next() {
+                Some('"' | '[') => self.field(identity, start),
+                Some(ch) if ch == '_' || ch.is_ascii_alphabetic() => self.field(identity, start),
+                _ => Ok(identity),
+            };
+        }
+        match rest.chars().next() {
+            Some('$') => {
+                let name = self.variable()?;
+                Ok(self.node(Expr::Variable(name), start))
+            }
+            Some('"') => self.string(),
+            Some(ch) if ch.is_ascii_digit() => self.number(),
+            Some('(') => {
+                self.advance(1);
+                let inner = self.pipe()?;
+                self.expect(")")?;
+                Ok(inner)
+            }
+            Some('[') => {
+                self.advance(1);
+                if self.eat("]") {
+                    return Ok(self.node(Expr::Array(None), start));
+                }
+                let items = self.pipe()?;
+                self.expect("]")?;
+                Ok(self.node(Expr::Array(Some(Box::new(items))), start))
+            }
+            Some('{') => {
+                self.advance(1);
+                self.object(start)
+            }
+            Some(_) => match self.identifier() {
+                Some(word) => self.word(word, start),
+                None => Err(self.error("expected an expression")),
+            },
+            None => Err(self.error("unexpected end of expression")),
+        }
+    }
+
+    /// A keyword, literal or function call starting with `word`
+    fn word(&mut self, word: String, start: usize) -> Result<Node> {
+        match word.as_str() {
+            "true" => Ok(self.node(Expr::Literal(Value::Bool(true)), start)),
+            "false" => Ok(self.node(Expr::Literal(Value::Bool(false)), start)),
+            "null" => Ok(self.node(Expr::Literal(Value::Null), start)),
+            "if" => self.conditional(start),
+            "reduce" => {
+                let source = self.postfix()?;
+                self.expect_keyword("as")?;
+                let name = self.variable()?;
+                self.expect("(")?;
+                let init = self.pipe()?;
+                self.expect(";")?;
+                let update = self.pipe()?;
+                self.expect(")")?;
+                Ok(self.node(
+                    Expr::Reduce {
+                        source: Box::new(source),
+                        name,
+                        init: Box::new(init),
+                        update: Box::new(update),
+                    },
+                    start,
+                ))
+            }
+            keyword if KEYWORDS.contains(&keyword) => {
+                self.offset = start;
+                Err(self.error(format!("unexpected `{keyword}`")))
+            }
+            _ => {
+                let mut args = Vec::new();
+                if self.rest().starts_with('(') {
+                    self.advance(1);
+                    loop {
+                        args.push(self.pipe()?);
+                        if !self.eat(";") {
+                            break;
+                        }
+                    }
+                    self.expect(")")?;
+                }
+                Ok(self.node(Expr::Call(word, args), start))
+            }
+        }
+    }
+
+    /// The rest of `if ... end` after `if`
+    fn conditional(&mut self, start: usize) -> Result<Node> {
+        let mut branches = Vec::new();
+        loop {
+            let condition = self.pipe()?;
+            self.expect_keyword("then")?;
+            let then = self.pipe()?;
+            branches.push((condition, then));
+            if !self.eat_keyword("elif") {
+                break;
+            }
+        }
+        let otherwise = if self.eat_keyword("else") {
+            Some(Box::new(self.pipe()?))
+        } else {
+            None
+        };
+        self.expect_keyword("end")?;
+        Ok(self.node(Expr::If(branches, otherwise), start))
+    }
+
+    /// The rest of an object constructor after `{`
+    fn object(&mut self, start: usize) -> Result<Node> {
+        let mut entries = Vec::new();
+        if self.eat("}") {
+            return Ok(self.node(Expr::Object(entries), start));
+        }
+        loop {
+            let key_start = self.start();
+            let (key, shorthand) = match self.rest().chars().next() {
+                Some('$') => {
+                    let name = self.variable()?;
+                    let variable = self.node(Expr::Variable(name.clone()), key_start);
+                    (
+                        self.node(Expr::Literal(Value::String(name)), key_start),
+                        variable,
+                    )
+                }
+                Some('"') => {
+                    let key = self.string()?;
+                    let identity = self.node(Expr::Identity, key_start);
+                    let field = self.node(
+                        Expr::Index(Box::new(identity), Box::new(key.clone())),
+                        key_start,
+                    );
+                    (key, field)
+                }
+                Some('(') => {
+                    self.advance(1);
+                    let key = self.pipe()?;
+                    self.expect(")")?;
+                    self.skip_space();
+                    if !self.rest().starts_with(':') {
+                        return Err(self.error("expected `:` after a computed key"));
+                    }
+                    (key.clone(), key)
+                }
+                _ => {
+                    let name = self
+                        .identifier()
+                        .ok_or_else(|| self.error("expected an object key"))?;
+                    let identity = self.node(Expr::Identity, key_start);
+                    let field = self.node(Expr::Field(Box::new(identity), name.clone()), key_start);
+                    (
+                        self.node(Expr::Literal(Value::String(name)), key_start),
+                        field,
+                    )
+                }
+            };
+            let value = if self.eat(":") {
+                self.alternative()?
+            } else {
+                shorthand
+            };
+            entries.push((key, value));
+            if !self.eat(",") {
+                break;
+            }
+        }
+        self.expect("}")?;
+        Ok(self.node(Expr::Object(entries), start))
+    }
+
+    fn number(&mut self) -> Result<Node> {
+        let start = self.offset;
+        let rest = self.rest();
+        let mut len = rest
+            .find(|ch: char| !ch.is_ascii_digit() && ch != '.')
+            .unwrap_or(rest.len());
+        let after = rest.get(len..).unwrap_or_default();
+        if after.starts_with(['e', 'E']) {
+            let exponent = after.get(1..).unwrap_or_default();
+            let sign = usize::from(exponent.starts_with(['+', '-']));
+            let digits = exponent
+                .get(sign..)
+                .unwrap_or_default()
+                .find(|ch: char| !ch.is_ascii_digit())
+                .unwrap_or(exponent.len().saturating_sub(sign));
+            len = len
+                .saturating_add(1)
+                .saturating_add(sign)
+                .saturating_add(digits);
+        }
+        let text = rest.get(..len).unwrap_or_default();
+        let number = text
+            .parse::<f64>()
+            .map_err(|_| self.error(format!("invalid number {text:?}")))?;
+        self.advance(len);
+        Ok(self.node(Expr::Literal(Value::Number(number)), start))
+    }
+
+    /// A string literal, which may contain `\(...)` interpolations
+    fn string(&mut self) -> Result<Node> {
+        let start = self.start();
+        self.expect("\"")?;
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        loop {
+            let mut chars = self.rest().chars();
+            let ch = chars
+                .next()
+                .ok_or_else(|| self.error("unterminated string"))?;
+            self.advance(ch.len_utf8());
+            match ch {
+                '"' => break,
+                '\\' => {
+                    let escape = chars
+                        .next()
+                        .ok_or_else(|| self.error("unterminated string"))?;
+                    self.advance(escape.len_utf8());
+                    match escape {
+                        '(' => {
+                            if !text.is_empty() {
+                                parts.push(Part::Text(std::mem::take(&mut text)));
+                            }
+                            parts.push(Part::Expr(self.pipe()?));
+                            self.expect(")")?;
+                        }
+                        'u' => text.push(self.unicode_escape()?),
+                        _ => text.push(match escape {
+                            'n' => '\n',
+                            't' => '\t',
+                            'r' => '\r',
+                            'b' => '\u{8}',
+                            'f' => '\u{c}',
+                            '"' | '\\' | '/' => escape,
+                            _ => return Err(self.error(format!("invalid escape `\\{escape}`"))),
+                        }),
+                    }
+                }
+                _ => text.push(ch),
+            }
+        }
+        if parts.is_empty() {
+            return Ok(self.node(Expr::Literal(Value::String(text)), start));
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(self.node(Expr::Interpolate(parts), start))
+    }
+
+    /// The rest of a `\uXXXX` escape, joining surrogate pairs
+    fn unicode_escape(&mut self) -> Result<char> {
+        let high = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) && self.rest().starts_with("\\u") {
+            self.advance(2);
+            let low = self.hex4()?;
+            0x10000_u32
+                .saturating_add((high.saturating_sub(0xD800)) << 10)
+                .saturating_add(low.saturating_sub(0xDC00))
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| self.error("invalid \\u escape"))
+    }
+
+    fn hex4(&mut self) -> Result<u32> {
+        let digits = self
+            .rest()
+            .get(..4)
+            .filter(|digits| digits.chars().all(|ch| ch.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error("expected four hex digits after \\u"))?;
+        let code = u32::from_str_radix(digits, 16).map_err(|_| self.error("invalid \\u escape"))?;
+        self.advance(4);
+        Ok(code)
+    }
+}
+
+fn is_identifier_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '_'
+}
+
+/// Variables in scope, innermost last
+type Env = Vec<(String, Value)>;
+
+fn outputs(node: &Node, input: &Value, env: &mut Env) -> Result<Vec<Value>> {
+    let mut out = Vec::new();
+    run(node, input, env, &mut out)?;
+    Ok(out)
+}
+
+/// Push every output of `node` for `input` to `out`; on an error, the
+/// outputs before it stay in `out`
+fn run(node: &Node, input: &Value, env: &mut Env, out: &mut Vec<Value>) -> Result<()> {
+    let span = node.span;
+    match &node.expr {
+        Expr::Identity => out.push(input.clone()),
+        Expr::Recurse => descendants(input, out),
+        Expr::Literal(value) => out.push(value.clone()),
+        Expr::Interpolate(parts) => {
+            let mut texts = vec![String::new()];
+            for part in parts {
+                match part {
+                    Part::Text(text) => texts.iter_mut().for_each(|out| out.push_str(text)),
+                    Part::Expr(expr) => {
+                        let values = outputs(expr, input, env)?;
+                        texts = texts
+                            .iter()
+                            .flat_map(|prefix| {
+                                values
+                                    .iter()
+                                    .map(move |value| format!("{prefix}{}", text(value)))
+                            })
+                            .collect();
+                    }
+                }
+            }
+            out.extend(texts.into_iter().map(Value::String));
+        }
+        Expr::Field(target, name) => {
+            for value in outputs(target, input, env)? {
+                out.push(field(&value, name, span)?);
+            }
+        }
+        Expr::Index(target, index) => {
+            for value in outputs(target, input, env)? {
+                for index in outputs(index, input, env)? {
+                    out.push(self::index(&value, &index, span)?);
+                }
+            }
+        }
+        Expr::Slice(target, from, to) => {
+            let bound = |bound: &Option<Box<Node>>, env: &mut Env| match bound {
+                Some(node) => outputs(node, input, env),
+                None => Ok(vec![Value::Null]),
+            };
+            for value in outputs(target, input, env)? {
+                for to in bound(to, env)? {
+                    for from in bound(from, env)? {
+                        out.push(slice(&value, &from, &to, span)?);
+                    }
+                }
+            }
+        }
+        Expr::Iterate(target) => {
+            for value in outputs(target, input, env)? {
+                match value {
+                    Value::Array(items) => out.extend(items),
+                    Value::Object(entries) => out.extend(entries.into_iter().map(|(_, v)| v)),
+                    other => {
+                        return Err(mismatch(
+                            "array or object",
+                            &other,
+                            span,
+                            "cannot iterate over",
+                        ));
+                    }
+                }
+            }
+        }
+        Expr::Try(inner) => {
+            // Outputs before the error are kept, as in jq
+            let _ = run(inner, input, env, out);
+        }
+        Expr::Pipe(left, right) => {
+            for value in outputs(left, input, env)? {
+                run(right, &value, env, out)?;
+            }
+        }
+        Expr::Comma(left, right) => {
+            run(left, input, env, out)?;
+            run(right, input, env, out)?;
+        }
+        Expr::Negate(inner) => {
+            for value in outputs(inner, input, env)? {
+                match value {
+                    Value::Number(n) => out.push(Value::Number(-n)),
+                    other => return Err(mismatch("number", &other, span, "cannot negate")),
+                }
+            }
+        }
+        Expr::Binary(op, left, right) => {
+            for right in outputs(right, input, env)? {
+                for left in outputs(left, input, env)? {
+                    out.push(binary(*op, left, right.clone(), span)?);
+                }
+            }
+        }
+        Expr::And(left, right) | Expr::Or(left, right) => {
+            let short_circuit = matches!(node.expr, Expr::Or(..));
+            for left in outputs(left, input, env)? {
+                if truthy(&left) == short_circuit {
+                    out.push(Value::Bool(short_circuit));
+                    continue;
+                }
+                for right in outputs(right, input, env)? {
+                    out.push(Value::Bool(truthy(&right)));
+                }
+            }
+        }
+        Expr::Alternative(left, right) => {
+            let mut values = Vec::new();
+            let _ = run(left, input, env, &mut values);
+            let before = out.len();
+            out.extend(values.into_iter().filter(truthy));
+            if out.len() == before {
+                run(right, input, env, out)?;
+            }
+        }
+        Expr::Array(items) => {
+            let items = match items {
+                Some(items) => outputs(items, input, env)?,
+                None => Vec::new(),
+            };
+            out.push(Value::Array(items.into()));
+        }
+        Expr::Object(entries) => {
+            let mut objects = vec![Object::new()];
+            for (key, value) in entries {
+                let keys = outputs(key, input, env)?;
+                let values = outputs(value, input, env)?;
+                let mut next = Vec::new();
+                for object in &objects {
+                    for key in &keys {
+                        let Value::String(key) = key else {
+                            return Err(mismatch(
+                                "string",
+                                key,
+                                span,
+                                "object keys must be strings, found",
+                            ));
+                        };
+                        for value in &values {
+                            let mut object = object.clone();
+                            object.insert(key.clone(), value.clone());
+                            next.push(object);
+                        }
+                    }
+                }
+                objects = next;
+            }
+            out.extend(objects.into_iter().map(Value::Object));
+        }
+        Expr::If(branches, otherwise) => {
+            conditional(branches, otherwise.as_deref(), input, env, out)?
+        }
+        Expr::Variable(name) => {
+            let value = env
+                .iter()
+                .rev()
+                .find(|(bound, _)| bound == name)
+                .map(|(_, value)| value.clone())
+                .ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::UndefinedVariable {
+                            name: format!("${name}"),
+                        },
+                        span,
+                    )
+                })?;
+            out.push(value);
+        }
+        Expr::Bind { source, name, body } => {
+            for value in outputs(source, input, env)? {
+                env.push((name.clone(), value));
+                let result = run(body, input, env, out);
+                env.pop();
+                result?;
+            }
+        }
+        Expr::Reduce {
+            source,
+            name,
+            init,
+            update,
+        } => {
+            let items = outputs(source, input, env)?;
+            for mut acc in outputs(init, input, env)? {
+                for item in &items {
+                    env.push((name.clone(), item.clone()));
+                    let next = outputs(update, &acc, env);
+                    env.pop();
+                    acc = next?.pop().unwrap_or(Value::Null);
+                }
+                out.push(acc);
+            }
+        }
+        Expr::Call(name, args) => call(name, args, span, input, env, out)?,
+    }
+    Ok(())
+}
+
+fn conditional(
+    branches: &[(Node, Node)],
+    otherwise: Option<&Node>,
+    input: &Value,
+    env: &mut Env,
+    out: &mut Vec<Value>,
+) -> Result<()> {
+    let Some(((condition, then), rest)) = branches.split_first() else {
+        match otherwise {
+            Some(otherwise) => return run(otherwise, input, env, out),
+            None => {
+                out.push(input.clone());
+                return Ok(());
+            }
+        }
+    };
+    for value in outputs(condition, input, env)? {
+        if truthy(&value) {
+            run(then, input, env, out)?;
+        } else {
+            conditional(rest, otherwise, input, env, out)?;
+        }
+    }
+    Ok(())
+}
+
+/// `value` and everything inside it, parents first
+fn descendants(value: &Value, out: &mut Vec<Value>) {
+    out.push(value.clone());
+    match value {
+        Value::Array(items) => items.iter().for_each(|item| descendants(item, out)),
+        Value::Object(entries) => entries.values().for_each(|item| descendants(item, out)),
+        _ => {}
+    }
+}
+
+fn call(
+    name: &str,
+    args: &[Node],
+    span: Span,
+    input: &Value,
+    env: &mut Env,
+    out: &mut Vec<Value>,
+) -> Result<()> {
+    match (name, args) {
+        ("empty", []) => {}
+        ("error", [message]) => {
+            let message = outputs(message, input, env)?
+                .first()
+                .map(text)
+                .unwrap_or_else(|| "null".to_string());
+            return Err(Error::with_message(ErrorKind::InvalidToken, span, message));
+        }
+        ("not", []) => out.push(Value::Bool(!truthy(input))),
+        ("length", []) => out.push(length(input, span)?),
+        ("keys" | "keys_unsorted", []) => {
+            let keys = match input {
+                Value::Object(entries) => {
+                    let mut keys: Vec<&String> = entries.keys().collect();
+                    if name == "keys" {
+                        keys.sort();
+                    }
+                    keys.into_iter()
+                        .map(|key| Value::from(key.as_str()))
+                        .collect()
+                }
+                Value::Array(items) => (0..items.len()).map(number_from_usize).collect(),
+                other => {
+                    return Err(mismatch(
+                        "object or array",
+                        other,
+                        span,
+                        "cannot take keys of",
+                    ));
+                }
+            };
+            out.push(Value::Array(keys));
+        }
+        ("has", [key]) => {
+            for key in outputs(key, input, env)? {
+                let has = match (input, &key) {
+                    (Value::Object(entries), Value::String(key)) => entries.contains_key(key),
+                    (Value::Array(items), Value::Number(n)) => {
+                        *n >= 0.0
+                            && integer(*n)
+                                .is_some_and(|i| usize::try_from(i).is_ok_and(|i| i < items.len()))
+                    }
+                    _ => {
+                        return Err(mismatch(
+                            "object or array",
+                            input,
+                            span,
+                            &format!("cannot check whether the key {} is in", text(&key)),
+                        ));
+                    }
+                };
+                out.push(Value::Bool(has));
+            }
+        }
+        ("contains", [needle]) => {
+            for needle in outputs(needle, input, env)? {
+                out.push(Value::Bool(contains(input, &needle, span)?));
+            }
+        }
+        ("type", []) => out.push(Value::from(input.type_name())),
+        ("select", [filter]) => {
+            for value in outputs(filter, input, env)? {
+                if truthy(&value) {
+                    out.push(input.clone());
+                }
+            }
+        }
+        ("map", [filter]) => {
+            let mut mapped = Vec::new();
+            for item in items(input, span)? {
+                run(filter, item, env, &mut mapped)?;
+            }
+            out.push(Value::Array(mapped.into()));
+        }
+        ("map_values", [filter]) => out.push(match input {
+            Value::Array(items) => {
+                let mut mapped = Array::new();
+                for item in items {
+                    if let Some(value) = outputs(filter, item, env)?.into_iter().next() {
+                        mapped.push(value);
+                    }
+                }
+                Value::Array(mapped)
+            }
+            Value::Object(entries) => {
+                let mut mapped = Object::new();
+                for (key, item) in entries {
+                    if let Some(value) = outputs(filter, item, env)?.into_iter().next() {
+                        mapped.insert(key.clone(), value);
+                    }
+                }
+                Value::Object(mapped)
+            }
+            other => return Err(mismatch("array or object", other, span, "cannot map over")),
+        }),
+        ("to_entries", []) => out.push(to_entries(input, span)?),
+        ("from_entries", []) => out.push(from_entries(input, span)?),
+        ("with_entries", [filter]) => {
+            let Value::Array(entries) = to_entries(input, span)? else {
+                return Ok(());
+            };
+            let mut mapped = Vec::new();
+            for entry in &entries {
+                run(filter, entry, env, &mut mapped)?;
+            }
+            out.push(from_entries(&Value::Array(mapped.into()), span)?);
+        }
+        ("add", []) => {
+            let mut sum = Value::Null;
+            for item in items(input, span)? {
+                sum = binary(Op::Add, sum, item.clone(), span)?;
+            }
+            out.push(sum);
+        }
+        ("any" | "all", []) => {
+            let values = items(input, span)?;
+            out.push(Value::Bool(if name == "any" {
+                values.into_iter().any(truthy)
+            } else {
+                values.into_iter().all(truthy)
+            }));
+        }
+        ("any" | "all", [filter]) => {
+            let want = name == "any";
+            let mut found = !want;
+            'items: for item in items(input, span)? {
+                for value in outputs(filter, item, env)? {
+                    if truthy(&value) == want {
+                        found = want;
+                        break 'items;
+                    }
+                }
+            }
+            out.push(Value::Bool(found));
+        }
+        ("flatten", []) => out.push(flatten(input, usize::MAX, span)?),
+        ("flatten", [depth]) => {
+            for depth in outputs(depth, input, env)? {
+                let depth = match depth {
+                    Value::Number(n) if n >= 0.0 => integer(n)
+                        .and_then(|n| usize::try_from(n).ok())
+                        .unwrap_or(usize::MAX),
+                    other => {
+                        return Err(mismatch(
+                            "non-negative number",
+                            &other,
+                            span,
+                            "flatten depth must be a non-negative number, found",
+                        ));
+                    }
+                };
+                out.push(flatten(input, depth, span)?);
+            }
+        }
+        ("range", [to]) => {
+            for to in outputs(to, input, env)? {
+                range(0.0, number(&to, span)?, span, out)?;
+            }
+        }
+        ("range", [from, to]) => {
+            for from in outputs(from, input, env)? {
+                for to in outputs(to, input, env)? {
+                    range(number(&from, span)?, number(&to, span)?, span, out)?;
+                }
+            }
+        }
+        ("floor" | "ceil" | "round" | "abs", []) => {
+            let n = number(input, span)?;
+            out.push(Value::Number(match name {
+                "floor" => n.floor(),
+                "ceil" => n.ceil(),
+                "round" => n.round(),
+                _ => n.abs(),
+            }));
+        }
+        ("sort", []) => {
+            let mut sorted: Vec<Value> = items_array(input, span)?.iter().cloned().collect();
+            sorted.sort();
+            out.push(Value::Array(sorted.into()));
+        }
+        ("sort_by" | "group_by" | "unique_by" | "min_by" | "max_by", [key]) => {
+            let mut keyed = Vec::new();
+            for item in items_array(input, span)? {
+                keyed.push((Value::Array(outputs(key, item, env)?.into()), item.clone()));
+            }
+            keyed.sort_by(|a, b| a.0.cmp(&b.0));
+            out.push(by_key(name, keyed));
+        }
+        ("unique" | "min" | "max", []) => {
+            let mut keyed: Vec<(Value, Value)> = items_array(input, span)?
+                .iter()
+                .map(|item| (item.clone(), item.clone()))
+                .collect();
+            keyed.sort_by(|a, b| a.0.cmp(&b.0));
+            out.push(by_key(&format!("{name}_by"), keyed));
+        }
+        ("reverse", []) => out.push(match input {
+            Value::Null => Value::Array(Array::new()),
+            Value::String(text) => Value::String(text.chars().rev().collect()),
+            other => Value::Array(items_array(other, span)?.iter().rev().cloned().collect()),
+        }),
+        ("first", []) => out.push(self::index(input, &Value::Number(0.0), span)?),
+        ("last", []) => out.push(self::index(input, &Value::Number(-1.0), span)?),
+        ("first", [filter]) => {
+            if let Some(value) = outputs(filter, input, env)?.into_iter().next() {
+                out.push(value);
+            }
+        }
+        ("last", [filter]) => {
+            if let Some(value) = outputs(filter, input, env)?.pop() {
+                out.push(value);
+            }
+        }
+        ("limit", [count, filter]) => {
+            for count in outputs(count, input, env)? {
+                let count = integer(number(&count, span)?)
+                    .and_then(|n| usize::try_from(n).ok())
+                    .unwrap_or(0);
+                out.extend(outputs(filter, input, env)?.into_iter().take(count));
+            }
+        }
+        ("recurse", []) => descendants(input, out),
+        ("tostring", []) => out.push(Value::String(text(input))),
+        ("tojson", []) => out.push(Value::String(json(input))),
+        ("fromjson", []) => {
+            let source = string(input, span, "cannot parse")?;
+            let value = crate::from_str(source).map_err(|err| {
+                Error::with_message(err.kind().clone(), span, format!("fromjson: {err}"))
+            })?;
+            out.push(value);
+        }
+        ("tonumber", []) => out.push(match input {
+            Value::Number(_) => input.clone(),
+            Value::String(text) => text.trim().parse::<f64>().map(Value::Number).map_err(|_| {
+                Error::with_message(
+                    ErrorKind::Expected {
+                        expected: "number".to_string(),
+                        found: "string".to_string(),
+                    },
+                    span,
+                    format!("cannot parse {text:?} as a number"),
+                )
+            })?,
+            other => {
+                return Err(mismatch(
+                    "number or string",
+                    other,
+                    span,
+                    "cannot convert to a number:",
+                ));
+            }
+        }),
+        ("ascii_downcase", []) => out.push(Value::from(
+            string(input, span, "cannot lowercase")?.to_ascii_lowercase(),
+        )),
+        ("ascii_upcase", []) => out.push(Value::from(
+            string(input, span, "cannot uppercase")?.to_ascii_uppercase(),
+        )),
+        ("startswith" | "endswith" | "ltrimstr" | "rtrimstr" | "split" | "join", [arg]) => {
+            for arg in outputs(arg, input, env)? {
+                out.push(string_function(name, input, &arg, span)?);
+            }
+        }
+        ("arrays" | "objects" | "strings" | "numbers" | "booleans" | "nulls", []) => {
+            let kind = match name {
+                "arrays" => "array",
+                "objects" => "object",
+                "strings" => "string",
+                "numbers" => "number",
+                "booleans" => "boolean",
+                _ => "null",
+            };
+            if input.type_name() == kind {
+                out.push(input.clone());
+            }
+        }
+        _ => {
+            return Err(Error::with_message(
+                ErrorKind::UndefinedVariable {
+                    name: format!("{name}/{}", args.len()),
+                },
+                span,
+                format!("unknown function {name}/{}", args.len()),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Result of a `*_by` function over items sorted by their keys
+fn by_key(name: &str, keyed: Vec<(Value, Value)>) -> Value {
+    match name {
+        "sort_by" => Value::Array(keyed.into_iter().map(|(_, item)| item).collect()),
+        "min_by" => keyed
+            .into_iter()
+            .next()
+            .map_or(Value::Null, |(_, item)| item),
+        "max_by" => keyed
+            .into_iter()
+            .last()
+            .map_or(Value::Null, |(_, item)| item),
+        _ => {
+            let mut groups: Vec<(Value, Array)> = Vec::new();
+            for (key, item) in keyed {
+                match groups.last_mut() {
+                    Some((last, group)) if *last == key => group.push(item),
+                    _ => groups.push((key, Array::from(vec![item]))),
+                }
+            }
+            if name == "group_by" {
+                Value::Array(
+                    groups
+                        .into_iter()
+                        .map(|(_, group)| Value::Array(group))
+                        .collect(),
+                )
+            } else {
+                Value::Array(
+                    groups
+                        .into_iter()
+                        .filter_map(|(_, group)| group.into_iter().next())
+                        .collect(),
+                )
+            }
+        }
+    }
+}
+
+fn string_function(name: &str, input: &Value, arg: &Value, span: Span) -> Result<Value> {
+    if name == "join" {
+        let separator = string(arg, span, "cannot join with")?;
+        let mut joined = String::new();
+        for (i, item) in items_array(input, span)?.iter().enumerate() {
+            if i > 0 {
+                joined.push_str(separator);
+            }
+            match item {
+                Value::Null => {}
+                Value::String(text) => joined.push_str(text),
+                Value::Bool(_) | Value::Number(_) => joined.push_str(&json(item)),
+                other => {
+                    return Err(mismatch(
+                        "string, number, boolean or null",
+                        other,
+                        span,
+                        "cannot join",
+                    ));
+                }
+            }
+        }
+        return Ok(Value::String(joined));
+    }
+    let (Value::String(text), Value::String(arg)) = (input, arg) else {
+        return match name {
+            "ltrimstr" | "rtrimstr" => Ok(input.clone()),
+            _ => Err(mismatch(
+                "strings",
+                if input.is_string() { arg } else { input },
+                span,
+                &format!("{name} needs strings, found"),
+            )),
+        };
+    };
+    Ok(match name {
+        "startswith" => Value::Bool(text.starts_with(arg.as_str())),
+        "endswith" => Value::Bool(text.ends_with(arg.as_str())),
+        "ltrimstr" => Value::from(text.strip_prefix(arg.as_str()).unwrap_or(text)),
+        "rtrimstr" => Value::from(text.strip_suffix(arg.as_str()).unwrap_or(text)),
+        _ => split(text, arg),
+    })
+}
+
+fn split(text: &str, separator: &str) -> Value {
+    if text.is_empty() {
+        return Value::Array(Array::new());
+    }
+    if separator.is_empty() {
+        return Value::Array(
+            text.chars()
+                .map(|ch| Value::String(ch.to_string()))
+                .collect(),
+        );
+    }
+    Value::Array(text.split(separator).map(Value::from).collect())
+}
+
+fn to_entries(input: &Value, span: Span) -> Result<Value> {
+    let Value::Object(entries) = input else {
+        return Err(mismatch("object", input, span, "cannot make entries of"));
+    };
+    Ok(Value::Array(
+        entries
+            .iter()
+            .map(|(key, value)| {
+                let mut entry = Object::new();
+                entry.insert("key", key.as_str());
+                entry.insert("value", value.clone());
+                Value::Object(entry)
+            })
+            .collect(),
+    ))
+}
+
+fn from_entries(input: &Value, span: Span) -> Result<Value> {
+    let mut object = Object::new();
+    for entry in items_array(input, span)? {
+        let key = ["key", "k", "name", "Name", "Key", "K"]
+            .iter()
+            .find_map(|name| entry.get(*name).filter(|key| !key.is_null()));
+        let key = match key {
+            Some(Value::String(key)) => key.clone(),
+            Some(key @ (Value::Number(_) | Value::Bool(_))) => json(key),
+            Some(other) => {
+                return Err(mismatch(
+                    "string",
+                    other,
+                    span,
+                    "entry keys must be strings, found",
+                ));
+            }
+            None => {
+                return Err(mismatch(
+                    "object with a key",
+                    entry,
+                    span,
+                    "cannot use as an entry:",
+                ));
+            }
+        };
+        let value = ["value", "v", "Value", "V"]
+            .iter()
+            .find_map(|name| entry.get(*name))
+            .cloned()
+            .unwrap_or(Value::Null);
+        object.insert(key, value);
+    }
+    Ok(Value::Object(object))
+}
+
+fn flatten(input: &Value, depth: usize, span: Span) -> Result<Value> {
+    fn push(items: &Array, depth: usize, out: &mut Array) {
+        for item in items {
+            match item {
+                Value::Array(inner) if depth > 0 => push(inner, depth.saturating_sub(1), out),
+                other => out.push(other.clone()),
+            }
+        }
+    }
+    let mut flat = Array::new();
+    push(items_array(input, span)?, depth, &mut flat);
+    Ok(Value::Array(flat))
+}
+
+/// `from`, `from + 1`, ... up to but not including `to`. Both bounds must be
+/// finite and within ±2^53, and the count at most [`MAX_RANGE`].
+fn range(from: f64, to: f64, span: Span, out: &mut Vec<Value>) -> Result<()> {
+    for bound in [from, to] {
+        if !bound.is_finite() || bound.abs() > MAX_RANGE_BOUND {
+            return Err(Error::with_message(
+                ErrorKind::Expected {
+                    expected: "number within ±2^53".to_string(),
+                    found: bound.to_string(),
+                },
+                span,
+                format!("range bounds must be finite numbers within ±2^53, found {bound}"),
+            ));
+        }
+    }
+    if from >= to {
+        return Ok(());
+    }
+    let count = integer((to - from).ceil())
+        .and_then(|count| u32::try_from(count).ok())
+        .filter(|count| *count <= MAX_RANGE)
+        .ok_or_else(|| {
+            Error::with_message(
+                ErrorKind::MaxSizeExceeded {
+                    max: usize::try_from(MAX_RANGE).unwrap_or(usize::MAX),
+                },
+                span,
+                format!(
+                    "range({from}; {to}) has more than {MAX_RANGE} numbers, the most one range produces"
+                ),
+            )
+        })?;
+    out.extend((0..count).map(|step| Value::Number(from + f64::from(step))));
+    Ok(())
+}
+
+/// Whether `haystack` contains `needle` in jq's sense: substrings, array
+/// items contained in some item and object values contained key by key
+fn contains(haystack: &Value, needle: &Value, span: Span) -> Result<bool> {
+    Ok(match (haystack, needle) {
+        (Value::String(haystack), Value::String(needle)) => haystack.contains(needle.as_str()),
+        (Value::Array(haystack), Value::Array(needle)) => {
+            for wanted in needle {
+                let mut found = false;
+                for item in haystack {
+                    if contains(item, wanted, span)? {
+                        found = true;
+                        break;
+                    }
+                }
+                if !found {
+                    return Ok(false);
+                }
+            }
+            true
+        }
+        (Value::Object(haystack), Value::Object(needle)) => {
+            for (key, wanted) in needle {
+                match haystack.get(key) {
+                    Some(value) if contains(value, wanted, span)? => {}
+                    _ => return Ok(false),
+                }
+            }
+            true
+        }
+        (haystack, needle) if haystack.type_name() == needle.type_name() => haystack == needle,
+        (haystack, needle) => {
+            return Err(mismatch(
+                haystack.type_name(),
+                needle,
+                span,
+                &format!("cannot check whether a {} contains", haystack.type_name()),
+            ));
+        }
+    })
+}
+
+fn length(value: &Value, span: Span) -> Result<Value> {
+    Ok(match value {
+        Value::Null => Value::Number(0.0),
+        Value::Number(n) => Value::Number(n.abs()),
+        Value::String(text) => number_from_usize(text.chars().count()),
+        Value::Array(items) => number_from_usize(items.len()),
+        Value::Object(entries) => number_from_usize(entries.len()),
+        Value::Bytes(bytes) => number_from_usize(bytes.len()),
+        other => {
+            return Err(mismatch(
+                "value with a length",
+                other,
+                span,
+                "cannot take the length of",
+            ));
+        }
+    })
+}
+
+fn field(value: &Value, name: &str, span: Span) -> Result<Value> {
+    match value {
+        Value::Object(entries) => Ok(entries.get(name).cloned().unwrap_or(Value::Null)),
+        Value::Null => Ok(Value::Null),
+        other => Err(mismatch(
+            "object",
+            other,
+            span,
+            &format!("cannot index with {name:?} into"),
+        )),
+    }
+}
+
+fn index(value: &Value, index: &Value, span: Span) -> Result<Value> {
+    match (value, index) {
+        (_, Value::String(name)) => field(value, name, span),
+        (Value::Array(items), Value::Number(n)) => Ok(position(*n, items.len())
+            .and_then(|i| items.get(i))
+            .cloned()
+            .unwrap_or(Value::Null)),
+        (Value::Null, Value::Number(_) | Value::Null) => Ok(Value::Null),
+        (Value::Array(_), other) => Err(mismatch(
+            "number",
+            other,
+            span,
+            "cannot index an array with",
+        )),
+        (other, _) => Err(mismatch(
+            "array or object",
+            other,
+            span,
+            &format!("cannot index with {} into", json(index)),
+        )),
+    }
+}
+
+/// Array position of jq index `n`, counting back from `len` when negative
+fn position(n: f64, len: usize) -> Option<usize> {
+    let i = integer(n.floor())?;
+    let len = i64::try_from(len).ok()?;
+    let i = if i < 0 { len.checked_add(i)? } else { i };
+    usize::try_from(i).ok()
+}
+
+fn slice(value: &Value, from: &Value, to: &Value, span: Span) -> Result<Value> {
+    let bound = |bound: &Value, len: usize, default: usize| -> Result<usize> {
+        match bound {
+            Value::Null => Ok(default),
+            Value::Number(n) => {
+                let len_i = i64::try_from(len).unwrap_or(i64::MAX);
+                let i = integer(n.floor()).unwrap_or(if *n < 0.0 { i64::MIN } else { i64::MAX });
+                let i = if i < 0 { len_i.saturating_add(i) } else { i };
+                Ok(usize::try_from(i.clamp(0, len_i)).unwrap_or(0))
+            }
+            other => Err(mismatch("number", other, span, "cannot slice with")),
+        }
+    };
+    match value {
+        Value::Null => Ok(Value::Null),
+        Value::Array(items) => {
+            let start = bound(from, items.len(), 0)?;
+            let end = bound(to, items.len(), items.len())?.max(start);
+            Ok(Value::Array(
+                items
+                    .iter()
+                    .skip(start)
+                    .take(end.saturating_sub(start))
+                    .cloned()
+                    .collect(),
+            ))
+        }
+        Value::String(text) => {
+            let len = text.chars().count();
+            let start = bound(from, len, 0)?;
+            let end = bound(to, len, len)?.max(start);
+            Ok(Value::String(
+                text.chars()
+                    .skip(start)
+                    .take(end.saturating_sub(start))
+                    .collect(),
+            ))
+        }
+        other => Err(mismatch("array or string", other, span, "cannot slice")),
+    }
+}
+
+fn binary(op: Op, left: Value, right: Value, span: Span) -> Result<Value> {
+    let ordering = || left.cmp(&right);
+    Ok(match op {
+        Op::Eq => Value::Bool(left == right),
+        Op::Ne => Value::Bool(left != right),
+        Op::Lt => Value::Bool(ordering() == Ordering::Less),
+        Op::Le => Value::Bool(ordering() != Ordering::Greater),
+        Op::Gt => Value::Bool(ordering() == Ordering::Greater),
+        Op::Ge => Value::Bool(ordering() != Ordering::Less),
+        Op::Add => match (left, right) {
+            (Value::Null, value) | (value, Value::Null) => value,
+            (Value::Number(a), Value::Number(b)) => Value::Number(a + b),
+            (Value::String(a), Value::String(b)) => Value::String(a + &b),
+            (Value::Array(mut a), Value::Array(b)) => {
+                a.0.extend(b);
+                Value::Array(a)
+            }
+            (Value::Object(mut a), Value::Object(b)) => {
+                for (key, value) in b {
+                    a.insert(key, value);
+                }
+                Value::Object(a)
+            }
+            (a, b) => return Err(operands("add", &a, &b, span)),
+        },
+        Op::Sub => match (left, right) {
+            (Value::Number(a), Value::Number(b)) => Value::Number(a - b),
+            (Value::Array(a), Value::Array(b)) => {
+                Value::Array(a.into_iter().filter(|item| !b.0.contains(item)).collect())
+            }
+            (a, b) => return Err(operands("subtract", &a, &b, span)),
+        },
+        Op::Mul => match (left, right) {
+            (Value::Number(a), Value::Number(b)) => Value::Number(a * b),
+            (Value::String(text), Value::Number(n)) | (Value::Number(n), Value::String(text)) => {
+                match integer(n.ceil()).and_then(|n| usize::try_from(n).ok()) {
+                    Some(count) if count > 0 => {
+                        // `repeat` panics or aborts when the result can't be
+                        // allocated, so reserve it first
+                        let mut repeated = String::new();
+                        if text
+                            .len()
+                            .checked_mul(count)
+                            .is_none_or(|len| repeated.try_reserve_exact(len).is_err())
+                        {
+                            return Err(Error::with_message(
+                                ErrorKind::InvalidToken,
+                                span,
+                                format!("cannot repeat a string {count} times"),
+                            ));
+                        }
+                        if !text.is_empty() {
+                            repeated.extend(std::iter::repeat_n(text.as_str(), count));
+                        }
+                        Value::String(repeated)
+                    }
+                    _ => Value::Null,
+                }
+            }
+            (Value::Object(a), Value::Object(b)) => deep_merge(a, b),
+            (a, b) => return Err(operands("multiply", &a, &b, span)),
+        },
+        Op::Div => match (left, right) {
+            (Value::Number(_), Value::Number(0.0)) => {
+                return Err(Error::with_message(
+                    ErrorKind::InvalidToken,
+                    span,
+                    "cannot divide by zero",
+                ));
+            }
+            (Value::Number(a), Value::Number(b)) => Value::Number(a / b),
+            (Value::String(text), Value::String(separator)) => split(&text, &separator),
+            (a, b) => return Err(operands("divide", &a, &b, span)),
+        },
+        Op::Rem => match (left, right) {
+            (Value::Number(a), Value::Number(b)) => {
+                let (a, b) = (integer(a).unwrap_or(0), integer(b).unwrap_or(0));
+                let rem = a.checked_rem(b).ok_or_else(|| {
+                    Error::with_message(ErrorKind::InvalidToken, span, "cannot divide by zero")
+                })?;
+                Value::from(rem)
+            }
+            (a, b) => return Err(operands("take the remainder of", &a, &b, span)),
+        },
+    })
+}
+
+fn deep_merge(mut base: Object, other: Object) -> Value {
+    for (key, value) in other {
+        let merged = match (base.remove(&key), value) {
+            (Some(Value::Object(a)), Value::Object(b)) => deep_merge(a, b),
+            (_, value) => value,
+        };
+        base.insert(key, merged);
+    }
+    Value::Object(base)
+}
+
+fn truthy(value: &Value) -> bool {
+    !matches!(value, Value::Null | Value::Bool(false))
+}
+
+fn items(value: &Value, span: Span) -> Result<Vec<&Value>> {
+    match value {
+        Value::Array(items) => Ok(items.iter().collect()),
+        Value::Object(entries) => Ok(entries.values().collect()),
+        other => Err(mismatch(
+            "array or object",
+            other,
+            span,
+            "cannot iterate over",
+        )),
+    }
+}
+
+fn items_array(value: &Value, span: Span) -> Result<&Array> {
+    match value {
+        Value::Array(items) => Ok(items),
+        other => Err(mismatch("array", other, span, "expected an array, found")),
+    }
+}
+
+fn number(value: &Value, span: Span) -> Result<f64> {
+    match value {
+        Value::Number(n) => Ok(*n),
+        other => Err(mismatch("number", other, span, "expected a number, found")),
+    }
+}
+
+fn string<'v>(value: &'v Value, span: Span, doing: &str) -> Result<&'v str> {
+    match value {
+        Value::String(text) => Ok(text),
+        other => Err(mismatch("string", other, span, doing)),
+    }
+}
+
+fn number_from_usize(n: usize) -> Value {
+    Value::from(u64::try_from(n).unwrap_or(u64::MAX))
+}
+
+/// `n` truncated to an integer, None when it is NaN or out of range
+fn integer(n: f64) -> Option<i64> {
+    if !n.is_finite() || n.abs() >= 9.2e18 {
+        return None;
+    }
+    #[allow(clippy::as_conversions)]
+    // In range and finite, so the cast only drops the fraction
+    let i = n.trunc() as i64;
+    Some(i)
+}
+
+/// Text of `value` in a string: strings as they are, anything else as JSON
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Datetime(datetime) => datetime.to_string(),
+        other => json(other),
+    }
+}
+
+fn json(value: &Value) -> String {
+    serialize(value, Format::Json).unwrap_or_else(|_| format!("{value:?}"))
+}
+
+fn mismatch(expected: &str, found: &Value, span: Span, doing: &str) -> Error {
+    Error::with_message(
+        ErrorKind::Expected {
+            expected: expected.to_string(),
+            found: found.type_name().to_string(),
+        },
+        span,
+        format!("{doing} {} ({})", found.type_name(), preview(found)),
+    )
+}
+
+fn operands(doing: &str, left: &Value, right: &Value, span: Span) -> Error {
+    Error::with_message(
+        ErrorKind::Expected {
+            expected: format!("values that can {doing}"),
+            found: format!("{} and {}", left.type_name(), right.type_name()),
+        },
+        span,
+        format!(
+            "cannot {doing} {} ({}) and {} ({})",
+            left.type_name(),
+            preview(left),
+            right.type_name(),
+            preview(right)
+        ),
+    )
+}
+
+/// Compact JSON of `value`, cut short for error messages
+fn preview(value: &Value) -> String {
+    let json = json(value);
+    match json.char_indices().nth(40) {
+        Some((cut, _)) => format!("{}...", json.get(..cut).unwrap_or_default()),
+        None => json,
+    }
+}
+
+fn syntax(span: Span, message: impl Into<String>) -> Error {
+    Error::with_message(ErrorKind::InvalidToken, span, message)
+}
+
+/// Span of bytes `start..end` of `source`, with 1-based lines and columns
+fn span(source: &str, start: usize, end: usize) -> Span {
+    Span::new(pos(source, start), pos(source, end))
+}
+
+fn pos(source: &str, offset: usize) -> Pos {
+    let before = source.get(..offset).unwrap_or(source);
+    let line = before.matches('\n').count().saturating_add(1);
+    let column = before
+        .rsplit('\n')
+        .next()
+        .map_or(0, |line| line.chars().count())
+        .saturating_add(1);
+    Pos::new(
+        offset,
+        u32::try_from(line).unwrap_or(u32::MAX),
+        u32::try_from(column).unwrap_or(u32::MAX),
+    )
+}
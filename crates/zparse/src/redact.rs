@@ -0,0 +1,98 @@
+//! Secret redaction
+//!
+//! Replaces the values of keys that look like secrets so converted documents
+//! can be shared safely. Keys are matched case-insensitively by substring, so
+//! the default `token` pattern also covers `access_token` and `X-Auth-Token`.
+
+use crate::value::Value;
+
+/// Key patterns redacted by [`Redactor::default`]
+pub const DEFAULT_PATTERNS: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "api_key",
+    "apikey",
+    "private_key",
+    "credential",
+];
+
+/// Replacement used by [`Redactor::default`]
+pub const DEFAULT_REPLACEMENT: &str = "[REDACTED]";
+
+/// Redacts values whose keys match configured patterns
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Redactor {
+    patterns: Vec<String>,
+    replacement: String,
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::new(DEFAULT_PATTERNS.iter().copied())
+    }
+}
+
+impl Redactor {
+    /// Create a redactor matching only `patterns`
+    pub fn new<I, S>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self {
+            patterns: patterns
+                .into_iter()
+                .map(|pattern| pattern.as_ref().to_ascii_lowercase())
+                .filter(|pattern| !pattern.is_empty())
+                .collect(),
+            replacement: DEFAULT_REPLACEMENT.to_string(),
+        }
+    }
+
+    /// Add a key pattern
+    pub fn with_pattern(mut self, pattern: impl AsRef<str>) -> Self {
+        let pattern = pattern.as_ref().to_ascii_lowercase();
+        if !pattern.is_empty() && !self.patterns.contains(&pattern) {
+            self.patterns.push(pattern);
+        }
+        self
+    }
+
+    /// Set the string that replaces redacted values
+    pub fn with_replacement(mut self, replacement: impl Into<String>) -> Self {
+        self.replacement = replacement.into();
+        self
+    }
+
+    /// Returns true if values under `key` are redacted
+    pub fn matches(&self, key: &str) -> bool {
+        let key = key.to_ascii_lowercase();
+        self.patterns.iter().any(|pattern| key.contains(pattern))
+    }
+
+    /// Redact matching entries anywhere in `value`
+    pub fn redact(&self, value: &mut Value) {
+        match value {
+            Value::Object(entries) => {
+                for (key, item) in entries.iter_mut() {
+                    if self.matches(key) {
+                        *item = Value::String(self.replacement.clone());
+                    } else {
+                        self.redact(item);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact(item)),
+            _ => {}
+        }
+    }
+}
+
+impl Value {
+    /// Redact values whose keys match the default secret patterns
+    pub fn redact_secrets(&mut self) {
+        Redactor::default().redact(self);
+    }
+}
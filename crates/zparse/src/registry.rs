@@ -0,0 +1,324 @@
+//! Formats looked up by name, so other crates can add their own
+//!
+//! [`Format`] covers the formats built into zparse. A [`FormatProvider`]
+//! describes any other one (HCL, Java properties, ...) by how to parse it to
+//! a [`Value`], serialize it back and recognize it. Providers live in a
+//! [`Registry`]; [`Registry::default`] holds the built-in formats and
+//! [property lists](crate::plist), and
+//! [`register`] adds a provider to the process-wide registry that
+//! [`global`] returns, where name and path based helpers such as
+//! [`convert_named`] find it.
+//!
+//! ```
+//! use zparse::registry::{FormatProvider, Registry};
+//! use zparse::{ConvertOptions, FormatOptions, Object, Result, Value};
+//!
+//! /// `key=value` lines
+//! struct Properties;
+//!
+//! impl FormatProvider for Properties {
+//!     fn name(&self) -> &str {
+//!         "properties"
+//!     }
+//!
+//!     fn extensions(&self) -> &[&str] {
+//!         &["properties"]
+//!     }
+//!
+//!     fn parse(&self, input: &str, _: &ConvertOptions) -> Result<Value> {
+//!         let entries: Object = input
+//!             .lines()
+//!             .filter_map(|line| line.split_once('='))
+//!             .map(|(key, value)| (key.trim().to_string(), Value::from(value.trim())))
+//!             .collect();
+//!         Ok(Value::Object(entries))
+//!     }
+//! }
+//!
+//! # fn main() -> Result<()> {
+//! let mut registry = Registry::default();
+//! registry.register(Properties);
+//! let json = registry.convert("port=8080", "properties", "json", &ConvertOptions::default())?;
+//! assert_eq!(json, r#"{"port":"8080"}"#);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::path::Path;
+use std::sync::{Arc, OnceLock, PoisonError, RwLock};
+
+use crate::convert::{ConvertOptions, Format, FormatOptions};
+use crate::error::{Error, ErrorKind, Result, Span};
+use crate::input::{self, Utf8Mode};
+use crate::value::Value;
+
+/// A document format zparse can read and, optionally, write
+pub trait FormatProvider: Send + Sync {
+    /// Name the format is looked up by, e.g. `"hcl"` (case-insensitive)
+    fn name(&self) -> &str;
+
+    /// File extensions without the dot, e.g. `["hcl", "tf"]`
+    fn extensions(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Parse a document
+    fn parse(&self, input: &str, options: &ConvertOptions) -> Result<Value>;
+
+    /// Parse a document from raw bytes, for formats that need not be text.
+    /// The default decodes UTF-8 as [`ConvertOptions::utf8`] says, skips a
+    /// byte order mark and calls [`Self::parse`].
+    fn parse_bytes(&self, input: &[u8], options: &ConvertOptions) -> Result<Value> {
+        let text = input::decode(input, options.utf8)?;
+        let mut value = self.parse(text.strip_prefix('\u{feff}').unwrap_or(&text), options)?;
+        if options.utf8 == Utf8Mode::Bytes {
+            input::restore_bytes(&mut value);
+        }
+        Ok(value)
+    }
+
+    /// Write a value as a document. The default fails: the format is read-only.
+    fn serialize(&self, _value: &Value, _options: &FormatOptions) -> Result<String> {
+        Err(Error::with_message(
+            ErrorKind::UnsupportedValue {
+                path: String::new(),
+            },
+            Span::empty(),
+            format!("{} output is not supported", self.name()),
+        ))
+    }
+
+    /// Whether the format can be written; keep in step with [`Self::serialize`]
+    fn can_serialize(&self) -> bool {
+        false
+    }
+
+    /// Whether `input` looks like this format, for documents without a
+    /// telling extension. The default never claims one.
+    fn detect(&self, _input: &str) -> bool {
+        false
+    }
+
+    /// The [`Format`] this provider stands for, when it is one of zparse's
+    /// own; conversions between two of those go through
+    /// [`convert_with_options`](crate::convert_with_options)
+    fn builtin(&self) -> Option<Format> {
+        None
+    }
+}
+
+/// One of the formats built into zparse as a [`FormatProvider`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Builtin(pub Format);
+
+impl FormatProvider for Builtin {
+    fn name(&self) -> &str {
+        match self.0 {
+            Format::Json => "json",
+            Format::Csv => "csv",
+            Format::Toml => "toml",
+            Format::Yaml => "yaml",
+            Format::Xml => "xml",
+        }
+    }
+
+    fn extensions(&self) -> &[&str] {
+        match self.0 {
+            Format::Json => &["json", "jsonc"],
+            Format::Csv => &["csv"],
+            Format::Toml => &["toml"],
+            Format::Yaml => &["yaml", "yml"],
+            Format::Xml => &["xml"],
+        }
+    }
+
+    fn parse(&self, input: &str, options: &ConvertOptions) -> Result<Value> {
+        crate::convert::parse_with_options(input, self.0, options)
+    }
+
+    fn serialize(&self, value: &Value, options: &FormatOptions) -> Result<String> {
+        self.0.serializer().serialize(value, options)
+    }
+
+    fn can_serialize(&self) -> bool {
+        true
+    }
+
+    fn detect(&self, input: &str) -> bool {
+        crate::detect_format_from_content(input) == Some(self.0)
+    }
+
+    fn builtin(&self) -> Option<Format> {
+        Some(self.0)
+    }
+}
+
+/// A set of formats, searched newest first so a later provider can replace
+/// an earlier one with the same name or extension
+#[derive(Clone)]
+pub struct Registry {
+    providers: Vec<Arc<dyn FormatProvider>>,
+}
+
+impl Default for Registry {
+    /// The built-in formats
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        for format in [
+            Format::Json,
+            Format::Csv,
+            Format::Toml,
+            Format::Yaml,
+            Format::Xml,
+        ] {
+            if format.is_enabled() {
+                registry.register(Builtin(format));
+            }
+        }
+        #[cfg(feature = "xml")]
+        registry.register(crate::plist::Plist);
+        registry
+    }
+}
+
+impl std::fmt::Debug for Registry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+impl Registry {
+    /// A registry without any formats, not even the built-in ones
+    pub fn empty() -> Self {
+        Self {
+            providers: Vec::new(),
+        }
+    }
+
+    /// Add a format
+    pub fn register(&mut self, provider: impl FormatProvider + 'static) {
+        self.register_arc(Arc::new(provider));
+    }
+
+    /// Add a format already behind an `Arc`
+    pub fn register_arc(&mut self, provider: Arc<dyn FormatProvider>) {
+        self.providers.push(provider);
+    }
+
+    /// Every format, newest first
+    pub fn providers(&self) -> impl Iterator<Item = &Arc<dyn FormatProvider>> {
+        self.providers.iter().rev()
+    }
+
+    /// Names of the formats, without repeats, in registration order
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for provider in &self.providers {
+            if !names
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(provider.name()))
+            {
+                names.push(provider.name());
+            }
+        }
+        names
+    }
+
+    /// The format called `name`
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn FormatProvider>> {
+        self.providers()
+            .find(|provider| provider.name().eq_ignore_ascii_case(name))
+    }
+
+    /// The format for a path's extension
+    pub fn for_path(&self, path: impl AsRef<Path>) -> Option<&Arc<dyn FormatProvider>> {
+        let ext = path.as_ref().extension()?.to_str()?;
+        self.providers().find(|provider| {
+            provider
+                .extensions()
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(ext))
+        })
+    }
+
+    /// The first format, newest first, that claims `input`
+    pub fn detect(&self, input: &str) -> Option<&Arc<dyn FormatProvider>> {
+        self.providers().find(|provider| provider.detect(input))
+    }
+
+    /// Parse `input` as the format called `from`
+    pub fn parse(&self, input: &str, from: &str, options: &ConvertOptions) -> Result<Value> {
+        self.require(from)?.parse(input, options)
+    }
+
+    /// Parse raw `input` as the format called `from`, see
+    /// [`FormatProvider::parse_bytes`]
+    pub fn parse_bytes(&self, input: &[u8], from: &str, options: &ConvertOptions) -> Result<Value> {
+        self.require(from)?.parse_bytes(input, options)
+    }
+
+    /// Convert `input` between two formats by name, writing with
+    /// `options.format`
+    pub fn convert(
+        &self,
+        input: &str,
+        from: &str,
+        to: &str,
+        options: &ConvertOptions,
+    ) -> Result<String> {
+        let reader = self.require(from)?;
+        let writer = self.require(to)?;
+        if let (Some(from), Some(to)) = (reader.builtin(), writer.builtin()) {
+            return crate::convert::convert_with_options(input, from, to, options);
+        }
+        let value = reader.parse(input, options)?;
+        writer.serialize(&value, &options.format)
+    }
+
+    fn require(&self, name: &str) -> Result<&Arc<dyn FormatProvider>> {
+        self.get(name).ok_or_else(|| {
+            Error::with_message(
+                ErrorKind::UnknownFormat {
+                    path: name.to_string(),
+                },
+                Span::empty(),
+                format!(
+                    "unknown format {name:?} (known: {})",
+                    self.names().join(", ")
+                ),
+            )
+        })
+    }
+}
+
+fn global_lock() -> &'static RwLock<Registry> {
+    static GLOBAL: OnceLock<RwLock<Registry>> = OnceLock::new();
+    GLOBAL.get_or_init(|| RwLock::new(Registry::default()))
+}
+
+/// Add a format to the process-wide registry
+pub fn register(provider: impl FormatProvider + 'static) {
+    global_lock()
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .register(provider);
+}
+
+/// A copy of the process-wide registry: the built-in formats and everything
+/// passed to [`register`] so far
+pub fn global() -> Registry {
+    global_lock()
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// [`Registry::convert`] on the process-wide registry
+pub fn convert_named(
+    input: &str,
+    from: &str,
+    to: &str,
+    options: &ConvertOptions,
+) -> Result<String> {
+    global().convert(input, from, to, options)
+}
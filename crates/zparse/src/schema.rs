@@ -0,0 +1,193 @@
+//! JSON Schema inference from sample documents
+//!
+//! [`infer`] walks one or more samples and describes what it observed: the
+//! types at each position, which object keys are always present, and small
+//! sets of repeated string values as enums. The result is a plain [`Value`]
+//! holding a draft 2020-12 schema, ready to serialize to any format.
+
+use indexmap::IndexMap;
+
+use crate::value::{Array, Object, Value};
+
+/// `$schema` URI of the emitted draft
+pub const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Largest set of distinct strings reported as an enum
+pub const MAX_ENUM_VALUES: usize = 10;
+
+/// Infer a JSON Schema describing every sample
+pub fn infer(samples: &[Value]) -> Value {
+    let mut shape = Shape::default();
+    samples.iter().for_each(|sample| shape.observe(sample));
+
+    let mut schema = Object::new();
+    schema.insert("$schema", DRAFT);
+    if let Value::Object(body) = shape.to_schema() {
+        for (key, value) in body {
+            schema.insert(key, value);
+        }
+    }
+    Value::Object(schema)
+}
+
+/// Everything observed at one position in the documents
+#[derive(Debug, Default)]
+struct Shape {
+    null: bool,
+    boolean: bool,
+    integer: bool,
+    number: bool,
+    datetime: bool,
+    bytes: bool,
+    strings: Option<Strings>,
+    items: Option<Box<Self>>,
+    object: Option<ObjectShape>,
+}
+
+/// Observed string values, tracked until they stop looking like an enum
+#[derive(Debug, Default)]
+struct Strings {
+    seen: usize,
+    distinct: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default)]
+struct ObjectShape {
+    seen: usize,
+    properties: IndexMap<String, (Shape, usize)>,
+}
+
+impl Shape {
+    fn observe(&mut self, value: &Value) {
+        match value {
+            Value::Null => self.null = true,
+            Value::Bool(_) => self.boolean = true,
+            Value::Number(n) if n.is_finite() && n.fract() == 0.0 => self.integer = true,
+            Value::Number(_) => self.number = true,
+            Value::Datetime(_) => self.datetime = true,
+            Value::Bytes(_) => self.bytes = true,
+            Value::String(text) => {
+                let strings = self.strings.get_or_insert_with(|| Strings {
+                    seen: 0,
+                    distinct: Some(Vec::new()),
+                });
+                strings.seen = strings.seen.saturating_add(1);
+                if let Some(distinct) = &mut strings.distinct
+                    && !distinct.contains(text)
+                {
+                    if distinct.len() < MAX_ENUM_VALUES {
+                        distinct.push(text.clone());
+                    } else {
+                        strings.distinct = None;
+                    }
+                }
+            }
+            Value::Array(items) => {
+                let shape = self.items.get_or_insert_with(Box::default);
+                items.iter().for_each(|item| shape.observe(item));
+            }
+            Value::Object(entries) => {
+                let object = self.object.get_or_insert_with(ObjectShape::default);
+                object.seen = object.seen.saturating_add(1);
+                for (key, item) in entries {
+                    let (shape, count) = object.properties.entry(key.clone()).or_default();
+                    shape.observe(item);
+                    *count = count.saturating_add(1);
+                }
+            }
+        }
+    }
+
+    fn to_schema(&self) -> Value {
+        let mut types: Vec<&str> = Vec::new();
+        let mut schema = Object::new();
+
+        if self.null {
+            types.push("null");
+        }
+        if self.boolean {
+            types.push("boolean");
+        }
+        if self.number {
+            types.push("number");
+        } else if self.integer {
+            types.push("integer");
+        }
+        if self.strings.is_some() || self.datetime || self.bytes {
+            types.push("string");
+            if self.datetime && self.strings.is_none() && !self.bytes {
+                schema.insert("format", "date-time");
+            }
+            if self.bytes && self.strings.is_none() && !self.datetime {
+                schema.insert("contentEncoding", "base64");
+            }
+        }
+        if let Some(strings) = &self.strings
+            && let Some(distinct) = &strings.distinct
+            && self.only_strings_or_null()
+            && strings.seen > distinct.len()
+        {
+            let mut values: Array = distinct.iter().map(|s| Value::from(s.as_str())).collect();
+            if self.null {
+                values.push(Value::Null);
+            }
+            schema.insert("enum", Value::Array(values));
+        }
+        if let Some(items) = &self.items {
+            types.push("array");
+            if !items.is_empty() {
+                schema.insert("items", items.to_schema());
+            }
+        }
+        if let Some(object) = &self.object {
+            types.push("object");
+            let mut properties = Object::new();
+            let mut required = Array::new();
+            for (key, (shape, count)) in &object.properties {
+                properties.insert(key.clone(), shape.to_schema());
+                if *count == object.seen {
+                    required.push(key.as_str());
+                }
+            }
+            schema.insert("properties", Value::Object(properties));
+            if !required.is_empty() {
+                schema.insert("required", Value::Array(required));
+            }
+        }
+
+        let type_value = match types.as_slice() {
+            [] => None,
+            [single] => Some(Value::from(*single)),
+            many => Some(Value::Array(many.iter().map(|t| Value::from(*t)).collect())),
+        };
+        let mut ordered = Object::new();
+        if let Some(type_value) = type_value {
+            ordered.insert("type", type_value);
+        }
+        for (key, value) in schema {
+            ordered.insert(key, value);
+        }
+        Value::Object(ordered)
+    }
+
+    fn only_strings_or_null(&self) -> bool {
+        !self.boolean
+            && !self.integer
+            && !self.number
+            && !self.datetime
+            && !self.bytes
+            && self.items.is_none()
+            && self.object.is_none()
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.null
+            && !self.boolean
+            && !self.integer
+            && !self.number
+            && !self.datetime
+            && self.strings.is_none()
+            && self.items.is_none()
+            && self.object.is_none()
+    }
+}
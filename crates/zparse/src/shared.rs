@@ -0,0 +1,234 @@
+//! Shared, reference-counted documents
+//!
+//! [`ArcValue`] mirrors [`Value`] but stores strings, arrays and objects
+//! behind [`Arc`]. Converting through an [`Interner`] makes identical strings
+//! and keys share a single allocation, which keeps documents with heavy
+//! repetition (enum-like fields across many records) small.
+//!
+//! Clones are cheap and the type is `Send + Sync`, so a parsed config can be
+//! handed to many threads. Edits go through [`ArcValue::as_array_mut`] and
+//! [`ArcValue::as_object_mut`], which copy only the containers on the path
+//! being changed; untouched subtrees stay shared with other clones.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use indexmap::IndexMap;
+
+use crate::value::{TomlDatetime, Value};
+
+/// Pool of shared strings
+#[derive(Clone, Debug, Default)]
+pub struct Interner {
+    strings: HashSet<Arc<str>>,
+}
+
+impl Interner {
+    /// Create an empty interner
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the shared copy of `text`, adding it to the pool if needed
+    pub fn intern(&mut self, text: &str) -> Arc<str> {
+        if let Some(existing) = self.strings.get(text) {
+            return Arc::clone(existing);
+        }
+        let shared: Arc<str> = Arc::from(text);
+        self.strings.insert(Arc::clone(&shared));
+        shared
+    }
+
+    /// Number of distinct strings in the pool
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Returns true if no strings have been interned
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+/// A [`Value`] with reference-counted strings and containers
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum ArcValue {
+    /// Null value
+    #[default]
+    Null,
+    /// Boolean value
+    Bool(bool),
+    /// Numeric value (f64)
+    Number(f64),
+    /// Shared string value
+    String(Arc<str>),
+    /// Shared array of values
+    Array(Arc<Vec<Self>>),
+    /// Shared object with order preservation
+    Object(Arc<IndexMap<Arc<str>, Self>>),
+    /// TOML datetime value
+    Datetime(TomlDatetime),
+    /// Shared raw bytes
+    Bytes(Arc<[u8]>),
+}
+
+impl ArcValue {
+    /// Convert `value`, sharing strings and keys through `interner`
+    pub fn from_value(value: &Value, interner: &mut Interner) -> Self {
+        match value {
+            Value::Null => Self::Null,
+            Value::Bool(b) => Self::Bool(*b),
+            Value::Number(n) => Self::Number(*n),
+            Value::String(s) => Self::String(interner.intern(s)),
+            Value::Datetime(dt) => Self::Datetime(dt.clone()),
+            Value::Bytes(bytes) => Self::Bytes(Arc::from(bytes.as_slice())),
+            Value::Array(items) => Self::Array(Arc::new(
+                items
+                    .iter()
+                    .map(|item| Self::from_value(item, interner))
+                    .collect(),
+            )),
+            Value::Object(entries) => Self::Object(Arc::new(
+                entries
+                    .iter()
+                    .map(|(key, item)| (interner.intern(key), Self::from_value(item, interner)))
+                    .collect(),
+            )),
+        }
+    }
+}
+
+impl ArcValue {
+    /// Returns true if the value is null
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// Returns the boolean value if this is a boolean
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Returns the number if this is a number
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Self::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Returns the string if this is a string
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the datetime if this is a datetime
+    pub fn as_datetime(&self) -> Option<&TomlDatetime> {
+        match self {
+            Self::Datetime(dt) => Some(dt),
+            _ => None,
+        }
+    }
+
+    /// Returns the bytes if this is a byte string
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Self::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements if this is an array
+    pub fn as_array(&self) -> Option<&[Self]> {
+        match self {
+            Self::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries if this is an object
+    pub fn as_object(&self) -> Option<&IndexMap<Arc<str>, Self>> {
+        match self {
+            Self::Object(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// Look up `key` if this is an object
+    pub fn get(&self, key: &str) -> Option<&Self> {
+        self.as_object()?.get(key)
+    }
+
+    /// Mutable access to the elements, copying the array first if it is shared
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<Self>> {
+        match self {
+            Self::Array(items) => Some(Arc::make_mut(items)),
+            _ => None,
+        }
+    }
+
+    /// Mutable access to the entries, copying the object first if it is shared
+    pub fn as_object_mut(&mut self) -> Option<&mut IndexMap<Arc<str>, Self>> {
+        match self {
+            Self::Object(entries) => Some(Arc::make_mut(entries)),
+            _ => None,
+        }
+    }
+
+    /// Convert back into an owned [`Value`]
+    pub fn to_value(&self) -> Value {
+        match self {
+            Self::Null => Value::Null,
+            Self::Bool(b) => Value::Bool(*b),
+            Self::Number(n) => Value::Number(*n),
+            Self::String(s) => Value::String(s.to_string()),
+            Self::Datetime(dt) => Value::Datetime(dt.clone()),
+            Self::Bytes(bytes) => Value::Bytes(bytes.to_vec()),
+            Self::Array(items) => Value::Array(items.iter().map(Self::to_value).collect()),
+            Self::Object(entries) => Value::Object(
+                entries
+                    .iter()
+                    .map(|(key, item)| (key.to_string(), item.to_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<&Value> for ArcValue {
+    fn from(value: &Value) -> Self {
+        value.dedup_strings()
+    }
+}
+
+impl From<Value> for ArcValue {
+    fn from(value: Value) -> Self {
+        value.dedup_strings()
+    }
+}
+
+impl From<&ArcValue> for Value {
+    fn from(value: &ArcValue) -> Self {
+        value.to_value()
+    }
+}
+
+impl From<ArcValue> for Value {
+    fn from(value: ArcValue) -> Self {
+        value.to_value()
+    }
+}
+
+impl Value {
+    /// Convert into an [`ArcValue`] in which identical strings and keys share
+    /// one allocation.
+    pub fn dedup_strings(&self) -> ArcValue {
+        ArcValue::from_value(self, &mut Interner::new())
+    }
+}
@@ -0,0 +1,80 @@
+//! Stable ordering of keys and arrays
+//!
+//! Sorting makes generated documents diff-friendly regardless of input order.
+
+use std::cmp::Ordering;
+
+use crate::value::{Array, Value};
+
+impl Value {
+    /// Recursively sort object keys lexicographically
+    pub fn sort_keys(&mut self) {
+        match self {
+            Self::Object(entries) => {
+                entries.0.sort_keys();
+                entries.0.values_mut().for_each(Self::sort_keys);
+            }
+            Self::Array(items) => items.iter_mut().for_each(Self::sort_keys),
+            _ => {}
+        }
+    }
+
+    /// Recursively sort arrays of objects by the value of `field`.
+    ///
+    /// Arrays containing non-object elements are left as-is. Field values are
+    /// compared with [`Value`]'s [`Ord`] and objects missing `field` sort
+    /// after those that have it; the sort is stable.
+    pub fn sort_arrays_by(&mut self, field: &str) {
+        match self {
+            Self::Object(entries) => entries
+                .0
+                .values_mut()
+                .for_each(|value| value.sort_arrays_by(field)),
+            Self::Array(items) => {
+                items.iter_mut().for_each(|item| item.sort_arrays_by(field));
+                if items.iter().all(Self::is_object) {
+                    items.0.sort_by(|a, b| {
+                        present_first(
+                            a.as_object().and_then(|obj| obj.get(field)),
+                            b.as_object().and_then(|obj| obj.get(field)),
+                        )
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Array {
+    /// Sort elements by the value at the dot-separated `path` inside each
+    /// one, e.g. `name`, `meta.created` or `ports.0` (segments index arrays
+    /// too). Compared like [`Value::sort_arrays_by`]: with [`Value`]'s [`Ord`],
+    /// elements without the path last, stably.
+    pub fn sort_by_path(&mut self, path: &str) {
+        self.0
+            .sort_by(|a, b| present_first(value_at(a, path), value_at(b, path)));
+    }
+}
+
+/// The value at a dot-separated path, where segments are object keys or
+/// array indices
+fn value_at<'v>(value: &'v Value, path: &str) -> Option<&'v Value> {
+    path.split('.')
+        .filter(|seg| !seg.is_empty())
+        .try_fold(value, |current, seg| match current {
+            Value::Array(items) => seg.parse::<usize>().ok().and_then(|index| items.get(index)),
+            Value::Object(entries) => entries.get(seg),
+            _ => None,
+        })
+}
+
+/// Order present values with [`Value`]'s [`Ord`], ahead of missing ones
+fn present_first(a: Option<&Value>, b: Option<&Value>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
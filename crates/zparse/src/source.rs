@@ -0,0 +1,130 @@
+//! Where documents are read from, so other crates can add their own
+//!
+//! zparse reads local files itself. An [`InputSource`] handles locations
+//! with a URL scheme it claims (`s3://`, `gs://`, ...) by opening them as a
+//! reader, which keeps cloud SDKs out of zparse. [`register`] adds a source
+//! to the process-wide list that [`open`] and [`read`] search, and that
+//! [`crate::parse_path`], [`crate::ops`] and the CLI read through, so a
+//! registered scheme works wherever a path does. Locations no source claims
+//! are opened as files.
+//!
+//! ```
+//! use std::io::{Cursor, Read};
+//!
+//! use zparse::source::{self, InputSource};
+//! use zparse::{Result, Value};
+//!
+//! /// Serves `mem://<json>` from the location itself
+//! struct Memory;
+//!
+//! impl InputSource for Memory {
+//!     fn schemes(&self) -> &[&str] {
+//!         &["mem"]
+//!     }
+//!
+//!     fn open(&self, location: &str) -> Result<Box<dyn Read + Send>> {
+//!         let body = location.trim_start_matches("mem://").to_string();
+//!         Ok(Box::new(Cursor::new(body.into_bytes())))
+//!     }
+//! }
+//!
+//! # fn main() -> Result<()> {
+//! source::register(Memory);
+//! let bytes = source::read("mem://[1, 2]")?;
+//! assert_eq!(zparse::from_bytes(&bytes)?, Value::from(vec![Value::from(1), Value::from(2)]));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::sync::{Arc, OnceLock, PoisonError, RwLock};
+
+use crate::error::{Error, ErrorKind, Result, Span};
+
+/// A place documents can be read from, named by URL scheme
+pub trait InputSource: Send + Sync {
+    /// Schemes handled, without `://`, e.g. `["s3"]` (case-insensitive)
+    fn schemes(&self) -> &[&str];
+
+    /// Open `location`, the whole string including the scheme, for reading
+    fn open(&self, location: &str) -> Result<Box<dyn Read + Send>>;
+}
+
+fn sources() -> &'static RwLock<Vec<Arc<dyn InputSource>>> {
+    static SOURCES: OnceLock<RwLock<Vec<Arc<dyn InputSource>>>> = OnceLock::new();
+    SOURCES.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+/// Add a source to the process-wide list. A later source replaces an earlier
+/// one for the schemes they share.
+pub fn register(source: impl InputSource + 'static) {
+    sources()
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(Arc::new(source));
+}
+
+/// The scheme of `location`, as in `s3` for `s3://bucket/key`. Windows paths
+/// like `C:\data` have none.
+pub fn scheme(location: &str) -> Option<&str> {
+    let (scheme, _) = location.split_once("://")?;
+    let mut chars = scheme.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        && scheme.len() > 1;
+    valid.then_some(scheme)
+}
+
+/// The registered source for `location`'s scheme, if any
+pub fn find(location: impl AsRef<Path>) -> Option<Arc<dyn InputSource>> {
+    let location = location.as_ref().to_str()?;
+    let scheme = scheme(location)?;
+    sources()
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .rev()
+        .find(|source| {
+            source
+                .schemes()
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(scheme))
+        })
+        .cloned()
+}
+
+/// Open `location` with the source registered for its scheme, or as a file
+pub fn open(location: impl AsRef<Path>) -> Result<Box<dyn Read + Send>> {
+    let location = location.as_ref();
+    if let (Some(source), Some(text)) = (find(location), location.to_str()) {
+        return source.open(text);
+    }
+    let file = File::open(location).map_err(|err| crate::ops::io_error(location, &err))?;
+    Ok(Box::new(file))
+}
+
+/// Read all of `location`, see [`open`]
+pub fn read(location: impl AsRef<Path>) -> Result<Vec<u8>> {
+    let location = location.as_ref();
+    if find(location).is_none() {
+        return std::fs::read(location).map_err(|err| crate::ops::io_error(location, &err));
+    }
+    let mut bytes = Vec::new();
+    open(location)?
+        .read_to_end(&mut bytes)
+        .map_err(|err| crate::ops::io_error(location, &err))?;
+    Ok(bytes)
+}
+
+/// The error a source returns for a location it could not open
+pub fn error(location: &str, message: impl std::fmt::Display) -> Error {
+    Error::with_message(
+        ErrorKind::Io {
+            path: location.to_string(),
+        },
+        Span::empty(),
+        format!("{location}: {message}"),
+    )
+}
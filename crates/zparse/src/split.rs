@@ -0,0 +1,804 @@
+//! Element-by-element access to a large array inside a document
+//!
+//! [`for_each_element`] hands each item of the array at a path to a callback,
+//! e.g. to write it out as its own file. JSON is read through the streaming
+//! parser, so only one item is built in memory at a time; other formats are
+//! parsed whole first. [`for_each_json_element_from_reader`] reads JSON from
+//! an [`std::io::Read`] as it goes, for documents too large to load.
+//!
+//! [`pick`] keeps only the first, last or a random sample of those items, and
+//! [`pick_ndjson`] does the same for newline-delimited JSON. Taking the head
+//! of a JSON document stops reading after the last item wanted.
+//!
+//! Paths are dot-separated object keys and array indices, as in
+//! `data.exports.0.rows`; an empty path is the root.
+
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+use std::ops::ControlFlow;
+
+use crate::convert::{ConvertOptions, Format, parse_with_options};
+use crate::error::{Error, ErrorKind, Pos, Result, Span};
+use crate::json::{Config as JsonConfig, Event, Parser};
+use crate::rng::SplitMix64;
+use crate::value::{Array, Object, Value};
+
+/// Call `each` with every item of the array at `path` in `input`, in order,
+/// returning how many there were.
+///
+/// Fails when `path` doesn't lead to an array, when the document is invalid
+/// (JSON is still checked to the end after the array) or when `each` fails.
+pub fn for_each_element<F>(
+    input: &str,
+    format: Format,
+    options: &ConvertOptions,
+    path: &str,
+    mut each: F,
+) -> Result<usize>
+where
+    F: FnMut(Value) -> Result<()>,
+{
+    walk(input, format, options, path, |item| {
+        each(item).map(ControlFlow::Continue)
+    })
+}
+
+/// [`for_each_element`] for JSON, streaming `input` with the parser `config`
+pub fn for_each_json_element<F>(
+    input: &[u8],
+    config: JsonConfig,
+    path: &str,
+    mut each: F,
+) -> Result<usize>
+where
+    F: FnMut(Value) -> Result<()>,
+{
+    walk_json(input, config, path, |item| {
+        each(item).map(ControlFlow::Continue)
+    })
+}
+
+/// [`for_each_json_element`] reading JSON from `reader` as it goes, so only
+/// the item being handed over is in memory, never the whole document.
+///
+/// Items are parsed with `config`, whose limits apply to one item at a time.
+/// Values outside the array are read one at a time and checked with the same
+/// parser, so the document is still validated to the end.
+pub fn for_each_json_element_from_reader<R, F>(
+    reader: R,
+    config: JsonConfig,
+    path: &str,
+    mut each: F,
+) -> Result<usize>
+where
+    R: Read,
+    F: FnMut(Value) -> Result<()>,
+{
+    Scanner::new(reader, config).walk(path, |item| each(item).map(ControlFlow::Continue))
+}
+
+/// Which items of an array [`pick`] keeps
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pick {
+    /// The first n items
+    Head(usize),
+    /// The last n items
+    Tail(usize),
+    /// n items chosen uniformly at random, kept in document order. The same
+    /// seed picks the same items from the same input.
+    Sample {
+        /// How many items to keep
+        count: usize,
+        /// Seed for the random choice
+        seed: u64,
+    },
+}
+
+/// The items of the array at `path` in `input` selected by `pick`.
+///
+/// JSON is streamed, holding at most the picked items in memory, and
+/// [`Pick::Head`] stops reading once it has them, so the rest of the document
+/// is not checked.
+pub fn pick(
+    input: &str,
+    format: Format,
+    options: &ConvertOptions,
+    path: &str,
+    pick: Pick,
+) -> Result<Vec<Value>> {
+    let mut picker = Picker::new(pick);
+    if picker.is_done() {
+        return Ok(Vec::new());
+    }
+    walk(input, format, options, path, |item| Ok(picker.offer(item)))?;
+    Ok(picker.finish())
+}
+
+/// The values of newline-delimited JSON selected by `pick`, one per non-blank
+/// line. Only the lines picked are parsed, so a broken line elsewhere is not
+/// reported.
+pub fn pick_ndjson(input: &str, config: JsonConfig, pick: Pick) -> Result<Vec<Value>> {
+    let mut offset = 0usize;
+    let mut lines = Vec::new();
+    for (number, raw) in input.split_inclusive('\n').enumerate() {
+        let line = raw.trim_end_matches(['\n', '\r']);
+        if !line.trim().is_empty() {
+            lines.push((number, offset, line));
+        }
+        offset = offset.saturating_add(raw.len());
+    }
+    let picked = match pick {
+        Pick::Head(count) => {
+            lines.truncate(count);
+            lines
+        }
+        Pick::Tail(count) => lines.split_off(lines.len().saturating_sub(count)),
+        Pick::Sample { .. } => {
+            let mut picker = Picker::new(pick);
+            lines.into_iter().for_each(|line| {
+                let _ = picker.offer(line);
+            });
+            picker.finish()
+        }
+    };
+    picked
+        .into_iter()
+        .map(|(number, offset, line)| {
+            Parser::with_config(line.as_bytes(), config)
+                .parse_value()
+                .map_err(|err| {
+                    let line = u32::try_from(number.saturating_add(1)).unwrap_or(u32::MAX);
+                    let shift =
+                        |pos: Pos| Pos::new(pos.offset.saturating_add(offset), line, pos.col);
+                    err.map_span(|span| Span::new(shift(span.start), shift(span.end)))
+                })
+        })
+        .collect()
+}
+
+/// Collects the items [`Pick`] asks for as they are offered one at a time
+struct Picker<T> {
+    pick: Pick,
+    /// Items kept so far with their position, in document order except while
+    /// sampling
+    kept: VecDeque<(usize, T)>,
+    seen: usize,
+    rng: SplitMix64,
+}
+
+impl<T> Picker<T> {
+    fn new(pick: Pick) -> Self {
+        let seed = match pick {
+            Pick::Sample { seed, .. } => seed,
+            Pick::Head(_) | Pick::Tail(_) => 0,
+        };
+        Self {
+            pick,
+            kept: VecDeque::new(),
+            seen: 0,
+            rng: SplitMix64(seed),
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        matches!(self.pick, Pick::Head(count) if self.kept.len() >= count)
+    }
+
+    /// Offer the next item, breaking once no later item can be picked
+    fn offer(&mut self, item: T) -> ControlFlow<()> {
+        let index = self.seen;
+        self.seen = self.seen.saturating_add(1);
+        match self.pick {
+            Pick::Head(_) => self.kept.push_back((index, item)),
+            Pick::Tail(count) => {
+                if count > 0 {
+                    if self.kept.len() == count {
+                        self.kept.pop_front();
+                    }
+                    self.kept.push_back((index, item));
+                }
+            }
+            // Reservoir sampling: item i replaces a kept one with probability
+            // count / (i + 1)
+            Pick::Sample { count, .. } => {
+                if self.kept.len() < count {
+                    self.kept.push_back((index, item));
+                } else {
+                    let slot = self.rng.below(self.seen);
+                    if let Some(kept) = self.kept.get_mut(slot) {
+                        *kept = (index, item);
+                    }
+                }
+            }
+        }
+        if self.is_done() {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+
+    fn finish(self) -> Vec<T> {
+        let mut kept = Vec::from(self.kept);
+        kept.sort_by_key(|(index, _)| *index);
+        kept.into_iter().map(|(_, item)| item).collect()
+    }
+}
+
+/// Hand each item of the array at `path` to `each` until it breaks, returning
+/// how many were handed over
+fn walk<F>(
+    input: &str,
+    format: Format,
+    options: &ConvertOptions,
+    path: &str,
+    mut each: F,
+) -> Result<usize>
+where
+    F: FnMut(Value) -> Result<ControlFlow<()>>,
+{
+    if format == Format::Json {
+        return walk_json(input.as_bytes(), options.json, path, each);
+    }
+    let mut value = parse_with_options(input, format, options)?;
+    let segments = segments(path);
+    let mut target = &mut value;
+    for segment in &segments {
+        target = match target {
+            Value::Array(items) => segment
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| items.get_mut(index)),
+            Value::Object(object) => object.get_mut(segment),
+            _ => None,
+        }
+        .ok_or_else(|| missing(path))?;
+    }
+    let Value::Array(items) = target else {
+        return Err(not_array(path, target.type_name()));
+    };
+    let mut count = 0usize;
+    for item in std::mem::take(items) {
+        count = count.saturating_add(1);
+        if each(item)?.is_break() {
+            break;
+        }
+    }
+    Ok(count)
+}
+
+fn walk_json<F>(input: &[u8], config: JsonConfig, path: &str, mut each: F) -> Result<usize>
+where
+    F: FnMut(Value) -> Result<ControlFlow<()>>,
+{
+    let target = segments(path);
+    let mut parser = Parser::with_config(input, config);
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut count = None;
+    while let Some(event) = parser.next_event()? {
+        match event {
+            Event::Key(key) => {
+                if let Some(Frame::Object(current)) = frames.last_mut() {
+                    *current = Some(key);
+                }
+            }
+            Event::ObjectEnd | Event::ArrayEnd => {
+                frames.pop();
+                advance(&mut frames);
+            }
+            start if count.is_none() && at(&frames, &target) => {
+                if start != Event::ArrayStart {
+                    let found = match &start {
+                        Event::Value(value) => value.type_name(),
+                        _ => "object",
+                    };
+                    return Err(not_array(path, found));
+                }
+                let mut items = 0usize;
+                loop {
+                    let flow = match next(&mut parser)? {
+                        Event::ArrayEnd => break,
+                        first => each(build(&mut parser, first)?)?,
+                    };
+                    items = items.saturating_add(1);
+                    if flow.is_break() {
+                        return Ok(items);
+                    }
+                }
+                count = Some(items);
+                advance(&mut frames);
+            }
+            Event::ObjectStart => frames.push(Frame::Object(None)),
+            Event::ArrayStart => frames.push(Frame::Array(0)),
+            Event::Value(_) => advance(&mut frames),
+        }
+    }
+    count.ok_or_else(|| missing(path))
+}
+
+/// An open container while streaming, with the position of the child being
+/// read
+enum Frame {
+    /// Key of the current entry
+    Object(Option<String>),
+    /// Index of the current item
+    Array(usize),
+}
+
+/// Whether the value about to be read sits at `target`
+fn at(frames: &[Frame], target: &[&str]) -> bool {
+    frames.len() == target.len()
+        && frames
+            .iter()
+            .zip(target)
+            .all(|(frame, segment)| match frame {
+                Frame::Object(key) => key.as_deref() == Some(*segment),
+                Frame::Array(index) => segment.parse::<usize>().ok() == Some(*index),
+            })
+}
+
+/// Move past a finished child of the innermost container
+fn advance(frames: &mut [Frame]) {
+    match frames.last_mut() {
+        Some(Frame::Object(key)) => *key = None,
+        Some(Frame::Array(index)) => *index = index.saturating_add(1),
+        None => {}
+    }
+}
+
+/// A container [`build`] has started but not finished
+enum Open {
+    /// The entries so far and the key of the one being read
+    Object(Object, Option<String>),
+    Array(Array),
+}
+
+/// Build the value that starts with `first`, keeping open containers on an
+/// explicit stack so deep input cannot overflow the call stack
+fn build(parser: &mut Parser<'_>, first: Event) -> Result<Value> {
+    let mut stack = Vec::new();
+    let mut event = first;
+    loop {
+        let value = match event {
+            Event::Value(value) => value,
+            Event::ObjectStart => {
+                stack.push(Open::Object(Object::new(), None));
+                event = next(parser)?;
+                continue;
+            }
+            Event::ArrayStart => {
+                stack.push(Open::Array(Array::new()));
+                event = next(parser)?;
+                continue;
+            }
+            Event::Key(key) => {
+                let Some(Open::Object(_, pending @ None)) = stack.last_mut() else {
+                    return Err(invalid(parser));
+                };
+                *pending = Some(key);
+                event = next(parser)?;
+                continue;
+            }
+            Event::ObjectEnd => match stack.pop() {
+                Some(Open::Object(object, None)) => Value::Object(object),
+                _ => return Err(invalid(parser)),
+            },
+            Event::ArrayEnd => match stack.pop() {
+                Some(Open::Array(items)) => Value::Array(items),
+                _ => return Err(invalid(parser)),
+            },
+        };
+        match stack.last_mut() {
+            None => return Ok(value),
+            Some(Open::Array(items)) => items.push(value),
+            Some(Open::Object(object, key)) => {
+                let key = key.take().ok_or_else(|| invalid(parser))?;
+                object.insert(key, value);
+            }
+        }
+        event = next(parser)?;
+    }
+}
+
+/// Reads JSON from a reader one value at a time: the path to the array is
+/// followed byte by byte, and each value is copied out whole and handed to
+/// the parser
+struct Scanner<R> {
+    reader: BufReader<R>,
+    config: JsonConfig,
+    /// Position of the next byte
+    pos: Pos,
+    /// The value being read, kept to reuse its allocation
+    scratch: Vec<u8>,
+}
+
+impl<R: Read> Scanner<R> {
+    fn new(reader: R, config: JsonConfig) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            config,
+            pos: Pos::new(0, 1, 1),
+            scratch: Vec::new(),
+        }
+    }
+
+    /// See [`walk_json`]; `path` is followed without building the values
+    /// beside it, which are only checked
+    fn walk<F>(mut self, path: &str, mut each: F) -> Result<usize>
+    where
+        F: FnMut(Value) -> Result<ControlFlow<()>>,
+    {
+        if self.peek()? == Some(0xEF) {
+            for byte in "\u{feff}".bytes() {
+                self.expect(byte)?;
+            }
+        }
+        // Whether each container entered on the way to the array is an object
+        let mut open = Vec::new();
+        for segment in segments(path) {
+            self.skip_space()?;
+            match self.peek()? {
+                Some(b'{') => {
+                    self.bump()?;
+                    open.push(true);
+                    self.find_key(segment, path)?;
+                }
+                Some(b'[') => {
+                    self.bump()?;
+                    open.push(false);
+                    self.find_index(segment, path)?;
+                }
+                _ => return Err(missing(path)),
+            }
+        }
+
+        self.skip_space()?;
+        match self.peek()? {
+            Some(b'[') => self.bump().map(drop)?,
+            Some(byte) => return Err(not_array(path, type_name(byte))),
+            None => return Err(self.unexpected_end()),
+        }
+        let mut count = 0usize;
+        let mut first = true;
+        while self.next_entry(b']', &mut first)? {
+            let item = self.value(true)?;
+            count = count.saturating_add(1);
+            if each(item)?.is_break() {
+                return Ok(count);
+            }
+        }
+
+        while let Some(is_object) = open.pop() {
+            let closer = if is_object { b'}' } else { b']' };
+            let mut first = false;
+            while self.next_entry(closer, &mut first)? {
+                if is_object {
+                    self.key()?;
+                }
+                self.value(false)?;
+            }
+        }
+        self.skip_space()?;
+        match self.peek()? {
+            None => Ok(count),
+            Some(_) => Err(self.invalid()),
+        }
+    }
+
+    /// Move past the entries of an object up to the value of `key`
+    fn find_key(&mut self, key: &str, path: &str) -> Result<()> {
+        let mut first = true;
+        while self.next_entry(b'}', &mut first)? {
+            if self.key()? == key {
+                return Ok(());
+            }
+            self.value(false)?;
+        }
+        Err(missing(path))
+    }
+
+    /// Move past the items of an array up to the one at `index`
+    fn find_index(&mut self, index: &str, path: &str) -> Result<()> {
+        let index = index.parse::<usize>().map_err(|_| missing(path))?;
+        let mut first = true;
+        let mut current = 0usize;
+        while self.next_entry(b']', &mut first)? {
+            if current == index {
+                return Ok(());
+            }
+            self.value(false)?;
+            current = current.saturating_add(1);
+        }
+        Err(missing(path))
+    }
+
+    /// Whether another entry follows in a container closed by `closer`,
+    /// reading the comma before it or the closer
+    fn next_entry(&mut self, closer: u8, first: &mut bool) -> Result<bool> {
+        self.skip_space()?;
+        if std::mem::take(first) {
+            if self.peek()? == Some(closer) {
+                self.bump()?;
+                return Ok(false);
+            }
+            return Ok(true);
+        }
+        match self.bump()? {
+            Some(b',') => {
+                self.skip_space()?;
+                if self.peek()? != Some(closer) {
+                    return Ok(true);
+                }
+                if !self.config.allow_trailing_commas {
+                    return Err(Error::new(ErrorKind::TrailingComma, self.span()));
+                }
+                self.bump()?;
+                Ok(false)
+            }
+            Some(byte) if byte == closer => Ok(false),
+            Some(_) => Err(self.invalid()),
+            None => Err(self.unexpected_end()),
+        }
+    }
+
+    /// An object key and the colon after it
+    fn key(&mut self) -> Result<String> {
+        self.skip_space()?;
+        if self.peek()? != Some(b'"') {
+            return Err(self.invalid());
+        }
+        let Value::String(key) = self.value(true)? else {
+            return Err(self.invalid());
+        };
+        self.skip_space()?;
+        self.expect(b':')?;
+        Ok(key)
+    }
+
+    /// Read the next value, built when `build` is set and otherwise only
+    /// checked
+    fn value(&mut self, build: bool) -> Result<Value> {
+        self.skip_space()?;
+        let start = self.pos;
+        let mut bytes = std::mem::take(&mut self.scratch);
+        bytes.clear();
+        self.copy_value(&mut bytes)?;
+        let mut parser = Parser::with_config(&bytes, self.config);
+        let value = if build {
+            parser.parse_value()
+        } else {
+            parser.validate().map(|()| Value::Null)
+        };
+        self.scratch = bytes;
+        value.map_err(|err| {
+            err.map_span(|span| Span::new(moved(span.start, start), moved(span.end, start)))
+        })
+    }
+
+    /// Copy the bytes of the next value into `bytes`. Containers are copied
+    /// to their matching close bracket, skipping brackets inside strings and
+    /// comments; checking the rest is left to the parser.
+    fn copy_value(&mut self, bytes: &mut Vec<u8>) -> Result<()> {
+        let mut depth = 0usize;
+        loop {
+            let Some(byte) = self.peek()? else {
+                return Err(self.unexpected_end());
+            };
+            match byte {
+                b'"' => self.copy_string(bytes)?,
+                b'/' if depth > 0 && self.config.allow_comments => self.copy_comment(bytes)?,
+                b'{' | b'[' => {
+                    depth = depth.saturating_add(1);
+                    self.copy(bytes)?;
+                }
+                b'}' | b']' if depth > 0 => {
+                    depth = depth.saturating_sub(1);
+                    self.copy(bytes)?;
+                }
+                b' ' | b'\t' | b'\n' | b'\r' | b',' | b':' | b'}' | b']' | b'/' if depth == 0 => {
+                    if bytes.is_empty() {
+                        return Err(self.invalid());
+                    }
+                    return Ok(());
+                }
+                _ => self.copy(bytes)?,
+            }
+            if depth == 0 && matches!(bytes.last(), Some(b'"' | b'}' | b']')) {
+                return Ok(());
+            }
+        }
+    }
+
+    fn copy_string(&mut self, bytes: &mut Vec<u8>) -> Result<()> {
+        let start = self.pos;
+        self.copy(bytes)?;
+        loop {
+            match self.peek()? {
+                Some(b'"') => return self.copy(bytes),
+                Some(b'\\') => {
+                    self.copy(bytes)?;
+                    if self.peek()?.is_some() {
+                        self.copy(bytes)?;
+                    }
+                }
+                Some(_) => self.copy(bytes)?,
+                None => {
+                    return Err(Error::new(
+                        ErrorKind::UnterminatedString,
+                        Span::new(start, self.pos),
+                    ));
+                }
+            }
+        }
+    }
+
+    fn copy_comment(&mut self, bytes: &mut Vec<u8>) -> Result<()> {
+        self.copy(bytes)?;
+        match self.peek()? {
+            Some(b'/') => {
+                while self.peek()?.is_some_and(|byte| byte != b'\n') {
+                    self.copy(bytes)?;
+                }
+                Ok(())
+            }
+            Some(b'*') => {
+                self.copy(bytes)?;
+                while !bytes.ends_with(b"*/") {
+                    if self.peek()?.is_none() {
+                        return Err(self.unexpected_end());
+                    }
+                    self.copy(bytes)?;
+                }
+                Ok(())
+            }
+            _ => Err(self.invalid()),
+        }
+    }
+
+    /// Skip whitespace, and comments when the config allows them
+    fn skip_space(&mut self) -> Result<()> {
+        let mut comment = Vec::new();
+        loop {
+            match self.peek()? {
+                Some(b' ' | b'\t' | b'\n' | b'\r') => self.bump().map(drop)?,
+                Some(b'/') if self.config.allow_comments => {
+                    comment.clear();
+                    self.copy_comment(&mut comment)?;
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// Move the next byte into `bytes`, within the config's size limit
+    fn copy(&mut self, bytes: &mut Vec<u8>) -> Result<()> {
+        if let Some(byte) = self.bump()? {
+            bytes.push(byte);
+        }
+        let max = self.config.max_size;
+        if max > 0 && bytes.len() > max {
+            return Err(Error::new(ErrorKind::MaxSizeExceeded { max }, self.span()));
+        }
+        Ok(())
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<()> {
+        match self.bump()? {
+            Some(byte) if byte == expected => Ok(()),
+            Some(_) => Err(self.invalid()),
+            None => Err(self.unexpected_end()),
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>> {
+        let pos = self.pos;
+        let buffer = self.reader.fill_buf().map_err(|err| {
+            Error::with_message(
+                ErrorKind::Io {
+                    path: String::new(),
+                },
+                Span::new(pos, pos),
+                format!("failed to read input: {err}"),
+            )
+        })?;
+        Ok(buffer.first().copied())
+    }
+
+    fn bump(&mut self) -> Result<Option<u8>> {
+        let byte = self.peek()?;
+        if let Some(byte) = byte {
+            self.reader.consume(1);
+            self.pos.offset = self.pos.offset.saturating_add(1);
+            if byte == b'\n' {
+                self.pos.line = self.pos.line.saturating_add(1);
+                self.pos.col = 1;
+            } else if byte & 0xC0 != 0x80 {
+                self.pos.col = self.pos.col.saturating_add(1);
+            }
+        }
+        Ok(byte)
+    }
+
+    fn span(&self) -> Span {
+        Span::new(self.pos, self.pos)
+    }
+
+    fn invalid(&self) -> Error {
+        Error::new(ErrorKind::InvalidToken, self.span())
+    }
+
+    fn unexpected_end(&self) -> Error {
+        Error::with_message(
+            ErrorKind::InvalidToken,
+            self.span(),
+            "unexpected end of input",
+        )
+    }
+}
+
+/// `pos` within a value that starts at `start`, as a position in the whole
+/// input
+fn moved(pos: Pos, start: Pos) -> Pos {
+    let offset = start.offset.saturating_add(pos.offset);
+    if pos.line <= 1 {
+        Pos::new(
+            offset,
+            start.line,
+            start.col.saturating_add(pos.col).saturating_sub(1),
+        )
+    } else {
+        Pos::new(
+            offset,
+            start.line.saturating_add(pos.line).saturating_sub(1),
+            pos.col,
+        )
+    }
+}
+
+/// The type of the value that starts with `byte`, named as
+/// [`Value::type_name`] names it
+fn type_name(byte: u8) -> &'static str {
+    match byte {
+        b'{' => "object",
+        b'"' => "string",
+        b't' | b'f' => "boolean",
+        b'n' => "null",
+        _ => "number",
+    }
+}
+
+fn next(parser: &mut Parser<'_>) -> Result<Event> {
+    parser.next_event()?.ok_or_else(|| invalid(parser))
+}
+
+fn invalid(parser: &Parser<'_>) -> Error {
+    Error::new(ErrorKind::InvalidToken, parser.event_span())
+}
+
+fn segments(path: &str) -> Vec<&str> {
+    if path.is_empty() {
+        Vec::new()
+    } else {
+        path.split('.').collect()
+    }
+}
+
+fn missing(path: &str) -> Error {
+    Error::with_message(
+        ErrorKind::PathNotFound {
+            path: path.to_string(),
+        },
+        Span::empty(),
+        format!("no array at {path:?}"),
+    )
+}
+
+fn not_array(path: &str, found: &str) -> Error {
+    Error::new(
+        ErrorKind::Expected {
+            expected: format!("array at {path:?}"),
+            found: found.to_string(),
+        },
+        Span::empty(),
+    )
+}
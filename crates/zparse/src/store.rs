@@ -0,0 +1,178 @@
+//! Hot-reloadable configuration snapshots
+//!
+//! [`ConfigStore`] owns the latest parsed version of a file as an
+//! [`ArcValue`]. Readers grab an `Arc<Snapshot>` and keep using it for as long
+//! as they like; a reload builds a new snapshot and swaps it in atomically, so
+//! readers never observe a partially updated document. Change detection polls
+//! the file's modification time and length, either on demand with
+//! [`ConfigStore::reload`] or from a background thread started by
+//! [`ConfigStore::watch`].
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+use crate::convert::ConvertOptions;
+use crate::error::{Error, Result};
+use crate::ops::{io_error, parse_file};
+use crate::shared::ArcValue;
+
+/// One parsed version of the watched file
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    /// Starts at 1 and increases by one on every successful reload
+    pub version: u64,
+    /// Parsed document
+    pub value: ArcValue,
+}
+
+/// File state used to detect changes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Stamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+/// Latest parsed snapshot of a configuration file
+#[derive(Debug)]
+pub struct ConfigStore {
+    path: PathBuf,
+    options: ConvertOptions,
+    current: RwLock<Arc<Snapshot>>,
+    stamp: Mutex<Stamp>,
+    last_error: Mutex<Option<Error>>,
+}
+
+impl ConfigStore {
+    /// Load `path`, inferring the format from its extension
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_options(path, ConvertOptions::default())
+    }
+
+    /// Load `path` with custom parser options
+    pub fn with_options(path: impl AsRef<Path>, options: ConvertOptions) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let stamp = stamp(&path)?;
+        let value = load(&path, &options)?;
+        Ok(Self {
+            path,
+            options,
+            current: RwLock::new(Arc::new(Snapshot { version: 1, value })),
+            stamp: Mutex::new(stamp),
+            last_error: Mutex::new(None),
+        })
+    }
+
+    /// Path of the watched file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Current snapshot; cheap to call and safe to hold across reloads
+    pub fn snapshot(&self) -> Arc<Snapshot> {
+        Arc::clone(&self.current.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Reload if the file changed since the last load.
+    ///
+    /// Returns `Ok(true)` when a new snapshot was swapped in. On error the
+    /// current snapshot is kept and the error is also available from
+    /// [`ConfigStore::last_error`].
+    pub fn reload(&self) -> Result<bool> {
+        let result = self.reload_inner(false);
+        self.record(&result);
+        result
+    }
+
+    /// Reload unconditionally
+    pub fn force_reload(&self) -> Result<Arc<Snapshot>> {
+        let result = self.reload_inner(true);
+        self.record(&result);
+        result.map(|_| self.snapshot())
+    }
+
+    /// Error from the most recent failed reload, cleared by a successful one
+    pub fn last_error(&self) -> Option<Error> {
+        self.last_error
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Poll for changes every `interval` on a background thread.
+    ///
+    /// Polling stops when the returned [`Watcher`] is dropped.
+    pub fn watch(self: &Arc<Self>, interval: Duration) -> Watcher {
+        let store = Arc::clone(self);
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+        let handle = std::thread::spawn(move || {
+            while !thread_stop.load(Ordering::Acquire) {
+                std::thread::park_timeout(interval);
+                if thread_stop.load(Ordering::Acquire) {
+                    break;
+                }
+                let _ = store.reload();
+            }
+        });
+        Watcher {
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    fn reload_inner(&self, force: bool) -> Result<bool> {
+        let mut last_stamp = self.stamp.lock().unwrap_or_else(PoisonError::into_inner);
+        let stamp = stamp(&self.path)?;
+        if !force && stamp == *last_stamp {
+            return Ok(false);
+        }
+
+        let value = load(&self.path, &self.options)?;
+        *last_stamp = stamp;
+        let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+        let version = current.version.saturating_add(1);
+        *current = Arc::new(Snapshot { version, value });
+        Ok(true)
+    }
+
+    fn record<T>(&self, result: &Result<T>) {
+        let mut last_error = self
+            .last_error
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        *last_error = result.as_ref().err().cloned();
+    }
+}
+
+/// Background polling started by [`ConfigStore::watch`]
+#[derive(Debug)]
+pub struct Watcher {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            handle.thread().unpark();
+            let _ = handle.join();
+        }
+    }
+}
+
+fn stamp(path: &Path) -> Result<Stamp> {
+    let metadata = std::fs::metadata(path).map_err(|err| io_error(path, &err))?;
+    Ok(Stamp {
+        modified: metadata.modified().ok(),
+        len: metadata.len(),
+    })
+}
+
+fn load(path: &Path, options: &ConvertOptions) -> Result<ArcValue> {
+    let (_, value) = parse_file(path, options)?;
+    Ok(value.dedup_strings())
+}
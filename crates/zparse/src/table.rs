@@ -0,0 +1,87 @@
+//! Flattening an array of records into rows of selected columns
+//!
+//! Each [`Column`] names a dot-separated path into a record, like
+//! `address.city` or `tags.0`, and an optional header (`city=address.city`).
+//! [`extract`] turns the records into objects holding just those columns, in
+//! order, ready to serialize as CSV or any other format. A path a record
+//! doesn't have gives a null, which CSV writes as an empty cell.
+
+use crate::error::{Error, ErrorKind, Result, Span};
+use crate::value::{Array, Object, Value};
+
+/// A column of the table: where to read it from each record and its header
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Column {
+    /// Header of the column, the path itself unless renamed
+    pub header: String,
+    /// Object keys and array indices leading to the cell in a record
+    pub path: Vec<String>,
+}
+
+impl Column {
+    /// A column from `header=path` or a bare `path`
+    pub fn parse(spec: &str) -> Self {
+        let (header, path) = spec.split_once('=').unwrap_or((spec, spec));
+        Self {
+            header: header.trim().to_string(),
+            path: path
+                .trim()
+                .split('.')
+                .filter(|segment| !segment.is_empty())
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+
+    /// The cell of this column in `record`, None when the path is missing
+    pub fn get<'v>(&self, record: &'v Value) -> Option<&'v Value> {
+        self.path
+            .iter()
+            .try_fold(record, |value, segment| match value {
+                Value::Array(_) => segment.parse::<usize>().ok().and_then(|i| value.get(i)),
+                _ => value.get(segment.as_str()),
+            })
+    }
+}
+
+/// Columns from a comma-separated list such as `id,name,city=address.city`
+pub fn columns(spec: &str) -> Vec<Column> {
+    spec.split(',')
+        .filter(|column| !column.trim().is_empty())
+        .map(Column::parse)
+        .collect()
+}
+
+/// One object per record of `records` with a key for each of `columns`.
+///
+/// `records` is an array of records, or a single object taken as the only
+/// record. Missing cells are null.
+pub fn extract(records: &Value, columns: &[Column]) -> Result<Value> {
+    let rows: Vec<&Value> = match records {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(_) => vec![records],
+        other => {
+            return Err(Error::new(
+                ErrorKind::Expected {
+                    expected: "array of records".to_string(),
+                    found: other.type_name().to_string(),
+                },
+                Span::empty(),
+            ));
+        }
+    };
+    Ok(Value::Array(
+        rows.into_iter()
+            .map(|record| {
+                let row: Object = columns
+                    .iter()
+                    .map(|column| {
+                        let cell = column.get(record).cloned().unwrap_or(Value::Null);
+                        (column.header.clone(), cell)
+                    })
+                    .collect();
+                Value::Object(row)
+            })
+            .collect::<Array>(),
+    ))
+}
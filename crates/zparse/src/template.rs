@@ -0,0 +1,445 @@
+//! Text templates rendered from parsed documents
+//!
+//! A template is plain text with `{{ ... }}` tags:
+//!
+//! - `{{ path.to.value }}` prints a value. Segments are object keys or array
+//!   indices; strings print as they are and other values as compact JSON.
+//! - `{{ for item in path }}` ... `{{ end }}` repeats its body for each item
+//!   of an array, with `item` and `loop.index`, `loop.first` and `loop.last`
+//!   in scope.
+//! - `{{ if path }}` ... `{{ else }}` ... `{{ end }}` tests
+//!   [`Value::is_truthy`], a missing path being false; `{{ if not path }}`
+//!   negates the test.
+//! - `{{ "{{" }}` prints a quoted literal, for braces in the output.
+//!
+//! Loop variables shadow keys of the document. A line holding nothing but a
+//! `for`, `if`, `else` or `end` tag is left out of the output, so block tags
+//! can sit on lines of their own.
+//!
+//! ```
+//! use zparse::template::render;
+//! # fn main() -> Result<(), zparse::Error> {
+//! let values = zparse::from_yaml_str("upstreams:\n  - name: api\n    port: 8080\n")?;
+//! let template = "{{ for up in upstreams }}\nserver {{ up.name }}:{{ up.port }};\n{{ end }}\n";
+//! assert_eq!(render(template, &values)?, "server api:8080;\n");
+//! # Ok(())
+//! # }
+//! ```
+
+use std::borrow::Cow;
+
+use crate::convert::{Format, serialize};
+use crate::error::{Error, ErrorKind, Pos, Result, Span};
+use crate::value::Value;
+
+/// Render `template` with values from `value`, see the [module docs](self)
+pub fn render(template: &str, value: &Value) -> Result<String> {
+    Template::parse(template)?.render(value)
+}
+
+/// A parsed template, reusable across documents
+#[derive(Clone, Debug)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    /// Parse `source`, failing on malformed tags and unbalanced blocks
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut tags = lex(source)?.into_iter();
+        let (nodes, end) = block(&mut tags)?;
+        match end {
+            None => Ok(Self { nodes }),
+            Some((_, span)) => Err(syntax(span, "unexpected {{ end }} or {{ else }}")),
+        }
+    }
+
+    /// Render the template against `value`
+    pub fn render(&self, value: &Value) -> Result<String> {
+        let mut scope = Scope {
+            root: value,
+            loops: Vec::new(),
+        };
+        let mut out = String::new();
+        render_nodes(&self.nodes, &mut scope, &mut out)?;
+        Ok(out)
+    }
+}
+
+type Path = Vec<String>;
+
+#[derive(Clone, Debug)]
+enum Node {
+    Text(String),
+    Print {
+        expr: Expr,
+        span: Span,
+    },
+    For {
+        name: String,
+        path: Path,
+        body: Vec<Self>,
+        span: Span,
+    },
+    If {
+        negate: bool,
+        path: Path,
+        then: Vec<Self>,
+        otherwise: Vec<Self>,
+    },
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Literal(String),
+    Path(Path),
+}
+
+/// A piece of the source: text between tags or a tag
+enum Piece {
+    Text(String),
+    Print(Expr),
+    For { name: String, path: Path },
+    If { negate: bool, path: Path },
+    Else,
+    End,
+}
+
+impl Piece {
+    const fn is_block(&self) -> bool {
+        matches!(
+            self,
+            Self::For { .. } | Self::If { .. } | Self::Else | Self::End
+        )
+    }
+}
+
+/// Split `source` into text and tags, dropping the lines of standalone
+/// block tags
+fn lex(source: &str) -> Result<Vec<(Piece, Span)>> {
+    let mut pieces = Vec::new();
+    let mut cursor = 0;
+    while let Some(open) = find(source, cursor, "{{") {
+        let body_start = open.saturating_add(2);
+        let close = find_close(source, body_start)
+            .ok_or_else(|| syntax(span(source, open, source.len()), "unterminated {{ tag"))?;
+        let end = close.saturating_add(2);
+        let tag_span = span(source, open, end);
+        let body = source.get(body_start..close).unwrap_or_default();
+        let piece = tag(body, tag_span)?;
+
+        let line_start = source
+            .get(..open)
+            .and_then(|before| before.rfind('\n'))
+            .map_or(0, |newline| newline.saturating_add(1));
+        let line_end = find(source, end, "\n").unwrap_or(source.len());
+        let blank = |range: std::ops::Range<usize>| {
+            source.get(range).is_some_and(|text| text.trim().is_empty())
+        };
+        let standalone = piece.is_block()
+            && cursor <= line_start
+            && blank(line_start..open)
+            && blank(end..line_end);
+        let (text_end, next) = if standalone {
+            (line_start, line_end.saturating_add(1).min(source.len()))
+        } else {
+            (open, end)
+        };
+        if let Some(text) = source.get(cursor..text_end).filter(|text| !text.is_empty()) {
+            pieces.push((Piece::Text(text.to_string()), tag_span));
+        }
+        pieces.push((piece, tag_span));
+        cursor = next;
+    }
+    if let Some(text) = source.get(cursor..).filter(|text| !text.is_empty()) {
+        pieces.push((Piece::Text(text.to_string()), Span::empty()));
+    }
+    Ok(pieces)
+}
+
+/// Offset of the `}}` closing a tag whose body starts at `start`, skipping
+/// over a quoted literal
+fn find_close(source: &str, start: usize) -> Option<usize> {
+    let body = source.get(start..)?;
+    let trimmed = body.trim_start();
+    if let Some(literal) = trimmed.strip_prefix('"') {
+        let quote_end = literal.find('"')?;
+        let after = start
+            .saturating_add(body.len() - trimmed.len())
+            .saturating_add(quote_end)
+            .saturating_add(2);
+        return find(source, after, "}}");
+    }
+    find(source, start, "}}")
+}
+
+fn tag(body: &str, span: Span) -> Result<Piece> {
+    let body = body.trim();
+    if let Some(literal) = body.strip_prefix('"') {
+        return literal
+            .strip_suffix('"')
+            .filter(|literal| !literal.contains('"'))
+            .map(|literal| Piece::Print(Expr::Literal(literal.to_string())))
+            .ok_or_else(|| syntax(span, "malformed string literal"));
+    }
+    let words: Vec<&str> = body.split_whitespace().collect();
+    match words.as_slice() {
+        ["for", name, "in", path] => {
+            if !is_identifier(name) || *name == "loop" {
+                return Err(syntax(span, format!("invalid loop variable {name:?}")));
+            }
+            Ok(Piece::For {
+                name: (*name).to_string(),
+                path: parse_path(path, span)?,
+            })
+        }
+        ["for", ..] => Err(syntax(span, "expected {{ for NAME in PATH }}")),
+        ["if", "not", path] => Ok(Piece::If {
+            negate: true,
+            path: parse_path(path, span)?,
+        }),
+        ["if", path] => Ok(Piece::If {
+            negate: false,
+            path: parse_path(path, span)?,
+        }),
+        ["if", ..] => Err(syntax(span, "expected {{ if PATH }} or {{ if not PATH }}")),
+        ["else"] => Ok(Piece::Else),
+        ["end"] => Ok(Piece::End),
+        [path] => Ok(Piece::Print(Expr::Path(parse_path(path, span)?))),
+        _ => Err(syntax(span, format!("invalid tag {{{{ {body} }}}}"))),
+    }
+}
+
+fn parse_path(path: &str, span: Span) -> Result<Path> {
+    let segments: Path = path.split('.').map(str::to_string).collect();
+    if segments.iter().any(String::is_empty) {
+        return Err(syntax(span, format!("invalid path {path:?}")));
+    }
+    Ok(segments)
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|ch: char| ch.is_ascii_digit())
+        && name.chars().all(|ch| ch.is_alphanumeric() || ch == '_')
+}
+
+/// Nodes of a block and the `else` or `end` that ended it, if any
+type Block = (Vec<Node>, Option<(Piece, Span)>);
+
+/// Nodes up to the next `else` or `end`, which is returned with its span
+fn block(pieces: &mut impl Iterator<Item = (Piece, Span)>) -> Result<Block> {
+    let mut nodes = Vec::new();
+    while let Some((piece, span)) = pieces.next() {
+        match piece {
+            Piece::Text(text) => nodes.push(Node::Text(text)),
+            Piece::Print(expr) => nodes.push(Node::Print { expr, span }),
+            Piece::For { name, path } => {
+                let (body, end) = block(pieces)?;
+                if !matches!(end, Some((Piece::End, _))) {
+                    return Err(unclosed(end, span, "for"));
+                }
+                nodes.push(Node::For {
+                    name,
+                    path,
+                    body,
+                    span,
+                });
+            }
+            Piece::If { negate, path } => {
+                let (then, end) = block(pieces)?;
+                let (otherwise, end) = match end {
+                    Some((Piece::Else, _)) => block(pieces)?,
+                    end => (Vec::new(), end),
+                };
+                if !matches!(end, Some((Piece::End, _))) {
+                    return Err(unclosed(end, span, "if"));
+                }
+                nodes.push(Node::If {
+                    negate,
+                    path,
+                    then,
+                    otherwise,
+                });
+            }
+            Piece::Else | Piece::End => return Ok((nodes, Some((piece, span)))),
+        }
+    }
+    Ok((nodes, None))
+}
+
+/// Error for a block opened at `open` and closed by `end` instead of
+/// `{{ end }}`
+fn unclosed(end: Option<(Piece, Span)>, open: Span, keyword: &str) -> Error {
+    match end {
+        Some((_, span)) => syntax(
+            span,
+            format!("unexpected {{{{ else }}}} in {keyword} block"),
+        ),
+        None => syntax(open, format!("{keyword} block is missing {{{{ end }}}}")),
+    }
+}
+
+/// Values in scope while rendering
+struct Scope<'a> {
+    root: &'a Value,
+    /// Enclosing loops, innermost last
+    loops: Vec<Loop<'a>>,
+}
+
+struct Loop<'a> {
+    name: &'a str,
+    item: &'a Value,
+    index: usize,
+    len: usize,
+}
+
+impl Loop<'_> {
+    fn info(&self) -> Value {
+        let mut info = crate::value::Object::new();
+        info.insert("index", u64::try_from(self.index).unwrap_or(u64::MAX));
+        info.insert("first", self.index == 0);
+        info.insert("last", self.index.saturating_add(1) == self.len);
+        Value::Object(info)
+    }
+}
+
+impl<'a> Scope<'a> {
+    /// Value at `path`: the first segment names a loop variable, `loop` or
+    /// a key of the document
+    fn lookup(&self, path: &[String]) -> Option<Cow<'a, Value>> {
+        let (first, rest) = path.split_first()?;
+        if let Some(binding) = self
+            .loops
+            .iter()
+            .rev()
+            .find(|binding| binding.name == first)
+        {
+            return walk(binding.item, rest).map(Cow::Borrowed);
+        }
+        if first == "loop"
+            && let Some(current) = self.loops.last()
+        {
+            return walk(&current.info(), rest).cloned().map(Cow::Owned);
+        }
+        walk(self.root, path).map(Cow::Borrowed)
+    }
+}
+
+fn walk<'v>(value: &'v Value, path: &[String]) -> Option<&'v Value> {
+    path.iter().try_fold(value, |value, segment| match value {
+        Value::Array(_) => value.get(segment.parse::<usize>().ok()?),
+        _ => value.get(segment.as_str()),
+    })
+}
+
+fn render_nodes<'a>(nodes: &'a [Node], scope: &mut Scope<'a>, out: &mut String) -> Result<()> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Print {
+                expr: Expr::Literal(text),
+                ..
+            } => out.push_str(text),
+            Node::Print {
+                expr: Expr::Path(path),
+                span,
+            } => {
+                let value = scope.lookup(path).ok_or_else(|| undefined(path, *span))?;
+                out.push_str(&display(&value));
+            }
+            Node::For {
+                name,
+                path,
+                body,
+                span,
+            } => {
+                let items = match scope.lookup(path) {
+                    Some(Cow::Borrowed(Value::Array(items))) => items,
+                    Some(Cow::Borrowed(Value::Null)) => continue,
+                    Some(other) => {
+                        return Err(Error::new(
+                            ErrorKind::Expected {
+                                expected: format!("array at {}", path.join(".")),
+                                found: other.type_name().to_string(),
+                            },
+                            *span,
+                        ));
+                    }
+                    None => return Err(undefined(path, *span)),
+                };
+                for (index, item) in items.iter().enumerate() {
+                    scope.loops.push(Loop {
+                        name,
+                        item,
+                        index,
+                        len: items.len(),
+                    });
+                    let rendered = render_nodes(body, scope, out);
+                    scope.loops.pop();
+                    rendered?;
+                }
+            }
+            Node::If {
+                negate,
+                path,
+                then,
+                otherwise,
+            } => {
+                let truthy = scope.lookup(path).is_some_and(|value| value.is_truthy());
+                let branch = if truthy == *negate { otherwise } else { then };
+                render_nodes(branch, scope, out)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Text printed for `value`
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Datetime(datetime) => datetime.to_string(),
+        other => serialize(other, Format::Json).unwrap_or_else(|_| format!("{other:?}")),
+    }
+}
+
+fn undefined(path: &[String], span: Span) -> Error {
+    Error::new(
+        ErrorKind::UndefinedVariable {
+            name: path.join("."),
+        },
+        span,
+    )
+}
+
+fn syntax(span: Span, message: impl Into<String>) -> Error {
+    Error::with_message(ErrorKind::InvalidToken, span, message)
+}
+
+/// Span of bytes `start..end` of `source`, with 1-based lines and columns
+fn span(source: &str, start: usize, end: usize) -> Span {
+    Span::new(pos(source, start), pos(source, end))
+}
+
+fn pos(source: &str, offset: usize) -> Pos {
+    let before = source.get(..offset).unwrap_or(source);
+    let line = before.matches('\n').count().saturating_add(1);
+    let column = before
+        .rsplit('\n')
+        .next()
+        .map_or(0, |line| line.chars().count())
+        .saturating_add(1);
+    Pos::new(
+        offset,
+        u32::try_from(line).unwrap_or(u32::MAX),
+        u32::try_from(column).unwrap_or(u32::MAX),
+    )
+}
+
+fn find(source: &str, start: usize, pattern: &str) -> Option<usize> {
+    source
+        .get(start..)?
+        .find(pattern)
+        .map(|offset| start.saturating_add(offset))
+}
@@ -0,0 +1,513 @@
+//! Reproducible pseudo-random documents, for benchmarks and fuzz-style tests
+//!
+//! [`generate`] builds a [`Value`] from [`Options`]: a seed, an approximate
+//! size in bytes of compact JSON, the deepest nesting and a [`Mix`] of value
+//! kinds. The same options give the same document on every platform and
+//! every run. Strings now and then hold quotes, backslashes, newlines and
+//! non-ASCII text so that escaping gets exercised too.
+//!
+//! [`generate_for`] shapes the document so a format can hold it: no nulls in
+//! TOML, and an array of flat records with the same columns for CSV.
+//! [`document`] also serializes it.
+//!
+//! ```
+//! use zparse::Format;
+//! use zparse::testgen::{Options, document};
+//! # fn main() -> Result<(), zparse::Error> {
+//! let options = Options::new().with_seed(7).with_size(4096).with_max_depth(4);
+//! let yaml = document(Format::Yaml, &options)?;
+//! assert_eq!(yaml, document(Format::Yaml, &options)?);
+//! assert!(zparse::from_yaml_str(&yaml)?.depth() <= 4);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::convert::{Format, FormatOptions, serialize_with_options};
+use crate::error::{Error, ErrorKind, Result, Span};
+use crate::rng::SplitMix64;
+use crate::value::{Array, Object, Value};
+
+const WORDS: &[&str] = &[
+    "alpha", "bravo", "cache", "delta", "echo", "field", "gamma", "host", "index", "json", "key",
+    "limit", "mode", "node", "owner", "port", "query", "region", "state", "token", "user", "value",
+    "width", "zone", "id", "name", "count", "enabled", "items", "tags", "path", "retry",
+];
+
+/// Fragments mixed into some strings to exercise escaping and UTF-8
+const TRICKY: &[&str] = &[
+    "\"quoted\"",
+    "back\\slash",
+    "line\nbreak",
+    "tab\tstop",
+    "<tag & amp>",
+    "ünïcödé",
+    "日本語",
+    "emoji 🚀",
+    "# not a comment",
+    "key: value",
+];
+
+/// Relative weights of the kinds of values in a generated document; a kind
+/// with weight 0 never appears
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Mix {
+    pub null: u32,
+    pub bool: u32,
+    /// Whole numbers up to about ten billion, some negative
+    pub integer: u32,
+    /// Numbers with up to four decimals
+    pub float: u32,
+    /// One to four words
+    pub string: u32,
+    pub array: u32,
+    pub object: u32,
+}
+
+impl Default for Mix {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Mix {
+    /// Mostly strings and numbers, with some nesting
+    pub const fn new() -> Self {
+        Self {
+            null: 1,
+            bool: 2,
+            integer: 4,
+            float: 2,
+            string: 6,
+            array: 2,
+            object: 3,
+        }
+    }
+
+    /// Every weight 0, to build a mix up from
+    pub const fn none() -> Self {
+        Self {
+            null: 0,
+            bool: 0,
+            integer: 0,
+            float: 0,
+            string: 0,
+            array: 0,
+            object: 0,
+        }
+    }
+
+    const fn weights(&self) -> [(Kind, u32); 7] {
+        [
+            (Kind::Null, self.null),
+            (Kind::Bool, self.bool),
+            (Kind::Integer, self.integer),
+            (Kind::Float, self.float),
+            (Kind::String, self.string),
+            (Kind::Array, self.array),
+            (Kind::Object, self.object),
+        ]
+    }
+
+    fn weight_mut(&mut self, kind: &str) -> Option<&mut u32> {
+        Some(match kind {
+            "null" => &mut self.null,
+            "bool" => &mut self.bool,
+            "integer" => &mut self.integer,
+            "float" => &mut self.float,
+            "string" => &mut self.string,
+            "array" => &mut self.array,
+            "object" => &mut self.object,
+            _ => return None,
+        })
+    }
+}
+
+/// `string=6,integer=4,object=1`: the kinds listed get those weights and
+/// the others 0
+impl FromStr for Mix {
+    type Err = Error;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        let invalid =
+            |message: String| Error::with_message(ErrorKind::InvalidToken, Span::empty(), message);
+        let mut mix = Self::none();
+        for entry in spec
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (kind, weight) = entry
+                .split_once('=')
+                .ok_or_else(|| invalid(format!("expected KIND=WEIGHT, found {entry:?}")))?;
+            let weight = weight
+                .trim()
+                .parse()
+                .map_err(|_| invalid(format!("invalid weight in {entry:?}")))?;
+            *mix.weight_mut(kind.trim()).ok_or_else(|| {
+                invalid(format!(
+                    "unknown kind {:?} (known: null, bool, integer, float, string, array, object)",
+                    kind.trim()
+                ))
+            })? = weight;
+        }
+        if mix.null == 0 && mix.bool == 0 && mix.integer == 0 && mix.float == 0 && mix.string == 0 {
+            return Err(invalid(format!(
+                "mix {spec:?} needs a scalar kind (null, bool, integer, float or string)"
+            )));
+        }
+        Ok(mix)
+    }
+}
+
+impl fmt::Display for Mix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries: Vec<String> = self
+            .weights()
+            .iter()
+            .filter(|(_, weight)| *weight > 0)
+            .map(|(kind, weight)| format!("{}={weight}", kind.name()))
+            .collect();
+        f.write_str(&entries.join(","))
+    }
+}
+
+/// What to generate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Options {
+    /// The same seed gives the same document
+    pub seed: u64,
+    /// Approximate size of the document as compact JSON, in bytes
+    pub size: usize,
+    /// Deepest nesting of arrays and objects as [`Value::depth`] counts it;
+    /// the root object is 1
+    pub max_depth: usize,
+    /// Most items in one array or object below the root
+    pub max_width: usize,
+    /// Kinds of values and how often each appears
+    pub mix: Mix,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Options {
+    /// About 64 KiB, nested at most 6 deep, seed 0
+    pub const fn new() -> Self {
+        Self {
+            seed: 0,
+            size: 64 * 1024,
+            max_depth: 6,
+            max_width: 8,
+            mix: Mix::new(),
+        }
+    }
+
+    /// Set the seed
+    pub const fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Set the approximate size in bytes
+    pub const fn with_size(mut self, size: usize) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Set the deepest nesting
+    pub const fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Set the most items per array or object
+    pub const fn with_max_width(mut self, width: usize) -> Self {
+        self.max_width = width;
+        self
+    }
+
+    /// Set the mix of value kinds
+    pub const fn with_mix(mut self, mix: Mix) -> Self {
+        self.mix = mix;
+        self
+    }
+}
+
+/// A document for `options`: an object whose entries are generated until
+/// the size is reached
+pub fn generate(options: &Options) -> Value {
+    let mut generator = Generator::new(options, options.mix);
+    generator.root()
+}
+
+/// A document for `options` that `format` can hold: TOML leaves out nulls
+/// and CSV gets an array of records sharing one set of scalar columns
+pub fn generate_for(format: Format, options: &Options) -> Value {
+    match format {
+        Format::Csv => {
+            let mix = Mix {
+                array: 0,
+                object: 0,
+                ..options.mix
+            };
+            Generator::new(options, mix).records()
+        }
+        Format::Toml => Generator::new(
+            options,
+            Mix {
+                null: 0,
+                ..options.mix
+            },
+        )
+        .root(),
+        Format::Json | Format::Yaml | Format::Xml => generate(options),
+    }
+}
+
+/// [`generate_for`] serialized as `format`
+pub fn document(format: Format, options: &Options) -> Result<String> {
+    serialize_with_options(
+        &generate_for(format, options),
+        format,
+        &FormatOptions::new(),
+    )
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    Null,
+    Bool,
+    Integer,
+    Float,
+    String,
+    Array,
+    Object,
+}
+
+impl Kind {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Null => "null",
+            Self::Bool => "bool",
+            Self::Integer => "integer",
+            Self::Float => "float",
+            Self::String => "string",
+            Self::Array => "array",
+            Self::Object => "object",
+        }
+    }
+
+    const fn is_container(self) -> bool {
+        matches!(self, Self::Array | Self::Object)
+    }
+}
+
+struct Generator<'o> {
+    options: &'o Options,
+    mix: Mix,
+    rng: SplitMix64,
+    /// Bytes of compact JSON still to generate
+    budget: usize,
+}
+
+impl<'o> Generator<'o> {
+    fn new(options: &'o Options, mix: Mix) -> Self {
+        Self {
+            options,
+            mix,
+            rng: SplitMix64(options.seed),
+            budget: options.size,
+        }
+    }
+
+    fn spend(&mut self, bytes: usize) {
+        self.budget = self.budget.saturating_sub(bytes);
+    }
+
+    fn root(&mut self) -> Value {
+        let mut root = Object::new();
+        self.spend(2);
+        while self.budget > 0 {
+            let key = self.key(&root);
+            self.spend(key.len().saturating_add(4));
+            let value = self.value(1);
+            root.insert(key, value);
+        }
+        Value::Object(root)
+    }
+
+    /// Rows with the same keys holding scalars, for CSV
+    fn records(&mut self) -> Value {
+        let mut columns = Object::new();
+        let width = self
+            .rng
+            .below(self.options.max_width.max(1))
+            .saturating_add(1);
+        let mut kinds = Vec::with_capacity(width);
+        for _ in 0..width {
+            let key = self.key(&columns);
+            columns.insert(key, Value::Null);
+            kinds.push(self.kind(0));
+        }
+        let mut rows = Array::new();
+        self.spend(2);
+        while self.budget > 0 {
+            let mut row = Object::new();
+            self.spend(3);
+            for (key, kind) in columns.keys().zip(&kinds) {
+                let cell = self.scalar(*kind);
+                self.spend(key.len().saturating_add(4));
+                row.insert(key.clone(), cell);
+            }
+            rows.push(row);
+        }
+        Value::Array(rows)
+    }
+
+    /// A kind drawn from the mix, containers only while `depth` is below the
+    /// deepest nesting
+    fn kind(&mut self, depth: usize) -> Kind {
+        let nest = depth < self.options.max_depth;
+        let weights: Vec<(Kind, u32)> = self
+            .mix
+            .weights()
+            .into_iter()
+            .filter(|(kind, weight)| *weight > 0 && (nest || !kind.is_container()))
+            .collect();
+        let total: u64 = weights.iter().map(|(_, weight)| u64::from(*weight)).sum();
+        if total == 0 {
+            return Kind::Null;
+        }
+        let mut roll = self.rng.next() % total;
+        for (kind, weight) in &weights {
+            let weight = u64::from(*weight);
+            if roll < weight {
+                return *kind;
+            }
+            roll = roll.saturating_sub(weight);
+        }
+        Kind::Null
+    }
+
+    /// A value whose containers sit `depth` levels below the root
+    fn value(&mut self, depth: usize) -> Value {
+        let kind = self.kind(depth);
+        match kind {
+            Kind::Array => {
+                self.spend(2);
+                let mut items = Array::new();
+                for _ in 0..self.width() {
+                    if self.budget == 0 {
+                        break;
+                    }
+                    self.spend(1);
+                    items.push(self.value(depth.saturating_add(1)));
+                }
+                Value::Array(items)
+            }
+            Kind::Object => {
+                self.spend(2);
+                let mut entries = Object::new();
+                for _ in 0..self.width() {
+                    if self.budget == 0 {
+                        break;
+                    }
+                    let key = self.key(&entries);
+                    self.spend(key.len().saturating_add(4));
+                    let value = self.value(depth.saturating_add(1));
+                    entries.insert(key, value);
+                }
+                Value::Object(entries)
+            }
+            scalar => self.scalar(scalar),
+        }
+    }
+
+    fn width(&mut self) -> usize {
+        self.rng.below(self.options.max_width.saturating_add(1))
+    }
+
+    fn scalar(&mut self, kind: Kind) -> Value {
+        let value = match kind {
+            Kind::Bool => Value::Bool(self.rng.next() & 1 == 1),
+            Kind::Integer => {
+                let magnitude = [100, 100_000, 10_000_000_000]
+                    .get(self.rng.below(3))
+                    .copied()
+                    .unwrap_or(100);
+                let n = i64::try_from(self.rng.below(magnitude)).unwrap_or(0);
+                Value::from(if self.rng.below(4) == 0 { -n } else { n })
+            }
+            Kind::Float => {
+                let n = i32::try_from(self.rng.below(2_000_000)).unwrap_or(0);
+                let scale = [10.0, 100.0, 1000.0, 10_000.0]
+                    .get(self.rng.below(4))
+                    .copied()
+                    .unwrap_or(10.0);
+                Value::Number(f64::from(n.saturating_sub(1_000_000)) / scale)
+            }
+            Kind::String => Value::String(self.string()),
+            Kind::Null | Kind::Array | Kind::Object => Value::Null,
+        };
+        self.spend(approximate_len(&value));
+        value
+    }
+
+    fn word(&mut self) -> &'static str {
+        WORDS
+            .get(self.rng.below(WORDS.len()))
+            .copied()
+            .unwrap_or("value")
+    }
+
+    fn string(&mut self) -> String {
+        let words = self.rng.below(4).saturating_add(1);
+        let mut text = String::new();
+        for i in 0..words {
+            if i > 0 {
+                text.push(' ');
+            }
+            if self.rng.below(16) == 0 {
+                text.push_str(
+                    TRICKY
+                        .get(self.rng.below(TRICKY.len()))
+                        .copied()
+                        .unwrap_or(""),
+                );
+            } else {
+                text.push_str(self.word());
+            }
+        }
+        text
+    }
+
+    /// A key not yet in `object`, usable as an XML element name
+    fn key(&mut self, object: &Object) -> String {
+        let mut key = self.word().to_string();
+        if self.rng.below(3) == 0 {
+            key.push('_');
+            key.push_str(self.word());
+        }
+        if object.contains_key(&key) {
+            key = format!("{key}_{}", object.len());
+        }
+        key
+    }
+}
+
+/// Bytes `value`, a scalar, takes as compact JSON, not counting escapes
+fn approximate_len(value: &Value) -> usize {
+    match value {
+        Value::Null | Value::Bool(true) => 4,
+        Value::Bool(false) => 5,
+        Value::Number(n) => n.to_string().len(),
+        Value::String(text) => text.len().saturating_add(2),
+        _ => 2,
+    }
+}
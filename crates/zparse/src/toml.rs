@@ -1,7 +1,9 @@
 //! TOML streaming parser module
 
+pub mod builder;
 pub mod event;
 pub mod parser;
 
+pub use builder::Builder;
 pub use event::Event;
-pub use parser::{Config, Parser};
+pub use parser::{Config, KeyStyle, Parser};
@@ -0,0 +1,291 @@
+//! Programmatic TOML documents
+//!
+//! [`Builder`] writes a document the way a person would: root keys first,
+//! then one `[table]` or `[[array.of.tables]]` section after another, with
+//! comments where they were added. Each call is checked against TOML's rules
+//! as it is made, so the result always reads back.
+//!
+//! ```
+//! use zparse::toml::Builder;
+//!
+//! # fn main() -> zparse::Result<()> {
+//! let mut doc = Builder::new();
+//! doc.comment("Generated by deploy");
+//! doc.set("title", "zparse")?;
+//! doc.add_table(["server"])?;
+//! doc.set("port", 8080)?;
+//! doc.add_array_of_tables(["server", "route"])?;
+//! doc.set("path", "/")?;
+//!
+//! assert_eq!(
+//!     doc.to_string(),
+//!     "# Generated by deploy\ntitle = \"zparse\"\n\n[server]\nport = 8080\n\n[[server.route]]\npath = \"/\"\n"
+//! );
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::convert::{FormatOptions, toml_inline_value, toml_key};
+use crate::error::{Error, ErrorKind, Result, Span};
+use crate::locate::escape_token;
+use crate::toml_compat::{TomlCompat, prepare_for_toml};
+use crate::value::{Array, Object, Value};
+
+/// Builds a TOML document section by section
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Builder {
+    /// Comments and keys before the first header
+    root_items: Vec<Item>,
+    /// One section per header, in the order added
+    sections: Vec<Section>,
+    /// The document as built so far
+    root: Object,
+    /// JSON pointers of tables opened by a `[table]` header
+    headers: HashSet<String>,
+    /// JSON pointers of values added with [`Builder::set`]
+    values: HashSet<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Section {
+    /// Path of the table
+    path: Vec<String>,
+    /// Whether the header is an array-of-tables entry
+    is_array: bool,
+    items: Vec<Item>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Item {
+    Comment(String),
+    Entry(String, Value),
+}
+
+impl Builder {
+    /// Start an empty document
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a `# comment` line (one per line of `text`) to the current section
+    pub fn comment(&mut self, text: impl Into<String>) -> &mut Self {
+        self.items().push(Item::Comment(text.into()));
+        self
+    }
+
+    /// Set `key` in the current section. Tables and arrays are written
+    /// inline; use [`Self::add_table`] for a section instead.
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<Value>) -> Result<&mut Self> {
+        let key = key.into();
+        // Checked as the only entry of a table so nulls are reported
+        let mut entry = Object::new();
+        entry.insert(key.clone(), value.into());
+        let value = match prepare_for_toml(Value::Object(entry), &TomlCompat::default())? {
+            Value::Object(mut entry) => entry.remove(&key).unwrap_or_default(),
+            value => value,
+        };
+
+        let path = self.current_path();
+        let (table, pointer) = resolve(&mut self.root, &path, &self.values)?;
+        if table.contains_key(&key) {
+            return Err(duplicate(&path, &key));
+        }
+        table.insert(key.clone(), value.clone());
+        self.values
+            .insert(format!("{pointer}/{}", escape_token(&key)));
+        self.items().push(Item::Entry(key, value));
+        Ok(self)
+    }
+
+    /// Start a `[path]` section; later [`Self::set`] calls go into it
+    pub fn add_table<I, S>(&mut self, path: I) -> Result<&mut Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let path: Vec<String> = path.into_iter().map(Into::into).collect();
+        let (name, parent) = split_path(&path)?;
+        let (table, pointer) = resolve(&mut self.root, parent, &self.values)?;
+        let pointer = format!("{pointer}/{}", escape_token(name));
+        match table.get(name) {
+            None => {
+                table.insert(name.to_string(), Value::Object(Object::new()));
+            }
+            Some(Value::Object(_))
+                if !self.headers.contains(&pointer) && !self.values.contains(&pointer) => {}
+            Some(_) => return Err(duplicate(parent, name)),
+        }
+        self.headers.insert(pointer);
+        self.sections.push(Section {
+            path,
+            is_array: false,
+            items: Vec::new(),
+        });
+        Ok(self)
+    }
+
+    /// Start a new `[[path]]` entry; later [`Self::set`] calls go into it
+    pub fn add_array_of_tables<I, S>(&mut self, path: I) -> Result<&mut Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let path: Vec<String> = path.into_iter().map(Into::into).collect();
+        let (name, parent) = split_path(&path)?;
+        let (table, pointer) = resolve(&mut self.root, parent, &self.values)?;
+        let pointer = format!("{pointer}/{}", escape_token(name));
+        match table.get_mut(name) {
+            None => {
+                table.insert(
+                    name.to_string(),
+                    Value::Array(Array::from(vec![Value::Object(Object::new())])),
+                );
+            }
+            Some(Value::Array(tables))
+                if !self.values.contains(&pointer) && tables.iter().all(Value::is_object) =>
+            {
+                tables.push(Value::Object(Object::new()));
+            }
+            Some(_) => return Err(duplicate(parent, name)),
+        }
+        self.sections.push(Section {
+            path,
+            is_array: true,
+            items: Vec::new(),
+        });
+        Ok(self)
+    }
+
+    /// The document as a value
+    pub fn to_value(&self) -> Value {
+        Value::Object(self.root.clone())
+    }
+
+    /// Write the document, formatting values with `options`
+    pub fn to_string_with(&self, options: &FormatOptions) -> String {
+        let mut output = String::new();
+        write_items(&self.root_items, options, &mut output);
+        for section in &self.sections {
+            if !output.is_empty() {
+                output.push('\n');
+            }
+            let path: Vec<String> = section.path.iter().map(|key| toml_key(key)).collect();
+            if section.is_array {
+                output.push_str(&format!("[[{}]]\n", path.join(".")));
+            } else {
+                output.push_str(&format!("[{}]\n", path.join(".")));
+            }
+            write_items(&section.items, options, &mut output);
+        }
+        output
+    }
+
+    fn items(&mut self) -> &mut Vec<Item> {
+        match self.sections.last_mut() {
+            Some(section) => &mut section.items,
+            None => &mut self.root_items,
+        }
+    }
+
+    fn current_path(&self) -> Vec<String> {
+        self.sections
+            .last()
+            .map(|section| section.path.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Display for Builder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_string_with(&FormatOptions::default()))
+    }
+}
+
+impl From<Builder> for Value {
+    fn from(builder: Builder) -> Self {
+        Self::Object(builder.root)
+    }
+}
+
+fn write_items(items: &[Item], options: &FormatOptions, output: &mut String) {
+    for item in items {
+        match item {
+            Item::Comment(text) => {
+                for line in text.lines() {
+                    output.push_str(format!("# {line}").trim_end());
+                    output.push('\n');
+                }
+            }
+            Item::Entry(key, value) => output.push_str(&format!(
+                "{} = {}\n",
+                toml_key(key),
+                toml_inline_value(value, options)
+            )),
+        }
+    }
+}
+
+/// Find the table at `path`, descending into the last entry of arrays of
+/// tables as TOML headers do. Returns the table and its JSON pointer.
+fn resolve<'r>(
+    root: &'r mut Object,
+    path: &[String],
+    values: &HashSet<String>,
+) -> Result<(&'r mut Object, String)> {
+    let mut table = root;
+    let mut pointer = String::new();
+    for (depth, key) in path.iter().enumerate() {
+        pointer.push('/');
+        pointer.push_str(&escape_token(key));
+        let parent = path.get(..depth).unwrap_or_default();
+        if values.contains(&pointer) {
+            return Err(duplicate(parent, key));
+        }
+        let next = table
+            .0
+            .entry(key.clone())
+            .or_insert_with(|| Value::Object(Object::new()));
+        table = match next {
+            Value::Object(obj) => obj,
+            Value::Array(tables) => {
+                let last = tables.len().saturating_sub(1);
+                pointer.push_str(&format!("/{last}"));
+                match tables.get_mut(last) {
+                    Some(Value::Object(obj)) => obj,
+                    _ => return Err(duplicate(parent, key)),
+                }
+            }
+            _ => return Err(duplicate(parent, key)),
+        };
+    }
+    Ok((table, pointer))
+}
+
+fn split_path(path: &[String]) -> Result<(&str, &[String])> {
+    match path.split_last() {
+        Some((name, parent)) => Ok((name, parent)),
+        None => Err(Error::with_message(
+            ErrorKind::InvalidKey,
+            Span::empty(),
+            "table path must not be empty".to_string(),
+        )),
+    }
+}
+
+fn duplicate(parent: &[String], key: &str) -> Error {
+    let key = parent
+        .iter()
+        .map(String::as_str)
+        .chain([key])
+        .map(toml_key)
+        .collect::<Vec<_>>()
+        .join(".");
+    Error::with_message(
+        ErrorKind::DuplicateKey { key: key.clone() },
+        Span::empty(),
+        format!("{key} is already defined"),
+    )
+}
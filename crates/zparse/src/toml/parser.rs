@@ -1,10 +1,13 @@
 //! TOML streaming parser implementation
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 
 use crate::error::{Error, ErrorKind, Result, Span};
+use crate::event::ValueEvents;
 use crate::lexer::toml::{TomlLexer, TomlToken, TomlTokenKind};
+use crate::locate::escape_token;
 use crate::toml::event::Event;
+use crate::warning::{Warning, WarningKind, i64_loses_precision};
 
 pub const DEFAULT_MAX_DEPTH: u16 = 128;
 pub const DEFAULT_MAX_SIZE: usize = 10 * 1024 * 1024;
@@ -60,7 +63,33 @@ pub struct Parser<'a> {
     events: VecDeque<Event>,
     root: Object,
     current_table: Vec<String>,
-    current_is_array: bool,
+    /// JSON pointer of the current table, including array-of-tables indices
+    current_pointer: String,
+    /// How each table or array was defined, by JSON pointer
+    defined: HashMap<String, Defined>,
+    /// Span of the first token of the most recent event
+    event_span: Span,
+    warnings: Vec<Warning>,
+    input: &'a [u8],
+    /// Spelling of each key by JSON pointer of its value, when recording
+    key_styles: Option<Vec<(String, KeyStyle)>>,
+    /// Spelling of the key segments parsed but not yet recorded
+    pending_styles: Vec<KeyStyle>,
+    /// JSON pointer of the value being parsed, maintained while recording
+    value_pointer: String,
+    /// Events of the parsed document, once read through [`EventReader`](crate::event::EventReader)
+    pub(crate) unified_events: Option<ValueEvents>,
+}
+
+/// How a TOML key was written in the source
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KeyStyle {
+    /// `key`
+    Bare,
+    /// `"key"`
+    Basic,
+    /// `'key'`
+    Literal,
 }
 
 impl<'a> Parser<'a> {
@@ -80,7 +109,15 @@ impl<'a> Parser<'a> {
             events: VecDeque::new(),
             root: Object::new(),
             current_table: Vec::new(),
-            current_is_array: false,
+            current_pointer: String::new(),
+            defined: HashMap::new(),
+            event_span: Span::empty(),
+            warnings: Vec::new(),
+            input,
+            key_styles: None,
+            pending_styles: Vec::new(),
+            value_pointer: String::new(),
+            unified_events: None,
         }
     }
 
@@ -94,39 +131,97 @@ impl<'a> Parser<'a> {
             Some(token) => token,
             None => return Ok(None),
         };
+        self.event_span = token.span;
 
         match token.kind {
             TomlTokenKind::LeftBracket | TomlTokenKind::DoubleLeftBracket => {
                 let is_array = matches!(token.kind, TomlTokenKind::DoubleLeftBracket);
                 let path = self.parse_table_header(token.kind)?;
-                if is_array {
-                    self.ensure_array_table(&path)?;
-                } else {
-                    self.ensure_table(&path)?;
-                }
+                self.open_table(&path, is_array)?;
+                self.pending_styles.clear();
                 self.current_table = path.clone();
-                self.current_is_array = is_array;
                 let event = Event::TableStart { path, is_array };
                 Ok(Some(event))
             }
             _ => {
-                let key = self.parse_key_path(Some(token))?;
-                self.expect_kind(TomlTokenKind::Equals)?;
-                let value = self.parse_value()?;
-                let table_path = self.current_table.clone();
-                let is_array = self.current_is_array;
-                self.insert_dotted_key(&table_path, is_array, &key, value.clone())?;
-                Ok(Some(Event::KeyValue { key, value }))
+                if self.key_styles.is_some() {
+                    self.value_pointer.clone_from(&self.current_pointer);
+                }
+                self.key_value(token).map(Some).map_err(|err| {
+                    if self.current_table.is_empty() {
+                        return err;
+                    }
+                    err.context(format!(
+                        "while parsing table [{}]",
+                        self.current_table.join(".")
+                    ))
+                })
             }
         }
     }
 
+    /// Read a `key = value` line starting with `token`
+    fn key_value(&mut self, token: TomlToken) -> Result<Event> {
+        let key = self.parse_key_path(Some(token))?;
+        self.record_key(&key);
+        let value = self
+            .assign(&key)
+            .map_err(|err| err.context(format!("while parsing key '{}'", key.join("."))))?;
+        Ok(Event::KeyValue { key, value })
+    }
+
+    /// Read the `= value` after `key` and add it to the current table
+    fn assign(&mut self, key: &[String]) -> Result<Value> {
+        self.expect_kind(TomlTokenKind::Equals)?;
+        let value = self.parse_value()?;
+        self.insert_key(key, value.clone())?;
+        Ok(value)
+    }
+
     /// Parse the full document into a Value
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(format = "toml"))
+    )]
     pub fn parse(&mut self) -> Result<Value> {
         while let Some(_event) = self.next_event()? {}
         Ok(Value::Object(std::mem::take(&mut self.root)))
     }
 
+    /// Check that the full document is valid TOML.
+    ///
+    /// TOML forbids defining a key or table twice, so the parser still
+    /// records each table as it goes; only the returned value is skipped.
+    pub fn validate(&mut self) -> Result<()> {
+        while self.next_event()?.is_some() {}
+        Ok(())
+    }
+
+    /// Span of the table header or key that started the most recent event
+    pub fn event_span(&self) -> Span {
+        self.event_span
+    }
+
+    /// Drain the non-fatal warnings recorded so far
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Start recording how keys are spelled, see [`Parser::take_key_styles`]
+    pub fn record_key_styles(&mut self) {
+        self.key_styles.get_or_insert_with(Vec::new);
+    }
+
+    /// Drain the recorded key spellings, each paired with the JSON pointer of
+    /// the value the key names. Table headers are included, as are keys of
+    /// inline tables.
+    pub fn take_key_styles(&mut self) -> Vec<(String, KeyStyle)> {
+        self.key_styles
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
     fn next_token(&mut self) -> Result<TomlToken> {
         let token = match self.buffered.take() {
             Some(token) => token,
@@ -209,7 +304,20 @@ impl<'a> Parser<'a> {
                     let next = self.next_token()?;
                     path.push(self.parse_key_from_token(next)?);
                 }
-                kind if kind == close => break,
+                kind if kind == close => {
+                    // A trailing comment swallows the newline, so compare lines.
+                    let next = self.peek_token()?;
+                    if !matches!(next.kind, TomlTokenKind::Newline | TomlTokenKind::Eof)
+                        && next.span.start.line == token.span.start.line
+                    {
+                        return Err(Error::with_message(
+                            ErrorKind::InvalidKey,
+                            next.span,
+                            "expected newline after table header".to_string(),
+                        ));
+                    }
+                    break;
+                }
                 TomlTokenKind::Newline => {
                     return Err(Error::with_message(
                         ErrorKind::InvalidKey,
@@ -230,10 +338,19 @@ impl<'a> Parser<'a> {
         Ok(path)
     }
 
-    fn parse_key_from_token(&self, token: TomlToken) -> Result<String> {
+    fn parse_key_from_token(&mut self, token: TomlToken) -> Result<String> {
+        let style = match token.kind {
+            TomlTokenKind::BareKey(_) => KeyStyle::Bare,
+            _ if self.input.get(token.span.start.offset) == Some(&b'\'') => KeyStyle::Literal,
+            _ => KeyStyle::Basic,
+        };
         match token.kind {
-            TomlTokenKind::BareKey(key) => Ok(key),
-            TomlTokenKind::String(key) => Ok(key),
+            TomlTokenKind::BareKey(key) | TomlTokenKind::String(key) => {
+                if self.key_styles.is_some() {
+                    self.pending_styles.push(style);
+                }
+                Ok(key)
+            }
             _ => Err(Error::with_message(
                 ErrorKind::InvalidKey,
                 token.span,
@@ -262,124 +379,236 @@ impl<'a> Parser<'a> {
         Ok(path)
     }
 
+    /// Record the spelling of the segments of `key`, just parsed, and extend
+    /// the value pointer to the value the key names
+    fn record_key(&mut self, key: &[String]) {
+        let styles = std::mem::take(&mut self.pending_styles);
+        if let Some(recorded) = self.key_styles.as_mut() {
+            for (part, style) in key.iter().zip(styles) {
+                self.value_pointer.push('/');
+                self.value_pointer.push_str(&escape_token(part));
+                recorded.push((self.value_pointer.clone(), style));
+            }
+        }
+    }
+
     fn parse_value(&mut self) -> Result<Value> {
         let token = self.next_token()?;
         self.parse_value_from_token(token)
     }
 
+    /// Parse the value starting at `token`.
+    ///
+    /// Arrays and inline tables still being read are kept on an explicit
+    /// stack rather than the call stack, so nesting is bounded only by
+    /// [`Config::max_depth`].
     fn parse_value_from_token(&mut self, token: TomlToken) -> Result<Value> {
-        let token = self.normalize_value_token(token)?;
-        match token.kind {
-            TomlTokenKind::String(value) => Ok(Value::String(value)),
-            TomlTokenKind::Integer(value) => Ok(Value::from(value)),
-            TomlTokenKind::Float(value) => Ok(Value::Number(value)),
-            TomlTokenKind::Bool(value) => Ok(Value::Bool(value)),
-            TomlTokenKind::Datetime(value) => {
-                let datetime = parse_toml_datetime(&value)?;
-                Ok(Value::Datetime(datetime))
-            }
-            TomlTokenKind::LeftBracket => self.parse_array(token.span),
-            TomlTokenKind::LeftBrace => self.parse_inline_table(token.span),
-            _ => Err(Error::with_message(
-                ErrorKind::InvalidToken,
-                token.span,
-                "expected value".to_string(),
-            )),
+        let mut stack = Vec::new();
+        let mut token = token;
+        loop {
+            let mut value = match self.start_value(token, &mut stack)? {
+                Start::Value(value) => value,
+                Start::Nested(next) => {
+                    token = next;
+                    continue;
+                }
+            };
+            token = loop {
+                match self.continue_nested(value, &mut stack)? {
+                    Step::Done(value) => return Ok(value),
+                    Step::Closed(closed) => value = closed,
+                    Step::Next(next) => break next,
+                }
+            };
         }
     }
 
-    fn parse_array(&mut self, opening_span: Span) -> Result<Value> {
-        self.depth = self.depth.saturating_add(1);
-        if self.config.max_depth > 0 && self.depth > self.config.max_depth {
-            return Err(Error::with_message(
-                ErrorKind::MaxDepthExceeded {
-                    max: self.config.max_depth,
-                },
-                opening_span,
-                "max depth exceeded".to_string(),
-            ));
-        }
-
-        let mut values = Vec::new();
-
-        loop {
-            let token = self.next_non_newline_token()?;
-            match token {
-                None => {
-                    return Err(Error::with_message(
-                        ErrorKind::InvalidArray,
-                        Span::empty(),
-                        "unterminated array".to_string(),
+    /// Read a scalar, or open an array or inline table and hand back the
+    /// token its first entry starts with
+    fn start_value(&mut self, token: TomlToken, stack: &mut Vec<Nested>) -> Result<Start> {
+        let token = self.normalize_value_token(token)?;
+        let value = match token.kind {
+            TomlTokenKind::String(value) => Value::String(value),
+            TomlTokenKind::Integer(value) => {
+                if i64_loses_precision(value) {
+                    self.warnings.push(Warning::new(
+                        WarningKind::PrecisionLoss {
+                            literal: value.to_string(),
+                        },
+                        token.span,
                     ));
                 }
-                Some(token) if token.kind == TomlTokenKind::RightBracket => break,
-                Some(token) if token.kind == TomlTokenKind::DoubleRightBracket => {
-                    self.buffered = Some(TomlToken::new(TomlTokenKind::RightBracket, token.span));
-                    break;
+                Value::from(value)
+            }
+            TomlTokenKind::Float(value) => Value::Number(value),
+            TomlTokenKind::Bool(value) => Value::Bool(value),
+            TomlTokenKind::Datetime(value) => Value::Datetime(parse_toml_datetime(&value)?),
+            TomlTokenKind::LeftBracket => {
+                self.enter_nested(token.span)?;
+                let base = self
+                    .key_styles
+                    .is_some()
+                    .then(|| self.value_pointer.clone());
+                match self.next_non_newline_token()? {
+                    None => return Err(unterminated_array()),
+                    Some(token) if token.kind == TomlTokenKind::RightBracket => {}
+                    Some(token) if token.kind == TomlTokenKind::DoubleRightBracket => {
+                        self.buffered =
+                            Some(TomlToken::new(TomlTokenKind::RightBracket, token.span));
+                    }
+                    Some(token) => {
+                        if let Some(base) = &base {
+                            self.value_pointer = format!("{base}/0");
+                        }
+                        stack.push(Nested::Array(Vec::new(), base));
+                        return Ok(Start::Nested(token));
+                    }
                 }
-                Some(token) => {
-                    let token = self.normalize_value_token(token)?;
-                    let value = self.parse_value_from_token(token)?;
-                    values.push(value);
-
-                    let token = self.next_non_newline_token()?;
-                    match token {
-                        Some(token) if token.kind == TomlTokenKind::Comma => {
-                            let next = self.next_non_newline_token()?;
-                            match next {
-                                Some(token) if token.kind == TomlTokenKind::RightBracket => {
-                                    break;
-                                }
-                                Some(token) if token.kind == TomlTokenKind::DoubleRightBracket => {
-                                    self.buffered = Some(TomlToken::new(
-                                        TomlTokenKind::RightBracket,
-                                        token.span,
-                                    ));
-                                    break;
-                                }
-                                Some(token) => {
-                                    self.buffered = Some(token);
-                                    continue;
-                                }
-                                None => {
-                                    return Err(Error::with_message(
-                                        ErrorKind::InvalidArray,
-                                        Span::empty(),
-                                        "unterminated array".to_string(),
-                                    ));
+                self.depth = self.depth.saturating_sub(1);
+                Value::Array(Array::new())
+            }
+            TomlTokenKind::LeftBrace => {
+                self.enter_nested(token.span)?;
+                match self.next_non_newline_token()? {
+                    Some(token) if token.kind == TomlTokenKind::RightBrace => {
+                        self.depth = self.depth.saturating_sub(1);
+                        return Ok(Start::Value(Value::Object(Object::new())));
+                    }
+                    Some(token) => {
+                        self.buffered = Some(token);
+                    }
+                    None => {
+                        return Err(Error::with_message(
+                            ErrorKind::InvalidInlineTable,
+                            Span::empty(),
+                            "unterminated inline table".to_string(),
+                        ));
+                    }
+                }
+                let base = self
+                    .key_styles
+                    .is_some()
+                    .then(|| self.value_pointer.clone());
+                let key = self.parse_inline_key()?;
+                stack.push(Nested::Table(Object::new(), key, base));
+                return Ok(Start::Nested(self.next_token()?));
+            }
+            _ => {
+                return Err(Error::with_message(
+                    ErrorKind::InvalidToken,
+                    token.span,
+                    "expected value".to_string(),
+                ));
+            }
+        };
+        Ok(Start::Value(value))
+    }
+
+    /// Store a finished value in the innermost open array or inline table,
+    /// then read up to its next entry or close it
+    fn continue_nested(&mut self, value: Value, stack: &mut Vec<Nested>) -> Result<Step> {
+        let Some(mut nested) = stack.pop() else {
+            return Ok(Step::Done(value));
+        };
+        match &mut nested {
+            Nested::Array(values, base) => {
+                values.push(value);
+                let token = match self.next_non_newline_token()? {
+                    Some(token) if token.kind == TomlTokenKind::Comma => {
+                        match self.next_non_newline_token()? {
+                            Some(token)
+                                if matches!(
+                                    token.kind,
+                                    TomlTokenKind::RightBracket | TomlTokenKind::DoubleRightBracket
+                                ) =>
+                            {
+                                token
+                            }
+                            Some(token) => {
+                                if let Some(base) = base {
+                                    self.value_pointer = format!("{base}/{}", values.len());
                                 }
+                                stack.push(nested);
+                                return Ok(Step::Next(token));
                             }
+                            None => return Err(unterminated_array()),
                         }
-                        Some(token) if token.kind == TomlTokenKind::RightBracket => break,
-                        Some(token) if token.kind == TomlTokenKind::DoubleRightBracket => {
-                            self.buffered =
-                                Some(TomlToken::new(TomlTokenKind::RightBracket, token.span));
-                            break;
-                        }
-                        Some(token) => {
-                            return Err(Error::with_message(
-                                ErrorKind::InvalidArray,
-                                token.span,
-                                "expected comma or closing bracket".to_string(),
-                            ));
-                        }
-                        None => {
-                            return Err(Error::with_message(
-                                ErrorKind::InvalidArray,
-                                Span::empty(),
-                                "unterminated array".to_string(),
-                            ));
+                    }
+                    Some(token) => token,
+                    None => return Err(unterminated_array()),
+                };
+                match token.kind {
+                    TomlTokenKind::RightBracket => {}
+                    TomlTokenKind::DoubleRightBracket => {
+                        self.buffered =
+                            Some(TomlToken::new(TomlTokenKind::RightBracket, token.span));
+                    }
+                    _ => {
+                        return Err(Error::with_message(
+                            ErrorKind::InvalidArray,
+                            token.span,
+                            "expected comma or closing bracket".to_string(),
+                        ));
+                    }
+                }
+            }
+            Nested::Table(obj, key, base) => {
+                insert_dotted_key_into(obj, key, value)?;
+                if let Some(base) = base {
+                    self.value_pointer.clone_from(base);
+                }
+
+                let token = self.next_token()?;
+                match token.kind {
+                    TomlTokenKind::Comma => {
+                        if self.peek_token()?.kind == TomlTokenKind::RightBrace {
+                            let _ = self.next_token()?;
+                        } else {
+                            *key = self.parse_inline_key()?;
+                            stack.push(nested);
+                            return Ok(Step::Next(self.next_token()?));
                         }
                     }
+                    TomlTokenKind::RightBrace => {}
+                    TomlTokenKind::Newline => {
+                        return Err(Error::with_message(
+                            ErrorKind::InvalidInlineTable,
+                            token.span,
+                            "newline not allowed in inline table".to_string(),
+                        ));
+                    }
+                    _ => {
+                        return Err(Error::with_message(
+                            ErrorKind::InvalidInlineTable,
+                            token.span,
+                            "expected comma or closing brace".to_string(),
+                        ));
+                    }
                 }
             }
         }
 
         self.depth = self.depth.saturating_sub(1);
-        Ok(Value::Array(Array(values)))
+        Ok(Step::Closed(match nested {
+            Nested::Array(values, base) => {
+                if let Some(base) = base {
+                    self.value_pointer = base;
+                }
+                Value::Array(Array(values))
+            }
+            Nested::Table(obj, _, _) => Value::Object(obj),
+        }))
     }
 
-    fn parse_inline_table(&mut self, opening_span: Span) -> Result<Value> {
+    /// Read `key =` inside an inline table
+    fn parse_inline_key(&mut self) -> Result<Vec<String>> {
+        let key = self.parse_key_path(None)?;
+        self.record_key(&key);
+        self.expect_kind(TomlTokenKind::Equals)?;
+        Ok(key)
+    }
+
+    fn enter_nested(&mut self, opening_span: Span) -> Result<()> {
         self.depth = self.depth.saturating_add(1);
         if self.config.max_depth > 0 && self.depth > self.config.max_depth {
             return Err(Error::with_message(
@@ -390,106 +619,279 @@ impl<'a> Parser<'a> {
                 "max depth exceeded".to_string(),
             ));
         }
+        Ok(())
+    }
 
-        let mut obj = Object::new();
-
-        let token = self.next_non_newline_token()?;
-        match token {
-            Some(token) if token.kind == TomlTokenKind::RightBrace => {
-                self.depth = self.depth.saturating_sub(1);
-                return Ok(Value::Object(obj));
-            }
-            Some(token) => {
-                self.buffered = Some(token);
+    fn normalize_value_token(&mut self, token: TomlToken) -> Result<TomlToken> {
+        match token.kind {
+            TomlTokenKind::DoubleLeftBracket => {
+                self.buffered = Some(TomlToken::new(TomlTokenKind::LeftBracket, token.span));
+                Ok(TomlToken::new(TomlTokenKind::LeftBracket, token.span))
             }
-            None => {
-                return Err(Error::with_message(
-                    ErrorKind::InvalidInlineTable,
-                    Span::empty(),
-                    "unterminated inline table".to_string(),
-                ));
+            TomlTokenKind::DoubleRightBracket => {
+                self.buffered = Some(TomlToken::new(TomlTokenKind::RightBracket, token.span));
+                Ok(TomlToken::new(TomlTokenKind::RightBracket, token.span))
             }
+            _ => Ok(token),
         }
+    }
 
-        loop {
-            let key = self.parse_key_path(None)?;
-            self.expect_kind(TomlTokenKind::Equals)?;
-            let value = self.parse_value()?;
-            insert_dotted_key_into(&mut obj, &key, value)?;
+    /// Create or reopen the table named by a `[path]` or `[[path]]` header,
+    /// enforcing the table definition rules of the spec
+    fn open_table(&mut self, path: &[String], is_array: bool) -> Result<()> {
+        let span = self.event_span;
+        let name = path.join(".");
+        let defined = &mut self.defined;
+        let mut current = &mut self.root;
+        let mut pointer = String::new();
+
+        for (index, part) in path.iter().enumerate() {
+            let is_last = index + 1 == path.len();
+            pointer.push('/');
+            pointer.push_str(&escape_token(part));
+            if let Some(recorded) = self.key_styles.as_mut()
+                && let Some(style) = self.pending_styles.get(index)
+            {
+                recorded.push((pointer.clone(), *style));
+            }
+            let kind = defined.get(&pointer).copied();
 
-            let token = self.next_token()?;
-            match token.kind {
-                TomlTokenKind::Comma => {
-                    let next = self.peek_token()?;
-                    if next.kind == TomlTokenKind::RightBrace {
-                        let _ = self.next_token()?;
-                        break;
+            if is_last && is_array {
+                match (current.get(part), kind) {
+                    (None, _) => {
+                        current.insert(part, Array::new());
+                        defined.insert(pointer.clone(), Defined::ArrayOfTables);
+                    }
+                    (Some(Value::Array(_)), Some(Defined::ArrayOfTables)) => {}
+                    (Some(Value::Array(_)), _) => {
+                        return Err(Error::with_message(
+                            ErrorKind::InvalidArray,
+                            span,
+                            format!("cannot append to static array {name}"),
+                        ));
+                    }
+                    _ => {
+                        return Err(Error::with_message(
+                            ErrorKind::InvalidArray,
+                            span,
+                            format!("array of tables {name} conflicts with an existing value"),
+                        ));
                     }
                 }
-                TomlTokenKind::RightBrace => break,
-                TomlTokenKind::Newline => {
+                if let Some(Value::Array(array)) = current.get_mut(part) {
+                    pointer.push_str(&format!("/{}", array.len()));
+                    array.push(Object::new());
+                }
+                self.current_pointer = pointer;
+                return Ok(());
+            }
+
+            match (current.get(part), kind) {
+                (None, _) => {
+                    current.insert(part, Object::new());
+                    let kind = if is_last {
+                        Defined::Header
+                    } else {
+                        Defined::Implicit
+                    };
+                    defined.insert(pointer.clone(), kind);
+                }
+                (Some(Value::Object(_)), Some(Defined::Inline)) => {
                     return Err(Error::with_message(
                         ErrorKind::InvalidInlineTable,
-                        token.span,
-                        "newline not allowed in inline table".to_string(),
+                        span,
+                        format!("cannot extend inline table {name}"),
                     ));
                 }
-                _ => {
+                (Some(Value::Object(_)), Some(Defined::Header)) if is_last => {
                     return Err(Error::with_message(
-                        ErrorKind::InvalidInlineTable,
-                        token.span,
-                        "expected comma or closing brace".to_string(),
+                        ErrorKind::DuplicateKey { key: name },
+                        span,
+                        "table defined twice".to_string(),
+                    ));
+                }
+                (Some(Value::Object(_)), Some(Defined::Dotted)) if is_last => {
+                    return Err(Error::with_message(
+                        ErrorKind::InvalidKey,
+                        span,
+                        format!("cannot reopen table {name} defined by dotted keys"),
+                    ));
+                }
+                (Some(Value::Object(_)), _) => {
+                    if is_last {
+                        defined.insert(pointer.clone(), Defined::Header);
+                    }
+                }
+                (Some(Value::Array(_)), Some(Defined::ArrayOfTables)) if !is_last => {}
+                (Some(Value::Array(_)), _) => {
+                    return Err(Error::with_message(
+                        ErrorKind::InvalidArray,
+                        span,
+                        format!("table {name} conflicts with an array"),
+                    ));
+                }
+                (Some(_), _) => {
+                    return Err(Error::with_message(
+                        ErrorKind::InvalidKey,
+                        span,
+                        format!("table {name} conflicts with an existing value"),
                     ));
                 }
             }
+
+            if let Some(Value::Array(array)) = current.get(part) {
+                pointer.push_str(&format!("/{}", array.len().saturating_sub(1)));
+            }
+            current = child_table(current, part)?;
         }
 
-        self.depth = self.depth.saturating_sub(1);
-        Ok(Value::Object(obj))
+        self.current_pointer = pointer;
+        Ok(())
     }
 
-    fn normalize_value_token(&mut self, token: TomlToken) -> Result<TomlToken> {
-        match token.kind {
-            TomlTokenKind::DoubleLeftBracket => {
-                self.buffered = Some(TomlToken::new(TomlTokenKind::LeftBracket, token.span));
-                Ok(TomlToken::new(TomlTokenKind::LeftBracket, token.span))
-            }
-            TomlTokenKind::DoubleRightBracket => {
-                self.buffered = Some(TomlToken::new(TomlTokenKind::RightBracket, token.span));
-                Ok(TomlToken::new(TomlTokenKind::RightBracket, token.span))
+    /// Assign `key` (possibly dotted) in the current table. Dotted keys may
+    /// only pass through tables that dotted keys created.
+    fn insert_key(&mut self, key: &[String], value: Value) -> Result<()> {
+        let span = self.event_span;
+        let defined = &mut self.defined;
+        let mut current = &mut self.root;
+        for part in &self.current_table {
+            current = child_table(current, part)?;
+        }
+
+        let Some((last, parents)) = key.split_last() else {
+            return Err(Error::with_message(
+                ErrorKind::InvalidKey,
+                span,
+                "empty key".to_string(),
+            ));
+        };
+        let mut pointer = self.current_pointer.clone();
+        for part in parents {
+            pointer.push('/');
+            pointer.push_str(&escape_token(part));
+            match (current.get(part), defined.get(&pointer)) {
+                (None, _) => {
+                    current.insert(part, Object::new());
+                    defined.insert(pointer.clone(), Defined::Dotted);
+                }
+                (Some(Value::Object(_)), Some(Defined::Dotted)) => {}
+                (Some(Value::Object(_)), Some(Defined::Inline)) => {
+                    return Err(Error::with_message(
+                        ErrorKind::InvalidInlineTable,
+                        span,
+                        format!("cannot extend inline table {}", key.join(".")),
+                    ));
+                }
+                (Some(Value::Object(_)), _) => {
+                    return Err(Error::with_message(
+                        ErrorKind::InvalidKey,
+                        span,
+                        format!(
+                            "cannot add dotted key {} to a table defined by a header",
+                            key.join(".")
+                        ),
+                    ));
+                }
+                _ => {
+                    return Err(Error::with_message(
+                        ErrorKind::InvalidKey,
+                        span,
+                        "key already assigned".to_string(),
+                    ));
+                }
             }
-            _ => Ok(token),
+            current = child_table(current, part)?;
         }
-    }
 
-    fn ensure_table(&mut self, path: &[String]) -> Result<()> {
-        let _ = ensure_table_path(&mut self.root, path)?;
+        if current.contains_key(last) {
+            return Err(Error::with_message(
+                ErrorKind::DuplicateKey { key: last.clone() },
+                span,
+                "duplicate key".to_string(),
+            ));
+        }
+        pointer.push('/');
+        pointer.push_str(&escape_token(last));
+        match value {
+            Value::Object(_) => {
+                defined.insert(pointer, Defined::Inline);
+            }
+            Value::Array(_) => {
+                defined.insert(pointer, Defined::StaticArray);
+            }
+            _ => {}
+        }
+        current.insert(last, value);
         Ok(())
     }
+}
 
-    fn ensure_array_table(&mut self, path: &[String]) -> Result<()> {
-        let _ = ensure_array_table_path(&mut self.root, path)?;
-        Ok(())
-    }
+/// An array or inline table being read: the entries so far, the key of the
+/// entry being read for tables, and the value pointer to restore afterwards
+enum Nested {
+    Array(Vec<Value>, Option<String>),
+    Table(Object, Vec<String>, Option<String>),
+}
 
-    fn insert_dotted_key(
-        &mut self,
-        table_path: &[String],
-        is_array: bool,
-        key: &[String],
-        value: Value,
-    ) -> Result<()> {
-        if is_array {
-            let table = get_array_table_last(&mut self.root, table_path)?;
-            insert_dotted_key_into(table, key, value)
-        } else {
-            let table = ensure_table_path(&mut self.root, table_path)?;
-            insert_dotted_key_into(table, key, value)
-        }
+enum Start {
+    Value(Value),
+    /// An array or inline table was opened; its first entry starts here
+    Nested(TomlToken),
+}
+
+enum Step {
+    /// The outermost value is complete
+    Done(Value),
+    /// An array or inline table was closed and is the next value to store
+    Closed(Value),
+    /// The next entry of the innermost open array or table starts here
+    Next(TomlToken),
+}
+
+fn unterminated_array() -> Error {
+    Error::with_message(
+        ErrorKind::InvalidArray,
+        Span::empty(),
+        "unterminated array".to_string(),
+    )
+}
+
+/// How a table or array came into existence, which decides whether it may be
+/// reopened or extended later
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Defined {
+    /// Parent of a header, e.g. `a` for `[a.b]`; may still get its own header
+    Implicit,
+    /// Named by a `[header]`
+    Header,
+    /// Created by a dotted key such as `a.b = 1`
+    Dotted,
+    /// Inline table value; closed to everything outside its braces
+    Inline,
+    /// Array value; cannot be extended by `[[header]]`
+    StaticArray,
+    /// Created by `[[header]]`
+    ArrayOfTables,
+}
+
+/// The table stored under `part`, or the last table of an array of tables
+fn child_table<'a>(table: &'a mut Object, part: &str) -> Result<&'a mut Object> {
+    let child = match table.get_mut(part) {
+        Some(Value::Array(array)) => array.iter_mut().last(),
+        other => other,
+    };
+    match child {
+        Some(Value::Object(object)) => Ok(object),
+        _ => Err(Error::with_message(
+            ErrorKind::InvalidKey,
+            Span::empty(),
+            "expected table".to_string(),
+        )),
     }
 }
 
-fn parse_toml_datetime(value: &str) -> Result<TomlDatetime> {
+pub(crate) fn parse_toml_datetime(value: &str) -> Result<TomlDatetime> {
     if let Ok(datetime) = OffsetDateTime::parse(value, &Rfc3339) {
         return Ok(TomlDatetime::OffsetDateTime(datetime));
     }
@@ -535,317 +937,6 @@ fn parse_toml_datetime(value: &str) -> Result<TomlDatetime> {
     ))
 }
 
-fn ensure_table_path<'a>(root: &'a mut Object, path: &[String]) -> Result<&'a mut Object> {
-    let mut current = root;
-    for part in path {
-        let entry = current.get(part).cloned();
-        match entry {
-            Some(Value::Object(_)) => {
-                current = current
-                    .get_mut(part)
-                    .and_then(|value| match value {
-                        Value::Object(obj) => Some(obj),
-                        _ => None,
-                    })
-                    .ok_or_else(|| {
-                        Error::with_message(
-                            ErrorKind::InvalidKey,
-                            Span::empty(),
-                            "expected table".to_string(),
-                        )
-                    })?;
-            }
-            Some(Value::Array(_)) => {
-                return Err(Error::with_message(
-                    ErrorKind::InvalidArray,
-                    Span::empty(),
-                    "array used where table expected".to_string(),
-                ));
-            }
-            Some(_) => {
-                return Err(Error::with_message(
-                    ErrorKind::InvalidKey,
-                    Span::empty(),
-                    "key already assigned".to_string(),
-                ));
-            }
-            None => {
-                current.insert(part, Object::new());
-                current = current
-                    .get_mut(part)
-                    .and_then(|value| match value {
-                        Value::Object(obj) => Some(obj),
-                        _ => None,
-                    })
-                    .ok_or_else(|| {
-                        Error::with_message(
-                            ErrorKind::InvalidKey,
-                            Span::empty(),
-                            "expected table".to_string(),
-                        )
-                    })?;
-            }
-        }
-    }
-    Ok(current)
-}
-
-fn ensure_array_table_path<'a>(root: &'a mut Object, path: &[String]) -> Result<&'a mut Object> {
-    if path.is_empty() {
-        return Err(Error::with_message(
-            ErrorKind::InvalidKey,
-            Span::empty(),
-            "empty array table path".to_string(),
-        ));
-    }
-
-    let mut current = root;
-    for (index, part) in path.iter().enumerate() {
-        let is_last = index + 1 == path.len();
-        let entry = current.get(part).cloned();
-        if is_last {
-            match entry {
-                None => {
-                    let mut array = Array::new();
-                    array.push(Object::new());
-                    current.insert(part, Value::Array(array));
-                }
-                Some(Value::Array(mut array)) => {
-                    array.push(Object::new());
-                    current.insert(part, Value::Array(array));
-                }
-                Some(_) => {
-                    return Err(Error::with_message(
-                        ErrorKind::InvalidArray,
-                        Span::empty(),
-                        "array table conflicts with existing value".to_string(),
-                    ));
-                }
-            }
-
-            let array = current
-                .get_mut(part)
-                .and_then(|value| match value {
-                    Value::Array(array) => Some(array),
-                    _ => None,
-                })
-                .ok_or_else(|| {
-                    Error::with_message(
-                        ErrorKind::InvalidArray,
-                        Span::empty(),
-                        "expected array table".to_string(),
-                    )
-                })?;
-            let last = array
-                .iter_mut()
-                .last()
-                .and_then(|value| match value {
-                    Value::Object(obj) => Some(obj),
-                    _ => None,
-                })
-                .ok_or_else(|| {
-                    Error::with_message(
-                        ErrorKind::InvalidArray,
-                        Span::empty(),
-                        "expected object in array table".to_string(),
-                    )
-                })?;
-            return Ok(last);
-        }
-
-        match entry {
-            Some(Value::Object(_)) => {
-                current = current
-                    .get_mut(part)
-                    .and_then(|value| match value {
-                        Value::Object(obj) => Some(obj),
-                        _ => None,
-                    })
-                    .ok_or_else(|| {
-                        Error::with_message(
-                            ErrorKind::InvalidKey,
-                            Span::empty(),
-                            "expected table".to_string(),
-                        )
-                    })?;
-            }
-            Some(Value::Array(_)) => {
-                let array = current
-                    .get_mut(part)
-                    .and_then(|value| match value {
-                        Value::Array(array) => Some(array),
-                        _ => None,
-                    })
-                    .ok_or_else(|| {
-                        Error::with_message(
-                            ErrorKind::InvalidArray,
-                            Span::empty(),
-                            "expected array table".to_string(),
-                        )
-                    })?;
-                let last = array
-                    .iter_mut()
-                    .last()
-                    .and_then(|value| match value {
-                        Value::Object(obj) => Some(obj),
-                        _ => None,
-                    })
-                    .ok_or_else(|| {
-                        Error::with_message(
-                            ErrorKind::InvalidArray,
-                            Span::empty(),
-                            "expected object in array table".to_string(),
-                        )
-                    })?;
-                current = last;
-            }
-            Some(_) => {
-                return Err(Error::with_message(
-                    ErrorKind::InvalidKey,
-                    Span::empty(),
-                    "key already assigned".to_string(),
-                ));
-            }
-            None => {
-                current.insert(part, Object::new());
-                current = current
-                    .get_mut(part)
-                    .and_then(|value| match value {
-                        Value::Object(obj) => Some(obj),
-                        _ => None,
-                    })
-                    .ok_or_else(|| {
-                        Error::with_message(
-                            ErrorKind::InvalidKey,
-                            Span::empty(),
-                            "expected table".to_string(),
-                        )
-                    })?;
-            }
-        }
-    }
-
-    Err(Error::with_message(
-        ErrorKind::InvalidKey,
-        Span::empty(),
-        "invalid array table path".to_string(),
-    ))
-}
-
-fn get_array_table_last<'a>(root: &'a mut Object, path: &[String]) -> Result<&'a mut Object> {
-    if path.is_empty() {
-        return Err(Error::with_message(
-            ErrorKind::InvalidKey,
-            Span::empty(),
-            "empty array table path".to_string(),
-        ));
-    }
-
-    let mut current = root;
-    for (index, part) in path.iter().enumerate() {
-        let is_last = index + 1 == path.len();
-        let entry = current.get(part).cloned();
-        if is_last {
-            let array = current
-                .get_mut(part)
-                .and_then(|value| match value {
-                    Value::Array(array) => Some(array),
-                    _ => None,
-                })
-                .ok_or_else(|| {
-                    Error::with_message(
-                        ErrorKind::InvalidArray,
-                        Span::empty(),
-                        "expected array table".to_string(),
-                    )
-                })?;
-            let last = array
-                .iter_mut()
-                .last()
-                .and_then(|value| match value {
-                    Value::Object(obj) => Some(obj),
-                    _ => None,
-                })
-                .ok_or_else(|| {
-                    Error::with_message(
-                        ErrorKind::InvalidArray,
-                        Span::empty(),
-                        "expected object in array table".to_string(),
-                    )
-                })?;
-            return Ok(last);
-        }
-
-        match entry {
-            Some(Value::Object(_)) => {
-                current = current
-                    .get_mut(part)
-                    .and_then(|value| match value {
-                        Value::Object(obj) => Some(obj),
-                        _ => None,
-                    })
-                    .ok_or_else(|| {
-                        Error::with_message(
-                            ErrorKind::InvalidKey,
-                            Span::empty(),
-                            "expected table".to_string(),
-                        )
-                    })?;
-            }
-            Some(Value::Array(_)) => {
-                let array = current
-                    .get_mut(part)
-                    .and_then(|value| match value {
-                        Value::Array(array) => Some(array),
-                        _ => None,
-                    })
-                    .ok_or_else(|| {
-                        Error::with_message(
-                            ErrorKind::InvalidArray,
-                            Span::empty(),
-                            "expected array table".to_string(),
-                        )
-                    })?;
-                let last = array
-                    .iter_mut()
-                    .last()
-                    .and_then(|value| match value {
-                        Value::Object(obj) => Some(obj),
-                        _ => None,
-                    })
-                    .ok_or_else(|| {
-                        Error::with_message(
-                            ErrorKind::InvalidArray,
-                            Span::empty(),
-                            "expected object in array table".to_string(),
-                        )
-                    })?;
-                current = last;
-            }
-            Some(_) => {
-                return Err(Error::with_message(
-                    ErrorKind::InvalidKey,
-                    Span::empty(),
-                    "key already assigned".to_string(),
-                ));
-            }
-            None => {
-                return Err(Error::with_message(
-                    ErrorKind::InvalidKey,
-                    Span::empty(),
-                    "missing array table".to_string(),
-                ));
-            }
-        }
-    }
-
-    Err(Error::with_message(
-        ErrorKind::InvalidKey,
-        Span::empty(),
-        "invalid array table path".to_string(),
-    ))
-}
-
 fn insert_dotted_key_into(table: &mut Object, key: &[String], value: Value) -> Result<()> {
     if key.is_empty() {
         return Err(Error::with_message(
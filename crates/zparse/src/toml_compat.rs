@@ -0,0 +1,189 @@
+//! YAML ↔ TOML conversion with TOML's restrictions made explicit
+//!
+//! TOML cannot represent everything YAML can: the root must be a table, there
+//! is no null, and some consumers still expect homogeneous arrays (TOML 0.5).
+//! [`prepare_for_toml`] checks a value against those rules before
+//! serialization, reporting the offending path or applying the coercions
+//! selected in [`TomlCompat`].
+
+use crate::convert::{ConvertOptions, Format, parse_with_options, serialize_with_options};
+use crate::error::{Error, ErrorKind, Result, Span};
+use crate::value::{Array, Object, Value};
+
+/// What to do with `null` values, which TOML cannot represent
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NullPolicy {
+    /// Fail with the path of the null value
+    #[default]
+    Error,
+    /// Drop null entries from tables and arrays
+    Omit,
+    /// Write nulls as empty strings
+    EmptyString,
+}
+
+/// Coercions applied when writing values as TOML
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TomlCompat {
+    /// Handling of `null` values
+    pub null: NullPolicy,
+    /// Reject arrays mixing value types (required by TOML 0.5 readers)
+    pub homogeneous_arrays: bool,
+    /// Wrap a non-table root under this key instead of failing
+    pub root_key: Option<String>,
+}
+
+impl TomlCompat {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the null handling policy
+    pub fn with_null(mut self, null: NullPolicy) -> Self {
+        self.null = null;
+        self
+    }
+
+    /// Require arrays to hold a single value type
+    pub fn with_homogeneous_arrays(mut self, enabled: bool) -> Self {
+        self.homogeneous_arrays = enabled;
+        self
+    }
+
+    /// Wrap non-table roots under `key`
+    pub fn with_root_key(mut self, key: impl Into<String>) -> Self {
+        self.root_key = Some(key.into());
+        self
+    }
+}
+
+/// Convert YAML to TOML, checking TOML's restrictions with `options.toml_compat`
+pub fn yaml_to_toml(input: &str, options: &ConvertOptions) -> Result<String> {
+    let value = parse_with_options(input, Format::Yaml, options)?;
+    let value = prepare_for_toml(value, &options.toml_compat)?;
+    serialize_with_options(&value, Format::Toml, &options.format)
+}
+
+/// Convert TOML to YAML
+pub fn toml_to_yaml(input: &str, options: &ConvertOptions) -> Result<String> {
+    let value = parse_with_options(input, Format::Toml, options)?;
+    serialize_with_options(&value, Format::Yaml, &options.format)
+}
+
+/// Check `value` against TOML's data model, applying the coercions in `compat`
+pub fn prepare_for_toml(value: Value, compat: &TomlCompat) -> Result<Value> {
+    let value = match value {
+        Value::Object(_) => value,
+        other => match &compat.root_key {
+            Some(key) => {
+                let mut root = Object::new();
+                root.insert(key.clone(), other);
+                Value::Object(root)
+            }
+            None => {
+                return Err(unsupported(
+                    "$",
+                    format!(
+                        "TOML documents must be a table at the root, found {}; \
+                         wrap the document under a key (root_key / --toml-root-key)",
+                        type_name(&other)
+                    ),
+                ));
+            }
+        },
+    };
+
+    let mut path = String::from("$");
+    match coerce(value, compat, &mut path)? {
+        Some(value) => Ok(value),
+        None => Ok(Value::Object(Object::new())),
+    }
+}
+
+/// Returns `None` when the value should be omitted
+fn coerce(value: Value, compat: &TomlCompat, path: &mut String) -> Result<Option<Value>> {
+    match value {
+        Value::Null => match compat.null {
+            NullPolicy::Error => Err(unsupported(
+                path,
+                format!(
+                    "TOML has no null value (at {path}); \
+                     omit it or write an empty string (null policy / --toml-null)"
+                ),
+            )),
+            NullPolicy::Omit => Ok(None),
+            NullPolicy::EmptyString => Ok(Some(Value::String(String::new()))),
+        },
+        Value::Array(items) => {
+            let mut out = Array::with_capacity(items.len());
+            let mut first_type: Option<&'static str> = None;
+            for (index, item) in items.into_iter().enumerate() {
+                let len = path.len();
+                path.push_str(&format!("[{index}]"));
+                let coerced = coerce(item, compat, path)
+                    .map_err(|err| err.context(format!("while converting item [{index}]")))?;
+                if let Some(item) = coerced {
+                    if compat.homogeneous_arrays {
+                        let kind = type_name(&item);
+                        match first_type {
+                            None => first_type = Some(kind),
+                            Some(expected) if expected != kind => {
+                                return Err(unsupported(
+                                    path,
+                                    format!(
+                                        "array at {} mixes {expected} and {kind}; \
+                                         homogeneous arrays are required",
+                                        path.get(..len).unwrap_or_default()
+                                    ),
+                                ));
+                            }
+                            Some(_) => {}
+                        }
+                    }
+                    out.push(item);
+                }
+                path.truncate(len);
+            }
+            Ok(Some(Value::Array(out)))
+        }
+        Value::Object(entries) => {
+            let mut out = Object::with_capacity(entries.len());
+            for (key, item) in entries {
+                let len = path.len();
+                path.push('.');
+                path.push_str(&key);
+                let coerced = coerce(item, compat, path)
+                    .map_err(|err| err.context(format!("while converting key '{key}'")))?;
+                path.truncate(len);
+                if let Some(item) = coerced {
+                    out.insert(key, item);
+                }
+            }
+            Ok(Some(Value::Object(out)))
+        }
+        other => Ok(Some(other)),
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Datetime(_) => "datetime",
+        Value::Bytes(_) => "bytes",
+        Value::Array(_) => "array",
+        Value::Object(_) => "table",
+    }
+}
+
+fn unsupported(path: &str, message: String) -> Error {
+    Error::with_message(
+        ErrorKind::UnsupportedValue {
+            path: path.to_string(),
+        },
+        Span::empty(),
+        message,
+    )
+}
@@ -2,11 +2,32 @@
 
 use indexmap::IndexMap;
 use indexmap::map::{IntoIter, Iter, Keys, Values};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::ops::Index;
-use time::{Date, OffsetDateTime, PrimitiveDateTime, Time};
+#[cfg(feature = "toml")]
+use std::str::FromStr;
+use time::{Date, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+
+mod try_from;
 
 /// A JSON/TOML/YAML/XML value
-#[derive(Debug, Clone, PartialEq, Default)]
+///
+/// # Equality, ordering and hashing
+///
+/// Values implement [`Eq`], [`Ord`] and [`Hash`] so they can key maps and sets
+/// and be sorted deterministically:
+///
+/// - Numbers compare by value, so `-0.0 == 0.0`, while every NaN equals every
+///   other NaN and sorts after positive infinity.
+/// - Objects ignore key order, like [`Object`]'s equality, and are ordered by
+///   their entries sorted by key.
+/// - Values of different types are ordered null < bool < number < string <
+///   datetime < bytes < array < object.
+#[derive(Debug, Clone, Default)]
 pub enum Value {
     /// Null value
     #[default]
@@ -23,10 +44,14 @@ pub enum Value {
     Object(Object),
     /// TOML datetime value
     Datetime(TomlDatetime),
+    /// Raw bytes that need not be valid UTF-8 (e.g. YAML `!!binary`)
+    Bytes(Vec<u8>),
 }
 
 /// TOML datetime values
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Ordered by kind in declaration order, then chronologically.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum TomlDatetime {
     OffsetDateTime(OffsetDateTime),
     LocalDateTime(PrimitiveDateTime),
@@ -34,6 +59,41 @@ pub enum TomlDatetime {
     LocalTime(Time),
 }
 
+impl TomlDatetime {
+    /// Give a local date-time the offset `offset`; other kinds are unchanged
+    pub fn with_local_offset(self, offset: UtcOffset) -> Self {
+        match self {
+            Self::LocalDateTime(value) => Self::OffsetDateTime(value.assume_offset(offset)),
+            other => other,
+        }
+    }
+
+    /// Move a date-time with an offset to UTC (`Z`); other kinds are unchanged
+    pub fn to_utc(self) -> Self {
+        match self {
+            Self::OffsetDateTime(value) => Self::OffsetDateTime(value.to_offset(UtcOffset::UTC)),
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for TomlDatetime {
+    /// Formats the datetime as TOML writes it, e.g. `1979-05-27T07:32:00Z`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&crate::convert::format_datetime(self))
+    }
+}
+
+#[cfg(feature = "toml")]
+impl FromStr for TomlDatetime {
+    type Err = crate::Error;
+
+    /// Parses any of the four TOML datetime forms
+    fn from_str(s: &str) -> crate::Result<Self> {
+        crate::toml::parser::parse_toml_datetime(s)
+    }
+}
+
 impl Value {
     /// Returns true if this value is null
     pub fn is_null(&self) -> bool {
@@ -70,6 +130,11 @@ impl Value {
         matches!(self, Self::Datetime(_))
     }
 
+    /// Returns true if this value is a byte string
+    pub fn is_bytes(&self) -> bool {
+        matches!(self, Self::Bytes(_))
+    }
+
     /// Returns the boolean value if this is a boolean, None otherwise
     pub fn as_bool(&self) -> Option<bool> {
         match self {
@@ -118,6 +183,14 @@ impl Value {
         }
     }
 
+    /// Returns the bytes if this is a byte string, None otherwise
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Self::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
     /// Returns a mutable reference to the array if this is an array, None otherwise
     pub fn as_array_mut(&mut self) -> Option<&mut Array> {
         match self {
@@ -133,6 +206,111 @@ impl Value {
             _ => None,
         }
     }
+
+    /// Returns the name of this value's type, e.g. `"object"` or `"number"`
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Null => "null",
+            Self::Bool(_) => "boolean",
+            Self::Number(_) => "number",
+            Self::String(_) => "string",
+            Self::Array(_) => "array",
+            Self::Object(_) => "object",
+            Self::Datetime(_) => "datetime",
+            Self::Bytes(_) => "bytes",
+        }
+    }
+
+    /// Returns the number of elements, entries, characters or bytes, or None
+    /// for values without a length
+    pub fn len(&self) -> Option<usize> {
+        match self {
+            Self::String(s) => Some(s.chars().count()),
+            Self::Array(a) => Some(a.len()),
+            Self::Object(o) => Some(o.len()),
+            Self::Bytes(b) => Some(b.len()),
+            Self::Null | Self::Bool(_) | Self::Number(_) | Self::Datetime(_) => None,
+        }
+    }
+
+    /// Returns true if this is an empty string, array, object or byte string
+    pub fn is_empty(&self) -> bool {
+        self.len() == Some(0)
+    }
+
+    /// Returns the array element at an index or the object entry at a key,
+    /// None if there is none or this is neither an array nor an object
+    pub fn get(&self, index: impl ValueIndex) -> Option<&Self> {
+        index.index_into(self)
+    }
+
+    /// Returns a mutable reference to the array element at an index or the
+    /// object entry at a key
+    pub fn get_mut(&mut self, index: impl ValueIndex) -> Option<&mut Self> {
+        index.index_into_mut(self)
+    }
+
+    /// Python-style truthiness: null, false, zero, NaN and empty strings,
+    /// arrays, objects and byte strings are false, everything else is true
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Self::Null => false,
+            Self::Bool(b) => *b,
+            Self::Number(n) => *n != 0.0 && !n.is_nan(),
+            Self::Datetime(_) => true,
+            Self::String(_) | Self::Array(_) | Self::Object(_) | Self::Bytes(_) => !self.is_empty(),
+        }
+    }
+}
+
+/// Types that can look up a child of a [`Value`]: `usize` indexes arrays and
+/// strings key objects
+pub trait ValueIndex {
+    /// Returns the child of `value` this index refers to
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value>;
+
+    /// Returns a mutable reference to the child of `value` this index refers to
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value>;
+}
+
+impl ValueIndex for usize {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        value.as_array()?.get(*self)
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        value.as_array_mut()?.get_mut(*self)
+    }
+}
+
+impl ValueIndex for str {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        value.as_object()?.get(self)
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        value.as_object_mut()?.get_mut(self)
+    }
+}
+
+impl ValueIndex for String {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        self.as_str().index_into(value)
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        self.as_str().index_into_mut(value)
+    }
+}
+
+impl<T: ValueIndex + ?Sized> ValueIndex for &T {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        (**self).index_into(value)
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        (**self).index_into_mut(value)
+    }
 }
 
 impl From<bool> for Value {
@@ -153,6 +331,54 @@ impl From<TomlDatetime> for Value {
     }
 }
 
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Self::Number(f64::from(value))
+    }
+}
+
+impl From<i8> for Value {
+    fn from(value: i8) -> Self {
+        Self::Number(f64::from(value))
+    }
+}
+
+impl From<i16> for Value {
+    fn from(value: i16) -> Self {
+        Self::Number(f64::from(value))
+    }
+}
+
+impl From<u8> for Value {
+    fn from(value: u8) -> Self {
+        Self::Number(f64::from(value))
+    }
+}
+
+impl From<u16> for Value {
+    fn from(value: u16) -> Self {
+        Self::Number(f64::from(value))
+    }
+}
+
+impl From<isize> for Value {
+    fn from(value: isize) -> Self {
+        #[allow(clippy::as_conversions)]
+        // JSON numbers are represented as f64; precision loss is acceptable here.
+        let number = value as f64;
+        Self::Number(number)
+    }
+}
+
+impl From<usize> for Value {
+    fn from(value: usize) -> Self {
+        #[allow(clippy::as_conversions)]
+        // JSON numbers are represented as f64; precision loss is acceptable here.
+        let number = value as f64;
+        Self::Number(number)
+    }
+}
+
 impl From<i32> for Value {
     fn from(value: i32) -> Self {
         Self::Number(f64::from(value))
@@ -195,6 +421,24 @@ impl From<&str> for Value {
     }
 }
 
+impl From<&String> for Value {
+    fn from(value: &String) -> Self {
+        Self::String(value.clone())
+    }
+}
+
+impl From<Cow<'_, str>> for Value {
+    fn from(value: Cow<'_, str>) -> Self {
+        Self::String(value.into_owned())
+    }
+}
+
+impl From<char> for Value {
+    fn from(value: char) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
 impl From<Array> for Value {
     fn from(value: Array) -> Self {
         Self::Array(value)
@@ -207,22 +451,76 @@ impl From<Object> for Value {
     }
 }
 
-#[allow(clippy::use_self)]
-impl From<Vec<Value>> for Value {
-    fn from(values: Vec<Value>) -> Self {
-        Self::Array(Array(values))
+impl<T: Into<Self>> From<Vec<T>> for Value {
+    fn from(values: Vec<T>) -> Self {
+        Self::Array(Array(values.into_iter().map(Into::into).collect()))
     }
 }
 
-#[allow(clippy::use_self)]
-impl From<IndexMap<String, Value>> for Value {
-    fn from(map: IndexMap<String, Value>) -> Self {
-        Self::Object(Object(map))
+impl<T: Clone + Into<Self>> From<&[T]> for Value {
+    fn from(values: &[T]) -> Self {
+        Self::Array(Array(values.iter().cloned().map(Into::into).collect()))
+    }
+}
+
+impl<T: Into<Self>, const N: usize> From<[T; N]> for Value {
+    fn from(values: [T; N]) -> Self {
+        Self::Array(Array(values.into_iter().map(Into::into).collect()))
+    }
+}
+
+/// Entries keep the map's iteration order
+impl<K: Into<String>, T: Into<Self>, S> From<IndexMap<K, T, S>> for Value {
+    fn from(map: IndexMap<K, T, S>) -> Self {
+        Self::Object(Object(
+            map.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        ))
+    }
+}
+
+/// Entries are sorted by key
+impl<K: Into<String>, T: Into<Self>> From<BTreeMap<K, T>> for Value {
+    fn from(map: BTreeMap<K, T>) -> Self {
+        Self::Object(Object(
+            map.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        ))
+    }
+}
+
+/// Entries are in the map's iteration order, which is arbitrary; sort the
+/// result with [`Value::sort_keys`] for stable output
+impl<K: Into<String>, T: Into<Self>, S> From<HashMap<K, T, S>> for Value {
+    fn from(map: HashMap<K, T, S>) -> Self {
+        Self::Object(Object(
+            map.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        ))
+    }
+}
+
+/// `None` becomes [`Value::Null`]
+impl<T: Into<Self>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
+
+/// Collects into [`Value::Array`]
+impl<T: Into<Self>> FromIterator<T> for Value {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::Array(Array(iter.into_iter().map(Into::into).collect()))
     }
 }
 
-/// An order-preserving object (map of string keys to values)
-#[derive(Debug, Clone, PartialEq, Default)]
+/// An order-preserving object (map of string keys to values).
+///
+/// Equality ignores the order of entries.
+#[derive(Debug, PartialEq, Eq, Default)]
 pub struct Object(pub(crate) IndexMap<String, Value>);
 
 impl Object {
@@ -262,6 +560,58 @@ impl Object {
         self.0.insert(key.into(), value.into())
     }
 
+    /// Gets the entry for `key` for in-place insertion or update; new keys go
+    /// at the end
+    pub fn entry(&mut self, key: impl Into<String>) -> indexmap::map::Entry<'_, String, Value> {
+        self.0.entry(key.into())
+    }
+
+    /// Returns the position of `key` in the object
+    pub fn index_of(&self, key: &str) -> Option<usize> {
+        self.0.get_index_of(key)
+    }
+
+    /// Inserts a key-value pair just before the entry at `index` (or at the
+    /// end when `index` is the length), moving the key there if it already
+    /// existed. Returns the previous value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`, like [`Vec::insert`].
+    pub fn insert_before(
+        &mut self,
+        index: usize,
+        key: impl Into<String>,
+        value: impl Into<Value>,
+    ) -> Option<Value> {
+        self.0.insert_before(index, key.into(), value.into()).1
+    }
+
+    /// Inserts a key-value pair just after the entry at `index`, moving the
+    /// key there if it already existed. Returns the previous value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index >= len`.
+    pub fn insert_after(
+        &mut self,
+        index: usize,
+        key: impl Into<String>,
+        value: impl Into<Value>,
+    ) -> Option<Value> {
+        self.insert_before(index.saturating_add(1), key, value)
+    }
+
+    /// Moves the entry at `from` to position `to`, shifting the entries in
+    /// between
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from >= len` or `to >= len`.
+    pub fn move_key(&mut self, from: usize, to: usize) {
+        self.0.move_index(from, to);
+    }
+
     /// Removes a key from the object, returning the value if the key was present
     pub fn remove(&mut self, key: &str) -> Option<Value> {
         self.0.swap_remove(key)
@@ -296,6 +646,44 @@ impl Object {
     pub fn clear(&mut self) {
         self.0.clear();
     }
+
+    /// Returns the underlying map, for the parts of the [`IndexMap`] API
+    /// that `Object` doesn't wrap
+    pub fn as_index_map(&self) -> &IndexMap<String, Value> {
+        &self.0
+    }
+
+    /// Returns the underlying map mutably
+    pub fn as_index_map_mut(&mut self) -> &mut IndexMap<String, Value> {
+        &mut self.0
+    }
+
+    /// Consumes the object, returning the underlying map
+    pub fn into_inner(mut self) -> IndexMap<String, Value> {
+        std::mem::take(&mut self.0)
+    }
+}
+
+impl Clone for Object {
+    fn clone(&self) -> Self {
+        let frame = CloneFrame::Object(
+            self.0.iter(),
+            IndexMap::with_capacity(self.0.len()),
+            String::new(),
+        );
+        match clone_nested(frame) {
+            Value::Object(obj) => obj,
+            _ => Self::new(),
+        }
+    }
+}
+
+impl Drop for Object {
+    fn drop(&mut self) {
+        if self.0.values().any(has_children) {
+            drop_iteratively(self.0.drain(..).map(|(_, value)| value).collect());
+        }
+    }
 }
 
 impl Index<&str> for Object {
@@ -329,8 +717,8 @@ impl IntoIterator for Object {
     type Item = (String, Value);
     type IntoIter = IntoIter<String, Value>;
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+    fn into_iter(mut self) -> Self::IntoIter {
+        std::mem::take(&mut self.0).into_iter()
     }
 }
 
@@ -340,6 +728,12 @@ impl From<IndexMap<String, Value>> for Object {
     }
 }
 
+impl From<Object> for IndexMap<String, Value> {
+    fn from(object: Object) -> Self {
+        object.into_inner()
+    }
+}
+
 impl FromIterator<(String, Value)> for Object {
     fn from_iter<I: IntoIterator<Item = (String, Value)>>(iter: I) -> Self {
         Self(IndexMap::from_iter(iter))
@@ -347,7 +741,7 @@ impl FromIterator<(String, Value)> for Object {
 }
 
 /// An array of values
-#[derive(Debug, Clone, PartialEq, Default)]
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
 pub struct Array(pub(crate) Vec<Value>);
 
 impl Array {
@@ -406,15 +800,60 @@ impl Array {
         self.0.clear();
     }
 
+    /// Returns the elements as a slice
+    pub fn as_slice(&self) -> &[Value] {
+        &self.0
+    }
+
+    /// Returns the underlying vector mutably, for the parts of the [`Vec`]
+    /// API that `Array` doesn't wrap
+    pub fn as_vec_mut(&mut self) -> &mut Vec<Value> {
+        &mut self.0
+    }
+
+    /// Consumes the array, returning the underlying vector
+    pub fn into_vec(mut self) -> Vec<Value> {
+        std::mem::take(&mut self.0)
+    }
+
     /// Inserts an element at the given index
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`, like [`Vec::insert`].
     pub fn insert(&mut self, index: usize, value: impl Into<Value>) {
         self.0.insert(index, value.into());
     }
 
     /// Removes and returns the element at the given index
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds, like [`Vec::remove`]; use
+    /// [`Array::get`] first when the index comes from input.
     pub fn remove(&mut self, index: usize) -> Value {
         self.0.remove(index)
     }
+
+    /// Keeps only the elements for which `keep` returns true, in order
+    pub fn retain(&mut self, keep: impl FnMut(&Value) -> bool) {
+        self.0.retain(keep);
+    }
+
+    /// Removes every element equal to an earlier one, keeping the first.
+    /// Unlike [`Vec::dedup`] the duplicates need not be adjacent.
+    pub fn dedup(&mut self) {
+        let mut seen = std::collections::HashSet::new();
+        let keep: Vec<bool> = self.0.iter().map(|item| seen.insert(item)).collect();
+        let mut keep = keep.into_iter();
+        self.0.retain(|_| keep.next().unwrap_or(true));
+    }
+}
+
+impl<T: Into<Value>> Extend<T> for Array {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.0.extend(iter.into_iter().map(Into::into));
+    }
 }
 
 impl Index<usize> for Array {
@@ -426,6 +865,116 @@ impl Index<usize> for Array {
     }
 }
 
+impl Clone for Array {
+    fn clone(&self) -> Self {
+        let frame = CloneFrame::Array(self.0.iter(), Vec::with_capacity(self.0.len()));
+        match clone_nested(frame) {
+            Value::Array(arr) => arr,
+            _ => Self::new(),
+        }
+    }
+}
+
+impl Drop for Array {
+    fn drop(&mut self) {
+        if self.0.iter().any(has_children) {
+            drop_iteratively(std::mem::take(&mut self.0));
+        }
+    }
+}
+
+fn has_children(value: &Value) -> bool {
+    match value {
+        Value::Array(items) => !items.0.is_empty(),
+        Value::Object(entries) => !entries.0.is_empty(),
+        _ => false,
+    }
+}
+
+/// An array or object being copied: the entries left to copy, the copies so
+/// far and, for objects, the key of the entry being copied
+enum CloneFrame<'v> {
+    Array(std::slice::Iter<'v, Value>, Vec<Value>),
+    Object(Iter<'v, String, Value>, IndexMap<String, Value>, String),
+}
+
+impl<'v> CloneFrame<'v> {
+    fn open(value: &'v Value) -> Option<Self> {
+        match value {
+            Value::Array(items) if !items.0.is_empty() => Some(Self::Array(
+                items.0.iter(),
+                Vec::with_capacity(items.0.len()),
+            )),
+            Value::Object(entries) if !entries.0.is_empty() => Some(Self::Object(
+                entries.0.iter(),
+                IndexMap::with_capacity(entries.0.len()),
+                String::new(),
+            )),
+            _ => None,
+        }
+    }
+
+    fn add(&mut self, value: Value) {
+        match self {
+            Self::Array(_, items) => items.push(value),
+            Self::Object(_, entries, key) => {
+                entries.insert(std::mem::take(key), value);
+            }
+        }
+    }
+
+    fn finish(self) -> Value {
+        match self {
+            Self::Array(_, items) => Value::Array(Array(items)),
+            Self::Object(_, entries, _) => Value::Object(Object(entries)),
+        }
+    }
+}
+
+/// Deep-copy a container from an explicit stack of open containers instead
+/// of recursing, so that copying a deeply nested document cannot overflow the
+/// call stack
+fn clone_nested(root: CloneFrame<'_>) -> Value {
+    let mut stack = vec![root];
+    loop {
+        let Some(frame) = stack.last_mut() else {
+            return Value::Null;
+        };
+        let next = match frame {
+            CloneFrame::Array(items, _) => items.next(),
+            CloneFrame::Object(entries, _, key) => entries.next().map(|(name, value)| {
+                key.clone_from(name);
+                value
+            }),
+        };
+        match next {
+            Some(value) => match CloneFrame::open(value) {
+                Some(child) => stack.push(child),
+                None => frame.add(value.clone()),
+            },
+            None => {
+                let finished = stack.pop().map_or(Value::Null, CloneFrame::finish);
+                match stack.last_mut() {
+                    Some(parent) => parent.add(finished),
+                    None => return finished,
+                }
+            }
+        }
+    }
+}
+
+/// Drop nested values from an explicit stack instead of recursing, so that
+/// dropping a deeply nested document cannot overflow the call stack
+fn drop_iteratively(mut stack: Vec<Value>) {
+    while let Some(mut value) = stack.pop() {
+        match &mut value {
+            Value::Array(items) => stack.append(&mut items.0),
+            Value::Object(entries) => stack.extend(entries.0.drain(..).map(|(_, value)| value)),
+            _ => {}
+        }
+    }
+}
+
 impl<'a> IntoIterator for &'a Array {
     type Item = &'a Value;
     type IntoIter = std::slice::Iter<'a, Value>;
@@ -439,8 +988,8 @@ impl IntoIterator for Array {
     type Item = Value;
     type IntoIter = std::vec::IntoIter<Value>;
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+    fn into_iter(mut self) -> Self::IntoIter {
+        std::mem::take(&mut self.0).into_iter()
     }
 }
 
@@ -450,6 +999,18 @@ impl From<Vec<Value>> for Array {
     }
 }
 
+impl From<Array> for Vec<Value> {
+    fn from(array: Array) -> Self {
+        array.into_vec()
+    }
+}
+
+impl AsRef<[Value]> for Array {
+    fn as_ref(&self) -> &[Value] {
+        self.as_slice()
+    }
+}
+
 impl FromIterator<Value> for Array {
     fn from_iter<I: IntoIterator<Item = Value>>(iter: I) -> Self {
         Self(Vec::from_iter(iter))
@@ -467,3 +1028,114 @@ impl IntoIterator for Value {
         }
     }
 }
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Null, Self::Null) => true,
+            (Self::Bool(a), Self::Bool(b)) => a == b,
+            (Self::Number(a), Self::Number(b)) => a == b || (a.is_nan() && b.is_nan()),
+            (Self::String(a), Self::String(b)) => a == b,
+            (Self::Array(a), Self::Array(b)) => a == b,
+            (Self::Object(a), Self::Object(b)) => a == b,
+            (Self::Datetime(a), Self::Datetime(b)) => a == b,
+            (Self::Bytes(a), Self::Bytes(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Value {}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Bool(a), Self::Bool(b)) => a.cmp(b),
+            (Self::Number(a), Self::Number(b)) => {
+                canonical_number(*a).total_cmp(&canonical_number(*b))
+            }
+            (Self::String(a), Self::String(b)) => a.cmp(b),
+            (Self::Array(a), Self::Array(b)) => a.cmp(b),
+            (Self::Object(a), Self::Object(b)) => a.cmp(b),
+            (Self::Datetime(a), Self::Datetime(b)) => a.cmp(b),
+            (Self::Bytes(a), Self::Bytes(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+        match self {
+            Self::Null => {}
+            Self::Bool(b) => b.hash(state),
+            Self::Number(n) => canonical_number(*n).to_bits().hash(state),
+            Self::String(s) => s.hash(state),
+            Self::Array(a) => a.hash(state),
+            Self::Object(o) => o.hash(state),
+            Self::Datetime(d) => d.hash(state),
+            Self::Bytes(b) => b.hash(state),
+        }
+    }
+}
+
+impl Value {
+    /// Position of this value's type in the cross-type order
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Null => 0,
+            Self::Bool(_) => 1,
+            Self::Number(_) => 2,
+            Self::String(_) => 3,
+            Self::Datetime(_) => 4,
+            Self::Bytes(_) => 5,
+            Self::Array(_) => 6,
+            Self::Object(_) => 7,
+        }
+    }
+}
+
+/// `n` with `-0.0` folded into `0.0` and every NaN into one positive NaN
+pub(crate) fn canonical_number(n: f64) -> f64 {
+    if n.is_nan() {
+        f64::NAN
+    } else if n == 0.0 {
+        0.0
+    } else {
+        n
+    }
+}
+
+impl Object {
+    /// Entries sorted by key, the order used to compare and hash objects
+    fn sorted_entries(&self) -> Vec<(&String, &Value)> {
+        let mut entries: Vec<_> = self.0.iter().collect();
+        entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        entries
+    }
+}
+
+impl PartialOrd for Object {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Object {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sorted_entries().cmp(&other.sorted_entries())
+    }
+}
+
+impl Hash for Object {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.sorted_entries().hash(state);
+    }
+}
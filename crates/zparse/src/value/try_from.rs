@@ -0,0 +1,227 @@
+//! Conversions from [`Value`] into Rust types
+//!
+//! Scalars convert only from the matching variant: a number is not a string
+//! and `"8080"` is not a `u16`. Integers must be whole and in range. A failed
+//! conversion is an [`ErrorKind::Expected`] naming the type wanted and the
+//! value found; conversions into collections add the JSON pointer of the item
+//! that failed.
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use zparse::{Value, from_str};
+//!
+//! # fn main() -> zparse::Result<()> {
+//! let config = from_str(r#"{"name": "api", "ports": {"web": 8080, "admin": 70000}}"#)?;
+//! let name = String::try_from(config.get("name").unwrap_or(&Value::Null))?;
+//! assert_eq!(name, "api");
+//!
+//! let ports: zparse::Result<HashMap<String, u16>> =
+//!     config.get("ports").unwrap_or(&Value::Null).try_into();
+//! assert_eq!(
+//!     ports.map_err(|err| err.message().to_string()),
+//!     Err("/admin: expected u16, found number 70000".to_string())
+//! );
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+
+use super::{Array, Object, TomlDatetime, Value};
+use crate::convert::{FormatOptions, serialize_json};
+use crate::error::{Error, ErrorKind, Result, Span};
+use crate::locate::escape_token;
+
+/// Longest scalar shown in an error before it is cut short
+const PREVIEW_CHARS: usize = 40;
+
+macro_rules! try_from_integer {
+    ($($ty:ty),*) => {$(
+        impl TryFrom<&Value> for $ty {
+            type Error = Error;
+
+            fn try_from(value: &Value) -> Result<Self> {
+                value
+                    .as_number()
+                    .and_then(whole)
+                    .and_then(|n| Self::try_from(n).ok())
+                    .ok_or_else(|| mismatch(stringify!($ty), value))
+            }
+        }
+
+        impl TryFrom<Value> for $ty {
+            type Error = Error;
+
+            fn try_from(value: Value) -> Result<Self> {
+                Self::try_from(&value)
+            }
+        }
+    )*};
+}
+
+try_from_integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+macro_rules! try_from_variant {
+    ($ty:ty, $variant:ident, $expected:literal) => {
+        impl TryFrom<&Value> for $ty {
+            type Error = Error;
+
+            fn try_from(value: &Value) -> Result<Self> {
+                match value {
+                    Value::$variant(inner) => Ok(inner.clone()),
+                    other => Err(mismatch($expected, other)),
+                }
+            }
+        }
+
+        impl TryFrom<Value> for $ty {
+            type Error = Error;
+
+            fn try_from(value: Value) -> Result<Self> {
+                match value {
+                    Value::$variant(inner) => Ok(inner),
+                    other => Err(mismatch($expected, &other)),
+                }
+            }
+        }
+    };
+}
+
+try_from_variant!(bool, Bool, "boolean");
+try_from_variant!(f64, Number, "number");
+try_from_variant!(String, String, "string");
+try_from_variant!(TomlDatetime, Datetime, "datetime");
+try_from_variant!(Array, Array, "array");
+try_from_variant!(Object, Object, "object");
+
+impl<T> TryFrom<Value> for Vec<T>
+where
+    T: TryFrom<Value, Error = Error>,
+{
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self> {
+        match value {
+            Value::Array(items) => items
+                .into_iter()
+                .enumerate()
+                .map(|(index, item)| T::try_from(item).map_err(|err| at(err, &index.to_string())))
+                .collect(),
+            other => Err(mismatch("array", &other)),
+        }
+    }
+}
+
+impl<'v, T> TryFrom<&'v Value> for Vec<T>
+where
+    T: TryFrom<&'v Value, Error = Error>,
+{
+    type Error = Error;
+
+    fn try_from(value: &'v Value) -> Result<Self> {
+        match value {
+            Value::Array(items) => items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| T::try_from(item).map_err(|err| at(err, &index.to_string())))
+                .collect(),
+            other => Err(mismatch("array", other)),
+        }
+    }
+}
+
+impl<K, T, S> TryFrom<Value> for HashMap<K, T, S>
+where
+    K: From<String> + Eq + Hash,
+    T: TryFrom<Value, Error = Error>,
+    S: BuildHasher + Default,
+{
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self> {
+        match value {
+            Value::Object(entries) => entries
+                .into_iter()
+                .map(|(key, item)| match T::try_from(item) {
+                    Ok(item) => Ok((K::from(key), item)),
+                    Err(err) => Err(at(err, &key)),
+                })
+                .collect(),
+            other => Err(mismatch("object", &other)),
+        }
+    }
+}
+
+impl<'v, K, T, S> TryFrom<&'v Value> for HashMap<K, T, S>
+where
+    K: From<String> + Eq + Hash,
+    T: TryFrom<&'v Value, Error = Error>,
+    S: BuildHasher + Default,
+{
+    type Error = Error;
+
+    fn try_from(value: &'v Value) -> Result<Self> {
+        match value {
+            Value::Object(entries) => entries
+                .iter()
+                .map(|(key, item)| match T::try_from(item) {
+                    Ok(item) => Ok((K::from(key.clone()), item)),
+                    Err(err) => Err(at(err, key)),
+                })
+                .collect(),
+            other => Err(mismatch("object", other)),
+        }
+    }
+}
+
+/// `n` as an integer if it has no fractional part
+fn whole(n: f64) -> Option<i128> {
+    if !n.is_finite() || n.fract() != 0.0 {
+        return None;
+    }
+    #[allow(clippy::as_conversions)]
+    // Saturates beyond i128, which every target type then rejects as out of range
+    let whole = n as i128;
+    Some(whole)
+}
+
+fn mismatch(expected: &str, found: &Value) -> Error {
+    Error::new(
+        ErrorKind::Expected {
+            expected: expected.to_string(),
+            found: describe(found),
+        },
+        Span::empty(),
+    )
+}
+
+/// The type of `value` and a short rendering of it
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Array(items) => format!("array of {} items", items.len()),
+        Value::Object(entries) => format!("object with {} keys", entries.len()),
+        scalar => {
+            let text = serialize_json(scalar, &FormatOptions::default());
+            let mut chars = text.chars();
+            let mut preview: String = chars.by_ref().take(PREVIEW_CHARS).collect();
+            if chars.next().is_some() {
+                preview.push('…');
+            }
+            format!("{} {preview}", scalar.type_name())
+        }
+    }
+}
+
+/// Prefix the message of `err` with the pointer token of the item it is about
+fn at(err: Error, token: &str) -> Error {
+    let token = escape_token(token);
+    err.map_message(|message| {
+        if message.starts_with('/') {
+            format!("/{token}{message}")
+        } else {
+            format!("/{token}: {message}")
+        }
+    })
+}
@@ -0,0 +1,79 @@
+//! Non-fatal diagnostics collected while parsing
+//!
+//! Parsers accept some input that is valid but probably unintended. Instead of
+//! failing, they record a [`Warning`] that callers can drain with
+//! `take_warnings()` once parsing is done.
+
+use std::fmt;
+
+use crate::error::Span;
+
+/// Largest magnitude below which every integer is exactly representable as f64
+#[cfg(feature = "toml")]
+const MAX_SAFE_INTEGER: u64 = 1 << 53;
+
+/// Warning kind for detailed categorization
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WarningKind {
+    /// A later object key replaced an earlier one (last wins)
+    DuplicateKey { key: String },
+    /// A later object key was dropped in favor of an earlier one (first wins)
+    IgnoredDuplicateKey { key: String },
+    /// A numeric literal cannot be represented exactly as f64
+    PrecisionLoss { literal: String },
+    /// A YAML tag was not understood and was dropped
+    IgnoredTag { tag: String },
+}
+
+impl fmt::Display for WarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateKey { key } => write!(f, "duplicate key {key:?}, last value wins"),
+            Self::IgnoredDuplicateKey { key } => {
+                write!(f, "duplicate key {key:?}, first value wins")
+            }
+            Self::PrecisionLoss { literal } => {
+                write!(f, "number {literal} loses precision as f64")
+            }
+            Self::IgnoredTag { tag } => write!(f, "ignored tag {tag}"),
+        }
+    }
+}
+
+/// A non-fatal parse diagnostic with its location
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Warning {
+    kind: WarningKind,
+    span: Span,
+}
+
+impl Warning {
+    pub const fn new(kind: WarningKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+
+    pub const fn kind(&self) -> &WarningKind {
+        &self.kind
+    }
+
+    pub const fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "warning at {}: {}", self.span.start, self.kind)
+    }
+}
+
+/// Returns true if converting `value` to f64 rounds it
+#[cfg(feature = "toml")]
+pub(crate) const fn i64_loses_precision(value: i64) -> bool {
+    let magnitude = value.unsigned_abs();
+    match magnitude.checked_shr(magnitude.trailing_zeros()) {
+        Some(mantissa) => mantissa > MAX_SAFE_INTEGER,
+        None => false,
+    }
+}
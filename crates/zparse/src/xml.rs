@@ -2,6 +2,9 @@
 
 pub mod model;
 pub mod parser;
+pub mod types;
+pub mod writer;
 
 pub use model::{Content, Document, Element};
-pub use parser::{Config, Parser};
+pub use parser::{Config, Parser, Whitespace};
+pub use types::{TextType, Types};
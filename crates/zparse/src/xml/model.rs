@@ -5,7 +5,23 @@ use indexmap::IndexMap;
 /// XML document
 #[derive(Clone, Debug, PartialEq)]
 pub struct Document {
+    /// Comments and processing instructions before the root element,
+    /// including the `<?xml ...?>` declaration
+    pub prolog: Vec<Content>,
     pub root: Element,
+    /// Comments and processing instructions after the root element
+    pub epilog: Vec<Content>,
+}
+
+impl Document {
+    /// A document holding only `root`
+    pub const fn new(root: Element) -> Self {
+        Self {
+            prolog: Vec::new(),
+            root,
+            epilog: Vec::new(),
+        }
+    }
 }
 
 /// XML element
@@ -21,4 +37,40 @@ pub struct Element {
 pub enum Content {
     Element(Element),
     Text(String),
+    /// A `<![CDATA[...]]>` section, written back as one
+    CData(String),
+    /// A `<!--...-->` comment
+    Comment(String),
+    /// A `<?target data?>` processing instruction
+    ProcessingInstruction {
+        target: String,
+        data: String,
+    },
+}
+
+impl Content {
+    /// The character data of a text node or CDATA section
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            Self::Text(text) | Self::CData(text) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+impl Drop for Element {
+    // Unnest children onto a heap stack so dropping a deeply nested tree
+    // cannot overflow the call stack
+    fn drop(&mut self) {
+        let nested = |content: &Content| matches!(content, Content::Element(element) if !element.children.is_empty());
+        if !self.children.iter().any(nested) {
+            return;
+        }
+        let mut stack = std::mem::take(&mut self.children);
+        while let Some(content) = stack.pop() {
+            if let Content::Element(mut element) = content {
+                stack.append(&mut element.children);
+            }
+        }
+    }
 }
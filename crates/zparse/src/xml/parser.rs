@@ -1,5 +1,7 @@
 //! XML parser implementation
 
+use std::borrow::Cow;
+
 use indexmap::IndexMap;
 
 use crate::error::{Error, ErrorKind, Pos, Result, Span};
@@ -7,27 +9,100 @@ use crate::lexer::Cursor;
 use crate::xml::model::{Content, Document, Element};
 
 pub const DEFAULT_MAX_SIZE: usize = 10 * 1024 * 1024;
+pub const DEFAULT_MAX_DEPTH: u16 = 128;
+
+/// What happens to the whitespace in text between tags.
+///
+/// Only literal spaces, tabs and line breaks count; whitespace written as a
+/// character reference (`&#32;`) is kept. Elements marked
+/// `xml:space="preserve"`, and the elements inside them, always keep their
+/// text as written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Whitespace {
+    /// Keep all text, including the indentation between elements
+    Preserve,
+    /// Drop text that is only whitespace, such as indentation, and keep other
+    /// text as written
+    #[default]
+    DropBlank,
+    /// Also trim the whitespace around text, so `<p>\n  hi\n</p>` holds `hi`
+    Trim,
+    /// Also collapse each run of whitespace inside text to one space
+    Collapse,
+}
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Config {
+    /// Maximum input size in bytes (0 means unlimited)
     pub max_size: usize,
+    /// Maximum element nesting depth (0 means unlimited)
+    pub max_depth: u16,
+    /// Keep comments as [`Content::Comment`] nodes instead of dropping them
+    pub comments: bool,
+    /// Keep processing instructions, including the XML declaration, as
+    /// [`Content::ProcessingInstruction`] nodes instead of dropping them
+    pub processing_instructions: bool,
+    /// Keep CDATA sections as [`Content::CData`] nodes instead of reading
+    /// them as text
+    pub cdata: bool,
+    /// Handling of whitespace in text; CDATA sections are always kept as is
+    pub whitespace: Whitespace,
 }
 
 impl Default for Config {
     fn default() -> Self {
-        Self {
-            max_size: DEFAULT_MAX_SIZE,
-        }
+        Self::new(DEFAULT_MAX_SIZE)
     }
 }
 
 impl Config {
     pub const fn new(max_size: usize) -> Self {
-        Self { max_size }
+        Self {
+            max_size,
+            max_depth: DEFAULT_MAX_DEPTH,
+            comments: true,
+            processing_instructions: true,
+            cdata: true,
+            whitespace: Whitespace::DropBlank,
+        }
     }
 
     pub const fn unlimited() -> Self {
-        Self { max_size: 0 }
+        Self {
+            max_size: 0,
+            max_depth: 0,
+            ..Self::new(0)
+        }
+    }
+
+    /// Set the maximum element nesting depth (0 means unlimited)
+    pub const fn with_max_depth(mut self, max_depth: u16) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Keep or drop comments
+    pub const fn with_comments(mut self, keep: bool) -> Self {
+        self.comments = keep;
+        self
+    }
+
+    /// Keep or drop processing instructions
+    pub const fn with_processing_instructions(mut self, keep: bool) -> Self {
+        self.processing_instructions = keep;
+        self
+    }
+
+    /// Keep CDATA sections, or read them as text
+    pub const fn with_cdata(mut self, keep: bool) -> Self {
+        self.cdata = keep;
+        self
+    }
+
+    /// Set the handling of whitespace in text
+    pub const fn with_whitespace(mut self, whitespace: Whitespace) -> Self {
+        self.whitespace = whitespace;
+        self
     }
 }
 
@@ -42,9 +117,7 @@ impl<'a> Parser<'a> {
     pub const fn new(input: &'a [u8]) -> Self {
         Self {
             cursor: Cursor::new(input),
-            config: Config {
-                max_size: DEFAULT_MAX_SIZE,
-            },
+            config: Config::new(DEFAULT_MAX_SIZE),
         }
     }
 
@@ -59,6 +132,18 @@ impl<'a> Parser<'a> {
         &self.config
     }
 
+    /// Check that the document is well-formed XML.
+    ///
+    /// The element tree is built to match closing tags and then dropped; use
+    /// [`Self::parse`] to keep it.
+    pub fn validate(&mut self) -> Result<()> {
+        self.parse().map(drop)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(format = "xml"))
+    )]
     pub fn parse(&mut self) -> Result<Document> {
         if self.config.max_size > 0 && self.cursor.remaining().len() > self.config.max_size {
             let pos = self.cursor.position();
@@ -73,86 +158,207 @@ impl<'a> Parser<'a> {
         }
 
         self.skip_whitespace();
-        let root = self.parse_element()?;
-        self.skip_whitespace();
-
-        if !self.cursor.is_eof() {
-            return Err(Error::at(
-                ErrorKind::InvalidToken,
-                self.cursor.position().offset,
-                self.cursor.position().line,
-                self.cursor.position().col,
-            ));
-        }
-
-        Ok(Document { root })
+        let mut prolog = Vec::new();
+        let mut open = Vec::new();
+        let root = self
+            .parse_root(&mut prolog, &mut open)
+            .map_err(|err| within(err, &open))?;
+        let epilog = self.parse_epilog()?;
+
+        Ok(Document {
+            prolog,
+            root,
+            epilog,
+        })
     }
 
-    fn parse_element(&mut self) -> Result<Element> {
-        self.expect_byte(b'<')?;
-
-        if self.cursor.current() == Some(b'?') {
-            self.skip_processing_instruction()?;
-            return self.parse_element();
-        }
-
-        if self.cursor.current() == Some(b'!') {
-            self.skip_declaration_or_comment()?;
-            return self.parse_element();
-        }
-
-        if self.cursor.current() == Some(b'/') {
-            return Err(self.error_here("unexpected closing tag"));
-        }
+    /// Read the root element and everything before it, keeping the comments
+    /// and processing instructions before it in `prolog`.
+    ///
+    /// Open elements are kept on an explicit stack rather than the call stack,
+    /// so nesting is bounded only by [`Config::max_depth`]. On error, `open`
+    /// holds the elements the failure is inside.
+    fn parse_root(
+        &mut self,
+        prolog: &mut Vec<Content>,
+        open: &mut Vec<Element>,
+    ) -> Result<Element> {
+        // Whether each open element preserves whitespace (`xml:space`)
+        let mut preserve: Vec<bool> = Vec::new();
+        loop {
+            if !open.is_empty() && self.cursor.current() != Some(b'<') {
+                if self.cursor.is_eof() {
+                    return Err(self.error_here("unterminated element"));
+                }
+                let whitespace = match preserve.last() {
+                    Some(true) => Whitespace::Preserve,
+                    _ => self.config.whitespace,
+                };
+                if let (Some(text), Some(parent)) = (self.parse_text(whitespace)?, open.last_mut())
+                {
+                    parent.children.push(Content::Text(text));
+                }
+                continue;
+            }
 
-        let name = self.parse_name()?;
-        let attributes = self.parse_attributes()?;
+            self.expect_byte(b'<')?;
+            match self.cursor.current() {
+                Some(b'!') if self.cursor.peek_bytes(8) == Some(b"![CDATA[") => {
+                    self.cursor.advance_by(8);
+                    let text = self.parse_cdata()?;
+                    let content = if self.config.cdata {
+                        Content::CData(text)
+                    } else {
+                        Content::Text(text)
+                    };
+                    match open.last_mut() {
+                        Some(parent) => parent.children.push(content),
+                        None => return Err(self.error_here("CDATA outside the root element")),
+                    }
+                    continue;
+                }
+                Some(b'?' | b'!') => {
+                    let misc = self.parse_misc()?;
+                    match open.last_mut() {
+                        Some(parent) => parent.children.extend(misc),
+                        None => {
+                            prolog.extend(misc);
+                            self.skip_whitespace();
+                        }
+                    }
+                    continue;
+                }
+                Some(b'/') => {
+                    self.cursor.advance();
+                    let start = self.cursor.position();
+                    let close_name = self.parse_name()?;
+                    let element = match open.pop() {
+                        Some(element) if element.name == close_name => {
+                            preserve.pop();
+                            element
+                        }
+                        other => {
+                            let message = match &other {
+                                Some(element) => format!(
+                                    "mismatched closing tag </{close_name}>, expected </{}>",
+                                    element.name
+                                ),
+                                None => format!("unexpected closing tag </{close_name}>"),
+                            };
+                            // Keep the element so the error names it
+                            open.extend(other);
+                            return Err(Error::with_message(
+                                ErrorKind::InvalidToken,
+                                Span::new(start, self.cursor.position()),
+                                message,
+                            ));
+                        }
+                    };
+                    self.skip_whitespace();
+                    self.expect_byte(b'>')?;
+                    match open.last_mut() {
+                        Some(parent) => parent.children.push(Content::Element(element)),
+                        None => return Ok(element),
+                    }
+                    continue;
+                }
+                _ => {}
+            }
 
-        if self.cursor.current() == Some(b'/') {
-            self.cursor.advance();
-            self.expect_byte(b'>')?;
-            return Ok(Element {
+            let start = self.cursor.position();
+            let name = self.parse_name()?;
+            let attributes = self.parse_attributes()?;
+            let element = Element {
                 name,
                 attributes,
                 children: Vec::new(),
-            });
-        }
-
-        self.expect_byte(b'>')?;
+            };
 
-        let mut children = Vec::new();
-        loop {
-            if self.cursor.current() == Some(b'<') && self.cursor.peek(1) == Some(b'/') {
-                self.cursor.advance_by(2);
-                let close_name = self.parse_name()?;
-                if close_name != name {
-                    return Err(self.error_here("mismatched closing tag"));
-                }
-                self.skip_whitespace();
+            if self.cursor.current() == Some(b'/') {
+                self.cursor.advance();
                 self.expect_byte(b'>')?;
-                break;
+                match open.last_mut() {
+                    Some(parent) => parent.children.push(Content::Element(element)),
+                    None => return Ok(element),
+                }
+                continue;
             }
 
-            if self.cursor.current() == Some(b'<') {
-                let child = self.parse_element()?;
-                children.push(Content::Element(child));
-                continue;
+            self.expect_byte(b'>')?;
+            let max = self.config.max_depth;
+            if max > 0 && open.len() >= usize::from(max) {
+                return Err(Error::at(
+                    ErrorKind::MaxDepthExceeded { max },
+                    start.offset,
+                    start.line,
+                    start.col,
+                ));
             }
+            let preserves = match element.attributes.get("xml:space").map(String::as_str) {
+                Some("preserve") => true,
+                Some("default") => false,
+                _ => preserve.last().copied().unwrap_or(false),
+            };
+            preserve.push(preserves);
+            open.push(element);
+        }
+    }
 
+    /// Read the comments and processing instructions after the root element
+    fn parse_epilog(&mut self) -> Result<Vec<Content>> {
+        let mut epilog = Vec::new();
+        loop {
+            self.skip_whitespace();
             if self.cursor.is_eof() {
-                return Err(self.error_here("unterminated element"));
+                return Ok(epilog);
             }
-
-            if let Some(text) = self.parse_text()? {
-                children.push(Content::Text(text));
+            match self.cursor.peek_bytes(2) {
+                Some(b"<?") | Some(b"<!") if self.cursor.peek_bytes(9) != Some(b"<![CDATA[") => {
+                    self.cursor.advance();
+                    epilog.extend(self.parse_misc()?);
+                }
+                _ => return Err(self.error_here("unexpected content after the root element")),
             }
         }
+    }
 
-        Ok(Element {
-            name,
-            attributes,
-            children,
-        })
+    /// Read a comment, processing instruction or declaration after its `<`,
+    /// returning the node for it if the config keeps it
+    fn parse_misc(&mut self) -> Result<Option<Content>> {
+        if self.cursor.current() == Some(b'?') {
+            self.cursor.advance();
+            let target = self.parse_name()?;
+            self.skip_whitespace();
+            let data = self.read_until(b"?>")?;
+            return Ok(self
+                .config
+                .processing_instructions
+                .then_some(Content::ProcessingInstruction { target, data }));
+        }
+
+        // cursor currently at '!'
+        if self.cursor.peek_bytes(3) == Some(b"!--") {
+            self.cursor.advance_by(3);
+            let comment = self.read_until(b"-->")?;
+            return Ok(self.config.comments.then_some(Content::Comment(comment)));
+        }
+
+        self.skip_until(b">")?;
+        Ok(None)
+    }
+
+    /// Read the text of a CDATA section, after its `<![CDATA[` opener
+    fn parse_cdata(&mut self) -> Result<String> {
+        let start = self.cursor.position();
+        while self.cursor.current().is_some() {
+            if self.cursor.peek_bytes(3) == Some(b"]]>") {
+                let text = bytes_to_string(self.cursor.slice_from(start.offset), start)?;
+                self.cursor.advance_by(3);
+                return Ok(text);
+            }
+            self.cursor.advance();
+        }
+        Err(self.error_here("unterminated markup"))
     }
 
     fn parse_attributes(&mut self) -> Result<IndexMap<String, String>> {
@@ -173,7 +379,7 @@ impl<'a> Parser<'a> {
             let value = self.parse_attribute_value()?;
 
             if attrs.contains_key(&name) {
-                return Err(self.error_here("duplicate attribute"));
+                return Err(self.error_here(&format!("duplicate attribute {name}")));
             }
             attrs.insert(name, value);
         }
@@ -189,13 +395,13 @@ impl<'a> Parser<'a> {
         };
         self.cursor.advance();
 
-        let start = self.cursor.pos();
+        let start = self.cursor.position();
         while let Some(b) = self.cursor.current() {
             if b == quote {
-                let raw = self.cursor.slice_from(start);
+                let raw = self.cursor.slice_from(start.offset);
                 self.cursor.advance();
-                let text = bytes_to_string(raw)?;
-                return decode_entities(&text);
+                let text = bytes_to_string(raw, start)?;
+                return decode_entities(&text, start);
             }
             self.cursor.advance();
         }
@@ -203,8 +409,10 @@ impl<'a> Parser<'a> {
         Err(self.error_here("unterminated attribute value"))
     }
 
-    fn parse_text(&mut self) -> Result<Option<String>> {
-        let start = self.cursor.pos();
+    /// Read text up to the next tag, handling its whitespace as `whitespace`
+    /// says; `None` when nothing is left of it
+    fn parse_text(&mut self, whitespace: Whitespace) -> Result<Option<String>> {
+        let start = self.cursor.position();
         while let Some(b) = self.cursor.current() {
             if b == b'<' {
                 break;
@@ -212,31 +420,44 @@ impl<'a> Parser<'a> {
             self.cursor.advance();
         }
 
-        let raw = self.cursor.slice_from(start);
-        let text = bytes_to_string(raw)?;
-        let text = decode_entities(&text)?;
+        let raw = self.cursor.slice_from(start.offset);
+        let text = bytes_to_string(raw, start)?;
+        // Decode as written first, so entity errors point into the source
+        let decoded = decode_entities(&text, start)?;
+
+        let handled = match whitespace {
+            Whitespace::Preserve => Cow::Borrowed(text.as_str()),
+            Whitespace::DropBlank if text.chars().all(is_xml_whitespace) => Cow::Borrowed(""),
+            Whitespace::DropBlank => Cow::Borrowed(text.as_str()),
+            Whitespace::Trim => Cow::Borrowed(text.trim_matches(is_xml_whitespace)),
+            Whitespace::Collapse => Cow::Owned(
+                text.split(is_xml_whitespace)
+                    .filter(|word| !word.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+        };
 
-        if text.trim().is_empty() {
+        if handled.is_empty() {
             Ok(None)
+        } else if handled == text {
+            Ok(Some(decoded))
         } else {
-            Ok(Some(text))
+            decode_entities(&handled, start).map(Some)
         }
     }
 
     fn parse_name(&mut self) -> Result<String> {
-        let start_pos = self.cursor.position();
-        let start = self.cursor.pos();
-
-        let Some(first) = self.cursor.current() else {
-            return Err(self.error_here("expected name"));
-        };
-        if !is_name_start(first) {
-            return Err(Error::at(
-                ErrorKind::InvalidToken,
-                start_pos.offset,
-                start_pos.line,
-                start_pos.col,
-            ));
+        let start = self.cursor.position();
+
+        match self.cursor.current() {
+            Some(first) if is_name_start(first) => {}
+            Some(first) => {
+                return Err(
+                    self.error_here(&format!("expected a name, found {:?}", char::from(first)))
+                );
+            }
+            None => return Err(self.error_here("expected a name")),
         }
 
         self.cursor.advance();
@@ -248,34 +469,16 @@ impl<'a> Parser<'a> {
             }
         }
 
-        let raw = self.cursor.slice_from(start);
-        bytes_to_string(raw)
+        let raw = self.cursor.slice_from(start.offset);
+        bytes_to_string(raw, start)
     }
 
-    fn skip_declaration_or_comment(&mut self) -> Result<()> {
-        // cursor currently at '!'
-        if self.cursor.peek(1) == Some(b'-') && self.cursor.peek(2) == Some(b'-') {
-            self.cursor.advance_by(3);
-            self.skip_until(b"-->")?;
-            return Ok(());
-        }
-
-        if self.cursor.peek(1) == Some(b'[')
-            && self.cursor.peek(2) == Some(b'C')
-            && self.cursor.peek(3) == Some(b'D')
-        {
-            self.cursor.advance_by(2);
-            self.skip_until(b"]]>")?;
-            return Ok(());
-        }
-
-        self.skip_until(b">")
-    }
-
-    fn skip_processing_instruction(&mut self) -> Result<()> {
-        // cursor currently at '?'
-        self.cursor.advance();
-        self.skip_until(b"?>")
+    /// The raw text up to `pattern`, moving past it
+    fn read_until(&mut self, pattern: &[u8]) -> Result<String> {
+        let start = self.cursor.position();
+        self.skip_until(pattern)?;
+        let raw = self.cursor.slice_from(start.offset);
+        bytes_to_string(raw.strip_suffix(pattern).unwrap_or(raw), start)
     }
 
     fn skip_until(&mut self, pattern: &[u8]) -> Result<()> {
@@ -294,7 +497,7 @@ impl<'a> Parser<'a> {
             self.cursor.advance();
             Ok(())
         } else {
-            Err(self.error_here("unexpected token"))
+            Err(self.error_here(&format!("expected '{}'", char::from(expected))))
         }
     }
 
@@ -318,38 +521,77 @@ impl<'a> Parser<'a> {
     }
 }
 
-fn bytes_to_string(bytes: &[u8]) -> Result<String> {
+/// The error `err` raised inside the `open` elements, with a frame naming them
+fn within(err: Error, open: &[Element]) -> Error {
+    if open.is_empty() {
+        return err;
+    }
+    let path: Vec<String> = open
+        .iter()
+        .map(|element| format!("<{}>", element.name))
+        .collect();
+    err.context(format!("while parsing {}", path.join("/")))
+}
+
+/// Position reached by reading `bytes` from `start`, counting columns in
+/// bytes as the cursor does
+fn pos_after(start: Pos, bytes: &[u8]) -> Pos {
+    bytes.iter().fold(start, |pos, byte| {
+        let offset = pos.offset.saturating_add(1);
+        match byte {
+            b'\n' => Pos::new(offset, pos.line.saturating_add(1), 1),
+            _ => Pos::new(offset, pos.line, pos.col.saturating_add(1)),
+        }
+    })
+}
+
+/// `bytes`, read from `start`, as a string
+fn bytes_to_string(bytes: &[u8], start: Pos) -> Result<String> {
     std::str::from_utf8(bytes)
         .map(|s| s.to_string())
-        .map_err(|_| {
+        .map_err(|err| {
+            let pos = pos_after(start, bytes.get(..err.valid_up_to()).unwrap_or_default());
+            let end = Pos::new(
+                pos.offset.saturating_add(1),
+                pos.line,
+                pos.col.saturating_add(1),
+            );
             Error::with_message(
                 ErrorKind::InvalidToken,
-                Span::empty(),
-                "invalid utf-8".to_string(),
+                Span::new(pos, end),
+                "invalid utf-8",
             )
         })
 }
 
+fn is_xml_whitespace(ch: char) -> bool {
+    matches!(ch, ' ' | '\t' | '\r' | '\n')
+}
+
 fn is_name_start(b: u8) -> bool {
-    matches!(b, b'A'..=b'Z' | b'a'..=b'z' | b'_' | b':')
+    // Bytes from 0x80 belong to non-ASCII characters, which XML allows in names
+    matches!(b, b'A'..=b'Z' | b'a'..=b'z' | b'_' | b':' | 0x80..=0xff)
 }
 
 fn is_name_char(b: u8) -> bool {
     is_name_start(b) || matches!(b, b'0'..=b'9' | b'-' | b'.')
 }
 
-fn decode_entities(input: &str) -> Result<String> {
+/// `input`, read from `start`, with its entity references replaced
+fn decode_entities(input: &str, start: Pos) -> Result<String> {
     let mut result = String::new();
-    let mut chars = input.chars().peekable();
-    while let Some(ch) = chars.next() {
+    let mut chars = input.char_indices();
+    while let Some((at, ch)) = chars.next() {
         if ch != '&' {
             result.push(ch);
             continue;
         }
 
         let mut entity = String::new();
-        for next in chars.by_ref() {
+        let mut end = input.len();
+        for (index, next) in chars.by_ref() {
             if next == ';' {
+                end = index.saturating_add(1);
                 break;
             }
             entity.push(next);
@@ -367,10 +609,15 @@ fn decode_entities(input: &str) -> Result<String> {
         match decoded {
             Some(ch) => result.push(ch),
             None => {
+                let entity_start = pos_after(start, input.get(..at).unwrap_or_default().as_bytes());
+                let entity_text = input.get(at..end).unwrap_or_default();
                 return Err(Error::with_message(
                     ErrorKind::InvalidToken,
-                    Span::empty(),
-                    "invalid xml entity".to_string(),
+                    Span::new(
+                        entity_start,
+                        pos_after(entity_start, entity_text.as_bytes()),
+                    ),
+                    format!("invalid xml entity {entity_text}"),
                 ));
             }
         }
@@ -0,0 +1,226 @@
+//! Types for XML text converted to other formats
+//!
+//! XML only has text, so `<port>8080</port>` becomes the string `"8080"`
+//! unless told otherwise. [`Types`] gives element text a type while
+//! converting: hints name the type of an element's text, and `infer` guesses
+//! numbers and booleans for the elements without a hint. Hinted text that
+//! does not fit its type fails the conversion with the element's name, so
+//! consumers can rely on the types they asked for.
+//!
+//! ```
+//! use zparse::{ConvertOptions, Format, Value, XmlTextType, XmlTypes};
+//!
+//! # fn main() -> zparse::Result<()> {
+//! let options = ConvertOptions {
+//!     xml_types: XmlTypes::new()
+//!         .with_hint("port", XmlTextType::Integer)
+//!         .with_hint("zip", XmlTextType::String)
+//!         .with_infer(true),
+//!     ..ConvertOptions::default()
+//! };
+//! let value = zparse::parse_with_options(
+//!     "<server><port>8080</port><zip>01234</zip><tls>true</tls></server>",
+//!     Format::Xml,
+//!     &options,
+//! )?;
+//! let text = |key: &str| value.get("server")?.get(key)?.get("#text").cloned();
+//! assert_eq!(text("port"), Some(Value::from(8080)));
+//! assert_eq!(text("zip"), Some(Value::from("01234")));
+//! assert_eq!(text("tls"), Some(Value::from(true)));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use indexmap::IndexMap;
+
+use crate::error::{Error, ErrorKind, Result, Span};
+use crate::value::Value;
+
+/// The type of an element's text
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextType {
+    /// Kept as a string, even when `infer` would guess otherwise
+    String,
+    /// A finite number
+    Number,
+    /// A number without a fractional part that fits 64 bits
+    Integer,
+    /// `true`/`false`, or `1`/`0` as in XML Schema
+    Bool,
+}
+
+impl TextType {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Number => "number",
+            Self::Integer => "integer",
+            Self::Bool => "bool",
+        }
+    }
+
+    fn value(self, text: &str) -> Option<Value> {
+        let trimmed = text.trim();
+        match self {
+            Self::String => Some(Value::String(text.to_string())),
+            Self::Number => trimmed
+                .parse::<f64>()
+                .ok()
+                .filter(|number| number.is_finite())
+                .map(Value::Number),
+            Self::Integer => trimmed.parse::<i64>().ok().map(Value::from),
+            Self::Bool => match trimmed {
+                "true" | "1" => Some(Value::Bool(true)),
+                "false" | "0" => Some(Value::Bool(false)),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl fmt::Display for TextType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// How the text of XML elements is typed when converting
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Types {
+    /// Guess numbers and booleans for elements without a hint
+    pub infer: bool,
+    /// The type of the text of elements with these names
+    pub hints: IndexMap<String, TextType>,
+}
+
+impl Types {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Guess the types of elements without a hint
+    pub fn with_infer(mut self, enabled: bool) -> Self {
+        self.infer = enabled;
+        self
+    }
+
+    /// Type the text of elements named `element`
+    pub fn with_hint(mut self, element: impl Into<String>, text_type: TextType) -> Self {
+        self.hints.insert(element.into(), text_type);
+        self
+    }
+
+    /// The hint for elements named `element`
+    pub fn hint(&self, element: &str) -> Option<TextType> {
+        self.hints.get(element).copied()
+    }
+
+    /// The value of `text` in an element named `element`
+    pub fn text_value(&self, element: &str, text: &str) -> Result<Value> {
+        match self.hint(element) {
+            Some(text_type) => text_type.value(text).ok_or_else(|| {
+                Error::with_message(
+                    ErrorKind::Expected {
+                        expected: text_type.to_string(),
+                        found: text.trim().to_string(),
+                    },
+                    Span::empty(),
+                    format!(
+                        "<{element}> holds {:?}, not a valid {text_type}",
+                        text.trim()
+                    ),
+                )
+            }),
+            None if self.infer => {
+                Ok(infer(text).unwrap_or_else(|| Value::String(text.to_string())))
+            }
+            None => Ok(Value::String(text.to_string())),
+        }
+    }
+}
+
+/// `port=integer,debug=bool`: elements and the types of their text
+impl FromStr for Types {
+    type Err = Error;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        let invalid =
+            |message: String| Error::with_message(ErrorKind::InvalidToken, Span::empty(), message);
+        let mut types = Self::new();
+        for entry in spec
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (element, text_type) = entry
+                .split_once('=')
+                .ok_or_else(|| invalid(format!("expected ELEMENT=TYPE, found {entry:?}")))?;
+            let text_type = match text_type.trim() {
+                "string" => TextType::String,
+                "number" => TextType::Number,
+                "integer" => TextType::Integer,
+                "bool" => TextType::Bool,
+                other => {
+                    return Err(invalid(format!(
+                        "unknown type {other:?} (known: string, number, integer, bool)"
+                    )));
+                }
+            };
+            types = types.with_hint(element.trim(), text_type);
+        }
+        Ok(types)
+    }
+}
+
+/// Booleans and numbers written as JSON would write them. Anything else,
+/// including `007`, `+1` and `1.`, stays a string: such text is more often a
+/// code than a number.
+fn infer(text: &str) -> Option<Value> {
+    let trimmed = text.trim();
+    match trimmed {
+        "true" => return Some(Value::Bool(true)),
+        "false" => return Some(Value::Bool(false)),
+        _ => {}
+    }
+    if !is_json_number(trimmed) {
+        return None;
+    }
+    trimmed
+        .parse::<f64>()
+        .ok()
+        .filter(|number| number.is_finite())
+        .map(Value::Number)
+}
+
+fn is_json_number(text: &str) -> bool {
+    fn digits(bytes: &mut std::iter::Peekable<std::str::Bytes<'_>>) -> usize {
+        let mut count = 0;
+        while bytes.next_if(u8::is_ascii_digit).is_some() {
+            count += 1;
+        }
+        count
+    }
+
+    let mut bytes = text.bytes().peekable();
+    bytes.next_if_eq(&b'-');
+    match bytes.next() {
+        Some(b'0') => {}
+        Some(b'1'..=b'9') => {
+            digits(&mut bytes);
+        }
+        _ => return false,
+    }
+    if bytes.next_if_eq(&b'.').is_some() && digits(&mut bytes) == 0 {
+        return false;
+    }
+    if bytes.next_if(|byte| matches!(byte, b'e' | b'E')).is_some() {
+        bytes.next_if(|byte| matches!(byte, b'+' | b'-'));
+        if digits(&mut bytes) == 0 {
+            return false;
+        }
+    }
+    bytes.next().is_none()
+}
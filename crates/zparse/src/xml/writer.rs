@@ -0,0 +1,265 @@
+//! XML writer
+//!
+//! Writes a [`Document`] back as XML, with its CDATA sections, comments and
+//! processing instructions where they were read, so a document parsed and
+//! written again keeps them. Elements are written compactly, without added
+//! whitespace, unless [`FormatOptions::pretty`] is set: then an element
+//! holding only elements, comments and processing instructions puts each on
+//! a line of its own, indented by two spaces per level. Elements with text or
+//! CDATA, and those marked `xml:space="preserve"`, are still written
+//! compactly with everything inside them, so their content reads back
+//! unchanged.
+//!
+//! Text and attribute values are escaped for where they appear: text escapes
+//! `&`, `<` and `>`, and attribute values also the quote character in use and
+//! the tab and line breaks a reader would otherwise normalize to spaces.
+//! Characters XML 1.0 cannot hold at all, such as most control characters,
+//! fail with [`ErrorKind::UnsupportedValue`] rather than being written into a
+//! document no reader accepts.
+
+use crate::convert::{FormatOptions, XmlQuote};
+use crate::error::{Error, ErrorKind, Result, Span};
+use crate::xml::model::{Content, Document, Element};
+
+/// Write `doc` as XML with default options, see [`to_string_with_options`]
+pub fn to_string(doc: &Document) -> Result<String> {
+    to_string_with_options(doc, &FormatOptions::default())
+}
+
+/// Write `doc` as XML, quoting attributes with `options.xml_quote` and
+/// writing non-ASCII text as references with `options.xml_ascii`. The prolog
+/// and epilog go on lines of their own; with `options.pretty`, nested elements
+/// are indented.
+pub fn to_string_with_options(doc: &Document, options: &FormatOptions) -> Result<String> {
+    let mut writer = Writer {
+        output: String::new(),
+        options,
+    };
+    for content in &doc.prolog {
+        writer.write_misc(content, &doc.root.name)?;
+        writer.output.push('\n');
+    }
+    writer.write_element(&doc.root)?;
+    for content in &doc.epilog {
+        writer.output.push('\n');
+        writer.write_misc(content, &doc.root.name)?;
+    }
+    Ok(writer.output)
+}
+
+/// Where a string is written, which decides what it escapes
+#[derive(Clone, Copy)]
+enum Context {
+    Text,
+    Attribute(char),
+}
+
+struct Writer<'o> {
+    output: String,
+    options: &'o FormatOptions,
+}
+
+impl Writer<'_> {
+    /// Write an element and its content, keeping open elements on an
+    /// explicit stack
+    fn write_element(&mut self, root: &Element) -> Result<()> {
+        let mut stack = Vec::new();
+        self.open_element(root, true, &mut stack)?;
+
+        while let Some((element, block, next)) = stack
+            .last_mut()
+            .map(|(element, block, children)| (*element, *block, children.next()))
+        {
+            match next {
+                Some(Content::Text(text)) if block && is_whitespace(text) => {}
+                Some(content) => {
+                    if block {
+                        self.new_line(stack.len());
+                    }
+                    match content {
+                        Content::Element(child) => self.open_element(child, block, &mut stack)?,
+                        other => self.write_misc(other, &element.name)?,
+                    }
+                }
+                None => {
+                    stack.pop();
+                    if block {
+                        self.new_line(stack.len());
+                    }
+                    self.output.push_str("</");
+                    self.output.push_str(&element.name);
+                    self.output.push('>');
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the start tag of `element`, or the whole element when it is
+    /// empty. Its children go on lines of their own only when its parent's
+    /// did too, so no whitespace is added inside text.
+    fn open_element<'d>(
+        &mut self,
+        element: &'d Element,
+        parent_block: bool,
+        stack: &mut Vec<(&'d Element, bool, std::slice::Iter<'d, Content>)>,
+    ) -> Result<()> {
+        let quote = match self.options.xml_quote {
+            XmlQuote::Double => '"',
+            XmlQuote::Single => '\'',
+        };
+        self.output.push('<');
+        self.output.push_str(&element.name);
+
+        for (key, value) in element.attributes.iter() {
+            self.output.push(' ');
+            self.output.push_str(key);
+            self.output.push('=');
+            self.output.push(quote);
+            self.escape(value, Context::Attribute(quote), &element.name)?;
+            self.output.push(quote);
+        }
+
+        if element.children.is_empty() {
+            self.output.push_str("/>");
+        } else {
+            self.output.push('>');
+            let block = parent_block && self.is_block(element);
+            stack.push((element, block, element.children.iter()));
+        }
+        Ok(())
+    }
+
+    /// Whether the children of `element` can go on lines of their own: only
+    /// when pretty printing, nothing but whitespace would sit between them and
+    /// the element does not ask for its whitespace to be kept
+    fn is_block(&self, element: &Element) -> bool {
+        self.options.pretty
+            && element.attributes.get("xml:space").map(String::as_str) != Some("preserve")
+            && element.children.iter().all(|content| match content {
+                Content::Text(text) => is_whitespace(text),
+                Content::CData(_) => false,
+                _ => true,
+            })
+    }
+
+    /// Start a line indented for `depth` levels of nesting
+    fn new_line(&mut self, depth: usize) {
+        self.output.push('\n');
+        for _ in 0..depth {
+            self.output.push_str("  ");
+        }
+    }
+
+    /// Write a node other than an element inside `parent`. Content the markup
+    /// cannot hold as is (`]]>` in CDATA, `--` in a comment, `?>` in an
+    /// instruction) is split so the output stays well-formed.
+    fn write_misc(&mut self, content: &Content, parent: &str) -> Result<()> {
+        match content {
+            Content::Element(element) => self.write_element(element)?,
+            Content::Text(text) => self.escape(text, Context::Text, parent)?,
+            Content::CData(text) => {
+                check_chars(text, parent)?;
+                self.output.push_str("<![CDATA[");
+                for ch in text.replace("]]>", "]]]]><![CDATA[>").chars() {
+                    if self.options.xml_ascii && !ch.is_ascii() {
+                        // References are not read inside CDATA, so step out
+                        self.output
+                            .push_str(&format!("]]>&#x{:X};<![CDATA[", u32::from(ch)));
+                    } else {
+                        self.output.push(ch);
+                    }
+                }
+                self.output.push_str("]]>");
+            }
+            Content::Comment(comment) => {
+                check_chars(comment, parent)?;
+                let mut comment = comment.clone();
+                while comment.contains("--") {
+                    comment = comment.replace("--", "- -");
+                }
+                if comment.ends_with('-') {
+                    comment.push(' ');
+                }
+                self.output.push_str("<!--");
+                self.output.push_str(&comment);
+                self.output.push_str("-->");
+            }
+            Content::ProcessingInstruction { target, data } => {
+                check_chars(data, parent)?;
+                self.output.push_str("<?");
+                self.output.push_str(target);
+                if !data.is_empty() {
+                    self.output.push(' ');
+                    self.output.push_str(&data.replace("?>", "? >"));
+                }
+                self.output.push_str("?>");
+            }
+        }
+        Ok(())
+    }
+
+    /// Write `input` escaped for `context` inside the element `parent`
+    fn escape(&mut self, input: &str, context: Context, parent: &str) -> Result<()> {
+        for ch in input.chars() {
+            match (ch, context) {
+                ('&', _) => self.output.push_str("&amp;"),
+                ('<', _) => self.output.push_str("&lt;"),
+                ('>', _) => self.output.push_str("&gt;"),
+                ('\r', _) => self.output.push_str("&#13;"),
+                ('\t', Context::Attribute(_)) => self.output.push_str("&#9;"),
+                ('\n', Context::Attribute(_)) => self.output.push_str("&#10;"),
+                ('"', Context::Attribute('"')) => self.output.push_str("&quot;"),
+                ('\'', Context::Attribute('\'')) => self.output.push_str("&apos;"),
+                _ if !is_xml_char(ch) => return Err(unsupported(ch, parent)),
+                _ if self.options.xml_ascii && !ch.is_ascii() => {
+                    self.output.push_str(&format!("&#x{:X};", u32::from(ch)));
+                }
+                _ => self.output.push(ch),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether `text` is only XML whitespace, which pretty output replaces with
+/// its own indentation
+fn is_whitespace(text: &str) -> bool {
+    text.chars()
+        .all(|ch| matches!(ch, ' ' | '\t' | '\r' | '\n'))
+}
+
+/// Whether XML 1.0 can hold `ch`, as text or as a character reference
+const fn is_xml_char(ch: char) -> bool {
+    matches!(ch, '\t' | '\n' | '\r' | ' '..='\u{D7FF}' | '\u{E000}'..='\u{FFFD}' | '\u{10000}'..)
+}
+
+fn check_chars(input: &str, parent: &str) -> Result<()> {
+    match input.chars().find(|ch| !is_xml_char(*ch)) {
+        Some(ch) => Err(unsupported(ch, parent)),
+        None => Ok(()),
+    }
+}
+
+fn unsupported(ch: char, element: &str) -> Error {
+    Error::with_message(
+        ErrorKind::UnsupportedValue {
+            path: format!("<{element}>"),
+        },
+        Span::empty(),
+        format!(
+            "<{element}> holds U+{:04X}, which XML 1.0 cannot represent",
+            u32::from(ch)
+        ),
+    )
+}
+
+/// `input` escaped as element text, for writers that build their own markup
+pub(crate) fn escape(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -2,6 +2,9 @@
 
 pub mod event;
 pub mod parser;
+pub mod style;
+mod timestamp;
 
 pub use event::Event;
-pub use parser::{Config, Parser};
+pub use parser::{Config, DuplicateKeys, Parser, Quote, Quotes, Schema, resolve_scalar};
+pub use style::{Indentation, detect_indentation};
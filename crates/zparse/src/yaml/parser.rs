@@ -1,32 +1,122 @@
 //! YAML streaming parser implementation
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 
 use crate::error::{Error, ErrorKind, Result, Span};
+use crate::event::push_events;
 use crate::lexer::yaml::{YamlLexer, YamlToken, YamlTokenKind};
+use crate::locate::escape_token;
 use crate::value::{Array, Object, Value};
+use crate::warning::{Warning, WarningKind};
 use crate::yaml::event::Event;
+use crate::yaml::timestamp;
 
 pub const DEFAULT_MAX_DEPTH: u16 = 128;
 
+/// What a mapping that repeats a key does
+///
+/// YAML forbids duplicate keys, but templated files (Helm charts, generated
+/// Kubernetes manifests) often repeat one, and other tools quietly keep a
+/// value. The lenient policies accept such files and record a
+/// [`Warning`] for each repeat.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Fail with [`ErrorKind::DuplicateKey`]
+    #[default]
+    Error,
+    /// Keep the first value
+    FirstWins,
+    /// Keep the last value, as JSON does
+    LastWins,
+}
+
+/// The quotes around a scalar in the source
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Quote {
+    /// `'...'`
+    Single,
+    /// `"..."`
+    Double,
+}
+
+/// The quoted keys and strings of a document, by JSON pointer of the value
+/// (for a key, the value it names)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Quotes {
+    pub keys: HashMap<String, Quote>,
+    pub strings: HashMap<String, Quote>,
+}
+
+/// Which plain scalars are null, booleans and numbers rather than strings.
+/// Quoted scalars are always strings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Schema {
+    /// The YAML 1.2 core schema: `null` and `~`, `true`/`false` (also
+    /// capitalized or upper case), decimal, `0o` octal and `0x` hex
+    /// integers, floats, `.inf` and `.nan`
+    #[default]
+    Core,
+    /// YAML 1.1 as most older tools read it: also `yes`/`no`/`on`/`off`,
+    /// `0b` binary and `0`-prefixed octal integers, `_` between digits and
+    /// base-60 integers like `1:30`; floats need a `.`
+    Yaml11,
+    /// The YAML 1.2 JSON schema: only `null`, `true`, `false` and numbers
+    /// as JSON writes them
+    Json,
+}
+
 /// Configuration for YAML parser
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Config {
     /// Maximum nesting depth (0 means unlimited)
     pub max_depth: u16,
+    /// What a repeated mapping key does
+    pub duplicate_keys: DuplicateKeys,
+    /// How plain scalars resolve to types
+    pub schema: Schema,
+    /// Read plain timestamps such as `2001-12-14` or `2001-12-14 21:59:43Z`
+    /// as datetimes, the kind TOML writes as dates and times, instead of
+    /// strings
+    pub timestamps: bool,
 }
 
 impl Default for Config {
     fn default() -> Self {
-        Self {
-            max_depth: DEFAULT_MAX_DEPTH,
-        }
+        Self::new(DEFAULT_MAX_DEPTH)
     }
 }
 
 impl Config {
     pub const fn new(max_depth: u16) -> Self {
-        Self { max_depth }
+        Self {
+            max_depth,
+            duplicate_keys: DuplicateKeys::Error,
+            schema: Schema::Core,
+            timestamps: false,
+        }
+    }
+
+    /// Create a new config with unlimited depth
+    pub const fn unlimited() -> Self {
+        Self::new(0)
+    }
+
+    /// Set what a repeated mapping key does
+    pub const fn with_duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> Self {
+        self.duplicate_keys = duplicate_keys;
+        self
+    }
+
+    /// Set how plain scalars resolve to types
+    pub const fn with_schema(mut self, schema: Schema) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    /// Set whether plain timestamps are read as datetimes
+    pub const fn with_timestamps(mut self, enabled: bool) -> Self {
+        self.timestamps = enabled;
+        self
     }
 }
 
@@ -39,6 +129,11 @@ pub struct Parser<'a> {
     depth: u16,
     events: VecDeque<Event>,
     parsed_once: bool,
+    /// JSON pointer segments of the value being parsed while recording key lines
+    path: Vec<String>,
+    key_lines: Option<Vec<(String, u32)>>,
+    quotes: Option<Quotes>,
+    warnings: Vec<Warning>,
 }
 
 impl<'a> Parser<'a> {
@@ -56,10 +151,18 @@ impl<'a> Parser<'a> {
             depth: 0,
             events: VecDeque::new(),
             parsed_once: false,
+            path: Vec::new(),
+            key_lines: None,
+            quotes: None,
+            warnings: Vec::new(),
         }
     }
 
     /// Parse entire document
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(format = "yaml"))
+    )]
     pub fn parse(&mut self) -> Result<Value> {
         self.parsed_once = true;
 
@@ -71,6 +174,104 @@ impl<'a> Parser<'a> {
         self.parse_block()
     }
 
+    /// Check that the entire document is valid YAML.
+    ///
+    /// Indentation decides where each block ends, so the parser builds the
+    /// document as it reads and drops it here; use [`Self::parse`] to keep it.
+    pub fn validate(&mut self) -> Result<()> {
+        self.parse().map(drop)
+    }
+
+    /// Drain the non-fatal warnings recorded so far
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        let mut warnings = self.lexer.take_warnings();
+        warnings.append(&mut self.warnings);
+        warnings.sort_by_key(|warning| warning.span().start.offset);
+        warnings
+    }
+
+    /// Record the line of every mapping key, by JSON pointer, while parsing
+    pub(crate) fn record_key_lines(&mut self) {
+        self.key_lines = Some(Vec::new());
+    }
+
+    /// Key lines recorded since [`Self::record_key_lines`]
+    pub(crate) fn take_key_lines(&mut self) -> Vec<(String, u32)> {
+        self.key_lines.take().unwrap_or_default()
+    }
+
+    /// Record the quotes of every quoted key and string while parsing
+    #[cfg(feature = "lossless")]
+    pub(crate) fn record_quotes(&mut self) {
+        self.quotes = Some(Quotes::default());
+    }
+
+    /// Quotes recorded since [`Self::record_quotes`]
+    #[cfg(feature = "lossless")]
+    pub(crate) fn take_quotes(&mut self) -> Quotes {
+        self.quotes.take().unwrap_or_default()
+    }
+
+    /// Whether the path of the value being parsed is kept
+    const fn tracks_path(&self) -> bool {
+        self.key_lines.is_some() || self.quotes.is_some()
+    }
+
+    /// The quote opening the scalar at `span`, if it has one
+    fn quote_at(&self, span: Span) -> Option<Quote> {
+        match self.lexer.byte_at(span.start.offset)? {
+            b'\'' => Some(Quote::Single),
+            b'"' => Some(Quote::Double),
+            _ => None,
+        }
+    }
+
+    fn enter_key(&mut self, key: &str, span: Span) {
+        if !self.tracks_path() {
+            return;
+        }
+        self.path.push(format!("/{}", escape_token(key)));
+        let pointer = self.path.concat();
+        let quote = self.quote_at(span);
+        if let Some(lines) = &mut self.key_lines {
+            lines.push((pointer.clone(), span.start.line));
+        }
+        if let (Some(quotes), Some(quote)) = (&mut self.quotes, quote) {
+            quotes.keys.insert(pointer, quote);
+        }
+    }
+
+    fn enter_index(&mut self, index: usize) {
+        if self.tracks_path() {
+            self.path.push(format!("/{index}"));
+        }
+    }
+
+    fn leave(&mut self) {
+        if self.tracks_path() {
+            self.path.pop();
+        }
+    }
+
+    /// The value of a plain scalar under the configured schema
+    fn plain(&self, value: &str) -> Value {
+        if self.config.timestamps
+            && let Some(datetime) = timestamp::parse(value.trim())
+        {
+            return Value::Datetime(datetime);
+        }
+        resolve_scalar(value, self.config.schema)
+    }
+
+    /// The value of a quoted scalar, recording its quotes at the current path
+    fn quoted(&mut self, value: String, span: Span) -> Value {
+        let quote = self.quote_at(span);
+        if let (Some(quotes), Some(quote)) = (&mut self.quotes, quote) {
+            quotes.strings.insert(self.path.concat(), quote);
+        }
+        Value::String(value)
+    }
+
     /// Get next event
     pub fn next_event(&mut self) -> Result<Option<Event>> {
         if let Some(event) = self.events.pop_front() {
@@ -126,15 +327,59 @@ impl<'a> Parser<'a> {
         Ok(token)
     }
 
+    /// Parse a block value.
+    ///
+    /// Enclosing collections wait on an explicit stack of [`Block`]s rather
+    /// than the call stack, so nesting is bounded only by
+    /// [`Config::max_depth`].
     fn parse_block(&mut self) -> Result<Value> {
+        let mut stack = Vec::new();
+        let mut next = Next::Block;
+        loop {
+            next = match next {
+                Next::Block => self.start_block(&mut stack)?,
+                Next::Item => self.start_sequence_item(&mut stack)?,
+                Next::Entry(mapping, key) => self.start_mapping_entry(mapping, key, &mut stack)?,
+                Next::Value(value) => match stack.pop() {
+                    None => return Ok(value),
+                    Some(Block::Sequence(mut items)) => {
+                        self.leave();
+                        items.push(value);
+                        self.next_sequence_item(items, &mut stack)?
+                    }
+                    Some(Block::Entry(mapping, key, key_span)) => {
+                        self.finish_mapping_entry(mapping, key, key_span, value, &mut stack)?
+                    }
+                    Some(Block::Dedent) => {
+                        let end = self.next_non_newline()?;
+                        if end.kind != YamlTokenKind::Dedent {
+                            self.buffered = Some(end);
+                        }
+                        Next::Value(value)
+                    }
+                },
+            };
+        }
+    }
+
+    fn start_block(&mut self, stack: &mut Vec<Block>) -> Result<Next> {
         let token = self.peek_non_newline()?;
         match token.kind {
-            YamlTokenKind::Dash => self.parse_sequence(),
-            YamlTokenKind::Scalar(_) | YamlTokenKind::QuotedScalar(_) => {
-                self.parse_mapping_or_scalar()
+            YamlTokenKind::Dash => {
+                self.bump_depth(token.span)?;
+                self.next_sequence_item(Vec::new(), stack)
+            }
+            YamlTokenKind::Scalar(_)
+            | YamlTokenKind::QuotedScalar(_)
+            | YamlTokenKind::Binary(_) => self.start_mapping_or_scalar(),
+            YamlTokenKind::LeftBracket => {
+                self.next_non_newline()?;
+                Ok(Next::Value(self.parse_flow_sequence(token.span)?))
+            }
+            YamlTokenKind::LeftBrace => {
+                self.next_non_newline()?;
+                Ok(Next::Value(self.parse_flow_mapping(token.span)?))
             }
-            YamlTokenKind::LeftBracket => self.parse_flow_sequence(token.span),
-            YamlTokenKind::LeftBrace => self.parse_flow_mapping(token.span),
             _ => Err(Error::with_message(
                 ErrorKind::InvalidToken,
                 token.span,
@@ -143,139 +388,101 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_sequence(&mut self) -> Result<Value> {
-        let opening = self.peek_non_newline()?;
-        self.bump_depth(opening.span)?;
-        let mut items = Vec::new();
-
-        loop {
-            let token = self.next_non_newline()?;
-            match token.kind {
-                YamlTokenKind::Dash => {
-                    let value = self.parse_sequence_item()?;
-                    items.push(value);
-                }
-                YamlTokenKind::Dedent => {
-                    self.buffered = Some(token);
-                    break;
-                }
-                YamlTokenKind::Eof => break,
-                _ => {
-                    self.buffered = Some(token);
-                    break;
-                }
-            }
-
-            let next = self.peek_non_newline()?;
-            match next.kind {
-                YamlTokenKind::Dash => continue,
-                YamlTokenKind::Dedent | YamlTokenKind::Eof => break,
-                _ => break,
-            }
+    /// Start the next item of a sequence at its dash, or close the sequence
+    fn next_sequence_item(&mut self, items: Vec<Value>, stack: &mut Vec<Block>) -> Result<Next> {
+        if self.peek_non_newline()?.kind == YamlTokenKind::Dash {
+            self.next_non_newline()?;
+            self.enter_index(items.len());
+            stack.push(Block::Sequence(items));
+            return Ok(Next::Item);
         }
-
         self.depth = self.depth.saturating_sub(1);
-        Ok(Value::Array(Array(items)))
+        Ok(Next::Value(Value::Array(Array(items))))
     }
 
-    fn parse_sequence_item(&mut self) -> Result<Value> {
+    fn start_sequence_item(&mut self, stack: &mut Vec<Block>) -> Result<Next> {
         let token = self.next_token()?;
-        match token.kind {
+        let value = match token.kind {
             YamlTokenKind::Newline => {
                 let next = self.next_non_newline()?;
-                match next.kind {
-                    YamlTokenKind::Indent => {
-                        let value = self.parse_block()?;
-                        let end = self.next_non_newline()?;
-                        if end.kind != YamlTokenKind::Dedent {
-                            self.buffered = Some(end);
-                        }
-                        Ok(value)
-                    }
-                    YamlTokenKind::Dedent => {
-                        self.buffered = Some(next);
-                        Ok(Value::Null)
-                    }
-                    _ => {
-                        self.buffered = Some(next);
-                        Ok(Value::Null)
-                    }
+                if next.kind == YamlTokenKind::Indent {
+                    stack.push(Block::Dedent);
+                    return Ok(Next::Block);
                 }
+                self.buffered = Some(next);
+                Value::Null
             }
-            YamlTokenKind::Scalar(value) => {
-                let peek = self.peek_token()?;
-                if peek.kind == YamlTokenKind::Colon {
-                    let obj = self.parse_mapping_entries(Some(value))?;
-                    Ok(Value::Object(obj))
-                } else {
-                    Ok(parse_scalar_value(&value))
-                }
+            YamlTokenKind::Scalar(value) | YamlTokenKind::QuotedScalar(value)
+                if self.peek_token()?.kind == YamlTokenKind::Colon =>
+            {
+                // A `- key: value` item; more keys may follow on the next
+                // lines, indented past the dash
+                self.bump_depth(token.span)?;
+                let mapping = Mapping::new(MappingKind::ItemFirst);
+                return Ok(Next::Entry(mapping, Some((value, token.span))));
             }
-            YamlTokenKind::QuotedScalar(value) => Ok(Value::String(value)),
-            YamlTokenKind::LeftBracket => self.parse_flow_sequence(token.span),
-            YamlTokenKind::LeftBrace => self.parse_flow_mapping(token.span),
+            YamlTokenKind::Scalar(value) => self.plain(&value),
+            YamlTokenKind::QuotedScalar(value) => self.quoted(value, token.span),
+            YamlTokenKind::Binary(bytes) => Value::Bytes(bytes),
+            YamlTokenKind::LeftBracket => self.parse_flow_sequence(token.span)?,
+            YamlTokenKind::LeftBrace => self.parse_flow_mapping(token.span)?,
             YamlTokenKind::Indent => {
-                let value = self.parse_block()?;
-                let end = self.next_non_newline()?;
-                if end.kind != YamlTokenKind::Dedent {
-                    self.buffered = Some(end);
-                }
-                Ok(value)
+                stack.push(Block::Dedent);
+                return Ok(Next::Block);
             }
-            _ => Err(Error::with_message(
-                ErrorKind::InvalidToken,
-                token.span,
-                "invalid sequence item".to_string(),
-            )),
-        }
-    }
-
-    fn parse_mapping(&mut self, opening_span: Span) -> Result<Value> {
-        self.bump_depth(opening_span)?;
-        let obj = self.parse_mapping_entries(None)?;
-        self.depth = self.depth.saturating_sub(1);
-        Ok(Value::Object(obj))
+            _ => {
+                return Err(Error::with_message(
+                    ErrorKind::InvalidToken,
+                    token.span,
+                    "invalid sequence item".to_string(),
+                ));
+            }
+        };
+        Ok(Next::Value(value))
     }
 
-    fn parse_mapping_or_scalar(&mut self) -> Result<Value> {
+    fn start_mapping_or_scalar(&mut self) -> Result<Next> {
         let first = self.next_non_newline()?;
-        match first.kind {
+        let value = match first.kind {
             YamlTokenKind::Scalar(value) => {
-                let next = self.peek_token()?;
-                if next.kind == YamlTokenKind::Colon {
-                    let obj = self.parse_mapping_entries(Some(value))?;
-                    Ok(Value::Object(obj))
-                } else {
-                    Ok(parse_scalar_value(&value))
+                if self.peek_token()?.kind == YamlTokenKind::Colon {
+                    self.bump_depth(first.span)?;
+                    let mapping = Mapping::new(MappingKind::Block);
+                    return Ok(Next::Entry(mapping, Some((value, first.span))));
                 }
+                self.plain(&value)
             }
-            YamlTokenKind::QuotedScalar(value) => Ok(Value::String(value)),
+            YamlTokenKind::QuotedScalar(value) => self.quoted(value, first.span),
+            YamlTokenKind::Binary(bytes) => Value::Bytes(bytes),
             _ => {
                 let first_span = first.span;
                 self.buffered = Some(first);
-                self.parse_mapping(first_span)
+                self.bump_depth(first_span)?;
+                return Ok(Next::Entry(Mapping::new(MappingKind::Block), None));
             }
-        }
+        };
+        Ok(Next::Value(value))
     }
 
-    fn parse_mapping_entries(&mut self, first_key: Option<String>) -> Result<Object> {
-        let mut obj = Object::new();
-        let mut pending_key = first_key;
-
-        loop {
-            let key = if let Some(key) = pending_key.take() {
-                key
-            } else {
+    /// Read the key of the next mapping entry (unless already read) and start
+    /// its value, or close the mapping when no key follows
+    fn start_mapping_entry(
+        &mut self,
+        mapping: Mapping,
+        key: Option<(String, Span)>,
+        stack: &mut Vec<Block>,
+    ) -> Result<Next> {
+        let (key, key_span) = match key {
+            Some(key) => key,
+            None => {
                 let token = self.next_non_newline()?;
                 match token.kind {
-                    YamlTokenKind::Scalar(value) | YamlTokenKind::QuotedScalar(value) => value,
-                    YamlTokenKind::Dedent | YamlTokenKind::Eof => {
-                        self.buffered = Some(token);
-                        break;
+                    YamlTokenKind::Scalar(value) | YamlTokenKind::QuotedScalar(value) => {
+                        (value, token.span)
                     }
-                    YamlTokenKind::Dash => {
+                    YamlTokenKind::Dedent | YamlTokenKind::Eof | YamlTokenKind::Dash => {
                         self.buffered = Some(token);
-                        break;
+                        return self.close_mapping(mapping, stack);
                     }
                     _ => {
                         return Err(Error::with_message(
@@ -285,80 +492,106 @@ impl<'a> Parser<'a> {
                         ));
                     }
                 }
-            };
-
-            let colon = self.next_non_newline()?;
-            if colon.kind != YamlTokenKind::Colon {
-                return Err(Error::with_message(
-                    ErrorKind::InvalidToken,
-                    colon.span,
-                    "expected ':'".to_string(),
-                ));
             }
+        };
 
-            let token = self.next_token()?;
-            let value = match token.kind {
-                YamlTokenKind::Scalar(value) => parse_scalar_value(&value),
-                YamlTokenKind::QuotedScalar(value) => Value::String(value),
-                YamlTokenKind::Newline => {
-                    let next = self.next_non_newline()?;
-                    match next.kind {
-                        YamlTokenKind::Indent => {
-                            let value = self.parse_block()?;
-                            let end = self.next_non_newline()?;
-                            if end.kind != YamlTokenKind::Dedent {
-                                self.buffered = Some(end);
-                            }
-                            value
-                        }
-                        YamlTokenKind::Dedent => {
-                            self.buffered = Some(next);
-                            Value::Null
-                        }
-                        _ => {
-                            self.buffered = Some(next);
-                            Value::Null
-                        }
+        let colon = self.next_non_newline()?;
+        if colon.kind != YamlTokenKind::Colon {
+            return Err(Error::with_message(
+                ErrorKind::InvalidToken,
+                colon.span,
+                "expected ':'".to_string(),
+            ));
+        }
+
+        self.enter_key(&key, key_span);
+        let token = self.next_token()?;
+        let value = match token.kind {
+            YamlTokenKind::Scalar(value) => self.plain(&value),
+            YamlTokenKind::QuotedScalar(value) => self.quoted(value, token.span),
+            YamlTokenKind::Binary(bytes) => Value::Bytes(bytes),
+            YamlTokenKind::Newline => {
+                let next = self.next_non_newline()?;
+                match next.kind {
+                    YamlTokenKind::Indent => {
+                        stack.push(Block::Entry(mapping, key, key_span));
+                        stack.push(Block::Dedent);
+                        return Ok(Next::Block);
                     }
-                }
-                YamlTokenKind::Indent => {
-                    let value = self.parse_block()?;
-                    let end = self.next_non_newline()?;
-                    if end.kind != YamlTokenKind::Dedent {
-                        self.buffered = Some(end);
+                    // A sequence may start in the key's own column
+                    // (`key:\n- item`)
+                    YamlTokenKind::Dash if next.span.start.col == key_span.start.col => {
+                        self.buffered = Some(next);
+                        stack.push(Block::Entry(mapping, key, key_span));
+                        return Ok(Next::Block);
+                    }
+                    _ => {
+                        self.buffered = Some(next);
+                        Value::Null
                     }
-                    value
-                }
-                YamlTokenKind::LeftBracket => self.parse_flow_sequence(token.span)?,
-                YamlTokenKind::LeftBrace => self.parse_flow_mapping(token.span)?,
-                _ => {
-                    return Err(Error::with_message(
-                        ErrorKind::InvalidToken,
-                        token.span,
-                        "expected value".to_string(),
-                    ));
                 }
-            };
-
-            if obj.contains_key(&key) {
+            }
+            YamlTokenKind::Indent => {
+                stack.push(Block::Entry(mapping, key, key_span));
+                stack.push(Block::Dedent);
+                return Ok(Next::Block);
+            }
+            YamlTokenKind::LeftBracket => self.parse_flow_sequence(token.span)?,
+            YamlTokenKind::LeftBrace => self.parse_flow_mapping(token.span)?,
+            _ => {
                 return Err(Error::with_message(
-                    ErrorKind::DuplicateKey { key },
-                    Span::empty(),
-                    "duplicate key".to_string(),
+                    ErrorKind::InvalidToken,
+                    token.span,
+                    "expected value".to_string(),
                 ));
             }
-            obj.insert(&key, value);
-
-            let next = self.peek_non_newline()?;
-            match next.kind {
-                YamlTokenKind::Scalar(_) | YamlTokenKind::QuotedScalar(_) => continue,
-                YamlTokenKind::Dedent | YamlTokenKind::Eof => break,
-                YamlTokenKind::Dash => break,
-                _ => break,
+        };
+        self.finish_mapping_entry(mapping, key, key_span, value, stack)
+    }
+
+    fn finish_mapping_entry(
+        &mut self,
+        mut mapping: Mapping,
+        key: String,
+        key_span: Span,
+        value: Value,
+        stack: &mut Vec<Block>,
+    ) -> Result<Next> {
+        self.leave();
+        self.insert_entry(&mut mapping.obj, &key, key_span, value)?;
+
+        // Keys of one mapping share a column; a key further left belongs
+        // to an enclosing mapping (after `- key: value` items)
+        let next = self.peek_non_newline()?;
+        match next.kind {
+            YamlTokenKind::Scalar(_) | YamlTokenKind::QuotedScalar(_)
+                if next.span.start.col == key_span.start.col =>
+            {
+                Ok(Next::Entry(mapping, None))
             }
+            _ => self.close_mapping(mapping, stack),
         }
+    }
 
-        Ok(obj)
+    fn close_mapping(&mut self, mut mapping: Mapping, stack: &mut Vec<Block>) -> Result<Next> {
+        match mapping.kind {
+            // The remaining keys of a `- key: value` item continue on the
+            // following lines, indented past the dash
+            MappingKind::ItemFirst if self.peek_non_newline()?.kind == YamlTokenKind::Indent => {
+                self.next_non_newline()?;
+                mapping.kind = MappingKind::ItemRest;
+                return self.start_mapping_entry(mapping, None, stack);
+            }
+            MappingKind::ItemRest => {
+                let end = self.next_non_newline()?;
+                if end.kind != YamlTokenKind::Dedent {
+                    self.buffered = Some(end);
+                }
+            }
+            MappingKind::Block | MappingKind::ItemFirst => {}
+        }
+        self.depth = self.depth.saturating_sub(1);
+        Ok(Next::Value(Value::Object(mapping.obj)))
     }
 
     fn bump_depth(&mut self, span: Span) -> Result<()> {
@@ -376,172 +609,452 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_flow_sequence(&mut self, opening_span: Span) -> Result<Value> {
-        self.bump_depth(opening_span)?;
-        let mut items = Vec::new();
-
-        loop {
-            let token = self.next_non_newline()?;
-            match token.kind {
-                YamlTokenKind::RightBracket => break,
-                YamlTokenKind::Comma => continue,
-                YamlTokenKind::LeftBracket => {
-                    let value = self.parse_flow_sequence(token.span)?;
-                    items.push(value);
-                }
-                YamlTokenKind::LeftBrace => {
-                    let value = self.parse_flow_mapping(token.span)?;
-                    items.push(value);
-                }
-                YamlTokenKind::Scalar(value) => {
-                    items.push(parse_scalar_value(&value));
-                }
-                YamlTokenKind::QuotedScalar(value) => {
-                    items.push(Value::String(value));
-                }
-                _ => {
-                    return Err(Error::with_message(
-                        ErrorKind::InvalidToken,
-                        token.span,
-                        "invalid flow sequence".to_string(),
-                    ));
-                }
-            }
-        }
-
-        self.depth = self.depth.saturating_sub(1);
-        Ok(Value::Array(Array(items)))
+        self.parse_flow(opening_span, Flow::Sequence(Vec::new()))
     }
 
     fn parse_flow_mapping(&mut self, opening_span: Span) -> Result<Value> {
-        self.bump_depth(opening_span)?;
-        let mut obj = Object::new();
+        self.parse_flow(opening_span, Flow::Mapping(Object::new(), None))
+    }
+
+    /// Parse a flow collection whose opening bracket has been read.
+    ///
+    /// Open collections are kept on an explicit stack rather than the call
+    /// stack, so nesting is bounded only by [`Config::max_depth`].
+    fn parse_flow(&mut self, opening_span: Span, root: Flow) -> Result<Value> {
+        let mut stack = Vec::new();
+        self.open_flow(opening_span, root, &mut stack)?;
 
         loop {
             let token = self.next_non_newline()?;
-            match token.kind {
-                YamlTokenKind::RightBrace => break,
-                YamlTokenKind::Comma => continue,
-                YamlTokenKind::Scalar(key) | YamlTokenKind::QuotedScalar(key) => {
-                    let colon = self.next_non_newline()?;
-                    if colon.kind != YamlTokenKind::Colon {
+            let Some(flow) = stack.last_mut() else {
+                return Ok(Value::Null);
+            };
+            let closed = match flow {
+                Flow::Sequence(items) => match token.kind {
+                    YamlTokenKind::RightBracket => true,
+                    YamlTokenKind::Comma => false,
+                    YamlTokenKind::LeftBracket => {
+                        self.enter_index(items.len());
+                        self.open_flow(token.span, Flow::Sequence(Vec::new()), &mut stack)?;
+                        false
+                    }
+                    YamlTokenKind::LeftBrace => {
+                        self.enter_index(items.len());
+                        self.open_flow(token.span, Flow::Mapping(Object::new(), None), &mut stack)?;
+                        false
+                    }
+                    YamlTokenKind::Scalar(value) => {
+                        items.push(self.plain(&value));
+                        false
+                    }
+                    YamlTokenKind::QuotedScalar(value) => {
+                        self.enter_index(items.len());
+                        let value = self.quoted(value, token.span);
+                        self.leave();
+                        items.push(value);
+                        false
+                    }
+                    YamlTokenKind::Binary(bytes) => {
+                        items.push(Value::Bytes(bytes));
+                        false
+                    }
+                    _ => {
                         return Err(Error::with_message(
                             ErrorKind::InvalidToken,
-                            colon.span,
-                            "expected ':' in flow mapping".to_string(),
+                            token.span,
+                            "invalid flow sequence".to_string(),
                         ));
                     }
-
-                    let value_token = self.next_non_newline()?;
-                    let value = match value_token.kind {
-                        YamlTokenKind::Scalar(value) => parse_scalar_value(&value),
-                        YamlTokenKind::QuotedScalar(value) => Value::String(value),
-                        YamlTokenKind::LeftBracket => self.parse_flow_sequence(value_token.span)?,
-                        YamlTokenKind::LeftBrace => self.parse_flow_mapping(value_token.span)?,
-                        _ => {
+                },
+                Flow::Mapping(obj, pending) => match token.kind {
+                    YamlTokenKind::RightBrace => true,
+                    YamlTokenKind::Comma => false,
+                    YamlTokenKind::Scalar(key) | YamlTokenKind::QuotedScalar(key) => {
+                        let key_span = token.span;
+                        let colon = self.next_non_newline()?;
+                        if colon.kind != YamlTokenKind::Colon {
                             return Err(Error::with_message(
                                 ErrorKind::InvalidToken,
-                                value_token.span,
-                                "expected value in flow mapping".to_string(),
+                                colon.span,
+                                "expected ':' in flow mapping".to_string(),
                             ));
                         }
-                    };
 
-                    insert_flow_value(&mut obj, &key, value)?;
-
-                    let next = self.peek_non_newline()?;
-                    match next.kind {
-                        YamlTokenKind::Comma => {
-                            let _ = self.next_non_newline()?;
-                        }
-                        YamlTokenKind::RightBrace => {
-                            let _ = self.next_non_newline()?;
-                            break;
-                        }
-                        _ => {}
+                        self.enter_key(&key, key_span);
+                        let value_token = self.next_non_newline()?;
+                        let value = match value_token.kind {
+                            YamlTokenKind::Scalar(value) => self.plain(&value),
+                            YamlTokenKind::QuotedScalar(value) => {
+                                self.quoted(value, value_token.span)
+                            }
+                            YamlTokenKind::Binary(bytes) => Value::Bytes(bytes),
+                            YamlTokenKind::LeftBracket => {
+                                *pending = Some((key, key_span));
+                                let nested = Flow::Sequence(Vec::new());
+                                self.open_flow(value_token.span, nested, &mut stack)?;
+                                continue;
+                            }
+                            YamlTokenKind::LeftBrace => {
+                                *pending = Some((key, key_span));
+                                let nested = Flow::Mapping(Object::new(), None);
+                                self.open_flow(value_token.span, nested, &mut stack)?;
+                                continue;
+                            }
+                            _ => {
+                                return Err(Error::with_message(
+                                    ErrorKind::InvalidToken,
+                                    value_token.span,
+                                    "expected value in flow mapping".to_string(),
+                                ));
+                            }
+                        };
+                        self.leave();
+                        self.insert_entry(obj, &key, key_span, value)?;
+                        self.end_flow_entry()?
                     }
-                }
-                _ => {
+                    _ => {
+                        return Err(Error::with_message(
+                            ErrorKind::InvalidToken,
+                            token.span,
+                            "invalid flow mapping".to_string(),
+                        ));
+                    }
+                },
+            };
+
+            if closed && let Some(value) = self.close_flow(&mut stack)? {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// Store a mapping entry, applying [`Config::duplicate_keys`] when `obj`
+    /// already holds `key`
+    fn insert_entry(
+        &mut self,
+        obj: &mut Object,
+        key: &str,
+        span: Span,
+        value: Value,
+    ) -> Result<()> {
+        if obj.contains_key(key) {
+            let key = key.to_string();
+            match self.config.duplicate_keys {
+                DuplicateKeys::Error => {
                     return Err(Error::with_message(
-                        ErrorKind::InvalidToken,
-                        token.span,
-                        "invalid flow mapping".to_string(),
+                        ErrorKind::DuplicateKey { key },
+                        span,
+                        "duplicate key".to_string(),
                     ));
                 }
+                DuplicateKeys::FirstWins => {
+                    let kind = WarningKind::IgnoredDuplicateKey { key };
+                    self.warnings.push(Warning::new(kind, span));
+                    return Ok(());
+                }
+                DuplicateKeys::LastWins => {
+                    let kind = WarningKind::DuplicateKey { key };
+                    self.warnings.push(Warning::new(kind, span));
+                }
             }
         }
+        obj.insert(key, value);
+        Ok(())
+    }
 
-        self.depth = self.depth.saturating_sub(1);
-        Ok(Value::Object(obj))
+    fn open_flow(&mut self, opening_span: Span, flow: Flow, stack: &mut Vec<Flow>) -> Result<()> {
+        self.bump_depth(opening_span)?;
+        stack.push(flow);
+        Ok(())
+    }
+
+    /// After a mapping entry, consume a following `,`, or a `}` and report
+    /// that the mapping is closed
+    fn end_flow_entry(&mut self) -> Result<bool> {
+        let next = self.peek_non_newline()?;
+        match next.kind {
+            YamlTokenKind::Comma => {
+                let _ = self.next_non_newline()?;
+                Ok(false)
+            }
+            YamlTokenKind::RightBrace => {
+                let _ = self.next_non_newline()?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Close the innermost collection and store it in its parent, closing
+    /// enclosing mappings whose `}` follows; the outermost collection is
+    /// returned once it closes
+    fn close_flow(&mut self, stack: &mut Vec<Flow>) -> Result<Option<Value>> {
+        loop {
+            self.depth = self.depth.saturating_sub(1);
+            let value = match stack.pop() {
+                Some(Flow::Sequence(items)) => Value::Array(Array(items)),
+                Some(Flow::Mapping(obj, _)) => Value::Object(obj),
+                None => return Ok(None),
+            };
+            match stack.last_mut() {
+                None => return Ok(Some(value)),
+                Some(Flow::Sequence(items)) => {
+                    self.leave();
+                    items.push(value);
+                    return Ok(None);
+                }
+                Some(Flow::Mapping(obj, pending)) => {
+                    self.leave();
+                    if let Some((key, key_span)) = pending.take() {
+                        self.insert_entry(obj, &key, key_span, value)?;
+                    }
+                    if !self.end_flow_entry()? {
+                        return Ok(None);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// What to parse next in a block value
+enum Next {
+    /// A block value: a sequence, a mapping, a scalar or a flow collection
+    Block,
+    /// The item after a sequence dash
+    Item,
+    /// An entry of a mapping, starting at its key when already read
+    Entry(Mapping, Option<(String, Span)>),
+    /// A finished value for the innermost enclosing [`Block`]
+    Value(Value),
+}
+
+/// A block collection waiting for a nested value
+enum Block {
+    /// A sequence waiting for its next item
+    Sequence(Vec<Value>),
+    /// A mapping waiting for the value of a key
+    Entry(Mapping, String, Span),
+    /// An indented value, whose closing dedent is read after it
+    Dedent,
+}
+
+struct Mapping {
+    obj: Object,
+    kind: MappingKind,
+}
+
+impl Mapping {
+    fn new(kind: MappingKind) -> Self {
+        Self {
+            obj: Object::new(),
+            kind,
+        }
     }
 }
 
-fn insert_flow_value(obj: &mut Object, key: &str, value: Value) -> Result<()> {
-    if obj.contains_key(key) {
-        return Err(Error::with_message(
-            ErrorKind::DuplicateKey {
-                key: key.to_string(),
-            },
-            Span::empty(),
-            "duplicate key".to_string(),
-        ));
-    }
-    obj.insert(key, value);
-    Ok(())
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MappingKind {
+    Block,
+    /// Keys on the line of a `- key: value` sequence item
+    ItemFirst,
+    /// Keys of a sequence item on the lines after its dash
+    ItemRest,
 }
 
-fn parse_scalar_value(value: &str) -> Value {
+/// A flow collection being read: the items of a sequence, or the entries of
+/// a mapping with the key of the nested collection being read
+#[derive(Debug)]
+enum Flow {
+    Sequence(Vec<Value>),
+    Mapping(Object, Option<(String, Span)>),
+}
+
+/// The value of the plain scalar `value` under the core schema
+pub(crate) fn parse_scalar_value(value: &str) -> Value {
+    resolve_scalar(value, Schema::Core)
+}
+
+/// The value of the plain (unquoted) scalar `value` under `schema`; what no
+/// rule of the schema matches is a string
+pub fn resolve_scalar(value: &str, schema: Schema) -> Value {
     let trimmed = value.trim();
     if trimmed.is_empty() {
         return Value::String(String::new());
     }
 
-    match trimmed {
-        "null" | "Null" | "NULL" | "~" => return Value::Null,
-        "true" | "True" | "TRUE" => return Value::Bool(true),
-        "false" | "False" | "FALSE" => return Value::Bool(false),
-        _ => {}
+    let resolved = match schema {
+        Schema::Core => resolve_core(trimmed),
+        Schema::Yaml11 => resolve_yaml11(trimmed),
+        Schema::Json => resolve_json(trimmed),
+    };
+    resolved.unwrap_or_else(|| Value::String(trimmed.to_string()))
+}
+
+fn resolve_core(text: &str) -> Option<Value> {
+    match text {
+        "null" | "Null" | "NULL" | "~" => Some(Value::Null),
+        "true" | "True" | "TRUE" => Some(Value::Bool(true)),
+        "false" | "False" | "FALSE" => Some(Value::Bool(false)),
+        _ => special_float(text)
+            .or_else(|| radix_integer(text.strip_prefix("0o")?, 8, false))
+            .or_else(|| radix_integer(text.strip_prefix("0x")?, 16, false))
+            .or_else(|| decimal(text, Schema::Core)),
     }
+}
 
-    if let Ok(int_val) = trimmed.parse::<i64>() {
-        return Value::from(int_val);
+fn resolve_yaml11(text: &str) -> Option<Value> {
+    match text {
+        "null" | "Null" | "NULL" | "~" => return Some(Value::Null),
+        "true" | "True" | "TRUE" | "yes" | "Yes" | "YES" | "on" | "On" | "ON" => {
+            return Some(Value::Bool(true));
+        }
+        "false" | "False" | "FALSE" | "no" | "No" | "NO" | "off" | "Off" | "OFF" => {
+            return Some(Value::Bool(false));
+        }
+        _ => {}
+    }
+    if let Some(special) = special_float(text) {
+        return Some(special);
     }
 
-    if !is_special_infinity_or_nan(trimmed)
-        && let Ok(float_val) = trimmed.parse::<f64>()
+    let (negative, unsigned) = split_sign(text);
+    if unsigned.starts_with('_') {
+        return None;
+    }
+    let digits = unsigned.replace('_', "");
+    if let Some(binary) = digits.strip_prefix("0b") {
+        return radix_integer(binary, 2, negative);
+    }
+    if let Some(hex) = digits.strip_prefix("0x") {
+        return radix_integer(hex, 16, negative);
+    }
+    if let Some(octal) = digits.strip_prefix('0')
+        && !octal.is_empty()
+        && octal.bytes().all(|byte| byte.is_ascii_digit())
     {
-        return Value::Number(float_val);
+        return radix_integer(octal, 8, negative);
     }
+    if unsigned.contains(':') {
+        return sexagesimal(&digits, negative);
+    }
+    decimal(&text.replace('_', ""), Schema::Yaml11)
+}
 
-    Value::String(trimmed.to_string())
+fn resolve_json(text: &str) -> Option<Value> {
+    match text {
+        "null" => Some(Value::Null),
+        "true" => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        _ => decimal(text, Schema::Json),
+    }
 }
 
-fn is_special_infinity_or_nan(input: &str) -> bool {
-    let lower = input.trim().to_ascii_lowercase();
-    matches!(
-        lower.as_str(),
-        "inf" | "+inf" | "-inf" | ".inf" | "+.inf" | "-.inf" | "nan" | ".nan"
-    )
+/// `.inf`, `-.inf` and `.nan` in their three capitalizations
+fn special_float(text: &str) -> Option<Value> {
+    let (negative, unsigned) = split_sign(text);
+    match unsigned {
+        ".inf" | ".Inf" | ".INF" if negative => Some(Value::Number(f64::NEG_INFINITY)),
+        ".inf" | ".Inf" | ".INF" => Some(Value::Number(f64::INFINITY)),
+        ".nan" | ".NaN" | ".NAN" if unsigned == text => Some(Value::Number(f64::NAN)),
+        _ => None,
+    }
 }
 
-fn emit_events(value: &Value, events: &mut VecDeque<Event>) {
-    match value {
-        Value::Object(obj) => {
-            events.push_back(Event::MappingStart);
-            for (key, value) in obj.iter() {
-                events.push_back(Event::Key(key.clone()));
-                emit_events(value, events);
-            }
-            events.push_back(Event::MappingEnd);
-        }
-        Value::Array(arr) => {
-            events.push_back(Event::SequenceStart);
-            for value in arr.iter() {
-                emit_events(value, events);
-            }
-            events.push_back(Event::SequenceEnd);
+fn split_sign(text: &str) -> (bool, &str) {
+    match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    }
+}
+
+/// An integer written in `radix` without a sign, or `None` when `digits` is
+/// not one or does not fit 64 bits
+fn radix_integer(digits: &str, radix: u32, negative: bool) -> Option<Value> {
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    let value = i64::from_str_radix(digits, radix).ok()?;
+    Some(Value::from(if negative { -value } else { value }))
+}
+
+/// A YAML 1.1 base-60 integer such as `1:30` (90)
+fn sexagesimal(digits: &str, negative: bool) -> Option<Value> {
+    let mut parts = digits.split(':');
+    let first = parts.next()?;
+    if first.starts_with('0') {
+        return None;
+    }
+    let mut value = first.parse::<u64>().ok()?;
+    for part in parts {
+        let part = match part.len() {
+            1 | 2 => part.parse::<u64>().ok().filter(|part| *part < 60)?,
+            _ => return None,
+        };
+        value = value.checked_mul(60)?.checked_add(part)?;
+    }
+    let value = i64::try_from(value).ok()?;
+    Some(Value::from(if negative { -value } else { value }))
+}
+
+/// A decimal integer or float as `schema` writes them:
+///
+/// - core: `[-+]?(\.[0-9]+|[0-9]+(\.[0-9]*)?)([eE][-+]?[0-9]+)?`
+/// - YAML 1.1 (underscores removed): `[-+]?([1-9][0-9]*|0)` for integers,
+///   and floats need a `.` and a signed exponent
+/// - JSON: `-?(0|[1-9][0-9]*)(\.[0-9]*)?([eE][-+]?[0-9]+)?`
+fn decimal(text: &str, schema: Schema) -> Option<Value> {
+    let digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+    if schema == Schema::Json && text.starts_with('+') {
+        return None;
+    }
+    let (_, unsigned) = split_sign(text);
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (unsigned, None),
+    };
+    let (whole, fraction) = match mantissa.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (mantissa, None),
+    };
+
+    let whole_ok = match schema {
+        Schema::Json => whole == "0" || (!whole.starts_with('0') && !whole.is_empty()),
+        Schema::Yaml11 if fraction.is_none() => whole == "0" || !whole.starts_with('0'),
+        _ => true,
+    };
+    let fraction_ok = match (schema, fraction) {
+        (Schema::Core | Schema::Yaml11, Some("")) => !whole.is_empty(),
+        (_, Some(fraction)) => digits(fraction) && (!whole.is_empty() || !fraction.is_empty()),
+        (_, None) => !whole.is_empty(),
+    };
+    let exponent_ok = match (schema, exponent) {
+        (_, None) => true,
+        (Schema::Yaml11, Some(exponent)) => {
+            fraction.is_some() && exponent.starts_with(['-', '+']) && exponent.len() > 1
         }
-        _ => {
-            events.push_back(Event::Value(value.clone()));
+        (_, Some(exponent)) => {
+            let (_, exponent) = split_sign(exponent);
+            !exponent.is_empty() && digits(exponent)
         }
+    };
+    let exponent_digits = exponent.is_none_or(|exponent| digits(split_sign(exponent).1));
+    if !(digits(whole) && whole_ok && fraction_ok && exponent_ok && exponent_digits) {
+        return None;
     }
+
+    if fraction.is_none()
+        && exponent.is_none()
+        && let Ok(integer) = text.parse::<i64>()
+    {
+        return Some(Value::from(integer));
+    }
+    text.parse::<f64>().ok().map(Value::Number)
+}
+
+/// Flatten `value` into events, walking containers from an explicit stack
+fn emit_events(value: &Value, events: &mut VecDeque<Event>) {
+    let mut unified = VecDeque::new();
+    push_events(value, &mut unified);
+    events.extend(unified.into_iter().map(Event::from));
 }
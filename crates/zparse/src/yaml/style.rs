@@ -0,0 +1,111 @@
+//! Indentation style of existing YAML documents
+//!
+//! Used to write a reformatted file back the way it was laid out, so tools
+//! like yamllint that require one consistent style keep accepting it.
+
+use std::collections::BTreeMap;
+
+use crate::convert::YamlSequenceStyle;
+
+/// Indentation found in a YAML document; a field is `None` when the document
+/// has no nesting that shows it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Indentation {
+    /// Spaces per nesting level of block mappings
+    pub width: Option<usize>,
+    /// Placement of the dashes of sequences nested in a mapping
+    pub sequences: Option<YamlSequenceStyle>,
+}
+
+/// Detect the indentation of `input` from keys that open a nested block.
+///
+/// Each `key:` line followed by a deeper key votes for a width and each one
+/// followed by a `-` votes for a sequence style; the most common vote wins.
+/// Block scalar contents, comments and document markers are ignored.
+pub fn detect_indentation(input: &str) -> Indentation {
+    let mut widths: BTreeMap<usize, usize> = BTreeMap::new();
+    let mut indented = 0usize;
+    let mut aligned = 0usize;
+    // Indentation of the key that opened a nested block on the previous line
+    let mut opener: Option<usize> = None;
+    // Indentation of the key owning the block scalar being skipped
+    let mut scalar: Option<usize> = None;
+
+    for line in input.lines() {
+        let content = line.trim_start_matches(' ');
+        let indent = line.len() - content.len();
+        let content = strip_comment(content).trim_end();
+        if content.is_empty() {
+            continue;
+        }
+        if let Some(owner) = scalar {
+            if indent > owner {
+                continue;
+            }
+            scalar = None;
+        }
+        if indent == 0 && (content.starts_with("---") || content.starts_with("...")) {
+            opener = None;
+            continue;
+        }
+
+        let is_item = content == "-" || content.starts_with("- ");
+        if let Some(parent) = opener.take() {
+            if is_item {
+                if indent == parent {
+                    aligned += 1;
+                } else if indent > parent {
+                    indented += 1;
+                    *widths.entry(indent - parent).or_default() += 1;
+                }
+            } else if indent > parent {
+                *widths.entry(indent - parent).or_default() += 1;
+            }
+        }
+
+        if is_block_scalar(content) {
+            scalar = Some(indent);
+        } else if !is_item && content.ends_with(':') {
+            opener = Some(indent);
+        }
+    }
+
+    let width = widths
+        .iter()
+        .max_by_key(|(width, votes)| (**votes, std::cmp::Reverse(**width)))
+        .map(|(width, _)| *width);
+    let sequences = match (indented, aligned) {
+        (0, 0) => None,
+        (indented, aligned) if aligned > indented => Some(YamlSequenceStyle::Aligned),
+        _ => Some(YamlSequenceStyle::Indented),
+    };
+    Indentation { width, sequences }
+}
+
+/// Drop a trailing `# comment` outside of quotes
+fn strip_comment(content: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (index, ch) in content.char_indices() {
+        match (quote, ch) {
+            (None, '"' | '\'') => quote = Some(ch),
+            (Some(open), ch) if ch == open => quote = None,
+            (None, '#') if previous == ' ' => return content.get(..index).unwrap_or(content),
+            _ => {}
+        }
+        previous = ch;
+    }
+    content
+}
+
+/// Whether the line ends with a `|` or `>` block scalar header
+fn is_block_scalar(content: &str) -> bool {
+    let header = content
+        .rsplit_once(' ')
+        .map_or(content, |(_, header)| header);
+    header.starts_with(['|', '>'])
+        && header
+            .chars()
+            .skip(1)
+            .all(|ch| matches!(ch, '+' | '-') || ch.is_ascii_digit())
+}
@@ -0,0 +1,106 @@
+//! YAML timestamps read as datetimes
+//!
+//! YAML 1.1 writes dates as `2001-12-14` and date-times as
+//! `2001-12-14t21:59:43.10-05:00` or `2001-12-14 21:59:43.10 -5`. With
+//! [`super::Config::timestamps`] such plain scalars become the
+//! [`TomlDatetime`] TOML uses, so they convert to TOML datetimes: a date to a
+//! local date, a date-time with a zone to an offset date-time, and one
+//! without a zone to a local date-time.
+
+use std::iter::Peekable;
+use std::str::Bytes;
+
+use time::{Date, Month, PrimitiveDateTime, Time, UtcOffset};
+
+use crate::value::TomlDatetime;
+
+/// The datetime `text` writes, if it is a YAML timestamp
+pub(crate) fn parse(text: &str) -> Option<TomlDatetime> {
+    let mut bytes = text.bytes().peekable();
+    let year = digits(&mut bytes, 4, 4)?;
+    expect(&mut bytes, b'-')?;
+    let month = digits(&mut bytes, 1, 2)?;
+    expect(&mut bytes, b'-')?;
+    let day = digits(&mut bytes, 1, 2)?;
+    let date = Date::from_calendar_date(
+        i32::try_from(year).ok()?,
+        Month::try_from(u8::try_from(month).ok()?).ok()?,
+        u8::try_from(day).ok()?,
+    )
+    .ok()?;
+
+    // A date on its own has two-digit month and day
+    if bytes.peek().is_none() {
+        return (text.len() == 10).then_some(TomlDatetime::LocalDate(date));
+    }
+
+    if bytes.next_if(|byte| matches!(byte, b'T' | b't')).is_none() && spaces(&mut bytes) == 0 {
+        return None;
+    }
+    let hour = digits(&mut bytes, 1, 2)?;
+    expect(&mut bytes, b':')?;
+    let minute = digits(&mut bytes, 2, 2)?;
+    expect(&mut bytes, b':')?;
+    let second = digits(&mut bytes, 2, 2)?;
+    let mut nanos = 0;
+    if bytes.next_if_eq(&b'.').is_some() {
+        let mut scale = 100_000_000;
+        while let Some(byte) = bytes.next_if(u8::is_ascii_digit) {
+            nanos += u32::from(byte - b'0') * scale;
+            scale /= 10;
+        }
+    }
+    let time = Time::from_hms_nano(
+        u8::try_from(hour).ok()?,
+        u8::try_from(minute).ok()?,
+        u8::try_from(second).ok()?,
+        nanos,
+    )
+    .ok()?;
+    let datetime = PrimitiveDateTime::new(date, time);
+
+    spaces(&mut bytes);
+    let offset = match bytes.next() {
+        None => return Some(TomlDatetime::LocalDateTime(datetime)),
+        Some(b'Z') => UtcOffset::UTC,
+        Some(sign @ (b'+' | b'-')) => {
+            let hours = i8::try_from(digits(&mut bytes, 1, 2)?).ok()?;
+            let minutes = match bytes.next_if_eq(&b':') {
+                Some(_) => i8::try_from(digits(&mut bytes, 2, 2)?).ok()?,
+                None => 0,
+            };
+            let sign = if sign == b'-' { -1 } else { 1 };
+            UtcOffset::from_hms(sign * hours, sign * minutes, 0).ok()?
+        }
+        Some(_) => return None,
+    };
+    if bytes.next().is_some() {
+        return None;
+    }
+    Some(TomlDatetime::OffsetDateTime(datetime.assume_offset(offset)))
+}
+
+/// A number of `min..=max` digits
+fn digits(bytes: &mut Peekable<Bytes<'_>>, min: usize, max: usize) -> Option<u32> {
+    let mut value = 0;
+    let mut count = 0;
+    while count < max
+        && let Some(byte) = bytes.next_if(u8::is_ascii_digit)
+    {
+        value = value * 10 + u32::from(byte - b'0');
+        count += 1;
+    }
+    (count >= min && bytes.peek().is_none_or(|byte| !byte.is_ascii_digit())).then_some(value)
+}
+
+fn expect(bytes: &mut Peekable<Bytes<'_>>, expected: u8) -> Option<()> {
+    bytes.next_if_eq(&expected).map(drop)
+}
+
+fn spaces(bytes: &mut Peekable<Bytes<'_>>) -> usize {
+    let mut count = 0;
+    while bytes.next_if(|byte| matches!(byte, b' ' | b'\t')).is_some() {
+        count += 1;
+    }
+    count
+}
@@ -0,0 +1,111 @@
+use std::path::Path;
+
+use zparse::ConvertOptions;
+use zparse::audit::{Location, UnusedReason, audit_dir};
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+fn ensure_eq<T: PartialEq + std::fmt::Debug>(left: T, right: T) -> TestResult {
+    if left == right {
+        Ok(())
+    } else {
+        Err(format!("assertion failed: left={left:?} right={right:?}").into())
+    }
+}
+
+fn write(dir: &Path, name: &str, content: &str) -> std::io::Result<()> {
+    let path = dir.join(name);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)
+}
+
+#[test]
+fn test_audit_reports_duplicates_conflicts_and_unused_includes() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    write(dir.path(), "base.yaml", "db:\n  host: a\n  port: 5432\n")?;
+    write(
+        dir.path(),
+        "prod.toml",
+        "[db]\nhost = \"b\"\nport = 5432\n\n[cache]\n\"$include\" = [\"shared/empty.json\", \"shared/ttl.json\"]\nttl = 60\n",
+    )?;
+    write(dir.path(), "shared/empty.json", "{}")?;
+    write(dir.path(), "shared/ttl.json", "{\"ttl\": 30}")?;
+    write(
+        dir.path(),
+        ".hidden/ignored.json",
+        "{\"db\": {\"host\": \"c\"}}",
+    )?;
+    write(dir.path(), "notes.txt", "not a config")?;
+
+    let report = audit_dir(dir.path(), &ConvertOptions::default())?;
+    ensure_eq(report.files.len(), 4)?;
+    ensure_eq(report.errors.is_empty(), true)?;
+
+    let duplicates: Vec<&str> = report
+        .duplicates
+        .iter()
+        .map(|d| d.pointer.as_str())
+        .collect();
+    ensure_eq(duplicates, vec!["/db/port"])?;
+    ensure_eq(
+        report.duplicates.first().map(|d| d.locations.clone()),
+        Some(vec![
+            Location {
+                file: dir.path().join("base.yaml"),
+                line: Some(3),
+            },
+            Location {
+                file: dir.path().join("prod.toml"),
+                line: Some(3),
+            },
+        ]),
+    )?;
+    let conflicts: Vec<&str> = report
+        .conflicts
+        .iter()
+        .map(|c| c.pointer.as_str())
+        .collect();
+    ensure_eq(conflicts, vec!["/db/host"])?;
+
+    let unused: Vec<(&str, UnusedReason)> = report
+        .unused_includes
+        .iter()
+        .map(|unused| (unused.pointer.as_str(), unused.reason))
+        .collect();
+    ensure_eq(
+        unused,
+        vec![
+            ("/cache", UnusedReason::Empty),
+            ("/cache", UnusedReason::Overridden),
+        ],
+    )?;
+    ensure_eq(!report.is_clean(), true)?;
+    ensure_eq(
+        report
+            .to_string()
+            .ends_with("4 files, 1 duplicates, 1 conflicts, 2 unused includes, 0 errors"),
+        true,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_audit_collects_errors_and_clean_trees() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    write(dir.path(), "a.json", "{\"name\": \"a\"}")?;
+    write(dir.path(), "b.yaml", "other: b\n")?;
+    let report = audit_dir(dir.path(), &ConvertOptions::default())?;
+    ensure_eq(report.is_clean(), true)?;
+
+    write(dir.path(), "broken.toml", "key = ")?;
+    write(dir.path(), "c.json", "{\"$include\": \"missing.json\"}")?;
+    let report = audit_dir(dir.path(), &ConvertOptions::default())?;
+    let failed: Vec<_> = report.errors.iter().map(|(file, _)| file.clone()).collect();
+    ensure_eq(
+        failed,
+        vec![dir.path().join("broken.toml"), dir.path().join("c.json")],
+    )?;
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use zparse::{
+    BytesEncoding, Format, FormatOptions, Object, Value, from_yaml_str, serialize,
+    serialize_with_options,
+};
+
+fn document(bytes: &[u8]) -> Value {
+    let mut object = Object::new();
+    object.insert("data", Value::Bytes(bytes.to_vec()));
+    Value::Object(object)
+}
+
+#[test]
+fn test_yaml_binary_decodes_to_bytes() -> zparse::Result<()> {
+    let value = from_yaml_str("data: !!binary 3q2+7w==\nname: blob\n")?;
+    let Value::Object(object) = value else {
+        return Err(zparse::Error::new(
+            zparse::ErrorKind::InvalidToken,
+            zparse::Span::empty(),
+        ));
+    };
+    if object.get("data").and_then(Value::as_bytes) != Some(&[0xde, 0xad, 0xbe, 0xef][..]) {
+        return Err(zparse::Error::new(
+            zparse::ErrorKind::InvalidToken,
+            zparse::Span::empty(),
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_yaml_binary_in_sequences_and_flow() -> zparse::Result<()> {
+    let block = from_yaml_str("- !!binary aGk=\n- plain\n")?;
+    let flow = from_yaml_str("items: [!!binary aGk=, \"aGk=\"]\n")?;
+
+    let block_first = block.as_array().and_then(|items| items.get(0)).cloned();
+    let flow_items = flow
+        .as_object()
+        .and_then(|object| object.get("items"))
+        .and_then(Value::as_array)
+        .map(|items| items.iter().cloned().collect::<Vec<_>>());
+    if block_first != Some(Value::Bytes(b"hi".to_vec()))
+        || flow_items
+            != Some(vec![
+                Value::Bytes(b"hi".to_vec()),
+                Value::String("aGk=".to_string()),
+            ])
+    {
+        return Err(zparse::Error::new(
+            zparse::ErrorKind::InvalidToken,
+            zparse::Span::empty(),
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_yaml_binary_rejects_invalid_base64() {
+    let result = from_yaml_str("data: !!binary not*base64\n");
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_json_bytes_default_to_base64() {
+    let output = serialize(&document(&[0, 255, 128]), Format::Json).ok();
+    assert_eq!(output.as_deref(), Some("{\"data\":\"AP+A\"}"));
+}
+
+#[test]
+fn test_json_and_toml_bytes_as_array() {
+    let options = FormatOptions::new().with_bytes(BytesEncoding::Array);
+    let json = serialize_with_options(&document(&[1, 2]), Format::Json, &options).ok();
+    let toml = serialize_with_options(&document(&[1, 2]), Format::Toml, &options).ok();
+    assert_eq!(json.as_deref(), Some("{\"data\":[1,2]}"));
+    assert_eq!(toml.as_deref(), Some("data = [1, 2]"));
+}
+
+#[test]
+fn test_yaml_bytes_round_trip() -> zparse::Result<()> {
+    let original = document(&[0xc3, 0x28, 0x00, 0xff]);
+    let yaml = serialize(&original, Format::Yaml)?;
+    if from_yaml_str(&yaml)? != original {
+        return Err(zparse::Error::new(
+            zparse::ErrorKind::InvalidToken,
+            zparse::Span::empty(),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use zparse::{KeyCase, convert_key, from_str};
+
+#[test]
+fn test_convert_key_between_styles() {
+    let cases = [
+        ("max_connections", KeyCase::Camel, "maxConnections"),
+        ("maxConnections", KeyCase::Snake, "max_connections"),
+        ("max-connections", KeyCase::Snake, "max_connections"),
+        ("maxConnections", KeyCase::Kebab, "max-connections"),
+        ("HTTPServerPort", KeyCase::Snake, "http_server_port"),
+        ("ipv4Address", KeyCase::Kebab, "ipv4-address"),
+        ("_id", KeyCase::Camel, "_id"),
+        ("__private_key", KeyCase::Camel, "__privateKey"),
+        ("already", KeyCase::Snake, "already"),
+    ];
+    for (input, case, expected) in cases {
+        assert_eq!(convert_key(input, case), expected, "{input} -> {case:?}");
+    }
+}
+
+#[test]
+fn test_rename_keys_recursively() -> Result<(), Box<dyn std::error::Error>> {
+    let mut value = from_str(
+        r#"{"server_config":{"listen_port":80,"tls-enabled":true},"user_list":[{"first_name":"a"}]}"#,
+    )?;
+    value.rename_keys(KeyCase::Camel);
+
+    let expected = from_str(
+        r#"{"serverConfig":{"listenPort":80,"tlsEnabled":true},"userList":[{"firstName":"a"}]}"#,
+    )?;
+    if value != expected {
+        return Err(format!("unexpected renamed document: {value:?}").into());
+    }
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use zparse::{codegen, from_str};
+
+#[test]
+fn test_codegen_structs_from_samples() -> Result<(), Box<dyn std::error::Error>> {
+    let samples = [
+        from_str(r#"{"id":1,"displayName":"a","server":{"port":80,"type":"http"}}"#)?,
+        from_str(r#"{"id":2,"server":{"port":81,"type":"https"},"ratio":0.5}"#)?,
+    ];
+    let code = codegen::rust_from_samples(&samples, "config");
+
+    let expected = [
+        "use serde::{Deserialize, Serialize};",
+        "pub struct Config {",
+        "    pub id: i64,",
+        "    #[serde(rename = \"displayName\")]",
+        "    pub display_name: Option<String>,",
+        "    pub server: ConfigServer,",
+        "    pub ratio: Option<f64>,",
+        "pub struct ConfigServer {",
+        "    pub port: i64,",
+        "    pub r#type: String,",
+    ];
+    for line in expected {
+        if !code.lines().any(|l| l == line) {
+            return Err(format!("missing {line:?} in generated code:\n{code}").into());
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_codegen_non_object_root_uses_alias() -> Result<(), Box<dyn std::error::Error>> {
+    let samples = [from_str(r#"[{"name":"a"},{"name":"b","tags":[]}]"#)?];
+    let code = codegen::rust_from_samples(&samples, "Users");
+
+    for line in ["pub type Users = Vec<UsersItem>;", "pub struct UsersItem {"] {
+        if !code.lines().any(|l| l == line) {
+            return Err(format!("missing {line:?} in generated code:\n{code}").into());
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_codegen_mixed_types_fall_back() -> Result<(), Box<dyn std::error::Error>> {
+    let samples = [from_str(r#"{"v":1}"#)?, from_str(r#"{"v":"x"}"#)?];
+    let code = codegen::rust_from_samples(&samples, "Root");
+    if !code.contains("pub v: serde_json::Value,") {
+        return Err(format!("expected fallback type:\n{code}").into());
+    }
+    Ok(())
+}
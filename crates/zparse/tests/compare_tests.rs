@@ -0,0 +1,173 @@
+use zparse::compare::PathSegment;
+use zparse::{
+    CompareOptions, Value, from_str, values_diff_first_mismatch, values_diff_first_mismatch_with,
+    values_equal, values_equal_with,
+};
+
+#[test]
+fn test_values_equal() -> Result<(), Box<dyn std::error::Error>> {
+    let a = from_str(r#"{"a":[1,{"b":true}],"c":null}"#)?;
+    let b = from_str(r#"{"a":[1,{"b":true}],"c":null}"#)?;
+    if !values_equal(&a, &b) {
+        return Err("identical documents should be equal".into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_first_mismatch_reports_path_and_values() -> Result<(), Box<dyn std::error::Error>> {
+    let a = from_str(r#"{"servers":[{"port":80},{"port":443}],"name":"a"}"#)?;
+    let b = from_str(r#"{"servers":[{"port":80},{"port":8443}],"name":"b"}"#)?;
+
+    let mismatch = values_diff_first_mismatch(&a, &b).ok_or("expected a mismatch")?;
+    let expected_path = vec![
+        PathSegment::Key("servers".to_string()),
+        PathSegment::Index(1),
+        PathSegment::Key("port".to_string()),
+    ];
+    if mismatch.path != expected_path {
+        return Err(format!("unexpected path: {:?}", mismatch.path).into());
+    }
+    if mismatch.left != Some(Value::from(443)) || mismatch.right != Some(Value::from(8443)) {
+        return Err(format!("unexpected values: {mismatch:?}").into());
+    }
+    if mismatch.to_string() != "mismatch at $.servers[1].port: left=443 right=8443" {
+        return Err(format!("unexpected message: {mismatch}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_first_mismatch_missing_keys_and_indices() -> Result<(), Box<dyn std::error::Error>> {
+    let a = from_str(r#"{"list":[1,2],"odd key":1}"#)?;
+    let b = from_str(r#"{"list":[1,2,3],"odd key":1}"#)?;
+    let mismatch = values_diff_first_mismatch(&a, &b).ok_or("expected a mismatch")?;
+    if mismatch.to_string() != "mismatch at $.list[2]: left=<missing> right=3" {
+        return Err(format!("unexpected message: {mismatch}").into());
+    }
+
+    let a = from_str(r#"{"odd key":1}"#)?;
+    let b = from_str(r#"{"odd key":1,"extra":2}"#)?;
+    let mismatch = values_diff_first_mismatch(&a, &b).ok_or("expected a mismatch")?;
+    if mismatch.path_string() != "$.extra" || mismatch.left.is_some() {
+        return Err(format!("unexpected mismatch: {mismatch:?}").into());
+    }
+
+    let a = from_str(r#"{"odd key":1}"#)?;
+    let b = from_str(r#"{"odd key":2}"#)?;
+    let mismatch = values_diff_first_mismatch(&a, &b).ok_or("expected a mismatch")?;
+    if mismatch.path_string() != r#"$["odd key"]"# {
+        return Err(format!("unexpected path: {}", mismatch.path_string()).into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_compare_options_epsilon() -> Result<(), Box<dyn std::error::Error>> {
+    let a = from_str(r#"{"ratio":0.30000000000000004,"n":1}"#)?;
+    let b = from_str(r#"{"ratio":0.3,"n":1.0}"#)?;
+    if values_equal(&a, &b) {
+        return Err("exact comparison should see the float difference".into());
+    }
+    if !values_equal_with(&a, &b, &CompareOptions::new().with_epsilon(1e-9)) {
+        return Err("epsilon comparison should accept the difference".into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_nan_and_signed_zero_agree_with_eq() -> Result<(), Box<dyn std::error::Error>> {
+    let pairs = [
+        (Value::Number(f64::NAN), Value::Number(f64::NAN), true),
+        (Value::Number(-0.0), Value::Number(0.0), true),
+        (Value::Number(f64::NAN), Value::Number(1.0), false),
+        (
+            Value::Number(f64::INFINITY),
+            Value::Number(f64::INFINITY),
+            true,
+        ),
+        (Value::Number(f64::INFINITY), Value::Number(1e300), false),
+    ];
+    let loose = CompareOptions::new().with_epsilon(1e-9);
+    for (a, b, equal) in pairs {
+        if (a == b) != equal || values_equal(&a, &b) != equal {
+            return Err(format!("{a:?} and {b:?} should compare {equal}").into());
+        }
+        if values_equal_with(&a, &b, &loose) != equal {
+            return Err(format!("{a:?} and {b:?} should compare {equal} with epsilon").into());
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_compare_options_key_order() -> Result<(), Box<dyn std::error::Error>> {
+    let a = from_str(r#"{"a":1,"b":2}"#)?;
+    let b = from_str(r#"{"b":2,"a":1}"#)?;
+    if !values_equal(&a, &b) {
+        return Err("key order is ignored by default".into());
+    }
+
+    let strict = CompareOptions::new().with_ignore_key_order(false);
+    let mismatch = values_diff_first_mismatch_with(&a, &b, &strict).ok_or("expected mismatch")?;
+    if mismatch.path_string() != "$" {
+        return Err(format!("unexpected path: {}", mismatch.path_string()).into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_compare_options_null_equals_missing() -> Result<(), Box<dyn std::error::Error>> {
+    let a = from_str(r#"{"a":1,"b":null}"#)?;
+    let b = from_str(r#"{"a":1}"#)?;
+    if values_equal(&a, &b) {
+        return Err("null and missing differ by default".into());
+    }
+    let options = CompareOptions::new()
+        .with_null_equals_missing(true)
+        .with_ignore_key_order(false);
+    if !values_equal_with(&a, &b, &options) {
+        return Err("null should match a missing key".into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_compare_options_ignore_array_order() -> Result<(), Box<dyn std::error::Error>> {
+    let a = from_str(r#"{"tags":["a","b","a"]}"#)?;
+    let b = from_str(r#"{"tags":["b","a","a"]}"#)?;
+    if values_equal(&a, &b) {
+        return Err("array order matters by default".into());
+    }
+    let options = CompareOptions::new().with_ignore_array_order(true);
+    if !values_equal_with(&a, &b, &options) {
+        return Err("reordered items should match".into());
+    }
+
+    let c = from_str(r#"{"tags":["b","a","b"]}"#)?;
+    let mismatch = values_diff_first_mismatch_with(&a, &c, &options).ok_or("expected mismatch")?;
+    if mismatch.path_string() != "$.tags[2]" || mismatch.right.is_some() {
+        return Err(format!("unexpected mismatch: {mismatch}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_compare_options_coerce_scalars() -> Result<(), Box<dyn std::error::Error>> {
+    let quoted = from_str(r#"{"server":{"port":"8080","tls":"true"}}"#)?;
+    let json = from_str(r#"{"server":{"port":8080,"tls":true}}"#)?;
+    if values_equal(&quoted, &json) {
+        return Err("strings and numbers differ by default".into());
+    }
+    let options = CompareOptions::new().with_coerce_scalars(true);
+    if !values_equal_with(&quoted, &json, &options) {
+        let mismatch = values_diff_first_mismatch_with(&quoted, &json, &options);
+        return Err(format!("coerced scalars should match: {mismatch:?}").into());
+    }
+
+    let other = from_str(r#"{"server":{"port":8081,"tls":true}}"#)?;
+    if values_equal_with(&quoted, &other, &options) {
+        return Err("different numbers should still differ".into());
+    }
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use std::path::Path;
+
+use zparse::{Compression, Format, detect_format_from_path};
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+#[test]
+fn test_compression_from_path_and_header() {
+    assert_eq!(
+        Compression::from_path("data.json.gz"),
+        Some(Compression::Gzip)
+    );
+    assert_eq!(
+        Compression::from_path("config.YAML.ZST"),
+        Some(Compression::Zstd)
+    );
+    assert_eq!(Compression::from_path("data.json"), None);
+    assert_eq!(
+        Compression::detect(&[0x1f, 0x8b, 8]),
+        Some(Compression::Gzip)
+    );
+    assert_eq!(
+        Compression::detect(&[0x28, 0xb5, 0x2f, 0xfd]),
+        Some(Compression::Zstd)
+    );
+    assert_eq!(Compression::detect(b"{\"a\": 1}"), None);
+}
+
+#[test]
+fn test_format_is_detected_through_compression_extension() {
+    assert_eq!(detect_format_from_path("data.json.gz"), Some(Format::Json));
+    assert_eq!(
+        detect_format_from_path("config.yml.zst"),
+        Some(Format::Yaml)
+    );
+    assert_eq!(detect_format_from_path("archive.gz"), None);
+    assert!(zparse::ops::is_jsonc_path(Path::new("settings.jsonc.gz")));
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn test_gzip_files_are_read_and_written() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    let input = dir.path().join("data.json.gz");
+    std::fs::write(
+        &input,
+        Compression::Gzip.compress(br#"{"name": "zparse"}"#)?,
+    )?;
+    let value = zparse::parse_path(&input)?;
+    if value.get("name").and_then(|name| name.as_string()) != Some("zparse") {
+        return Err(format!("unexpected value: {value:?}").into());
+    }
+
+    let output = dir.path().join("data.toml.gz");
+    zparse::ops::convert_path(&input, &output, None, &zparse::ConvertOptions::default())?;
+    let written = std::fs::read(&output)?;
+    if Compression::detect(&written) != Some(Compression::Gzip) {
+        return Err("output was not compressed".into());
+    }
+    let text = String::from_utf8(Compression::Gzip.decompress(&written)?)?;
+    if text.trim() != "name = \"zparse\"" {
+        return Err(format!("unexpected output: {text:?}").into());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn test_gzip_header_is_detected_without_extension() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    let input = dir.path().join("data.json");
+    std::fs::write(&input, Compression::Gzip.compress(b"[1, 2]")?)?;
+    let (format, value) = zparse::ops::parse_file(&input, &zparse::ConvertOptions::default())?;
+    if format != Format::Json || value.as_array().map(|items| items.len()) != Some(2) {
+        return Err(format!("unexpected value: {value:?}").into());
+    }
+
+    let corrupt = dir.path().join("corrupt.json.gz");
+    std::fs::write(&corrupt, [0x1f, 0x8b, 0, 1, 2])?;
+    if zparse::parse_path(&corrupt).is_ok() {
+        return Err("corrupt gzip should not parse".into());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn test_zstd_round_trip() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    let input = dir.path().join("config.yaml.zst");
+    std::fs::write(&input, Compression::Zstd.compress(b"a: 1\nb: two words\n")?)?;
+    let value = zparse::parse_path(&input)?;
+    if value.get("b").and_then(|b| b.as_string()) != Some("two words") {
+        return Err(format!("unexpected value: {value:?}").into());
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "zstd"))]
+#[test]
+fn test_zstd_without_feature_names_it() -> TestResult {
+    let err = Compression::Zstd
+        .decompress(&[0x28, 0xb5, 0x2f, 0xfd])
+        .err()
+        .ok_or("expected an error")?;
+    if !matches!(err.kind(), zparse::ErrorKind::FormatDisabled { feature } if feature == "zstd") {
+        return Err(format!("unexpected error: {err:?}").into());
+    }
+    Ok(())
+}
+
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+fn format_in_place_keeps_compression(name: &str, compression: Compression) -> TestResult {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join(name);
+    std::fs::write(&path, compression.compress(br#"{"a":[1]}"#)?)?;
+
+    let options = zparse::ConvertOptions {
+        format: zparse::FormatOptions::new().with_pretty(true),
+        ..Default::default()
+    };
+    zparse::ops::format_in_place(&path, &options, None)?;
+    let written = std::fs::read(&path)?;
+    if Compression::detect(&written) != Some(compression) {
+        return Err(format!("{name} was written uncompressed").into());
+    }
+    let formatted = zparse::ops::format_file(&path, &options)?;
+    if formatted != "{\n  \"a\": [\n    1\n  ]\n}" {
+        return Err(format!("unexpected output: {formatted:?}").into());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn test_format_in_place_keeps_gzip() -> TestResult {
+    format_in_place_keeps_compression("data.json.gz", Compression::Gzip)
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn test_format_in_place_keeps_zstd() -> TestResult {
+    format_in_place_keeps_compression("data.json.zst", Compression::Zstd)
+}
@@ -0,0 +1,226 @@
+use std::path::Path;
+
+use zparse::{ConfigLoader, ErrorKind, MergeStrategy, Value, from_str};
+
+fn write(dir: &Path, name: &str, content: &str) -> std::io::Result<()> {
+    let path = dir.join(name);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)
+}
+
+#[test]
+fn test_layers_merge_in_order() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let root = dir.path();
+    write(
+        root,
+        "defaults.toml",
+        "[server]\nhost = \"localhost\"\nport = 80\n\n[log]\nlevel = \"info\"\n",
+    )?;
+    write(root, "config.d/20-site.yaml", "server:\n  port: 8080\n")?;
+    write(root, "config.d/10-base.yaml", "log:\n  level: debug\n")?;
+    write(root, "config.d/notes.txt", "ignored")?;
+    write(
+        root,
+        "overrides.json",
+        r#"{"log": {"level": "${ZPARSE_TEST_UNSET_LEVEL:-warn}"}}"#,
+    )?;
+
+    let config = ConfigLoader::new()
+        .source(root.join("defaults.toml"))
+        .source(root.join("config.d/*.yaml"))
+        .source(root.join("overrides.json"))
+        .optional(root.join("local.toml"))
+        .load()?;
+
+    let expected =
+        from_str(r#"{"server": {"host": "localhost", "port": 8080}, "log": {"level": "warn"}}"#)?;
+    if config.value() != &expected {
+        return Err(format!("unexpected config: {:?}", config.value()).into());
+    }
+    let names: Vec<_> = config
+        .files()
+        .iter()
+        .filter_map(|path| path.file_name()?.to_str())
+        .collect();
+    if names
+        != [
+            "defaults.toml",
+            "10-base.yaml",
+            "20-site.yaml",
+            "overrides.json",
+        ]
+    {
+        return Err(format!("unexpected load order: {names:?}").into());
+    }
+
+    let source = |pointer| {
+        config
+            .source(pointer)
+            .and_then(|path| path.file_name()?.to_str())
+    };
+    if source("/server/host") != Some("defaults.toml")
+        || source("/server/port") != Some("20-site.yaml")
+        || source("/log/level") != Some("overrides.json")
+        || source("/missing").is_some()
+    {
+        return Err(format!(
+            "unexpected sources: {:?}",
+            config.sources().collect::<Vec<_>>()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_replaced_values_drop_stale_sources() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let root = dir.path();
+    write(
+        root,
+        "a.json",
+        r#"{"db": {"host": "a", "port": 1}, "tags": ["x"]}"#,
+    )?;
+    write(
+        root,
+        "b.json",
+        r#"{"db": {"host": "b"}, "tags": ["y", "z"]}"#,
+    )?;
+
+    let config = ConfigLoader::new()
+        .source(root.join("a.json"))
+        .source(root.join("b.json"))
+        .with_strategy(MergeStrategy::Shallow)
+        .load()?;
+
+    let db = config
+        .value()
+        .as_object()
+        .and_then(|object| object.get("db"));
+    let expected = from_str(r#"{"host": "b"}"#)?;
+    if db != Some(&expected) {
+        return Err(format!("shallow merge kept nested keys: {db:?}").into());
+    }
+    if config.source("/db/port").is_some()
+        || config.source("/tags/1") != Some(root.join("b.json").as_path())
+    {
+        return Err(format!(
+            "unexpected sources: {:?}",
+            config.sources().collect::<Vec<_>>()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_missing_required_source() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let result = ConfigLoader::new()
+        .source(dir.path().join("missing.toml"))
+        .load();
+    if !matches!(result, Err(ref err) if matches!(err.kind(), ErrorKind::Io { .. })) {
+        return Err(format!("expected an i/o error, got {result:?}").into());
+    }
+
+    let empty = ConfigLoader::new()
+        .source(dir.path().join("*.toml"))
+        .optional(dir.path().join("missing.toml"))
+        .load()?;
+    if empty.value() != &Value::Object(Default::default()) {
+        return Err(format!("expected an empty object, got {:?}", empty.value()).into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_errors_name_the_file() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    write(
+        dir.path(),
+        "app.toml",
+        "url = \"${ZPARSE_TEST_UNSET_URL}\"\n",
+    )?;
+    let loader = ConfigLoader::new().source(dir.path().join("app.toml"));
+
+    let Err(err) = loader.load() else {
+        return Err("undefined variable should fail".into());
+    };
+    if !matches!(err.kind(), ErrorKind::UndefinedVariable { .. })
+        || !err.message().contains("app.toml")
+    {
+        return Err(format!("unexpected error: {err:?}").into());
+    }
+    let raw = loader.with_interpolation(false).load()?;
+    let url = raw
+        .value()
+        .as_object()
+        .and_then(|object| object.get("url"))
+        .and_then(Value::as_string);
+    if url != Some("${ZPARSE_TEST_UNSET_URL}") {
+        return Err(format!("unexpected url: {url:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_provenance_reports_file_and_line() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let root = dir.path();
+    write(
+        root,
+        "base.toml",
+        "name = \"app\"\n\n[server]\nhost = \"localhost\"\nport = 80\n\n[[workers]]\nid = 1\n\n[[workers]]\nid = 2\nlimits = { cpu = 2 }\n",
+    )?;
+    write(
+        root,
+        "site.yaml",
+        "server:\n  port: 8080\nreplicas:\n  - zone: a\n  - zone: b\n",
+    )?;
+    write(
+        root,
+        "local.json",
+        "{\n  \"server\": {\n    \"tls\": {\"enabled\": true}\n  },\n  \"tags\": [{\"k\": 1}, {\"k\": 2}]\n}\n",
+    )?;
+
+    let config = ConfigLoader::new()
+        .source(root.join("base.toml"))
+        .source(root.join("site.yaml"))
+        .source(root.join("local.json"))
+        .load()?;
+
+    let origin = |pointer| {
+        config.provenance(pointer).map(|origin| {
+            let name = origin.path.file_name().and_then(|name| name.to_str());
+            (name.unwrap_or_default().to_string(), origin.line)
+        })
+    };
+    let expected = [
+        ("/name", "base.toml", Some(1)),
+        ("/server/host", "base.toml", Some(4)),
+        ("/server/port", "site.yaml", Some(2)),
+        ("/workers/1/id", "base.toml", Some(11)),
+        ("/workers/1/limits/cpu", "base.toml", Some(12)),
+        ("/replicas/1/zone", "site.yaml", Some(5)),
+        ("/server/tls/enabled", "local.json", Some(3)),
+        ("/tags/1/k", "local.json", Some(5)),
+    ];
+    for (pointer, file, line) in expected {
+        let found = origin(pointer);
+        if found != Some((file.to_string(), line)) {
+            return Err(format!("unexpected provenance for {pointer}: {found:?}").into());
+        }
+    }
+
+    let display = config
+        .provenance("/server/port")
+        .map(|origin| origin.to_string())
+        .unwrap_or_default();
+    if !display.ends_with("site.yaml:2") {
+        return Err(format!("unexpected display: {display}").into());
+    }
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use zparse::{Format, convert};
+use zparse::{ConvertOptions, Format, convert, convert_with_options};
 
 #[test]
 fn test_json_to_toml() -> Result<(), Box<dyn std::error::Error>> {
@@ -61,3 +61,120 @@ fn test_csv_to_json() -> Result<(), Box<dyn std::error::Error>> {
     }
     Ok(())
 }
+
+#[test]
+fn test_pretty_json_output() -> zparse::Result<()> {
+    let options = ConvertOptions {
+        format: zparse::FormatOptions::new().with_pretty(true),
+        ..Default::default()
+    };
+    let output = convert_with_options(
+        "{\"a\":[1,{}],\"b\":[]}",
+        Format::Json,
+        Format::Json,
+        &options,
+    )?;
+    if output != "{\n  \"a\": [\n    1,\n    {}\n  ],\n  \"b\": []\n}" {
+        return Err(zparse::Error::with_message(
+            zparse::ErrorKind::InvalidToken,
+            zparse::Span::empty(),
+            output,
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_serializers_match_serialize() -> Result<(), Box<dyn std::error::Error>> {
+    use zparse::{FormatOptions, JsonSerializer, Serializer, YamlSerializer};
+
+    fn emit(serializer: &dyn Serializer, value: &zparse::Value) -> zparse::Result<String> {
+        serializer.serialize(value, &FormatOptions::new().with_pretty(true))
+    }
+
+    let value = zparse::from_str(r#"{"name":"test","tags":["a","b"]}"#)?;
+    for format in [
+        Format::Json,
+        Format::Csv,
+        Format::Toml,
+        Format::Yaml,
+        Format::Xml,
+    ] {
+        let options = FormatOptions::new().with_pretty(true);
+        let expected = zparse::serialize_with_options(&value, format, &options).ok();
+        if emit(format.serializer(), &value).ok() != expected {
+            return Err(format!("{format:?} serializer differs from serialize").into());
+        }
+    }
+    if emit(&JsonSerializer, &value)?
+        != "{\n  \"name\": \"test\",\n  \"tags\": [\n    \"a\",\n    \"b\"\n  ]\n}"
+    {
+        return Err("unexpected pretty JSON".into());
+    }
+    if emit(&YamlSerializer, &value)? != "name: \"test\"\ntags:\n  - \"a\"\n  - \"b\"" {
+        return Err("unexpected YAML".into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_yaml_output_quotes_keys_with_indicators() -> Result<(), Box<dyn std::error::Error>> {
+    let input = r##"{"plain key":1,"#hash":2,"a: b":3,"-dash":4}"##;
+    let output = convert(input, Format::Json, Format::Yaml)?;
+    if output != "plain key: 1\n\"#hash\": 2\n\"a: b\": 3\n\"-dash\": 4" {
+        return Err(format!("unexpected yaml: {output:?}").into());
+    }
+    let back = convert(&output, Format::Yaml, Format::Json)?;
+    if back != input {
+        return Err(format!("keys changed in round trip: {back}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_xml_output_names_are_well_formed() -> Result<(), Box<dyn std::error::Error>> {
+    let input = r#"{"key with spaces":1,"@at":2,"1st":3,"ns:tag":4,"日本":5}"#;
+    let output = convert(input, Format::Json, Format::Xml)?;
+    let expected = "<root><key_with_spaces>1</key_with_spaces><_at>2</_at><_1st>3</_1st>\
+                    <ns:tag>4</ns:tag><日本>5</日本></root>";
+    if output != expected {
+        return Err(format!("unexpected xml: {output}").into());
+    }
+    convert(&output, Format::Xml, Format::Json)?;
+    Ok(())
+}
+
+#[test]
+fn test_validate_agrees_with_parse() -> Result<(), Box<dyn std::error::Error>> {
+    let cases = [
+        (
+            Format::Json,
+            r#"{"a": ["x\n", 1.5, null], "b": {"c": true}}"#,
+        ),
+        (Format::Json, r#"{"a": "unterminated}"#),
+        (Format::Json, r#"["bad \q escape"]"#),
+        (Format::Json, "[1, 2] 3"),
+        (Format::Json, ""),
+        (Format::Csv, "name,age\n\"Smith, J\",42\n"),
+        (Format::Csv, "name,age\n\"open,42\n"),
+        (Format::Toml, "[a]\nb = 1\n"),
+        (Format::Toml, "[a]\nb = 1\n[a]\n"),
+        (Format::Yaml, "a:\n  - b\n  - c: d\n"),
+        (Format::Yaml, "a: [b\n"),
+        (Format::Xml, "<a><b x=\"1\">t</b></a>"),
+        (Format::Xml, "<a><b></a>"),
+    ];
+    let options = ConvertOptions::default();
+    for (format, input) in cases {
+        let parsed = zparse::parse_with_options(input, format, &options).map(drop);
+        let validated = zparse::validate(input, format, &options);
+        if parsed.as_ref().err().map(zparse::Error::kind)
+            != validated.as_ref().err().map(zparse::Error::kind)
+        {
+            return Err(
+                format!("{format:?} {input:?}: parse {parsed:?}, validate {validated:?}").into(),
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use zparse::converter::{Converter, CsvToToml, FromXml, ToXml, XmlToCsv, converter};
+use zparse::{ConvertOptions, Format, XmlMapping, convert};
+
+#[test]
+fn test_converter_matches_convert_for_every_pair() {
+    let inputs = [
+        (Format::Json, r#"[{"name":"Ada","age":36}]"#),
+        (Format::Csv, "name,age\nAda,36\n"),
+        (Format::Toml, "name = \"Ada\"\nage = 36\n"),
+        (Format::Yaml, "name: Ada\nage: 36\n"),
+        (
+            Format::Xml,
+            "<root><row><name>Ada</name><age>36</age></row></root>",
+        ),
+    ];
+    for (from, input) in inputs {
+        for (to, _) in inputs {
+            let expected = convert(input, from, to).ok();
+            let actual = converter(from, to)
+                .convert(input, &ConvertOptions::default())
+                .ok();
+            assert_eq!(actual, expected, "{from:?} -> {to:?}");
+        }
+    }
+}
+
+#[test]
+fn test_xml_mapping_names_keys_and_elements() {
+    let mapping = XmlMapping {
+        root: "doc".to_string(),
+        attributes_key: "$attrs".to_string(),
+        text_key: "$text".to_string(),
+        ..XmlMapping::default()
+    };
+    let options = ConvertOptions::default();
+
+    let to_json = FromXml {
+        to: Format::Json,
+        mapping: mapping.clone(),
+    };
+    let json = to_json.convert(r#"<doc><item id="1">a</item></doc>"#, &options);
+    assert_eq!(
+        json.ok().as_deref(),
+        Some(r#"{"doc":{"item":{"$attrs":{"id":"1"},"$text":"a"}}}"#)
+    );
+
+    let to_xml = ToXml {
+        from: Format::Json,
+        mapping,
+    };
+    let xml = to_xml.convert(r#"{"item":{"$attrs":{"id":"1"},"$text":"a"}}"#, &options);
+    assert_eq!(
+        xml.ok().as_deref(),
+        Some(r#"<doc><item id="1">a</item></doc>"#)
+    );
+}
+
+#[test]
+fn test_csv_strategies_take_their_own_names() {
+    let options = ConvertOptions::default();
+    let toml = CsvToToml {
+        table: "people".to_string(),
+    }
+    .convert("name\nAda\n", &options);
+    assert_eq!(toml.ok().as_deref(), Some("people = [{name = \"Ada\"}]"));
+
+    let csv = XmlToCsv {
+        mapping: XmlMapping {
+            row: "person".to_string(),
+            ..XmlMapping::default()
+        },
+    }
+    .convert(
+        "<people><person><name>Ada</name></person><row/></people>",
+        &options,
+    );
+    assert_eq!(csv.ok().as_deref(), Some("name\n\"Ada\"\n"));
+}
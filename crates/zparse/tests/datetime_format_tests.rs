@@ -0,0 +1,90 @@
+use time::UtcOffset;
+use zparse::{
+    Format, FormatOptions, TomlDatetime, convert_with_options, from_toml_str,
+    serialize_with_options,
+};
+
+const INPUT: &str = "local = 1979-05-27T07:32:00\noffset = 1979-05-27T07:32:00-07:00\ndate = 1979-05-27\ntime = 07:32:00\n";
+
+fn write(format: Format, options: FormatOptions) -> Option<String> {
+    let value = from_toml_str(INPUT).ok()?;
+    serialize_with_options(&value, format, &options).ok()
+}
+
+fn plus_two() -> UtcOffset {
+    UtcOffset::from_hms(2, 0, 0).unwrap_or(UtcOffset::UTC)
+}
+
+#[test]
+fn test_datetimes_are_kept_by_default() {
+    assert_eq!(
+        write(Format::Json, FormatOptions::default()).as_deref(),
+        Some(
+            r#"{"local":"1979-05-27T07:32:00","offset":"1979-05-27T07:32:00-07:00","date":"1979-05-27","time":"07:32:00"}"#
+        )
+    );
+}
+
+#[test]
+fn test_local_offset_is_attached_to_local_datetimes() {
+    let options = FormatOptions::new().with_datetime_local_offset(Some(plus_two()));
+    assert_eq!(
+        write(Format::Json, options).as_deref(),
+        Some(
+            r#"{"local":"1979-05-27T07:32:00+02:00","offset":"1979-05-27T07:32:00-07:00","date":"1979-05-27","time":"07:32:00"}"#
+        )
+    );
+}
+
+#[test]
+fn test_utc_normalization() {
+    let options = FormatOptions::new().with_datetime_utc(true);
+    assert_eq!(
+        write(Format::Yaml, options).as_deref(),
+        Some(
+            "local: 1979-05-27T07:32:00\noffset: 1979-05-27T14:32:00Z\ndate: 1979-05-27\ntime: 07:32:00"
+        )
+    );
+
+    let options = options.with_datetime_local_offset(Some(plus_two()));
+    assert_eq!(
+        write(Format::Toml, options).as_deref(),
+        Some(
+            "local = 1979-05-27T05:32:00Z\noffset = 1979-05-27T14:32:00Z\ndate = 1979-05-27\ntime = 07:32:00"
+        )
+    );
+}
+
+#[test]
+fn test_toml_to_json_conversion_uses_datetime_options() {
+    let mut options = zparse::ConvertOptions::default();
+    options.format = options.format.with_datetime_utc(true);
+    assert_eq!(
+        convert_with_options(
+            "at = 2024-01-01T00:30:00+01:00",
+            Format::Toml,
+            Format::Json,
+            &options
+        )
+        .ok()
+        .as_deref(),
+        Some(r#"{"at":"2023-12-31T23:30:00Z"}"#)
+    );
+}
+
+#[test]
+fn test_toml_datetime_helpers() {
+    let local: Option<TomlDatetime> = "1979-05-27T07:32:00".parse().ok();
+    assert_eq!(
+        local
+            .map(|dt| dt.with_local_offset(plus_two()).to_utc().to_string())
+            .as_deref(),
+        Some("1979-05-27T05:32:00Z")
+    );
+    let date: Option<TomlDatetime> = "1979-05-27".parse().ok();
+    assert_eq!(
+        date.map(|dt| dt.with_local_offset(plus_two()).to_utc().to_string())
+            .as_deref(),
+        Some("1979-05-27")
+    );
+}
@@ -0,0 +1,99 @@
+//! Deep nesting with unlimited configs must not overflow the stack; these run
+//! on the default test thread stack.
+
+use zparse::error::ErrorKind;
+use zparse::{
+    ConvertOptions, Format, JsonConfig, TomlConfig, Value, XmlConfig, YamlConfig,
+    convert_with_options,
+};
+
+const DEPTH: usize = 100_000;
+
+fn unlimited() -> ConvertOptions {
+    ConvertOptions {
+        json: JsonConfig::unlimited(),
+        toml: TomlConfig::unlimited(),
+        yaml: YamlConfig::unlimited(),
+        xml: XmlConfig::unlimited(),
+        ..Default::default()
+    }
+}
+
+fn nested_arrays(depth: usize) -> String {
+    format!("{}{}", "[".repeat(depth), "]".repeat(depth))
+}
+
+#[test]
+fn test_deep_json_round_trips_and_drops() {
+    let input = nested_arrays(DEPTH);
+    let value = zparse::parse_with_options(&input, Format::Json, &unlimited()).unwrap_or_default();
+    assert_eq!(value.depth(), DEPTH);
+
+    let copy = value.clone();
+    assert_eq!(zparse::serialize(&copy, Format::Json).ok(), Some(input));
+    drop(copy);
+    drop(value);
+}
+
+#[test]
+fn test_deep_xml_converts_both_ways() {
+    let input = format!("{}{}", "<a>".repeat(DEPTH), "</a>".repeat(DEPTH));
+    let json = convert_with_options(&input, Format::Xml, Format::Json, &unlimited());
+    assert!(
+        json.as_deref()
+            .is_ok_and(|json| json.starts_with(r#"{"a":{"a":"#))
+    );
+
+    let xml =
+        json.and_then(|json| convert_with_options(&json, Format::Json, Format::Xml, &unlimited()));
+    assert!(xml.is_ok_and(|xml| xml.len() == 7 * DEPTH + 10 && xml.ends_with("</a></a></root>")));
+}
+
+#[test]
+fn test_deep_toml_inline_arrays() {
+    let input = format!("a = {}\n", nested_arrays(DEPTH));
+    let output = convert_with_options(&input, Format::Toml, Format::Toml, &unlimited());
+    assert_eq!(output.ok().map(|toml| toml.len()), Some(input.len()));
+}
+
+#[test]
+fn test_deep_yaml_flow_and_block() {
+    let flow = nested_arrays(DEPTH);
+    let value = zparse::parse_with_options(&flow, Format::Yaml, &unlimited()).unwrap_or_default();
+    assert_eq!(value.depth(), DEPTH);
+
+    // Block nesting needs a column of indentation per level
+    let depth = 3_000;
+    let mut block = String::new();
+    for level in 0..depth {
+        block.push_str(&" ".repeat(level));
+        block.push_str("a:\n");
+    }
+    block.push_str(&" ".repeat(depth));
+    block.push_str("b: 1\n");
+    let mut parser = zparse::YamlParser::with_config(block.as_bytes(), YamlConfig::unlimited());
+    let mut events = 0;
+    while let Ok(Some(_)) = parser.next_event() {
+        events += 1;
+    }
+    // Start and end of every mapping, each key and the one scalar
+    assert_eq!(events, 3 * (depth + 1) + 1);
+}
+
+#[test]
+fn test_default_limits_stop_deep_input() {
+    let xml = format!("{}{}", "<a>".repeat(200), "</a>".repeat(200));
+    let err = zparse::from_xml_str(&xml)
+        .err()
+        .map(|err| err.kind().clone());
+    assert_eq!(err, Some(ErrorKind::MaxDepthExceeded { max: 128 }));
+
+    let yaml = "a:\n  b:\n    c: 1\n";
+    let mut parser = zparse::YamlParser::with_config(yaml.as_bytes(), YamlConfig::new(2));
+    let err = parser.parse().err().map(|err| err.kind().clone());
+    assert_eq!(err, Some(ErrorKind::MaxDepthExceeded { max: 2 }));
+    assert!(matches!(
+        zparse::from_yaml_str(yaml),
+        Ok(Value::Object(obj)) if obj.len() == 1
+    ));
+}
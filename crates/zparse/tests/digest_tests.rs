@@ -0,0 +1,79 @@
+use zparse::{Array, Format, Value, from_str, parse_bytes};
+
+fn parse(input: &str) -> Value {
+    from_str(input).unwrap_or_default()
+}
+
+#[test]
+fn test_hash_canonical_is_stable() {
+    // Pinned so the encoding never changes between releases
+    assert_eq!(
+        Value::Null.hash_canonical().to_string(),
+        "1b16b1df538ba12dc3f97edbb85caa7050d46c148134290feba80f8236c83db9"
+    );
+    assert_eq!(
+        parse(r#"{"name": "zparse", "port": 8080}"#)
+            .hash_canonical()
+            .to_string(),
+        "0e1b2cf74e54158093b230a26b960643d40b86bfa706a29c871d7d6b9b9d77e8"
+    );
+    // Inputs spanning several blocks and needing an extra padding block
+    assert_eq!(
+        Value::from("x".repeat(1000)).hash_canonical().to_string(),
+        "e6c0b888a2d45d17f85275766f14557244656550ded4c4681b8b49a66b9e921c"
+    );
+    assert_eq!(
+        Value::from("y".repeat(47)).hash_canonical().to_string(),
+        "c5c7cc9699ce7318bca61ffc81431ba575d0a02fc3ccfb288d47bc5745b7fad7"
+    );
+    // Many small updates that straddle block boundaries
+    let items: Array = (0..50).map(|len| Value::from("k".repeat(len))).collect();
+    assert_eq!(
+        Value::Array(items).hash_canonical().to_string(),
+        "44513139ac32f29582be488b0b09f3de13f29e5155ad44e6766da00d724a4691"
+    );
+}
+
+#[test]
+fn test_hash_canonical_ignores_key_order_and_format() {
+    let json = parse(r#"{"port": 8080, "name": "zparse", "tags": ["a"]}"#);
+    let toml = parse_bytes(
+        b"name = \"zparse\"\ntags = [\"a\"]\nport = 8080\n",
+        Format::Toml,
+    )
+    .unwrap_or_default();
+
+    assert_eq!(json.hash_canonical(), toml.hash_canonical());
+    assert_eq!(
+        Value::Number(-0.0).hash_canonical(),
+        Value::Number(0.0).hash_canonical()
+    );
+    assert_eq!(
+        Value::Number(f64::NAN).hash_canonical(),
+        Value::Number(-f64::NAN).hash_canonical()
+    );
+}
+
+#[test]
+fn test_hash_canonical_distinguishes_values() {
+    let digests = [
+        Value::Null,
+        Value::Bool(false),
+        Value::Number(0.0),
+        Value::from(""),
+        Value::Bytes(Vec::new()),
+        Value::Array(Array::new()),
+        parse("{}"),
+        parse(r#"["a", "b"]"#),
+        parse(r#"["ab"]"#),
+        parse(r#"{"a": "b"}"#),
+        parse(r#"{"ab": ""}"#),
+    ]
+    .map(|value| value.hash_canonical());
+
+    for (i, a) in digests.iter().enumerate() {
+        for b in digests.iter().skip(i + 1) {
+            assert_ne!(a, b);
+        }
+    }
+}
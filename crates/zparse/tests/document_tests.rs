@@ -0,0 +1,81 @@
+use zparse::{
+    ConvertOptions, Extension, Format, JsonConfig, Value, parse_document, parse_document_file,
+};
+
+#[test]
+fn test_plain_json_has_no_extensions() -> Result<(), Box<dyn std::error::Error>> {
+    let document = parse_document(r#"{"a": 1}"#, Format::Json, &ConvertOptions::default())?;
+    if document.format != Format::Json || document.is_lenient() || document.source.is_some() {
+        return Err(format!("unexpected document: {document:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_json_extensions_are_recorded() -> Result<(), Box<dyn std::error::Error>> {
+    let options = ConvertOptions {
+        json: JsonConfig::default()
+            .with_comments(true)
+            .with_trailing_commas(true),
+        ..Default::default()
+    };
+    let input = "// settings\n{\"a\": [1, 2,], /* note */ \"b\": true,}";
+    let document = parse_document(input, Format::Json, &options)?;
+
+    if document.extensions != [Extension::JsonComments, Extension::JsonTrailingCommas] {
+        return Err(format!("unexpected extensions: {:?}", document.extensions).into());
+    }
+    if document.warnings != ["stripped 2 comment(s)", "accepted 2 trailing comma(s)"] {
+        return Err(format!("unexpected warnings: {:?}", document.warnings).into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_byte_order_mark_is_stripped() -> Result<(), Box<dyn std::error::Error>> {
+    let document = parse_document("\u{feff}[1]", Format::Json, &ConvertOptions::default())?;
+    if document.extensions != [Extension::ByteOrderMark] {
+        return Err(format!("unexpected extensions: {:?}", document.extensions).into());
+    }
+    if document.into_value() != Value::Array(vec![Value::Number(1.0)].into_iter().collect()) {
+        return Err("unexpected value".into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_xml_version_is_detected() -> Result<(), Box<dyn std::error::Error>> {
+    let input = "<?xml version='1.1' encoding=\"UTF-8\"?><root><a>1</a></root>";
+    let document = parse_document(input, Format::Xml, &ConvertOptions::default())?;
+    if document.version.as_deref() != Some("1.1") {
+        return Err(format!("unexpected version: {:?}", document.version).into());
+    }
+
+    let document = parse_document("<root/>", Format::Xml, &ConvertOptions::default())?;
+    if document.version.is_some() {
+        return Err(format!("unexpected version: {:?}", document.version).into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_parse_document_file_records_source() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "name = \"zparse\"\n")?;
+
+    let document = parse_document_file(&path, &ConvertOptions::default())?;
+    if document.format != Format::Toml || document.source.as_deref() != Some(path.as_path()) {
+        return Err(format!("unexpected document: {document:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_parse_document_file_unknown_extension() {
+    let result = parse_document_file("settings.unknown", &ConvertOptions::default());
+    assert!(matches!(
+        result,
+        Err(err) if matches!(err.kind(), zparse::ErrorKind::UnknownFormat { .. })
+    ));
+}
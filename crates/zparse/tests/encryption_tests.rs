@@ -0,0 +1,94 @@
+use zparse::encryption::{Cipher, Encryption, Marker};
+use zparse::{Error, ErrorKind, Format, FormatOptions, Redactor, Span, from_str};
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+/// Tags each payload with the pointer it was encrypted at
+struct TaggingCipher;
+
+impl Cipher for TaggingCipher {
+    fn decrypt(&self, pointer: &str, payload: &str) -> zparse::Result<String> {
+        payload
+            .strip_prefix(&format!("{pointer}:"))
+            .map(str::to_string)
+            .ok_or_else(|| {
+                Error::with_message(
+                    ErrorKind::InvalidToken,
+                    Span::empty(),
+                    format!("payload does not belong at {pointer}"),
+                )
+            })
+    }
+
+    fn encrypt(&self, pointer: &str, plaintext: &str) -> zparse::Result<String> {
+        Ok(format!("{pointer}:{plaintext}"))
+    }
+}
+
+#[test]
+fn test_decrypt_marked_values_anywhere() -> TestResult {
+    let hooks = Encryption::new(&TaggingCipher);
+    let value = hooks.parse(
+        "db:\n  host: \"ENC[/db/host:localhost]\"\n  port: 5432\nitems:\n  - \"ENC[/items/0:a]\"\n  - \"ENC[not closed\"\n",
+        Format::Yaml,
+        &Default::default(),
+    )?;
+
+    let expected = from_str(
+        r#"{"db": {"host": "localhost", "port": 5432}, "items": ["a", "ENC[not closed"]}"#,
+    )?;
+    if value != expected {
+        return Err(format!("unexpected decrypted value: {value:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_decrypt_errors_come_from_the_cipher() -> TestResult {
+    let mut value = from_str(r#"{"moved": "ENC[/elsewhere:secret]"}"#)?;
+    match Encryption::new(&TaggingCipher).decrypt(&mut value) {
+        Err(err) if err.to_string().contains("does not belong at /moved") => Ok(()),
+        other => Err(format!("expected a cipher error, got {other:?}").into()),
+    }
+}
+
+#[test]
+fn test_encrypt_only_matching_keys_once() -> TestResult {
+    let hooks = Encryption::new(&TaggingCipher);
+    let value = from_str(
+        r#"{"user": "app", "password": "hunter2", "secrets": {"api": "k", "n": 1}, "token": "ENC[/token:t]"}"#,
+    )?;
+
+    let toml = hooks.serialize(&value, Format::Toml, &FormatOptions::default())?;
+    let expected = "user = \"app\"\npassword = \"ENC[/password:hunter2]\"\nsecrets = {api = \"ENC[/secrets/api:k]\", n = 1}\ntoken = \"ENC[/token:t]\"";
+    if toml != expected {
+        return Err(format!("unexpected encrypted output: {toml:?}").into());
+    }
+
+    let round_trip = hooks.parse(&toml, Format::Toml, &Default::default())?;
+    let expected = from_str(
+        r#"{"user": "app", "password": "hunter2", "token": "t", "secrets": {"api": "k", "n": 1}}"#,
+    )?;
+    if round_trip != expected {
+        return Err(format!("unexpected round trip: {round_trip:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_custom_marker_and_keys() -> TestResult {
+    let hooks = Encryption::new(&TaggingCipher)
+        .with_marker(Marker {
+            prefix: "vault:".to_string(),
+            suffix: String::new(),
+        })
+        .with_keys(Redactor::new(["dsn"]));
+    let mut value = from_str(r#"{"dsn": "postgres://db", "password": "plain"}"#)?;
+    hooks.encrypt(&mut value)?;
+
+    let expected = from_str(r#"{"dsn": "vault:/dsn:postgres://db", "password": "plain"}"#)?;
+    if value != expected {
+        return Err(format!("unexpected encrypted value: {value:?}").into());
+    }
+    Ok(())
+}
@@ -19,3 +19,16 @@ fn test_error_display() {
     assert!(display.contains("error at"));
     assert!(display.contains("invalid escape sequence"));
 }
+
+#[test]
+fn test_error_context_frames() {
+    let err = Error::at(ErrorKind::InvalidToken, 0, 1, 1)
+        .context("while parsing key 'a'")
+        .context("while parsing table [t]");
+    assert_eq!(
+        err.frames(),
+        ["while parsing key 'a'", "while parsing table [t]"]
+    );
+    let display = err.to_string();
+    assert!(display.ends_with("\n  while parsing key 'a'\n  while parsing table [t]"));
+}
@@ -0,0 +1,93 @@
+#![cfg(feature = "unstable")]
+
+use zparse::event::{Container, Event, EventReader, ValueEvents, collect, reader};
+use zparse::{ConvertOptions, Format, Value};
+
+fn events(input: &str, format: Format) -> zparse::Result<Vec<Event>> {
+    let mut reader = reader(input, format, &ConvertOptions::default())?;
+    let mut events = Vec::new();
+    while let Some(event) = reader.read_event()? {
+        events.push(event);
+    }
+    Ok(events)
+}
+
+#[test]
+fn test_formats_produce_the_same_events() {
+    let expected = events(r#"{"name":"zparse","tags":["a","b"]}"#, Format::Json).ok();
+    assert!(expected.as_ref().is_some_and(|events| events.len() == 9));
+    assert_eq!(
+        events("name: zparse\ntags:\n  - a\n  - b\n", Format::Yaml).ok(),
+        expected
+    );
+    assert_eq!(
+        events("name = \"zparse\"\ntags = [\"a\", \"b\"]\n", Format::Toml).ok(),
+        expected
+    );
+}
+
+#[test]
+fn test_events_match_their_format_specific_ones() {
+    let mut parser = zparse::YamlParser::new(b"- 1\n- {a: 2}\n");
+    let mut unified = Vec::new();
+    while let Ok(Some(event)) = parser.next_event() {
+        unified.push(Event::from(event));
+    }
+    assert_eq!(
+        unified,
+        vec![
+            Event::ContainerStart(Container::Array),
+            Event::Value(Value::from(1)),
+            Event::ContainerStart(Container::Object),
+            Event::Key("a".to_string()),
+            Event::Value(Value::from(2)),
+            Event::ContainerEnd(Container::Object),
+            Event::ContainerEnd(Container::Array),
+        ]
+    );
+    assert_eq!(
+        zparse::json::Event::from(Event::ContainerStart(Container::Array)),
+        zparse::json::Event::ArrayStart
+    );
+}
+
+#[test]
+fn test_collect_rebuilds_values() {
+    let input = r#"<root id="1"><item>a</item><item>b</item></root>"#;
+    let options = ConvertOptions::default();
+    let value = reader(input, Format::Xml, &options).and_then(|mut events| collect(&mut *events));
+    assert_eq!(
+        value.ok(),
+        zparse::parse_with_options(input, Format::Xml, &options).ok()
+    );
+
+    let value = zparse::from_str(r#"[1, {"a": [true, null]}, "x"]"#).unwrap_or_default();
+    assert_eq!(collect(&mut ValueEvents::new(&value)).ok(), Some(value));
+}
+
+struct Replay(std::vec::IntoIter<Event>);
+
+impl EventReader for Replay {
+    fn read_event(&mut self) -> zparse::Result<Option<Event>> {
+        Ok(self.0.next())
+    }
+}
+
+#[test]
+fn test_collect_rejects_unbalanced_events() {
+    let cases = [
+        vec![Event::ContainerStart(Container::Array)],
+        vec![
+            Event::ContainerStart(Container::Array),
+            Event::ContainerEnd(Container::Object),
+        ],
+        vec![
+            Event::ContainerStart(Container::Object),
+            Event::Value(Value::Null),
+        ],
+        vec![Event::Key("a".to_string())],
+    ];
+    for events in cases {
+        assert!(collect(&mut Replay(events.into_iter())).is_err());
+    }
+}
@@ -0,0 +1,57 @@
+use zparse::{ConvertOptions, ErrorKind, Format, convert, parse_with_options, serialize};
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+const SAMPLES: [(Format, &str); 3] = [
+    (Format::Toml, "name = \"api\"\n"),
+    (Format::Yaml, "name: api\n"),
+    (Format::Xml, "<name>api</name>"),
+];
+
+#[test]
+fn test_json_and_csv_are_always_enabled() -> TestResult {
+    if !Format::Json.is_enabled() || !Format::Csv.is_enabled() {
+        return Err("json and csv must always be enabled".into());
+    }
+    let output = convert("name\napi\n", Format::Csv, Format::Json)?;
+    if output != r#"[{"name":"api"}]"# {
+        return Err(format!("unexpected output: {output}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_formats_follow_their_features() -> TestResult {
+    let expected = [
+        cfg!(feature = "toml"),
+        cfg!(feature = "yaml"),
+        cfg!(feature = "xml"),
+    ];
+    for ((format, _), enabled) in SAMPLES.iter().zip(expected) {
+        if format.is_enabled() != enabled {
+            return Err(format!("{format:?} enabled: {}", format.is_enabled()).into());
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_disabled_formats_fail_with_their_feature() -> TestResult {
+    for (format, input) in SAMPLES {
+        let parsed = parse_with_options(input, format, &ConvertOptions::default());
+        let written = serialize(&zparse::Value::from("api"), format);
+        let converted = convert(input, format, format);
+        if format.is_enabled() {
+            parsed?;
+            continue;
+        }
+        for err in [parsed.err(), written.err(), converted.err()] {
+            match err.as_ref().map(zparse::Error::kind) {
+                Some(ErrorKind::FormatDisabled { feature })
+                    if *feature == format!("{format:?}").to_lowercase() => {}
+                other => return Err(format!("{format:?}: unexpected {other:?}").into()),
+            }
+        }
+    }
+    Ok(())
+}
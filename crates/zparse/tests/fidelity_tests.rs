@@ -0,0 +1,302 @@
+//! Conversion fidelity across every ordered pair of formats
+//!
+//! Each document in `tests/fixtures/fidelity` is written in format `a`,
+//! converted to `b` and back to `a`, and must read back equal to the original
+//! once the lossiness rules of both formats are applied. A pair fails only
+//! when a value changes in a way those rules do not describe.
+//!
+//! The rules, applied to both sides before comparing:
+//!
+//! - JSON has no datetimes: they come back as strings that spell them.
+//! - TOML has no null: null is written as `""`. YAML to TOML goes through
+//!   [`zparse::TomlCompat`], which refuses null by default, so that pair is
+//!   unsupported for documents holding one. A TOML document is a table, so
+//!   documents with another root are unsupported.
+//! - XML holds only text: scalars come back as strings that spell them, the
+//!   document is wrapped in a `root` element (once per trip through XML), an
+//!   element's text is read as `{"#text": ...}`, a one-item array is read as
+//!   the item, an array inside an array is flattened into it, an empty array
+//!   disappears, null, `""` and `{}` all become an empty element, and
+//!   characters that cannot appear in element names become `_`. A root
+//!   array only survives when written from CSV, which puts each record in a
+//!   `row` element.
+//! - CSV holds an array of flat records, so any other document is
+//!   unsupported. Cells are text with types inferred on reading, and an
+//!   empty cell reads as null.
+//! - YAML has no further losses.
+//!
+//! Strings are compared with numbers, booleans and datetimes by what they
+//! spell, and object key order is ignored. The matrix of results is written
+//! to `fidelity-report.md` under `CARGO_TARGET_TMPDIR`.
+
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use zparse::{
+    CompareOptions, ConvertOptions, Format, Object, Value, convert, parse_with_options, serialize,
+    values_diff_first_mismatch, values_diff_first_mismatch_with,
+};
+
+const FORMATS: [Format; 5] = [
+    Format::Json,
+    Format::Yaml,
+    Format::Toml,
+    Format::Xml,
+    Format::Csv,
+];
+
+/// Outcome of one ordered pair for one document
+#[derive(Debug)]
+enum Outcome {
+    /// The document came back unchanged
+    Exact,
+    /// The document came back equal under the lossiness rules
+    Lossy,
+    /// One of the formats cannot hold the document, as the rules describe
+    Unsupported,
+    /// The document changed in a way the rules do not describe
+    Failed(String),
+}
+
+impl Outcome {
+    fn cell(&self) -> &'static str {
+        match self {
+            Self::Exact => "exact",
+            Self::Lossy => "lossy",
+            Self::Unsupported => "n/a",
+            Self::Failed(_) => "**FAIL**",
+        }
+    }
+}
+
+fn format_name(format: Format) -> &'static str {
+    match format {
+        Format::Json => "json",
+        Format::Yaml => "yaml",
+        Format::Toml => "toml",
+        Format::Xml => "xml",
+        Format::Csv => "csv",
+    }
+}
+
+/// Whether the rules let `original` make the trip from `a` to `b` and back
+fn supported(original: &Value, a: Format, b: Format) -> bool {
+    let records = original.as_array().is_some_and(|rows| {
+        !rows.is_empty()
+            && rows.iter().all(|row| {
+                row.as_object().is_some_and(|fields| {
+                    fields
+                        .values()
+                        .all(|value| !matches!(value, Value::Array(_) | Value::Object(_)))
+                })
+            })
+    });
+    let pair = [a, b];
+    if pair.contains(&Format::Toml) && original.as_object().is_none() {
+        return false;
+    }
+    if pair.contains(&Format::Csv) && !records {
+        return false;
+    }
+    if pair.contains(&Format::Xml) && original.as_array().is_some() && a != Format::Csv {
+        return false;
+    }
+    !(a == Format::Yaml && b == Format::Toml && contains_null(original))
+}
+
+fn contains_null(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Array(items) => items.iter().any(contains_null),
+        Value::Object(object) => object.values().any(contains_null),
+        _ => false,
+    }
+}
+
+/// An object key as the XML serializer names its element
+fn xml_name(key: &str) -> String {
+    let mut name: String = key
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if !name.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        name.insert(0, '_');
+    }
+    name
+}
+
+/// Apply the lossiness rules of every format in `lossy` to `value`
+fn normalize(value: &Value, lossy: &BTreeSet<&str>) -> Value {
+    let mut value = value.clone();
+    if lossy.contains("xml") {
+        while let Some(inner) = value
+            .as_object()
+            .filter(|object| object.len() == 1)
+            .and_then(|object| object.get("root"))
+        {
+            value = inner.clone();
+        }
+    }
+    normalize_item(value, lossy)
+}
+
+fn normalize_item(value: Value, lossy: &BTreeSet<&str>) -> Value {
+    let xml = lossy.contains("xml");
+    let value = match value {
+        Value::Null if lossy.contains("toml") => Value::String(String::new()),
+        Value::Array(items) => {
+            let mut flat = Vec::new();
+            for item in items {
+                match normalize_item(item, lossy) {
+                    Value::Array(inner) if xml => flat.extend(inner),
+                    item => flat.push(item),
+                }
+            }
+            match flat.len() {
+                0 if xml => Value::Null,
+                1 if xml => flat.pop().unwrap_or(Value::Null),
+                _ => Value::Array(flat.into()),
+            }
+        }
+        Value::Object(object) => {
+            if xml && object.len() == 1 {
+                if let Some(text) = object.get("#text") {
+                    return normalize_item(text.clone(), lossy);
+                }
+            }
+            if xml && object.is_empty() {
+                return Value::Null;
+            }
+            let mut out = Object::new();
+            for (key, item) in object {
+                let key = if xml { xml_name(&key) } else { key };
+                match normalize_item(item, lossy) {
+                    // An empty array disappears from XML entirely
+                    Value::Null if xml => {}
+                    item => {
+                        out.insert(key, item);
+                    }
+                }
+            }
+            Value::Object(out)
+        }
+        value => value,
+    };
+    match value {
+        Value::String(text) if text.is_empty() && (xml || lossy.contains("csv")) => Value::Null,
+        value => value,
+    }
+}
+
+/// Write `original` as `a`, convert it to `b` and back, and compare
+fn round_trip(original: &Value, a: Format, b: Format) -> Outcome {
+    if !supported(original, a, b) {
+        return Outcome::Unsupported;
+    }
+    let options = ConvertOptions::default();
+    let trip = || -> zparse::Result<Value> {
+        let text = serialize(original, a)?;
+        let there = convert(&text, a, b)?;
+        let back = convert(&there, b, a)?;
+        parse_with_options(&back, a, &options)
+    };
+    let back = match trip() {
+        Ok(back) => back,
+        Err(err) => return Outcome::Failed(format!("conversion error: {err}")),
+    };
+    if values_diff_first_mismatch(original, &back).is_none() {
+        return Outcome::Exact;
+    }
+    let lossy = BTreeSet::from([format_name(a), format_name(b)]);
+    let compare = CompareOptions::new()
+        .with_coerce_scalars(true)
+        .with_null_equals_missing(true);
+    match values_diff_first_mismatch_with(
+        &normalize(original, &lossy),
+        &normalize(&back, &lossy),
+        &compare,
+    ) {
+        None => Outcome::Lossy,
+        Some(mismatch) => Outcome::Failed(mismatch.to_string()),
+    }
+}
+
+fn corpus() -> Result<Vec<(String, Value)>, Box<dyn std::error::Error>> {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/fidelity");
+    let mut documents = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+            .to_string();
+        let format = zparse::detect_format_from_path(&path)
+            .ok_or_else(|| std::io::Error::other(format!("unknown format: {name}")))?;
+        let text = fs::read_to_string(&path)?;
+        documents.push((
+            name,
+            parse_with_options(&text, format, &ConvertOptions::default())?,
+        ));
+    }
+    documents.sort_by(|left, right| left.0.cmp(&right.0));
+    Ok(documents)
+}
+
+fn write_report(report: &str) -> std::io::Result<()> {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR"));
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join("fidelity-report.md"), report)
+}
+
+#[test]
+fn test_conversion_fidelity_matrix() -> Result<(), Box<dyn std::error::Error>> {
+    let mut report = String::from("# Conversion fidelity\n\n");
+    report.push_str(
+        "Rows are the format written first, columns the format converted to and back from.\n",
+    );
+    let mut failures = Vec::new();
+    for (name, original) in corpus()? {
+        write!(report, "\n## {name}\n\n| |")?;
+        for b in FORMATS {
+            write!(report, " {} |", format_name(b))?;
+        }
+        report.push_str("\n|---|");
+        report.push_str(&"---|".repeat(FORMATS.len()));
+        report.push('\n');
+        for a in FORMATS {
+            write!(report, "| {} |", format_name(a))?;
+            for b in FORMATS {
+                if a == b {
+                    report.push_str(" |");
+                    continue;
+                }
+                let outcome = round_trip(&original, a, b);
+                write!(report, " {} |", outcome.cell())?;
+                if let Outcome::Failed(reason) = outcome {
+                    failures.push(format!(
+                        "{name}: {} -> {} -> {}: {reason}",
+                        format_name(a),
+                        format_name(b),
+                        format_name(a)
+                    ));
+                }
+            }
+            report.push('\n');
+        }
+    }
+    write_report(&report)?;
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(failures.join("\n")).into())
+    }
+}
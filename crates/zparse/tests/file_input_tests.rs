@@ -0,0 +1,70 @@
+use zparse::{ConvertOptions, ErrorKind, FileInput, Format, Utf8Mode, Value, parse_path};
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+#[test]
+fn test_file_input_reads_without_copying() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("export.json");
+    std::fs::write(&path, "{\"rows\": [1, 2, 3]}")?;
+
+    let file = FileInput::open(&path)?;
+    let input = file.input();
+    if input.len() != 19 || input.filename() != Some(path.display().to_string().as_str()) {
+        return Err(format!("unexpected input: {input:?}").into());
+    }
+    let value = zparse::from_str(file.as_str()?)?;
+    if value.get("rows").and_then(|rows| rows.get(2)) != Some(&Value::from(3)) {
+        return Err(format!("unexpected value: {value:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_file_input_errors() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    let missing = FileInput::open(dir.path().join("missing.json"));
+    if !matches!(
+        missing.as_ref().map_err(|err| err.kind()),
+        Err(ErrorKind::Io { .. })
+    ) {
+        return Err(format!("expected an io error, got {missing:?}").into());
+    }
+
+    let path = dir.path().join("latin1.json");
+    std::fs::write(&path, b"\"caf\xe9\"")?;
+    let file = FileInput::open(&path)?;
+    let err = file.as_str().err().ok_or("expected invalid UTF-8")?;
+    if err.span().start.offset != 4 {
+        return Err(format!("unexpected error: {err:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_parse_path_reads_files() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "\u{feff}name = \"zparse\"\n")?;
+    if parse_path(&path)?.get("name") != Some(&Value::from("zparse")) {
+        return Err("unexpected value".into());
+    }
+
+    let empty = dir.path().join("empty.json");
+    std::fs::write(&empty, "")?;
+    if parse_path(&empty).is_ok() {
+        return Err("an empty JSON file should not parse".into());
+    }
+
+    let latin1 = dir.path().join("latin1.yaml");
+    std::fs::write(&latin1, b"name: caf\xe9\n")?;
+    let options = ConvertOptions {
+        utf8: Utf8Mode::Lossy,
+        ..Default::default()
+    };
+    let (format, value) = zparse::ops::parse_file(&latin1, &options)?;
+    if format != Format::Yaml || value.get("name") != Some(&Value::from("caf\u{fffd}")) {
+        return Err(format!("unexpected value: {value:?}").into());
+    }
+    Ok(())
+}
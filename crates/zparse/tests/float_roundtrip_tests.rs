@@ -0,0 +1,80 @@
+//! Property-based tests for float output
+//!
+//! Every finite f64 written by the serializers must read back bit for bit,
+//! whether it is printed in positional or scientific notation.
+
+use proptest::prelude::*;
+use proptest::test_runner::TestCaseError;
+use zparse::{Format, FormatOptions, Object, Value, parse_with_options, serialize_with_options};
+
+fn finite_f64() -> impl Strategy<Value = f64> {
+    prop_oneof![
+        proptest::num::f64::NORMAL,
+        proptest::num::f64::SUBNORMAL,
+        proptest::num::f64::ZERO,
+        // Coordinates and other short decimals are where drift was observed.
+        (-180_000_000_i32..180_000_000_i32).prop_map(|micro| f64::from(micro) / 1_000_000.0),
+    ]
+}
+
+fn round_trip(number: f64, format: Format, options: &FormatOptions) -> Result<(), TestCaseError> {
+    let mut object = Object::new();
+    object.insert("n", Value::Number(number));
+    let text = serialize_with_options(&Value::Object(object), format, options)
+        .map_err(|err| TestCaseError::fail(err.to_string()))?;
+    let parsed = parse_with_options(&text, format, &Default::default())
+        .map_err(|err| TestCaseError::fail(format!("{err} in {text:?}")))?;
+    let read = parsed
+        .as_object()
+        .and_then(|object| object.get("n"))
+        .and_then(Value::as_number);
+    prop_assert_eq!(read.map(f64::to_bits), Some(number.to_bits()), "{}", text);
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn json_floats_round_trip(number in finite_f64()) {
+        round_trip(number, Format::Json, &FormatOptions::default())?;
+    }
+
+    #[test]
+    fn json_scientific_floats_round_trip(number in finite_f64()) {
+        let options = FormatOptions::new().with_exponent_threshold(Some(5));
+        round_trip(number, Format::Json, &options)?;
+    }
+
+    #[test]
+    fn toml_floats_round_trip(number in finite_f64()) {
+        round_trip(number, Format::Toml, &FormatOptions::default())?;
+    }
+
+    #[test]
+    fn yaml_floats_round_trip(number in finite_f64()) {
+        round_trip(number, Format::Yaml, &FormatOptions::default())?;
+    }
+}
+
+#[test]
+fn test_shortest_representation() -> zparse::Result<()> {
+    let values = Value::Array(
+        vec![
+            Value::Number(0.1),
+            Value::Number(0.1 + 0.2),
+            Value::Number(f64::MAX),
+            Value::Number(f64::MIN_POSITIVE),
+            Value::Number(5e-324),
+        ]
+        .into(),
+    );
+    let options = FormatOptions::new().with_exponent_threshold(Some(16));
+    let output = serialize_with_options(&values, Format::Json, &options)?;
+    if output != "[0.1,0.30000000000000004,1.7976931348623157e308,2.2250738585072014e-308,5e-324]" {
+        return Err(zparse::Error::with_message(
+            zparse::ErrorKind::InvalidNumber,
+            zparse::Span::empty(),
+            output,
+        ));
+    }
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use zparse::{Format, detect_format_from_path};
+use zparse::{Format, detect_format_from_content, detect_format_from_path};
 
 #[test]
 fn detect_format_from_path_supports_extensions() {
@@ -17,3 +17,43 @@ fn detect_format_from_path_returns_none_for_unknown_or_missing_extensions() {
     assert_eq!(detect_format_from_path("input"), None);
     assert_eq!(detect_format_from_path("input.txt"), None);
 }
+
+#[test]
+fn detect_format_from_content_recognizes_each_format() {
+    assert_eq!(
+        detect_format_from_content("{\"name\": \"zparse\"}"),
+        Some(Format::Json)
+    );
+    assert_eq!(
+        detect_format_from_content("  [1, 2, 3]\n"),
+        Some(Format::Json)
+    );
+    assert_eq!(
+        detect_format_from_content("[server]\nport = 8080\n"),
+        Some(Format::Toml)
+    );
+    assert_eq!(
+        detect_format_from_content("title = \"x\"\n"),
+        Some(Format::Toml)
+    );
+    assert_eq!(
+        detect_format_from_content("server:\n  port: 8080\n"),
+        Some(Format::Yaml)
+    );
+    assert_eq!(detect_format_from_content("- a\n- b\n"), Some(Format::Yaml));
+    assert_eq!(
+        detect_format_from_content("<?xml version=\"1.0\"?><root/>"),
+        Some(Format::Xml)
+    );
+    assert_eq!(
+        detect_format_from_content("name,age\nada,36\n"),
+        Some(Format::Csv)
+    );
+}
+
+#[test]
+fn detect_format_from_content_returns_none_for_unrecognized_input() {
+    assert_eq!(detect_format_from_content(""), None);
+    assert_eq!(detect_format_from_content("   \n"), None);
+    assert_eq!(detect_format_from_content("just some words"), None);
+}
@@ -0,0 +1,167 @@
+use zparse::Format;
+use zparse::highlight::{Highlight, TokenClass, highlight, highlight_csv};
+
+/// Pair each highlighted range with its text
+fn classes(input: &str, highlights: &[Highlight]) -> Vec<(String, TokenClass)> {
+    highlights
+        .iter()
+        .filter_map(|(range, class)| Some((input.get(range.clone())?.to_string(), *class)))
+        .collect()
+}
+
+fn pairs(items: &[(&str, TokenClass)]) -> Vec<(String, TokenClass)> {
+    items
+        .iter()
+        .map(|(text, class)| ((*text).to_string(), *class))
+        .collect()
+}
+
+#[test]
+fn test_json_keys_values_and_comments() {
+    let input = "{\"a\": [1, true, null], // note\n \"b\": \"x\"}";
+    assert_eq!(
+        classes(input, &highlight(input, Format::Json)),
+        pairs(&[
+            ("{", TokenClass::Punctuation),
+            ("\"a\"", TokenClass::Key),
+            (":", TokenClass::Punctuation),
+            ("[", TokenClass::Punctuation),
+            ("1", TokenClass::Number),
+            (",", TokenClass::Punctuation),
+            ("true", TokenClass::Boolean),
+            (",", TokenClass::Punctuation),
+            ("null", TokenClass::Null),
+            ("]", TokenClass::Punctuation),
+            (",", TokenClass::Punctuation),
+            ("// note", TokenClass::Comment),
+            ("\"b\"", TokenClass::Key),
+            (":", TokenClass::Punctuation),
+            ("\"x\"", TokenClass::String),
+            ("}", TokenClass::Punctuation),
+        ])
+    );
+}
+
+#[test]
+fn test_invalid_tail_after_lexing_error() {
+    let input = "{\"a\": @oops}";
+    let highlights = highlight(input, Format::Json);
+    assert_eq!(
+        highlights.last(),
+        Some(&(6..input.len(), TokenClass::Invalid))
+    );
+}
+
+#[test]
+fn test_toml_headers_and_values() {
+    let input = "[server.http] # main\nport = 8080\nstarted = 1979-05-27\nlist = [\n  [1],\n]\n";
+    let highlighted = classes(input, &highlight(input, Format::Toml));
+    let expected = pairs(&[
+        ("[", TokenClass::Punctuation),
+        ("server", TokenClass::Key),
+        (".", TokenClass::Punctuation),
+        ("http", TokenClass::Key),
+        ("]", TokenClass::Punctuation),
+        ("# main", TokenClass::Comment),
+        ("port", TokenClass::Key),
+        ("=", TokenClass::Punctuation),
+        ("8080", TokenClass::Number),
+        ("started", TokenClass::Key),
+        ("=", TokenClass::Punctuation),
+        ("1979-05-27", TokenClass::Datetime),
+        ("list", TokenClass::Key),
+        ("=", TokenClass::Punctuation),
+        ("[", TokenClass::Punctuation),
+        ("[", TokenClass::Punctuation),
+        ("1", TokenClass::Number),
+        ("]", TokenClass::Punctuation),
+        (",", TokenClass::Punctuation),
+        ("]", TokenClass::Punctuation),
+    ]);
+    assert_eq!(highlighted, expected);
+}
+
+#[test]
+fn test_yaml_scalars_tags_and_comments() {
+    let input = "name: app # comment\nitems:\n  - 1\n  - ~\n  - !!str yes\n";
+    assert_eq!(
+        classes(input, &highlight(input, Format::Yaml)),
+        pairs(&[
+            ("name", TokenClass::Key),
+            (":", TokenClass::Punctuation),
+            ("app", TokenClass::String),
+            ("# comment", TokenClass::Comment),
+            ("items", TokenClass::Key),
+            (":", TokenClass::Punctuation),
+            ("-", TokenClass::Punctuation),
+            ("1", TokenClass::Number),
+            ("-", TokenClass::Punctuation),
+            ("~", TokenClass::Null),
+            ("-", TokenClass::Punctuation),
+            ("!!str", TokenClass::Tag),
+            ("yes", TokenClass::String),
+        ])
+    );
+}
+
+#[test]
+fn test_xml_tags_attributes_and_text() {
+    let input = "<?xml version=\"1.0\"?><!-- c --><a id='1'>hi <![CDATA[x]]></a><b/>";
+    assert_eq!(
+        classes(input, &highlight(input, Format::Xml)),
+        pairs(&[
+            ("<?xml version=\"1.0\"?>", TokenClass::Directive),
+            ("<!-- c -->", TokenClass::Comment),
+            ("<", TokenClass::Punctuation),
+            ("a", TokenClass::Tag),
+            ("id", TokenClass::Key),
+            ("=", TokenClass::Punctuation),
+            ("'1'", TokenClass::String),
+            (">", TokenClass::Punctuation),
+            ("hi", TokenClass::Text),
+            ("<![CDATA[x]]>", TokenClass::String),
+            ("</", TokenClass::Punctuation),
+            ("a", TokenClass::Tag),
+            (">", TokenClass::Punctuation),
+            ("<", TokenClass::Punctuation),
+            ("b", TokenClass::Tag),
+            ("/>", TokenClass::Punctuation),
+        ])
+    );
+}
+
+#[test]
+fn test_xml_unterminated_tag_is_invalid() {
+    let input = "<a>text</a";
+    assert_eq!(
+        highlight(input, Format::Xml).last(),
+        Some(&(7..10, TokenClass::Invalid))
+    );
+}
+
+#[test]
+fn test_csv_header_and_inferred_fields() {
+    let input = "name;age\n\"Ada\";36\n";
+    assert_eq!(
+        classes(input, &highlight_csv(input, b';')),
+        pairs(&[
+            ("name", TokenClass::Key),
+            (";", TokenClass::Punctuation),
+            ("age", TokenClass::Key),
+            ("\"Ada\"", TokenClass::String),
+            (";", TokenClass::Punctuation),
+            ("36", TokenClass::Number),
+        ])
+    );
+}
+
+#[test]
+fn test_ranges_are_sorted_and_disjoint() {
+    let input = "a: [1, {b: c}] # x\n";
+    let highlights = highlight(input, Format::Yaml);
+    assert!(
+        highlights
+            .windows(2)
+            .all(|pair| matches!(pair, [(first, _), (second, _)] if first.end <= second.start))
+    );
+}
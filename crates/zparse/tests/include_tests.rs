@@ -0,0 +1,56 @@
+use std::path::Path;
+
+use zparse::{ErrorKind, IncludeResolver, from_str, load_with_includes};
+
+fn fixture(name: &str) -> String {
+    format!(
+        "{}/tests/fixtures/include/{name}",
+        env!("CARGO_MANIFEST_DIR")
+    )
+}
+
+#[test]
+fn test_include_key_and_yaml_tag() -> Result<(), Box<dyn std::error::Error>> {
+    let value = load_with_includes(fixture("main.json"))?;
+    let expected = from_str(
+        r#"{
+            "app": {"name": "main", "port": 8080, "debug": true},
+            "features": ["a"],
+            "db": {"host": "localhost", "port": 5432}
+        }"#,
+    )?;
+    if value != expected {
+        return Err(format!("unexpected resolved document: {value:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_include_cycle_is_rejected() {
+    let result = load_with_includes(fixture("cycle_a.json"));
+    assert!(matches!(
+        result,
+        Err(err) if matches!(err.kind(), ErrorKind::InvalidInclude { .. })
+            && err.message().contains("cycle")
+    ));
+}
+
+#[test]
+fn test_include_outside_root_is_rejected() {
+    let result = load_with_includes(fixture("escape.json"));
+    assert!(matches!(
+        result,
+        Err(err) if err.message().contains("outside the include root")
+    ));
+}
+
+#[test]
+fn test_resolver_with_wider_root() -> Result<(), Box<dyn std::error::Error>> {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut resolver = IncludeResolver::new(&fixtures)?;
+    let value = resolver.load(fixture("escape.json"))?;
+    if !value.is_object() {
+        return Err(format!("expected included object, got {value:?}").into());
+    }
+    Ok(())
+}
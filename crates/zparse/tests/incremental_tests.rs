@@ -0,0 +1,97 @@
+use zparse::{Format, IncrementalDocument, from_toml_str, from_yaml_str};
+
+/// Whether the edited document is in the same state as one parsed afresh
+fn same_as_fresh(doc: &IncrementalDocument) -> bool {
+    let fresh = IncrementalDocument::new(doc.format(), doc.text());
+    doc.sections().eq(fresh.sections())
+        && doc.errors() == fresh.errors()
+        && doc.warnings() == fresh.warnings()
+        && doc.highlights() == fresh.highlights()
+        && doc.value().ok() == fresh.value().ok()
+}
+
+#[test]
+fn test_toml_edit_reparses_one_section() {
+    let mut doc = IncrementalDocument::new(Format::Toml, "a = 1\n[b]\nx = 1\n[c]\ny = 2\n");
+    let reparse = doc.edit(14..15, "22").ok();
+    assert_eq!(
+        reparse.map(|reparse| (reparse.range, reparse.sections)),
+        Some((6..17, 1))
+    );
+    assert_eq!(doc.text(), "a = 1\n[b]\nx = 22\n[c]\ny = 2\n");
+    assert!(same_as_fresh(&doc));
+}
+
+#[test]
+fn test_toml_errors_are_located_in_the_document() {
+    let mut doc = IncrementalDocument::new(Format::Toml, "[a]\nx = 1\n[b]\ny = 2\n");
+    assert!(doc.edit(18..19, "@").is_ok());
+    let errors = doc.errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors.first().map(|err| err.span().start.line), Some(4));
+    assert!(same_as_fresh(&doc));
+
+    assert!(doc.edit(18..19, "3").is_ok());
+    assert!(doc.errors().is_empty());
+    assert!(same_as_fresh(&doc));
+}
+
+#[test]
+fn test_toml_new_header_splits_section() {
+    let mut doc = IncrementalDocument::new(Format::Toml, "[a]\nx = 1\ny = 2\n[c]\nz = 3\n");
+    assert!(doc.edit(10..10, "[b]\n").is_ok());
+    assert_eq!(doc.sections().count(), 3);
+    assert!(same_as_fresh(&doc));
+}
+
+#[test]
+fn test_toml_unclosed_string_swallows_later_headers() {
+    let mut doc = IncrementalDocument::new(Format::Toml, "[a]\nx = 1\n[b]\ny = '''\n");
+    assert!(doc.edit(8..9, "'''").is_ok());
+    assert_eq!(doc.sections().count(), 1);
+    assert!(doc.errors().is_empty());
+    assert!(same_as_fresh(&doc));
+}
+
+#[test]
+fn test_toml_value_merges_sections() {
+    let input = "title = 'x'\n[[item]]\nname = 'a'\n[item.meta]\nok = true\n[[item]]\nname = 'b'\n";
+    let doc = IncrementalDocument::new(Format::Toml, input);
+    assert_eq!(doc.value().ok(), from_toml_str(input).ok());
+}
+
+#[test]
+fn test_toml_duplicate_table_across_sections() {
+    let doc = IncrementalDocument::new(Format::Toml, "[a]\nx = 1\n[b]\n[a]\ny = 2\n");
+    let errors = doc.errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors.first().map(|err| err.span().start.line), Some(4));
+    assert!(doc.value().is_err());
+}
+
+#[test]
+fn test_yaml_indented_insert_joins_previous_section() {
+    let mut doc = IncrementalDocument::new(Format::Yaml, "a:\n  x: 1\nb: 2\n");
+    assert!(doc.edit(10..10, "  y: 3\n").is_ok());
+    assert_eq!(doc.sections().count(), 2);
+    assert_eq!(
+        doc.value().ok(),
+        from_yaml_str("a:\n  x: 1\n  y: 3\nb: 2\n").ok()
+    );
+    assert!(same_as_fresh(&doc));
+}
+
+#[test]
+fn test_yaml_duplicate_key_across_sections_warns() {
+    let doc = IncrementalDocument::new(Format::Yaml, "# config\na: 1\nb: 2\na: 3\n");
+    assert_eq!(doc.warnings().len(), 1);
+    assert_eq!(doc.value().ok(), from_yaml_str("b: 2\na: 3\n").ok());
+}
+
+#[test]
+fn test_edit_outside_document_fails() {
+    let mut doc = IncrementalDocument::new(Format::Json, "{\"a\": \"é\"}");
+    assert!(doc.edit(3..40, "").is_err());
+    assert!(doc.edit(8..9, "").is_err());
+    assert_eq!(doc.text(), "{\"a\": \"é\"}");
+}
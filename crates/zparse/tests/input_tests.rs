@@ -1,4 +1,6 @@
-use zparse::Input;
+use zparse::{
+    ConvertOptions, Format, Input, Utf8Mode, Value, parse_bytes, parse_bytes_with_options,
+};
 
 #[test]
 fn test_input_from_str() {
@@ -31,3 +33,83 @@ fn test_input_from_str_trait() {
     let input: Input = "hello".into();
     assert_eq!(input.len(), 5);
 }
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+fn ensure_eq<T: PartialEq + std::fmt::Debug>(left: T, right: T) -> TestResult {
+    if left == right {
+        Ok(())
+    } else {
+        Err(format!("assertion failed: left={left:?} right={right:?}").into())
+    }
+}
+
+fn utf8_options(utf8: Utf8Mode) -> ConvertOptions {
+    ConvertOptions {
+        utf8,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_strict_utf8_points_at_the_invalid_byte() -> TestResult {
+    let cases: [(Format, &[u8], u32); 5] = [
+        (Format::Json, b"{\n  \"name\": \"caf\xe9\"\n}", 15),
+        (Format::Yaml, b"a: 1\nname: caf\xe9\n", 10),
+        (Format::Toml, b"a = 1\nname = \"caf\xe9\"\n", 12),
+        (Format::Csv, b"a,name\n1,caf\xe9\n", 6),
+        (Format::Xml, b"<a>\n<name>caf\xe9</name></a>", 10),
+    ];
+    for (format, input, col) in cases {
+        let Err(err) = parse_bytes(input, format) else {
+            return Err(format!("{format:?}: invalid UTF-8 should fail").into());
+        };
+        let start = err.span().start;
+        if (start.line, start.col) != (2, col) || !err.message().contains("0xE9") {
+            return Err(format!("{format:?}: unexpected error {err:?}").into());
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_lossy_utf8_replaces_invalid_sequences() -> TestResult {
+    let value = parse_bytes_with_options(
+        b"name: caf\xe9-\xf0\x9f\n",
+        Format::Yaml,
+        &utf8_options(Utf8Mode::Lossy),
+    )?;
+    ensure_eq(
+        value.get("name"),
+        Some(&Value::from("caf\u{fffd}-\u{fffd}")),
+    )
+}
+
+#[test]
+fn test_bytes_utf8_keeps_invalid_strings_as_bytes() -> TestResult {
+    let options = utf8_options(Utf8Mode::Bytes);
+    let value = parse_bytes_with_options(
+        b"{\"name\": \"caf\xe9\", \"ok\": \"caf\xc3\xa9\", \"k\xff\": 1}",
+        Format::Json,
+        &options,
+    )?;
+    ensure_eq(value.get("name"), Some(&Value::Bytes(b"caf\xe9".to_vec())))?;
+    ensure_eq(value.get("ok"), Some(&Value::from("café")))?;
+    ensure_eq(value.get("k\u{fffd}"), Some(&Value::from(1)))?;
+
+    let csv = parse_bytes_with_options(b"a\n\xff\xfe\n", Format::Csv, &options)?;
+    ensure_eq(
+        csv.get(0).and_then(|row| row.get("a")),
+        Some(&Value::Bytes(vec![0xff, 0xfe])),
+    )
+}
+
+#[test]
+fn test_bytes_utf8_refuses_its_own_stand_ins() {
+    let options = utf8_options(Utf8Mode::Bytes);
+    let input = "a: \u{10ff80}\n".as_bytes().to_vec();
+    assert!(parse_bytes_with_options(&input, Format::Yaml, &options).is_ok());
+    let mut invalid = input.clone();
+    invalid.push(0xff);
+    assert!(parse_bytes_with_options(&invalid, Format::Yaml, &options).is_err());
+}
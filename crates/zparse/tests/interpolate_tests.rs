@@ -0,0 +1,63 @@
+use zparse::{ErrorKind, Value, from_str};
+
+fn lookup(name: &str) -> Option<String> {
+    match name {
+        "HOST" => Some("db.internal".to_string()),
+        "EMPTY" => Some(String::new()),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_interpolate_nested_strings() -> Result<(), Box<dyn std::error::Error>> {
+    let mut value = from_str(
+        r#"{"db":{"url":"postgres://${HOST}:${PORT:-5432}/app"},"hosts":["${HOST}"],"n":1}"#,
+    )?;
+    value.interpolate_with(lookup)?;
+
+    let expected = from_str(
+        r#"{"db":{"url":"postgres://db.internal:5432/app"},"hosts":["db.internal"],"n":1}"#,
+    )?;
+    if value != expected {
+        return Err(format!("unexpected interpolation result: {value:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_interpolate_default_applies_to_empty() -> Result<(), Box<dyn std::error::Error>> {
+    let mut value = Value::from("${EMPTY:-fallback}|${EMPTY}");
+    value.interpolate_with(lookup)?;
+    if value.as_string() != Some("fallback|") {
+        return Err(format!("unexpected value: {value:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_interpolate_keeps_plain_dollars() -> Result<(), Box<dyn std::error::Error>> {
+    let mut value = Value::from("price: $5, pa$$word, $${HOST}");
+    value.interpolate_with(lookup)?;
+    if value.as_string() != Some("price: $5, pa$$word, ${HOST}") {
+        return Err(format!("unexpected value: {value:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_interpolate_undefined_variable() {
+    let mut value = Value::from("${MISSING}");
+    let result = value.interpolate_with(lookup);
+    assert!(matches!(
+        result,
+        Err(err) if matches!(err.kind(), ErrorKind::UndefinedVariable { name } if name == "MISSING")
+    ));
+}
+
+#[test]
+fn test_interpolate_rejects_malformed_placeholders() {
+    for input in ["${HOST", "${}", "${BAD-NAME}"] {
+        let mut value = Value::from(input);
+        assert!(value.interpolate_with(lookup).is_err(), "{input}");
+    }
+}
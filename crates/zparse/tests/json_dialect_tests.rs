@@ -0,0 +1,120 @@
+use zparse::{
+    ConvertOptions, Format, FormatOptions, JsonDialect, JsonQuote, Value, convert_with_options,
+    from_str, serialize_with_options,
+};
+
+fn write(input: &str, options: FormatOptions) -> Option<String> {
+    let value = from_str(input).ok()?;
+    serialize_with_options(&value, Format::Json, &options).ok()
+}
+
+#[test]
+fn test_strict_json_ignores_dialect_options() {
+    let options = FormatOptions::new()
+        .with_pretty(true)
+        .with_json_trailing_commas(true)
+        .with_json_unquoted_keys(true)
+        .with_json_quote(JsonQuote::Single);
+    assert_eq!(
+        write(r#"{"a":[1]}"#, options).as_deref(),
+        Some("{\n  \"a\": [\n    1\n  ]\n}")
+    );
+}
+
+#[test]
+fn test_jsonc_trailing_commas_in_pretty_output() {
+    let options = FormatOptions::new()
+        .with_pretty(true)
+        .with_json_dialect(JsonDialect::Jsonc)
+        .with_json_trailing_commas(true);
+    assert_eq!(
+        write(r#"{"a":[1,2],"b":{}}"#, options).as_deref(),
+        Some("{\n  \"a\": [\n    1,\n    2,\n  ],\n  \"b\": {},\n}")
+    );
+}
+
+#[test]
+fn test_jsonc_keeps_keys_quoted() {
+    let options = FormatOptions::new()
+        .with_json_dialect(JsonDialect::Jsonc)
+        .with_json_unquoted_keys(true);
+    assert_eq!(write(r#"{"a":1}"#, options).as_deref(), Some(r#"{"a":1}"#));
+}
+
+#[test]
+fn test_json5_unquoted_identifier_keys() {
+    let options = FormatOptions::new()
+        .with_json_dialect(JsonDialect::Json5)
+        .with_json_unquoted_keys(true);
+    assert_eq!(
+        write(r#"{"name":1,"$id":2,"a-b":3,"1x":4}"#, options).as_deref(),
+        Some(r#"{name:1,$id:2,"a-b":3,"1x":4}"#)
+    );
+}
+
+#[test]
+fn test_json5_single_quotes() {
+    let options = FormatOptions::new()
+        .with_json_dialect(JsonDialect::Json5)
+        .with_json_quote(JsonQuote::Single);
+    assert_eq!(
+        write(r#"{"k":"say \"it's\""}"#, options).as_deref(),
+        Some(r#"{'k':'say "it\'s"'}"#)
+    );
+}
+
+#[test]
+fn test_json5_multiline_strings() {
+    let options = FormatOptions::new()
+        .with_json_dialect(JsonDialect::Json5)
+        .with_json_multiline_strings(true);
+    assert_eq!(
+        write(r#"{"text":"one\ntwo"}"#, options).as_deref(),
+        Some("{\"text\":\"one\\n\\\ntwo\"}")
+    );
+}
+
+#[test]
+fn test_json5_non_finite_numbers() {
+    let value = Value::from(vec![
+        Value::Number(f64::NAN),
+        Value::Number(f64::INFINITY),
+        Value::Number(f64::NEG_INFINITY),
+    ]);
+    let json5 = FormatOptions::new().with_json_dialect(JsonDialect::Json5);
+    assert_eq!(
+        serialize_with_options(&value, Format::Json, &json5)
+            .ok()
+            .as_deref(),
+        Some("[NaN,Infinity,-Infinity]")
+    );
+    assert_eq!(
+        serialize_with_options(&value, Format::Json, &FormatOptions::new())
+            .ok()
+            .as_deref(),
+        Some("[null,null,null]")
+    );
+}
+
+#[test]
+fn test_jsonc_settings_round_trip() {
+    let input =
+        "{\n  // editor\n  \"editor.tabSize\": 2,\n  \"files.exclude\": {\"**/.git\": true,},\n}";
+    let options = ConvertOptions {
+        json: zparse::JsonConfig::default()
+            .with_comments(true)
+            .with_trailing_commas(true),
+        format: FormatOptions::new()
+            .with_pretty(true)
+            .with_json_dialect(JsonDialect::Jsonc)
+            .with_json_trailing_commas(true),
+        ..Default::default()
+    };
+    let output = convert_with_options(input, Format::Json, Format::Json, &options);
+    assert_eq!(
+        output.ok().as_deref(),
+        Some(
+            "{\n  \"editor.tabSize\": 2,\n  \"files.exclude\": {\n    \"**/.git\": true,\n  },\n}"
+        )
+    );
+}
@@ -0,0 +1,70 @@
+use zparse::{MultiObject, MultiValue, Value, from_str, from_str_multi};
+
+fn scalar(value: impl Into<Value>) -> MultiValue {
+    MultiValue::Scalar(value.into())
+}
+
+#[test]
+fn test_parse_multi_keeps_repeated_keys() -> zparse::Result<()> {
+    let input = r#"{"cookie": "a=1", "path": "/", "cookie": "b=2"}"#;
+    let value = from_str_multi(input)?;
+
+    let mut expected = MultiObject::new();
+    expected.push("cookie", scalar("a=1"));
+    expected.push("path", scalar("/"));
+    expected.push("cookie", scalar("b=2"));
+    if value != MultiValue::Object(expected) {
+        return Err(zparse::Error::new(
+            zparse::ErrorKind::InvalidToken,
+            zparse::Span::empty(),
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_multi_object_lookups() {
+    let value = from_str_multi(r#"{"role": "user", "id": 1, "role": "admin", "id": 2}"#).ok();
+    let object = value.as_ref().and_then(MultiValue::as_object);
+
+    let roles: Vec<_> = object
+        .map(|object| object.get_all("role").cloned().collect())
+        .unwrap_or_default();
+    assert_eq!(roles, vec![scalar("user"), scalar("admin")]);
+    assert_eq!(
+        object.and_then(|object| object.get("role")),
+        Some(&scalar("admin"))
+    );
+    assert_eq!(
+        object.map(MultiObject::duplicate_keys),
+        Some(vec!["role", "id"])
+    );
+    assert_eq!(object.map(MultiObject::len), Some(4));
+}
+
+#[test]
+fn test_nested_duplicates_are_detected() {
+    let nested = from_str_multi(r#"[{"ok": true}, {"inner": {"a": 1, "a": 2}}]"#).ok();
+    let clean = from_str_multi(r#"[{"ok": true}, {"inner": {"a": 1, "b": 2}}]"#).ok();
+    assert_eq!(nested.as_ref().map(MultiValue::has_duplicates), Some(true));
+    assert_eq!(clean.as_ref().map(MultiValue::has_duplicates), Some(false));
+}
+
+#[test]
+fn test_into_value_matches_regular_parse() -> zparse::Result<()> {
+    let input = r#"{"a": 1, "b": [{"c": 1, "c": 2}], "a": {"d": null}}"#;
+    let collapsed = from_str_multi(input)?.into_value();
+    if collapsed != from_str(input)? {
+        return Err(zparse::Error::new(
+            zparse::ErrorKind::InvalidToken,
+            zparse::Span::empty(),
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_parse_multi_scalars_and_errors() {
+    assert_eq!(from_str_multi("42").ok(), Some(scalar(42.0)));
+    assert!(from_str_multi(r#"{"a": 1,"#).is_err());
+}
@@ -639,3 +639,31 @@ fn test_parse_complex_jsonc_style_document() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_parse_non_ascii_strings() -> Result<()> {
+    let input = r#"["π €", "\uD834\uDD1E"]"#;
+    let mut parser = Parser::new(input.as_bytes());
+    let value = parse_value_or_fail(&mut parser)?;
+
+    ensure_eq(
+        value,
+        Value::Array(
+            vec![
+                Value::String("π €".to_string()),
+                Value::String("\u{1D11E}".to_string()),
+            ]
+            .into(),
+        ),
+    )
+}
+
+#[test]
+fn test_parse_rejects_missing_separators_and_trailing_content() -> Result<()> {
+    for input in [r#"[1 2]"#, r#"{"a":1 "b":2}"#, "[1]]", "{} 1", "01"] {
+        if Parser::new(input.as_bytes()).parse_value().is_ok() {
+            return fail(format!("{input:?} should be rejected"));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,83 @@
+//! JSONTestSuite conformance
+//!
+//! Runs the JSON parser over files from Nicolas Seriot's JSONTestSuite
+//! (<https://github.com/nst/JSONTestSuite>). Files starting with `y_` must
+//! parse, `n_` must be rejected and `i_` may go either way but must not panic.
+//!
+//! A representative subset is vendored under `tests/fixtures/json_test_suite`;
+//! point `JSON_TEST_SUITE_DIR` at the suite's `test_parsing` directory to run
+//! the full corpus. Files the parser still gets wrong are listed in `XFAIL`;
+//! the test fails when any other file breaks and also when a listed file
+//! starts passing, so the list always reflects the current status.
+//! [`Parser::validate`] must accept exactly the files `parse_value` accepts.
+
+use std::fs;
+use std::path::PathBuf;
+
+use zparse::json::Parser;
+
+/// Files whose outcome is known to be wrong
+const XFAIL: &[&str] = &[];
+
+#[test]
+fn test_json_test_suite() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = std::env::var_os("JSON_TEST_SUITE_DIR").map_or_else(
+        || {
+            PathBuf::from(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/fixtures/json_test_suite"
+            ))
+        },
+        PathBuf::from,
+    );
+
+    let mut names = Vec::new();
+    let mut failing = Vec::new();
+    let mut disagreeing = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let name = name.to_string();
+        let expected = match name.get(..2) {
+            Some("y_") => Some(true),
+            Some("n_") => Some(false),
+            Some("i_") => None,
+            _ => continue,
+        };
+        let input = fs::read(&path)?;
+        let accepted = Parser::new(&input).parse_value().is_ok();
+        if Parser::new(&input).validate().is_ok() != accepted {
+            disagreeing.push(name.clone());
+        }
+        if expected.is_some_and(|expected| expected != accepted) {
+            failing.push(name.clone());
+        }
+        names.push(name);
+    }
+    if names.is_empty() {
+        return Err(format!("no test files in {}", dir.display()).into());
+    }
+
+    failing.sort();
+    let regressions: Vec<_> = failing
+        .iter()
+        .filter(|name| !XFAIL.contains(&name.as_str()))
+        .collect();
+    let fixed: Vec<_> = XFAIL
+        .iter()
+        .filter(|name| names.iter().any(|present| present == *name))
+        .filter(|name| !failing.iter().any(|failed| failed == *name))
+        .collect();
+    if !disagreeing.is_empty() {
+        return Err(format!("validate and parse_value disagree on: {disagreeing:?}").into());
+    }
+    if !regressions.is_empty() || !fixed.is_empty() {
+        return Err(format!(
+            "failing files not in XFAIL: {regressions:?}; fixed files to remove: {fixed:?}"
+        )
+        .into());
+    }
+    Ok(())
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "unstable")]
+
 use zparse::lexer::cursor::Cursor;
 
 #[test]
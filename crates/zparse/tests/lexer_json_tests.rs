@@ -1,3 +1,5 @@
+#![cfg(feature = "unstable")]
+
 use std::fmt::Debug;
 use zparse::error::{Error, ErrorKind, Result, Span};
 use zparse::lexer::json::JsonLexer;
@@ -214,3 +216,31 @@ fn test_lexer_whitespace() -> Result<()> {
     ensure_eq(token.span.start.line, 2)?;
     Ok(())
 }
+
+#[test]
+fn test_iterator_stops_before_eof() {
+    let kinds = JsonLexer::new(b"[1, true]")
+        .map(|token| token.map(|token| token.kind))
+        .collect::<Result<Vec<_>>>();
+    assert_eq!(
+        kinds.ok().as_deref(),
+        Some(
+            &[
+                TokenKind::LeftBracket,
+                TokenKind::Number(1.0),
+                TokenKind::Comma,
+                TokenKind::True,
+                TokenKind::RightBracket,
+            ][..]
+        )
+    );
+}
+
+#[test]
+fn test_iterator_ends_after_error() {
+    let mut lexer = JsonLexer::new(b"[@, 1]");
+    assert!(matches!(lexer.next(), Some(Ok(_))));
+    assert!(matches!(lexer.next(), Some(Err(_))));
+    assert!(lexer.next().is_none());
+    assert!(lexer.next().is_none());
+}
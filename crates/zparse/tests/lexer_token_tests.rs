@@ -1,3 +1,5 @@
+#![cfg(feature = "unstable")]
+
 use zparse::error::{Pos, Span};
 use zparse::lexer::token::{Token, TokenKind};
 
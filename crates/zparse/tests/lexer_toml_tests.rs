@@ -1,3 +1,5 @@
+#![cfg(feature = "unstable")]
+
 use zparse::error::{Error, ErrorKind, Result};
 use zparse::lexer::toml::{TomlLexer, TomlTokenKind};
 
@@ -95,3 +97,31 @@ fn matches_token(lexer: &mut TomlLexer<'_>, expected: TomlTokenKind) -> Result<(
     }
     Ok(())
 }
+
+#[test]
+fn test_iterator_spans() {
+    let input = "key = 'v'";
+    let text: Vec<_> = TomlLexer::new(input.as_bytes())
+        .filter_map(|token| token.ok())
+        .filter_map(|token| input.get(token.span.start.offset..token.span.end.offset))
+        .collect();
+    assert_eq!(text, ["key", "=", "'v'"]);
+}
+
+#[test]
+fn test_iterator_ends_after_error() {
+    let mut lexer = TomlLexer::new(b"key = \"open");
+    assert_eq!(lexer.by_ref().filter(Result::is_ok).count(), 2);
+    assert!(lexer.next().is_none());
+}
+
+#[test]
+fn test_comment_keeps_newline() -> Result<()> {
+    let mut lexer = TomlLexer::new(b"a = 1 # note\n[b]\n");
+    matches_token(&mut lexer, TomlTokenKind::BareKey("a".to_string()))?;
+    matches_token(&mut lexer, TomlTokenKind::Equals)?;
+    matches_token(&mut lexer, TomlTokenKind::Integer(1))?;
+    matches_token(&mut lexer, TomlTokenKind::Newline)?;
+    matches_token(&mut lexer, TomlTokenKind::LeftBracket)?;
+    Ok(())
+}
@@ -1,4 +1,6 @@
-use zparse::error::{Error, ErrorKind, Result, Span};
+#![cfg(feature = "unstable")]
+
+use zparse::error::{Error, ErrorKind, Pos, Result, Span};
 use zparse::lexer::yaml::{YamlLexer, YamlTokenKind};
 
 fn next_kind(lexer: &mut YamlLexer<'_>) -> Result<YamlTokenKind> {
@@ -48,3 +50,112 @@ fn test_sequence() -> Result<()> {
     ensure_eq(next_kind(&mut lexer)?, YamlTokenKind::Newline)?;
     Ok(())
 }
+
+fn span(start: (usize, u32, u32), end: (usize, u32, u32)) -> Span {
+    Span::new(
+        Pos::new(start.0, start.1, start.2),
+        Pos::new(end.0, end.1, end.2),
+    )
+}
+
+#[test]
+fn test_token_spans() -> Result<()> {
+    let input = b"# header
+name: John
+items:
+  - [1, \"two\"]
+";
+    let mut lexer = YamlLexer::new(input);
+
+    let mut spans = Vec::new();
+    loop {
+        let token = lexer.next_token()?;
+        if token.kind == YamlTokenKind::Eof {
+            spans.push((token.kind, token.span));
+            break;
+        }
+        spans.push((token.kind, token.span));
+    }
+
+    let expected = vec![
+        (
+            YamlTokenKind::Scalar("name".to_string()),
+            span((9, 2, 1), (13, 2, 5)),
+        ),
+        (YamlTokenKind::Colon, span((13, 2, 5), (14, 2, 6))),
+        (
+            YamlTokenKind::Scalar("John".to_string()),
+            span((15, 2, 7), (19, 2, 11)),
+        ),
+        (YamlTokenKind::Newline, span((19, 2, 11), (19, 2, 11))),
+        (
+            YamlTokenKind::Scalar("items".to_string()),
+            span((20, 3, 1), (25, 3, 6)),
+        ),
+        (YamlTokenKind::Colon, span((25, 3, 6), (26, 3, 7))),
+        (YamlTokenKind::Newline, span((26, 3, 7), (26, 3, 7))),
+        (YamlTokenKind::Indent, span((29, 4, 3), (29, 4, 3))),
+        (YamlTokenKind::Dash, span((29, 4, 3), (30, 4, 4))),
+        (YamlTokenKind::LeftBracket, span((31, 4, 5), (32, 4, 6))),
+        (
+            YamlTokenKind::Scalar("1".to_string()),
+            span((32, 4, 6), (33, 4, 7)),
+        ),
+        (YamlTokenKind::Comma, span((33, 4, 7), (34, 4, 8))),
+        (
+            YamlTokenKind::QuotedScalar("two".to_string()),
+            span((35, 4, 9), (40, 4, 14)),
+        ),
+        (YamlTokenKind::RightBracket, span((40, 4, 14), (41, 4, 15))),
+        (YamlTokenKind::Newline, span((41, 4, 15), (41, 4, 15))),
+        (YamlTokenKind::Dedent, span((42, 5, 1), (42, 5, 1))),
+        (YamlTokenKind::Eof, span((42, 5, 1), (42, 5, 1))),
+    ];
+    ensure_eq(spans, expected)
+}
+
+#[test]
+fn test_eof_span_without_trailing_newline() -> Result<()> {
+    let mut lexer = YamlLexer::new(
+        b"a: 1
+b: 22",
+    );
+    let mut token = lexer.next_token()?;
+    while token.kind != YamlTokenKind::Eof {
+        token = lexer.next_token()?;
+    }
+    ensure_eq(token.span, span((10, 2, 6), (10, 2, 6)))
+}
+
+#[test]
+fn test_lexer_errors_have_locations() -> Result<()> {
+    let cases: [(&[u8], Pos); 3] = [
+        (b"a: 1\nb: \"open\n", Pos::new(8, 2, 4)),
+        (b"a:\n\tb: 1\n", Pos::new(3, 2, 1)),
+        (b"a:\n    b: 1\n  c: 2\n", Pos::new(14, 3, 3)),
+    ];
+    for (input, expected) in cases {
+        let mut lexer = YamlLexer::new(input);
+        let err = loop {
+            match lexer.next_token() {
+                Ok(token) if token.kind == YamlTokenKind::Eof => {
+                    return ensure_eq(Some(input), None);
+                }
+                Ok(_) => continue,
+                Err(err) => break err,
+            }
+        };
+        ensure_eq(err.span().start, expected)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_iterator_stops_before_eof() {
+    let kinds = YamlLexer::new(b"- a\n")
+        .map(|token| token.map(|token| token.kind))
+        .collect::<Result<Vec<_>>>()
+        .unwrap_or_default();
+    assert_eq!(kinds.first(), Some(&YamlTokenKind::Dash));
+    assert!(!kinds.contains(&YamlTokenKind::Eof));
+}
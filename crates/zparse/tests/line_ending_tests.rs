@@ -0,0 +1,94 @@
+#[cfg(feature = "unstable")]
+use zparse::lexer::yaml::{YamlLexer, YamlTokenKind};
+use zparse::{ConvertOptions, Format, Value, parse_with_options};
+
+const YAML: &str = "name: app # comment\nport: 8080\ntags:\n  - 'a b'\n  - \"c\"\nnested:\n  key: value\n  flow: {x: 1, y: [2, 3]}\n";
+const TOML: &str =
+    "title = \"app\"\n# comment\n[server]\nhost = 'localhost'\nports = [\n  80,\n  443,\n]\n";
+
+fn parse(input: &str, format: Format) -> zparse::Result<Value> {
+    parse_with_options(input, format, &ConvertOptions::default())
+}
+
+/// Replace every other LF with CRLF to mimic a file edited on two platforms
+fn mixed(input: &str) -> String {
+    input
+        .split_inclusive('\n')
+        .enumerate()
+        .map(|(index, line)| {
+            if index % 2 == 0 {
+                line.replace('\n', "\r\n")
+            } else {
+                line.to_string()
+            }
+        })
+        .collect()
+}
+
+fn has_carriage_return(value: &Value) -> bool {
+    match value {
+        Value::String(text) => text.contains('\r'),
+        Value::Array(items) => items.iter().any(has_carriage_return),
+        Value::Object(object) => object
+            .iter()
+            .any(|(key, value)| key.contains('\r') || has_carriage_return(value)),
+        _ => false,
+    }
+}
+
+#[test]
+fn test_crlf_and_mixed_line_endings_parse_like_lf() -> Result<(), Box<dyn std::error::Error>> {
+    for (input, format) in [(YAML, Format::Yaml), (TOML, Format::Toml)] {
+        let expected = parse(input, format)?;
+        for variant in [input.replace('\n', "\r\n"), mixed(input)] {
+            let value = parse(&variant, format)?;
+            if value != expected || has_carriage_return(&value) {
+                return Err(format!("{format:?} mismatch for {variant:?}: {value:?}").into());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_toml_multiline_strings_normalize_crlf() -> Result<(), Box<dyn std::error::Error>> {
+    let input =
+        "basic = \"\"\"\r\none\r\ntwo \\\r\n   three\"\"\"\r\nliteral = '''\r\nraw\r\n'''\r\n";
+    let value = parse(input, Format::Toml)?;
+    let object = value.as_object().ok_or("expected table")?;
+
+    let basic = object.get("basic").and_then(Value::as_string);
+    if basic != Some("one\ntwo three") {
+        return Err(format!("unexpected basic string: {basic:?}").into());
+    }
+    let literal = object.get("literal").and_then(Value::as_string);
+    if literal != Some("raw\n") {
+        return Err(format!("unexpected literal string: {literal:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_toml_rejects_lone_carriage_return() {
+    let result = parse("a = 1\rb = 2\n", Format::Toml);
+    assert!(result.is_err());
+}
+
+#[cfg(feature = "unstable")]
+#[test]
+fn test_yaml_crlf_spans_exclude_carriage_return() -> Result<(), Box<dyn std::error::Error>> {
+    let mut lexer = YamlLexer::new(b"a: 1\r\nb: 2\r\n");
+    let mut newlines = Vec::new();
+    loop {
+        let token = lexer.next_token()?;
+        match token.kind {
+            YamlTokenKind::Eof => break,
+            YamlTokenKind::Newline => newlines.push(token.span.start.col),
+            _ => {}
+        }
+    }
+    if newlines != [5, 5] {
+        return Err(format!("unexpected newline columns: {newlines:?}").into());
+    }
+    Ok(())
+}
@@ -0,0 +1,147 @@
+#![cfg(feature = "lossless")]
+
+use zparse::lossless::{Comments, KeyStyle, parse_lossless};
+use zparse::{ConvertOptions, Format, FormatOptions, Value, YamlStringStyle};
+
+fn roundtrip(input: &str) -> String {
+    parse_lossless(input, Format::Toml, &ConvertOptions::default())
+        .and_then(|doc| doc.serialize(Format::Toml, &FormatOptions::default()))
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_toml_key_styles_roundtrip() {
+    assert_eq!(
+        roundtrip("\"name\" = 1\nbare = 2"),
+        "\"name\" = 1\nbare = 2"
+    );
+    assert_eq!(roundtrip("'my key' = 1"), "'my key' = 1");
+    assert_eq!(
+        roundtrip("[\"a.b\".c]\n'd' = {\"e\" = [{f = 1}]}"),
+        "\"a.b\" = {c = {'d' = {\"e\" = [{f = 1}]}}}"
+    );
+    // Without lossless parsing only keys that need it are quoted
+    assert_eq!(
+        zparse::from_toml_str("\"name\" = 1")
+            .and_then(|value| zparse::serialize(&value, Format::Toml))
+            .unwrap_or_default(),
+        "name = 1"
+    );
+}
+
+#[test]
+fn test_toml_key_styles_after_edits() {
+    let doc = parse_lossless(
+        "\"a\" = 1\n'b' = 2",
+        Format::Toml,
+        &ConvertOptions::default(),
+    )
+    .ok();
+    assert_eq!(
+        doc.as_ref().and_then(|doc| doc.key_style("/a")),
+        Some(KeyStyle::Basic)
+    );
+    assert_eq!(doc.as_ref().and_then(|doc| doc.key_style("/c")), None);
+
+    let out = doc.and_then(|mut doc| {
+        if let Value::Object(entries) = doc.value_mut() {
+            entries.insert("c d", 3);
+            entries.insert("e", 4);
+        }
+        doc.serialize(Format::Toml, &FormatOptions::default()).ok()
+    });
+    assert_eq!(
+        out.as_deref(),
+        Some("\"a\" = 1\n'b' = 2\n\"c d\" = 3\ne = 4")
+    );
+}
+
+fn jsonc_options() -> ConvertOptions {
+    ConvertOptions {
+        json: zparse::JsonConfig::default()
+            .with_comments(true)
+            .with_trailing_commas(true),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_jsonc_comments_attach_to_values() {
+    let input = "// header\n{\n  // leading\n  \"a\": 1, // trailing\n  \"b\": [\n    true\n    // dangling\n  ]\n}\n// footer";
+    let doc = parse_lossless(input, Format::Json, &jsonc_options()).ok();
+    let comments = |pointer: &str| {
+        doc.as_ref()
+            .and_then(|doc| doc.comments(pointer))
+            .cloned()
+            .unwrap_or_default()
+    };
+    assert_eq!(comments("").leading, vec!["// header"]);
+    assert_eq!(comments("").trailing, vec!["// footer"]);
+    assert_eq!(comments("/a").leading, vec!["// leading"]);
+    assert_eq!(comments("/a").trailing, vec!["// trailing"]);
+    assert_eq!(comments("/b").dangling, vec!["// dangling"]);
+    assert_eq!(comments("/b/0"), Comments::default());
+}
+
+#[test]
+fn test_jsonc_comments_roundtrip() {
+    let input = "{\n  // Editor\n  \"editor.tabSize\": 2, /* spaces */\n  \"files.exclude\": {\"target\": true,},\n}";
+    let out = parse_lossless(input, Format::Json, &jsonc_options())
+        .and_then(|doc| doc.serialize(Format::Json, &FormatOptions::default()))
+        .unwrap_or_default();
+    assert_eq!(
+        out,
+        "{\n  // Editor\n  \"editor.tabSize\": 2, /* spaces */\n  \"files.exclude\": {\n    \"target\": true\n  }\n}"
+    );
+
+    // Comments survive edits to other keys, and keep JSONC trailing commas
+    let options = FormatOptions::new()
+        .with_json_dialect(zparse::JsonDialect::Jsonc)
+        .with_json_trailing_commas(true);
+    let out = parse_lossless(input, Format::Json, &jsonc_options())
+        .and_then(|mut doc| {
+            if let Value::Object(entries) = doc.value_mut() {
+                entries.remove("files.exclude");
+            }
+            doc.serialize(Format::Json, &options)
+        })
+        .unwrap_or_default();
+    assert_eq!(
+        out,
+        "{\n  // Editor\n  \"editor.tabSize\": 2, /* spaces */\n}"
+    );
+}
+
+#[test]
+fn test_json_without_comments_serializes_as_before() {
+    let out = parse_lossless("{\"a\": [1, 2]}", Format::Json, &jsonc_options())
+        .and_then(|doc| doc.serialize(Format::Json, &FormatOptions::default()))
+        .unwrap_or_default();
+    assert_eq!(out, "{\"a\":[1,2]}");
+}
+
+#[test]
+fn test_yaml_quotes_preserved() {
+    let input =
+        "name: zparse\n'key': \"value\"\nsaid: 'it''s'\nlist:\n  - 'a'\n  - b\nflow: {\"k\": 'v'}";
+    let doc = parse_lossless(input, Format::Yaml, &ConvertOptions::default());
+    let preserve = FormatOptions::new().with_yaml_strings(YamlStringStyle::Preserve);
+    assert_eq!(
+        doc.as_ref()
+            .ok()
+            .and_then(|doc| doc.serialize(Format::Yaml, &preserve).ok())
+            .as_deref(),
+        Some(
+            "name: zparse\n'key': \"value\"\nsaid: 'it''s'\nlist:\n  - 'a'\n  - b\nflow:\n  \"k\": 'v'"
+        )
+    );
+    // The default still quotes every string, whatever the source did
+    assert_eq!(
+        doc.ok()
+            .and_then(|doc| doc.serialize(Format::Yaml, &FormatOptions::default()).ok())
+            .as_deref(),
+        Some(
+            "name: \"zparse\"\nkey: \"value\"\nsaid: \"it's\"\nlist:\n  - \"a\"\n  - \"b\"\nflow:\n  k: \"v\""
+        )
+    );
+}
@@ -0,0 +1,59 @@
+use zparse::{MergeStrategy, Value, from_toml_str, merge_all};
+
+#[test]
+fn test_deep_merge_combines_nested_tables() -> Result<(), Box<dyn std::error::Error>> {
+    let base = from_toml_str("name = \"base\"\n[server]\nhost = \"localhost\"\nport = 80\n")?;
+    let overlay = from_toml_str("[server]\nport = 8080\ntls = true\n")?;
+
+    let merged = merge_all([base, overlay], MergeStrategy::Deep);
+    let server = merged
+        .as_object()
+        .and_then(|obj| obj.get("server"))
+        .and_then(Value::as_object)
+        .ok_or("missing server table")?;
+
+    if server.get("host") != Some(&Value::from("localhost")) {
+        return Err("deep merge dropped base key".into());
+    }
+    if server.get("port") != Some(&Value::from(8080)) {
+        return Err("deep merge did not override port".into());
+    }
+    if server.get("tls") != Some(&Value::Bool(true)) {
+        return Err("deep merge did not add overlay key".into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_shallow_merge_replaces_top_level_keys() -> Result<(), Box<dyn std::error::Error>> {
+    let mut base = from_toml_str("[server]\nhost = \"localhost\"\nport = 80\n")?;
+    let overlay = from_toml_str("[server]\nport = 8080\n")?;
+
+    base.merge(overlay, MergeStrategy::Shallow);
+    let server = base
+        .as_object()
+        .and_then(|obj| obj.get("server"))
+        .and_then(Value::as_object)
+        .ok_or("missing server table")?;
+
+    if server.len() != 1 || server.get("port") != Some(&Value::from(8080)) {
+        return Err("shallow merge should replace the whole table".into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_merge_replaces_arrays_and_scalars() {
+    let mut base = Value::from(vec![Value::from(1), Value::from(2)]);
+    base.merge(Value::from(vec![Value::from(3)]), MergeStrategy::Deep);
+    assert_eq!(base, Value::from(vec![Value::from(3)]));
+
+    let mut base = Value::from("text");
+    base.merge(Value::Null, MergeStrategy::Deep);
+    assert!(base.is_null());
+}
+
+#[test]
+fn test_merge_all_empty_is_null() {
+    assert!(merge_all(Vec::new(), MergeStrategy::Deep).is_null());
+}
@@ -0,0 +1,83 @@
+use zparse::{Array, Value, from_str};
+
+fn parse(input: &str) -> Value {
+    from_str(input).unwrap_or_default()
+}
+
+#[test]
+fn test_depth_matches_parser_limits() {
+    assert_eq!(Value::Null.depth(), 0);
+    assert_eq!(Value::Array(Array::new()).depth(), 1);
+    assert_eq!(parse(r#"{"a": [1], "b": 2}"#).depth(), 2);
+
+    let nested = format!("{}{}", "[".repeat(128), "]".repeat(128));
+    assert_eq!(parse(&nested).depth(), 128);
+}
+
+#[test]
+fn test_count_nodes() {
+    assert_eq!(Value::from("x").count_nodes(), 1);
+    // Root object, "a" array, its two numbers and "b"
+    assert_eq!(parse(r#"{"a": [1, 2], "b": null}"#).count_nodes(), 5);
+}
+
+#[test]
+fn test_approx_memory_grows_with_content() {
+    let small = parse(r#"{"a": "b"}"#);
+    let large = parse(&format!(r#"{{"a": "{}"}}"#, "b".repeat(4096)));
+
+    assert_eq!(Value::Null.approx_memory(), std::mem::size_of::<Value>());
+    assert!(small.approx_memory() > Value::Null.approx_memory());
+    assert!(large.approx_memory() > small.approx_memory() + 4000);
+}
+
+#[test]
+fn test_metrics_beyond_parser_depth() {
+    let mut value = Value::Null;
+    for _ in 0..1000 {
+        value = Value::Array(vec![value].into());
+    }
+
+    assert_eq!(value.depth(), 1000);
+    assert_eq!(value.count_nodes(), 1001);
+    assert!(value.approx_memory() > 1000 * std::mem::size_of::<Value>());
+}
+
+#[test]
+fn test_stats_summarizes_structure() {
+    let value = parse(r#"{"users": [{"id": 1, "name": "a"}, {"id": 2, "tags": []}], "ok": true}"#);
+    let stats = value.stats();
+
+    assert_eq!(stats.input_bytes, None);
+    assert_eq!(stats.max_depth, 4);
+    assert_eq!(stats.types.get("object"), Some(&3));
+    assert_eq!(stats.types.get("array"), Some(&2));
+    assert_eq!(stats.types.get("number"), Some(&2));
+    assert_eq!(stats.keys.get("id"), Some(&2));
+    assert_eq!(stats.top_keys(1), vec![("id", 2)]);
+    assert_eq!(stats.array_lengths.get(&0), Some(&1));
+    assert_eq!(stats.array_lengths.get(&1), Some(&1));
+    assert_eq!(stats.max_array_length, 2);
+    assert_eq!(stats.string_bytes, 1);
+    assert_eq!(stats.memory_bytes, value.approx_memory());
+}
+
+#[test]
+fn test_stats_value_and_display() {
+    let items = format!("[{}]", vec!["0"; 120].join(","));
+    let stats = zparse::Stats {
+        input_bytes: Some(items.len()),
+        ..parse(&items).stats()
+    };
+    let value = stats.to_value();
+
+    let field = |outer: &str, inner: &str| value.get(outer).and_then(|v| v.get(inner)).cloned();
+    assert_eq!(field("array_lengths", "100-999"), Some(Value::from(1)));
+    assert_eq!(field("types", "number"), Some(Value::from(120)));
+    assert!(stats.to_string().contains("100-999"));
+    assert!(
+        stats
+            .to_string()
+            .starts_with(&format!("input:        {} bytes", items.len()))
+    );
+}
@@ -0,0 +1,111 @@
+use zparse::{
+    Format, FormatOptions, IntegralFloats, Object, Value, from_toml_str, serialize_with_options,
+};
+
+fn document(number: f64) -> Value {
+    let mut object = Object::new();
+    object.insert("n", Value::Number(number));
+    Value::Object(object)
+}
+
+fn write(number: f64, format: Format, options: FormatOptions) -> Option<String> {
+    serialize_with_options(&document(number), format, &options).ok()
+}
+
+#[test]
+fn test_integral_floats_default_to_integers() {
+    let options = FormatOptions::default();
+    assert_eq!(
+        write(8080.0, Format::Toml, options).as_deref(),
+        Some("n = 8080")
+    );
+    assert_eq!(
+        write(8080.0, Format::Json, options).as_deref(),
+        Some("{\"n\":8080}")
+    );
+    assert_eq!(
+        write(8080.0, Format::Yaml, options).as_deref(),
+        Some("n: 8080")
+    );
+}
+
+#[test]
+fn test_integral_floats_as_decimal() {
+    let options = FormatOptions::new().with_integral_floats(IntegralFloats::Decimal);
+    assert_eq!(
+        write(8080.0, Format::Toml, options).as_deref(),
+        Some("n = 8080.0")
+    );
+    assert_eq!(
+        write(-3.0, Format::Json, options).as_deref(),
+        Some("{\"n\":-3.0}")
+    );
+    assert_eq!(
+        write(0.5, Format::Json, options).as_deref(),
+        Some("{\"n\":0.5}")
+    );
+}
+
+#[test]
+fn test_toml_integers_beyond_i64_are_floats() -> zparse::Result<()> {
+    let output = serialize_with_options(&document(1e20), Format::Toml, &FormatOptions::default())?;
+    if output != "n = 100000000000000000000.0" {
+        return Err(zparse::Error::new(
+            zparse::ErrorKind::InvalidNumber,
+            zparse::Span::empty(),
+        ));
+    }
+    if from_toml_str(&output)? != document(1e20) {
+        return Err(zparse::Error::new(
+            zparse::ErrorKind::InvalidNumber,
+            zparse::Span::empty(),
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_float_precision() {
+    let options = FormatOptions::new().with_float_precision(Some(2));
+    assert_eq!(
+        write(1.23456, Format::Json, options).as_deref(),
+        Some("{\"n\":1.23}")
+    );
+    assert_eq!(
+        write(42.0, Format::Json, options).as_deref(),
+        Some("{\"n\":42}")
+    );
+}
+
+#[test]
+fn test_exponent_threshold() {
+    let options = FormatOptions::new().with_exponent_threshold(Some(6));
+    assert_eq!(
+        write(1234567.0, Format::Json, options).as_deref(),
+        Some("{\"n\":1.234567e6}")
+    );
+    assert_eq!(
+        write(0.0000012, Format::Toml, options).as_deref(),
+        Some("n = 1.2e-6")
+    );
+    assert_eq!(
+        write(123456.0, Format::Json, options).as_deref(),
+        Some("{\"n\":123456}")
+    );
+
+    let rounded = options.with_float_precision(Some(1));
+    assert_eq!(
+        write(0.0000012345, Format::Yaml, rounded).as_deref(),
+        Some("n: 1.2e-6")
+    );
+}
+
+#[test]
+fn test_csv_uses_number_options() {
+    let mut row = Object::new();
+    row.insert("n", Value::Number(2.5));
+    let rows = Value::Array(vec![Value::Object(row)].into());
+    let options = FormatOptions::new().with_float_precision(Some(3));
+    let output = serialize_with_options(&rows, Format::Csv, &options).ok();
+    assert_eq!(output.as_deref(), Some("n\n2.500\n"));
+}
@@ -0,0 +1,141 @@
+use zparse::{ConvertOptions, Format, FormatOptions, Value, ops};
+
+#[test]
+fn test_parse_file_infers_format() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let jsonc = dir.path().join("app.jsonc");
+    std::fs::write(&jsonc, "{\n  // port\n  \"port\": 80,\n}\n")?;
+    let unnamed = dir.path().join("settings");
+    std::fs::write(&unnamed, "[server]\nport = 80\n")?;
+
+    let (format, value) = ops::parse_file(&jsonc, &ConvertOptions::default())?;
+    if format != Format::Json
+        || value.as_object().and_then(|object| object.get("port")) != Some(&Value::Number(80.0))
+    {
+        return Err(format!("unexpected jsonc result: {format:?} {value:?}").into());
+    }
+    let (format, _) = ops::parse_file(&unnamed, &ConvertOptions::default())?;
+    if format != Format::Toml {
+        return Err(format!("extensionless TOML detected as {format:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_convert_path_uses_output_extension() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let input = dir.path().join("app.json");
+    let output = dir.path().join("app.toml");
+    std::fs::write(&input, r#"{"name":"zparse"}"#)?;
+
+    ops::convert_path(&input, &output, None, &ConvertOptions::default())?;
+    let written = std::fs::read_to_string(&output)?;
+    if written != "name = \"zparse\"" {
+        return Err(format!("unexpected output: {written:?}").into());
+    }
+    if ops::convert_path(
+        &input,
+        dir.path().join("app.out"),
+        None,
+        &ConvertOptions::default(),
+    )
+    .is_ok()
+    {
+        return Err("unknown output extension should fail".into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_convert_in_place_keeps_backup() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("app.yaml");
+    std::fs::write(&path, "port: 80\n")?;
+
+    ops::convert_in_place(
+        &path,
+        Format::Json,
+        &ConvertOptions::default(),
+        Some(".bak"),
+    )?;
+    let converted = std::fs::read_to_string(&path)?;
+    let backup = std::fs::read_to_string(dir.path().join("app.yaml.bak"))?;
+    if converted != r#"{"port":80}"# || backup != "port: 80\n" {
+        return Err(format!("unexpected files: {converted:?} {backup:?}").into());
+    }
+    let leftovers = std::fs::read_dir(dir.path())?.count();
+    if leftovers != 2 {
+        return Err(format!("expected only the file and its backup, found {leftovers}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_format_in_place() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("app.json");
+    std::fs::write(&path, r#"{"a":[1]}"#)?;
+
+    let options = ConvertOptions {
+        format: FormatOptions::new().with_pretty(true),
+        ..Default::default()
+    };
+    ops::format_in_place(&path, &options, None)?;
+    let formatted = std::fs::read_to_string(&path)?;
+    if formatted != "{\n  \"a\": [\n    1\n  ]\n}" {
+        return Err(format!("unexpected output: {formatted:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_write_atomic_without_file_name_is_io_error() {
+    let err = ops::write_atomic("/", b"{}", None).err();
+    assert!(
+        matches!(
+            err.as_ref().map(zparse::Error::kind),
+            Some(zparse::ErrorKind::Io { .. })
+        ),
+        "unexpected error: {err:?}"
+    );
+    assert!(err.is_some_and(|err| err.message().ends_with("path has no file name")));
+}
+
+#[cfg(feature = "lossless")]
+#[test]
+fn test_format_file_keeps_jsonc_comments() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("settings.jsonc");
+    std::fs::write(&path, "{\n    // port\n    \"port\": 80,\n}\n")?;
+
+    let formatted = ops::format_file(&path, &ConvertOptions::default())?;
+    if formatted != "{\n  // port\n  \"port\": 80\n}" {
+        return Err(format!("unexpected output: {formatted:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_textconv_file_ignores_key_order_and_layout() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let left = dir.path().join("left.toml");
+    std::fs::write(
+        &left,
+        "name = 'app' # service\n[server]\nport = 8_080\nhost = \"a\"\n",
+    )?;
+    let right = dir.path().join("right.toml");
+    std::fs::write(
+        &right,
+        "name = \"app\"\n\n[server]\nhost = 'a'\nport = 8080\n",
+    )?;
+
+    let left = ops::textconv_file(&left, &ConvertOptions::default())?;
+    let right = ops::textconv_file(&right, &ConvertOptions::default())?;
+    if left != right || !left.ends_with('\n') {
+        return Err(format!("unexpected output: {left:?} vs {right:?}").into());
+    }
+    if left.find("host") > left.find("port") {
+        return Err(format!("keys not sorted: {left:?}").into());
+    }
+    Ok(())
+}
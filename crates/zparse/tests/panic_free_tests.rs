@@ -0,0 +1,194 @@
+//! The "never panics" contract: every parsing entry point returns `Ok` or
+//! `Err` for any input, including text that splits multi-byte characters
+//! across the places the lexers look at.
+//!
+//! Inputs are built from fragments of each format's syntax mixed with
+//! multi-byte characters, which reaches far more parser states than random
+//! strings. The fuzz targets in `fuzz/` run the same calls without a time
+//! limit.
+
+use proptest::prelude::*;
+use zparse::highlight::highlight;
+use zparse::{
+    ConvertOptions, Format, IncrementalDocument, key_lines, parse_bytes, parse_document,
+    parse_with_options, serialize,
+};
+
+const FORMATS: [Format; 5] = [
+    Format::Json,
+    Format::Csv,
+    Format::Toml,
+    Format::Yaml,
+    Format::Xml,
+];
+
+/// Pieces of JSON, TOML, YAML, XML and CSV syntax, and characters that take
+/// two, three and four bytes in UTF-8
+const FRAGMENTS: &[&str] = &[
+    "{",
+    "}",
+    "[",
+    "]",
+    "(",
+    ")",
+    ":",
+    ",",
+    ";",
+    "=",
+    "\"",
+    "'",
+    "`",
+    "\\",
+    "\\u",
+    "\\x",
+    "\\uD83D",
+    "/",
+    "//",
+    "/*",
+    "*/",
+    "#",
+    "!",
+    "!!",
+    "&",
+    "*",
+    "|",
+    ">",
+    "<",
+    "</",
+    "/>",
+    "<?",
+    "?>",
+    "<!--",
+    "-->",
+    "<![CDATA[",
+    "]]>",
+    "&amp;",
+    "&#",
+    "&#x",
+    "-",
+    "---",
+    "...",
+    "?",
+    "%",
+    "@",
+    ".",
+    "+",
+    "_",
+    "0",
+    "1",
+    "-1",
+    "1.5e3",
+    "0x1F",
+    "0o7",
+    "0b1",
+    "1979-05-27",
+    "T07:32:00",
+    "Z",
+    "+01:00",
+    "true",
+    "false",
+    "null",
+    "~",
+    "inf",
+    "nan",
+    "a",
+    "key",
+    "[[",
+    "]]",
+    "\"\"\"",
+    "'''",
+    " ",
+    "  ",
+    "\t",
+    "\n",
+    "\r\n",
+    "\r",
+    "\u{feff}",
+    "é",
+    "ß",
+    "日本",
+    "€",
+    "🎉",
+    "\u{200b}",
+    "\u{0}",
+];
+
+fn document() -> impl Strategy<Value = String> {
+    prop_oneof![
+        4 => prop::collection::vec(prop::sample::select(FRAGMENTS), 0..48)
+            .prop_map(|parts| parts.concat()),
+        1 => any::<String>(),
+    ]
+}
+
+/// Parse `input` every way the crate offers; only a panic fails the test
+fn parse_every_way(input: &str) {
+    let options = ConvertOptions::default();
+    for format in FORMATS {
+        if let Ok(value) = parse_with_options(input, format, &options) {
+            for to in FORMATS {
+                let _ = serialize(&value, to);
+            }
+        }
+        let _ = parse_document(input, format, &options);
+        let _ = highlight(input, format);
+        let _ = key_lines(input, format, &options);
+        #[cfg(feature = "unstable")]
+        if let Ok(mut reader) = zparse::event::reader(input, format, &options) {
+            let _ = zparse::event::collect(reader.as_mut());
+        }
+        #[cfg(feature = "lossless")]
+        let _ = zparse::lossless::parse_lossless(input, format, &options);
+    }
+    let _ = zparse::query::Query::parse(input);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn test_parsing_never_panics(input in document()) {
+        parse_every_way(&input);
+    }
+
+    #[test]
+    fn test_parsing_bytes_never_panics(bytes in prop::collection::vec(any::<u8>(), 0..64)) {
+        for format in FORMATS {
+            let _ = parse_bytes(&bytes, format);
+        }
+    }
+
+    #[test]
+    fn test_incremental_edits_never_panic(
+        input in document(),
+        edit in document(),
+        start in 0usize..64,
+        len in 0usize..16,
+    ) {
+        for format in FORMATS {
+            let mut doc = IncrementalDocument::new(format, input.clone());
+            let end = start.saturating_add(len);
+            let _ = doc.edit(start..end, &edit);
+            let _ = doc.edit(end..start, &edit);
+        }
+    }
+}
+
+#[test]
+fn test_multibyte_text_at_every_boundary() {
+    let samples = [
+        "{\"é\": \"日本\"}",
+        "key = \"🎉\"\n[tablé]\nx = 'ß'\n",
+        "ключ: значение\n  - 日本\n  - é: ß\n",
+        "<é a=\"日\">ß<![CDATA[🎉]]></é>",
+        "é,日本\n🎉,ß\n",
+    ];
+    for sample in samples {
+        for (index, _) in sample.char_indices() {
+            let (before, after) = sample.split_at(index);
+            parse_every_way(before);
+            parse_every_way(after);
+            parse_every_way(&format!("{before}\n{after}"));
+        }
+    }
+}
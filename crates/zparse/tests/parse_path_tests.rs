@@ -0,0 +1,84 @@
+use zparse::{ErrorKind, Format, Value, parse_bytes, parse_file, parse_path};
+
+fn port(value: &Value) -> Option<f64> {
+    value
+        .as_object()
+        .and_then(|object| object.get("port"))
+        .and_then(Value::as_number)
+}
+
+#[test]
+fn test_parse_path_every_format() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let files = [
+        ("app.json", "{\"port\": 80}"),
+        ("app.jsonc", "{\n  // port\n  \"port\": 80,\n}"),
+        ("app.toml", "port = 80\n"),
+        ("app.yaml", "port: 80\n"),
+        ("app.conf", "\u{feff}port = 80\n"),
+    ];
+    for (name, content) in files {
+        let path = dir.path().join(name);
+        std::fs::write(&path, content)?;
+        let value = parse_path(&path)?;
+        if port(&value) != Some(80.0) {
+            return Err(format!("unexpected value for {name}: {value:?}").into());
+        }
+    }
+
+    let xml = dir.path().join("app.xml");
+    std::fs::write(&xml, "<app port=\"80\"/>")?;
+    if parse_path(&xml)?.as_object().is_none() {
+        return Err("xml should parse to an object".into());
+    }
+    let csv = dir.path().join("app.csv");
+    std::fs::write(&csv, "name,port\napi,80\n")?;
+    if parse_path(&csv)?.as_array().map(|rows| rows.len()) != Some(1) {
+        return Err("csv should parse to one row".into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_parse_path_missing_file() {
+    let err = parse_path("does/not/exist.json");
+    assert!(matches!(
+        err.map_err(|err| err.kind().clone()),
+        Err(zparse::ErrorKind::Io { .. })
+    ));
+}
+
+#[test]
+fn test_parse_bytes() -> Result<(), Box<dyn std::error::Error>> {
+    let value = parse_bytes(b"\xef\xbb\xbfport: 80\n", Format::Yaml)?;
+    if port(&value) != Some(80.0) {
+        return Err(format!("unexpected value: {value:?}").into());
+    }
+    let Err(err) = parse_bytes(b"{\"port\": \"\xff\"}", Format::Json) else {
+        return Err("invalid UTF-8 should fail".into());
+    };
+    if err.span().start.offset != 10 {
+        return Err(format!("unexpected error position: {err:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_parse_file_unknown_format() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let settings = dir.path().join("settings");
+    std::fs::write(&settings, "port = 80\n")?;
+    if port(&parse_file(&settings)?) != Some(80.0) {
+        return Err("extensionless TOML should be detected from its content".into());
+    }
+
+    let notes = dir.path().join("notes.txt");
+    std::fs::write(&notes, "just some text")?;
+    let Err(err) = parse_file(&notes) else {
+        return Err("undetectable content should fail".into());
+    };
+    if !matches!(err.kind(), ErrorKind::UnknownFormat { .. }) || !err.message().contains(".yaml") {
+        return Err(format!("unexpected error: {err}").into());
+    }
+    Ok(())
+}
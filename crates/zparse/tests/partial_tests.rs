@@ -0,0 +1,59 @@
+use zparse::partial::{parse_first, parse_until_path, parse_until_path_with_options};
+use zparse::{ConvertOptions, Format, Value};
+
+#[test]
+fn test_parse_until_path_every_format() {
+    let inputs = [
+        (
+            Format::Json,
+            r#"{"metadata": {"version": 3, "tags": ["a", "b"]}}"#,
+        ),
+        (Format::Yaml, "metadata:\n  version: 3\n  tags: [a, b]\n"),
+        (
+            Format::Toml,
+            "[metadata]\nversion = 3\ntags = [\"a\", \"b\"]\n",
+        ),
+    ];
+    for (format, input) in inputs {
+        let version = parse_until_path(input, format, "metadata.version");
+        assert_eq!(version.ok(), Some(Some(Value::from(3))), "{format:?}");
+        let tag = parse_until_path(input, format, "metadata.tags.1");
+        assert_eq!(tag.ok(), Some(Some(Value::from("b"))), "{format:?}");
+        let missing = parse_until_path(input, format, "metadata.name");
+        assert_eq!(missing.ok(), Some(None), "{format:?}");
+    }
+}
+
+#[test]
+fn test_parse_until_path_stops_reading_json() {
+    let input = r#"[{"id": 1}, {"id": 2, "meta": {"x": [true]}}, {"id": "#;
+    let meta = parse_until_path(input, Format::Json, "1.meta");
+    assert_eq!(
+        meta.ok().flatten(),
+        zparse::from_str(r#"{"x": [true]}"#).ok()
+    );
+
+    // The path is past the error, so the error is reported
+    assert!(parse_until_path(input, Format::Json, "2.id").is_err());
+    // The whole document is a path too
+    let options = ConvertOptions::default();
+    let whole = parse_until_path_with_options("[1, 2]", Format::Json, "", &options);
+    assert_eq!(whole.ok().flatten(), zparse::from_str("[1, 2]").ok());
+}
+
+#[test]
+fn test_parse_first_items() {
+    let options = ConvertOptions::default();
+    let items = parse_first(r#"[{"a": 1}, [2], 3, bad"#, Format::Json, 3, &options);
+    assert_eq!(
+        items.ok().map(Value::Array),
+        zparse::from_str(r#"[{"a": 1}, [2], 3]"#).ok()
+    );
+
+    let rows = parse_first("id\n1\n2\n3\n", Format::Csv, 2, &options);
+    assert_eq!(rows.ok().map(|rows| rows.len()), Some(2));
+    let single = parse_first(r#"{"a": 1}"#, Format::Json, 5, &options);
+    assert_eq!(single.ok().map(|items| items.len()), Some(1));
+    let short = parse_first("[1]", Format::Json, 5, &options);
+    assert_eq!(short.ok().map(|items| items.len()), Some(1));
+}
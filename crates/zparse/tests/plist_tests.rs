@@ -0,0 +1,103 @@
+use std::fs;
+use std::path::PathBuf;
+
+use zparse::plist::{from_bytes, from_str, to_string};
+use zparse::registry::Registry;
+use zparse::{ConvertOptions, ErrorKind, TomlDatetime, Value};
+
+fn fixture(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/plist")
+        .join(name);
+    fs::read(path).unwrap_or_default()
+}
+
+fn xml_fixture() -> Value {
+    from_bytes(&fixture("info.plist")).unwrap_or_default()
+}
+
+#[test]
+fn test_xml_plist_maps_every_type() {
+    let value = xml_fixture();
+    assert_eq!(value.get("CFBundleName"), Some(&Value::from("Zed")));
+    assert_eq!(value.get("Version"), Some(&Value::from(42)));
+    assert_eq!(value.get("Negative"), Some(&Value::from(-7)));
+    assert_eq!(value.get("Big"), Some(&Value::from(1_099_511_627_776_u64)));
+    assert_eq!(value.get("Ratio"), Some(&Value::Number(0.5)));
+    assert_eq!(value.get("Enabled"), Some(&Value::Bool(true)));
+    assert_eq!(value.get("Off"), Some(&Value::Bool(false)));
+    assert_eq!(value.get("Icon"), Some(&Value::Bytes(vec![0, 1, 0xfe])));
+    assert_eq!(
+        value.get("Tags").and_then(|tags| tags.get(1)),
+        Some(&Value::from("ünï"))
+    );
+    assert_eq!(
+        value.get("Nested").and_then(|nested| nested.get("Empty")),
+        Some(&Value::Array(zparse::Array::new()))
+    );
+    let built = "2024-01-31T12:00:00Z".parse::<TomlDatetime>().ok();
+    assert_eq!(value.get("Built"), built.map(Value::Datetime).as_ref());
+}
+
+#[test]
+fn test_binary_plist_matches_xml() {
+    let binary = from_bytes(&fixture("info.bplist"));
+    assert!(binary.is_ok(), "{binary:?}");
+    assert_eq!(binary.unwrap_or_default(), xml_fixture());
+}
+
+#[test]
+fn test_plist_output_round_trips() {
+    let value = xml_fixture();
+    let written = to_string(&value).unwrap_or_default();
+    assert!(written.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE plist"));
+    assert!(written.contains("\t<key>Version</key>\n\t<integer>42</integer>\n"));
+    assert!(written.contains("\t\t<array/>\n"));
+    assert_eq!(from_str(&written).ok(), Some(value));
+
+    let escaped = to_string(&Value::from("a < b & c")).unwrap_or_default();
+    assert!(escaped.contains("<string>a &lt; b &amp; c</string>"));
+}
+
+#[test]
+fn test_plist_output_rejects_null_and_times() {
+    let value = zparse::from_str(r#"{"a": [1, null]}"#).unwrap_or_default();
+    let err = to_string(&value).err();
+    assert!(err.is_some_and(
+        |err| matches!(err.kind(), ErrorKind::UnsupportedValue { path } if path == "a.1")
+    ));
+
+    let time = "07:32:00".parse::<TomlDatetime>().map(Value::Datetime);
+    assert!(time.is_ok_and(|time| to_string(&time).is_err()));
+}
+
+#[test]
+fn test_invalid_plists() {
+    assert!(from_str("<plist><dict><string>x</string></dict></plist>").is_err());
+    assert!(from_str("<plist><integer>1</integer><integer>2</integer></plist>").is_err());
+    assert!(from_str("<plist><integer>nope</integer></plist>").is_err());
+    assert!(from_bytes(b"bplist00\x00").is_err());
+
+    // An array containing itself
+    let mut cyclic = b"bplist00\xa1\x00\x08".to_vec();
+    cyclic.extend_from_slice(&[0, 0, 0, 0, 0, 0, 1, 1]);
+    cyclic.extend_from_slice(&1u64.to_be_bytes());
+    cyclic.extend_from_slice(&0u64.to_be_bytes());
+    cyclic.extend_from_slice(&10u64.to_be_bytes());
+    let err = from_bytes(&cyclic).err();
+    assert!(err.is_some_and(|err| matches!(err.kind(), ErrorKind::MaxDepthExceeded { .. })));
+}
+
+#[test]
+fn test_plist_in_registry() {
+    let registry = Registry::default();
+    let plist = registry.for_path("Info.plist");
+    assert_eq!(plist.map(|provider| provider.name()), Some("plist"));
+    let options = ConvertOptions::default();
+    let json = registry
+        .parse_bytes(&fixture("info.bplist"), "plist", &options)
+        .and_then(|value| zparse::serialize(&value, zparse::Format::Json));
+    assert!(json.is_ok_and(|json| json.contains(r#""CFBundleName":"Zed""#)));
+    let back = registry.convert(r#"{"k": true}"#, "json", "plist", &options);
+    assert!(back.is_ok_and(|text| text.contains("<key>k</key>\n\t<true/>")));
+}
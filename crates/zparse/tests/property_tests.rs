@@ -3,10 +3,11 @@
 //! These tests use proptest to verify:
 //! 1. Roundtrip property: parse(value) -> serialize -> parse == original
 //! 2. Valid JSON never panics: any valid JSON parses without error
+//! 3. `values_equal_with` agrees with an independent structural comparison
 
 use proptest::prelude::*;
 use proptest::test_runner::TestCaseError;
-use zparse::{Value, from_str};
+use zparse::{CompareOptions, Value, from_str, values_equal_with};
 
 /// Serialize a Value to JSON string
 fn serialize_value(value: &Value) -> String {
@@ -39,6 +40,7 @@ fn serialize_value(value: &Value) -> String {
             format!("{{{}}}", pairs.join(","))
         }
         Value::Datetime(dt) => format!("\"{}\"", format_toml_datetime(dt)),
+        Value::Bytes(_) => "null".to_string(),
     }
 }
 
@@ -153,6 +155,17 @@ proptest! {
         let parsed = parse_or_fail(&serialized)?;
         assert_values_equal(&parsed, &value)?;
     }
+
+    /// Test that `compare` agrees with the structural comparison above
+    #[test]
+    fn compare_agrees_with_structural_equality(a in arb_json_value(), b in arb_json_value()) {
+        let options = CompareOptions::new().with_epsilon(1e-10);
+        prop_assert!(values_equal_with(&a, &a.clone(), &options));
+        prop_assert_eq!(
+            values_equal_with(&a, &b, &options),
+            assert_values_equal(&a, &b).is_ok()
+        );
+    }
 }
 
 /// Compare two values, handling float comparisons with tolerance
@@ -0,0 +1,389 @@
+use zparse::query::{Query, eval};
+use zparse::{ErrorKind, Format, Value};
+
+/// Outputs of `expr` for the JSON `input` as compact JSON, space separated
+fn query(expr: &str, input: &str) -> Option<String> {
+    let value = zparse::from_str(input).ok()?;
+    let outputs = eval(expr, &value).ok()?;
+    let json: Option<Vec<String>> = outputs
+        .iter()
+        .map(|value| zparse::serialize(value, Format::Json).ok())
+        .collect();
+    json.map(|json| json.join(" "))
+}
+
+fn error_kind(expr: &str, input: &str) -> Option<ErrorKind> {
+    let value = zparse::from_str(input).ok()?;
+    eval(expr, &value).err().map(|err| err.kind().clone())
+}
+
+const USERS: &str = r#"{"users": [
+    {"name": "ana", "age": 31, "tags": ["admin", "dev"]},
+    {"name": "bo", "age": 17, "tags": []},
+    {"name": "cy", "age": 45, "tags": ["dev"]}
+]}"#;
+
+#[test]
+fn test_paths() {
+    let doc = r#"{"a": {"b": [10, 20, 30]}, "odd key": 1}"#;
+    assert_eq!(query(".", "[1]").as_deref(), Some("[1]"));
+    assert_eq!(query(".a.b[1]", doc).as_deref(), Some("20"));
+    assert_eq!(query(".a.b[-1]", doc).as_deref(), Some("30"));
+    assert_eq!(query(".a.b[5]", doc).as_deref(), Some("null"));
+    assert_eq!(query(".a.b[1:]", doc).as_deref(), Some("[20,30]"));
+    assert_eq!(query(".a.b[:-1]", doc).as_deref(), Some("[10,20]"));
+    assert_eq!(query(r#"."odd key""#, doc).as_deref(), Some("1"));
+    assert_eq!(query(r#".["odd key"]"#, doc).as_deref(), Some("1"));
+    assert_eq!(query(".a.b[]", doc).as_deref(), Some("10 20 30"));
+    assert_eq!(query(".missing.deeper", doc).as_deref(), Some("null"));
+    assert_eq!(query(r#""héllo"[1:3]"#, "null").as_deref(), Some(r#""él""#));
+    assert_eq!(
+        query("[..] | length", r#"{"a": [1, {"b": 2}]}"#).as_deref(),
+        Some("5")
+    );
+}
+
+#[test]
+fn test_pipes_and_generators() {
+    assert_eq!(
+        query(".users[] | .name", USERS).as_deref(),
+        Some(r#""ana" "bo" "cy""#)
+    );
+    assert_eq!(query("1, 2 | . * 10", "null").as_deref(), Some("10 20"));
+    assert_eq!(query("[.[] | . + 1]", "[1, 2]").as_deref(), Some("[2,3]"));
+    assert_eq!(
+        query("{a: (1, 2)}", "null").as_deref(),
+        Some(r#"{"a":1} {"a":2}"#)
+    );
+    assert_eq!(
+        query("[(1, 2) + (10, 20)]", "null").as_deref(),
+        Some("[11,12,21,22]")
+    );
+}
+
+#[test]
+fn test_select_map_and_interpolation() {
+    assert_eq!(
+        query(
+            r#"[.users[] | select(.age >= 18) | "\(.name) is \(.age)"]"#,
+            USERS
+        )
+        .as_deref(),
+        Some(r#"["ana is 31","cy is 45"]"#)
+    );
+    assert_eq!(
+        query(
+            "[.users[] | select(.tags | contains([\"dev\"])) | .name]",
+            USERS
+        )
+        .as_deref(),
+        Some(r#"["ana","cy"]"#)
+    );
+    assert_eq!(
+        query(".users | map(.age) | add", USERS).as_deref(),
+        Some("93")
+    );
+    assert_eq!(
+        query(r#""\([1, {"a": null}]) \("x")""#, "null").as_deref(),
+        Some(r#""[1,{\"a\":null}] x""#)
+    );
+}
+
+#[test]
+fn test_object_construction() {
+    assert_eq!(
+        query(
+            ".users[0] | {name, n: (.tags | length), (.name): .age}",
+            USERS
+        )
+        .as_deref(),
+        Some(r#"{"name":"ana","n":2,"ana":31}"#)
+    );
+    assert_eq!(
+        query(r#". as $x | {$x, "y": 1}"#, "5").as_deref(),
+        Some(r#"{"x":5,"y":1}"#)
+    );
+    assert!(matches!(
+        error_kind("{(1): 2}", "null"),
+        Some(ErrorKind::Expected { .. })
+    ));
+}
+
+#[test]
+fn test_arithmetic() {
+    assert_eq!(query("1 + 2 * 3 - 4 / 2", "null").as_deref(), Some("5"));
+    assert_eq!(query("-(1 + 2) % 2", "null").as_deref(), Some("-1"));
+    assert_eq!(
+        query(r#""ab" + "cd", "ab" * 2"#, "null").as_deref(),
+        Some(r#""abcd" "abab""#)
+    );
+    assert_eq!(
+        query("[1, 2, 3, 2] - [2]", "null").as_deref(),
+        Some("[1,3]")
+    );
+    assert_eq!(query("null + 1", "null").as_deref(), Some("1"));
+    assert_eq!(
+        query(r#"{"a": {"b": 1}} * {"a": {"c": 2}}"#, "null").as_deref(),
+        Some(r#"{"a":{"b":1,"c":2}}"#)
+    );
+    assert_eq!(
+        query(r#"{"a": 1} + {"a": 2, "b": 3}"#, "null").as_deref(),
+        Some(r#"{"a":2,"b":3}"#)
+    );
+    assert_eq!(
+        query(r#""a,b" / ",""#, "null").as_deref(),
+        Some(r#"["a","b"]"#)
+    );
+    assert!(error_kind("1 / 0", "null").is_some());
+    assert!(matches!(
+        error_kind(r#"1 + "a""#, "null"),
+        Some(ErrorKind::Expected { .. })
+    ));
+}
+
+#[test]
+fn test_comparisons_and_logic() {
+    assert_eq!(
+        query(
+            "1 < 2, \"a\" > 1, null < false, [1] == [1], 1 != 1.0",
+            "null"
+        )
+        .as_deref(),
+        Some("true true true true false")
+    );
+    assert_eq!(query("0 and \"\"", "null").as_deref(), Some("true"));
+    assert_eq!(
+        query("null or false, (true | not)", "null").as_deref(),
+        Some("false false")
+    );
+    assert_eq!(
+        query(".a // \"default\"", "{}").as_deref(),
+        Some(r#""default""#)
+    );
+    assert_eq!(
+        query(".a // \"default\"", r#"{"a": 0}"#).as_deref(),
+        Some("0")
+    );
+    assert_eq!(query("(.[] | .x) // 9", "[1]").as_deref(), Some("9"));
+}
+
+#[test]
+fn test_conditionals_variables_and_reduce() {
+    let classify = "[.[] | if . < 0 then \"neg\" elif . == 0 then \"zero\" else \"pos\" end]";
+    assert_eq!(
+        query(classify, "[-1, 0, 2]").as_deref(),
+        Some(r#"["neg","zero","pos"]"#)
+    );
+    assert_eq!(query("if false then 1 end", "7").as_deref(), Some("7"));
+    assert_eq!(
+        query(
+            ".limit as $max | [.items[] | select(. <= $max)]",
+            r#"{"limit": 2, "items": [1, 2, 3]}"#
+        )
+        .as_deref(),
+        Some("[1,2]")
+    );
+    assert_eq!(
+        query("reduce .[] as $n (0; . + $n)", "[1, 2, 3]").as_deref(),
+        Some("6")
+    );
+    assert!(matches!(
+        error_kind("$nope", "null"),
+        Some(ErrorKind::UndefinedVariable { name }) if name == "$nope"
+    ));
+}
+
+#[test]
+fn test_builtins() {
+    let cases = [
+        ("length", r#""héllo""#, "5"),
+        ("keys", r#"{"b": 1, "a": 2}"#, r#"["a","b"]"#),
+        ("keys_unsorted", r#"{"b": 1, "a": 2}"#, r#"["b","a"]"#),
+        ("has(\"a\"), has(\"z\")", r#"{"a": null}"#, "true false"),
+        (
+            "[.[] | type]",
+            r#"[null, true, 1, "s", [], {}]"#,
+            r#"["null","boolean","number","string","array","object"]"#,
+        ),
+        ("to_entries", r#"{"a": 1}"#, r#"[{"key":"a","value":1}]"#),
+        ("with_entries(.value += 1)", r#"{"a": 1}"#, ""),
+        ("with_entries(.key |= ascii_upcase)", r#"{"a": 1}"#, ""),
+        (
+            "with_entries({key: (.key | ascii_upcase), value})",
+            r#"{"a": 1}"#,
+            r#"{"A":1}"#,
+        ),
+        (
+            "from_entries",
+            r#"[{"name": "x", "value": 1}, {"k": 2, "v": 3}]"#,
+            r#"{"x":1,"2":3}"#,
+        ),
+        (
+            "map_values(. * 2)",
+            r#"{"a": 1, "b": 2}"#,
+            r#"{"a":2,"b":4}"#,
+        ),
+        ("flatten, flatten(1)", "[1, [2, [3]]]", "[1,2,3] [1,2,[3]]"),
+        ("[range(3)], [range(2; 4)]", "null", "[0,1,2] [2,3]"),
+        (
+            "[.[] | floor, ceil, round, abs]",
+            "[-1.5]",
+            "[-2,-1,-2,1.5]",
+        ),
+        (
+            "sort, unique, min, max, reverse",
+            "[3, 1, 3]",
+            "[1,3,3] [1,3] 1 3 [3,1,3]",
+        ),
+        (
+            "sort_by(.a) | map(.b)",
+            r#"[{"a": 2, "b": "x"}, {"a": 1, "b": "y"}]"#,
+            r#"["y","x"]"#,
+        ),
+        ("group_by(. % 2)", "[1, 2, 3, 4]", "[[2,4],[1,3]]"),
+        ("unique_by(length)", r#"["a", "bb", "c"]"#, r#"["a","bb"]"#),
+        (
+            "min_by(.n), max_by(.n) | .id",
+            r#"[{"n": 2, "id": 1}, {"n": 1, "id": 2}]"#,
+            "2 1",
+        ),
+        (
+            "first, last, first(.[] | select(. > 1)), last(.[])",
+            "[1, 2, 3]",
+            "1 3 2 3",
+        ),
+        ("[limit(2; .[])]", "[1, 2, 3]", "[1,2]"),
+        (
+            "any, all, any(. > 2), all(. > 0)",
+            "[1, 2]",
+            "true true false true",
+        ),
+        (
+            "[.[] | tostring], (.[0] | tojson)",
+            r#"[1, "a", [true]]"#,
+            r#"["1","a","[true]"] "1""#,
+        ),
+        (
+            ". | fromjson | .a, (\"12\" | tonumber)",
+            r#""{\"a\": [1]}""#,
+            "[1] 12",
+        ),
+        ("split(\", \") | join(\"-\")", r#""a, b, c""#, r#""a-b-c""#),
+        (
+            "startswith(\"ab\"), endswith(\"x\"), ltrimstr(\"a\"), rtrimstr(\"c\")",
+            r#""abc""#,
+            r#"true false "bc" "ab""#,
+        ),
+        (
+            "[.[] | numbers], [.[] | strings]",
+            r#"[1, "a", null]"#,
+            r#"[1] ["a"]"#,
+        ),
+        ("[.[] | select(. != null)] | length", "[1, null, 2]", "2"),
+        ("[recurse | numbers]", r#"{"a": [1, {"b": 2}]}"#, "[1,2]"),
+        ("[empty, 1]", "null", "[1]"),
+    ];
+    for (expr, input, expected) in cases {
+        let expected = (!expected.is_empty()).then(|| expected.to_string());
+        assert_eq!(query(expr, input), expected, "{expr}");
+    }
+}
+
+#[test]
+fn test_errors_and_try() {
+    assert!(matches!(
+        error_kind(".a", "[1]"),
+        Some(ErrorKind::Expected { expected, found }) if expected == "object" && found == "array"
+    ));
+    assert_eq!(
+        query("[.[] | .a?]", r#"[{"a": 1}, 2]"#).as_deref(),
+        Some("[1]")
+    );
+    assert_eq!(query("[.[]?]", "3").as_deref(), Some("[]"));
+    assert_eq!(
+        query("[(1, error(\"x\"), 3)?]", "null").as_deref(),
+        Some("[1]")
+    );
+    let message = eval("error(\"boom\")", &Value::Null)
+        .err()
+        .map(|err| err.to_string());
+    assert!(message.is_some_and(|message| message.contains("boom")));
+    assert!(matches!(
+        error_kind("nope(1)", "null"),
+        Some(ErrorKind::UndefinedVariable { name }) if name == "nope/1"
+    ));
+}
+
+#[test]
+fn test_syntax_errors() {
+    for expr in [
+        ".[",
+        "{a:}",
+        "if . then 1",
+        "1 +",
+        "\"abc",
+        ".a |",
+        "reduce . as $x (0)",
+        "then",
+        ")",
+    ] {
+        assert!(Query::parse(expr).is_err(), "{expr}");
+    }
+    let err = Query::parse(".a | .b ]").err();
+    assert!(err.is_some_and(|err| err.span().start.col == 9));
+    let nested = format!("{}1{}", "[".repeat(60), "]".repeat(60));
+    assert!(Query::parse(&nested).is_ok());
+    let nested = format!("{}1{}", "[".repeat(1000), "]".repeat(1000));
+    assert!(matches!(
+        Query::parse(&nested).err().map(|err| err.kind().clone()),
+        Some(ErrorKind::MaxDepthExceeded { .. })
+    ));
+}
+
+#[test]
+fn test_query_reuse_and_comments() {
+    let query = Query::parse(".n * 2 # double it\n").ok();
+    let run = |n: i64| {
+        let mut doc = zparse::Object::new();
+        doc.insert("n", n);
+        query
+            .as_ref()
+            .and_then(|query| query.eval(&Value::Object(doc)).ok())
+    };
+    assert_eq!(run(2), Some(vec![Value::from(4)]));
+    assert_eq!(run(5), Some(vec![Value::from(10)]));
+}
+
+#[test]
+fn test_oversized_string_repetition_is_an_error() {
+    assert_eq!(
+        error_kind(". * 1e18", "\"ab\""),
+        Some(ErrorKind::InvalidToken)
+    );
+    assert_eq!(query(". * 3", "\"ab\"").as_deref(), Some("\"ababab\""));
+}
+
+#[test]
+fn test_range_bounds_are_limited() {
+    // Past 2^53 adding 1 no longer changes a number, which used to loop forever
+    assert!(matches!(
+        error_kind("range(9007199254740992; 9007199254740994)", "null"),
+        Some(ErrorKind::Expected { .. })
+    ));
+    assert!(matches!(
+        error_kind("range(0; 1e12)", "null"),
+        Some(ErrorKind::MaxSizeExceeded { .. })
+    ));
+    assert!(matches!(
+        error_kind("range(-1e300; 0)", "null"),
+        Some(ErrorKind::Expected { .. })
+    ));
+    assert_eq!(
+        query("[range(9007199254740990; 9007199254740992)]", "null").as_deref(),
+        Some("[9007199254740990,9007199254740991]")
+    );
+    assert_eq!(
+        query("[range(0.5; 3)], [range(3; 1)]", "null").as_deref(),
+        Some("[0.5,1.5,2.5] []")
+    );
+}
@@ -0,0 +1,39 @@
+use zparse::{Redactor, from_str};
+
+#[test]
+fn test_redact_default_patterns() -> Result<(), Box<dyn std::error::Error>> {
+    let mut value = from_str(
+        r#"{"db":{"user":"app","Password":"hunter2"},"services":[{"name":"x","access_token":"abc"}],"secrets":{"a":1}}"#,
+    )?;
+    value.redact_secrets();
+
+    let expected = from_str(
+        r#"{"db":{"user":"app","Password":"[REDACTED]"},"services":[{"name":"x","access_token":"[REDACTED]"}],"secrets":"[REDACTED]"}"#,
+    )?;
+    if value != expected {
+        return Err(format!("unexpected redaction result: {value:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_redact_custom_patterns_and_replacement() -> Result<(), Box<dyn std::error::Error>> {
+    let mut value = from_str(r#"{"dsn":"postgres://u:p@h","password":"kept"}"#)?;
+    Redactor::new(["DSN"])
+        .with_replacement("***")
+        .redact(&mut value);
+
+    let expected = from_str(r#"{"dsn":"***","password":"kept"}"#)?;
+    if value != expected {
+        return Err(format!("unexpected redaction result: {value:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_redactor_matches_case_insensitive_substring() {
+    let redactor = Redactor::default().with_pattern("session");
+    assert!(redactor.matches("X-Auth-Token"));
+    assert!(redactor.matches("userSessionId"));
+    assert!(!redactor.matches("username"));
+}
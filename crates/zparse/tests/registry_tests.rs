@@ -0,0 +1,132 @@
+use zparse::registry::{self, Builtin, FormatProvider, Registry};
+use zparse::{ConvertOptions, ErrorKind, Format, FormatOptions, Object, Result, Value};
+
+/// `key=value` lines, read and written
+struct Properties;
+
+impl FormatProvider for Properties {
+    fn name(&self) -> &str {
+        "properties"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["properties"]
+    }
+
+    fn parse(&self, input: &str, _: &ConvertOptions) -> Result<Value> {
+        let entries: Object = input
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim().to_string(), Value::from(value.trim())))
+            .collect();
+        Ok(Value::Object(entries))
+    }
+
+    fn serialize(&self, value: &Value, _: &FormatOptions) -> Result<String> {
+        let entries = value.as_object().map(|object| {
+            object
+                .iter()
+                .map(|(key, value)| format!("{key}={}\n", value.as_string().unwrap_or_default()))
+                .collect::<String>()
+        });
+        Ok(entries.unwrap_or_default())
+    }
+
+    fn can_serialize(&self) -> bool {
+        true
+    }
+
+    fn detect(&self, input: &str) -> bool {
+        input.lines().all(|line| line.contains('='))
+    }
+}
+
+/// Uppercases every string, read-only
+struct Shout;
+
+impl FormatProvider for Shout {
+    fn name(&self) -> &str {
+        "JSON"
+    }
+
+    fn parse(&self, input: &str, _: &ConvertOptions) -> Result<Value> {
+        Ok(Value::from(input.to_uppercase()))
+    }
+}
+
+#[test]
+fn test_default_registry_has_builtins() {
+    let registry = Registry::default();
+    assert_eq!(
+        registry.names(),
+        vec!["json", "csv", "toml", "yaml", "xml", "plist"]
+    );
+    assert_eq!(
+        registry.for_path("a/b.YML").and_then(|p| p.builtin()),
+        Some(Format::Yaml)
+    );
+    assert_eq!(
+        registry.detect("a = 1").and_then(|p| p.builtin()),
+        Some(Format::Toml)
+    );
+    assert!(Registry::empty().get("json").is_none());
+}
+
+#[test]
+fn test_builtin_pairs_convert_like_convert_with_options() {
+    let options = ConvertOptions::default();
+    let input = "name,port\napi,8080\n";
+    let expected = zparse::convert_with_options(input, Format::Csv, Format::Toml, &options);
+    let converted = Registry::default().convert(input, "csv", "TOML", &options);
+    assert!(expected.is_ok());
+    assert_eq!(converted, expected);
+    assert_eq!(
+        Builtin(Format::Json).parse("[1]", &options),
+        zparse::from_str("[1]")
+    );
+}
+
+#[test]
+fn test_custom_provider_converts_both_ways() {
+    let mut registry = Registry::default();
+    registry.register(Properties);
+    let options = ConvertOptions::default();
+
+    let json = registry.convert("host = db\nport=5432", "properties", "json", &options);
+    assert_eq!(json.as_deref().ok(), Some(r#"{"host":"db","port":"5432"}"#));
+    let properties = registry.convert(r#"{"a": "b"}"#, "json", "properties", &options);
+    assert_eq!(properties.as_deref().ok(), Some("a=b\n"));
+    assert_eq!(
+        registry.for_path("app.properties").map(|p| p.name()),
+        Some("properties")
+    );
+    assert_eq!(registry.detect("x=1").map(|p| p.name()), Some("properties"));
+}
+
+#[test]
+fn test_later_providers_win_and_errors() {
+    let mut registry = Registry::default();
+    registry.register(Shout);
+    let options = ConvertOptions::default();
+
+    assert_eq!(registry.names().len(), 6);
+    assert_eq!(
+        registry.parse("hi", "json", &options),
+        Ok(Value::from("HI"))
+    );
+    let read_only = registry.convert("a = 1", "toml", "json", &options);
+    assert!(read_only.is_err_and(|err| matches!(err.kind(), ErrorKind::UnsupportedValue { .. })));
+    let unknown = registry.parse("", "hcl", &options);
+    assert!(unknown.is_err_and(|err| {
+        matches!(err.kind(), ErrorKind::UnknownFormat { path } if path == "hcl")
+            && err.message().contains("json, csv")
+    }));
+}
+
+#[test]
+fn test_global_registry() {
+    registry::register(Properties);
+    assert!(registry::global().get("properties").is_some());
+    let yaml = registry::convert_named("a=1", "properties", "yaml", &ConvertOptions::default());
+    assert_eq!(yaml.as_deref().ok(), Some("a: \"1\""));
+}
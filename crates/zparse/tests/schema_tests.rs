@@ -0,0 +1,65 @@
+use zparse::{Value, from_str, schema};
+
+fn property<'a>(schema: &'a Value, name: &str) -> Option<&'a Value> {
+    schema
+        .as_object()?
+        .get("properties")?
+        .as_object()?
+        .get(name)
+}
+
+#[test]
+fn test_infer_types_required_and_enums() -> Result<(), Box<dyn std::error::Error>> {
+    let samples = [
+        from_str(r#"{"id":1,"status":"open","score":1.5}"#)?,
+        from_str(r#"{"id":2,"status":"open","score":2,"owner":null}"#)?,
+        from_str(r#"{"id":3,"status":"closed","owner":"x"}"#)?,
+    ];
+    let inferred = schema::infer(&samples);
+
+    let expected_required = from_str(r#"["id","status"]"#)?;
+    let required = inferred.as_object().and_then(|obj| obj.get("required"));
+    if required != Some(&expected_required) {
+        return Err(format!("unexpected required keys: {required:?}").into());
+    }
+
+    let checks = [
+        ("id", r#"{"type":"integer"}"#),
+        ("status", r#"{"type":"string","enum":["open","closed"]}"#),
+        ("score", r#"{"type":"number"}"#),
+        ("owner", r#"{"type":["null","string"]}"#),
+    ];
+    for (name, expected) in checks {
+        let expected = from_str(expected)?;
+        if property(&inferred, name) != Some(&expected) {
+            return Err(format!("unexpected schema for {name}: {inferred:?}").into());
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_infer_nested_arrays() -> Result<(), Box<dyn std::error::Error>> {
+    let samples = [from_str(r#"{"points":[[1,2],[3,4.5]],"empty":[]}"#)?];
+    let inferred = schema::infer(&samples);
+
+    let points =
+        from_str(r#"{"type":"array","items":{"type":"array","items":{"type":"number"}}}"#)?;
+    if property(&inferred, "points") != Some(&points) {
+        return Err(format!("unexpected points schema: {inferred:?}").into());
+    }
+    if property(&inferred, "empty") != Some(&from_str(r#"{"type":"array"}"#)?) {
+        return Err(format!("unexpected empty array schema: {inferred:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_infer_without_samples() {
+    let inferred = schema::infer(&[]);
+    let keys: Vec<&String> = inferred
+        .as_object()
+        .map(|obj| obj.keys().collect())
+        .unwrap_or_default();
+    assert_eq!(keys, ["$schema"]);
+}
@@ -0,0 +1,102 @@
+use std::sync::Arc;
+
+use zparse::{ArcValue, Interner, from_str};
+
+#[test]
+fn test_dedup_strings_shares_repeated_values() -> Result<(), Box<dyn std::error::Error>> {
+    let value = from_str(r#"[{"status":"open"},{"status":"open"},{"status":"closed"}]"#)?;
+    let shared = value.dedup_strings();
+
+    let ArcValue::Array(rows) = &shared else {
+        return Err("expected array".into());
+    };
+    let status = |index: usize| match rows.get(index) {
+        Some(ArcValue::Object(row)) => match row.get("status") {
+            Some(ArcValue::String(s)) => Some(Arc::clone(s)),
+            _ => None,
+        },
+        _ => None,
+    };
+    let (first, second, third) = (status(0), status(1), status(2));
+    match (first, second, third) {
+        (Some(a), Some(b), Some(c)) => {
+            if !Arc::ptr_eq(&a, &b) {
+                return Err("identical strings should share an allocation".into());
+            }
+            if Arc::ptr_eq(&a, &c) {
+                return Err("different strings must not be shared".into());
+            }
+        }
+        _ => return Err(format!("unexpected rows: {rows:?}").into()),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_interner_shares_keys_and_values() {
+    let mut interner = Interner::new();
+    let a = interner.intern("name");
+    let b = interner.intern("name");
+    assert!(Arc::ptr_eq(&a, &b));
+    assert_eq!(interner.len(), 1);
+
+    let value = zparse::Value::from("name");
+    let converted = ArcValue::from_value(&value, &mut interner);
+    assert!(matches!(converted, ArcValue::String(s) if Arc::ptr_eq(&s, &a)));
+    assert_eq!(interner.len(), 1);
+}
+
+#[test]
+fn test_arc_value_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<ArcValue>();
+}
+
+#[test]
+fn test_arc_value_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let value = from_str(r#"{"name":"svc","ports":[80,443],"tls":{"enabled":true}}"#)?;
+    let shared = ArcValue::from(&value);
+
+    if shared.get("name").and_then(ArcValue::as_str) != Some("svc") {
+        return Err("missing name".into());
+    }
+    if shared
+        .get("ports")
+        .and_then(ArcValue::as_array)
+        .map(<[_]>::len)
+        != Some(2)
+    {
+        return Err("missing ports".into());
+    }
+    if zparse::Value::from(shared) != value {
+        return Err("round trip changed the document".into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_arc_value_edits_copy_only_the_changed_path() -> Result<(), Box<dyn std::error::Error>> {
+    let original = ArcValue::from(from_str(r#"{"a":{"x":1},"b":{"y":2}}"#)?);
+    let mut edited = original.clone();
+
+    let a = edited
+        .as_object_mut()
+        .and_then(|entries| entries.get_mut("a"))
+        .and_then(ArcValue::as_object_mut)
+        .ok_or("missing a")?;
+    a.insert(Arc::from("x"), ArcValue::Number(10.0));
+
+    let x = |value: &ArcValue| value.get("a").and_then(|a| a.get("x")).cloned();
+    if x(&original) != Some(ArcValue::Number(1.0)) || x(&edited) != Some(ArcValue::Number(10.0)) {
+        return Err("edit leaked into the original".into());
+    }
+    match (original.get("b"), edited.get("b")) {
+        (Some(ArcValue::Object(before)), Some(ArcValue::Object(after))) => {
+            if !Arc::ptr_eq(before, after) {
+                return Err("untouched subtree should stay shared".into());
+            }
+        }
+        _ => return Err("missing b".into()),
+    }
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use zparse::from_str;
+
+#[test]
+fn test_sort_keys_recursively() -> Result<(), Box<dyn std::error::Error>> {
+    let mut value = from_str(r#"{"b":1,"a":{"z":[{"y":1,"x":2}],"c":3}}"#)?;
+    value.sort_keys();
+
+    let keys: Vec<&String> = value.as_object().ok_or("expected object")?.keys().collect();
+    if keys != ["a", "b"] {
+        return Err(format!("unexpected top-level order: {keys:?}").into());
+    }
+    let serialized = zparse::serialize(&value, zparse::Format::Json)?;
+    if serialized != r#"{"a":{"c":3,"z":[{"x":2,"y":1}]},"b":1}"# {
+        return Err(format!("unexpected nested order: {serialized}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_sort_arrays_by_field() -> Result<(), Box<dyn std::error::Error>> {
+    let mut value = from_str(
+        r#"{"users":[{"name":"carol"},{"id":1},{"name":"alice"},{"name":"bob"}],"tags":["b","a"]}"#,
+    )?;
+    value.sort_arrays_by("name");
+
+    let expected = from_str(
+        r#"{"users":[{"name":"alice"},{"name":"bob"},{"name":"carol"},{"id":1}],"tags":["b","a"]}"#,
+    )?;
+    if value != expected {
+        return Err(format!("unexpected sorted document: {value:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_sort_arrays_by_numeric_field_is_stable() -> Result<(), Box<dyn std::error::Error>> {
+    let mut value = from_str(r#"[{"p":2,"k":"a"},{"p":1,"k":"b"},{"p":2,"k":"c"}]"#)?;
+    value.sort_arrays_by("p");
+
+    let expected = from_str(r#"[{"p":1,"k":"b"},{"p":2,"k":"a"},{"p":2,"k":"c"}]"#)?;
+    if value != expected {
+        return Err(format!("unexpected sorted array: {value:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_array_sort_by_path() -> Result<(), Box<dyn std::error::Error>> {
+    let value = from_str(
+        r#"[{"id":"c","meta":{"ports":[443]}},{"id":"a"},{"id":"b","meta":{"ports":[80]}},{"id":"d","meta":{"ports":[80]}}]"#,
+    )?;
+    let mut items = value.as_array().cloned().ok_or("expected array")?;
+    items.sort_by_path("meta.ports.0");
+
+    let ids: Vec<&str> = items
+        .iter()
+        .filter_map(|item| item.get("id").and_then(|id| id.as_string()))
+        .collect();
+    if ids != ["b", "d", "c", "a"] {
+        return Err(format!("unexpected order: {ids:?}").into());
+    }
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use std::io::{Cursor, Read};
+
+use zparse::source::{self, InputSource};
+use zparse::{ConvertOptions, ErrorKind, Format, Result, Value};
+
+type TestResult = std::result::Result<(), Box<dyn std::error::Error>>;
+
+/// Serves a few fixed documents under `fixture://`
+struct Fixtures;
+
+impl InputSource for Fixtures {
+    fn schemes(&self) -> &[&str] {
+        &["fixture"]
+    }
+
+    fn open(&self, location: &str) -> Result<Box<dyn Read + Send>> {
+        let key = location.split_once("://").map_or(location, |(_, key)| key);
+        let body: &[u8] = match key {
+            "bucket/config.toml" => b"name = \"zparse\"\nport = 8080\n",
+            "bucket/untyped" => b"{\"items\": [1, 2]}",
+            _ => return Err(source::error(location, "no such object")),
+        };
+        Ok(Box::new(Cursor::new(body)))
+    }
+}
+
+#[test]
+fn test_scheme() {
+    assert_eq!(source::scheme("s3://bucket/key.json"), Some("s3"));
+    assert_eq!(source::scheme("git+ssh://host/repo"), Some("git+ssh"));
+    assert_eq!(source::scheme("C://data.json"), None);
+    assert_eq!(source::scheme("config/app.json"), None);
+    assert_eq!(source::scheme("://missing"), None);
+}
+
+#[test]
+fn test_registered_source_is_used_by_parse_path() -> TestResult {
+    source::register(Fixtures);
+
+    let value = zparse::parse_path("fixture://bucket/config.toml")?;
+    if value.get("port") != Some(&Value::from(8080)) {
+        return Err(format!("unexpected value: {value:?}").into());
+    }
+
+    let (format, value) =
+        zparse::ops::parse_file("fixture://bucket/untyped", &ConvertOptions::default())?;
+    if format != Format::Json
+        || value.get("items").and_then(|items| items.get(1)) != Some(&Value::from(2))
+    {
+        return Err(format!("unexpected {format:?} value: {value:?}").into());
+    }
+
+    let yaml = zparse::ops::convert_file(
+        "FIXTURE://bucket/config.toml",
+        Format::Yaml,
+        &ConvertOptions::default(),
+    )?;
+    if yaml != "name: \"zparse\"\nport: 8080" {
+        return Err(format!("unexpected yaml: {yaml}").into());
+    }
+
+    match zparse::parse_path("fixture://bucket/missing.json") {
+        Err(err) if matches!(err.kind(), ErrorKind::Io { path } if path == "fixture://bucket/missing.json") => {
+            Ok(())
+        }
+        other => Err(format!("expected a source error, got {other:?}").into()),
+    }
+}
+
+#[test]
+fn test_unclaimed_locations_are_files() -> TestResult {
+    match source::read("unclaimed://bucket/a.json") {
+        Err(err) if matches!(err.kind(), ErrorKind::Io { .. }) => {}
+        other => return Err(format!("expected a file error, got {other:?}").into()),
+    }
+
+    let dir = std::env::temp_dir().join(format!("zparse-source-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join("local.json");
+    std::fs::write(&path, "[true]")?;
+    let mut text = String::new();
+    source::open(&path)?.read_to_string(&mut text)?;
+    let _ = std::fs::remove_dir_all(&dir);
+    if text != "[true]" {
+        return Err(format!("unexpected file contents: {text}").into());
+    }
+    Ok(())
+}
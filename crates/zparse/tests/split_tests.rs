@@ -0,0 +1,248 @@
+use zparse::split::{
+    Pick, for_each_element, for_each_json_element, for_each_json_element_from_reader, pick,
+    pick_ndjson,
+};
+use zparse::{ConvertOptions, ErrorKind, Format, JsonConfig, Value, from_str};
+
+fn collect(input: &str, format: Format, path: &str) -> zparse::Result<Vec<Value>> {
+    let mut items = Vec::new();
+    let count = for_each_element(input, format, &ConvertOptions::default(), path, |item| {
+        items.push(item);
+        Ok(())
+    })?;
+    if count != items.len() {
+        return Err(zparse::Error::new(
+            ErrorKind::InvalidToken,
+            zparse::Span::empty(),
+        ));
+    }
+    Ok(items)
+}
+
+#[test]
+fn test_split_streams_json_array_at_path() -> Result<(), Box<dyn std::error::Error>> {
+    let input = r#"{"meta":{"items":[0]},"data":{"items":[{"id":1,"tags":["a"]},[2,{"x":null}],"three"]},"after":true}"#;
+    let items = collect(input, Format::Json, "data.items")?;
+    let expected = vec![
+        from_str(r#"{"id":1,"tags":["a"]}"#)?,
+        from_str(r#"[2,{"x":null}]"#)?,
+        Value::from("three"),
+    ];
+    if items != expected {
+        return Err(format!("unexpected items: {items:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_split_root_and_indexed_paths() -> Result<(), Box<dyn std::error::Error>> {
+    let count = for_each_json_element(b"[1, [2], 3]", JsonConfig::default(), "", |_| Ok(()))?;
+    if count != 3 {
+        return Err(format!("root array should have three items, got {count}").into());
+    }
+    let nested = collect(r#"[{"rows":[1]},{"rows":[2,3]}]"#, Format::Json, "1.rows")?;
+    if nested != vec![Value::from(2), Value::from(3)] {
+        return Err(format!("unexpected items: {nested:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_split_other_formats() -> Result<(), Box<dyn std::error::Error>> {
+    let yaml = collect("jobs:\n  - name: a\n  - name: b\n", Format::Yaml, "jobs")?;
+    let toml = collect(
+        "[[jobs]]\nname = \"a\"\n[[jobs]]\nname = \"b\"\n",
+        Format::Toml,
+        "jobs",
+    )?;
+    if yaml != toml || yaml.len() != 2 {
+        return Err(format!("unexpected items: {yaml:?} / {toml:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_split_errors() {
+    let kind = |input: &str, path: &str| {
+        collect(input, Format::Json, path)
+            .err()
+            .map(|err| err.kind().clone())
+    };
+    assert!(matches!(
+        kind(r#"{"items":{"a":1}}"#, "items"),
+        Some(ErrorKind::Expected { .. })
+    ));
+    assert!(matches!(
+        kind(r#"{"items":[]}"#, "other"),
+        Some(ErrorKind::PathNotFound { .. })
+    ));
+    // Content after the array is still validated
+    assert!(kind(r#"{"items":[1], "x": }"#, "items").is_some());
+}
+
+#[test]
+fn test_split_stops_when_callback_fails() {
+    let mut seen = 0;
+    let result = for_each_json_element(b"[1,2,3]", JsonConfig::default(), "", |_| {
+        seen += 1;
+        if seen == 2 {
+            return Err(zparse::Error::new(
+                ErrorKind::InvalidToken,
+                zparse::Span::empty(),
+            ));
+        }
+        Ok(())
+    });
+    assert!(result.is_err());
+    assert_eq!(seen, 2);
+}
+
+#[test]
+fn test_pick_head_stops_before_invalid_tail() -> Result<(), Box<dyn std::error::Error>> {
+    let options = ConvertOptions::default();
+    let items = pick("[1, 2, 3, oops", Format::Json, &options, "", Pick::Head(2))?;
+    if items != vec![Value::from(1), Value::from(2)] {
+        return Err(format!("unexpected head: {items:?}").into());
+    }
+    if pick("[1, oops", Format::Json, &options, "", Pick::Tail(1)).is_ok() {
+        return Err("tail should read the whole array".into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_pick_tail_and_sample_keep_document_order() -> Result<(), Box<dyn std::error::Error>> {
+    let input = "items = [1, 2, 3, 4, 5, 6, 7, 8]";
+    let options = ConvertOptions::default();
+    let tail = pick(input, Format::Toml, &options, "items", Pick::Tail(3))?;
+    if tail != vec![Value::from(6), Value::from(7), Value::from(8)] {
+        return Err(format!("unexpected tail: {tail:?}").into());
+    }
+    let sample = Pick::Sample { count: 4, seed: 42 };
+    let first = pick(input, Format::Toml, &options, "items", sample)?;
+    let again = pick(input, Format::Toml, &options, "items", sample)?;
+    if first.len() != 4 || first != again {
+        return Err(format!("sample should be 4 stable items: {first:?} vs {again:?}").into());
+    }
+    let numbers: Vec<f64> = first.iter().filter_map(Value::as_number).collect();
+    if !numbers.is_sorted() {
+        return Err(format!("sample should keep document order: {numbers:?}").into());
+    }
+    let all = pick(
+        input,
+        Format::Toml,
+        &options,
+        "items",
+        Pick::Sample { count: 20, seed: 1 },
+    )?;
+    if all.len() != 8 {
+        return Err(format!("oversized sample should keep every item: {all:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_pick_ndjson_parses_only_picked_lines() -> Result<(), Box<dyn std::error::Error>> {
+    let input = "{\"id\":1}\n\nnot json\n{\"id\":3}\r\n{\"id\":4}\n";
+    let config = JsonConfig::default();
+    let head = pick_ndjson(input, config, Pick::Head(1))?;
+    if head != vec![from_str(r#"{"id":1}"#)?] {
+        return Err(format!("unexpected head: {head:?}").into());
+    }
+    let tail = pick_ndjson(input, config, Pick::Tail(2))?;
+    if tail != vec![from_str(r#"{"id":3}"#)?, from_str(r#"{"id":4}"#)?] {
+        return Err(format!("unexpected tail: {tail:?}").into());
+    }
+    let Err(err) = pick_ndjson(input, config, Pick::Head(2)) else {
+        return Err("the broken second record should fail".into());
+    };
+    if err.span().start.line != 3 || err.span().start.offset != 10 {
+        return Err(format!("error should point at line 3: {err}").into());
+    }
+    Ok(())
+}
+
+fn collect_from_reader(input: &str, config: JsonConfig, path: &str) -> zparse::Result<Vec<Value>> {
+    let mut items = Vec::new();
+    for_each_json_element_from_reader(input.as_bytes(), config, path, |item| {
+        items.push(item);
+        Ok(())
+    })?;
+    Ok(items)
+}
+
+#[test]
+fn test_split_from_reader_matches_in_memory() -> Result<(), Box<dyn std::error::Error>> {
+    let cases = [
+        (
+            r#"{"meta":{"items":[0]},"data":{"items":[{"id":1,"tags":["a]"]},[2,{"x":null}],"th\"ree"]},"after":true}"#,
+            "data.items",
+        ),
+        (r#"[{"rows":[1]},{"rows":[2,3]}]"#, "1.rows"),
+        (" [ 1 , -2.5e3 , true , null , \"x\" ] ", ""),
+        (r#"{"a":[]}"#, "a"),
+    ];
+    for (input, path) in cases {
+        let streamed = collect_from_reader(input, JsonConfig::default(), path)?;
+        let loaded = collect(input, Format::Json, path)?;
+        if streamed != loaded {
+            return Err(format!("{input}: {streamed:?} != {loaded:?}").into());
+        }
+    }
+
+    let bom = collect_from_reader("\u{feff}[1]", JsonConfig::default(), "")?;
+    if bom != vec![Value::from(1)] {
+        return Err(format!("unexpected items: {bom:?}").into());
+    }
+
+    let jsonc = "{ // rows\n \"rows\": [1, /* ] */ 2,],\n}";
+    let config = JsonConfig::default()
+        .with_comments(true)
+        .with_trailing_commas(true);
+    let items = collect_from_reader(jsonc, config, "rows")?;
+    if items != vec![Value::from(1), Value::from(2)] {
+        return Err(format!("unexpected items: {items:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_split_from_reader_errors() {
+    let kind = |input: &str, path: &str| {
+        collect_from_reader(input, JsonConfig::default(), path)
+            .err()
+            .map(|err| err.kind().clone())
+    };
+    assert!(matches!(
+        kind(r#"{"items":{"a":1}}"#, "items"),
+        Some(ErrorKind::Expected { .. })
+    ));
+    assert!(matches!(
+        kind(r#"{"items":[]}"#, "other"),
+        Some(ErrorKind::PathNotFound { .. })
+    ));
+    assert!(matches!(kind("[1,]", ""), Some(ErrorKind::TrailingComma)));
+    // Content after the array is still validated, and so is trailing input
+    assert!(kind(r#"{"items":[1], "x": tru}"#, "items").is_some());
+    assert!(kind(r#"{"items":[1]} {}"#, "items").is_some());
+    assert!(kind(r#"{"items":[1]"#, "items").is_some());
+
+    let err = collect_from_reader(
+        "{\"items\": [1,\n  {\"a\": nul}]}",
+        JsonConfig::default(),
+        "items",
+    )
+    .err();
+    assert!(err.is_some_and(|err| err.span().start.line == 2 && err.span().start.col == 9));
+}
+
+#[test]
+fn test_split_deeply_nested_items_without_limits() -> Result<(), Box<dyn std::error::Error>> {
+    let depth = 200_000;
+    let input = format!("[{}{}]", "[".repeat(depth), "]".repeat(depth));
+    let count = for_each_json_element(input.as_bytes(), JsonConfig::unlimited(), "", |_| Ok(()))?;
+    if count != 1 {
+        return Err(format!("expected one item, got {count}").into());
+    }
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use zparse::{ArcValue, ConfigStore};
+
+#[test]
+fn test_reload_swaps_snapshot_on_change() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("app.toml");
+    std::fs::write(&path, "port = 80\n")?;
+
+    let store = ConfigStore::open(&path)?;
+    let first = store.snapshot();
+    if first.version != 1 || first.value.get("port") != Some(&ArcValue::Number(80.0)) {
+        return Err(format!("unexpected initial snapshot: {first:?}").into());
+    }
+    if store.reload()? {
+        return Err("unchanged file should not reload".into());
+    }
+
+    std::fs::write(&path, "port = 8080\n")?;
+    if !store.reload()? {
+        return Err("changed file should reload".into());
+    }
+    let second = store.snapshot();
+    if second.version != 2 || second.value.get("port") != Some(&ArcValue::Number(8080.0)) {
+        return Err(format!("unexpected reloaded snapshot: {second:?}").into());
+    }
+    // Handles taken before the reload keep their version.
+    if first.value.get("port") != Some(&ArcValue::Number(80.0)) {
+        return Err("old snapshot changed".into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_failed_reload_keeps_current_snapshot() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("app.json");
+    std::fs::write(&path, r#"{"ok":true}"#)?;
+
+    let store = ConfigStore::open(&path)?;
+    std::fs::write(&path, r#"{"ok":"#)?;
+    if store.reload().is_ok() || store.last_error().is_none() {
+        return Err("invalid document should fail to reload".into());
+    }
+    if store.snapshot().version != 1 {
+        return Err("failed reload must keep the previous snapshot".into());
+    }
+
+    std::fs::write(&path, r#"{"ok":false}"#)?;
+    store.force_reload()?;
+    if store.last_error().is_some() || store.snapshot().version != 2 {
+        return Err("successful reload should clear the error".into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_watch_picks_up_changes() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("app.yaml");
+    std::fs::write(&path, "level: info\n")?;
+
+    let store = Arc::new(ConfigStore::open(&path)?);
+    let watcher = store.watch(Duration::from_millis(10));
+    std::fs::write(&path, "level: debug-verbose\n")?;
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while store.snapshot().version == 1 && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    drop(watcher);
+
+    let level = store.snapshot().value.get("level").cloned();
+    if level != Some(ArcValue::String(Arc::from("debug-verbose"))) {
+        return Err(format!("watcher did not reload: {level:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_open_reads_like_parse_file() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("app.jsonc");
+    std::fs::write(&path, "\u{feff}{\n  // listen port\n  \"port\": 8080,\n}\n")?;
+
+    let store = ConfigStore::open(&path)?;
+    let port = store.snapshot().value.get("port").cloned();
+    if port != Some(ArcValue::Number(8080.0)) {
+        return Err(format!("jsonc file with a BOM should load: {port:?}").into());
+    }
+    Ok(())
+}
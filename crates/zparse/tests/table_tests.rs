@@ -0,0 +1,49 @@
+use zparse::table::{Column, columns, extract};
+use zparse::{ErrorKind, Format, Value, from_str, serialize};
+
+fn parse(input: &str) -> Value {
+    from_str(input).unwrap_or_default()
+}
+
+#[test]
+fn test_column_specs() {
+    assert_eq!(
+        Column::parse("city=address.city"),
+        Column {
+            header: "city".to_string(),
+            path: vec!["address".to_string(), "city".to_string()],
+        }
+    );
+    let parsed = columns("id, name ,,tags.0");
+    let headers: Vec<&str> = parsed.iter().map(|c| c.header.as_str()).collect();
+    assert_eq!(headers, vec!["id", "name", "tags.0"]);
+    assert!(Column::parse("").path.is_empty());
+}
+
+#[test]
+fn test_extract_flattens_paths_into_rows() {
+    let records = parse(
+        r#"[
+            {"id": 1, "name": "Ada", "address": {"city": "London"}, "tags": ["x", "y"]},
+            {"id": 2, "name": "Lin, Q", "tags": []}
+        ]"#,
+    );
+    let rows = extract(&records, &columns("id,name,city=address.city,tags.0")).unwrap_or_default();
+    assert_eq!(
+        serialize(&rows, Format::Csv).ok().as_deref(),
+        Some("id,name,city,tags.0\n1,\"Ada\",\"London\",\"x\"\n2,\"Lin, Q\",,\n")
+    );
+}
+
+#[test]
+fn test_extract_single_record_and_errors() {
+    let record = parse(r#"{"a": {"b": true}}"#);
+    let rows = extract(&record, &columns("a.b")).unwrap_or_default();
+    assert_eq!(
+        rows.get(0).and_then(|row| row.get("a.b")),
+        Some(&Value::Bool(true))
+    );
+
+    let err = extract(&Value::from(3), &columns("a")).err();
+    assert!(err.is_some_and(|err| matches!(err.kind(), ErrorKind::Expected { .. })));
+}
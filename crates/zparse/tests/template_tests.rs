@@ -0,0 +1,119 @@
+use zparse::template::{Template, render};
+use zparse::{ErrorKind, from_str, from_yaml_str};
+
+#[test]
+fn test_render_prints_paths() -> Result<(), Box<dyn std::error::Error>> {
+    let value = from_str(
+        r#"{"server":{"name":"api","port":8080,"tls":true,"hosts":["a","b"],"extra":{"x":1.5}}}"#,
+    )?;
+    let out = render(
+        "{{server.name}}:{{ server.port }} tls={{server.tls}} {{server.hosts.1}} {{server.extra}}",
+        &value,
+    )?;
+    if out != r#"api:8080 tls=true b {"x":1.5}"# {
+        return Err(format!("unexpected output: {out:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_render_loops_and_conditions() -> Result<(), Box<dyn std::error::Error>> {
+    let value =
+        from_yaml_str("upstreams:\n  - name: api\n    backup: true\n  - name: web\nlisten: 80\n")?;
+    let template = "\
+upstream {
+  {{ for up in upstreams }}
+  server {{ up.name }}{{ if up.backup }} backup{{ end }};{{ if not loop.last }} # {{ loop.index }}{{ end }}
+  {{ end }}
+}
+{{ if ssl }}
+listen 443 ssl;
+{{ else }}
+listen {{ listen }};
+{{ end }}
+";
+    let out = render(template, &value)?;
+    let expected = "\
+upstream {
+  server api backup; # 0
+  server web;
+}
+listen 80;
+";
+    if out != expected {
+        return Err(format!("unexpected output: {out:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_render_nested_loops_shadow_document_keys() -> Result<(), Box<dyn std::error::Error>> {
+    let value = from_str(r#"{"name":"root","groups":[{"name":"g","items":[1,2]}]}"#)?;
+    let out = render(
+        "{{for name in groups}}{{for item in name.items}}{{name.name}}{{item}} {{end}}{{end}}{{name}}",
+        &value,
+    )?;
+    if out != "g1 g2 root" {
+        return Err(format!("unexpected output: {out:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_render_literal_braces() -> Result<(), Box<dyn std::error::Error>> {
+    let out = render(r#"{{ "{{" }}name{{ "}}" }}"#, &from_str("{}")?)?;
+    if out != "{{name}}" {
+        return Err(format!("unexpected output: {out:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_render_reports_undefined_path_with_position() {
+    let value = from_str(r#"{"a":{"b":1}}"#).unwrap_or_default();
+    let err = render("ok\n  {{ a.c }}", &value).err();
+    let found = err.map(|err| {
+        (
+            err.kind().clone(),
+            err.span().start.line,
+            err.span().start.col,
+        )
+    });
+    assert_eq!(
+        found,
+        Some((
+            ErrorKind::UndefinedVariable {
+                name: "a.c".to_string()
+            },
+            2,
+            3
+        ))
+    );
+}
+
+#[test]
+fn test_parse_rejects_unbalanced_blocks() {
+    for source in [
+        "{{ for x in xs }}",
+        "{{ end }}",
+        "{{ if a }}{{ else }}{{ else }}{{ end }}",
+        "{{ for 1 in xs }}{{ end }}",
+        "{{ a..b }}",
+        "{{ a",
+    ] {
+        assert!(
+            Template::parse(source).is_err(),
+            "{source:?} should not parse"
+        );
+    }
+}
+
+#[test]
+fn test_for_over_non_array_fails() {
+    let value = from_str(r#"{"xs":{"a":1},"none":null}"#).unwrap_or_default();
+    assert!(render("{{for x in xs}}{{end}}", &value).is_err());
+    assert_eq!(
+        render("{{for x in none}}{{x}}{{end}}", &value).ok(),
+        Some(String::new())
+    );
+}
@@ -0,0 +1,122 @@
+use zparse::testgen::{Mix, Options, document, generate, generate_for};
+use zparse::{Format, Value};
+
+fn json(value: &Value) -> String {
+    zparse::serialize(value, Format::Json).unwrap_or_default()
+}
+
+#[test]
+fn test_same_seed_same_document() {
+    let options = Options::new().with_seed(42).with_size(8 * 1024);
+    assert_eq!(generate(&options), generate(&options));
+    assert_ne!(generate(&options), generate(&options.with_seed(43)));
+}
+
+#[test]
+fn test_size_is_approximate() {
+    for size in [1024, 64 * 1024, 512 * 1024] {
+        let len = json(&generate(&Options::new().with_seed(1).with_size(size))).len();
+        assert!(
+            len >= size * 9 / 10 && len <= size * 11 / 10,
+            "{size}: {len}"
+        );
+    }
+    assert_eq!(json(&generate(&Options::new().with_size(0))), "{}");
+}
+
+#[test]
+fn test_depth_and_width() {
+    for max_depth in [1, 2, 5] {
+        let options = Options::new()
+            .with_seed(3)
+            .with_size(32 * 1024)
+            .with_max_depth(max_depth);
+        let value = generate(&options);
+        assert!(value.depth() <= max_depth, "{max_depth}");
+    }
+    let deep = Options::new()
+        .with_size(32 * 1024)
+        .with_max_depth(8)
+        .with_mix("integer=1,array=4".parse().unwrap_or_default());
+    assert_eq!(generate(&deep).depth(), 8);
+
+    let narrow = Options::new().with_size(4096).with_max_width(2);
+    let stats = generate(&narrow).stats();
+    assert!(stats.max_array_length <= 2);
+}
+
+#[test]
+fn test_mix() {
+    let mix: Result<Mix, _> = "string=1, object=1".parse();
+    assert_eq!(
+        mix.as_ref().map(ToString::to_string).ok().as_deref(),
+        Some("string=1,object=1")
+    );
+    let options = Options::new()
+        .with_size(16 * 1024)
+        .with_mix(mix.unwrap_or_default());
+    let stats = generate(&options).stats();
+    let kinds: Vec<&str> = stats.types.keys().copied().collect();
+    assert_eq!(kinds, ["object", "string"]);
+
+    assert!("string=x".parse::<Mix>().is_err());
+    assert!("strings=1".parse::<Mix>().is_err());
+    assert!("object=1,array=1".parse::<Mix>().is_err());
+}
+
+#[test]
+fn test_every_format_reads_back() {
+    let options = Options::new()
+        .with_seed(9)
+        .with_size(16 * 1024)
+        .with_max_depth(4);
+    for format in [
+        Format::Json,
+        Format::Yaml,
+        Format::Toml,
+        Format::Csv,
+        Format::Xml,
+    ] {
+        let text = document(format, &options);
+        assert!(text.is_ok(), "{format:?}: {text:?}");
+        let text = text.unwrap_or_default();
+        let parsed = match format {
+            Format::Xml => zparse::from_xml_str(&text).map(|_| Value::Null),
+            _ => zparse::parse_with_options(&text, format, &Default::default()),
+        };
+        assert!(parsed.is_ok(), "{format:?}: {parsed:?}");
+        if matches!(format, Format::Json | Format::Yaml | Format::Toml) {
+            assert_eq!(
+                parsed.ok(),
+                Some(generate_for(format, &options)),
+                "{format:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_csv_records_share_columns() {
+    let value = generate_for(Format::Csv, &Options::new().with_seed(5).with_size(8192));
+    let rows = value
+        .as_array()
+        .map(|rows| rows.iter().collect::<Vec<_>>())
+        .unwrap_or_default();
+    assert!(rows.len() > 10);
+    let columns = |row: &Value| {
+        row.as_object()
+            .map(|row| row.keys().cloned().collect::<Vec<_>>())
+            .unwrap_or_default()
+    };
+    let first = rows.first().map(|row| columns(row)).unwrap_or_default();
+    assert!(!first.is_empty());
+    for row in &rows {
+        assert_eq!(columns(row), first);
+        assert!(row.as_object().is_some_and(|row| {
+            row.values()
+                .all(|cell| !cell.is_array() && !cell.is_object())
+        }));
+    }
+    let toml = generate_for(Format::Toml, &Options::new().with_size(8192));
+    assert_eq!(toml.stats().types.get("null"), None);
+}
@@ -0,0 +1,78 @@
+use zparse::error::ErrorKind;
+use zparse::{TomlBuilder, Value, from_toml_str};
+
+fn basket() -> zparse::Result<TomlBuilder> {
+    let mut doc = TomlBuilder::new();
+    doc.comment("Fruit basket\n");
+    doc.set("owner", "Ada")?;
+    doc.add_array_of_tables(["fruit"])?;
+    doc.set("name", "apple")?;
+    doc.add_table(["fruit", "physical"])?;
+    doc.comment("measured");
+    doc.set("color", "red")?;
+    doc.add_array_of_tables(["fruit"])?;
+    doc.set("name", "banana")?;
+    doc.add_table(["fruit", "physical"])?;
+    doc.set("sizes", Value::from(vec![Value::from(1), Value::from(2)]))?;
+    doc.add_table(["server config"])?;
+    doc.set("port", 8080)?;
+    Ok(doc)
+}
+
+#[test]
+fn test_builder_writes_sections_in_order_and_reads_back() {
+    let doc = basket();
+    assert!(doc.is_ok());
+    let doc = doc.unwrap_or_default();
+
+    let text = doc.to_string();
+    assert_eq!(
+        text,
+        concat!(
+            "# Fruit basket\n",
+            "owner = \"Ada\"\n",
+            "\n[[fruit]]\n",
+            "name = \"apple\"\n",
+            "\n[fruit.physical]\n",
+            "# measured\n",
+            "color = \"red\"\n",
+            "\n[[fruit]]\n",
+            "name = \"banana\"\n",
+            "\n[fruit.physical]\n",
+            "sizes = [1, 2]\n",
+            "\n[\"server config\"]\n",
+            "port = 8080\n",
+        )
+    );
+    assert_eq!(from_toml_str(&text).ok(), Some(doc.to_value()));
+}
+
+#[test]
+fn test_builder_rejects_redefinitions() {
+    let mut doc = TomlBuilder::new();
+    assert!(doc.set("name", "x").is_ok());
+    assert!(doc.add_table(["server"]).is_ok());
+    assert!(doc.set("host", "localhost").is_ok());
+
+    let kind =
+        |result: zparse::Result<&mut TomlBuilder>| result.err().map(|err| err.kind().clone());
+    assert_eq!(
+        kind(doc.set("host", "example.com")),
+        Some(ErrorKind::DuplicateKey {
+            key: "server.host".to_string()
+        })
+    );
+    assert!(kind(doc.add_table(["server"])).is_some());
+    assert!(kind(doc.add_table(["name", "inner"])).is_some());
+    assert!(kind(doc.add_array_of_tables(["server", "host"])).is_some());
+    assert!(kind(doc.add_table(Vec::<String>::new())).is_some());
+    assert!(matches!(
+        kind(doc.set("missing", Value::Null)),
+        Some(ErrorKind::UnsupportedValue { .. })
+    ));
+
+    // Implicitly created parents can still get their own header
+    assert!(doc.add_table(["a", "b"]).is_ok());
+    assert!(doc.add_table(["a"]).is_ok());
+    assert!(doc.to_string().ends_with("[a.b]\n\n[a]\n"));
+}
@@ -0,0 +1,79 @@
+use zparse::toml_compat::prepare_for_toml;
+use zparse::{ConvertOptions, ErrorKind, Format, NullPolicy, TomlCompat, convert_with_options};
+
+fn yaml_to_toml(input: &str, compat: TomlCompat) -> zparse::Result<String> {
+    let options = ConvertOptions {
+        toml_compat: compat,
+        ..Default::default()
+    };
+    convert_with_options(input, Format::Yaml, Format::Toml, &options)
+}
+
+#[test]
+fn test_yaml_null_reports_path_by_default() {
+    let result = yaml_to_toml("server:\n  host: null\n", TomlCompat::new());
+    assert!(matches!(
+        result,
+        Err(err) if matches!(err.kind(), ErrorKind::UnsupportedValue { path } if path == "$.server.host")
+    ));
+}
+
+#[test]
+fn test_yaml_null_policies() -> Result<(), Box<dyn std::error::Error>> {
+    let input = "a: 1\nb: null\nlist: [1, null]\n";
+
+    let omitted = yaml_to_toml(input, TomlCompat::new().with_null(NullPolicy::Omit))?;
+    if omitted != "a = 1\nlist = [1]" {
+        return Err(format!("unexpected omit output: {omitted:?}").into());
+    }
+
+    let empty = yaml_to_toml(input, TomlCompat::new().with_null(NullPolicy::EmptyString))?;
+    if empty != "a = 1\nb = \"\"\nlist = [1, \"\"]" {
+        return Err(format!("unexpected empty-string output: {empty:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_yaml_root_must_be_table() -> Result<(), Box<dyn std::error::Error>> {
+    let result = yaml_to_toml("- a\n- b\n", TomlCompat::new());
+    if !matches!(&result, Err(err) if err.message().contains("table at the root")) {
+        return Err(format!("expected a root error, got {result:?}").into());
+    }
+
+    let wrapped = yaml_to_toml("- a\n- b\n", TomlCompat::new().with_root_key("items"))?;
+    if wrapped != "items = [\"a\", \"b\"]" {
+        return Err(format!("unexpected wrapped output: {wrapped:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_homogeneous_arrays_option() -> Result<(), Box<dyn std::error::Error>> {
+    let mixed = zparse::from_str(r#"{"list":[1,"x"]}"#)?;
+    if prepare_for_toml(mixed.clone(), &TomlCompat::new()).is_err() {
+        return Err("TOML 1.0 allows mixed arrays".into());
+    }
+    let strict = TomlCompat::new().with_homogeneous_arrays(true);
+    let result = prepare_for_toml(mixed, &strict);
+    if !matches!(
+        &result,
+        Err(err) if matches!(err.kind(), ErrorKind::UnsupportedValue { path } if path == "$.list[1]")
+    ) {
+        return Err(format!("expected a mixed array error, got {result:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_toml_keys_are_quoted_when_needed() -> Result<(), Box<dyn std::error::Error>> {
+    let output = zparse::convert(
+        r#"{"a b":{"c.d":1},"plain-key":2}"#,
+        Format::Json,
+        Format::Toml,
+    )?;
+    if output != "\"a b\" = {\"c.d\" = 1}\nplain-key = 2" {
+        return Err(format!("unexpected key quoting: {output:?}").into());
+    }
+    Ok(())
+}
@@ -0,0 +1,132 @@
+//! TOML spec conformance matrix
+//!
+//! Each case is named after its counterpart in the toml-test suite
+//! (`valid/...` must parse, `invalid/...` must be rejected). Cases the parser
+//! still gets wrong are listed in `KNOWN_GAPS`; the test fails when a new
+//! case breaks and also when a listed gap starts passing, so the list always
+//! reflects the current compliance status.
+
+use zparse::toml::Parser;
+
+const VALID: &[(&str, &str)] = &[
+    ("valid/table/empty", "[a]\n"),
+    ("valid/table/without-super", "[x.y.z.w]\n[x]\n"),
+    (
+        "valid/table/sub-of-dotted",
+        "[fruit]\napple.color = \"red\"\napple.taste.sweet = true\n[fruit.apple.texture]\nsmooth = true\n",
+    ),
+    ("valid/table/names-with-dots", "[a.\"b.c\"]\nx = 1\n"),
+    ("valid/table/whitespace", "[ a . b ]\nx = 1\n"),
+    (
+        "valid/table/array-implicit",
+        "[[albums.songs]]\nname = \"Glory Days\"\n",
+    ),
+    (
+        "valid/table/array-nest",
+        "[[albums]]\nname = \"a\"\n[[albums.songs]]\nname = \"s\"\n[[albums]]\nname = \"b\"\n[[albums.songs]]\nname = \"t\"\n",
+    ),
+    (
+        "valid/table/array-table-array",
+        "[[a]]\n[[a.b]]\n[a.b.c]\nd = \"val0\"\n[[a.b]]\n[a.b.c]\nd = \"val1\"\n",
+    ),
+    (
+        "valid/array/array-subtables",
+        "[[fruits]]\nname = \"apple\"\n[fruits.physical]\ncolor = \"red\"\n[[fruits.varieties]]\nname = \"red delicious\"\n[[fruits]]\nname = \"banana\"\n[[fruits.varieties]]\nname = \"plantain\"\n",
+    ),
+    ("valid/key/dotted-same-section", "a.b = 1\na.c = 2\n"),
+    ("valid/key/quoted-dots", "\"a.b\" = 1\n"),
+    ("valid/inline-table/nest", "a = { b = { c = 1 } }\n"),
+    ("valid/comment/everywhere", "# c\n[a] # c\nb = 1 # c\n"),
+    ("valid/string/multiline", "a = \"\"\"\nline\"\"\"\n"),
+    ("valid/integer/underscore", "a = 1_000\n"),
+    ("valid/integer/hex", "a = 0xff\n"),
+    ("valid/float/exponent", "a = 1e10\n"),
+    ("valid/datetime/local-date", "a = 1979-05-27\n"),
+];
+
+const INVALID: &[(&str, &str)] = &[
+    ("invalid/table/duplicate", "[a]\nb = 1\n[a]\nc = 2\n"),
+    ("invalid/table/redefine-1", "[a.b]\n[a]\n[a]\n"),
+    ("invalid/table/duplicate-table-array", "[tbl]\n[[tbl]]\n"),
+    ("invalid/table/duplicate-table-array2", "[[tbl]]\n[tbl]\n"),
+    (
+        "invalid/table/duplicate-key-table",
+        "[fruit]\ntype = \"apple\"\n[fruit.type]\n",
+    ),
+    (
+        "invalid/table/append-with-dotted-keys-1",
+        "[a.b.c]\nz = 9\n[a]\nb.c.t = 9\n",
+    ),
+    (
+        "invalid/table/append-with-dotted-keys-2",
+        "[a.b.c.d]\nz = 9\n[a]\nb.c.d.k.t = 8\n",
+    ),
+    (
+        "invalid/table/redefine-2",
+        "[fruit]\napple.color = \"red\"\n[fruit.apple]\n",
+    ),
+    (
+        "invalid/table/redefine-3",
+        "[fruit]\napple.taste.sweet = true\n[fruit.apple.taste]\n",
+    ),
+    ("invalid/table/overwrite-with-deep-table", "a = 1\n[a.b]\n"),
+    ("invalid/table/text-after-table", "[a] b = 1\n"),
+    ("invalid/table/empty", "[]\n"),
+    ("invalid/inline-table/add", "a = {b = 1}\na.c = 2\n"),
+    (
+        "invalid/inline-table/overwrite-01",
+        "a = {b = 1}\n[a]\nc = 2\n",
+    ),
+    (
+        "invalid/inline-table/overwrite-02",
+        "a = {b = {}}\n[a.b.c]\n",
+    ),
+    ("invalid/array/extend-defined-aot", "a = []\n[[a]]\n"),
+    ("invalid/array/tables-1", "a = [{b = 1}]\n[a.c]\n"),
+    ("invalid/key/duplicate", "a = 1\na = 2\n"),
+    ("invalid/key/dotted-redefine-table", "a = 1\na.b = 2\n"),
+    ("invalid/key/without-value", "a =\n"),
+    ("invalid/key/newline", "a\n= 1\n"),
+    ("invalid/string/bad-escape", "a = \"\\q\"\n"),
+    ("invalid/integer/leading-zero", "a = 01\n"),
+    ("invalid/float/trailing-point", "a = 1.\n"),
+    ("invalid/array/missing-separator", "a = [1 2]\n"),
+];
+
+/// Cases the parser does not handle correctly yet
+const KNOWN_GAPS: &[&str] = &[
+    "valid/integer/hex",
+    "invalid/integer/leading-zero",
+    "invalid/float/trailing-point",
+];
+
+#[test]
+fn test_toml_conformance_matrix() -> Result<(), String> {
+    let cases = VALID
+        .iter()
+        .map(|(name, input)| (name, input, true))
+        .chain(INVALID.iter().map(|(name, input)| (name, input, false)));
+
+    let mut failing = Vec::new();
+    for (name, input, valid) in cases {
+        let accepted = Parser::new(input.as_bytes()).parse().is_ok();
+        if accepted != valid {
+            failing.push(*name);
+        }
+    }
+
+    let regressions: Vec<_> = failing
+        .iter()
+        .filter(|name| !KNOWN_GAPS.contains(name))
+        .collect();
+    let fixed: Vec<_> = KNOWN_GAPS
+        .iter()
+        .filter(|name| !failing.contains(name))
+        .collect();
+    if !regressions.is_empty() || !fixed.is_empty() {
+        return Err(format!(
+            "failing cases not in KNOWN_GAPS: {regressions:?}; fixed gaps to remove: {fixed:?}"
+        ));
+    }
+    Ok(())
+}
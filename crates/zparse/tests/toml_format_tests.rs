@@ -0,0 +1,123 @@
+use zparse::{
+    Format, FormatOptions, Object, TomlStringStyle, Value, from_toml_str, serialize_with_options,
+};
+
+fn document(text: &str) -> Value {
+    let mut object = Object::new();
+    object.insert("text", Value::String(text.to_string()));
+    Value::Object(object)
+}
+
+fn to_toml(text: &str, options: FormatOptions) -> zparse::Result<String> {
+    serialize_with_options(&document(text), Format::Toml, &options)
+}
+
+#[test]
+fn test_default_style_escapes_newlines() {
+    let output = to_toml("a\nb", FormatOptions::default()).ok();
+    assert_eq!(output.as_deref(), Some("text = \"a\\nb\""));
+}
+
+#[test]
+fn test_multiline_basic_and_literal_output() -> zparse::Result<()> {
+    let script = "#!/bin/sh\necho \"hi\"\n";
+
+    let basic = to_toml(
+        script,
+        FormatOptions::new().with_toml_strings(TomlStringStyle::MultilineBasic),
+    )?;
+    let literal = to_toml(
+        script,
+        FormatOptions::new().with_toml_strings(TomlStringStyle::MultilineLiteral),
+    )?;
+
+    if basic != "text = \"\"\"\n#!/bin/sh\necho \"hi\"\n\"\"\"" {
+        return Err(zparse::Error::with_message(
+            zparse::ErrorKind::InvalidToken,
+            zparse::Span::empty(),
+            format!("unexpected basic output: {basic}"),
+        ));
+    }
+    if literal != "text = '''\n#!/bin/sh\necho \"hi\"\n'''" {
+        return Err(zparse::Error::with_message(
+            zparse::ErrorKind::InvalidToken,
+            zparse::Span::empty(),
+            format!("unexpected literal output: {literal}"),
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_literal_falls_back_to_basic() -> zparse::Result<()> {
+    let options = FormatOptions::new().with_toml_strings(TomlStringStyle::MultilineLiteral);
+    for text in ["has '''\nquotes", "ends with\nquote'", "carriage\r\nreturn"] {
+        let output = to_toml(text, options)?;
+        if !output.starts_with("text = \"\"\"\n") {
+            return Err(zparse::Error::with_message(
+                zparse::ErrorKind::InvalidToken,
+                zparse::Span::empty(),
+                format!("expected basic fallback, got {output}"),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_long_strings_are_folded() -> zparse::Result<()> {
+    let options = FormatOptions::new()
+        .with_toml_strings(TomlStringStyle::MultilineBasic)
+        .with_toml_fold_width(10);
+    let output = to_toml("alpha beta gamma  delta", options)?;
+    if output != "text = \"\"\"\nalpha \\\nbeta \\\ngamma  \\\ndelta\"\"\"" {
+        return Err(zparse::Error::with_message(
+            zparse::ErrorKind::InvalidToken,
+            zparse::Span::empty(),
+            format!("unexpected folded output: {output}"),
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_multiline_strings_round_trip() -> zparse::Result<()> {
+    let samples = [
+        "plain",
+        "line one\nline two\n",
+        "\nleading newline",
+        "quotes \"\" and \"\"\" inside\n\"",
+        "back\\slash\\\nand \\n literal",
+        "tabs\tand\r\nwindows\nline endings",
+        "it's '''\nliteral'",
+        "spaces   at the end   \n   and start",
+        "a long line that will need folding somewhere in the middle of it\nand another one right here",
+    ];
+    let styles = [
+        FormatOptions::new().with_toml_strings(TomlStringStyle::MultilineBasic),
+        FormatOptions::new()
+            .with_toml_strings(TomlStringStyle::MultilineBasic)
+            .with_toml_fold_width(10),
+        FormatOptions::new().with_toml_strings(TomlStringStyle::MultilineLiteral),
+    ];
+    for options in styles {
+        for sample in samples {
+            let output = to_toml(sample, options)?;
+            let parsed = from_toml_str(&output)?;
+            if parsed != document(sample) {
+                return Err(zparse::Error::with_message(
+                    zparse::ErrorKind::InvalidToken,
+                    zparse::Span::empty(),
+                    format!("round trip failed for {sample:?} via {output:?}: {parsed:?}"),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_basic_string_escapes_parse() {
+    let value = from_toml_str("text = \"a\\tb\\\\c\\\"d\\ne\"").ok();
+    assert_eq!(value, Some(document("a\tb\\c\"d\ne")));
+}
@@ -2,7 +2,7 @@ use time::format_description::well_known::Rfc3339;
 use time::macros::format_description;
 use time::{Date, OffsetDateTime, PrimitiveDateTime, Time};
 use zparse::error::{Error, ErrorKind, Result};
-use zparse::toml::parser::{Config, Parser};
+use zparse::toml::parser::{Config, KeyStyle, Parser};
 use zparse::{Span, TomlDatetime, Value};
 
 fn ensure_eq<T: PartialEq + std::fmt::Debug>(left: T, right: T) -> Result<()> {
@@ -249,3 +249,47 @@ fn test_size_limit_counts_ignorable_prefix() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_record_key_styles() -> Result<()> {
+    let input = b"\"a b\".c = 1\n[[t]]\n'x' = {y = [{\"z\" = 2}]}\n[[t]]\nx = 3\n";
+    let mut parser = Parser::new(input);
+    parser.record_key_styles();
+    parser.parse()?;
+
+    let styles = parser.take_key_styles();
+    ensure_eq(
+        styles,
+        vec![
+            ("/a b".to_string(), KeyStyle::Basic),
+            ("/a b/c".to_string(), KeyStyle::Bare),
+            ("/t".to_string(), KeyStyle::Bare),
+            ("/t/0/x".to_string(), KeyStyle::Literal),
+            ("/t/0/x/y".to_string(), KeyStyle::Bare),
+            ("/t/0/x/y/0/z".to_string(), KeyStyle::Basic),
+            ("/t".to_string(), KeyStyle::Bare),
+            ("/t/1/x".to_string(), KeyStyle::Bare),
+        ],
+    )?;
+    ensure_eq(Parser::new(input).take_key_styles(), Vec::new())
+}
+
+#[test]
+fn test_parse_error_frames_name_key_and_table() -> Result<()> {
+    let input = b"[server.ssh]\nport = \"a\n";
+    let Err(err) = Parser::new(input).parse() else {
+        return Err(Error::with_message(
+            ErrorKind::InvalidToken,
+            Span::empty(),
+            "expected a parse error".to_string(),
+        ));
+    };
+    ensure_eq(err.kind(), &ErrorKind::UnterminatedString)?;
+    ensure_eq(
+        err.frames(),
+        &[
+            "while parsing key 'port'".to_string(),
+            "while parsing table [server.ssh]".to_string(),
+        ][..],
+    )
+}
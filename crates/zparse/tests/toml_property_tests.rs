@@ -40,7 +40,7 @@ fn serialize_value(value: &Value) -> String {
         }
         Value::Object(obj) => serialize_inline_table(obj),
         Value::Datetime(dt) => format_toml_datetime(dt),
-        Value::Null => "".to_string(),
+        Value::Null | Value::Bytes(_) => "".to_string(),
     }
 }
 
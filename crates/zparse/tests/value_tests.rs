@@ -62,6 +62,48 @@ fn test_value_from_impls() {
     assert!(matches!(v, Value::Array(arr) if arr.len() == 2));
 }
 
+#[test]
+fn test_value_from_std_collections() {
+    let expected = zparse::from_str(r#"[1, 2, 3]"#).unwrap_or_default();
+    assert_eq!(Value::from(vec![1u8, 2, 3]), expected);
+    assert_eq!(Value::from([1i64, 2, 3]), expected);
+    assert_eq!(Value::from(&[1u16, 2, 3][..]), expected);
+    assert_eq!((1u32..=3).collect::<Value>(), expected);
+
+    assert_eq!(Value::from(Some("x")), Value::from("x"));
+    assert_eq!(Value::from(None::<i32>), Value::Null);
+    assert_eq!(
+        Value::from(vec![Some(1.5), None]),
+        zparse::from_str("[1.5, null]").unwrap_or_default()
+    );
+
+    let btree = std::collections::BTreeMap::from([("b", vec!["x"]), ("a", vec![])]);
+    let v = Value::from(btree);
+    assert_eq!(
+        v,
+        zparse::from_str(r#"{"a": [], "b": ["x"]}"#).unwrap_or_default()
+    );
+    let keys: Vec<_> = v
+        .as_object()
+        .map(|obj| obj.keys().cloned().collect())
+        .unwrap_or_default();
+    assert_eq!(keys, ["a", "b"]);
+
+    let hash = std::collections::HashMap::from([("port".to_string(), 8080usize)]);
+    assert_eq!(
+        Value::from(hash),
+        zparse::from_str(r#"{"port": 8080}"#).unwrap_or_default()
+    );
+
+    assert_eq!(Value::from('z'), Value::from("z"));
+    assert_eq!(
+        Value::from(std::borrow::Cow::Borrowed("c")),
+        Value::from("c")
+    );
+    assert_eq!(Value::from(-3i8), Value::Number(-3.0));
+    assert_eq!(Value::from(0.5f32), Value::Number(0.5));
+}
+
 #[test]
 fn test_object_basics() {
     let mut obj = Object::new();
@@ -119,6 +161,48 @@ fn test_object_order_preservation() {
     );
 }
 
+fn keys(obj: &Object) -> Vec<&str> {
+    obj.keys().map(String::as_str).collect()
+}
+
+#[test]
+fn test_object_entry_and_ordered_insertion() {
+    let mut obj: Object = [("name", "api"), ("port", "80")]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), Value::from(value)))
+        .collect();
+
+    obj.entry("tags").or_insert_with(|| Value::from(vec!["a"]));
+    if let Some(Value::Array(tags)) = obj.get_mut("tags") {
+        tags.push("b");
+    }
+    *obj.entry("name").or_insert(Value::Null) = Value::from("web");
+    assert_eq!(obj.get("name"), Some(&Value::from("web")));
+    assert_eq!(obj.get("tags"), Some(&Value::from(vec!["a", "b"])));
+    assert_eq!(keys(&obj), ["name", "port", "tags"]);
+
+    assert_eq!(obj.insert_before(1, "host", "localhost"), None);
+    assert_eq!(keys(&obj), ["name", "host", "port", "tags"]);
+    assert_eq!(obj.insert_after(0, "version", 2), None);
+    assert_eq!(keys(&obj), ["name", "version", "host", "port", "tags"]);
+
+    // Existing keys move and keep the new value
+    assert_eq!(
+        obj.insert_after(obj.index_of("tags").unwrap_or_default(), "name", "db"),
+        Some(Value::from("web"))
+    );
+    assert_eq!(keys(&obj), ["version", "host", "port", "tags", "name"]);
+    assert_eq!(obj.insert_before(0, "port", 5432), Some(Value::from("80")));
+    assert_eq!(keys(&obj), ["port", "version", "host", "tags", "name"]);
+
+    obj.move_key(4, 0);
+    assert_eq!(keys(&obj), ["name", "port", "version", "host", "tags"]);
+    obj.move_key(0, 4);
+    assert_eq!(keys(&obj), ["port", "version", "host", "tags", "name"]);
+    assert_eq!(obj.index_of("host"), Some(2));
+    assert_eq!(obj.index_of("missing"), None);
+}
+
 #[test]
 fn test_object_iter() {
     let mut obj = Object::new();
@@ -159,6 +243,44 @@ fn test_array_basics() {
     assert_eq!(arr.len(), 1);
 }
 
+#[test]
+fn test_array_extend_retain_dedup() {
+    let mut arr = Array::new();
+    arr.extend([1, 2, 3]);
+    arr.extend(vec!["a", "b"]);
+    arr.extend(Some(Value::Null));
+    assert_eq!(
+        Value::Array(arr.clone()),
+        Value::from(vec![
+            Value::from(1),
+            Value::from(2),
+            Value::from(3),
+            Value::from("a"),
+            Value::from("b"),
+            Value::Null,
+        ])
+    );
+
+    arr.retain(Value::is_number);
+    assert_eq!(Value::Array(arr.clone()), Value::from(vec![1, 2, 3]));
+
+    let mut arr: Array = [3, 1, 3, 2, 1]
+        .into_iter()
+        .map(Value::from)
+        .chain([Value::from(vec![1]), Value::from(vec![1])])
+        .collect();
+    arr.dedup();
+    assert_eq!(
+        Value::Array(arr),
+        Value::from(vec![
+            Value::from(3),
+            Value::from(1),
+            Value::from(2),
+            Value::from(vec![1]),
+        ])
+    );
+}
+
 #[test]
 fn test_array_index() {
     let mut arr = Array::new();
@@ -205,3 +327,102 @@ fn test_non_array_value_into_iterator() {
     let collected: Vec<_> = value.into_iter().collect();
     assert!(collected.is_empty());
 }
+
+#[test]
+fn test_value_scripting_helpers() {
+    let mut value =
+        zparse::from_str(r#"{"name": "zπ", "tags": ["a", "b"], "port": 0}"#).unwrap_or_default();
+
+    assert_eq!(value.len(), Some(3));
+    assert_eq!(value.get("name").and_then(Value::len), Some(2));
+    assert_eq!(
+        value.get("tags").and_then(|tags| tags.get(1)),
+        Some(&Value::from("b"))
+    );
+    assert_eq!(value.get("tags").and_then(|tags| tags.get(2)), None);
+    assert_eq!(value.get(0), None);
+    assert_eq!(Value::Number(1.0).len(), None);
+
+    if let Some(port) = value.get_mut(String::from("port")) {
+        *port = Value::Number(8080.0);
+    }
+    assert!(value.get("port").is_some_and(Value::is_truthy));
+
+    assert_eq!(value.type_name(), "object");
+    assert_eq!(Value::Null.type_name(), "null");
+    assert_eq!(Value::Bool(true).type_name(), "boolean");
+
+    assert!(!Value::Null.is_truthy());
+    assert!(!Value::Number(0.0).is_truthy());
+    assert!(!Value::Number(f64::NAN).is_truthy());
+    assert!(!Value::from("").is_truthy());
+    assert!(!Value::Array(Array::new()).is_truthy());
+    assert!(Value::from("0").is_truthy());
+    assert!(Value::Array(Array::new()).is_empty());
+    assert!(!Value::Null.is_empty());
+}
+
+#[test]
+fn test_value_eq_ord_hash() {
+    use std::collections::{BTreeSet, HashSet};
+
+    assert_eq!(Value::Number(f64::NAN), Value::Number(f64::NAN));
+    assert_eq!(Value::Number(-0.0), Value::Number(0.0));
+    assert!(Value::Number(f64::NAN) > Value::Number(f64::INFINITY));
+    assert!(Value::Number(-1.0) < Value::Number(-0.0));
+
+    let ab = zparse::from_str(r#"{"a": 1, "b": 2}"#).unwrap_or_default();
+    let ba = zparse::from_str(r#"{"b": 2, "a": 1}"#).unwrap_or_default();
+    assert_eq!(ab, ba);
+    assert_eq!(ab.cmp(&ba), std::cmp::Ordering::Equal);
+    let set: HashSet<Value> = [ab.clone(), ba, Value::Number(-0.0), Value::Number(0.0)].into();
+    assert_eq!(set.len(), 2);
+
+    let sorted: Vec<Value> = BTreeSet::from([
+        ab,
+        Value::from(vec![Value::from(2)]),
+        Value::from(vec![Value::from(1), Value::from(3)]),
+        Value::from("b"),
+        Value::from("a"),
+        Value::Number(f64::NAN),
+        Value::Number(1.0),
+        Value::Bool(true),
+        Value::Null,
+    ])
+    .into_iter()
+    .collect();
+    assert_eq!(
+        sorted.iter().map(Value::type_name).collect::<Vec<_>>(),
+        [
+            "null", "boolean", "number", "number", "string", "string", "array", "array", "object"
+        ]
+    );
+    assert_eq!(sorted.get(2), Some(&Value::Number(1.0)));
+    assert_eq!(sorted.get(4), Some(&Value::from("a")));
+    assert_eq!(
+        sorted.get(6),
+        Some(&Value::from(vec![Value::from(1), Value::from(3)]))
+    );
+}
+
+#[test]
+fn test_raw_collection_access() {
+    let mut obj = Object::new();
+    obj.insert("b", 1);
+    obj.insert("a", 2);
+    obj.as_index_map_mut().sort_keys();
+    assert_eq!(
+        obj.as_index_map().get_index(0),
+        Some((&"a".to_string(), &Value::from(2)))
+    );
+    let map: indexmap::IndexMap<String, Value> = obj.clone().into();
+    assert_eq!(map, obj.into_inner());
+
+    let mut arr: Array = (1..=4).map(Value::from).collect();
+    arr.as_vec_mut().truncate(3);
+    arr.as_vec_mut().reverse();
+    assert_eq!(arr.as_slice().first(), Some(&Value::from(3)));
+    assert_eq!(arr.as_ref().len(), 3);
+    let values: Vec<Value> = arr.clone().into();
+    assert_eq!(values, arr.into_vec());
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use zparse::{ErrorKind, Value, from_str};
+
+fn message<T>(result: zparse::Result<T>) -> Option<String> {
+    result.err().map(|err| err.message().to_string())
+}
+
+#[test]
+fn test_scalars_convert_from_matching_values() {
+    assert_eq!(
+        String::try_from(Value::from("api")).ok().as_deref(),
+        Some("api")
+    );
+    assert_eq!(i64::try_from(Value::from(-42)).ok(), Some(-42));
+    assert_eq!(u16::try_from(&Value::from(8080)).ok(), Some(8080));
+    assert_eq!(f64::try_from(Value::from(1.5)).ok(), Some(1.5));
+    assert_eq!(bool::try_from(&Value::Bool(true)).ok(), Some(true));
+}
+
+#[test]
+fn test_integers_must_be_whole_and_in_range() {
+    assert_eq!(
+        message(u16::try_from(Value::from(70000))).as_deref(),
+        Some("expected u16, found number 70000")
+    );
+    assert_eq!(
+        message(i64::try_from(Value::from(1.5))).as_deref(),
+        Some("expected i64, found number 1.5")
+    );
+    assert_eq!(
+        message(u8::try_from(Value::from(-1))).as_deref(),
+        Some("expected u8, found number -1")
+    );
+    assert!(u64::try_from(Value::Number(f64::INFINITY)).is_err());
+}
+
+#[test]
+fn test_mismatches_name_the_value_found() {
+    let err = u16::try_from(Value::from("8080")).err();
+    assert_eq!(
+        err.as_ref().map(|err| err.kind().clone()),
+        Some(ErrorKind::Expected {
+            expected: "u16".to_string(),
+            found: "string \"8080\"".to_string(),
+        })
+    );
+    assert_eq!(
+        message(String::try_from(Value::Null)).as_deref(),
+        Some("expected string, found null")
+    );
+    assert_eq!(
+        message(bool::try_from(Value::from("x".repeat(60)))).as_deref(),
+        Some(format!("expected boolean, found string \"{}…", "x".repeat(39)).as_str())
+    );
+    assert_eq!(
+        message(String::try_from(Value::from(vec![
+            Value::Null,
+            Value::Null
+        ])))
+        .as_deref(),
+        Some("expected string, found array of 2 items")
+    );
+}
+
+#[test]
+fn test_collections_report_the_failing_item() -> Result<(), Box<dyn std::error::Error>> {
+    let value = from_str(r#"{"servers": [{"port": 80}, {"port": "443"}], "tags": ["a", "b"]}"#)?;
+
+    let tags: Vec<String> = value.get("tags").unwrap_or(&Value::Null).try_into()?;
+    if tags != ["a", "b"] {
+        return Err(format!("unexpected tags: {tags:?}").into());
+    }
+
+    let servers: zparse::Result<HashMap<String, Vec<HashMap<String, u16>>>> =
+        value.clone().try_into();
+    let expected = "/servers/1/port: expected u16, found string \"443\"";
+    if message(servers).as_deref() != Some(expected) {
+        return Err("expected the pointer of the failing port".into());
+    }
+
+    let ports: HashMap<String, u16> = from_str(r#"{"http": 80, "https": 443}"#)?.try_into()?;
+    if ports.get("https") != Some(&443) {
+        return Err(format!("unexpected ports: {ports:?}").into());
+    }
+    Ok(())
+}
+
+struct Port(u16);
+
+impl TryFrom<Value> for Port {
+    type Error = zparse::Error;
+
+    fn try_from(value: Value) -> zparse::Result<Self> {
+        u16::try_from(value)
+            .map(Port)
+            .map_err(|err| err.context("while reading a port"))
+    }
+}
+
+#[test]
+fn test_failing_item_keeps_context_frames() {
+    let ports: zparse::Result<Vec<Port>> = Vec::try_from(Value::from(vec![Value::from(80)]));
+    assert_eq!(
+        ports
+            .map(|ports| ports.iter().map(|port| port.0).collect::<Vec<_>>())
+            .ok(),
+        Some(vec![80])
+    );
+
+    let result: zparse::Result<Vec<Port>> =
+        Vec::try_from(Value::from(vec![Value::from(80), Value::from("443")]));
+
+    let err = result.err();
+    assert_eq!(
+        err.as_ref().map(|err| err.message()),
+        Some("/1: expected u16, found string \"443\"")
+    );
+    assert_eq!(
+        err.as_ref().map(zparse::Error::frames),
+        Some(&["while reading a port".to_string()][..])
+    );
+}
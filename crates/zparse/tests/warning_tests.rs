@@ -0,0 +1,163 @@
+use zparse::{JsonParser, TomlParser, WarningKind, YamlConfig, YamlDuplicateKeys, YamlParser};
+
+#[test]
+fn test_json_duplicate_key_warns_and_last_wins() -> Result<(), Box<dyn std::error::Error>> {
+    let mut parser = JsonParser::new(br#"{"a": 1, "b": {"c": 2, "c": 3}, "a": 4}"#);
+    let value = parser.parse_value()?;
+    let kinds: Vec<_> = parser
+        .take_warnings()
+        .into_iter()
+        .map(|warning| (warning.kind().clone(), warning.span().start.offset))
+        .collect();
+
+    let expected = vec![
+        (WarningKind::DuplicateKey { key: "c".into() }, 23),
+        (WarningKind::DuplicateKey { key: "a".into() }, 32),
+    ];
+    if kinds != expected {
+        return Err(format!("unexpected warnings: {kinds:?}").into());
+    }
+    let a = value.as_object().and_then(|obj| obj.get("a"));
+    if a.and_then(|a| a.as_number()) != Some(4.0) {
+        return Err(format!("expected last value to win, got {a:?}").into());
+    }
+    if !parser.take_warnings().is_empty() {
+        return Err("warnings should be drained".into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_json_validate_still_warns_on_duplicate_keys() -> Result<(), Box<dyn std::error::Error>> {
+    let mut parser = JsonParser::new(br#"{"a": 1, "b": {"c": 2, "c": 3}, "a": [{"a": 5}]}"#);
+    parser.validate()?;
+    let kinds: Vec<_> = parser
+        .take_warnings()
+        .into_iter()
+        .map(|warning| (warning.kind().clone(), warning.span().start.offset))
+        .collect();
+
+    let expected = vec![
+        (WarningKind::DuplicateKey { key: "c".into() }, 23),
+        (WarningKind::DuplicateKey { key: "a".into() }, 32),
+    ];
+    if kinds != expected {
+        return Err(format!("unexpected warnings: {kinds:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_json_precision_loss_only_for_inexact_integers() -> Result<(), Box<dyn std::error::Error>> {
+    let mut parser = JsonParser::new(b"[9007199254740992, 9007199254740993, 0.1, -0, 1e30]");
+    parser.parse_value()?;
+    let kinds: Vec<_> = parser
+        .take_warnings()
+        .into_iter()
+        .map(|warning| warning.kind().clone())
+        .collect();
+
+    let expected = vec![WarningKind::PrecisionLoss {
+        literal: "9007199254740993".into(),
+    }];
+    if kinds != expected {
+        return Err(format!("unexpected warnings: {kinds:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_toml_precision_loss() -> Result<(), Box<dyn std::error::Error>> {
+    let mut parser =
+        TomlParser::new(b"small = 42\nbig = 9223372036854775807\nround = 4611686018427387904\n");
+    parser.parse()?;
+    let warnings = parser.take_warnings();
+    let [warning] = warnings.as_slice() else {
+        return Err(format!("expected one warning, got {warnings:?}").into());
+    };
+    if warning.to_string() != "warning at 17:2:7: number 9223372036854775807 loses precision as f64"
+    {
+        return Err(format!("unexpected warning: {warning}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_yaml_tags_are_ignored_with_warning() -> Result<(), Box<dyn std::error::Error>> {
+    let mut parser = YamlParser::new(b"a: !!int 5\nb: !custom text\n");
+    let value = parser.parse()?;
+    let kinds: Vec<_> = parser
+        .take_warnings()
+        .into_iter()
+        .map(|warning| warning.kind().clone())
+        .collect();
+
+    let expected = vec![
+        WarningKind::IgnoredTag {
+            tag: "!!int".into(),
+        },
+        WarningKind::IgnoredTag {
+            tag: "!custom".into(),
+        },
+    ];
+    if kinds != expected {
+        return Err(format!("unexpected warnings: {kinds:?}").into());
+    }
+    let b = value.as_object().and_then(|obj| obj.get("b"));
+    if b.and_then(|b| b.as_string()) != Some("text") {
+        return Err(format!("unexpected value for b: {b:?}").into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_yaml_duplicate_key_policies() -> Result<(), Box<dyn std::error::Error>> {
+    let input = b"a: 1\nb: {c: 2, c: 3}\na: 4\n";
+    if YamlParser::new(input).parse().is_ok() {
+        return Err("duplicate keys should fail by default".into());
+    }
+
+    for (policy, a, c, kind) in [
+        (YamlDuplicateKeys::FirstWins, 1.0, 2.0, "first"),
+        (YamlDuplicateKeys::LastWins, 4.0, 3.0, "last"),
+    ] {
+        let config = YamlConfig::default().with_duplicate_keys(policy);
+        let mut parser = YamlParser::with_config(input, config);
+        let value = parser.parse()?;
+        let warnings: Vec<_> = parser
+            .take_warnings()
+            .into_iter()
+            .map(|warning| (warning.kind().to_string(), warning.span().start.offset))
+            .collect();
+
+        let expected = vec![
+            (format!("duplicate key \"c\", {kind} value wins"), 15),
+            (format!("duplicate key \"a\", {kind} value wins"), 21),
+        ];
+        if warnings != expected {
+            return Err(format!("unexpected warnings for {policy:?}: {warnings:?}").into());
+        }
+        let found_a = value.get("a").and_then(|a| a.as_number());
+        let found_c = value
+            .get("b")
+            .and_then(|b| b.get("c"))
+            .and_then(|c| c.as_number());
+        if found_a != Some(a) || found_c != Some(c) {
+            return Err(format!("unexpected value for {policy:?}: {value:?}").into());
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_document_collects_parser_warnings() -> Result<(), Box<dyn std::error::Error>> {
+    let document = zparse::parse_document(
+        r#"{"a": 1, "a": 2}"#,
+        zparse::Format::Json,
+        &zparse::ConvertOptions::default(),
+    )?;
+    if document.warnings != [r#"warning at 9:1:10: duplicate key "a", last value wins"#] {
+        return Err(format!("unexpected warnings: {:?}", document.warnings).into());
+    }
+    Ok(())
+}
@@ -1,6 +1,9 @@
 use zparse::error::{Error, ErrorKind, Result};
-use zparse::xml::parser::Parser;
-use zparse::{Span, XmlContent};
+use zparse::xml::parser::{Config, Parser, Whitespace};
+use zparse::{
+    ConvertOptions, Format, Span, XmlContent, XmlDocument, XmlElement, XmlTextType, XmlTypes,
+    convert_with_options, from_xml_str, to_xml_string,
+};
 
 fn ensure_eq<T: PartialEq + std::fmt::Debug>(left: T, right: T) -> Result<()> {
     if left == right {
@@ -25,6 +28,16 @@ fn test_parse_simple_element() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_parse_prolog_on_separate_lines() -> Result<()> {
+    let input = b"<?xml version=\"1.0\"?>\n<!DOCTYPE root>\n<!-- note -->\n<root/>\n";
+    let mut parser = Parser::new(input);
+    let doc = parser.parse()?;
+
+    ensure_eq(doc.root.name.as_str(), "root")?;
+    Ok(())
+}
+
 #[test]
 fn test_parse_with_attributes() -> Result<()> {
     let input = b"<root id=\"1\" name='test'></root>";
@@ -171,3 +184,303 @@ fn test_convert_malformed_xml_to_json_returns_error() -> Result<()> {
         ))
     }
 }
+
+#[test]
+fn test_parse_cdata_and_comments_inside_element() -> Result<()> {
+    let input = b"<root><!-- note --><?pi data?><![CDATA[a < b]]></root>";
+    let mut parser = Parser::new(input);
+    let doc = parser.parse()?;
+
+    ensure_eq(
+        doc.root.children.as_slice(),
+        &[
+            XmlContent::Comment(" note ".to_string()),
+            XmlContent::ProcessingInstruction {
+                target: "pi".to_string(),
+                data: "data".to_string(),
+            },
+            XmlContent::CData("a < b".to_string()),
+        ][..],
+    )?;
+
+    let config = Config::default()
+        .with_comments(false)
+        .with_processing_instructions(false)
+        .with_cdata(false);
+    let doc = Parser::with_config(input, config).parse()?;
+    ensure_eq(
+        doc.root.children.as_slice(),
+        &[XmlContent::Text("a < b".to_string())][..],
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_cdata_comments_and_instructions_survive_a_round_trip() -> Result<()> {
+    let input = "<?xml version=\"1.0\"?>\n<!-- generated -->\n<page><script><![CDATA[if (a < b && c) { run(); }]]></script><!-- keep --><?php echo 1; ?><p>a &amp; b</p></page>\n<!-- end -->";
+    let doc = from_xml_str(input)?;
+    ensure_eq(doc.prolog.len(), 2)?;
+    ensure_eq(
+        doc.epilog.as_slice(),
+        &[XmlContent::Comment(" end ".to_string())][..],
+    )?;
+    let written = to_xml_string(&doc)?;
+    ensure_eq(written.as_str(), input)?;
+    ensure_eq(from_xml_str(&written)?, doc)?;
+
+    let formatted = zparse::ops::format_str(input, Format::Xml, &ConvertOptions::default())?;
+    ensure_eq(formatted.as_str(), input)?;
+
+    let json = convert_with_options(input, Format::Xml, Format::Json, &ConvertOptions::default())?;
+    ensure_eq(
+        json.as_str(),
+        r##"{"page":{"script":{"#text":"if (a < b && c) { run(); }"},"p":{"#text":"a & b"}}}"##,
+    )
+}
+
+#[test]
+fn test_pretty_format_keeps_indented_layout() -> Result<()> {
+    let input = "<?xml version=\"1.0\"?>\n<config>\n  <!-- servers -->\n  <server name=\"a\">\n    <port>80</port>\n    <empty/>\n  </server>\n  <note>mixed <b>text</b> here</note>\n</config>";
+    let mut options = ConvertOptions {
+        format: zparse::FormatOptions::new().with_pretty(true),
+        ..Default::default()
+    };
+    let formatted = zparse::ops::format_str(input, Format::Xml, &options)?;
+    ensure_eq(formatted.as_str(), input)?;
+
+    // Kept indentation is replaced rather than doubled
+    options.xml = Config::default().with_whitespace(Whitespace::Preserve);
+    let formatted = zparse::ops::format_str(input, Format::Xml, &options)?;
+    ensure_eq(formatted.as_str(), input)?;
+
+    let compact = "<config><server name=\"a\"><port>80</port></server></config>";
+    let formatted = zparse::ops::format_str(compact, Format::Xml, &options)?;
+    ensure_eq(
+        formatted.as_str(),
+        "<config>\n  <server name=\"a\">\n    <port>80</port>\n  </server>\n</config>",
+    )?;
+
+    // Nothing is added inside text or where whitespace is to be kept
+    let kept = "<doc><p>a <b><i>b</i></b></p><pre xml:space=\"preserve\"><x/><y/></pre></doc>";
+    let formatted = zparse::ops::format_str(kept, Format::Xml, &options)?;
+    ensure_eq(
+        formatted.as_str(),
+        "<doc>\n  <p>a <b><i>b</i></b></p>\n  <pre xml:space=\"preserve\"><x/><y/></pre>\n</doc>",
+    )
+}
+
+#[test]
+fn test_written_markup_stays_well_formed() -> Result<()> {
+    let doc = XmlDocument::new(XmlElement {
+        name: "a".to_string(),
+        attributes: Default::default(),
+        children: vec![
+            XmlContent::CData("x]]>y".to_string()),
+            XmlContent::Comment("a---b-".to_string()),
+            XmlContent::ProcessingInstruction {
+                target: "pi".to_string(),
+                data: "1?>2".to_string(),
+            },
+        ],
+    });
+    let written = to_xml_string(&doc)?;
+    ensure_eq(
+        written.as_str(),
+        "<a><![CDATA[x]]]]><![CDATA[>y]]><!--a- - -b- --><?pi 1? >2?></a>",
+    )?;
+    let text: String = from_xml_str(&written)?
+        .root
+        .children
+        .iter()
+        .filter_map(XmlContent::text)
+        .collect();
+    ensure_eq(text.as_str(), "x]]>y")
+}
+
+/// Line, column and message, with its frames, of the error for `input`
+fn error_at(input: &[u8]) -> Result<(u32, u32, String)> {
+    match Parser::new(input).parse() {
+        Ok(_) => Err(Error::with_message(
+            ErrorKind::InvalidToken,
+            Span::empty(),
+            "expected an error".to_string(),
+        )),
+        Err(err) => {
+            let mut message = err.message().to_string();
+            for frame in err.frames() {
+                message.push_str(&format!(" ({frame})"));
+            }
+            Ok((err.span().start.line, err.span().start.col, message))
+        }
+    }
+}
+
+#[test]
+fn test_errors_name_the_open_elements() -> Result<()> {
+    let input = b"<config>\n  <server>\n    <port>80</prot>\n  </server>\n</config>";
+    ensure_eq(
+        error_at(input)?,
+        (
+            3,
+            15,
+            "mismatched closing tag </prot>, expected </port> \
+             (while parsing <config>/<server>/<port>)"
+                .to_string(),
+        ),
+    )?;
+    ensure_eq(
+        error_at(b"<config>\n<server host=80/>\n</config>")?,
+        (
+            2,
+            14,
+            "expected quoted attribute value (while parsing <config>)".to_string(),
+        ),
+    )
+}
+
+#[test]
+fn test_entity_errors_point_at_the_entity() -> Result<()> {
+    ensure_eq(
+        error_at(b"<a>\n  <b>x &nbsp; y</b>\n</a>")?,
+        (
+            2,
+            8,
+            "invalid xml entity &nbsp; (while parsing <a>/<b>)".to_string(),
+        ),
+    )?;
+    ensure_eq(
+        error_at(b"<a k='1 &bad;'/>")?,
+        (1, 9, "invalid xml entity &bad;".to_string()),
+    )
+}
+
+#[test]
+fn test_trailing_content_error_has_a_location() -> Result<()> {
+    ensure_eq(
+        error_at(b"<a/>\n<b/>")?,
+        (
+            2,
+            1,
+            "unexpected content after the root element".to_string(),
+        ),
+    )
+}
+
+#[test]
+fn test_xml_text_types_from_hints_and_inference() -> Result<()> {
+    let xml = "<server><port>8080</port><zip>01234</zip><tls>true</tls><ratio>1.5</ratio><id>007</id></server>";
+    let convert = |xml_types: XmlTypes| {
+        let options = ConvertOptions {
+            xml_types,
+            ..ConvertOptions::default()
+        };
+        convert_with_options(xml, Format::Xml, Format::Json, &options)
+    };
+
+    ensure_eq(
+        convert(XmlTypes::new())?,
+        r##"{"server":{"port":{"#text":"8080"},"zip":{"#text":"01234"},"tls":{"#text":"true"},"ratio":{"#text":"1.5"},"id":{"#text":"007"}}}"##.to_string(),
+    )?;
+    ensure_eq(
+        convert(XmlTypes::new().with_infer(true))?,
+        r##"{"server":{"port":{"#text":8080},"zip":{"#text":"01234"},"tls":{"#text":true},"ratio":{"#text":1.5},"id":{"#text":"007"}}}"##.to_string(),
+    )?;
+    ensure_eq(
+        convert("port=integer, id=number, ratio=string".parse()?)?,
+        r##"{"server":{"port":{"#text":8080},"zip":{"#text":"01234"},"tls":{"#text":"true"},"ratio":{"#text":"1.5"},"id":{"#text":7}}}"##.to_string(),
+    )
+}
+
+#[test]
+fn test_xml_text_hints_are_checked() -> Result<()> {
+    let options = ConvertOptions {
+        xml_types: XmlTypes::new().with_hint("port", XmlTextType::Integer),
+        ..ConvertOptions::default()
+    };
+    let err = convert_with_options(
+        "<server><port>http</port></server>",
+        Format::Xml,
+        Format::Yaml,
+        &options,
+    )
+    .err()
+    .map(|err| err.to_string());
+    ensure_eq(
+        err.as_deref(),
+        Some("error at 0:0:0: <port> holds \"http\", not a valid integer"),
+    )?;
+
+    let csv = convert_with_options(
+        "<root><row><zip>01234</zip><n>2</n></row></root>",
+        Format::Xml,
+        Format::Csv,
+        &ConvertOptions {
+            xml_types: "zip=string".parse()?,
+            ..ConvertOptions::default()
+        },
+    )?;
+    ensure_eq(csv.trim_end(), "zip,n\n\"01234\",2")?;
+
+    ensure_eq(
+        "port=int"
+            .parse::<XmlTypes>()
+            .err()
+            .map(|err| err.to_string()),
+        Some(
+            "error at 0:0:0: unknown type \"int\" (known: string, number, integer, bool)"
+                .to_string(),
+        ),
+    )
+}
+
+#[test]
+fn test_whitespace_modes() -> Result<()> {
+    let input = b"<a>\n  <b>\n    Blue   widget\n  </b>\n  <c xml:space=\"preserve\">  x  <d> y </d></c>\n  <e>&#32;z&#32;</e>\n</a>";
+    let texts = |whitespace: Whitespace| -> Result<Vec<String>> {
+        let doc =
+            Parser::with_config(input, Config::default().with_whitespace(whitespace)).parse()?;
+        let mut texts = Vec::new();
+        let mut stack = vec![&doc.root];
+        while let Some(element) = stack.pop() {
+            for child in element.children.iter().rev() {
+                match child {
+                    XmlContent::Element(child) => stack.push(child),
+                    XmlContent::Text(text) => texts.push(format!("{}={text:?}", element.name)),
+                    _ => {}
+                }
+            }
+        }
+        texts.sort();
+        Ok(texts)
+    };
+
+    ensure_eq(
+        texts(Whitespace::DropBlank)?,
+        vec![
+            "b=\"\\n    Blue   widget\\n  \"".to_string(),
+            "c=\"  x  \"".to_string(),
+            "d=\" y \"".to_string(),
+            "e=\" z \"".to_string(),
+        ],
+    )?;
+    ensure_eq(
+        texts(Whitespace::Trim)?,
+        vec![
+            "b=\"Blue   widget\"".to_string(),
+            "c=\"  x  \"".to_string(),
+            "d=\" y \"".to_string(),
+            "e=\" z \"".to_string(),
+        ],
+    )?;
+    ensure_eq(
+        texts(Whitespace::Collapse)?.first().cloned(),
+        Some("b=\"Blue widget\"".to_string()),
+    )?;
+    ensure_eq(
+        texts(Whitespace::Preserve)?
+            .iter()
+            .filter(|text| text.starts_with("a="))
+            .count(),
+        4,
+    )
+}
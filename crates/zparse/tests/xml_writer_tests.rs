@@ -0,0 +1,129 @@
+use zparse::{
+    ConvertOptions, ErrorKind, Format, FormatOptions, Value, XmlContent, XmlDocument, XmlElement,
+    XmlQuote, convert_with_options, from_str, parse_with_options, serialize_with_options,
+    to_xml_string,
+};
+
+fn element(name: &str, attributes: &[(&str, &str)], children: Vec<XmlContent>) -> XmlElement {
+    XmlElement {
+        name: name.to_string(),
+        attributes: attributes
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect(),
+        children,
+    }
+}
+
+#[test]
+fn test_text_and_attributes_escape_for_their_context() {
+    let doc = XmlDocument::new(element(
+        "a",
+        &[("t", "it's \"x\"\tand\nline & <b>")],
+        vec![XmlContent::Text("it's \"q\" & <b>\r\n".to_string())],
+    ));
+    assert_eq!(
+        to_xml_string(&doc).ok().as_deref(),
+        Some(
+            "<a t=\"it's &quot;x&quot;&#9;and&#10;line &amp; &lt;b&gt;\">it's \"q\" &amp; &lt;b&gt;&#13;\n</a>"
+        )
+    );
+}
+
+#[test]
+fn test_xml_to_xml_honours_quote_and_ascii_options() {
+    let input = r#"<a t="say &quot;hi&quot;">café</a>"#;
+    let options = ConvertOptions {
+        format: FormatOptions::new()
+            .with_xml_quote(XmlQuote::Single)
+            .with_xml_ascii(true),
+        ..Default::default()
+    };
+    assert_eq!(
+        convert_with_options(input, Format::Xml, Format::Xml, &options)
+            .ok()
+            .as_deref(),
+        Some("<a t='say \"hi\"'>caf&#xE9;</a>")
+    );
+    // Default options leave the document as it was
+    assert_eq!(
+        convert_with_options(input, Format::Xml, Format::Xml, &ConvertOptions::default())
+            .ok()
+            .as_deref(),
+        Some(input)
+    );
+}
+
+#[test]
+fn test_quote_and_ascii_options() {
+    let value = from_str(r##"{"a": {"@attributes": {"t": "it's \"é\""}, "#text": "café ☕"}}"##);
+    let options = FormatOptions::new()
+        .with_xml_quote(XmlQuote::Single)
+        .with_xml_ascii(true);
+    let xml = value
+        .as_ref()
+        .ok()
+        .and_then(|value| serialize_with_options(value, Format::Xml, &options).ok());
+    assert_eq!(
+        xml.as_deref(),
+        Some("<root><a t='it&apos;s \"&#xE9;\"'>caf&#xE9; &#x2615;</a></root>")
+    );
+
+    // What was written reads back as what was given
+    let read = xml
+        .as_deref()
+        .and_then(|xml| parse_with_options(xml, Format::Xml, &ConvertOptions::default()).ok());
+    let a = read
+        .as_ref()
+        .and_then(|value| value.get("root"))
+        .and_then(|root| root.get("a"));
+    assert_eq!(
+        a.and_then(|a| a.get("#text")),
+        Some(&Value::from("café ☕"))
+    );
+    assert_eq!(
+        a.and_then(|a| a.get("@attributes"))
+            .and_then(|attributes| attributes.get("t")),
+        Some(&Value::from("it's \"é\""))
+    );
+}
+
+#[test]
+fn test_cdata_steps_out_for_ascii_references() {
+    let doc = XmlDocument::new(element(
+        "s",
+        &[],
+        vec![XmlContent::CData("if (a < b) { say(\"é\"); }".to_string())],
+    ));
+    let options = FormatOptions::new().with_xml_ascii(true);
+    assert_eq!(
+        zparse::xml::writer::to_string_with_options(&doc, &options)
+            .ok()
+            .as_deref(),
+        Some("<s><![CDATA[if (a < b) { say(\"]]>&#xE9;<![CDATA[\"); }]]></s>")
+    );
+}
+
+#[test]
+fn test_characters_xml_cannot_hold_are_errors() {
+    let doc = XmlDocument::new(element(
+        "a",
+        &[],
+        vec![XmlContent::Text("bell\u{7}".to_string())],
+    ));
+    let err = to_xml_string(&doc).err();
+    assert!(matches!(
+        err.as_ref().map(zparse::Error::kind),
+        Some(ErrorKind::UnsupportedValue { path }) if path == "<a>"
+    ));
+    assert_eq!(
+        err.map(|err| err.to_string()).as_deref(),
+        Some("error at 0:0:0: <a> holds U+0007, which XML 1.0 cannot represent")
+    );
+
+    let value = from_str(r#"{"k": "\u0000"}"#);
+    let xml = value
+        .as_ref()
+        .map(|value| serialize_with_options(value, Format::Xml, &FormatOptions::new()));
+    assert!(matches!(xml, Ok(Err(_))));
+}
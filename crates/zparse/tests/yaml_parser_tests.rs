@@ -153,3 +153,127 @@ fn test_next_event_after_parse_does_not_reparse() -> Result<()> {
     ensure_eq(next, None)?;
     Ok(())
 }
+
+#[test]
+fn test_parse_errors_carry_locations() -> Result<()> {
+    let cases: [(&[u8], &str); 3] = [
+        (b"name: a\nname: b\n", "8:2:1"),
+        (b"map: {a: 1, a: 2}\n", "12:1:13"),
+        (b"list:\n  - [1, 2\n", "16:3:1"),
+    ];
+    for (input, expected) in cases {
+        let err = match Parser::new(input).parse() {
+            Ok(value) => return ensure_eq(Err(value), Ok(expected)),
+            Err(err) => err,
+        };
+        ensure_eq(err.span().start.to_string().as_str(), expected)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_parse_sequence_aligned_with_key() -> Result<()> {
+    let value = Parser::new(b"a:\n- 1\n- 2\nb: 3\n").parse()?;
+    ensure_eq(
+        zparse::serialize(&value, zparse::Format::Json)?,
+        r#"{"a":[1,2],"b":3}"#.to_string(),
+    )
+}
+
+#[test]
+fn test_parse_sequence_item_mappings() -> Result<()> {
+    let input = b"list:\n- name: a\n  tags:\n  - t\n  port: 1\n- name: b\nafter: 1\n";
+    let value = Parser::new(input).parse()?;
+    ensure_eq(
+        zparse::serialize(&value, zparse::Format::Json)?,
+        r#"{"list":[{"name":"a","tags":["t"],"port":1},{"name":"b"}],"after":1}"#.to_string(),
+    )
+}
+
+#[test]
+fn test_parse_plain_scalars_with_spaces_and_colons() -> Result<()> {
+    let input = b"a: hello world\nt: 07:32:00\nu: http://x.y/z\nf: [a b, c]\nl:\n  - one two\n";
+    let value = Parser::new(input).parse()?;
+    ensure_eq(
+        zparse::serialize(&value, zparse::Format::Json)?,
+        r#"{"a":"hello world","t":"07:32:00","u":"http://x.y/z","f":["a b","c"],"l":["one two"]}"#
+            .to_string(),
+    )
+}
+
+#[test]
+fn test_parse_sequence_item_with_quoted_first_key() -> Result<()> {
+    let value = Parser::new(b"- \"#text\": a\n  \"b\": 2\n- \"c\"\n").parse()?;
+    ensure_eq(
+        zparse::serialize(&value, zparse::Format::Json)?,
+        r##"[{"#text":"a","b":2},"c"]"##.to_string(),
+    )
+}
+
+#[test]
+fn test_parse_single_quoted_with_doubled_quotes() -> Result<()> {
+    let value = Parser::new(b"a: 'it''s on'\nb: ['''', 'x''']\n").parse()?;
+    ensure_eq(
+        zparse::serialize(&value, zparse::Format::Json)?,
+        r#"{"a":"it's on","b":["'","x'"]}"#.to_string(),
+    )
+}
+
+#[test]
+fn test_plain_scalar_schemas() -> Result<()> {
+    use zparse::yaml::{Config, Schema};
+
+    let input = b"a: yes\nb: 0755\nc: 1:30\nd: 0o17\ne: 0x1F\nf: -.inf\ng: 1_000\nh: 1e3\ni: True\nj: 007\nk: 0b101\n";
+    let json = |schema| -> Result<String> {
+        let value = Parser::with_config(input, Config::default().with_schema(schema)).parse()?;
+        let f = value.get("f").and_then(Value::as_number);
+        ensure_eq(f == Some(f64::NEG_INFINITY), schema != Schema::Json)?;
+        zparse::serialize(&value, zparse::Format::Json)
+    };
+    ensure_eq(
+        json(Schema::Core)?,
+        r#"{"a":"yes","b":755,"c":"1:30","d":15,"e":31,"f":null,"g":"1_000","h":1000,"i":true,"j":7,"k":"0b101"}"#
+            .to_string(),
+    )?;
+    ensure_eq(
+        json(Schema::Yaml11)?,
+        r#"{"a":true,"b":493,"c":90,"d":"0o17","e":31,"f":null,"g":1000,"h":"1e3","i":true,"j":7,"k":5}"#
+            .to_string(),
+    )?;
+    ensure_eq(
+        json(Schema::Json)?,
+        r#"{"a":"yes","b":"0755","c":"1:30","d":"0o17","e":"0x1F","f":"-.inf","g":"1_000","h":1000,"i":"True","j":"007","k":"0b101"}"#
+            .to_string(),
+    )
+}
+
+#[test]
+fn test_plain_timestamps_as_datetimes() -> Result<()> {
+    use zparse::TomlDatetime;
+    use zparse::yaml::Config;
+
+    let input = b"d: 2001-12-14\nl: 2001-12-14 21:59:43\no: 2001-12-14t21:59:43.10 -5\nz: 2001-12-14T21:59:43Z\ns: '2001-12-14'\nbad: 2001-13-14\n";
+    let value = Parser::with_config(input, Config::default().with_timestamps(true)).parse()?;
+    let kind = |key: &str| match value.get(key) {
+        Some(Value::Datetime(TomlDatetime::LocalDate(_))) => "date",
+        Some(Value::Datetime(TomlDatetime::LocalDateTime(_))) => "local",
+        Some(Value::Datetime(TomlDatetime::OffsetDateTime(_))) => "offset",
+        Some(Value::String(_)) => "string",
+        _ => "other",
+    };
+    ensure_eq(
+        ["d", "l", "o", "z", "s", "bad"].map(kind),
+        ["date", "local", "offset", "offset", "string", "string"],
+    )?;
+    ensure_eq(
+        zparse::serialize(&value, zparse::Format::Json)?,
+        r#"{"d":"2001-12-14","l":"2001-12-14T21:59:43","o":"2001-12-14T21:59:43.1-05:00","z":"2001-12-14T21:59:43Z","s":"2001-12-14","bad":"2001-13-14"}"#
+            .to_string(),
+    )?;
+
+    // Off by default
+    ensure_eq(
+        Parser::new(b"d: 2001-12-14\n").parse()?.get("d").cloned(),
+        Some(Value::from("2001-12-14")),
+    )
+}
@@ -39,6 +39,7 @@ fn serialize_value(value: &Value) -> String {
         }
         Value::Object(obj) => serialize_flow_mapping(obj),
         Value::Datetime(_) => "null".to_string(),
+        Value::Bytes(_) => "null".to_string(),
     }
 }
 
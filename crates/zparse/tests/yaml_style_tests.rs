@@ -0,0 +1,104 @@
+use zparse::yaml::{Indentation, detect_indentation};
+use zparse::{
+    ConvertOptions, Format, FormatOptions, YamlSequenceStyle, YamlStringStyle,
+    convert_with_options, from_str, serialize_with_options,
+};
+
+fn sample() -> zparse::Value {
+    from_str(r#"{"server":{"ports":[80,443]},"list":[{"name":"a","tags":["t"]}]}"#)
+        .unwrap_or_default()
+}
+
+fn yaml(options: FormatOptions) -> String {
+    serialize_with_options(&sample(), Format::Yaml, &options).unwrap_or_default()
+}
+
+#[test]
+fn test_yaml_default_indentation() {
+    assert_eq!(
+        yaml(FormatOptions::default()),
+        "server:\n  ports:\n    - 80\n    - 443\nlist:\n  - name: \"a\"\n    tags:\n      - \"t\""
+    );
+}
+
+#[test]
+fn test_yaml_indent_width_and_aligned_sequences() {
+    let options = FormatOptions::new()
+        .with_yaml_indent(Some(4))
+        .with_yaml_sequences(Some(YamlSequenceStyle::Aligned));
+    let output = yaml(options);
+    assert_eq!(
+        output,
+        "server:\n    ports:\n    - 80\n    - 443\nlist:\n- name: \"a\"\n  tags:\n  - \"t\""
+    );
+    assert_eq!(zparse::from_yaml_str(&output).ok(), Some(sample()));
+}
+
+#[test]
+fn test_detect_indentation() {
+    assert_eq!(
+        detect_indentation("a:\n    b: 1\n    c:\n    - 1\n"),
+        Indentation {
+            width: Some(4),
+            sequences: Some(YamlSequenceStyle::Aligned),
+        }
+    );
+    assert_eq!(
+        detect_indentation("# a:\na:\n  - x\nb: |\n  c:\n      d\n"),
+        Indentation {
+            width: Some(2),
+            sequences: Some(YamlSequenceStyle::Indented),
+        }
+    );
+    assert_eq!(detect_indentation("a: 1\n"), Indentation::default());
+}
+
+#[test]
+fn test_reformat_yaml_keeps_detected_style() {
+    let input = "a:\n    b:\n    - 1\n    c: 2";
+    let options = ConvertOptions {
+        format: FormatOptions::new().with_yaml_sequences(Some(YamlSequenceStyle::Aligned)),
+        ..Default::default()
+    };
+    // The width comes from the file, the sequence style from the options
+    assert_eq!(
+        convert_with_options(input, Format::Yaml, Format::Yaml, &options)
+            .ok()
+            .as_deref(),
+        Some(input)
+    );
+    let options = ConvertOptions {
+        format: FormatOptions::new().with_yaml_indent(Some(2)),
+        ..Default::default()
+    };
+    assert_eq!(
+        convert_with_options(input, Format::Yaml, Format::Yaml, &options)
+            .ok()
+            .as_deref(),
+        Some("a:\n  b:\n  - 1\n  c: 2")
+    );
+}
+
+#[test]
+fn test_yaml_nested_and_empty_containers_read_back() {
+    let value =
+        from_str(r#"{"k":[[{"a":1},[]],{"l":[1,2],"m":{"n":{}}}],"e":{}}"#).unwrap_or_default();
+    let output =
+        serialize_with_options(&value, Format::Yaml, &FormatOptions::default()).unwrap_or_default();
+    assert_eq!(zparse::from_yaml_str(&output).ok(), Some(value));
+}
+
+#[test]
+fn test_yaml_strings_quoted_as_needed() {
+    let value = from_str(
+        r##"{"name":"zparse","on":"no","list":["yes","1.0","2024-01-01","0x1F","v1.2","a: b","- x","#c","it's","","~"]}"##,
+    )
+    .unwrap_or_default();
+    let options = FormatOptions::new().with_yaml_strings(YamlStringStyle::AsNeeded);
+    let output = serialize_with_options(&value, Format::Yaml, &options).unwrap_or_default();
+    assert_eq!(
+        output,
+        "name: zparse\n\"on\": \"no\"\nlist:\n  - \"yes\"\n  - \"1.0\"\n  - \"2024-01-01\"\n  - \"0x1F\"\n  - v1.2\n  - \"a: b\"\n  - \"- x\"\n  - \"#c\"\n  - it's\n  - \"\"\n  - \"~\""
+    );
+    assert_eq!(zparse::from_yaml_str(&output).ok(), Some(value));
+}
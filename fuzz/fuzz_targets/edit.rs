@@ -0,0 +1,26 @@
+//! Edits to an [`IncrementalDocument`] must never panic, including ranges
+//! that are reversed, out of bounds or inside a multi-byte character.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zparse::{Format, IncrementalDocument};
+
+const FORMATS: [Format; 5] = [
+    Format::Json,
+    Format::Csv,
+    Format::Toml,
+    Format::Yaml,
+    Format::Xml,
+];
+
+fuzz_target!(|input: (u8, String, Vec<(u16, u16, String)>)| {
+    let (selector, text, edits) = input;
+    let format = FORMATS[usize::from(selector) % FORMATS.len()];
+    let mut doc = IncrementalDocument::new(format, text);
+    for (start, end, replacement) in edits {
+        let _ = doc.edit(usize::from(start)..usize::from(end), &replacement);
+    }
+    let _ = doc.errors();
+    let _ = doc.warnings();
+});
@@ -0,0 +1,41 @@
+//! Every parsing entry point must return instead of panicking, whatever the
+//! input. The first byte picks the format; the rest is the document.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zparse::highlight::highlight;
+use zparse::{ConvertOptions, Format, event, key_lines, parse_bytes, parse_document, serialize};
+
+const FORMATS: [Format; 5] = [
+    Format::Json,
+    Format::Csv,
+    Format::Toml,
+    Format::Yaml,
+    Format::Xml,
+];
+
+fuzz_target!(|data: &[u8]| {
+    let Some((selector, bytes)) = data.split_first() else {
+        return;
+    };
+    let format = FORMATS[usize::from(*selector) % FORMATS.len()];
+    let _ = parse_bytes(bytes, format);
+    let Ok(input) = std::str::from_utf8(bytes) else {
+        return;
+    };
+
+    let options = ConvertOptions::default();
+    if let Ok(value) = zparse::parse_with_options(input, format, &options) {
+        for to in FORMATS {
+            let _ = serialize(&value, to);
+        }
+    }
+    let _ = parse_document(input, format, &options);
+    let _ = zparse::lossless::parse_lossless(input, format, &options);
+    let _ = highlight(input, format);
+    let _ = key_lines(input, format, &options);
+    if let Ok(mut reader) = event::reader(input, format, &options) {
+        let _ = event::collect(reader.as_mut());
+    }
+});
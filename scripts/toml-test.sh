@@ -0,0 +1,26 @@
+#!/bin/bash
+
+# Run the toml-test suite (https://github.com/toml-lang/toml-test) against the
+# zparse adapters in crates/zparse/examples. Requires `toml-test` on PATH:
+#   go install github.com/toml-lang/toml-test/cmd/toml-test@v1.5.0
+
+# Exit on error
+set -e
+
+GREEN='\033[0;32m'
+RED='\033[0;31m'
+NC='\033[0m' # No Color
+
+if ! command -v toml-test &> /dev/null; then
+    echo -e "${RED}toml-test not found; install it with:${NC}"
+    echo "  go install github.com/toml-lang/toml-test/cmd/toml-test@v1.5.0"
+    exit 1
+fi
+
+cargo build -p zparse --example toml_test_decoder --example toml_test_encoder
+
+echo -e "${GREEN}Running toml-test decoder tests...${NC}"
+toml-test "$@" target/debug/examples/toml_test_decoder
+
+echo -e "\n${GREEN}Running toml-test encoder tests...${NC}"
+toml-test -encoder "$@" target/debug/examples/toml_test_encoder